@@ -198,6 +198,14 @@ def main():
         ):
             sys.exit(1)
 
+        # Step 7: Rebuild the message index for the completeness check
+        if not run_script(
+            "validation/scripts/build_message_index.py",
+            [],
+            "Rebuild official message index"
+        ):
+            sys.exit(1)
+
     # Optional Step: Extract config keys
     if args.extract_config_keys:
         if not args.pdf_path:
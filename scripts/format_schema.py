@@ -0,0 +1,209 @@
+#!/usr/bin/env python3
+"""
+Rewrite the schema data files in canonical form, or check that they are.
+
+Canonical form is what every script here already writes, pinned down so
+that a hand edit or a new tool cannot reorder a file and bury a one-line
+change in a thousand-line diff:
+
+- object keys in the order of KEY_ORDER below for messages, fields and
+  the other objects the files are mostly made of, and otherwise in the order
+  the JSON Schema in schema/ lists the properties; keys neither describes
+  follow in alphabetical order; the keys of maps (enumeration values,
+  config groups) and of objects the schema does not describe keep their
+  order
+- messages and configuration keys sorted by name
+- class, message and key IDs as upper-case hex, "0x3B" and "0x2091004C"
+- two-space indentation, ASCII with \\u escapes, shortest round-trip floats,
+  and a final newline
+
+Usage:
+    uv run python scripts/format_schema.py            # rewrite in place
+    uv run python scripts/format_schema.py --check    # exit 1 if not canonical
+    uv run python scripts/format_schema.py --check data/messages/ubx_messages.json
+"""
+
+from __future__ import annotations
+
+import argparse
+import difflib
+import json
+import sys
+from pathlib import Path
+
+PROJECT_ROOT = Path(__file__).parent.parent
+SCHEMA_DIR = PROJECT_ROOT / "schema"
+
+# Data file -> the JSON Schema describing it (None: layout only).
+FILES = {
+    "data/messages/ubx_messages.json": "ubx-message-schema-v1.5.json",
+    "data/config_keys/unified_config_keys.json": "ubx-config-keys-schema.json",
+    "data/messages/enumerations.json": None,
+}
+
+# Top-level lists kept sorted, and the key they are sorted by.
+SORTED_LISTS = {"messages": "name", "keys": "name"}
+
+# Hex ID properties and their width in digits.
+HEX_IDS = {"class_id": 2, "message_id": 2, "key_id": 8}
+
+# Key order by $defs name ("" for the top level), where the order the data
+# has always been written in differs from the order of the JSON Schema.
+KEY_ORDER = {
+    "": ["schema_version", "generated", "source_document", "source_documents"],
+    "message": [
+        "name", "class_id", "message_id", "message_type", "needs_ack", "description", "deprecated",
+        "payload", "supported_versions", "variant_aliases", "variants", "comment", "examples",
+        "config_migration",
+    ],
+    "message_variant": ["name", "discriminator", "payload", "description"],
+    "repeated_group": [
+        "name", "description", "repetition_type", "count_field", "count", "constant_count",
+        "group_size_bytes", "base_offset", "fields",
+    ],
+    "field": [
+        "name", "byte_offset", "data_type", "description", "unit", "scale", "reserved", "fixed_value",
+    ],
+    "config_key": ["name", "key_id", "data_type", "scale", "unit", "description"],
+    "bitfield_entry": ["name", "bit_start", "bit_end", "description", "data_type"],
+    "config_migration": ["port", "message", "keys"],
+    "config_migration_key": ["field", "key", "when", "ports", "values"],
+}
+TRAILING_KEYS = {"config_key": ["sources", "group", "item_id"]}
+
+
+class Node:
+    """One subschema, with its $refs, oneOf/anyOf/allOf alternatives resolved."""
+
+    def __init__(self, root: dict, schema: dict | None, name: str | None = None):
+        self.root = root
+        self.name = name
+        ref = (schema or {}).get("$ref", "")
+        if ref.startswith("#/$defs/"):
+            self.name = ref.rsplit("/", 1)[1]
+        self.parts = list(self._flatten(schema or {}, set()))
+
+    def _flatten(self, schema: dict, seen: set[str]):
+        ref = schema.get("$ref")
+        if ref and ref.startswith("#/") and ref not in seen:
+            target = self.root
+            for part in ref[2:].split("/"):
+                target = target.get(part, {})
+            yield from self._flatten(target, seen | {ref})
+        yield schema
+        for keyword in ("allOf", "oneOf", "anyOf"):
+            for alternative in schema.get(keyword, []):
+                yield from self._flatten(alternative, seen)
+
+    def order(self) -> list[str]:
+        """Property names in canonical order."""
+        names = list(KEY_ORDER.get(self.name, []))
+        trailing = TRAILING_KEYS.get(self.name, [])
+        for part in self.parts:
+            names.extend(n for n in part.get("properties", {}) if n not in names and n not in trailing)
+        return names + trailing
+
+    def child(self, key: str) -> Node:
+        for part in self.parts:
+            if key in part.get("properties", {}):
+                return Node(self.root, part["properties"][key])
+        for part in self.parts:
+            if isinstance(part.get("additionalProperties"), dict):
+                return Node(self.root, part["additionalProperties"])
+        return Node(self.root, None)
+
+    def items(self) -> Node:
+        for part in self.parts:
+            if isinstance(part.get("items"), dict):
+                return Node(self.root, part["items"])
+        return Node(self.root, None)
+
+
+def normalize_hex(value, digits: int):
+    """'0x3b' -> '0x3B'; anything that isn't a hex string is left alone."""
+    if isinstance(value, str) and value.lower().startswith("0x"):
+        try:
+            return f"0x{int(value, 16):0{digits}X}"
+        except ValueError:
+            pass
+    return value
+
+
+def canonicalize(value, node: Node, top_level: bool = False):
+    """The canonical form of `value` as described by `node`."""
+    if isinstance(value, list):
+        items = node.items()
+        return [canonicalize(v, items) for v in value]
+    if not isinstance(value, dict):
+        return value
+
+    if not node.order():
+        # A map, or an object the schema does not describe.
+        keys = list(value)
+    else:
+        known = [k for k in node.order() if k in value]
+        keys = known + sorted(k for k in value if k not in known)
+
+    out = {}
+    for key in keys:
+        item = canonicalize(value[key], node.child(key))
+        if key in HEX_IDS:
+            item = normalize_hex(item, HEX_IDS[key])
+        if top_level and key in SORTED_LISTS and isinstance(item, list):
+            item = sorted(item, key=lambda entry: str(entry.get(SORTED_LISTS[key], "")))
+        out[key] = item
+    return out
+
+
+def format_file(path: Path, schema_name: str | None) -> str:
+    """The canonical text of the data file at `path`."""
+    data = json.loads(path.read_text())
+    if schema_name:
+        root = json.loads((SCHEMA_DIR / schema_name).read_text())
+        data = canonicalize(data, Node(root, root, ""), top_level=True)
+    return json.dumps(data, indent=2) + "\n"
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Rewrite schema data files in canonical form")
+    parser.add_argument("files", nargs="*", help="Files to format (default: all schema data files)")
+    parser.add_argument("--check", action="store_true", help="Don't write; exit 1 if a file is not canonical")
+    parser.add_argument("--diff", action="store_true", help="With --check, show what would change")
+    args = parser.parse_args()
+
+    targets = FILES
+    if args.files:
+        targets = {}
+        for name in args.files:
+            relative = Path(name).resolve().relative_to(PROJECT_ROOT.resolve()).as_posix()
+            if relative not in FILES:
+                print(f"{name}: not a schema data file ({', '.join(FILES)})", file=sys.stderr)
+                return 2
+            targets[relative] = FILES[relative]
+
+    unformatted = []
+    for relative, schema_name in targets.items():
+        path = PROJECT_ROOT / relative
+        current = path.read_text()
+        canonical = format_file(path, schema_name)
+        if current == canonical:
+            continue
+        unformatted.append(relative)
+        if not args.check:
+            path.write_text(canonical)
+            print(f"formatted {relative}")
+        elif args.diff:
+            sys.stdout.writelines(difflib.unified_diff(
+                current.splitlines(keepends=True), canonical.splitlines(keepends=True),
+                f"a/{relative}", f"b/{relative}", n=2))
+
+    if args.check and unformatted:
+        for relative in unformatted:
+            print(f"not canonical: {relative}", file=sys.stderr)
+        print("run: uv run python scripts/format_schema.py", file=sys.stderr)
+        return 1
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
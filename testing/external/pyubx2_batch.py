@@ -0,0 +1,66 @@
+#!/usr/bin/env python3
+"""
+Batch pyubx2 backend for the Rust differential orchestrator (ubx-differential).
+
+Reads one JSON request per line on stdin:
+    {"frame": "<hex>", "message_type": "output"}
+and writes one JSON observation per line on stdout:
+    {"verdict": "parsed", "name": "NAV-PVT", "fields": {...}}
+
+`verdict` is "parsed", "unsupported" (pyubx2 has no definition) or
+"rejected" (pyubx2 refused the frame), with the error in "detail". Fields
+are pyubx2's own attributes as JSON: numbers, and text for byte strings
+that decode as text; other byte strings are left out. All comparison is
+done by the orchestrator.
+
+Exits with status 3 if pyubx2 is not installed.
+"""
+
+import json
+import sys
+from pathlib import Path
+
+sys.path.insert(0, str(Path(__file__).resolve().parent.parent))
+
+from external.pyubx2_adapter import is_available, parse_ubx_bytes
+
+
+def json_value(value):
+    """A JSON-compatible form of a pyubx2 attribute, or None to leave it out."""
+    if isinstance(value, bool):
+        return int(value)
+    if isinstance(value, (int, float, str)):
+        return value
+    if isinstance(value, bytes):
+        text = value.rstrip(b"\x00")
+        if text and all(32 <= b < 127 for b in text):
+            return text.decode("ascii")
+    return None
+
+
+def observe(request: dict) -> dict:
+    result = parse_ubx_bytes(bytes.fromhex(request["frame"]), request.get("message_type") or "output")
+    if not result or not result.get("parsed"):
+        error = (result or {}).get("error") or "no result"
+        verdict = "unsupported" if "unknown" in error.lower() else "rejected"
+        return {"verdict": verdict, "detail": error}
+    fields = {}
+    for name, value in result["fields"].items():
+        value = json_value(value)
+        if value is not None:
+            fields[name] = value
+    return {"verdict": "parsed", "name": result["name"], "fields": fields}
+
+
+def main() -> int:
+    if not is_available():
+        print("pyubx2 is not installed", file=sys.stderr)
+        return 3
+    for line in sys.stdin:
+        if line.strip():
+            print(json.dumps(observe(json.loads(line))), flush=True)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,129 @@
+//! Framing, single-message decode and whole-log throughput of the runtime
+//! schema decoder and of ublox-rs, whose parsers are generated at compile
+//! time from its message definitions.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use serde_json::json;
+use ubx_schema::frame::{checksum, checksum_bytewise, frames, FrameParser};
+use ubx_schema::arena::Arena;
+use ubx_schema::{Decoder, Encoder, Schema};
+
+fn nav_pvt(encoder: &Encoder<'_>, itow: u32) -> Vec<u8> {
+    let fields = json!({
+        "iTOW": itow, "year": 2026, "month": 10, "day": 14, "hour": 9, "min": 30, "sec": 5,
+        "fixType": 3, "flags": 1, "numSV": 24, "lon": 1512093000, "lat": -338688000, "hMSL": 36000,
+    });
+    encoder.encode_json(&json!({"name": "UBX-NAV-PVT", "fields": fields})).unwrap().to_bytes()
+}
+
+fn rxm_rawx(encoder: &Encoder<'_>, measurements: usize) -> Vec<u8> {
+    let meas: Vec<_> = (0..measurements)
+        .map(|i| {
+            json!({
+                "prMes": 2.1e7 + i as f64 * 1e5, "cpMes": 1.1e8 + i as f64 * 5e5, "doMes": -1200.5 + i as f64,
+                "gnssId": i % 4, "svId": i + 1, "sigId": 0, "locktime": 64000, "cno": 30 + i % 20,
+                "prStdev": 2, "cpStdev": 3, "doStdev": 4, "trkStat": 7,
+            })
+        })
+        .collect();
+    let fields = json!({"rcvTow": 369430.0, "week": 2400, "leaps": 18, "numMeas": measurements, "repeated_group": meas});
+    encoder.encode_json(&json!({"name": "UBX-RXM-RAWX", "fields": fields})).unwrap().to_bytes()
+}
+
+/// Ten minutes of a 1 Hz log: NAV-PVT and RXM-RAWX with 40 measurements
+/// per epoch, with a few bytes of line noise between epochs.
+fn log(encoder: &Encoder<'_>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for epoch in 0..600u32 {
+        out.extend(rxm_rawx(encoder, 40));
+        out.extend(nav_pvt(encoder, 369_430_000 + epoch * 1000));
+        out.extend(b"\x00\xB5\x00");
+    }
+    out
+}
+
+/// Packets ublox-rs parses from `data`, known or not.
+fn ublox_packets(data: &[u8]) -> usize {
+    let mut parser = ublox::Parser::default();
+    let mut packets = parser.consume(data);
+    let mut n = 0;
+    while let Some(packet) = packets.next() {
+        n += usize::from(packet.is_ok());
+    }
+    n
+}
+
+fn framing(c: &mut Criterion) {
+    let schema = Schema::load_default().unwrap();
+    let log = log(&Encoder::new(&schema));
+    let mut group = c.benchmark_group("framing");
+    group.throughput(Throughput::Bytes(log.len() as u64));
+    group.bench_function("frames", |b| b.iter(|| frames(black_box(&log)).count()));
+    group.bench_function("frame_parser_4k_chunks", |b| {
+        b.iter(|| {
+            let mut parser = FrameParser::new();
+            let mut n = 0;
+            for chunk in black_box(&log).chunks(4096) {
+                parser.push(chunk);
+                while let Some(frame) = parser.next_frame() {
+                    n += usize::from(frame.is_ok());
+                }
+            }
+            n
+        })
+    });
+    group.finish();
+}
+
+fn checksums(c: &mut Criterion) {
+    let schema = Schema::load_default().unwrap();
+    let frame = rxm_rawx(&Encoder::new(&schema), 40);
+    let body = &frame[2..frame.len() - 2];
+    let mut group = c.benchmark_group("checksum");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.bench_function("blocked", |b| b.iter(|| checksum(black_box(body))));
+    group.bench_function("bytewise", |b| b.iter(|| checksum_bytewise(black_box(body))));
+    group.finish();
+}
+
+fn single_messages(c: &mut Criterion) {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::new(&schema);
+    for (name, frame) in [("nav_pvt", nav_pvt(&encoder, 369_430_000)), ("rxm_rawx_40", rxm_rawx(&encoder, 40))] {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(frame.len() as u64));
+        let (class, id, payload) = (frame[2], frame[3], &frame[6..frame.len() - 2]);
+        group.bench_function("schema", |b| b.iter(|| decoder.decode(class, id, black_box(payload))));
+        group.bench_function("ublox_rs", |b| b.iter(|| ublox_packets(black_box(&frame))));
+        group.finish();
+    }
+}
+
+fn whole_log(c: &mut Criterion) {
+    let schema = Schema::load_default().unwrap();
+    let log = log(&Encoder::new(&schema));
+    let decoder = Decoder::new(&schema);
+    let mut group = c.benchmark_group("log");
+    group.throughput(Throughput::Bytes(log.len() as u64));
+    group.sample_size(20);
+    group.bench_function("schema", |b| {
+        b.iter(|| frames(black_box(&log)).flatten().map(|f| decoder.decode_frame(&f)).filter(|m| m.parsed).count())
+    });
+    group.bench_function("schema_arena", |b| {
+        let mut arena = Arena::new();
+        b.iter(|| {
+            frames(black_box(&log))
+                .flatten()
+                .filter(|f| decoder.decode_in(&mut arena, f.class, f.id, f.payload).parsed)
+                .count()
+        })
+    });
+    group.bench_function("ublox_rs", |b| b.iter(|| ublox_packets(black_box(&log))));
+    group.finish();
+}
+
+criterion_group!(benches, framing, checksums, single_messages, whole_log);
+criterion_main!(benches);
@@ -0,0 +1 @@
+//! Benchmarks for the schema decoder; see `benches/`.
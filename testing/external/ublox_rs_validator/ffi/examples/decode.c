@@ -0,0 +1,44 @@
+/*
+ * Print the messages in a .ubx file, reading it in chunks as from a port.
+ *
+ *   cargo build --release
+ *   cc -Iinclude examples/decode.c target/release/libubx_schema_ffi.a -lpthread -ldl -lm -o ubx-decode-c
+ *   ./ubx-decode-c capture.ubx
+ */
+#include <stdio.h>
+
+#include "ubx_schema.h"
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s capture.ubx\n", argv[0]);
+        return 2;
+    }
+    if (ubx_abi_version() != UBX_ABI_VERSION) {
+        fprintf(stderr, "ubx_schema.h does not match the library\n");
+        return 2;
+    }
+    FILE *file = fopen(argv[1], "rb");
+    if (!file) {
+        perror(argv[1]);
+        return 2;
+    }
+
+    ubx_decoder *decoder = ubx_decoder_new(UBX_SCALE | UBX_EXPAND_FLAGS);
+    uint8_t buf[512];
+    size_t n;
+    char *json;
+    while ((n = fread(buf, 1, sizeof buf, file)) > 0) {
+        if (ubx_decoder_push(decoder, buf, n) < 0) {
+            fprintf(stderr, "%s\n", ubx_last_error());
+            break;
+        }
+        while (ubx_decoder_next(decoder, &json) == 1) {
+            puts(json);
+            ubx_string_free(json);
+        }
+    }
+    ubx_decoder_free(decoder);
+    fclose(file);
+    return 0;
+}
@@ -0,0 +1,94 @@
+/*
+ * C API for the UBX schema decoder (testing/external/ublox_rs_validator/ffi).
+ *
+ * Link against libubx_schema_ffi.a (with -lpthread -ldl -lm) or
+ * libubx_schema_ffi.so. The schema is embedded in the library.
+ *
+ * Decoded messages are JSON text in the shape `ubx-decode --format json`
+ * writes, plus the byte "offset" of their frame. Every string the library
+ * returns through an out parameter is freed with ubx_string_free(). Functions
+ * returning int return a negative UBX_ERR_* code on failure, and
+ * ubx_last_error() describes it. All functions are safe to call from several
+ * threads; a decoder handle must be used by one thread at a time.
+ */
+#ifndef UBX_SCHEMA_H
+#define UBX_SCHEMA_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Bumped when a declaration here changes incompatibly; compare with ubx_abi_version(). */
+#define UBX_ABI_VERSION 1u
+
+/* Decode option flags. */
+#define UBX_SCALE (1u << 0)            /* apply field scale factors */
+#define UBX_EXPAND_FLAGS (1u << 1)     /* expand bitfields into named flags */
+#define UBX_INCLUDE_RESERVED (1u << 2) /* keep reserved fields */
+
+/* Return codes. */
+#define UBX_OK 0
+#define UBX_ERR_ARGUMENT (-1) /* a null pointer or otherwise invalid argument */
+#define UBX_ERR_SCHEMA (-2)   /* the schema file could not be loaded */
+#define UBX_ERR_INTERNAL (-3) /* the decoder failed; do not use the handle again */
+
+/* A streaming decoder. */
+typedef struct UbxDecoder ubx_decoder;
+
+/*
+ * Decode every frame in buf with default options. On success stores a JSON
+ * array of messages in *out_json and returns how many there are. buf may be
+ * NULL when len is 0.
+ */
+int ubx_decode(const uint8_t *buf, size_t len, char **out_json);
+
+/* As ubx_decode(), with UBX_* option flags. */
+int ubx_decode_ex(const uint8_t *buf, size_t len, uint32_t flags, char **out_json);
+
+/* Free a string returned by the library. NULL is ignored. */
+void ubx_string_free(char *s);
+
+/* A decoder using the embedded schema, or NULL if out of memory. */
+ubx_decoder *ubx_decoder_new(uint32_t flags);
+
+/* A decoder using the ubx_messages.json at path, or NULL if it cannot be loaded. */
+ubx_decoder *ubx_decoder_new_with_schema(const char *path, uint32_t flags);
+
+/*
+ * Add received bytes; frames may be split across calls. Returns how many
+ * decoded messages are ready for ubx_decoder_next().
+ */
+int ubx_decoder_push(ubx_decoder *decoder, const uint8_t *buf, size_t len);
+
+/*
+ * Take the next decoded message as a JSON object. Returns 1 and stores it in
+ * *out_json, or 0 if none is ready.
+ */
+int ubx_decoder_next(ubx_decoder *decoder, char **out_json);
+
+/* Bytes held back waiting for the rest of a frame. */
+size_t ubx_decoder_pending(const ubx_decoder *decoder);
+
+/* Free a decoder and any messages not yet taken. NULL is ignored. */
+void ubx_decoder_free(ubx_decoder *decoder);
+
+/*
+ * The last error on the calling thread, or NULL. Valid until the next failing
+ * call on the same thread; not to be freed.
+ */
+const char *ubx_last_error(void);
+
+/* Version of the embedded schema; not to be freed. */
+const char *ubx_schema_version(void);
+
+/* UBX_ABI_VERSION of the library. */
+uint32_t ubx_abi_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* UBX_SCHEMA_H */
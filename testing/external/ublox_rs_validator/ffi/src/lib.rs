@@ -0,0 +1,330 @@
+//! C ABI for the schema decoder, so C and C++ applications on embedded Linux
+//! can link the decoder directly. `include/ubx_schema.h` declares it.
+//!
+//! ```text
+//! char *json;
+//! int n = ubx_decode(buf, len, &json);       // every frame in buf, as a JSON array
+//! if (n >= 0) { puts(json); ubx_string_free(json); }
+//!
+//! ubx_decoder *dec = ubx_decoder_new(UBX_SCALE | UBX_EXPAND_FLAGS);
+//! ubx_decoder_push(dec, chunk, chunk_len);    // e.g. each read() from the port
+//! while (ubx_decoder_next(dec, &json) == 1) { handle(json); ubx_string_free(json); }
+//! ubx_decoder_free(dec);
+//! ```
+//!
+//! Messages are the objects `ubx-decode --format json` writes, plus the byte
+//! `offset` of their frame. Strings returned to the caller are freed with
+//! `ubx_string_free`. Functions returning `int` return a negative `UBX_ERR_*`
+//! code on failure, with a description from `ubx_last_error`. Panics do not
+//! cross the boundary.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::OnceLock;
+
+use serde_json::{Map, Value as Json};
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Schema};
+
+/// Bumped when a declaration in `ubx_schema.h` changes incompatibly.
+pub const UBX_ABI_VERSION: u32 = 1;
+
+/// Apply field scale factors.
+pub const UBX_SCALE: u32 = 1 << 0;
+/// Expand bitfields into named flags.
+pub const UBX_EXPAND_FLAGS: u32 = 1 << 1;
+/// Keep reserved fields.
+pub const UBX_INCLUDE_RESERVED: u32 = 1 << 2;
+
+pub const UBX_OK: c_int = 0;
+/// A null pointer or otherwise invalid argument.
+pub const UBX_ERR_ARGUMENT: c_int = -1;
+/// The schema file could not be loaded.
+pub const UBX_ERR_SCHEMA: c_int = -2;
+/// The decoder panicked; the handle should not be used again.
+pub const UBX_ERR_INTERNAL: c_int = -3;
+
+/// `data/messages/ubx_messages.json`.
+const SCHEMA_JSON: &str = include_str!("../../../../../data/messages/ubx_messages.json");
+
+fn default_schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::from_json_str(SCHEMA_JSON).expect("the embedded schema loads"))
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(code: c_int, message: impl Into<String>) -> c_int {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+    code
+}
+
+/// Run `f`, turning a panic into `UBX_ERR_INTERNAL`.
+fn guard(f: impl FnOnce() -> c_int) -> c_int {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| fail(UBX_ERR_INTERNAL, "panic in the decoder"))
+}
+
+fn options(flags: u32) -> DecodeOptions {
+    DecodeOptions {
+        scale: flags & UBX_SCALE != 0,
+        expand_flags: flags & UBX_EXPAND_FLAGS != 0,
+        include_reserved: flags & UBX_INCLUDE_RESERVED != 0,
+    }
+}
+
+fn message_json(offset: u64, msg: &DecodedMessage) -> Json {
+    let mut object = Map::new();
+    object.insert("offset".into(), offset.into());
+    if let Ok(Json::Object(fields)) = serde_json::to_value(msg) {
+        object.extend(fields);
+    }
+    Json::Object(object)
+}
+
+/// `len` bytes at `buf`; `buf` may be null when `len` is 0.
+///
+/// # Safety
+///
+/// A non-null `buf` must point to `len` readable bytes.
+unsafe fn bytes<'a>(buf: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (buf.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        // SAFETY: checked non-null; the caller guarantees the length.
+        (false, _) => Some(unsafe { slice::from_raw_parts(buf, len) }),
+    }
+}
+
+/// Hand `json` to the caller through `out`.
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+unsafe fn give(out: *mut *mut c_char, json: String) {
+    // serde_json escapes NUL, so the text has none.
+    let text = CString::new(json).unwrap_or_default();
+    // SAFETY: the caller checked `out` is non-null and it is writable.
+    unsafe { *out = text.into_raw() };
+}
+
+/// Decode every frame in `buf` with the embedded schema and default options.
+/// See `ubx_decode_ex`.
+///
+/// # Safety
+///
+/// As for `ubx_decode_ex`.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decode(buf: *const u8, len: usize, out_json: *mut *mut c_char) -> c_int {
+    // SAFETY: same contract.
+    unsafe { ubx_decode_ex(buf, len, 0, out_json) }
+}
+
+/// Decode every frame in `buf` with the embedded schema. On success stores a
+/// JSON array of messages in `*out_json` and returns how many there are.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes (or be null with `len` 0) and
+/// `out_json` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decode_ex(buf: *const u8, len: usize, flags: u32, out_json: *mut *mut c_char) -> c_int {
+    guard(|| {
+        // SAFETY: the caller's contract.
+        let Some(data) = (unsafe { bytes(buf, len) }) else {
+            return fail(UBX_ERR_ARGUMENT, "buf is null");
+        };
+        if out_json.is_null() {
+            return fail(UBX_ERR_ARGUMENT, "out_json is null");
+        }
+        let decoder = Decoder::with_options(default_schema(), options(flags));
+        let messages: Vec<Json> = frames(data)
+            .flatten()
+            .map(|frame| message_json(frame.offset as u64, &decoder.decode_frame(&frame)))
+            .collect();
+        let count = c_int::try_from(messages.len()).unwrap_or(c_int::MAX);
+        // SAFETY: checked non-null above.
+        unsafe { give(out_json, Json::Array(messages).to_string()) };
+        count
+    })
+}
+
+/// Free a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a string from this library not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: it came from `CString::into_raw` in `give`.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// A streaming decoder: frames may be split across pushed chunks.
+pub struct UbxDecoder {
+    /// A schema loaded by `ubx_decoder_new_with_schema`; the embedded one otherwise.
+    schema: Option<Schema>,
+    options: DecodeOptions,
+    parser: FrameParser,
+    ready: VecDeque<String>,
+}
+
+impl UbxDecoder {
+    fn new(schema: Option<Schema>, flags: u32) -> *mut UbxDecoder {
+        Box::into_raw(Box::new(UbxDecoder {
+            schema,
+            options: options(flags),
+            parser: FrameParser::new(),
+            ready: VecDeque::new(),
+        }))
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        let decoder = Decoder::with_options(self.schema.as_ref().unwrap_or_else(|| default_schema()), self.options);
+        self.parser.push(data);
+        while let Some(result) = self.parser.next_frame() {
+            if let Ok((offset, frame)) = result {
+                let msg = decoder.decode(frame.class, frame.id, &frame.payload);
+                self.ready.push_back(message_json(offset, &msg).to_string());
+            }
+        }
+    }
+}
+
+/// A streaming decoder using the embedded schema and `UBX_*` option `flags`.
+#[no_mangle]
+pub extern "C" fn ubx_decoder_new(flags: u32) -> *mut UbxDecoder {
+    catch_unwind(|| UbxDecoder::new(None, flags)).unwrap_or(ptr::null_mut())
+}
+
+/// A streaming decoder using the schema file at `path`, or null if it cannot
+/// be loaded.
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decoder_new_with_schema(path: *const c_char, flags: u32) -> *mut UbxDecoder {
+    if path.is_null() {
+        fail(UBX_ERR_ARGUMENT, "path is null");
+        return ptr::null_mut();
+    }
+    // SAFETY: the caller's contract.
+    let path = unsafe { CStr::from_ptr(path) }.to_string_lossy().into_owned();
+    match catch_unwind(|| Schema::load(&path)) {
+        Ok(Ok(schema)) => UbxDecoder::new(Some(schema), flags),
+        Ok(Err(e)) => {
+            fail(UBX_ERR_SCHEMA, format!("{path}: {e}"));
+            ptr::null_mut()
+        }
+        Err(_) => {
+            fail(UBX_ERR_INTERNAL, "panic loading the schema");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Add received bytes. Returns how many decoded messages are ready for
+/// `ubx_decoder_next`.
+///
+/// # Safety
+///
+/// `decoder` must come from `ubx_decoder_new*` and not be freed; `buf` must
+/// point to `len` readable bytes (or be null with `len` 0).
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decoder_push(decoder: *mut UbxDecoder, buf: *const u8, len: usize) -> c_int {
+    guard(|| {
+        // SAFETY: the caller's contract.
+        let Some(decoder) = (unsafe { decoder.as_mut() }) else {
+            return fail(UBX_ERR_ARGUMENT, "decoder is null");
+        };
+        // SAFETY: the caller's contract.
+        let Some(data) = (unsafe { bytes(buf, len) }) else {
+            return fail(UBX_ERR_ARGUMENT, "buf is null");
+        };
+        decoder.push(data);
+        c_int::try_from(decoder.ready.len()).unwrap_or(c_int::MAX)
+    })
+}
+
+/// Take the next decoded message as a JSON object. Returns 1 and stores it in
+/// `*out_json`, or 0 if none is ready.
+///
+/// # Safety
+///
+/// `decoder` must come from `ubx_decoder_new*` and not be freed; `out_json`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decoder_next(decoder: *mut UbxDecoder, out_json: *mut *mut c_char) -> c_int {
+    guard(|| {
+        // SAFETY: the caller's contract.
+        let Some(decoder) = (unsafe { decoder.as_mut() }) else {
+            return fail(UBX_ERR_ARGUMENT, "decoder is null");
+        };
+        if out_json.is_null() {
+            return fail(UBX_ERR_ARGUMENT, "out_json is null");
+        }
+        match decoder.ready.pop_front() {
+            Some(json) => {
+                // SAFETY: checked non-null above.
+                unsafe { give(out_json, json) };
+                1
+            }
+            None => 0,
+        }
+    })
+}
+
+/// Bytes held back waiting for the rest of a frame; 0 for null.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from `ubx_decoder_new*` and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decoder_pending(decoder: *const UbxDecoder) -> usize {
+    // SAFETY: the caller's contract.
+    unsafe { decoder.as_ref() }.map_or(0, |d| d.parser.pending())
+}
+
+/// Free a decoder and any messages not yet taken. Null is ignored.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from `ubx_decoder_new*` and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn ubx_decoder_free(decoder: *mut UbxDecoder) {
+    if !decoder.is_null() {
+        // SAFETY: it came from `Box::into_raw` in `UbxDecoder::new`.
+        drop(unsafe { Box::from_raw(decoder) });
+    }
+}
+
+/// The last error on this thread, or null. Valid until the next failing call
+/// on the same thread; not to be freed.
+#[no_mangle]
+pub extern "C" fn ubx_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Version of the embedded schema; not to be freed.
+#[no_mangle]
+pub extern "C" fn ubx_schema_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(default_schema().schema_version.clone()).unwrap_or_default())
+        .as_ptr()
+}
+
+/// `UBX_ABI_VERSION` of the library, to check against the header at run time.
+#[no_mangle]
+pub extern "C" fn ubx_abi_version() -> u32 {
+    UBX_ABI_VERSION
+}
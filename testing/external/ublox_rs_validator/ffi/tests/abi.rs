@@ -0,0 +1,76 @@
+//! The C functions, called through their Rust declarations.
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use serde_json::Value as Json;
+use ubx_schema::{Encoder, Schema};
+use ubx_schema_ffi::*;
+
+fn pvt_frame() -> Vec<u8> {
+    let schema = Schema::load_default().unwrap();
+    Encoder::new(&schema)
+        .encode_json(&serde_json::json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "lat": 473_977_418}}))
+        .unwrap()
+        .to_bytes()
+}
+
+unsafe fn take(s: *mut c_char) -> Json {
+    let json = serde_json::from_str(CStr::from_ptr(s).to_str().unwrap()).unwrap();
+    ubx_string_free(s);
+    json
+}
+
+#[test]
+fn decodes_buffers_and_streams() {
+    let pvt = pvt_frame();
+    let mut capture = b"junk".to_vec();
+    capture.extend_from_slice(&pvt);
+    capture.extend_from_slice(&pvt);
+
+    unsafe {
+        let mut json = ptr::null_mut();
+        assert_eq!(ubx_decode_ex(capture.as_ptr(), capture.len(), UBX_SCALE, &mut json), 2);
+        let messages = take(json);
+        assert_eq!(messages[0]["offset"], 4);
+        assert_eq!(messages[1]["offset"], 4 + pvt.len());
+        assert_eq!(messages[0]["name"], "UBX-NAV-PVT");
+        assert_eq!(messages[0]["fields"]["lat"], 47.3977418);
+
+        assert_eq!(ubx_decode(ptr::null(), 0, &mut json), 0);
+        assert_eq!(take(json), serde_json::json!([]));
+
+        let decoder = ubx_decoder_new(UBX_SCALE);
+        let (head, tail) = capture.split_at(4 + 20);
+        assert_eq!(ubx_decoder_push(decoder, head.as_ptr(), head.len()), 0);
+        assert_eq!(ubx_decoder_pending(decoder), 20);
+        assert_eq!(ubx_decoder_next(decoder, &mut json), 0);
+        assert_eq!(ubx_decoder_push(decoder, tail.as_ptr(), tail.len()), 2);
+        assert_eq!(ubx_decoder_next(decoder, &mut json), 1);
+        assert_eq!(take(json), messages[0]);
+        assert_eq!(ubx_decoder_next(decoder, &mut json), 1);
+        assert_eq!(take(json), messages[1]);
+        assert_eq!(ubx_decoder_next(decoder, &mut json), 0);
+        ubx_decoder_free(decoder);
+    }
+}
+
+#[test]
+fn reports_errors_without_panicking() {
+    unsafe {
+        let mut json = ptr::null_mut();
+        assert_eq!(ubx_decode(ptr::null(), 8, &mut json), UBX_ERR_ARGUMENT);
+        assert_eq!(CStr::from_ptr(ubx_last_error()).to_str().unwrap(), "buf is null");
+        assert_eq!(ubx_decoder_next(ptr::null_mut(), &mut json), UBX_ERR_ARGUMENT);
+
+        let path = CString::new("/nonexistent/ubx_messages.json").unwrap();
+        assert!(ubx_decoder_new_with_schema(path.as_ptr(), 0).is_null());
+        let error = CStr::from_ptr(ubx_last_error()).to_str().unwrap();
+        assert!(error.starts_with("/nonexistent/ubx_messages.json: "), "{error}");
+
+        ubx_decoder_free(ptr::null_mut());
+        ubx_string_free(ptr::null_mut());
+        assert!(!CStr::from_ptr(ubx_schema_version()).to_bytes().is_empty());
+        assert_eq!(ubx_abi_version(), UBX_ABI_VERSION);
+    }
+}
@@ -0,0 +1,35 @@
+//! Feed arbitrary bytes to the streaming parser in chunks. The first byte
+//! picks the chunk size. The buffer must never grow past one maximum-length
+//! frame plus the chunk, however the length fields are forged.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ubx_schema::frame::{frames, FrameParser, FRAME_OVERHEAD};
+
+const MAX_FRAME: usize = FRAME_OVERHEAD + u16::MAX as usize;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk, stream)) = data.split_first() else {
+        return;
+    };
+    let mut parser = FrameParser::new();
+    let mut last = None;
+    let mut found = 0;
+    for chunk in stream.chunks(usize::from(chunk).max(1)) {
+        parser.push(chunk);
+        while let Some(result) = parser.next_frame() {
+            if let Ok((offset, frame)) = result {
+                assert!(last.is_none_or(|l| offset > l), "offsets must increase");
+                last = Some(offset);
+                found += 1;
+                let start = offset as usize;
+                assert_eq!(&stream[start..start + frame.to_bytes().len()], frame.to_bytes());
+            }
+        }
+        assert!(parser.pending() <= MAX_FRAME + chunk.len());
+    }
+    // The whole-buffer scan sees everything the stream does, plus frames
+    // behind a false sync still waiting for data at the end.
+    assert!(found <= frames(stream).flatten().count());
+});
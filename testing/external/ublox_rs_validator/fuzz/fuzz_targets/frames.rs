@@ -0,0 +1,29 @@
+//! Scan arbitrary bytes for frames and decode each one with the schema.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use ubx_schema::frame::frames;
+use ubx_schema::{Decoder, Schema};
+
+fn schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::load_default().expect("schema loads"))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let decoder = Decoder::new(schema());
+    let mut iter = frames(data);
+    let mut end = 0;
+    for frame in iter.by_ref().flatten() {
+        assert!(frame.offset >= end, "frames overlap");
+        end = frame.offset + frame.raw.len();
+        assert!(end <= data.len());
+        assert_eq!(&data[frame.offset..end], frame.raw);
+        decoder.decode_frame(&frame);
+    }
+    let stats = iter.stats();
+    assert!(stats.skipped_bytes <= data.len());
+});
@@ -0,0 +1,281 @@
+//! Python bindings for the schema decoder and encoder.
+//!
+//! Built with maturin as the `ubx_schema` module, with the schema embedded:
+//!
+//! ```text
+//! import ubx_schema
+//! for msg in ubx_schema.decode(open("capture.ubx", "rb").read(), scale=True, expand_flags=True):
+//!     print(msg["offset"], msg["name"], msg["fields"]["iTOW"])
+//! frame = ubx_schema.encode({"name": "CFG-RATE", "fields": {"measRate": 200, "navRate": 1, "timeRef": 1}})
+//! schema = ubx_schema.Schema("ubx_messages.json")   # another schema file
+//! ```
+//!
+//! Messages are dicts with the keys `parse_ubx_message` in `testing/lib`
+//! returns (`name`, `class_id`, `message_id`, `payload_length`, `fields`,
+//! `parsed` and `variant` for variants); `decode` adds the frame `offset`.
+//! Decoding runs without the GIL.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString};
+use serde_json::{Map, Value as Json};
+use ubx_schema::frame::frames;
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, EncodeOptions, Encoder, Fields, Schema, Value};
+
+create_exception!(ubx_schema, UbxError, PyException, "A schema, framing or encoding error.");
+
+/// `data/messages/ubx_messages.json`.
+const SCHEMA_JSON: &str = include_str!("../../../../../data/messages/ubx_messages.json");
+
+fn default_schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::from_json_str(SCHEMA_JSON).expect("the embedded schema loads"))
+}
+
+fn to_py_err(e: ubx_schema::Error) -> PyErr {
+    UbxError::new_err(e.to_string())
+}
+
+fn value_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Unsigned(v) => (*v).into_pyobject(py)?.into_any(),
+        Value::Signed(v) => (*v).into_pyobject(py)?.into_any(),
+        Value::Float(v) => (*v).into_pyobject(py)?.into_any(),
+        Value::Text(s) => s.as_str().into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(value_to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Record(fields) => fields_to_py(py, fields)?.into_any(),
+    })
+}
+
+fn fields_to_py<'py>(py: Python<'py>, fields: &Fields) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (name, value) in fields.iter() {
+        dict.set_item(name, value_to_py(py, value)?)?;
+    }
+    Ok(dict)
+}
+
+fn message_to_py<'py>(py: Python<'py>, offset: Option<usize>, msg: &DecodedMessage) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    if let Some(offset) = offset {
+        dict.set_item("offset", offset)?;
+    }
+    dict.set_item("name", &msg.name)?;
+    dict.set_item("class_id", msg.class_id)?;
+    dict.set_item("message_id", msg.message_id)?;
+    if let Some(variant) = &msg.variant {
+        dict.set_item("variant", variant)?;
+    }
+    dict.set_item("payload_length", msg.payload_length)?;
+    dict.set_item("fields", fields_to_py(py, &msg.fields)?)?;
+    dict.set_item("parsed", msg.parsed)?;
+    if let Some(raw) = &msg.payload_raw {
+        dict.set_item("payload_raw", raw)?;
+    }
+    Ok(dict)
+}
+
+/// A message description for the encoder, from Python values.
+fn py_to_json(obj: &Bound<'_, PyAny>) -> PyResult<Json> {
+    if obj.is_none() {
+        return Ok(Json::Null);
+    }
+    // bool is a subclass of int, so check it first.
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Json::Bool(b.is_true()));
+    }
+    if obj.is_instance_of::<PyInt>() {
+        return match obj.extract::<i64>() {
+            Ok(v) => Ok(v.into()),
+            Err(_) => Ok(obj.extract::<u64>()?.into()),
+        };
+    }
+    if let Ok(v) = obj.downcast::<PyFloat>() {
+        return Ok(v.value().into());
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(Json::String(s.to_str()?.to_string()));
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, value) in dict.iter() {
+            map.insert(key.extract::<String>()?, py_to_json(&value)?);
+        }
+        return Ok(Json::Object(map));
+    }
+    if let Ok(items) = obj.try_iter() {
+        return items.map(|item| py_to_json(&item?)).collect::<PyResult<Vec<_>>>().map(Json::Array);
+    }
+    Err(PyTypeError::new_err(format!("cannot encode a {}", obj.get_type().name()?)))
+}
+
+fn decode_all<'py>(
+    py: Python<'py>,
+    schema: &Schema,
+    data: &[u8],
+    options: DecodeOptions,
+) -> PyResult<Bound<'py, PyList>> {
+    let messages: Vec<(usize, DecodedMessage)> = py.allow_threads(|| {
+        let decoder = Decoder::with_options(schema, options);
+        frames(data).flatten().map(|frame| (frame.offset, decoder.decode_frame(&frame))).collect()
+    });
+    let list = PyList::empty(py);
+    for (offset, msg) in &messages {
+        list.append(message_to_py(py, Some(*offset), msg)?)?;
+    }
+    Ok(list)
+}
+
+fn encode_message<'py>(
+    py: Python<'py>,
+    schema: &Schema,
+    message: &Bound<'py, PyDict>,
+    scaled: bool,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let json = py_to_json(message.as_any())?;
+    let frame = Encoder::with_options(schema, EncodeOptions { scaled }).encode_json(&json).map_err(to_py_err)?;
+    let bytes = frame.try_to_bytes().map_err(|e| to_py_err(e.into()))?;
+    Ok(PyBytes::new(py, &bytes))
+}
+
+fn options(scale: bool, expand_flags: bool, include_reserved: bool) -> DecodeOptions {
+    DecodeOptions {
+        scale,
+        expand_flags,
+        include_reserved,
+    }
+}
+
+/// A loaded message schema.
+#[pyclass(name = "Schema", module = "ubx_schema", frozen)]
+struct PySchema {
+    schema: Schema,
+}
+
+#[pymethods]
+impl PySchema {
+    /// Load `path`, or the embedded schema if omitted.
+    #[new]
+    #[pyo3(signature = (path = None))]
+    fn new(path: Option<PathBuf>) -> PyResult<Self> {
+        let schema = match path {
+            Some(path) => Schema::load(path).map_err(to_py_err)?,
+            None => default_schema().clone(),
+        };
+        Ok(Self { schema })
+    }
+
+    #[getter]
+    fn version(&self) -> &str {
+        &self.schema.schema_version
+    }
+
+    /// Names of the messages, e.g. `UBX-NAV-PVT`.
+    fn message_names(&self) -> Vec<String> {
+        self.schema.messages.iter().map(|m| m.name.clone()).collect()
+    }
+
+    /// Decode every frame in `data`.
+    #[pyo3(signature = (data, *, scale = false, expand_flags = false, include_reserved = false))]
+    fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        data: &[u8],
+        scale: bool,
+        expand_flags: bool,
+        include_reserved: bool,
+    ) -> PyResult<Bound<'py, PyList>> {
+        decode_all(py, &self.schema, data, options(scale, expand_flags, include_reserved))
+    }
+
+    /// Decode one payload.
+    #[pyo3(signature = (
+        class_id, message_id, payload, *, scale = false, expand_flags = false, include_reserved = false
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn decode_message<'py>(
+        &self,
+        py: Python<'py>,
+        class_id: u8,
+        message_id: u8,
+        payload: &[u8],
+        scale: bool,
+        expand_flags: bool,
+        include_reserved: bool,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let decoder = Decoder::with_options(&self.schema, options(scale, expand_flags, include_reserved));
+        message_to_py(py, None, &decoder.decode(class_id, message_id, payload))
+    }
+
+    /// Encode `{"name": ..., "fields": {...}}` as a complete frame.
+    #[pyo3(signature = (message, *, scaled = false))]
+    fn encode<'py>(
+        &self,
+        py: Python<'py>,
+        message: &Bound<'py, PyDict>,
+        scaled: bool,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        encode_message(py, &self.schema, message, scaled)
+    }
+}
+
+/// Decode every frame in `data` with the embedded schema.
+#[pyfunction]
+#[pyo3(signature = (data, *, scale = false, expand_flags = false, include_reserved = false))]
+fn decode<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    scale: bool,
+    expand_flags: bool,
+    include_reserved: bool,
+) -> PyResult<Bound<'py, PyList>> {
+    decode_all(py, default_schema(), data, options(scale, expand_flags, include_reserved))
+}
+
+/// Decode one payload with the embedded schema.
+#[pyfunction]
+#[pyo3(signature = (
+    class_id, message_id, payload, *, scale = false, expand_flags = false, include_reserved = false
+))]
+fn decode_message<'py>(
+    py: Python<'py>,
+    class_id: u8,
+    message_id: u8,
+    payload: &[u8],
+    scale: bool,
+    expand_flags: bool,
+    include_reserved: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let decoder = Decoder::with_options(default_schema(), options(scale, expand_flags, include_reserved));
+    message_to_py(py, None, &decoder.decode(class_id, message_id, payload))
+}
+
+/// Encode a message description with the embedded schema.
+#[pyfunction]
+#[pyo3(signature = (message, *, scaled = false))]
+fn encode<'py>(py: Python<'py>, message: &Bound<'py, PyDict>, scaled: bool) -> PyResult<Bound<'py, PyBytes>> {
+    encode_message(py, default_schema(), message, scaled)
+}
+
+#[pymodule]
+#[pyo3(name = "ubx_schema")]
+fn ubx_schema_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("schema_version", &default_schema().schema_version)?;
+    m.add("UbxError", m.py().get_type::<UbxError>())?;
+    m.add_class::<PySchema>()?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_message, m)?)?;
+    m.add_function(wrap_pyfunction!(encode, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Hide the location in a capture so it can be shared publicly.
+//!
+//! Position-bearing messages are rewritten in place and their checksums
+//! recomputed; every other frame is copied unchanged. Positions move by a
+//! constant latitude/longitude/height offset, optionally plus random jitter.
+//! The jitter is drawn once per epoch (iTOW) from a seeded generator, so the
+//! NAV-PVT, NAV-HPPOSLLH and NAV-POSECEF of one epoch still agree with each
+//! other and the same seed reproduces the same output. ECEF positions are
+//! moved through geodetic coordinates so they stay consistent with the LLH
+//! messages. NAV-RELPOSNED only describes the base-to-rover vector; a
+//! constant offset moves both ends and leaves it alone, and jitter is added
+//! to it with the length and heading recomputed.
+//!
+//! All-zero positions (no fix yet) are left as they are: shifting them would
+//! publish the offset itself.
+
+use std::collections::BTreeMap;
+
+use crate::decode::{read_scalar, Decoder};
+use crate::frame::{frames, Frame, FrameRef};
+use crate::geo::{ecef_to_llh, llh_to_ecef, Ecef, Llh, WGS84_A};
+use crate::schema::{BaseType, PayloadDef, Schema};
+use crate::value::Value;
+
+/// A constant shift applied to every position.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Offset {
+    /// Degrees.
+    pub lat: f64,
+    /// Degrees.
+    pub lon: f64,
+    /// Metres.
+    pub height: f64,
+}
+
+/// A coordinate stored as a main integer field plus an optional
+/// high-precision remainder.
+#[derive(Clone, Copy)]
+struct Coord {
+    main: &'static str,
+    hp: Option<&'static str>,
+    /// Physical units per LSB of `main` and of `hp`.
+    unit: f64,
+    hp_unit: f64,
+}
+
+const fn coord(main: &'static str, hp: Option<&'static str>, unit: f64, hp_unit: f64) -> Coord {
+    Coord {
+        main,
+        hp,
+        unit,
+        hp_unit,
+    }
+}
+
+const DEG: f64 = 1e-7;
+const DEG_HP: f64 = 1e-9;
+const MM: f64 = 1e-3;
+const CM: f64 = 1e-2;
+const HP: f64 = 1e-4;
+
+enum Kind {
+    Llh { lat: Coord, lon: Coord, heights: &'static [Coord] },
+    Ecef([Coord; 3]),
+    Relative { ned: [Coord; 3], length: Coord, heading: &'static str },
+}
+
+struct Rule {
+    messages: &'static [&'static str],
+    kind: Kind,
+}
+
+const HEIGHTS: &[Coord] = &[coord("height", None, MM, 0.0), coord("hMSL", None, MM, 0.0)];
+const HP_HEIGHTS: &[Coord] = &[coord("height", Some("heightHp"), MM, HP), coord("hMSL", Some("hMSLHp"), MM, HP)];
+
+const RULES: &[Rule] = &[
+    Rule {
+        messages: &["NAV-PVT", "NAV-PVAT", "NAV-POSLLH", "NAV2-PVT", "NAV2-POSLLH"],
+        kind: Kind::Llh {
+            lat: coord("lat", None, DEG, 0.0),
+            lon: coord("lon", None, DEG, 0.0),
+            heights: HEIGHTS,
+        },
+    },
+    Rule {
+        messages: &["NAV-HPPOSLLH"],
+        kind: Kind::Llh {
+            lat: coord("lat", Some("latHp"), DEG, DEG_HP),
+            lon: coord("lon", Some("lonHp"), DEG, DEG_HP),
+            heights: HP_HEIGHTS,
+        },
+    },
+    Rule {
+        messages: &["NAV-POSECEF", "NAV2-POSECEF", "NAV-SOL"],
+        kind: Kind::Ecef([
+            coord("ecefX", None, CM, 0.0),
+            coord("ecefY", None, CM, 0.0),
+            coord("ecefZ", None, CM, 0.0),
+        ]),
+    },
+    Rule {
+        messages: &["NAV-HPPOSECEF"],
+        kind: Kind::Ecef([
+            coord("ecefX", Some("ecefXHp"), CM, HP),
+            coord("ecefY", Some("ecefYHp"), CM, HP),
+            coord("ecefZ", Some("ecefZHp"), CM, HP),
+        ]),
+    },
+    Rule {
+        messages: &["NAV-SVIN", "NAV2-SVIN"],
+        kind: Kind::Ecef([
+            coord("meanX", Some("meanXHP"), CM, HP),
+            coord("meanY", Some("meanYHP"), CM, HP),
+            coord("meanZ", Some("meanZHP"), CM, HP),
+        ]),
+    },
+    Rule {
+        messages: &["TIM-SVIN"],
+        kind: Kind::Ecef([
+            coord("meanX", None, CM, 0.0),
+            coord("meanY", None, CM, 0.0),
+            coord("meanZ", None, CM, 0.0),
+        ]),
+    },
+    Rule {
+        messages: &["NAV-RELPOSNED"],
+        kind: Kind::Relative {
+            ned: [
+                coord("relPosN", Some("relPosHPN"), CM, HP),
+                coord("relPosE", Some("relPosHPE"), CM, HP),
+                coord("relPosD", Some("relPosHPD"), CM, HP),
+            ],
+            length: coord("relPosLength", Some("relPosHPLength"), CM, HP),
+            heading: "relPosHeading",
+        },
+    },
+];
+
+/// Reads and writes coordinates of one payload through its schema definition.
+struct Fields<'a> {
+    def: &'a PayloadDef,
+    payload: &'a mut [u8],
+}
+
+impl Fields<'_> {
+    fn slot(&self, name: &str) -> Option<(usize, usize, BaseType)> {
+        let field = self.def.field(name)?;
+        let base = field.data_type.base()?;
+        let offset = field.byte_offset?;
+        (offset + base.size() <= self.payload.len()).then_some((offset, base.size(), base))
+    }
+
+    fn int(&self, name: &str) -> Option<i64> {
+        let (offset, size, base) = self.slot(name)?;
+        match read_scalar(base, &self.payload[offset..offset + size]) {
+            Value::Signed(v) => Some(v),
+            Value::Unsigned(v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    fn set_int(&mut self, name: &str, value: i64) {
+        if let Some((offset, size, _)) = self.slot(name) {
+            self.payload[offset..offset + size].copy_from_slice(&value.to_le_bytes()[..size]);
+        }
+    }
+
+    fn get(&self, c: Coord) -> Option<f64> {
+        let main = self.int(c.main)? as f64 * c.unit;
+        let hp = match c.hp {
+            Some(name) => self.int(name)? as f64 * c.hp_unit,
+            None => 0.0,
+        };
+        Some(main + hp)
+    }
+
+    fn set(&mut self, c: Coord, value: f64) {
+        match c.hp {
+            Some(name) => {
+                let ratio = (c.unit / c.hp_unit).round() as i64;
+                let fine = (value / c.hp_unit).round() as i64;
+                self.set_int(c.main, fine / ratio);
+                self.set_int(name, fine % ratio);
+            }
+            None => self.set_int(c.main, (value / c.unit).round() as i64),
+        }
+    }
+}
+
+/// What [`Anonymizer::anonymize`] did to a log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnonymizeStats {
+    pub frames: usize,
+    /// Rewritten frames by message name.
+    pub rewritten: BTreeMap<String, usize>,
+    /// Bytes outside UBX frames (NMEA, RTCM, noise) that were left out.
+    pub dropped_bytes: usize,
+}
+
+pub struct Anonymizer<'s> {
+    schema: &'s Schema,
+    offset: Offset,
+    jitter: f64,
+    seed: u64,
+}
+
+impl<'s> Anonymizer<'s> {
+    pub fn new(schema: &'s Schema, offset: Offset) -> Self {
+        Self {
+            schema,
+            offset,
+            jitter: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Add up to `metres` of random displacement per axis, drawn per epoch from `seed`.
+    pub fn jitter(mut self, metres: f64, seed: u64) -> Self {
+        self.jitter = metres;
+        self.seed = seed;
+        self
+    }
+
+    /// North, east and up jitter in metres for the epoch at `itow`.
+    fn noise(&self, itow: Option<u32>) -> [f64; 3] {
+        if self.jitter == 0.0 {
+            return [0.0; 3];
+        }
+        let mut state = self.seed ^ u64::from(itow.unwrap_or(0)).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        [0; 3].map(|_| {
+            let unit = (splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+            (unit * 2.0 - 1.0) * self.jitter
+        })
+    }
+
+    fn shift(&self, p: Llh, [north, east, up]: [f64; 3]) -> Llh {
+        let lat = (p.lat + self.offset.lat + (north / WGS84_A).to_degrees()).clamp(-90.0, 90.0);
+        let east_deg = (east / (WGS84_A * p.lat.to_radians().cos().max(1e-6))).to_degrees();
+        let lon = (p.lon + self.offset.lon + east_deg + 180.0).rem_euclid(360.0) - 180.0;
+        Llh {
+            lat,
+            lon,
+            height: p.height + self.offset.height + up,
+        }
+    }
+
+    /// The rewritten frame if `frame` carries a position, `None` otherwise.
+    /// `itow` is the epoch the frame belongs to and keys the jitter.
+    pub fn rewrite(&self, frame: &FrameRef<'_>, itow: Option<u32>) -> Option<Frame> {
+        let msg = self.schema.message_for_payload(frame.class, frame.id, frame.payload)?;
+        let rule = RULES.iter().find(|r| r.messages.contains(&msg.short_name()))?;
+        let (def, _) = msg.payload_for(frame.payload)?;
+        let mut payload = frame.payload.to_vec();
+        let mut fields = Fields {
+            def,
+            payload: &mut payload,
+        };
+        let noise = self.noise(itow);
+
+        match &rule.kind {
+            Kind::Llh { lat, lon, heights } => {
+                let (lat_deg, lon_deg) = (fields.get(*lat)?, fields.get(*lon)?);
+                if lat_deg == 0.0 && lon_deg == 0.0 {
+                    return None;
+                }
+                let moved = self.shift(Llh { lat: lat_deg, lon: lon_deg, height: 0.0 }, noise);
+                fields.set(*lat, moved.lat);
+                fields.set(*lon, moved.lon);
+                for h in heights.iter() {
+                    if let Some(value) = fields.get(*h) {
+                        fields.set(*h, value + moved.height);
+                    }
+                }
+            }
+            Kind::Ecef(xyz) => {
+                let [x, y, z] = [fields.get(xyz[0])?, fields.get(xyz[1])?, fields.get(xyz[2])?];
+                if x == 0.0 && y == 0.0 && z == 0.0 {
+                    return None;
+                }
+                let moved = llh_to_ecef(self.shift(ecef_to_llh(Ecef { x, y, z }), noise));
+                fields.set(xyz[0], moved.x);
+                fields.set(xyz[1], moved.y);
+                fields.set(xyz[2], moved.z);
+            }
+            Kind::Relative { ned, length, heading } => {
+                let [n, e, d] = [fields.get(ned[0])?, fields.get(ned[1])?, fields.get(ned[2])?];
+                if self.jitter == 0.0 || (n == 0.0 && e == 0.0 && d == 0.0) {
+                    return None;
+                }
+                let [n, e, d] = [n + noise[0], e + noise[1], d - noise[2]];
+                fields.set(ned[0], n);
+                fields.set(ned[1], e);
+                fields.set(ned[2], d);
+                fields.set(*length, (n * n + e * e + d * d).sqrt());
+                if fields.slot(heading).is_some() {
+                    let degrees = e.atan2(n).to_degrees().rem_euclid(360.0);
+                    fields.set(coord(heading, None, 1e-5, 0.0), degrees);
+                }
+            }
+        }
+        Some(Frame::new(frame.class, frame.id, payload))
+    }
+
+    /// Anonymize a whole log. Only UBX frames are kept: NMEA sentences would
+    /// reveal the position in plain text.
+    pub fn anonymize(&self, input: &[u8]) -> (Vec<u8>, AnonymizeStats) {
+        let mut out = Vec::with_capacity(input.len());
+        let mut stats = AnonymizeStats::default();
+        let decoder = Decoder::new(self.schema);
+        let mut epoch = None;
+        for frame in frames(input).flatten() {
+            stats.frames += 1;
+            let msg = decoder.decode_frame(&frame);
+            epoch = msg.itow().or(epoch);
+            match self.rewrite(&frame, epoch) {
+                Some(rewritten) => {
+                    out.extend(rewritten.to_bytes());
+                    *stats.rewritten.entry(msg.short_name().to_string()).or_default() += 1;
+                }
+                None => out.extend_from_slice(frame.raw),
+            }
+        }
+        stats.dropped_bytes = input.len() - out.len();
+        (out, stats)
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,555 @@
+//! Decoding into a caller-owned arena, for hot loops over large logs.
+//!
+//! [`Decoder::decode`] builds a [`DecodedMessage`] with a `String` per field
+//! name and a `Vec` per record and repeated group. [`Decoder::decode_in`]
+//! decodes the same message into an [`Arena`] instead: values are appended
+//! to flat buffers, names are borrowed from the schema and the arena is reset
+//! at the start of each frame, so once its buffers have grown to the largest
+//! message in the log no further allocation happens. The message is a view
+//! that borrows the arena until the next frame.
+//!
+//! ```no_run
+//! # let schema = ubx_schema::Schema::load_default().unwrap();
+//! # let log = std::fs::read("capture.ubx").unwrap();
+//! use ubx_schema::arena::Arena;
+//! let decoder = ubx_schema::Decoder::new(&schema);
+//! let mut arena = Arena::new();
+//! for frame in ubx_schema::frame::frames(&log).flatten() {
+//!     let msg = decoder.decode_in(&mut arena, frame.class, frame.id, frame.payload);
+//!     if let Some(cno) = msg.fields.get("cno").and_then(|v| v.as_u64()) {
+//!         println!("{} {cno}", msg.name);
+//!     }
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::decode::{read_scalar, resolve_count, DecodeOptions, DecodedMessage, Decoder};
+use crate::schema::{fields_extent, BaseOffset, BaseType, Count, DataType, FieldDef, PayloadDef};
+use crate::value::{Fields, Value};
+
+/// A run of entries in one of the arena's buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Node {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    /// Bytes of [`Arena::text`].
+    Text(Span),
+    /// Entries of [`Arena::nodes`]; their names are empty.
+    Array(Span),
+    Record(Span),
+}
+
+/// Reusable storage for [`Decoder::decode_in`].
+///
+/// `'s` is the lifetime of the schema, whose field names the arena borrows.
+#[derive(Debug, Default)]
+pub struct Arena<'s> {
+    /// Finished values. The members of a record or array are contiguous;
+    /// theirs come earlier.
+    nodes: Vec<(&'s str, Node)>,
+    /// Members of the records being decoded, moved to `nodes` when complete.
+    stack: Vec<(&'s str, Node)>,
+    /// Characters and hex payloads.
+    text: String,
+    /// Integer fields decoded so far, for counts and offsets; repeated-group
+    /// members are dropped after each instance.
+    raw: Vec<(&'s str, u64)>,
+}
+
+impl<'s> Arena<'s> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discard the last message, keeping the buffers.
+    pub fn reset(&mut self) {
+        self.nodes.clear();
+        self.stack.clear();
+        self.text.clear();
+        self.raw.clear();
+    }
+
+    fn raw(&self, name: &str) -> Option<u64> {
+        self.raw.iter().rev().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    /// Move the members pushed since `mark` into `nodes`.
+    fn close(&mut self, mark: usize) -> Span {
+        let start = self.nodes.len();
+        self.nodes.extend(self.stack.drain(mark..));
+        Span {
+            start,
+            len: self.nodes.len() - start,
+        }
+    }
+
+    fn push_text(&mut self, chars: impl Iterator<Item = char>) -> Node {
+        let start = self.text.len();
+        self.text.extend(chars);
+        Node::Text(Span {
+            start,
+            len: self.text.len() - start,
+        })
+    }
+
+    fn scalar(&mut self, base: BaseType, bytes: &[u8]) -> Node {
+        match read_scalar(base, bytes) {
+            Value::Unsigned(v) => Node::Unsigned(v),
+            Value::Signed(v) => Node::Signed(v),
+            Value::Float(v) => Node::Float(v),
+            _ => self.push_text(bytes.iter().map(|&b| char::from(b))),
+        }
+    }
+
+    fn array(&mut self, base: BaseType, bytes: &[u8]) -> Node {
+        if base == BaseType::CH {
+            let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+            return self.push_text(bytes[..len].iter().map(|&b| char::from(b)));
+        }
+        let mark = self.stack.len();
+        for chunk in bytes.chunks_exact(base.size()) {
+            let node = self.scalar(base, chunk);
+            self.stack.push(("", node));
+        }
+        Node::Array(self.close(mark))
+    }
+
+    /// [`Decoder::decode_payload`], leaving the top-level fields in `nodes`.
+    fn payload(&mut self, options: DecodeOptions, def: &'s PayloadDef, payload: &[u8]) -> Span {
+        let end = self.fields(options, &def.fields, payload);
+        for group in &def.repeated_groups {
+            let base = match &group.base_offset {
+                BaseOffset::Fixed(n) => *n,
+                BaseOffset::Dynamic { base, terms } => terms.iter().fold(*base, |acc, (field, mult)| {
+                    acc + self.raw(field).unwrap_or(0) as usize * mult
+                }),
+                BaseOffset::AfterFields => end,
+            };
+            let size = if group.group_size > 0 {
+                group.group_size
+            } else {
+                fields_extent(&group.fields)
+            };
+            let items = self.group(options, &group.fields, &group.count, size, &def.fields, payload, base);
+            self.stack.push((&group.name, Node::Array(items)));
+        }
+        self.close(0)
+    }
+
+    /// Push the fields in `defs` onto the stack, as `Decoder::decode_fields`
+    /// adds them to its output. Returns the end offset of the last one.
+    fn fields(&mut self, options: DecodeOptions, defs: &'s [FieldDef], data: &[u8]) -> usize {
+        let mut end = 0;
+        for field in defs {
+            let Some(offset) = field.byte_offset else {
+                continue;
+            };
+            if offset >= data.len() {
+                continue;
+            }
+            let value = match &field.data_type {
+                DataType::Scalar(base) => {
+                    let Some(bytes) = data.get(offset..offset + base.size()) else {
+                        continue;
+                    };
+                    end = end.max(offset + base.size());
+                    let value = self.scalar(*base, bytes);
+                    match value {
+                        Node::Unsigned(v) => self.raw.push((&field.name, v)),
+                        Node::Signed(v) if v >= 0 => self.raw.push((&field.name, v as u64)),
+                        _ => {}
+                    }
+                    value
+                }
+                DataType::Array { base, count } => {
+                    let available = (data.len() - offset) / base.size();
+                    let n = resolve_count(count, defs, |name| self.raw(name)).unwrap_or(available).min(available);
+                    let bytes = &data[offset..offset + n * base.size()];
+                    end = end.max(offset + bytes.len());
+                    self.array(*base, bytes)
+                }
+                DataType::Group {
+                    fields,
+                    count,
+                    element_size,
+                } => {
+                    let items = self.group(options, fields, count, *element_size, defs, data, offset);
+                    end = end.max(offset + items.len * element_size);
+                    Node::Array(items)
+                }
+                DataType::Unknown(_) => continue,
+            };
+            if field.reserved && !options.include_reserved {
+                continue;
+            }
+            let value = self.present(options, field, value);
+            self.stack.push((&field.name, value));
+        }
+        end
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn group(
+        &mut self,
+        options: DecodeOptions,
+        fields: &'s [FieldDef],
+        count: &Count,
+        size: usize,
+        scope: &[FieldDef],
+        data: &[u8],
+        base: usize,
+    ) -> Span {
+        let mark = self.stack.len();
+        if size > 0 && base < data.len() {
+            let available = (data.len() - base) / size;
+            let n = resolve_count(count, scope, |name| self.raw(name)).unwrap_or(available).min(available);
+            for i in 0..n {
+                let start = base + i * size;
+                let (raw_mark, record_mark) = (self.raw.len(), self.stack.len());
+                self.fields(options, fields, &data[start..start + size]);
+                let record = self.close(record_mark);
+                self.raw.truncate(raw_mark);
+                self.stack.push(("", Node::Record(record)));
+            }
+        }
+        self.close(mark)
+    }
+
+    /// `Decoder::present`: bitfield expansion and scaling.
+    fn present(&mut self, options: DecodeOptions, field: &'s FieldDef, value: Node) -> Node {
+        if options.expand_flags && !field.bits.is_empty() {
+            let raw = match value {
+                Node::Unsigned(v) => Some(v),
+                Node::Signed(v) => u64::try_from(v).ok(),
+                _ => None,
+            };
+            if let Some(raw) = raw {
+                let mark = self.stack.len();
+                for bit in &field.bits {
+                    if bit.reserved && !options.include_reserved {
+                        continue;
+                    }
+                    self.stack.push((&bit.name, Node::Unsigned(bit.extract(raw))));
+                }
+                return Node::Record(self.close(mark));
+            }
+        }
+        if let (true, Some(mult)) = (options.scale, field.multiplier()) {
+            if let Node::Array(items) = value {
+                for (_, item) in &mut self.nodes[items.start..items.start + items.len] {
+                    *item = scale(*item, mult);
+                }
+                return value;
+            }
+            return scale(value, mult);
+        }
+        value
+    }
+}
+
+fn scale(node: Node, mult: f64) -> Node {
+    match node {
+        Node::Unsigned(v) => Node::Float(v as f64 * mult),
+        Node::Signed(v) => Node::Float(v as f64 * mult),
+        Node::Float(v) => Node::Float(v * mult),
+        other => other,
+    }
+}
+
+impl<'s> Decoder<'s> {
+    /// [`Decoder::decode`] into `arena`, which is reset first.
+    pub fn decode_in<'a>(
+        &self,
+        arena: &'a mut Arena<'s>,
+        class_id: u8,
+        message_id: u8,
+        payload: &[u8],
+    ) -> ArenaMessage<'a, 's> {
+        arena.reset();
+        let msg = self.schema().message_for_payload(class_id, message_id, payload);
+        let def = msg.and_then(|m| m.payload_for(payload));
+        let (name, variant, fields, parsed) = match (msg, def) {
+            (Some(msg), Some((def, variant))) => {
+                let fields = arena.payload(self.options(), def, payload);
+                (Ok(msg.name.as_str()), variant.map(|v| v.name.as_str()), fields, true)
+            }
+            (msg, _) => {
+                let name = match msg {
+                    Some(msg) => Ok(msg.name.as_str()),
+                    None => {
+                        let start = arena.text.len();
+                        let _ = write!(arena.text, "UBX-{class_id:02X}-{message_id:02X}");
+                        Err(start)
+                    }
+                };
+                (name, None, Span { start: 0, len: 0 }, false)
+            }
+        };
+        let raw_start = arena.text.len();
+        if !parsed {
+            for byte in payload {
+                let _ = write!(arena.text, "{byte:02x}");
+            }
+        }
+        let arena: &'a Arena<'s> = arena;
+        ArenaMessage {
+            name: name.unwrap_or_else(|start| &arena.text[start..raw_start]),
+            class_id,
+            message_id,
+            variant,
+            payload_length: payload.len(),
+            fields: ArenaFields { arena, span: fields },
+            parsed,
+            payload_raw: (!parsed).then(|| &arena.text[raw_start..]),
+        }
+    }
+}
+
+/// A message decoded by [`Decoder::decode_in`], borrowing its arena.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaMessage<'a, 's> {
+    pub name: &'a str,
+    pub class_id: u8,
+    pub message_id: u8,
+    pub variant: Option<&'s str>,
+    pub payload_length: usize,
+    pub fields: ArenaFields<'a, 's>,
+    pub parsed: bool,
+    pub payload_raw: Option<&'a str>,
+}
+
+impl ArenaMessage<'_, '_> {
+    /// Name without the `UBX-` prefix.
+    pub fn short_name(&self) -> &str {
+        self.name.strip_prefix("UBX-").unwrap_or(self.name)
+    }
+
+    /// GPS time of week in milliseconds, for messages that carry `iTOW`.
+    pub fn itow(&self) -> Option<u32> {
+        self.fields.get("iTOW")?.as_u64()?.try_into().ok()
+    }
+
+    /// The message [`Decoder::decode`] returns.
+    pub fn to_message(&self) -> DecodedMessage {
+        DecodedMessage {
+            name: self.name.to_string(),
+            class_id: self.class_id,
+            message_id: self.message_id,
+            variant: self.variant.map(str::to_string),
+            payload_length: self.payload_length,
+            fields: self.fields.to_fields(),
+            parsed: self.parsed,
+            payload_raw: self.payload_raw.map(str::to_string),
+        }
+    }
+}
+
+/// Named values in payload order: a message's fields, an expanded bitfield
+/// or one instance of a repeated group.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaFields<'a, 's> {
+    arena: &'a Arena<'s>,
+    span: Span,
+}
+
+impl<'a, 's> ArenaFields<'a, 's> {
+    fn entries(&self) -> &'a [(&'s str, Node)] {
+        &self.arena.nodes[self.span.start..self.span.start + self.span.len]
+    }
+
+    pub fn get(&self, name: &str) -> Option<ArenaValue<'a, 's>> {
+        self.iter().find(|(n, _)| *n == name).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'s str, ArenaValue<'a, 's>)> + 'a {
+        let arena = self.arena;
+        self.entries().iter().map(move |(name, node)| (*name, ArenaValue::new(arena, *node)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.span.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.span.len == 0
+    }
+
+    pub fn to_fields(&self) -> Fields {
+        self.iter().map(|(name, value)| (name.to_string(), value.to_value())).collect()
+    }
+}
+
+/// The elements of an array or repeated group.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaArray<'a, 's> {
+    arena: &'a Arena<'s>,
+    span: Span,
+}
+
+impl<'a, 's> ArenaArray<'a, 's> {
+    pub fn get(&self, index: usize) -> Option<ArenaValue<'a, 's>> {
+        (index < self.span.len).then(|| ArenaValue::new(self.arena, self.arena.nodes[self.span.start + index].1))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ArenaValue<'a, 's>> + 'a {
+        let arena = self.arena;
+        arena.nodes[self.span.start..self.span.start + self.span.len]
+            .iter()
+            .map(move |(_, node)| ArenaValue::new(arena, *node))
+    }
+
+    pub fn len(&self) -> usize {
+        self.span.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.span.len == 0
+    }
+}
+
+/// A [`Value`] in an arena.
+#[derive(Debug, Clone, Copy)]
+pub enum ArenaValue<'a, 's> {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(&'a str),
+    Array(ArenaArray<'a, 's>),
+    Record(ArenaFields<'a, 's>),
+}
+
+impl<'a, 's> ArenaValue<'a, 's> {
+    fn new(arena: &'a Arena<'s>, node: Node) -> Self {
+        match node {
+            Node::Unsigned(v) => ArenaValue::Unsigned(v),
+            Node::Signed(v) => ArenaValue::Signed(v),
+            Node::Float(v) => ArenaValue::Float(v),
+            Node::Text(span) => ArenaValue::Text(&arena.text[span.start..span.start + span.len]),
+            Node::Array(span) => ArenaValue::Array(ArenaArray { arena, span }),
+            Node::Record(span) => ArenaValue::Record(ArenaFields { arena, span }),
+        }
+    }
+
+    /// Numeric view of the value, if it has one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ArenaValue::Unsigned(v) => Some(v as f64),
+            ArenaValue::Signed(v) => Some(v as f64),
+            ArenaValue::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Integer view of the value, if it is integral.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ArenaValue::Unsigned(v) => i64::try_from(v).ok(),
+            ArenaValue::Signed(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ArenaValue::Unsigned(v) => Some(v),
+            ArenaValue::Signed(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            ArenaValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_record(&self) -> Option<ArenaFields<'a, 's>> {
+        match *self {
+            ArenaValue::Record(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<ArenaArray<'a, 's>> {
+        match *self {
+            ArenaValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match *self {
+            ArenaValue::Unsigned(v) => Value::Unsigned(v),
+            ArenaValue::Signed(v) => Value::Signed(v),
+            ArenaValue::Float(v) => Value::Float(v),
+            ArenaValue::Text(s) => Value::Text(s.to_string()),
+            ArenaValue::Array(items) => Value::Array(items.iter().map(|v| v.to_value()).collect()),
+            ArenaValue::Record(fields) => Value::Record(fields.to_fields()),
+        }
+    }
+}
+
+// Serialized as the owned types are, so writers need not convert.
+
+impl Serialize for ArenaValue<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ArenaValue::Unsigned(v) => serializer.serialize_u64(*v),
+            ArenaValue::Signed(v) => serializer.serialize_i64(*v),
+            ArenaValue::Float(v) => serializer.serialize_f64(*v),
+            ArenaValue::Text(s) => serializer.serialize_str(s),
+            ArenaValue::Array(items) => items.serialize(serializer),
+            ArenaValue::Record(fields) => fields.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for ArenaArray<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for item in self.iter() {
+            seq.serialize_element(&item)?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for ArenaFields<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (name, value) in self.iter() {
+            map.serialize_entry(name, &value)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for ArenaMessage<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("name", self.name)?;
+        map.serialize_entry("class_id", &self.class_id)?;
+        map.serialize_entry("message_id", &self.message_id)?;
+        if let Some(variant) = self.variant {
+            map.serialize_entry("variant", variant)?;
+        }
+        map.serialize_entry("payload_length", &self.payload_length)?;
+        map.serialize_entry("fields", &self.fields)?;
+        map.serialize_entry("parsed", &self.parsed)?;
+        if let Some(raw) = self.payload_raw {
+            map.serialize_entry("payload_raw", raw)?;
+        }
+        map.end()
+    }
+}
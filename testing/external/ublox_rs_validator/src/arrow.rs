@@ -0,0 +1,603 @@
+//! Arrow columnar batches of one message type.
+//!
+//! [`RecordBatchBuilder`] collects decoded messages of one type, or the
+//! elements of one of its repeated groups, into a [`RecordBatch`]. A batch
+//! has one [`Array`] per field, in the Arrow memory layout: a validity
+//! bitmap, then little-endian values, or for strings `i32` offsets into
+//! UTF-8 data. Analytics code can use the buffers as they are.
+//! [`StreamWriter`] writes batches in the Arrow IPC streaming format, which
+//! `pyarrow.ipc.open_stream`, Polars' `read_ipc_stream` and DuckDB read
+//! without a conversion step. [`decode_record_batch`] decodes a log's
+//! frames of the chosen type on the [`batch`](crate::batch) threads. The
+//! format is written directly, so no Arrow library is needed.
+//!
+//! Columns follow [`sqlite`](crate::sqlite)'s tables and
+//! [`parquet`](crate::parquet)'s types. `offset` is the frame's byte
+//! offset; group rows add the element's `idx`. Nested values are JSON text.
+//! With [`RecordBatchBuilder::with_host_times`], a `host_time` column after
+//! `offset` holds each frame's host receive time ([`crate::host_time`]).
+
+use std::cmp::Reverse;
+use std::io::Write;
+
+use crate::batch::decode_frames;
+use crate::decode::{DecodedMessage, Decoder};
+use crate::error::{Error, Result};
+use crate::frame::frames;
+use crate::host_time::HostTimes;
+use crate::parquet::{Cell, ColumnType};
+use crate::schema::normalize_name;
+use crate::sqlite::{Column, Table};
+use crate::value::{Fields, Value};
+
+/// Messages per batch written by `ubx-arrow` by default.
+pub const DEFAULT_BATCH_ROWS: usize = 65_536;
+
+/// Metadata version V5.
+const METADATA_VERSION: i16 = 4;
+
+/// Prefix of every encapsulated IPC message.
+const CONTINUATION: [u8; 4] = [0xFF; 4];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrowField {
+    pub name: String,
+    pub data_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// One column of a batch in the Arrow layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    pub field: ArrowField,
+    len: usize,
+    null_count: usize,
+    validity: Vec<u8>,
+    values: Vec<u8>,
+    /// String columns: `len + 1` offsets into `values`.
+    offsets: Vec<i32>,
+}
+
+impl Array {
+    pub fn new(field: ArrowField) -> Self {
+        let offsets = match field.data_type {
+            ColumnType::Utf8 | ColumnType::Json => vec![0],
+            _ => Vec::new(),
+        };
+        Self {
+            field,
+            len: 0,
+            null_count: 0,
+            validity: Vec::new(),
+            values: Vec::new(),
+            offsets,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Validity bitmap, least significant bit first.
+    pub fn validity(&self) -> &[u8] {
+        &self.validity
+    }
+
+    /// The value buffer: little-endian values, with zeros under nulls, or
+    /// the UTF-8 data of a string column.
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+
+    /// Offsets of a string column into [`values`](Self::values).
+    pub fn offsets(&self) -> &[i32] {
+        &self.offsets
+    }
+
+    pub fn is_valid(&self, i: usize) -> bool {
+        i < self.len && self.validity[i / 8] >> (i % 8) & 1 == 1
+    }
+
+    /// Append a value; one that doesn't fit the column's type is null.
+    pub fn push(&mut self, cell: Option<Cell>) {
+        let width = match self.field.data_type {
+            ColumnType::Int32 => 4,
+            ColumnType::Int64 | ColumnType::Double => 8,
+            ColumnType::Utf8 | ColumnType::Json => 0,
+        };
+        let valid = match (self.field.data_type, cell) {
+            (ColumnType::Int32, Some(Cell::Int(v))) => match i32::try_from(v) {
+                Ok(v) => {
+                    self.values.extend_from_slice(&v.to_le_bytes());
+                    true
+                }
+                Err(_) => false,
+            },
+            (ColumnType::Int64, Some(Cell::Int(v))) => {
+                self.values.extend_from_slice(&v.to_le_bytes());
+                true
+            }
+            (ColumnType::Double, Some(Cell::Double(v))) => {
+                self.values.extend_from_slice(&v.to_le_bytes());
+                true
+            }
+            (ColumnType::Double, Some(Cell::Int(v))) => {
+                self.values.extend_from_slice(&(v as f64).to_le_bytes());
+                true
+            }
+            (ColumnType::Utf8 | ColumnType::Json, Some(Cell::Text(s))) => {
+                self.values.extend_from_slice(s.as_bytes());
+                true
+            }
+            _ => false,
+        };
+        if !valid {
+            self.values.resize(self.values.len() + width, 0);
+            self.null_count += 1;
+        }
+        if width == 0 {
+            self.offsets.push(self.values.len() as i32);
+        }
+        if self.len.is_multiple_of(8) {
+            self.validity.push(0);
+        }
+        if valid {
+            self.validity[self.len / 8] |= 1 << (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    pub fn i64_at(&self, i: usize) -> Option<i64> {
+        if !self.is_valid(i) {
+            return None;
+        }
+        match self.field.data_type {
+            ColumnType::Int32 => Some(i64::from(i32::from_le_bytes(self.values[i * 4..][..4].try_into().ok()?))),
+            ColumnType::Int64 => Some(i64::from_le_bytes(self.values[i * 8..][..8].try_into().ok()?)),
+            _ => None,
+        }
+    }
+
+    pub fn f64_at(&self, i: usize) -> Option<f64> {
+        match self.field.data_type {
+            ColumnType::Double if self.is_valid(i) => {
+                Some(f64::from_le_bytes(self.values[i * 8..][..8].try_into().ok()?))
+            }
+            ColumnType::Double => None,
+            _ => self.i64_at(i).map(|v| v as f64),
+        }
+    }
+
+    pub fn str_at(&self, i: usize) -> Option<&str> {
+        if !self.is_valid(i) || self.offsets.is_empty() {
+            return None;
+        }
+        let (start, end) = (self.offsets[i] as usize, self.offsets[i + 1] as usize);
+        std::str::from_utf8(&self.values[start..end]).ok()
+    }
+}
+
+/// Columns of equal length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordBatch {
+    pub columns: Vec<Array>,
+}
+
+impl RecordBatch {
+    pub fn num_rows(&self) -> usize {
+        self.columns.first().map_or(0, Array::len)
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Array> {
+        self.columns.iter().find(|c| c.field.name == name)
+    }
+
+    pub fn fields(&self) -> Vec<ArrowField> {
+        self.columns.iter().map(|c| c.field.clone()).collect()
+    }
+}
+
+/// Builds [`RecordBatch`]es from the messages of one type and variant.
+#[derive(Debug, Clone)]
+pub struct RecordBatchBuilder {
+    name: String,
+    variant: Option<String>,
+    group: Option<String>,
+    columns: Vec<Column>,
+    arrays: Vec<Array>,
+    host_times: Option<HostTimes>,
+}
+
+impl RecordBatchBuilder {
+    /// A builder for `message` (e.g. `NAV-PVT` or its variant alias
+    /// `MGA-GPS-EPH`) decoded by `decoder`: a row per message, or with
+    /// `group`, a row per element of that repeated group.
+    pub fn new(decoder: &Decoder<'_>, message: &str, group: Option<&str>) -> Result<Self> {
+        let schema = decoder.schema();
+        let wanted = normalize_name(message);
+        let def = schema
+            .message_by_name(&wanted)
+            .ok_or_else(|| Error::UnknownMessage(message.to_string()))?;
+        let variant = def.variants.iter().find(|v| wanted == format!("{}-{}", def.name, v.name));
+        let payload = match (variant, &def.payload) {
+            (Some(v), _) => &v.payload,
+            (None, Some(payload)) => payload,
+            (None, None) => {
+                let example = def.variant_aliases.first().map_or("", String::as_str);
+                return Err(Error::Schema(format!("{} has variants; name one, e.g. {example}", def.short_name())));
+            }
+        };
+        let table = Table::for_payload(def, variant.map(|v| v.name.as_str()), payload, decoder.options());
+        let mut fields = vec![ArrowField {
+            name: "offset".into(),
+            data_type: ColumnType::Int64,
+            nullable: false,
+        }];
+        let columns = match group {
+            Some(group) => {
+                let (_, child) = table
+                    .groups
+                    .into_iter()
+                    .find(|(name, _)| name == group)
+                    .ok_or_else(|| Error::Schema(format!("{} has no repeated group {group}", def.name)))?;
+                fields.push(ArrowField {
+                    name: "idx".into(),
+                    data_type: ColumnType::Int32,
+                    nullable: false,
+                });
+                child.columns
+            }
+            None => table.columns,
+        };
+        fields.extend(columns.iter().map(|c| ArrowField {
+            name: c.name.clone(),
+            data_type: ColumnType::for_column(c),
+            nullable: true,
+        }));
+        Ok(Self {
+            name: def.name.clone(),
+            variant: variant.map(|v| v.name.clone()),
+            group: group.map(str::to_string),
+            columns,
+            arrays: fields.into_iter().map(Array::new).collect(),
+            host_times: None,
+        })
+    }
+
+    /// Add a nullable `host_time` column of the frames' times in
+    /// `host_times`, looked up by the offset passed to
+    /// [`RecordBatchBuilder::push`].
+    pub fn with_host_times(mut self, host_times: HostTimes) -> Self {
+        if self.host_times.is_none() {
+            let field = ArrowField {
+                name: "host_time".into(),
+                data_type: ColumnType::Double,
+                nullable: true,
+            };
+            self.arrays.insert(1, Array::new(field));
+        }
+        self.host_times = Some(host_times);
+        self
+    }
+
+    pub fn fields(&self) -> Vec<ArrowField> {
+        self.arrays.iter().map(|a| a.field.clone()).collect()
+    }
+
+    /// Rows collected so far.
+    pub fn len(&self) -> usize {
+        self.arrays[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `msg`, found at byte `offset`, if it is of the builder's type
+    /// and variant. True when it was added.
+    pub fn push(&mut self, offset: usize, msg: &DecodedMessage) -> bool {
+        if !msg.parsed || msg.name != self.name || msg.variant != self.variant {
+            return false;
+        }
+        let host_time = self.host_times.as_ref().map(|times| times.at(offset).map(Cell::Double));
+        let offset = Cell::Int(offset as i64);
+        let records: Vec<(Option<i64>, &Fields)> = match &self.group {
+            Some(group) => msg
+                .get(group)
+                .and_then(Value::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Value::as_record)
+                .enumerate()
+                .map(|(i, r)| (Some(i as i64), r))
+                .collect(),
+            None => vec![(None, &msg.fields)],
+        };
+        for (idx, fields) in records {
+            let mut arrays = self.arrays.iter_mut();
+            arrays.next().expect("offset column").push(Some(offset.clone()));
+            if let Some(cell) = &host_time {
+                arrays.next().expect("host_time column").push(cell.clone());
+            }
+            if let Some(idx) = idx {
+                arrays.next().expect("idx column").push(Some(Cell::Int(idx)));
+            }
+            for (array, column) in arrays.zip(&self.columns) {
+                let cell = fields.get_path(&column.path).and_then(|v| Cell::from_value(v, array.field.data_type));
+                array.push(cell);
+            }
+        }
+        true
+    }
+
+    /// The rows collected so far, leaving the builder empty.
+    pub fn finish(&mut self) -> RecordBatch {
+        let empty: Vec<Array> = self.arrays.iter().map(|a| Array::new(a.field.clone())).collect();
+        RecordBatch {
+            columns: std::mem::replace(&mut self.arrays, empty),
+        }
+    }
+}
+
+/// Decode the frames of `message` in `data` on up to `threads` threads and
+/// collect them into one batch; see [`RecordBatchBuilder::new`].
+pub fn decode_record_batch(
+    decoder: &Decoder<'_>,
+    data: &[u8],
+    threads: usize,
+    message: &str,
+    group: Option<&str>,
+) -> Result<RecordBatch> {
+    let mut builder = RecordBatchBuilder::new(decoder, message, group)?;
+    let def = decoder
+        .schema()
+        .message_by_name(&builder.name)
+        .ok_or_else(|| Error::UnknownMessage(message.to_string()))?;
+    let (class, id) = (def.class_id, def.message_id);
+    let selected: Vec<_> = frames(data).flatten().filter(|f| f.class == class && f.id == id).collect();
+    for (frame, msg) in selected.iter().zip(decode_frames(decoder, &selected, threads)) {
+        builder.push(frame.offset, &msg);
+    }
+    Ok(builder.finish())
+}
+
+/// Minimal FlatBuffers objects for the IPC metadata.
+enum Node {
+    /// `(field id, value)`.
+    Table(Vec<(u16, Scalar)>),
+    Tables(Vec<Node>),
+    Str(String),
+    /// `(count, bytes)` of a vector of structs aligned to 8.
+    Structs(usize, Vec<u8>),
+}
+
+enum Scalar {
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Ref(Node),
+}
+
+impl Scalar {
+    fn size(&self) -> usize {
+        match self {
+            Scalar::U8(_) | Scalar::Bool(_) => 1,
+            Scalar::I16(_) => 2,
+            Scalar::I32(_) | Scalar::Ref(_) => 4,
+            Scalar::I64(_) => 8,
+        }
+    }
+}
+
+/// Lays FlatBuffers objects out front to back: every object sits after
+/// the ones that refer to it, so offsets are forward as the format needs.
+struct FlatBuilder {
+    buf: Vec<u8>,
+}
+
+impl FlatBuilder {
+    fn finish(root: &Node) -> Vec<u8> {
+        let mut b = Self { buf: vec![0; 4] };
+        let pos = b.node(root);
+        b.patch(0, pos as u32);
+        b.pad(8);
+        b.buf
+    }
+
+    fn pad(&mut self, align: usize) {
+        while !self.buf.len().is_multiple_of(align) {
+            self.buf.push(0);
+        }
+    }
+
+    fn patch(&mut self, at: usize, value: u32) {
+        self.buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn reference(&mut self, at: usize, node: &Node) {
+        let target = self.node(node);
+        self.patch(at, (target - at) as u32);
+    }
+
+    fn node(&mut self, node: &Node) -> usize {
+        match node {
+            Node::Table(fields) => {
+                let slots = fields.iter().map(|(id, _)| usize::from(*id) + 1).max().unwrap_or(0);
+                self.pad(2);
+                let vtable = self.buf.len();
+                self.buf.resize(vtable + 4 + 2 * slots, 0);
+                self.pad(8);
+                let table = self.buf.len();
+                self.buf.extend_from_slice(&((table - vtable) as i32).to_le_bytes());
+                let mut order: Vec<&(u16, Scalar)> = fields.iter().collect();
+                order.sort_by_key(|(_, v)| Reverse(v.size()));
+                let mut refs = Vec::new();
+                for (id, value) in order {
+                    self.pad(value.size());
+                    let at = self.buf.len();
+                    match value {
+                        Scalar::U8(v) => self.buf.push(*v),
+                        Scalar::Bool(v) => self.buf.push(u8::from(*v)),
+                        Scalar::I16(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                        Scalar::I32(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                        Scalar::I64(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                        Scalar::Ref(node) => {
+                            self.buf.extend_from_slice(&[0; 4]);
+                            refs.push((at, node));
+                        }
+                    }
+                    let slot = vtable + 4 + 2 * usize::from(*id);
+                    self.buf[slot..slot + 2].copy_from_slice(&((at - table) as u16).to_le_bytes());
+                }
+                let table_size = (self.buf.len() - table) as u16;
+                self.buf[vtable..vtable + 2].copy_from_slice(&((4 + 2 * slots) as u16).to_le_bytes());
+                self.buf[vtable + 2..vtable + 4].copy_from_slice(&table_size.to_le_bytes());
+                for (at, node) in refs {
+                    self.reference(at, node);
+                }
+                table
+            }
+            Node::Tables(items) => {
+                self.pad(4);
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                self.buf.resize(start + 4 + 4 * items.len(), 0);
+                for (i, item) in items.iter().enumerate() {
+                    self.reference(start + 4 + 4 * i, item);
+                }
+                start
+            }
+            Node::Str(s) => {
+                self.pad(4);
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                self.buf.extend_from_slice(s.as_bytes());
+                self.buf.push(0);
+                start
+            }
+            Node::Structs(count, bytes) => {
+                while !(self.buf.len() + 4).is_multiple_of(8) {
+                    self.buf.push(0);
+                }
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(*count as u32).to_le_bytes());
+                self.buf.extend_from_slice(bytes);
+                start
+            }
+        }
+    }
+}
+
+/// `Message` with a `Schema` (1) or `RecordBatch` (3) header.
+fn message(header_type: u8, header: Node, body_length: usize) -> Vec<u8> {
+    FlatBuilder::finish(&Node::Table(vec![
+        (0, Scalar::I16(METADATA_VERSION)),
+        (1, Scalar::U8(header_type)),
+        (2, Scalar::Ref(header)),
+        (3, Scalar::I64(body_length as i64)),
+    ]))
+}
+
+fn field_node(field: &ArrowField) -> Node {
+    // Type union: Int = 2, FloatingPoint = 3, Utf8 = 5.
+    let (type_id, type_table) = match field.data_type {
+        ColumnType::Int32 => (2, vec![(0, Scalar::I32(32)), (1, Scalar::Bool(true))]),
+        ColumnType::Int64 => (2, vec![(0, Scalar::I32(64)), (1, Scalar::Bool(true))]),
+        ColumnType::Double => (3, vec![(0, Scalar::I16(2))]),
+        ColumnType::Utf8 | ColumnType::Json => (5, vec![]),
+    };
+    Node::Table(vec![
+        (0, Scalar::Ref(Node::Str(field.name.clone()))),
+        (1, Scalar::Bool(field.nullable)),
+        (2, Scalar::U8(type_id)),
+        (3, Scalar::Ref(Node::Table(type_table))),
+        (5, Scalar::Ref(Node::Tables(Vec::new()))),
+    ])
+}
+
+/// Writes record batches in the Arrow IPC streaming format.
+pub struct StreamWriter<W: Write> {
+    out: W,
+    fields: Vec<ArrowField>,
+    pub batches: usize,
+    pub rows: usize,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Start a stream of batches with `fields`.
+    pub fn new(mut out: W, fields: Vec<ArrowField>) -> Result<Self> {
+        let schema = Node::Table(vec![(1, Scalar::Ref(Node::Tables(fields.iter().map(field_node).collect())))]);
+        write_message(&mut out, &message(1, schema, 0), &[])?;
+        Ok(Self {
+            out,
+            fields,
+            batches: 0,
+            rows: 0,
+        })
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if batch.fields() != self.fields {
+            return Err(Error::Encode("record batch fields differ from the stream schema".into()));
+        }
+        let mut body = Vec::new();
+        let mut nodes = Vec::new();
+        let mut buffers = Vec::new();
+        let mut add_buffer = |body: &mut Vec<u8>, data: &[u8]| {
+            buffers.extend_from_slice(&(body.len() as i64).to_le_bytes());
+            buffers.extend_from_slice(&(data.len() as i64).to_le_bytes());
+            body.extend_from_slice(data);
+            body.resize(body.len().next_multiple_of(8), 0);
+        };
+        for array in &batch.columns {
+            nodes.extend_from_slice(&(array.len() as i64).to_le_bytes());
+            nodes.extend_from_slice(&(array.null_count() as i64).to_le_bytes());
+            add_buffer(&mut body, array.validity());
+            if array.offsets().is_empty() {
+                add_buffer(&mut body, array.values());
+            } else {
+                let offsets: Vec<u8> = array.offsets().iter().flat_map(|o| o.to_le_bytes()).collect();
+                add_buffer(&mut body, &offsets);
+                add_buffer(&mut body, array.values());
+            }
+        }
+        let buffer_count = buffers.len() / 16;
+        let header = Node::Table(vec![
+            (0, Scalar::I64(batch.num_rows() as i64)),
+            (1, Scalar::Ref(Node::Structs(batch.columns.len(), nodes))),
+            (2, Scalar::Ref(Node::Structs(buffer_count, buffers))),
+        ]);
+        write_message(&mut self.out, &message(3, header, body.len()), &body)?;
+        self.batches += 1;
+        self.rows += batch.num_rows();
+        Ok(())
+    }
+
+    /// Write the end-of-stream marker and flush.
+    pub fn finish(mut self) -> Result<W> {
+        self.out.write_all(&CONTINUATION)?;
+        self.out.write_all(&0u32.to_le_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// An encapsulated message: continuation, metadata length, metadata padded
+/// to 8 bytes, then the body.
+fn write_message(out: &mut impl Write, metadata: &[u8], body: &[u8]) -> Result<()> {
+    out.write_all(&CONTINUATION)?;
+    out.write_all(&(metadata.len() as u32).to_le_bytes())?;
+    out.write_all(metadata)?;
+    out.write_all(body)?;
+    Ok(())
+}
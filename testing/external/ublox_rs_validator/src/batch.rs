@@ -0,0 +1,67 @@
+//! Parallel decoding of whole logs.
+//!
+//! Finding frames is a cheap serial scan; decoding them is where the time
+//! goes. [`decode_buffer`] frames a buffer first, then decodes the frames on
+//! scoped threads, each taking the next chunk of frames from a shared
+//! counter so slow messages do not hold up one thread, and returns the
+//! messages in frame order. The threads are the standard library's, so the
+//! library needs no thread-pool dependency.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::decode::{DecodedMessage, Decoder};
+use crate::frame::{FrameIter, FrameRef, ScanStats};
+
+/// Frames per unit of work.
+pub const CHUNK_FRAMES: usize = 512;
+
+/// `threads`, or one per CPU for 0.
+pub fn thread_count(threads: usize) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        n => n,
+    }
+}
+
+/// Decode `frames` on up to `threads` threads (0 for one per CPU), in order.
+pub fn decode_frames(decoder: &Decoder<'_>, frames: &[FrameRef<'_>], threads: usize) -> Vec<DecodedMessage> {
+    let chunks: Vec<&[FrameRef<'_>]> = frames.chunks(CHUNK_FRAMES).collect();
+    let threads = thread_count(threads).min(chunks.len());
+    if threads <= 1 {
+        return frames.iter().map(|f| decoder.decode_frame(f)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let done: Mutex<Vec<(usize, Vec<DecodedMessage>)>> = Mutex::new(Vec::with_capacity(chunks.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(chunk) = chunks.get(i) else {
+                    break;
+                };
+                let messages = chunk.iter().map(|f| decoder.decode_frame(f)).collect();
+                done.lock().unwrap_or_else(|e| e.into_inner()).push((i, messages));
+            });
+        }
+    });
+    let mut done = done.into_inner().unwrap_or_else(|e| e.into_inner());
+    done.sort_unstable_by_key(|(i, _)| *i);
+    done.into_iter().flat_map(|(_, messages)| messages).collect()
+}
+
+/// Frame `data` and decode every valid frame on up to `threads` threads,
+/// returning frames and messages in input order with the scan counters.
+pub fn decode_buffer<'a>(
+    decoder: &Decoder<'_>,
+    data: &'a [u8],
+    threads: usize,
+) -> (Vec<(FrameRef<'a>, DecodedMessage)>, ScanStats) {
+    let mut iter = FrameIter::new(data);
+    let frames: Vec<FrameRef<'a>> = iter.by_ref().flatten().collect();
+    let messages = decode_frames(decoder, &frames, threads);
+    (frames.into_iter().zip(messages).collect(), iter.stats())
+}
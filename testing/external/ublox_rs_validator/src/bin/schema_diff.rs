@@ -0,0 +1,91 @@
+//! Compare two versions of the schema set and write a changelog fragment.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::config::{default_keys_path, ConfigKeys};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::schema_diff::{
+    changelog, diff_config_keys, diff_messages, read_version, Impact, KEYS_FILE, MESSAGES_FILE,
+};
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "schema-diff", about = "Report what changed in the schema between two versions")]
+struct Args {
+    /// Old version: a git revision, or the root of another checkout
+    old: String,
+
+    /// New version, the same way [default: the working tree]
+    new: Option<String>,
+
+    /// Path to ubx_messages.json in the working tree
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to unified_config_keys.json in the working tree
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+
+    /// Compare messages only
+    #[arg(long)]
+    no_keys: bool,
+
+    /// Heading of the changelog fragment [default: "Schema changes since OLD"]
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Write the fragment to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("schema-diff: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let old_schema = Schema::from_json_str(&read_version(&args.old, &args.schema, MESSAGES_FILE)?)?;
+    let new_schema = match &args.new {
+        Some(spec) => Schema::from_json_str(&read_version(spec, &args.schema, MESSAGES_FILE)?)?,
+        None => Schema::load(&args.schema)?,
+    };
+    let mut changes = diff_messages(&old_schema, &new_schema);
+    if !args.no_keys {
+        let keys = |spec: Option<&String>| -> ubx_schema::Result<ConfigKeys> {
+            match spec {
+                Some(spec) => {
+                    let text = read_version(spec, &args.keys, KEYS_FILE)?;
+                    ConfigKeys::from_json(&serde_json::from_str(&text)?)
+                }
+                None => ConfigKeys::load(&args.keys),
+            }
+        };
+        changes.extend(diff_config_keys(&keys(Some(&args.old))?, &keys(args.new.as_ref())?));
+    }
+
+    let title = args.title.clone().unwrap_or_else(|| format!("Schema changes since {}", args.old));
+    let fragment = changelog(&title, &changes);
+    match &args.output {
+        Some(path) => fs::write(path, &fragment)?,
+        None => print!("{fragment}"),
+    }
+    let breaking = changes.iter().filter(|c| c.impact == Impact::Breaking).count();
+    eprintln!(
+        "schema-diff: {} messages -> {}; {} changes, {breaking} breaking",
+        old_schema.messages.len(),
+        new_schema.messages.len(),
+        changes.len()
+    );
+    Ok(breaking == 0)
+}
@@ -0,0 +1,80 @@
+//! Validate the message schema, enumerations and configuration key database.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::config::default_keys_path;
+use ubx_schema::lint::{lint_config_keys, lint_messages, Issue, Severity};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "schema-lint", about = "Check the schema files for inconsistencies")]
+struct Args {
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to enumerations.json (default: next to the schema)
+    #[arg(long)]
+    enumerations: Option<PathBuf>,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+
+    /// Don't print warnings
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Fail on warnings as well as errors
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("schema-lint: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn read_json(path: &Path) -> ubx_schema::Result<Json> {
+    let text = fs::read_to_string(path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let enums_path = args
+        .enumerations
+        .clone()
+        .unwrap_or_else(|| args.schema.with_file_name("enumerations.json"));
+    let enums = read_json(&enums_path)?;
+    let keys = read_json(&args.keys)?;
+
+    let mut issues: Vec<Issue> = lint_messages(&schema, Some(&enums));
+    issues.extend(lint_config_keys(&keys));
+
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+    for issue in &issues {
+        if issue.severity == Severity::Error || !args.quiet {
+            println!("{issue}");
+        }
+    }
+    eprintln!(
+        "schema-lint: {} messages, {} configuration keys: {errors} errors, {warnings} warnings",
+        schema.messages.len(),
+        keys.get("keys").and_then(Json::as_array).map_or(0, Vec::len)
+    );
+    Ok(errors == 0 && (warnings == 0 || !args.deny_warnings))
+}
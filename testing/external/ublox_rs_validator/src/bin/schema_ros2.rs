@@ -0,0 +1,91 @@
+//! Generate a ROS 2 interface package from the message schema.
+//!
+//! ```text
+//! schema-ros2 ~/ros2_ws/src/ubx_msgs --scale --expand-flags --header
+//! schema-ros2 ubx_msgs -m NAV-PVT,NAV-SAT,NAV-DOP
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::ros2::Package;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Schema};
+
+#[derive(Parser)]
+#[command(name = "schema-ros2", about = "Generate ROS 2 .msg definitions from the message schema")]
+struct Args {
+    /// Package directory to write; its msg/ directory is replaced
+    output: PathBuf,
+
+    /// Package name [default: the directory name]
+    #[arg(long)]
+    package: Option<String>,
+
+    /// Only these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Scaled fields are float64 physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Bitfield members are fields of their own
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Include reserved fields
+    #[arg(long)]
+    include_reserved: bool,
+
+    /// Start each message with a std_msgs/Header
+    #[arg(long)]
+    header: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("schema-ros2: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let name = match &args.package {
+        Some(name) => name.clone(),
+        None => args.output.file_name().map_or("ubx_msgs".into(), |n| n.to_string_lossy().into_owned()),
+    };
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        include_reserved: args.include_reserved,
+    };
+    let package = Package::generate(&schema, &name, &args.message, options, args.header);
+    let duplicates = package.duplicate_types();
+    if !duplicates.is_empty() {
+        eprintln!("schema-ros2: type names defined twice: {}", duplicates.join(", "));
+        return Ok(false);
+    }
+    if package.messages.is_empty() {
+        eprintln!("schema-ros2: no matching messages");
+        return Ok(false);
+    }
+    let files = package.write(&args.output)?;
+    eprintln!(
+        "schema-ros2: {} message types, {files} files written to {}",
+        package.messages.len(),
+        args.output.display()
+    );
+    Ok(true)
+}
@@ -0,0 +1,89 @@
+//! Generate SQL table definitions from the message schema.
+//!
+//! ```text
+//! schema-sql --dialect postgres --scale --expand-flags -o ubx.sql
+//! schema-sql -m NAV-PVT,NAV-SAT | sqlite3 drive.db
+//! ```
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sqlite::{frames_ddl, schema_tables, Dialect};
+use ubx_schema::{DecodeOptions, Schema};
+
+#[derive(Parser)]
+#[command(name = "schema-sql", about = "Generate CREATE TABLE statements from the message schema")]
+struct Args {
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// SQL dialect: sqlite or postgres
+    #[arg(short, long, default_value_t = Dialect::Sqlite)]
+    dialect: Dialect,
+
+    /// Only these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Scaled fields are floating-point physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// One column per bitfield member
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Include reserved fields
+    #[arg(long)]
+    include_reserved: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("schema-sql: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        include_reserved: args.include_reserved,
+    };
+    let tables = schema_tables(&schema, &args.message, options);
+    if tables.is_empty() {
+        eprintln!("schema-sql: no matching messages");
+        return Ok(false);
+    }
+    let mut sql = frames_ddl(args.dialect).to_string();
+    for table in &tables {
+        sql += &table.ddl_for(args.dialect);
+    }
+    match &args.output {
+        Some(path) => fs::write(path, &sql)?,
+        None => io::stdout().lock().write_all(sql.as_bytes())?,
+    }
+    let children: usize = tables.iter().map(|t| t.groups.len()).sum();
+    eprintln!(
+        "schema-sql: {} message tables, {children} group tables ({})",
+        tables.len(),
+        args.dialect
+    );
+    Ok(true)
+}
@@ -0,0 +1,155 @@
+//! Release versions of the schema set and query their compatibility.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand};
+use ubx_schema::config::{default_keys_path, ConfigKeys};
+use ubx_schema::registry::{default_manifest_path, Manifest, Release, Stability, Version};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::schema_diff::{changelog, diff_config_keys, diff_messages, read_version, KEYS_FILE, MESSAGES_FILE};
+use ubx_schema::time::unix_to_datetime;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "schema-version", about = "Version the schema set and check compatibility between versions")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Version manifest
+    #[arg(long, global = true, default_value_os_t = default_manifest_path())]
+    manifest: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, global = true, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, global = true, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List definitions changed since the current release; exits 1 if any
+    Check,
+    /// Record the working tree as a new release and print its changelog
+    Release {
+        /// The new version; must be at least the bump its changes require
+        version: Version,
+
+        /// Git revision of the current release [default: schema-vCURRENT]
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Release date [default: today]
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Show what would be recorded without writing the manifest
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// Whether a message or key definition held between two releases; exits 1 if not
+    Stable {
+        /// Message or configuration key name
+        name: String,
+        from: Version,
+        to: Version,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("schema-version: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    match &args.command {
+        Command::Check => {
+            let manifest = Manifest::load(&args.manifest)?;
+            let unreleased = manifest.unreleased(&Schema::load(&args.schema)?, Some(&ConfigKeys::load(&args.keys)?));
+            for item in &unreleased {
+                println!("{item}");
+            }
+            eprintln!("schema-version: {} definitions changed since {}", unreleased.len(), manifest.current);
+            Ok(unreleased.is_empty())
+        }
+        Command::Release {
+            version,
+            since,
+            date,
+            dry_run,
+        } => {
+            let schema = Schema::load(&args.schema)?;
+            let keys = ConfigKeys::load(&args.keys)?;
+            let date = date.clone().unwrap_or_else(today);
+            let mut manifest = if args.manifest.exists() {
+                Manifest::load(&args.manifest)?
+            } else {
+                Manifest {
+                    current: *version,
+                    releases: Vec::new(),
+                }
+            };
+
+            let mut changes = Vec::new();
+            let mut title = format!("Schema {version}");
+            if let Some(previous) = manifest.latest() {
+                let revision = since.clone().unwrap_or_else(|| format!("schema-v{}", previous.version));
+                let old_schema = Schema::from_json_str(&read_version(&revision, &args.schema, MESSAGES_FILE)?)?;
+                let old_keys = read_version(&revision, &args.keys, KEYS_FILE)?;
+                let old_keys = ConfigKeys::from_json(&serde_json::from_str(&old_keys)?)?;
+                changes = diff_messages(&old_schema, &schema);
+                changes.extend(diff_config_keys(&old_keys, &keys));
+                title = format!("Schema {version} (since {})", previous.version);
+            }
+            let release = Release::new(*version, &date, &schema, Some(&keys), manifest.latest(), &changes);
+            let counts = (release.breaking.len(), release.added.len(), release.changed.len());
+            manifest.push(release)?;
+
+            print!("{}", changelog(&title, &changes));
+            if !dry_run {
+                manifest.save(&args.manifest)?;
+            }
+            eprintln!(
+                "schema-version: {version}: {} breaking, {} added, {} changed{}",
+                counts.0,
+                counts.1,
+                counts.2,
+                if *dry_run { " (not written)" } else { "" }
+            );
+            Ok(true)
+        }
+        Command::Stable { name, from, to } => {
+            let manifest = Manifest::load(&args.manifest)?;
+            let stability = manifest.stability(name, *from, *to)?;
+            match &stability {
+                Stability::Unchanged => println!("{name}: unchanged from {from} to {to}"),
+                Stability::Compatible { changed_in } => {
+                    let versions: Vec<String> = changed_in.iter().map(ToString::to_string).collect();
+                    println!("{name}: compatible from {from} to {to}, changed in {}", versions.join(", "));
+                }
+                Stability::Breaking { since } => println!("{name}: broken in {since}"),
+                Stability::Added { in_version } => println!("{name}: added in {in_version}"),
+                Stability::Removed => println!("{name}: removed by {to}"),
+            }
+            Ok(stability.is_stable())
+        }
+    }
+}
+
+fn today() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+    let t = unix_to_datetime(now);
+    format!("{:04}-{:02}-{:02}", t.year, t.month, t.day)
+}
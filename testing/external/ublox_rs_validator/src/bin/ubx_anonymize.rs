@@ -0,0 +1,99 @@
+//! Move every position in a .ubx log so the capture can be shared without
+//! revealing where it was recorded.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use ubx_schema::anonymize::{Anonymizer, Offset};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-anonymize", about = "Offset or jitter the positions in a UBX log")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Constant shift as LAT,LON[,HEIGHT] in degrees and metres, e.g. -12.5,40.25
+    #[arg(long, value_parser = parse_offset, allow_hyphen_values = true)]
+    offset: Option<Offset>,
+
+    /// Random displacement of up to this many metres per axis, per epoch
+    #[arg(long, default_value_t = 0.0)]
+    jitter: f64,
+
+    /// Seed for --jitter (default: from the clock)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn parse_offset(s: &str) -> Result<Offset, String> {
+    let parts: Vec<f64> = s
+        .split(',')
+        .map(|p| p.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid offset '{s}', expected LAT,LON[,HEIGHT]"))?;
+    match parts[..] {
+        [lat, lon] => Ok(Offset { lat, lon, height: 0.0 }),
+        [lat, lon, height] => Ok(Offset { lat, lon, height }),
+        _ => Err(format!("invalid offset '{s}', expected LAT,LON[,HEIGHT]")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-anonymize: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    if args.offset.is_none() && args.jitter <= 0.0 {
+        return Err(Error::Config("give --offset, --jitter or both".into()));
+    }
+    let schema = Schema::load(&args.schema)?;
+    let input = LogFile::open(&args.input)?;
+
+    let seed = args.seed.unwrap_or_else(|| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        now.as_nanos() as u64
+    });
+    let anonymizer = Anonymizer::new(&schema, args.offset.unwrap_or_default()).jitter(args.jitter, seed);
+    let (output, stats) = anonymizer.anonymize(&input);
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    out.write_all(&output)?;
+    out.flush()?;
+
+    let rewritten: Vec<String> = stats.rewritten.iter().map(|(name, n)| format!("{name} {n}")).collect();
+    let total: usize = stats.rewritten.values().sum();
+    eprintln!(
+        "ubx-anonymize: rewrote {total} of {} frames ({})",
+        stats.frames,
+        if rewritten.is_empty() { "none".to_string() } else { rewritten.join(", ") }
+    );
+    if stats.dropped_bytes > 0 {
+        eprintln!("ubx-anonymize: left out {} bytes of NMEA and other non-UBX data", stats.dropped_bytes);
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Decode one message type of a UBX log to an Arrow IPC stream.
+//!
+//! ```text
+//! ubx-arrow drive.ubx -m NAV-PVT --scale                  # drive_nav_pvt.arrows
+//! ubx-arrow drive.ubx -m RXM-RAWX --group repeated_group -o rawx.arrows -j 4
+//! python -c "import pyarrow as pa; print(pa.ipc.open_stream('rawx.arrows').read_all())"
+//! ```
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::arrow::{RecordBatchBuilder, StreamWriter, DEFAULT_BATCH_ROWS};
+use ubx_schema::batch::decode_frames;
+use ubx_schema::frame::frames;
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sqlite::table_name;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-arrow", about = "Decode one UBX message type to an Arrow IPC stream")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Message type, e.g. NAV-PVT, or a variant such as MGA-GPS-EPH
+    #[arg(short, long)]
+    message: String,
+
+    /// Write a row per element of this repeated group, e.g. measurements
+    #[arg(short, long)]
+    group: Option<String>,
+
+    /// Output file [default: the input name with the message appended and an .arrows extension]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Messages decoded per record batch
+    #[arg(long, default_value_t = DEFAULT_BATCH_ROWS)]
+    batch_rows: usize,
+
+    /// Decode on this many threads (0: one per CPU)
+    #[arg(short, long, default_value_t = 0)]
+    jobs: usize,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into one column per member
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// ubx-capture --timestamps CSV of the input; adds a host_time column
+    #[arg(short, long)]
+    timestamps: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-arrow: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut builder = RecordBatchBuilder::new(&decoder, &args.message, args.group.as_deref())?;
+    if let Some(path) = &args.timestamps {
+        builder = builder.with_host_times(HostTimes::load(path)?);
+    }
+    let def = schema
+        .message_by_name(&args.message)
+        .ok_or_else(|| ubx_schema::Error::UnknownMessage(args.message.clone()))?;
+    let output = args.output.clone().unwrap_or_else(|| {
+        let stem = args.input.file_stem().map_or("ubx".into(), |s| s.to_string_lossy().into_owned());
+        args.input.with_file_name(format!("{stem}_{}.arrows", table_name(&args.message)))
+    });
+    let input = LogFile::open(&args.input)?;
+
+    let selected: Vec<_> = frames(&input)
+        .flatten()
+        .filter(|f| f.class == def.class_id && f.id == def.message_id)
+        .collect();
+    let mut writer = StreamWriter::new(BufWriter::new(File::create(&output)?), builder.fields())?;
+    let mut messages = 0;
+    for chunk in selected.chunks(args.batch_rows.max(1)) {
+        for (frame, msg) in chunk.iter().zip(decode_frames(&decoder, chunk, args.jobs)) {
+            messages += usize::from(builder.push(frame.offset, &msg));
+        }
+        if !builder.is_empty() {
+            writer.write(&builder.finish())?;
+        }
+    }
+    let (batches, rows) = (writer.batches, writer.rows);
+    writer.finish()?;
+    eprintln!(
+        "ubx-arrow: {messages} messages, {rows} rows in {batches} batches to {}",
+        output.display()
+    );
+    Ok(())
+}
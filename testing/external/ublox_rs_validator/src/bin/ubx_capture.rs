@@ -0,0 +1,208 @@
+//! Log raw UBX from a serial port while printing decoded summaries.
+
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use ubx_schema::completeness::{default_index_path, MessageIndex};
+use ubx_schema::frame::{FillerFilter, Frame, FrameParser, Transport};
+use ubx_schema::profile::AutoProfile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Decoder, Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-capture", about = "Capture UBX from a serial port")]
+struct Args {
+    /// Serial port, e.g. /dev/ttyACM0 or COM3
+    #[arg(required_unless_present = "list")]
+    port: Option<String>,
+
+    /// Raw .ubx output file
+    #[arg(short, long, required_unless_present = "list")]
+    output: Option<PathBuf>,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Write a CSV of host receive times (offset,host_time,message)
+    #[arg(long)]
+    timestamps: Option<PathBuf>,
+
+    /// Set a message output rate on the current port before capturing, e.g.
+    /// NAV-PVT=1; may be repeated or comma separated
+    #[arg(long, value_delimiter = ',', value_parser = parse_rate)]
+    rate: Vec<(String, u8)>,
+
+    /// Stop after this many seconds
+    #[arg(long)]
+    duration: Option<f64>,
+
+    /// Stop after this many frames
+    #[arg(long)]
+    count: Option<usize>,
+
+    /// Don't print decoded summaries
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Poll MON-VER and MON-GNSS first, and decode with the profile of the
+    /// receiver's generation once it answers
+    #[arg(long)]
+    auto_profile: bool,
+
+    /// Path to message_index.json, which maps generations to manuals (for --auto-profile)
+    #[arg(long, default_value_os_t = default_index_path())]
+    message_index: PathBuf,
+
+    /// Interface behind the port (uart, ddc or spi); I2C and SPI bridges
+    /// pad idle reads with 0xFF, which is dropped from the output file
+    #[arg(long, default_value_t = Transport::Uart)]
+    transport: Transport,
+
+    /// List available serial ports and exit
+    #[arg(long)]
+    list: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn parse_rate(s: &str) -> Result<(String, u8), String> {
+    let (name, rate) = s.split_once('=').ok_or("expected MESSAGE=RATE")?;
+    let rate = rate.parse().map_err(|_| format!("invalid rate: {rate}"))?;
+    Ok((name.to_string(), rate))
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-capture: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    if args.list {
+        for port in serialport::available_ports().map_err(io::Error::from)? {
+            println!("{}", port.port_name);
+        }
+        return Ok(());
+    }
+    let (Some(port_name), Some(output)) = (&args.port, &args.output) else {
+        unreachable!("clap enforces port and output");
+    };
+
+    let schema = Schema::load(&args.schema)?;
+
+    // CFG-MSG with a 3-byte payload sets the rate on the port it arrives on.
+    let mut rate_frames = Vec::new();
+    for (name, rate) in &args.rate {
+        let msg = schema
+            .message_by_name(name)
+            .ok_or_else(|| Error::UnknownMessage(name.clone()))?;
+        rate_frames.push(Frame::new(0x06, 0x01, vec![msg.class_id, msg.message_id, *rate]));
+    }
+    // The index is only consulted once a MON-VER comes back from the auto-profile polls.
+    let mut index = MessageIndex::default();
+    if args.auto_profile {
+        rate_frames.push(Frame::new(0x0A, 0x04, Vec::new()));
+        rate_frames.push(Frame::new(0x0A, 0x28, Vec::new()));
+        index = MessageIndex::load(&args.message_index)?;
+    }
+    let mut schema = AutoProfile::new(schema, index);
+
+    let mut port = serialport::new(port_name, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    for frame in &rate_frames {
+        port.write_all(&frame.to_bytes())?;
+    }
+    port.flush()?;
+
+    let mut out = BufWriter::new(File::create(output)?);
+    let mut timestamps = match &args.timestamps {
+        Some(path) => {
+            let mut w = BufWriter::new(File::create(path)?);
+            writeln!(w, "offset,host_time,message")?;
+            Some(w)
+        }
+        None => None,
+    };
+
+    let started = Instant::now();
+    let deadline = args.duration.map(|s| started + Duration::from_secs_f64(s));
+    let mut parser = FrameParser::new();
+    let mut filler = FillerFilter::new(args.transport);
+    let mut buf = [0u8; 4096];
+    let mut total = 0usize;
+    loop {
+        if deadline.is_some_and(|d| Instant::now() >= d) || args.count.is_some_and(|n| total >= n) {
+            break;
+        }
+        let n = match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let data = filler.strip(&buf[..n]);
+        if data.is_empty() {
+            continue;
+        }
+        out.write_all(&data)?;
+        out.flush()?;
+        let host_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        parser.push(&data);
+        while let Some(result) = parser.next_frame() {
+            let Ok((offset, frame)) = result else {
+                continue;
+            };
+            if args.count.is_some_and(|n| total >= n) {
+                break;
+            }
+            total += 1;
+            let msg = Decoder::new(schema.schema()).decode(frame.class, frame.id, &frame.payload);
+            if args.auto_profile && schema.observe(&msg) {
+                if let (Some(receiver), Some(profile)) = (schema.receiver(), schema.profile()) {
+                    eprintln!("ubx-capture: {receiver}: using the {} profile", profile.name);
+                }
+            }
+            if let Some(w) = timestamps.as_mut() {
+                writeln!(w, "{offset},{host_time:.6},{}", msg.name)?;
+            }
+            if !args.quiet {
+                println!("{:10.3} {}", started.elapsed().as_secs_f64(), msg.summary(4));
+            }
+        }
+        if let Some(w) = timestamps.as_mut() {
+            w.flush()?;
+        }
+    }
+
+    let stats = parser.stats();
+    eprintln!(
+        "ubx-capture: {} frames, {} checksum errors, {} bytes skipped",
+        stats.frames, stats.checksum_errors, stats.skipped_bytes
+    );
+    if args.transport.has_filler() {
+        let t = filler.stats();
+        eprintln!(
+            "ubx-capture: {}: {} reads ({} idle), {} filler bytes, longest run {}",
+            args.transport, t.reads, t.idle_reads, t.filler_bytes, t.longest_filler_run
+        );
+    }
+    Ok(())
+}
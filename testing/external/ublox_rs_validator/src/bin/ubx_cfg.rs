@@ -0,0 +1,158 @@
+//! Apply a declarative TOML/YAML configuration to a receiver via CFG-VALSET.
+//!
+//! ```toml
+//! layers = ["ram", "flash"]
+//!
+//! [keys]
+//! CFG-RATE-MEAS = 100
+//! CFG-NAVSPG-DYNMODEL = "AUTOMOT"
+//!
+//! [keys.CFG-MSGOUT]
+//! UBX_NAV_PVT_USB = 1
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::config::{collect_keys, default_keys_path, valset_frames, ConfigKeys, Layers};
+use ubx_schema::frame::FrameParser;
+use ubx_schema::transact::{send_with_ack, Outcome, RetryPolicy};
+use ubx_schema::Error;
+
+#[derive(Parser)]
+#[command(name = "ubx-cfg", about = "Apply a TOML/YAML key configuration with CFG-VALSET")]
+struct Args {
+    /// Configuration file (.toml, .yaml/.yml or .json)
+    config: PathBuf,
+
+    /// Serial port to send the configuration to
+    #[arg(short, long, required_unless_present_any = ["output", "dry_run"])]
+    port: Option<String>,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Write the VALSET frames to a .ubx file instead of sending them
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Print the VALSET frames as hex and exit
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Layers to write, overriding the file, e.g. ram,bbr,flash
+    #[arg(long)]
+    layers: Option<String>,
+
+    /// Values of keys with a scale factor are physical quantities
+    #[arg(long)]
+    scaled: bool,
+
+    /// Seconds to wait for each ACK
+    #[arg(long, default_value_t = 1.0)]
+    timeout: f64,
+
+    /// Times to resend a VALSET that was not acknowledged or was rejected
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-cfg: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn load_document(path: &Path) -> ubx_schema::Result<Json> {
+    let text = fs::read_to_string(path)?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match ext.as_str() {
+        "toml" => toml::from_str(&text).map_err(|e| Error::Config(format!("{}: {e}", path.display()))),
+        "yaml" | "yml" => serde_yaml::from_str(&text).map_err(|e| Error::Config(format!("{}: {e}", path.display()))),
+        _ => Ok(serde_json::from_str(&text)?),
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let db = ConfigKeys::load(&args.keys)?;
+    let doc = load_document(&args.config)?;
+    let keys = doc
+        .get("keys")
+        .and_then(Json::as_object)
+        .ok_or_else(|| Error::Config("configuration needs a 'keys' table".into()))?;
+    let values = collect_keys(&db, keys, args.scaled)?;
+    if values.is_empty() {
+        return Err(Error::Config("no keys to set".into()));
+    }
+
+    let layers = match (&args.layers, doc.get("layers")) {
+        (Some(s), _) => Layers::parse(s)?,
+        (None, Some(Json::String(s))) => Layers::parse(s)?,
+        (None, Some(Json::Array(names))) => Layers::from_names(names.iter().filter_map(Json::as_str))?,
+        (None, Some(other)) => return Err(Error::Config(format!("invalid layers: {other}"))),
+        (None, None) => Layers::default(),
+    };
+    let frames = valset_frames(&values, layers);
+
+    if args.dry_run {
+        for kv in &values {
+            println!("{:<40} 0x{:08X} {}", kv.name, kv.key_id, hex::encode(&kv.value));
+        }
+        for frame in &frames {
+            println!("{}", hex::encode(frame.try_to_bytes()?));
+        }
+        return Ok(());
+    }
+    if let Some(path) = &args.output {
+        let mut out = BufWriter::new(File::create(path)?);
+        for frame in &frames {
+            out.write_all(&frame.try_to_bytes()?)?;
+        }
+        out.flush()?;
+        eprintln!("ubx-cfg: wrote {} keys in {} VALSET frames", values.len(), frames.len());
+        return Ok(());
+    }
+
+    let Some(port_name) = &args.port else {
+        unreachable!("clap enforces port");
+    };
+    let mut port = serialport::new(port_name, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    let mut parser = FrameParser::new();
+    let policy = RetryPolicy {
+        timeout: Duration::from_secs_f64(args.timeout),
+        retries: args.retries,
+        ..RetryPolicy::default()
+    };
+    for (i, frame) in frames.iter().enumerate() {
+        let report = send_with_ack(&mut *port, &mut parser, frame, &policy)?;
+        match report.outcome {
+            Outcome::Ack => {}
+            Outcome::Nak => return Err(Error::Config(format!("VALSET {}/{} rejected ({report})", i + 1, frames.len()))),
+            Outcome::NoReply => return Err(Error::Config(format!("VALSET {}/{}: {report}", i + 1, frames.len()))),
+        }
+    }
+    eprintln!(
+        "ubx-cfg: {} keys applied in {} VALSET frames, all acknowledged",
+        values.len(),
+        frames.len()
+    );
+    Ok(())
+}
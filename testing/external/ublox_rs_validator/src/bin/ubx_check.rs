@@ -0,0 +1,131 @@
+//! Check the decoded values of a .ubx log for plausibility.
+//!
+//! Prints one line per warning, prefixed with the frame offset, one per
+//! inconsistent epoch and one per implausible move between NAV-PVT
+//! solutions; the summary keeps structural problems (checksum
+//! errors, messages the schema does not define) apart from the semantic
+//! warnings. Exits 1 when there are warnings.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::epoch::{EpochChecker, Tolerances};
+use ubx_schema::frame::frames;
+use ubx_schema::kinematic::{Bounds, KinematicChecker};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::semantic::{Validator, Warning};
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-check", about = "Check decoded UBX values against schema and GNSS plausibility rules")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// Report NAV-PVT and NAV-HPPOSLLH, or LLH and ECEF, positions further apart than this, metres
+    #[arg(long, default_value_t = Tolerances::default().position)]
+    position_tolerance: f64,
+
+    /// Largest plausible ground speed, m/s
+    #[arg(long, default_value_t = Bounds::default().max_speed)]
+    max_speed: f64,
+
+    /// Largest plausible horizontal acceleration, m/s²
+    #[arg(long, default_value_t = Bounds::default().max_acceleration)]
+    max_acceleration: f64,
+
+    /// Only print the summary
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Path to enumerations.json (default: next to the schema)
+    #[arg(long)]
+    enumerations: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("ubx-check: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let enums_path = args
+        .enumerations
+        .clone()
+        .unwrap_or_else(|| args.schema.with_file_name("enumerations.json"));
+    let enums: Json = serde_json::from_str(&fs::read_to_string(enums_path)?)?;
+    let validator = Validator::new(&schema, Some(&enums));
+    let mut epochs = EpochChecker::new(Tolerances {
+        position: args.position_tolerance,
+        ..Tolerances::default()
+    });
+    let mut kinematic = KinematicChecker::new(Bounds {
+        max_speed: args.max_speed,
+        max_acceleration: args.max_acceleration,
+        ..Bounds::default()
+    });
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: false,
+        },
+    );
+
+    let input = LogFile::open(&args.input)?;
+    let mut iter = frames(&input);
+    let mut unknown = 0;
+    let mut by_rule = BTreeMap::new();
+    let mut report = |warning: &Warning, prefix: String| {
+        if !args.quiet {
+            println!("{prefix}{warning}");
+        }
+        *by_rule.entry(warning.rule).or_insert(0) += 1;
+    };
+    for frame in iter.by_ref().flatten() {
+        let msg = decoder.decode_frame(&frame);
+        if !msg.parsed {
+            unknown += 1;
+            continue;
+        }
+        for warning in validator.check(&msg) {
+            report(&warning, format!("{}: {}: ", frame.offset, msg.short_name()));
+        }
+        for warning in epochs.push(&msg).into_iter().chain(kinematic.push(&msg)) {
+            report(&warning, String::new());
+        }
+    }
+    for warning in epochs.finish() {
+        report(&warning, String::new());
+    }
+    let stats = iter.stats();
+
+    let warnings: usize = by_rule.values().sum();
+    let rules: Vec<String> = by_rule.iter().map(|(rule, n)| format!("{n} {}", rule.label())).collect();
+    let detail = if rules.is_empty() { String::new() } else { format!(" ({})", rules.join(", ")) };
+    eprintln!(
+        "ubx-check: {} frames, {} epochs: {} checksum errors, {unknown} unknown messages; {warnings} warnings{detail}",
+        stats.frames,
+        epochs.epochs(),
+        stats.checksum_errors
+    );
+    Ok(warnings == 0)
+}
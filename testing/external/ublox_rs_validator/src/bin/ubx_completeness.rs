@@ -0,0 +1,86 @@
+//! Report messages documented by u-blox that the schema does not define.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::completeness::{check, default_index_path, MessageIndex};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-completeness", about = "Check the schema against the official message index")]
+struct Args {
+    /// Only these product generations, e.g. F9,M10
+    #[arg(short, long, value_delimiter = ',')]
+    generation: Vec<String>,
+
+    /// Also list schema messages no manual documents
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Path to message_index.json
+    #[arg(long, default_value_os_t = default_index_path())]
+    index: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("ubx-completeness: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let index = MessageIndex::load(&args.index)?;
+    let mut report = check(&schema, &index);
+    if !args.generation.is_empty() {
+        report
+            .generations
+            .retain(|g| args.generation.iter().any(|name| name.eq_ignore_ascii_case(&g.name)));
+    }
+
+    for generation in &report.generations {
+        println!(
+            "{}: {}/{} documented messages defined ({} manual{})",
+            generation.name,
+            generation.documented - generation.missing.len(),
+            generation.documented,
+            generation.manuals,
+            if generation.manuals == 1 { "" } else { "s" }
+        );
+        for entry in &generation.missing {
+            println!("  missing {} (0x{:02X} 0x{:02X})", entry.name, entry.class_id, entry.message_id);
+        }
+    }
+    for m in &report.id_mismatches {
+        println!(
+            "{}: documented as 0x{:02X} 0x{:02X}, schema has 0x{:02X} 0x{:02X}",
+            m.name, m.documented.0, m.documented.1, m.schema.0, m.schema.1
+        );
+    }
+    if args.verbose {
+        for name in &report.undocumented {
+            println!("not in any indexed manual: {name}");
+        }
+    }
+
+    let missing: usize = report.generations.iter().map(|g| g.missing.len()).sum();
+    eprintln!(
+        "ubx-completeness: {} documented messages, {} schema messages: {missing} missing, {} ID mismatches",
+        index.messages.len(),
+        schema.messages.len(),
+        report.id_mismatches.len()
+    );
+    Ok(missing == 0 && report.id_mismatches.is_empty())
+}
@@ -0,0 +1,125 @@
+//! Check the golden test-vector corpus against the schema decoder and the
+//! ublox crate, or record a new vector from a frame.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use ubx_schema::conformance::{corpus_suite, write_reports};
+use ubx_schema::corpus::{default_corpus_path, load_corpus, Vector};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Decoder, Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-corpus", about = "Run or extend the golden UBX test-vector corpus")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Corpus directory
+    #[arg(long, global = true, default_value_os_t = default_corpus_path())]
+    corpus: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, global = true, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check every vector; exits non-zero if any backend disagrees
+    Check {
+        /// Also list the vectors that pass
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Also write the results as JUnit XML to this file
+        #[arg(long)]
+        junit: Option<PathBuf>,
+
+        /// Also write per-message pass/fail counts as JSON to this file
+        #[arg(long)]
+        summary: Option<PathBuf>,
+    },
+    /// Record a vector from a frame, expecting today's decode (review it before committing)
+    Add {
+        /// The complete frame as hex
+        frame: String,
+
+        /// What the vector covers
+        #[arg(short, long)]
+        description: String,
+
+        /// File name within the message's directory, without .json
+        #[arg(short, long, default_value = "default")]
+        name: String,
+
+        /// Replace an existing vector
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-corpus: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::new(&schema);
+    match &args.command {
+        Command::Check {
+            verbose,
+            junit,
+            summary,
+        } => {
+            let vectors = load_corpus(&args.corpus)?;
+            let suite = corpus_suite(&decoder, &args.corpus, &vectors);
+            for case in &suite.cases {
+                if case.failures.is_empty() {
+                    if *verbose {
+                        println!("ok    {}", case.name);
+                    }
+                    continue;
+                }
+                println!("FAIL  {}", case.name);
+                for failure in &case.failures {
+                    println!("      {failure}");
+                }
+            }
+            let failed = suite.failed();
+            eprintln!("ubx-corpus: {} vectors, {failed} failed", vectors.len());
+            write_reports(junit.as_deref(), summary.as_deref(), &schema.schema_version, &[suite])?;
+            Ok(failed == 0)
+        }
+        Command::Add {
+            frame,
+            description,
+            name,
+            force,
+        } => {
+            let bytes = hex::decode(frame.replace(char::is_whitespace, ""))
+                .map_err(|e| Error::Corpus(format!("invalid frame hex: {e}")))?;
+            let vector = Vector::record(&decoder, &bytes, description)?;
+            let short = vector.expected.name.strip_prefix("UBX-").unwrap_or(&vector.expected.name);
+            let dir: &Path = &args.corpus.join(short);
+            let path = dir.join(format!("{name}.json"));
+            if path.exists() && !force {
+                return Err(Error::Corpus(format!("{} exists; use --force to replace it", path.display())));
+            }
+            fs::create_dir_all(dir)?;
+            fs::write(&path, serde_json::to_string_pretty(&vector)? + "\n")?;
+            eprintln!("ubx-corpus: wrote {}; check the expected fields by hand", path.display());
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Coverage statistics per protocol version and product generation.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::completeness::{default_index_path, MessageIndex};
+use ubx_schema::coverage::{coverage, default_metadata_path, load_metadata, render_table, Coverage};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-coverage", about = "Count defined messages and fields per protocol version")]
+struct Args {
+    /// Write the statistics as JSON to a file, or `-` for stdout instead of the tables
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Earlier `--json` output to show changes against
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Path to message_index.json
+    #[arg(long, default_value_os_t = default_index_path())]
+    index: PathBuf,
+
+    /// Path to manual_metadata.json
+    #[arg(long, default_value_os_t = default_metadata_path())]
+    metadata: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-coverage: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let index = MessageIndex::load(&args.index)?;
+    let manuals = load_metadata(&args.metadata)?;
+    let report = coverage(&schema, &index, &manuals);
+
+    let baseline: Option<Coverage> = match &args.baseline {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+            Some(serde_json::from_str(&text)?)
+        }
+        None => None,
+    };
+
+    let json = serde_json::to_string_pretty(&report)?;
+    match &args.json {
+        Some(path) if path.to_str() == Some("-") => {
+            println!("{json}");
+            return Ok(());
+        }
+        Some(path) => fs::write(path, json + "\n")?,
+        None => {}
+    }
+    print!("{}", render_table(&report, baseline.as_ref()));
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Thin a .ubx log to every Nth epoch per message type, keeping
+//! configuration and version messages, to make small test fixtures.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::decimate::Decimation;
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-decimate", about = "Keep every Nth epoch of each UBX message type")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Keep one epoch in this many
+    #[arg(short = 'n', long, default_value_t = 10)]
+    every: u32,
+
+    /// Factor for one message type, e.g. NAV-SAT=60 (0 drops it); may be repeated or comma separated
+    #[arg(short = 't', long = "type", value_delimiter = ',', value_parser = parse_factor)]
+    types: Vec<(String, u32)>,
+
+    /// Also keep every frame of these classes or messages, e.g. ESF,MON-HW
+    #[arg(short, long, value_delimiter = ',')]
+    keep: Vec<String>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn parse_factor(s: &str) -> Result<(String, u32), String> {
+    let (name, every) = s.split_once('=').ok_or("expected MESSAGE=N")?;
+    let every = every.parse().map_err(|_| format!("invalid factor: {every}"))?;
+    Ok((name.to_string(), every))
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-decimate: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let input = LogFile::open(&args.input)?;
+
+    let mut decimation = Decimation::new(args.every).keep(&args.keep);
+    for (name, every) in &args.types {
+        decimation = decimation.every_for(name, *every);
+    }
+    let kept = decimation.apply(&schema, &input);
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut bytes = 0;
+    for frame in &kept {
+        out.write_all(frame.raw)?;
+        bytes += frame.raw.len();
+    }
+    out.flush()?;
+
+    let total = frames(&input).flatten().count();
+    eprintln!(
+        "ubx-decimate: kept {} of {total} frames ({bytes} of {} bytes)",
+        kept.len(),
+        input.len()
+    );
+    Ok(())
+}
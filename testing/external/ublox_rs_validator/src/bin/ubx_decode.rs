@@ -0,0 +1,199 @@
+//! Decode a .ubx log into JSON, NDJSON, CSV or gpsd reports using the schema.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::batch::decode_frames;
+use ubx_schema::completeness::{default_index_path, MessageIndex};
+use ubx_schema::error::FrameError;
+use ubx_schema::frame::{FrameIter, FrameRef, LengthPolicy, OversizeAction, MAX_PAYLOAD_LEN};
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::output::{CsvField, Format, MessageWriter};
+use ubx_schema::profile::Profile;
+use ubx_schema::schema::{default_schema_path, normalize_name};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-decode", about = "Decode UBX logs using the message schema")]
+struct Args {
+    /// Input .ubx file (stdin if omitted or '-')
+    input: Option<PathBuf>,
+
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format: json, ndjson, csv, gpsd (TPV/SKY reports) or influx (line protocol);
+    /// gpsd and influx imply --scale --expand-flags
+    #[arg(short, long, default_value = "json")]
+    format: Format,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into named members
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Include reserved fields
+    #[arg(long)]
+    include_reserved: bool,
+
+    /// CSV columns as MESSAGE.field (e.g. NAV-PVT.lat,NAV-PVT.flags.gnssFixOK): a row per message
+    /// instead of per field; implies --format csv --scale --expand-flags
+    #[arg(long, value_delimiter = ',')]
+    fields: Vec<CsvField>,
+
+    /// Only output these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Decode on this many threads (0: one per CPU); holds the decoded log in memory
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Decode only the messages this product generation documents (M8, M9, M10, F9, F10, X20)
+    #[arg(short, long)]
+    generation: Option<String>,
+
+    /// Largest plausible payload length; longer length fields get --on-oversize
+    #[arg(long, default_value_t = MAX_PAYLOAD_LEN)]
+    max_length: usize,
+
+    /// Frames over --max-length or past the end of the log: resync, error or wait
+    #[arg(long, default_value_t = OversizeAction::Resync)]
+    on_oversize: OversizeAction,
+
+    /// ubx-capture --timestamps CSV of the input; adds each message's host_time
+    #[arg(short, long)]
+    timestamps: Option<PathBuf>,
+
+    /// Path to message_index.json, which maps generations to manuals
+    #[arg(long, default_value_os_t = default_index_path())]
+    message_index: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-decode: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The next valid frame; checksum errors are skipped, length errors fail.
+fn next_frame<'a>(iter: &mut FrameIter<'a>) -> ubx_schema::Result<Option<FrameRef<'a>>> {
+    for frame in iter.by_ref() {
+        match frame {
+            Ok(frame) => return Ok(Some(frame)),
+            Err(e @ (FrameError::Oversized { .. } | FrameError::Truncated { .. })) => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+    Ok(None)
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let mut schema = Schema::load(&args.schema)?;
+    if let Some(generation) = &args.generation {
+        schema = Profile::new(&MessageIndex::load(&args.message_index)?, generation)?.apply(&schema);
+    }
+    let wide = !args.fields.is_empty();
+    if wide && !matches!(args.format, Format::Json | Format::Csv) {
+        let message = "--fields writes CSV; use it without --format or with --format csv";
+        return Err(ubx_schema::Error::Config(message.into()));
+    }
+    for field in &args.fields {
+        field.check(&schema)?;
+    }
+    let decoder = Decoder::with_options(
+        &schema,
+        args.format.decode_options(DecodeOptions {
+            scale: args.scale || wide,
+            expand_flags: args.expand_flags || wide,
+            include_reserved: args.include_reserved,
+        }),
+    );
+    let wanted: Vec<String> = args.message.iter().map(|m| normalize_name(m)).collect();
+
+    let input = match &args.input {
+        Some(path) => LogFile::open(path)?,
+        None => LogFile::read(io::stdin().lock())?,
+    };
+    let host_times = args.timestamps.as_ref().map(HostTimes::load).transpose()?.unwrap_or_default();
+
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let writer = match args.format {
+        Format::Gpsd => {
+            let device = args.input.as_ref().filter(|p| p.as_os_str() != "-");
+            MessageWriter::gpsd(&device.map_or("stdin".into(), |p| p.display().to_string()), out)?
+        }
+        _ if wide => MessageWriter::csv_fields(args.fields.clone(), out)?,
+        format => MessageWriter::new(format, out)?,
+    };
+    let mut writer = writer.with_host_times(host_times);
+
+    let selected = |msg: &DecodedMessage| {
+        let alias = msg.variant.as_ref().map(|v| format!("{}-{v}", msg.name));
+        wanted.is_empty() || wanted.iter().any(|w| *w == msg.name || Some(w) == alias.as_ref())
+    };
+    let mut iter = FrameIter::with_policy(&input, LengthPolicy::new(args.max_length, args.on_oversize));
+    // A framing error ends the input, but what was decoded before it is still written out and terminated.
+    let mut framing = Ok(());
+    if args.jobs == 1 {
+        while let Some(frame) = next_frame(&mut iter).unwrap_or_else(|e| {
+            framing = Err(e);
+            None
+        }) {
+            let msg = decoder.decode_frame(&frame);
+            if selected(&msg) {
+                writer.write(frame.offset, &msg)?;
+            }
+        }
+    } else {
+        let mut frames = Vec::new();
+        while let Some(frame) = next_frame(&mut iter).unwrap_or_else(|e| {
+            framing = Err(e);
+            None
+        }) {
+            frames.push(frame);
+        }
+        let messages = decode_frames(&decoder, &frames, args.jobs);
+        for (frame, msg) in frames.iter().zip(&messages).filter(|(_, msg)| selected(msg)) {
+            writer.write(frame.offset, msg)?;
+        }
+    }
+    writer.finish()?;
+    framing?;
+
+    let stats = iter.stats();
+    if stats.checksum_errors + stats.oversized > 0 {
+        eprintln!(
+            "ubx-decode: {} frames, {} checksum errors, {} oversized lengths, {} bytes skipped",
+            stats.frames, stats.checksum_errors, stats.oversized, stats.skipped_bytes
+        );
+    }
+    if iter.position() < input.len() {
+        eprintln!(
+            "ubx-decode: log ends inside a frame at offset {}, {} bytes left undecoded",
+            iter.position(),
+            input.len() - iter.position()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Remove byte-identical repeated frames from a .ubx log, as left by a
+//! logger writing twice or by polls overlapping periodic output.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::dedup::{Dedup, DEFAULT_WINDOW_MS};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-dedup", about = "Remove repeated identical UBX frames")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// How long after a frame an identical one is a repeat, in milliseconds of receiver time
+    #[arg(short, long, default_value_t = DEFAULT_WINDOW_MS)]
+    window: u32,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-dedup: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let input = LogFile::open(&args.input)?;
+    let (output, stats) = Dedup::new(args.window).strip(&schema, &input);
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    out.write_all(&output)?;
+    out.flush()?;
+
+    eprintln!(
+        "ubx-dedup: removed {} of {} frames ({} bytes)",
+        stats.removed, stats.frames, stats.removed_bytes
+    );
+    if stats.other_bytes > 0 {
+        eprintln!("ubx-dedup: kept {} bytes outside UBX frames (NMEA, RTCM, corrupt frames)", stats.other_bytes);
+    }
+    for (name, count) in &stats.by_type {
+        eprintln!("  {name:<16} {count}");
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Compare two UBX captures (or two frames) field by field.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::diff::{diff_captures, CaptureMessage};
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::{default_schema_path, normalize_name};
+use ubx_schema::{DecodeOptions, Decoder, Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-diff", about = "Field-level comparison of two UBX captures")]
+struct Args {
+    /// Left .ubx file, or a hex frame with --hex
+    left: String,
+
+    /// Right .ubx file, or a hex frame with --hex
+    right: String,
+
+    /// Arguments are hex-encoded frames rather than file names
+    #[arg(long)]
+    hex: bool,
+
+    /// Compare physical (scaled) values
+    #[arg(long)]
+    scale: bool,
+
+    /// Compare bitfields member by member
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Include reserved fields
+    #[arg(long)]
+    include_reserved: bool,
+
+    /// Treat floats closer than this as equal
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f64,
+
+    /// Only compare these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Only print the summary
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("ubx-diff: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn read_input(arg: &str, hex: bool) -> ubx_schema::Result<Vec<u8>> {
+    if hex {
+        let digits: String = arg.chars().filter(|c| !c.is_whitespace()).collect();
+        return hex::decode(digits).map_err(|e| Error::Encode(format!("invalid hex: {e}")));
+    }
+    Ok(LogFile::open(arg)?.to_vec())
+}
+
+fn load(args: &Args, decoder: &Decoder, arg: &str, wanted: &[String]) -> ubx_schema::Result<Vec<CaptureMessage>> {
+    let input = read_input(arg, args.hex)?;
+    let mut out = Vec::new();
+    for frame in frames(&input).flatten() {
+        let message = decoder.decode_frame(&frame);
+        if !wanted.is_empty() && !wanted.contains(&message.name) {
+            continue;
+        }
+        out.push(CaptureMessage {
+            offset: frame.offset,
+            raw: frame.raw.to_vec(),
+            message,
+        });
+    }
+    Ok(out)
+}
+
+/// Returns whether the inputs are identical.
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: args.scale,
+            expand_flags: args.expand_flags,
+            include_reserved: args.include_reserved,
+        },
+    );
+    let wanted: Vec<String> = args.message.iter().map(|m| normalize_name(m)).collect();
+    let left = load(args, &decoder, &args.left, &wanted)?;
+    let right = load(args, &decoder, &args.right, &wanted)?;
+
+    let diff = diff_captures(&left, &right, args.tolerance);
+    if !args.quiet {
+        for m in &diff.matched {
+            if m.fields.is_empty() && m.bytes_equal {
+                continue;
+            }
+            println!(
+                "{} (left @{}, right @{})",
+                m.key.label(),
+                left[m.left].offset,
+                right[m.right].offset
+            );
+            if m.fields.is_empty() {
+                println!("  fields equal, bytes differ");
+            }
+            let show = |v: &Option<ubx_schema::Value>| v.as_ref().map_or("-".to_string(), |v| v.to_string());
+            for f in &m.fields {
+                println!("  {}: {} -> {}", f.path, show(&f.left), show(&f.right));
+            }
+        }
+        for (key, i) in &diff.only_left {
+            println!("only in left: {} @{}", key.label(), left[*i].offset);
+        }
+        for (key, j) in &diff.only_right {
+            println!("only in right: {} @{}", key.label(), right[*j].offset);
+        }
+    }
+    eprintln!(
+        "ubx-diff: {} matched, {} differ, {} differ in bytes only, {} only in left, {} only in right",
+        diff.matched.len(),
+        diff.changed(),
+        diff.bytes_only(),
+        diff.only_left.len(),
+        diff.only_right.len()
+    );
+    Ok(diff.is_identical())
+}
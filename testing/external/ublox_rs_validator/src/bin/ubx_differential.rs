@@ -0,0 +1,137 @@
+//! Run the golden corpus or captured logs through the schema decoder, the
+//! ublox crate and pyubx2, and report where they disagree.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::conformance::{differential_suites, write_reports};
+use ubx_schema::corpus::default_corpus_path;
+use ubx_schema::differential::{
+    corpus_samples, default_pyubx2_script, log_samples, run, Backend, Report, ScriptBackend, UbloxBackend,
+};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-differential", about = "Compare the schema decoder with other UBX decoders")]
+struct Args {
+    /// .ubx logs to check; the golden corpus if none are given
+    inputs: Vec<PathBuf>,
+
+    /// Corpus directory, used when no logs are given
+    #[arg(long, default_value_os_t = default_corpus_path())]
+    corpus: PathBuf,
+
+    /// Skip the ublox crate
+    #[arg(long)]
+    no_ublox: bool,
+
+    /// Skip pyubx2
+    #[arg(long)]
+    no_pyubx2: bool,
+
+    /// Python interpreter with pyubx2 installed
+    #[arg(long, default_value = "python3")]
+    python: String,
+
+    /// pyubx2 batch script
+    #[arg(long, default_value_os_t = default_pyubx2_script())]
+    pyubx2_script: PathBuf,
+
+    /// Relative tolerance for float comparisons
+    #[arg(long, default_value_t = 1e-6)]
+    tolerance: f64,
+
+    /// Write the full report as JSON to this file (- for stdout)
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Write the results as JUnit XML to this file, a test suite per decoder
+    #[arg(long)]
+    junit: Option<PathBuf>,
+
+    /// Write per-message pass/fail counts as JSON to this file
+    #[arg(long)]
+    summary: Option<PathBuf>,
+
+    /// Disagreements to print (0 for all)
+    #[arg(short = 'n', long, default_value_t = 50)]
+    limit: usize,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run_args(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-differential: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run_args(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let samples = if args.inputs.is_empty() {
+        corpus_samples(&args.corpus)?
+    } else {
+        let mut samples = Vec::new();
+        for path in &args.inputs {
+            samples.extend(log_samples(&path.display().to_string(), &LogFile::open(path)?));
+        }
+        samples
+    };
+
+    let mut backends: Vec<Box<dyn Backend>> = Vec::new();
+    if !args.no_ublox {
+        backends.push(Box::new(UbloxBackend));
+    }
+    if !args.no_pyubx2 {
+        backends.push(Box::new(ScriptBackend::pyubx2(&args.python, &args.pyubx2_script)));
+    }
+    let report = run(&schema, &samples, &mut backends, args.tolerance);
+
+    match args.json.as_deref().map(|p| p.to_str() == Some("-")) {
+        Some(true) => println!("{}", serde_json::to_string_pretty(&report)?),
+        Some(false) => fs::write(args.json.as_ref().unwrap(), serde_json::to_string_pretty(&report)? + "\n")?,
+        None => print_report(&report, args.limit),
+    }
+    if args.junit.is_some() || args.summary.is_some() {
+        let suites = differential_suites(&Decoder::new(&schema), &samples, &report);
+        write_reports(args.junit.as_deref(), args.summary.as_deref(), &schema.schema_version, &suites)?;
+    }
+    Ok(report.is_clean())
+}
+
+fn print_report(report: &Report, limit: usize) {
+    let shown = if limit == 0 { report.disagreements.len() } else { limit };
+    for d in report.disagreements.iter().take(shown) {
+        let field = d.field.as_deref().map(|f| format!(".{f}")).unwrap_or_default();
+        println!("{}  {}{field}  {}: {} vs schema {}", d.source, d.message, d.backend, d.other, d.schema);
+    }
+    if report.disagreements.len() > shown {
+        println!("... {} more", report.disagreements.len() - shown);
+    }
+    eprintln!(
+        "ubx-differential: {} frames, {} decoded by the schema",
+        report.samples, report.schema_parsed
+    );
+    for b in &report.backends {
+        if let Some(error) = &b.error {
+            eprintln!("  {:<9} unavailable: {error}", b.name);
+            continue;
+        }
+        eprintln!(
+            "  {:<9} {} parsed, {} unsupported, {} rejected; {} fields compared, {} disagreements",
+            b.name, b.parsed, b.unsupported, b.rejected, b.fields_compared, b.disagreements
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Encode JSON message descriptions into framed UBX bytes.
+//!
+//! Input is a JSON object, a JSON array of objects or NDJSON, each of the form
+//! `{"name": "CFG-RATE", "fields": {"measRate": 100, "navRate": 1, "timeRef": 1}}`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{EncodeOptions, Encoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-encode", about = "Encode JSON message descriptions as UBX frames")]
+struct Args {
+    /// Input JSON/NDJSON files (stdin if omitted or '-')
+    inputs: Vec<PathBuf>,
+
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write one hex-encoded frame per line instead of binary
+    #[arg(long)]
+    hex: bool,
+
+    /// Field values are scaled physical quantities
+    #[arg(long)]
+    scaled: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-encode: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let encoder = Encoder::with_options(&schema, EncodeOptions { scaled: args.scaled });
+
+    let mut descriptions = Vec::new();
+    if args.inputs.is_empty() {
+        descriptions.extend(read_descriptions(&mut io::stdin().lock())?);
+    }
+    for path in &args.inputs {
+        if path.as_os_str() == "-" {
+            descriptions.extend(read_descriptions(&mut io::stdin().lock())?);
+        } else {
+            descriptions.extend(read_descriptions(&mut File::open(path)?)?);
+        }
+    }
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    for description in &descriptions {
+        let bytes = encoder.encode_json(description)?.try_to_bytes()?;
+        if args.hex {
+            writeln!(out, "{}", hex::encode(bytes))?;
+        } else {
+            out.write_all(&bytes)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Accept a single object, an array of objects, or one object per line.
+fn read_descriptions(input: &mut dyn Read) -> ubx_schema::Result<Vec<Json>> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    match serde_json::from_str(&text) {
+        Ok(Json::Array(items)) => Ok(items),
+        Ok(item) => Ok(vec![item]),
+        Err(_) => text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).map_err(Into::into))
+            .collect(),
+    }
+}
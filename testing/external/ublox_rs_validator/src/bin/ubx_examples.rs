@@ -0,0 +1,47 @@
+//! Run the example frames embedded in the schema's message definitions.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::examples::run;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-examples", about = "Check the schema's example frames decode to their expected values")]
+struct Args {
+    /// Only these messages, e.g. NAV-PVT
+    messages: Vec<String>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run_args(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("ubx-examples: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run_args(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let report = run(&schema, &args.messages);
+    for failure in &report.failures {
+        println!("{failure}");
+    }
+    eprintln!(
+        "ubx-examples: {} examples in {} messages, {} failed",
+        report.examples,
+        report.messages,
+        report.failures.len()
+    );
+    Ok(report.failures.is_empty())
+}
@@ -0,0 +1,111 @@
+//! Extract messages from a .ubx log by type and time window, byte for byte.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::filter::{Filter, ItowRange};
+use ubx_schema::frame::frames;
+use ubx_schema::index::LogIndex;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::parse_iso8601;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-filter", about = "Extract UBX messages by type and time window")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Keep only these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Drop these messages; may be repeated or comma separated
+    #[arg(short = 'x', long, value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// First iTOW to keep, in milliseconds
+    #[arg(long)]
+    from_itow: Option<u32>,
+
+    /// Keep iTOW values below this, in milliseconds
+    #[arg(long)]
+    to_itow: Option<u32>,
+
+    /// First UTC time to keep, e.g. 2024-01-15T12:00:00Z
+    #[arg(long, value_parser = parse_utc)]
+    from: Option<f64>,
+
+    /// Keep UTC times before this
+    #[arg(long, value_parser = parse_utc)]
+    to: Option<f64>,
+
+    /// Select through the sidecar index <input>.idx, building or refreshing it as needed
+    #[arg(long)]
+    index: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn parse_utc(s: &str) -> Result<f64, String> {
+    parse_iso8601(s)
+        .map(|t| t.to_unix())
+        .ok_or_else(|| format!("invalid time '{s}', expected YYYY-MM-DDTHH:MM:SSZ"))
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-filter: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let input = LogFile::open(&args.input)?;
+
+    let mut filter = Filter::default().include(&args.message).exclude(&args.exclude);
+    if args.from_itow.is_some() || args.to_itow.is_some() {
+        filter = filter.itow(ItowRange {
+            from: args.from_itow,
+            to: args.to_itow,
+        });
+    }
+    if args.from.is_some() || args.to.is_some() {
+        filter = filter.utc(args.from, args.to);
+    }
+    let (selected, total) = if args.index {
+        let (index, _) = LogIndex::load_or_build(&schema, &args.input, &input, true)?;
+        (filter.apply_indexed(&schema, &input, &index)?, index.entries.len())
+    } else {
+        (filter.apply(&schema, &input), frames(&input).flatten().count())
+    };
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut bytes = 0;
+    for frame in &selected {
+        out.write_all(frame.raw)?;
+        bytes += frame.raw.len();
+    }
+    out.flush()?;
+
+    eprintln!("ubx-filter: kept {} of {total} frames ({bytes} bytes)", selected.len());
+    Ok(())
+}
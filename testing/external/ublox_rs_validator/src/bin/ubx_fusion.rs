@@ -0,0 +1,149 @@
+//! Sensor-fusion status of an ADR/UDR drive log: fusion-mode transitions,
+//! per-sensor calibration and mount-alignment convergence, from ESF-STATUS
+//! and ESF-ALG.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::fusion::{
+    alignment_label, calibration_label, fusion_mode_label, sensor_type_label, FusionAnalyzer, StatusHistory, FAULTS,
+};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::timing::Statistics;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-fusion", about = "Sensor-fusion calibration and alignment report for UBX drive logs")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// List every fusion-mode, calibration and alignment status change
+    #[arg(long)]
+    changes: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-fusion: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn seconds(ms: Option<i64>) -> String {
+    ms.map_or("-".to_string(), |ms| format!("{:.1} s", ms as f64 / 1_000.0))
+}
+
+fn times(history: &StatusHistory, label: fn(u8) -> &'static str) -> String {
+    let times: Vec<String> = history
+        .time_in
+        .iter()
+        .map(|(&value, &ms)| format!("{} {}", label(value), seconds(Some(ms))))
+        .collect();
+    format!("{} changes; {}", history.changes.len(), times.join(", "))
+}
+
+fn changes(history: &StatusHistory, label: fn(u8) -> &'static str) {
+    for c in &history.changes {
+        println!("    iTOW {}: {} -> {}", c.itow, label(c.from), label(c.to));
+    }
+}
+
+fn spread(stats: &Statistics) -> String {
+    match (stats.mean(), stats.std_dev()) {
+        (Some(mean), Some(sd)) => format!("{mean:.2}° ± {sd:.2}° (range {:.2}°)", stats.max - stats.min),
+        _ => "-".to_string(),
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: false,
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut fusion = FusionAnalyzer::new();
+    for frame in frames(&input).flatten() {
+        // ESF-STATUS (0x10) and ESF-ALG (0x14)
+        if frame.class == 0x10 && matches!(frame.id, 0x10 | 0x14) {
+            fusion.push(&decoder.decode_frame(&frame));
+        }
+    }
+
+    if fusion.fusion.epochs > 0 {
+        let current = fusion.fusion.current().map_or("-", fusion_mode_label);
+        println!("ESF-STATUS: {} epochs, now {current}", fusion.fusion.epochs);
+        println!("  time to fusion {}", seconds(fusion.fusion.time_to.get(&1).copied()));
+        println!("  fusion mode: {}", times(&fusion.fusion, fusion_mode_label));
+        if args.changes {
+            changes(&fusion.fusion, fusion_mode_label);
+        }
+    }
+    for (&sensor_type, sensor) in &fusion.sensors {
+        let calibration = sensor.calibration.current().map_or("-", calibration_label);
+        let faults: Vec<String> = FAULTS
+            .iter()
+            .zip(sensor.faults)
+            .filter(|(_, n)| *n > 0)
+            .map(|(name, n)| format!("{name} {n}"))
+            .collect();
+        println!(
+            "  sensor {sensor_type} ({}): {} Hz, used {}/{}, {calibration}, calibrated after {}, faults: {}",
+            sensor_type_label(sensor_type),
+            sensor.freq,
+            sensor.used,
+            sensor.epochs,
+            seconds(sensor.time_to_calibrated()),
+            if faults.is_empty() { "none".to_string() } else { faults.join(", ") }
+        );
+        if args.changes {
+            changes(&sensor.calibration, calibration_label);
+        }
+    }
+    let alignment = &fusion.alignment;
+    if alignment.status.epochs > 0 {
+        let current = alignment.status.current().map_or("-", alignment_label);
+        println!("ESF-ALG: {} epochs, now {current}", alignment.status.epochs);
+        println!(
+            "  time to coarse {}, to fine {}",
+            seconds(alignment.status.time_to.get(&3).copied()),
+            seconds(alignment.status.time_to.get(&4).copied())
+        );
+        println!("  status: {}", times(&alignment.status, alignment_label));
+        if args.changes {
+            changes(&alignment.status, alignment_label);
+        }
+        let [tilt, yaw, angle] = alignment.errors;
+        println!("  errors: tilt {tilt}, yaw {yaw}, angle {angle}");
+        println!(
+            "  fine: yaw {}, pitch {}, roll {}",
+            spread(&alignment.yaw),
+            spread(&alignment.pitch),
+            spread(&alignment.roll)
+        );
+        if let Some((yaw, pitch, roll)) = alignment.last {
+            println!("  last: yaw {yaw:.2}°, pitch {pitch:.2}°, roll {roll:.2}°");
+        }
+    }
+    if fusion.fusion.epochs + alignment.status.epochs == 0 {
+        eprintln!("ubx-fusion: no ESF-STATUS or ESF-ALG in the log");
+    }
+    Ok(())
+}
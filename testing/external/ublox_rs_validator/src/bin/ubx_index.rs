@@ -0,0 +1,81 @@
+//! Build the sidecar frame index of a .ubx log.
+//!
+//! ```text
+//! ubx-index capture.ubx            # writes capture.ubx.idx
+//! ubx-index capture.ubx --print    # offset,class,id,length,itow per frame
+//! ubx-filter capture.ubx --index --from-itow 360000000 --to-itow 360060000
+//! ```
+
+use std::collections::BTreeSet;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::index::{sidecar_path, LogIndex};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-index", about = "Index the frames of a UBX log for random access")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Index file to write (default: <input>.idx)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Print the entries as CSV instead of writing the index
+    #[arg(long)]
+    print: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-index: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let input = LogFile::open(&args.input)?;
+    let index = LogIndex::build(&schema, &input);
+
+    if args.print {
+        let mut out = BufWriter::new(io::stdout().lock());
+        writeln!(out, "offset,class,id,length,itow")?;
+        for e in &index.entries {
+            let itow = e.itow.map(|t| t.to_string()).unwrap_or_default();
+            writeln!(out, "{},0x{:02X},0x{:02X},{},{itow}", e.offset, e.class, e.id, e.payload_len)?;
+        }
+        out.flush()?;
+        return Ok(());
+    }
+
+    let path = args.output.clone().unwrap_or_else(|| sidecar_path(&args.input));
+    index.write(&path)?;
+    let types: BTreeSet<_> = index.entries.iter().map(|e| (e.class, e.id)).collect();
+    let itows = index.entries.iter().filter_map(|e| e.itow);
+    let span = match (itows.clone().min(), itows.max()) {
+        (Some(first), Some(last)) => format!(", iTOW {first}..{last}"),
+        _ => String::new(),
+    };
+    eprintln!(
+        "ubx-index: {} frames, {} message types{span} -> {}",
+        index.entries.len(),
+        types.len(),
+        path.display()
+    );
+    Ok(())
+}
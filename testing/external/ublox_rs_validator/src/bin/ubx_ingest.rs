@@ -0,0 +1,102 @@
+//! File sample frames from receiver captures into the golden corpus,
+//! tagged with the receiver model and firmware from MON-VER.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::corpus::{default_corpus_path, load_corpus};
+use ubx_schema::filter::Filter;
+use ubx_schema::ingest::{split, vector_for};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-ingest", about = "Add sample frames from receiver captures to the test corpus")]
+struct Args {
+    /// Capture files (.ubx)
+    #[arg(required = true)]
+    captures: Vec<PathBuf>,
+
+    /// Samples to keep per message type and capture
+    #[arg(short = 'n', long, default_value_t = 3)]
+    per_type: usize,
+
+    /// Only these messages, e.g. NAV-PVT,RXM-RAWX
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Leave these messages out
+    #[arg(short = 'x', long, value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// List what would be filed without writing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Corpus directory
+    #[arg(long, default_value_os_t = default_corpus_path())]
+    corpus: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-ingest: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::new(&schema);
+    let filter = Filter::default().include(&args.message).exclude(&args.exclude);
+
+    let mut known = HashSet::new();
+    if args.corpus.is_dir() {
+        for (_, vector) in load_corpus(&args.corpus)? {
+            known.insert(vector.bytes()?);
+        }
+    }
+
+    let mut taken = HashSet::new();
+    let mut filed = 0;
+    for capture in &args.captures {
+        let data = LogFile::open(capture)?;
+        let (samples, stats) = split(&decoder, &data, args.per_type, &filter, &known);
+        let source = capture.file_name().map_or_else(|| capture.display().to_string(), |n| n.to_string_lossy().into());
+        for sample in &samples {
+            let (path, vector) = vector_for(&decoder, sample, &source, &args.corpus, &mut taken)?;
+            let label = path.strip_prefix(&args.corpus).unwrap_or(&path).display();
+            println!("{label}  {}", vector.description);
+            if !args.dry_run {
+                fs::create_dir_all(path.parent().expect("vector paths have a directory"))?;
+                fs::write(&path, serde_json::to_string_pretty(&vector)? + "\n")?;
+            }
+            known.insert(sample.frame.raw.to_vec());
+        }
+        filed += samples.len();
+        eprintln!(
+            "ubx-ingest: {}: {} frames, {} samples, {} duplicates, {} unknown, {} surplus",
+            capture.display(),
+            stats.frames,
+            samples.len(),
+            stats.duplicates,
+            stats.unknown,
+            stats.surplus
+        );
+    }
+    let verb = if args.dry_run { "would file" } else { "filed" };
+    eprintln!("ubx-ingest: {verb} {filed} vectors; check their expected fields before committing");
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Jamming and spoofing timeline of a .ubx capture, from MON-RF, SEC-SIG
+//! and MON-SPAN.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::interference::{InterferenceMonitor, StateChange};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-interference", about = "Jamming and spoofing indicator timeline for UBX captures")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// List every MON-SPAN spectrum summary, not only narrowband peaks
+    #[arg(long)]
+    spectra: bool,
+
+    /// Write the whole report as JSON instead
+    #[arg(long)]
+    json: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-interference: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn when(offset: usize, itow: Option<u32>) -> String {
+    match itow {
+        Some(itow) => format!("@{offset} iTOW {itow}"),
+        None => format!("@{offset}"),
+    }
+}
+
+fn describe(change: &StateChange) -> String {
+    let from = change.from.map_or("", |from| change.indicator.state_label(from));
+    let arrow = if from.is_empty() { String::new() } else { format!("{from} -> ") };
+    format!(
+        "{}: {} {}: {arrow}{}",
+        when(change.offset, change.itow),
+        change.source,
+        change.indicator.label(),
+        change.label
+    )
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut monitor = InterferenceMonitor::new();
+    for frame in frames(&input).flatten() {
+        monitor.push(frame.offset, &decoder.decode_frame(&frame));
+    }
+    let report = monitor.finish();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for change in &report.changes {
+            println!("{}", describe(change));
+        }
+        for s in report.spectra.iter().filter(|s| args.spectra || s.narrowband) {
+            let peak = if s.narrowband { " narrowband" } else { "" };
+            println!(
+                "{}: MON-SPAN block {} center {:.3} MHz: peak {} dB at {:.3} MHz, median {} dB, PGA {} dB{peak}",
+                when(s.offset, s.itow),
+                s.block,
+                s.center_hz as f64 / 1e6,
+                s.peak_db,
+                s.peak_hz / 1e6,
+                s.median_db,
+                s.pga_db
+            );
+        }
+    }
+    let events = report.events().count();
+    let narrowband = report.spectra.iter().filter(|s| s.narrowband).count();
+    eprintln!(
+        "ubx-interference: {} indicators, {events} jamming/spoofing events, {} spectra ({narrowband} narrowband)",
+        report.counts.len(),
+        report.spectra.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Bridge a u-blox receiver to MAVLink ground stations.
+//!
+//! NAV-PVT becomes `GPS_RAW_INT` and NAV-SAT `GPS_STATUS`, with NAV-DOP
+//! supplying `eph`/`epv`; enable those messages on the receiver. Packets go
+//! to a UDP address (QGroundControl and MAVProxy listen on 14550) or a
+//! serial port such as a telemetry radio.
+//!
+//! ```text
+//! ubx-mavlink -p /dev/ttyACM0 --udp 192.168.1.10:14550
+//! ubx-mavlink flight.ubx --realtime --serial /dev/ttyUSB0 --serial-baud 57600
+//! ```
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::mavlink::{GpsBridge, PacketWriter, Version, MAV_COMP_ID_GPS};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::itow_delta;
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-mavlink", about = "Send UBX navigation solutions as MAVLink GPS messages")]
+struct Args {
+    /// Input .ubx file (stdin if '-'); omit to read --port
+    #[arg(required_unless_present = "port", conflicts_with = "port")]
+    input: Option<PathBuf>,
+
+    /// Read from this serial port
+    #[arg(short, long)]
+    port: Option<String>,
+
+    /// Baud rate for --port
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Send to this UDP address
+    #[arg(long, default_value = "127.0.0.1:14550", conflicts_with = "serial")]
+    udp: String,
+
+    /// Send to this serial port instead of UDP
+    #[arg(long)]
+    serial: Option<String>,
+
+    /// Baud rate for --serial
+    #[arg(long, default_value_t = 57_600)]
+    serial_baud: u32,
+
+    /// MAVLink system ID
+    #[arg(long, default_value_t = 1)]
+    system_id: u8,
+
+    /// MAVLink component ID
+    #[arg(long, default_value_t = MAV_COMP_ID_GPS)]
+    component_id: u8,
+
+    /// Send MAVLink 1 packets
+    #[arg(long)]
+    mavlink1: bool,
+
+    /// Pace file input by its iTOW, as the receiver sent it
+    #[arg(long)]
+    realtime: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+enum Output {
+    Udp(UdpSocket),
+    Serial(Box<dyn serialport::SerialPort>),
+}
+
+impl Output {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        match self {
+            Output::Udp(socket) => socket.send(packet).map(|_| ()),
+            Output::Serial(port) => port.write_all(packet),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-mavlink: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let version = if args.mavlink1 { Version::V1 } else { Version::V2 };
+    let mut bridge = GpsBridge::new(PacketWriter::new(version, args.system_id, args.component_id));
+
+    let mut output = match &args.serial {
+        Some(name) => Output::Serial(serialport::new(name, args.serial_baud).open().map_err(io::Error::from)?),
+        None => {
+            let socket = UdpSocket::bind("0.0.0.0:0")?;
+            socket.connect(&args.udp)?;
+            Output::Udp(socket)
+        }
+    };
+    let mut packets = 0u64;
+    let mut forward = |msg: &DecodedMessage| -> io::Result<()> {
+        for packet in bridge.translate(msg) {
+            output.send(&packet)?;
+            packets += 1;
+        }
+        Ok(())
+    };
+
+    if let Some(path) = &args.input {
+        let input = LogFile::open(path)?;
+        let mut last_itow = None;
+        for frame in frames(&input).flatten() {
+            let msg = decoder.decode_frame(&frame);
+            if let (true, Some(itow)) = (args.realtime, msg.itow()) {
+                let gap = last_itow.map_or(0, |prev| itow_delta(prev, itow));
+                if (1..60_000).contains(&gap) {
+                    thread::sleep(Duration::from_millis(gap as u64));
+                }
+                last_itow = Some(itow);
+            }
+            forward(&msg)?;
+        }
+    } else if let Some(port_name) = &args.port {
+        let mut port = serialport::new(port_name, args.baud)
+            .timeout(Duration::from_millis(100))
+            .open()
+            .map_err(io::Error::from)?;
+        let mut parser = FrameParser::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match port.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e.into()),
+            };
+            parser.push(&buf[..n]);
+            while let Some(result) = parser.next_frame() {
+                if let Ok((_, frame)) = result {
+                    forward(&decoder.decode(frame.class, frame.id, &frame.payload))?;
+                }
+            }
+        }
+    }
+
+    let target = args.serial.as_deref().unwrap_or(&args.udp);
+    eprintln!("ubx-mavlink: {packets} packets sent to {target}");
+    Ok(())
+}
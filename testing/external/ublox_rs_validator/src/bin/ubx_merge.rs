@@ -0,0 +1,123 @@
+//! Merge several .ubx logs into one stream ordered by iTOW or host receive
+//! time, with a CSV recording which log each frame came from.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::merge::{merge, Order, Source};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Decoder, Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-merge", about = "Merge UBX logs into one time-ordered stream")]
+struct Args {
+    /// Input logs as PATH or LABEL=PATH (label defaults to the file stem)
+    #[arg(required = true, num_args = 2..)]
+    inputs: Vec<String>,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Order by `itow` or by `host` receive time
+    #[arg(long, default_value = "itow")]
+    order: Order,
+
+    /// ubx-capture --timestamps CSV for each input, in the same order (for --order host)
+    #[arg(short, long)]
+    timestamps: Vec<PathBuf>,
+
+    /// Write a CSV of offset,source,source_offset,time,message for the merged stream
+    #[arg(long)]
+    sources: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-merge: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn label_and_path(input: &str) -> (String, PathBuf) {
+    match input.split_once('=') {
+        Some((label, path)) if !label.is_empty() => (label.to_string(), PathBuf::from(path)),
+        _ => {
+            let path = PathBuf::from(input);
+            let stem = Path::new(input).file_stem().map(|s| s.to_string_lossy().into_owned());
+            (stem.unwrap_or_else(|| input.to_string()), path)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    if !args.timestamps.is_empty() && args.timestamps.len() != args.inputs.len() {
+        return Err(Error::Config(format!(
+            "{} timestamp files for {} inputs; give one per input",
+            args.timestamps.len(),
+            args.inputs.len()
+        )));
+    }
+
+    let mut sources = Vec::new();
+    for (i, input) in args.inputs.iter().enumerate() {
+        let (label, path) = label_and_path(input);
+        let host_times = match args.timestamps.get(i) {
+            Some(csv) => Some(HostTimes::load(csv)?),
+            None => None,
+        };
+        sources.push(Source {
+            label,
+            data: fs::read(&path)?,
+            host_times,
+        });
+    }
+    let merged = merge(&schema, &sources, args.order)?;
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut annotations = match &args.sources {
+        Some(path) => {
+            let mut w = BufWriter::new(File::create(path)?);
+            writeln!(w, "offset,source,source_offset,time,message")?;
+            Some(w)
+        }
+        None => None,
+    };
+    let decoder = Decoder::new(&schema);
+    let mut offset = 0;
+    let mut counts = vec![0usize; sources.len()];
+    for m in &merged {
+        out.write_all(m.frame.raw)?;
+        if let Some(w) = annotations.as_mut() {
+            let time = m.time.map_or_else(String::new, |t| format!("{t:.3}"));
+            let name = decoder.decode_frame(&m.frame).name;
+            writeln!(w, "{offset},{},{},{time},{name}", sources[m.source].label, m.frame.offset)?;
+        }
+        offset += m.frame.raw.len();
+        counts[m.source] += 1;
+    }
+    out.flush()?;
+    if let Some(w) = annotations.as_mut() {
+        w.flush()?;
+    }
+
+    let per_source: Vec<String> = sources.iter().zip(&counts).map(|(s, n)| format!("{} {n}", s.label)).collect();
+    eprintln!("ubx-merge: {} frames ({})", merged.len(), per_source.join(", "));
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Split an AssistNow Offline file into MGA frames, select them by date and
+//! constellation, and upload them with flow control on MGA-ACK.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::config::{default_keys_path, valset_frames, ConfigKeys, Layers};
+use ubx_schema::frame::FrameParser;
+use ubx_schema::gnss::Gnss;
+use ubx_schema::mga::{parse_mga_ack, split, AssistFrame, Selection};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::{parse_iso8601, unix_to_datetime, DateTime};
+use ubx_schema::transact::{send_with_ack, wait_for, RetryPolicy};
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-mga", about = "Split and upload AssistNow Offline (MGA-ANO) data")]
+struct Args {
+    /// AssistNow Offline file (.mgaoffline / .ubx)
+    input: PathBuf,
+
+    /// First day to upload, e.g. 2024-01-15 (default: today, UTC)
+    #[arg(long, value_parser = parse_date)]
+    date: Option<DateTime>,
+
+    /// Number of days to upload from --date
+    #[arg(long, default_value_t = 1)]
+    days: u32,
+
+    /// Upload every day in the file
+    #[arg(long, conflicts_with_all = ["date", "days"])]
+    all_days: bool,
+
+    /// Only these constellations (e.g. gps,galileo); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    gnss: Vec<Gnss>,
+
+    /// Print the frames per day and constellation and exit
+    #[arg(long)]
+    list: bool,
+
+    /// Write the selected frames to a .ubx file instead of uploading
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Serial port to upload to
+    #[arg(short, long, required_unless_present_any = ["list", "output"])]
+    port: Option<String>,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Enable MGA-ACK first by setting CFG-NAVSPG-ACKAIDING in RAM (generation 9+)
+    #[arg(long)]
+    enable_ack: bool,
+
+    /// Don't wait for MGA-ACK; pace frames by --interval instead
+    #[arg(long, conflicts_with = "enable_ack")]
+    no_ack: bool,
+
+    /// Milliseconds between frames with --no-ack
+    #[arg(long, default_value_t = 20)]
+    interval: u64,
+
+    /// Seconds to wait for each MGA-ACK
+    #[arg(long, default_value_t = 1.0)]
+    timeout: f64,
+
+    /// Times to resend a frame that was not acknowledged
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+}
+
+fn parse_date(s: &str) -> Result<DateTime, String> {
+    parse_iso8601(s).ok_or_else(|| format!("invalid date '{s}', expected YYYY-MM-DD"))
+}
+
+fn today() -> DateTime {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    unix_to_datetime((secs / 86_400.0).floor() * 86_400.0)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-mga: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn day_label(frame: &AssistFrame) -> String {
+    frame.date.map_or_else(
+        || "-".to_string(),
+        |d| format!("{:04}-{:02}-{:02}", d.year, d.month, d.day),
+    )
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let all = split(&schema, &fs::read(&args.input)?)?;
+
+    if args.list {
+        let mut counts = BTreeMap::<(String, String, String), usize>::new();
+        for f in &all {
+            let gnss = f.gnss().map_or_else(|| "-".to_string(), |g| g.name().to_string());
+            *counts.entry((day_label(f), gnss, f.name.clone())).or_default() += 1;
+        }
+        println!("{:<10}  {:<8}  {:<14} {:>6}", "date", "gnss", "message", "frames");
+        for ((day, gnss, name), n) in counts {
+            println!("{day:<10}  {gnss:<8}  {name:<14} {n:>6}");
+        }
+        return Ok(());
+    }
+
+    let selection = Selection {
+        days: (!args.all_days).then(|| (args.date.unwrap_or_else(today), args.days)),
+        gnss: args.gnss.clone(),
+    };
+    let selected: Vec<&AssistFrame> = all.iter().filter(|f| selection.matches(f)).collect();
+    if !selected.iter().any(|f| f.date.is_some()) {
+        let days: BTreeSet<String> = all.iter().filter(|f| f.date.is_some()).map(day_label).collect();
+        if let (Some(first), Some(last)) = (days.first(), days.last()) {
+            return Err(Error::Config(format!(
+                "no MGA-ANO frames match the selection; the file covers {first} to {last}"
+            )));
+        }
+    }
+
+    if let Some(path) = &args.output {
+        let mut out = BufWriter::new(File::create(path)?);
+        for f in &selected {
+            out.write_all(&f.frame.to_bytes())?;
+        }
+        out.flush()?;
+        eprintln!("ubx-mga: wrote {} of {} frames", selected.len(), all.len());
+        return Ok(());
+    }
+
+    let Some(port_name) = &args.port else {
+        unreachable!("clap enforces port");
+    };
+    let mut port = serialport::new(port_name, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    let mut parser = FrameParser::new();
+    let timeout = Duration::from_secs_f64(args.timeout);
+
+    if args.enable_ack {
+        let keys = ConfigKeys::load(&args.keys)?;
+        let kv = keys.key_value("CFG-NAVSPG-ACKAIDING", &Json::Bool(true), false)?;
+        let policy = RetryPolicy {
+            timeout,
+            retries: args.retries,
+            ..RetryPolicy::default()
+        };
+        for frame in valset_frames(&[kv], Layers::RAM) {
+            let report = send_with_ack(&mut *port, &mut parser, &frame, &policy)?;
+            if !report.is_ack() {
+                return Err(Error::Config(format!("CFG-NAVSPG-ACKAIDING not accepted: {report}")));
+            }
+        }
+    }
+
+    let (mut accepted, mut rejected, mut unacknowledged) = (0, 0, 0);
+    for (i, f) in selected.iter().enumerate() {
+        let bytes = f.frame.to_bytes();
+        if args.no_ack {
+            port.write_all(&bytes)?;
+            port.flush()?;
+            thread::sleep(Duration::from_millis(args.interval));
+            continue;
+        }
+        let mut outcome = None;
+        for _ in 0..=args.retries {
+            port.write_all(&bytes)?;
+            port.flush()?;
+            outcome = wait_for(&mut *port, &mut parser, timeout, |reply| {
+                parse_mga_ack(reply).filter(|ack| ack.acknowledges(&f.frame))
+            })?;
+            if outcome.is_some() {
+                break;
+            }
+        }
+        let label = || format!("{}/{} {} {}", i + 1, selected.len(), f.name, day_label(f));
+        match outcome {
+            Some(ack) if ack.accepted => accepted += 1,
+            Some(ack) => {
+                rejected += 1;
+                eprintln!("ubx-mga: {}: rejected: {}", label(), ack.info());
+            }
+            None => {
+                unacknowledged += 1;
+                eprintln!("ubx-mga: {}: no MGA-ACK", label());
+            }
+        }
+        if accepted + rejected == 0 && unacknowledged == 3 {
+            return Err(Error::Config(
+                "no MGA-ACK received; enable aiding acknowledgment (--enable-ack) or use --no-ack".into(),
+            ));
+        }
+    }
+
+    if args.no_ack {
+        eprintln!("ubx-mga: sent {} frames without flow control", selected.len());
+        return Ok(());
+    }
+    eprintln!(
+        "ubx-mga: {} frames: {accepted} accepted, {rejected} rejected, {unacknowledged} unacknowledged",
+        selected.len()
+    );
+    if rejected + unacknowledged > 0 {
+        return Err(Error::Config(format!("{} frames not accepted", rejected + unacknowledged)));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Convert a legacy receiver configuration (CFG-PRT, CFG-MSG, CFG-RATE,
+//! CFG-NAV5) into configuration keys for generation 9 and later receivers.
+//!
+//! ```text
+//! ubx-migrate m8.txt -o f9.toml          # a u-center configuration, for ubx-cfg
+//! ubx-migrate polls.ubx -o valset.ubx    # a capture of poll responses, as VALSET frames
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::{json, Map, Value as Json};
+use ubx_schema::config::{default_keys_path, valset_frames, ConfigKeys, Layers};
+use ubx_schema::migrate::{read_config_frames, Migrator};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-migrate", about = "Convert legacy CFG messages into configuration keys")]
+struct Args {
+    /// Capture (.ubx) or u-center configuration (.txt) with legacy CFG messages
+    input: PathBuf,
+
+    /// Output: .toml, .yaml/.yml or .json for ubx-cfg, or .ubx for VALSET frames [default: TOML to stdout]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Layers to write, e.g. ram,bbr,flash
+    #[arg(long, default_value = "ram")]
+    layers: String,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-migrate: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let db = ConfigKeys::load(&args.keys)?;
+    let layers = Layers::parse(&args.layers)?;
+
+    let mut migrator = Migrator::new(&schema, &db);
+    for frame in read_config_frames(&fs::read(&args.input)?)? {
+        migrator.push(&frame);
+    }
+    let migration = migrator.finish();
+    for note in &migration.skipped {
+        eprintln!("ubx-migrate: skipped {note}");
+    }
+    if migration.values.is_empty() {
+        return Err(Error::Config(format!("no settings to migrate in {} CFG messages", migration.frames)));
+    }
+
+    let ext = args
+        .output
+        .as_ref()
+        .and_then(|p| p.extension())
+        .and_then(|e| e.to_str())
+        .unwrap_or("toml")
+        .to_ascii_lowercase();
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    if ext == "ubx" {
+        for frame in valset_frames(&migration.values, layers) {
+            out.write_all(&frame.try_to_bytes()?)?;
+        }
+    } else {
+        let mut keys = Map::new();
+        for kv in &migration.values {
+            let key = db.get(&kv.name).expect("migrated keys are in the database");
+            keys.insert(kv.name.clone(), key.decode_value(&kv.value, false)?);
+        }
+        let names: Vec<&str> = [(layers.ram, "ram"), (layers.bbr, "bbr"), (layers.flash, "flash")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect();
+        let doc = json!({"layers": names, "keys": Json::Object(keys)});
+        let text = match ext.as_str() {
+            "json" => serde_json::to_string_pretty(&doc)? + "\n",
+            "yaml" | "yml" => serde_yaml::to_string(&doc).map_err(|e| Error::Config(e.to_string()))?,
+            _ => toml::to_string(&doc).map_err(|e| Error::Config(e.to_string()))?,
+        };
+        out.write_all(text.as_bytes())?;
+    }
+    out.flush()?;
+    eprintln!(
+        "ubx-migrate: {} keys from {} CFG messages, {} settings skipped",
+        migration.values.len(),
+        migration.frames,
+        migration.skipped.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! Shrink a capture that triggers a decode failure to the shortest byte
+//! sequence that still triggers it, for a bug report.
+
+use std::env;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, ExitCode, Stdio};
+
+use clap::Parser;
+use ubx_schema::differential::{default_pyubx2_script, Backend, ScriptBackend, UbloxBackend};
+use ubx_schema::minimize::{failures, minimize};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-minimize", about = "Minimize a capture that makes a decoder fail")]
+struct Args {
+    /// Capture to minimize
+    input: PathBuf,
+
+    /// Where to write the minimized capture [default: INPUT.min.ubx]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// List the failures in the capture and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Which failure of `--list` to keep, counting from 1
+    #[arg(short, long, default_value_t = 1)]
+    failure: usize,
+
+    /// Keep whatever makes this shell command exit nonzero instead; `{}` is
+    /// replaced by the candidate file, which is appended if there is no `{}`
+    #[arg(short, long)]
+    command: Option<String>,
+
+    /// Skip the ublox crate
+    #[arg(long)]
+    no_ublox: bool,
+
+    /// Compare with pyubx2 too (slow: it runs once per candidate)
+    #[arg(long)]
+    pyubx2: bool,
+
+    /// Python interpreter with pyubx2 installed
+    #[arg(long, default_value = "python3")]
+    python: String,
+
+    /// pyubx2 batch script
+    #[arg(long, default_value_os_t = default_pyubx2_script())]
+    pyubx2_script: PathBuf,
+
+    /// Relative tolerance for float comparisons
+    #[arg(long, default_value_t = 1e-6)]
+    tolerance: f64,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-minimize: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let data = fs::read(&args.input)?;
+    let output = args.output.clone().unwrap_or_else(|| args.input.with_extension("min.ubx"));
+    let mut tests = 0;
+
+    let minimized = if let Some(command) = &args.command {
+        let scratch = env::temp_dir().join(format!("ubx-minimize-{}.ubx", process::id()));
+        let result = minimize(&data, |candidate| {
+            tests += 1;
+            fs::write(&scratch, candidate).is_ok() && !succeeds(command, &scratch)
+        });
+        let _ = fs::remove_file(&scratch);
+        result
+    } else {
+        let schema = Schema::load(&args.schema)?;
+        let mut backends: Vec<Box<dyn Backend>> = Vec::new();
+        if !args.no_ublox {
+            backends.push(Box::new(UbloxBackend));
+        }
+        if args.pyubx2 {
+            backends.push(Box::new(ScriptBackend::pyubx2(&args.python, &args.pyubx2_script)));
+        }
+
+        // Panics are caught and counted as failures; don't print each one.
+        panic::set_hook(Box::new(|_| {}));
+        let found = failures(&schema, &data, &mut backends, args.tolerance);
+        if args.list {
+            for (i, failure) in found.iter().enumerate() {
+                println!("{:>3}  {failure}", i + 1);
+            }
+            return Ok(!found.is_empty());
+        }
+        if found.is_empty() {
+            eprintln!("ubx-minimize: no failures in {}", args.input.display());
+            return Ok(false);
+        }
+        let Some(target) = found.get(args.failure.wrapping_sub(1)) else {
+            return Err(Error::Capture(format!("no failure {}, only {}", args.failure, found.len())));
+        };
+        eprintln!("ubx-minimize: keeping {target}");
+        minimize(&data, |candidate| {
+            tests += 1;
+            failures(&schema, candidate, &mut backends, args.tolerance).contains(target)
+        })
+    };
+
+    let Some(minimized) = minimized else {
+        eprintln!("ubx-minimize: {} does not make the command fail", args.input.display());
+        return Ok(false);
+    };
+    fs::write(&output, &minimized)?;
+    println!("{}", hex::encode(&minimized));
+    eprintln!(
+        "ubx-minimize: {} -> {} bytes after {tests} tests, written to {}",
+        data.len(),
+        minimized.len(),
+        output.display()
+    );
+    Ok(true)
+}
+
+/// Whether `command` exits zero on the candidate at `path`.
+fn succeeds(command: &str, path: &Path) -> bool {
+    let path = path.display().to_string();
+    let line = if command.contains("{}") {
+        command.replace("{}", &path)
+    } else {
+        format!("{command} {path}")
+    };
+    Command::new("sh")
+        .arg("-c")
+        .arg(line)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
@@ -0,0 +1,182 @@
+//! Publish decoded UBX messages to an MQTT broker as JSON.
+//!
+//! Each message goes to `PREFIX/NAME` (`ubx/NAV-PVT` by default) in the
+//! same shape `ubx-decode` produces, unless `--topic` maps it elsewhere.
+//!
+//! ```text
+//! ubx-mqtt -p /dev/ttyACM0 --broker broker.local --prefix fleet/truck-12 --qos 1 --scale --expand-flags
+//! ubx-mqtt capture.ubx --topic NAV-PVT=fleet/truck-12/fix --message-qos NAV-SAT=0 -m NAV-PVT,NAV-SAT
+//! ```
+
+use std::io::{self, ErrorKind, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use ubx_schema::filter::Filter;
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::mqtt::{parse_mapping, ConnectOptions, MqttClient, QoS, Topics};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-mqtt", about = "Publish decoded UBX messages to MQTT topics")]
+struct Args {
+    /// Input .ubx file (stdin if '-'); omit to read --port
+    #[arg(required_unless_present = "port", conflicts_with = "port")]
+    input: Option<PathBuf>,
+
+    /// Read from this serial port
+    #[arg(short, long)]
+    port: Option<String>,
+
+    /// Baud rate for --port
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Broker address, host[:port]
+    #[arg(long, default_value = "localhost:1883")]
+    broker: String,
+
+    /// MQTT client identifier [default: ubx-schema-PID]
+    #[arg(long)]
+    client_id: Option<String>,
+
+    #[arg(long)]
+    username: Option<String>,
+
+    #[arg(long, requires = "username")]
+    password: Option<String>,
+
+    /// Topic prefix; messages go to PREFIX/NAV-PVT and so on
+    #[arg(long, default_value = "ubx")]
+    prefix: String,
+
+    /// Publish a message type to its own topic, as MESSAGE=TOPIC; may be repeated
+    #[arg(long, value_parser = parse_mapping)]
+    topic: Vec<(String, String)>,
+
+    /// QoS for all messages: 0, 1 or 2
+    #[arg(long, default_value = "0")]
+    qos: QoS,
+
+    /// QoS for one message type, as MESSAGE=QOS; may be repeated
+    #[arg(long, value_parser = parse_mapping)]
+    message_qos: Vec<(String, String)>,
+
+    /// Set the retain flag, so new subscribers get the latest message at once
+    #[arg(long)]
+    retain: bool,
+
+    /// Only publish these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Seconds to wait for the broker to connect or acknowledge
+    #[arg(long, default_value_t = 5.0)]
+    timeout: f64,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into named members
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// ubx-capture --timestamps CSV of the input; adds each message's host_time
+    #[arg(short, long, requires = "input")]
+    timestamps: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-mqtt: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: args.scale,
+            expand_flags: args.expand_flags,
+            ..DecodeOptions::default()
+        },
+    );
+    let filter = Filter::default().include(&args.message);
+    let mut topics = Topics::new(&args.prefix, args.qos);
+    for (message, topic) in &args.topic {
+        topics = topics.map(message, topic);
+    }
+    for (message, qos) in &args.message_qos {
+        topics = topics.map_qos(message, qos.parse()?);
+    }
+
+    let mut options = ConnectOptions {
+        username: args.username.clone(),
+        password: args.password.clone(),
+        ..ConnectOptions::default()
+    };
+    if let Some(id) = &args.client_id {
+        options.client_id = id.clone();
+    }
+    let mut client = MqttClient::connect(&args.broker, &options, Duration::from_secs_f64(args.timeout))?;
+    let publish = |client: &mut MqttClient, msg: &DecodedMessage, host_time| -> ubx_schema::Result<()> {
+        if filter.type_matches(msg) {
+            client.publish_timed(msg, host_time, &topics, args.retain)?;
+        }
+        Ok(())
+    };
+
+    if let Some(path) = &args.input {
+        let input = LogFile::open(path)?;
+        let host_times = args.timestamps.as_ref().map(HostTimes::load).transpose()?.unwrap_or_default();
+        for frame in frames(&input).flatten() {
+            publish(&mut client, &decoder.decode_frame(&frame), host_times.at(frame.offset))?;
+        }
+    } else if let Some(port_name) = &args.port {
+        let mut port = serialport::new(port_name, args.baud)
+            .timeout(Duration::from_millis(100))
+            .open()
+            .map_err(io::Error::from)?;
+        let mut parser = FrameParser::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match port.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::TimedOut => {
+                    client.keep_alive()?;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            parser.push(&buf[..n]);
+            while let Some(result) = parser.next_frame() {
+                if let Ok((_, frame)) = result {
+                    publish(&mut client, &decoder.decode(frame.class, frame.id, &frame.payload), None)?;
+                }
+            }
+            client.keep_alive()?;
+        }
+    }
+
+    let published = client.published;
+    client.disconnect()?;
+    eprintln!("ubx-mqtt: {published} messages published to {}", args.broker);
+    Ok(())
+}
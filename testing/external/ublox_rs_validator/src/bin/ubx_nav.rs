@@ -0,0 +1,89 @@
+//! Decode the navigation messages carried in RXM-SFRBX into ephemerides and
+//! almanacs, one JSON object per line.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::gnss::Gnss;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::nav::NavDecoder;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-nav", about = "Decode ephemerides and almanacs from RXM-SFRBX")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output NDJSON file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Only these constellations (e.g. gps,galileo); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    gnss: Vec<Gnss>,
+
+    /// Leave out almanacs
+    #[arg(long)]
+    no_almanac: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-nav: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut nav = NavDecoder::new();
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for frame in frames(&input).flatten() {
+        if (frame.class, frame.id) != (0x02, 0x13) {
+            continue;
+        }
+        for data in nav.push(&decoder.decode_frame(&frame)) {
+            if (!args.gnss.is_empty() && !args.gnss.contains(&data.gnss())) || (args.no_almanac && data.is_almanac()) {
+                continue;
+            }
+            serde_json::to_writer(&mut out, &data)?;
+            writeln!(out)?;
+            *counts.entry(data.kind()).or_default() += 1;
+        }
+    }
+    out.flush()?;
+
+    eprintln!("ubx-nav: {} records", counts.values().sum::<usize>());
+    for (kind, count) in &counts {
+        eprintln!("  {kind:<18} {count}");
+    }
+    Ok(())
+}
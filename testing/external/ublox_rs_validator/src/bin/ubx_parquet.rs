@@ -0,0 +1,105 @@
+//! Export a decoded UBX log to Parquet for pandas, Polars or DuckDB.
+//!
+//! ```text
+//! ubx-parquet drive.ubx --scale --expand-flags          # drive_parquet/nav_pvt.parquet, ...
+//! ubx-parquet drive.ubx --wide -o drive.parquet -m NAV-PVT,NAV-DOP
+//! duckdb -c "SELECT avg(hAcc) FROM 'drive_parquet/nav_pvt.parquet'"
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::filter::Filter;
+use ubx_schema::frame::frames;
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::parquet::{Layout, ParquetExporter};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-parquet", about = "Export decoded UBX messages to Parquet files")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output directory, or with --wide the output file
+    /// [default: the input name with a _parquet suffix, or a .parquet extension]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write one wide table with a row per message instead of a file per message type
+    #[arg(long)]
+    wide: bool,
+
+    /// Only export these messages (e.g. NAV-PVT); may be repeated or comma separated
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into one column per member
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// ubx-capture --timestamps CSV of the input; adds a host_time column
+    #[arg(short, long)]
+    timestamps: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-parquet: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let filter = Filter::default().include(&args.message);
+    let layout = if args.wide { Layout::Wide } else { Layout::PerMessage };
+    let output = args.output.clone().unwrap_or_else(|| match layout {
+        Layout::Wide => args.input.with_extension("parquet"),
+        Layout::PerMessage => {
+            let stem = args.input.file_stem().map_or("ubx".into(), |s| s.to_string_lossy().into_owned());
+            args.input.with_file_name(format!("{stem}_parquet"))
+        }
+    });
+    let input = LogFile::open(&args.input)?;
+
+    let mut exporter = ParquetExporter::new(&schema, options, layout, &output)?;
+    if let Some(path) = &args.timestamps {
+        exporter = exporter.with_host_times(HostTimes::load(path)?);
+    }
+    for frame in frames(&input).flatten() {
+        let msg = decoder.decode_frame(&frame);
+        if filter.type_matches(&msg) {
+            exporter.write(frame.offset, &msg)?;
+        }
+    }
+    let messages = exporter.messages;
+    let files = exporter.finish()?;
+    eprintln!(
+        "ubx-parquet: {messages} messages in {} files ({layout}) under {}",
+        files.len(),
+        output.display()
+    );
+    Ok(())
+}
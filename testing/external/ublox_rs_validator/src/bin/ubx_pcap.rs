@@ -0,0 +1,120 @@
+//! Extract and decode UBX frames from pcap/pcapng captures.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::output::{Format, MessageWriter};
+use ubx_schema::pcap::{read_packets, Extractor};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-pcap", about = "Extract UBX frames from pcap/pcapng captures")]
+struct Args {
+    /// Input .pcap or .pcapng file
+    input: PathBuf,
+
+    /// Decoded output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format: json, ndjson, csv, gpsd (TPV/SKY reports) or influx (line protocol);
+    /// gpsd and influx imply --scale --expand-flags
+    #[arg(short, long, default_value = "ndjson")]
+    format: Format,
+
+    /// Also write the extracted frames to this .ubx file
+    #[arg(long)]
+    ubx: Option<PathBuf>,
+
+    /// Only use these streams (indices from --list-streams)
+    #[arg(long, value_delimiter = ',')]
+    stream: Vec<usize>,
+
+    /// List the streams carrying UBX and exit
+    #[arg(long)]
+    list_streams: bool,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into named members
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-pcap: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let data = LogFile::open(&args.input)?;
+    let packets = read_packets(&data)?;
+
+    let mut extractor = Extractor::new();
+    let frames: Vec<_> = packets.iter().flat_map(|p| extractor.push(p)).collect();
+
+    if args.list_streams {
+        let keys: Vec<_> = extractor.streams().collect();
+        for (i, key) in keys.iter().enumerate() {
+            let count = frames.iter().filter(|f| f.stream == i).count();
+            if count > 0 {
+                println!("{i}: {key} ({count} frames)");
+            }
+        }
+        return Ok(());
+    }
+
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        args.format.decode_options(DecodeOptions {
+            scale: args.scale,
+            expand_flags: args.expand_flags,
+            include_reserved: false,
+        }),
+    );
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut writer = MessageWriter::new(args.format, out)?;
+    let mut ubx = match &args.ubx {
+        Some(path) => Some(BufWriter::new(File::create(path)?)),
+        None => None,
+    };
+
+    for extracted in &frames {
+        if !args.stream.is_empty() && !args.stream.contains(&extracted.stream) {
+            continue;
+        }
+        let frame = &extracted.frame;
+        if let Some(ubx) = ubx.as_mut() {
+            ubx.write_all(&frame.to_bytes())?;
+        }
+        let msg = decoder.decode(frame.class, frame.id, &frame.payload);
+        writer.write(extracted.offset as usize, &msg)?;
+    }
+    writer.finish()?;
+    if let Some(mut ubx) = ubx {
+        ubx.flush()?;
+    }
+    eprintln!("ubx-pcap: {} packets, {} frames", packets.len(), frames.len());
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Rebuild a .ubx stream from `ubx-decode` JSON or NDJSON output.
+//!
+//! ```text
+//! ubx-decode drive.ubx -f ndjson --include-reserved > drive.ndjson
+//! # edit drive.ndjson, e.g. set fixType to 0 for a stretch of NAV-PVT records
+//! ubx-rebuild drive.ndjson -o edited.ubx
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::error::Error;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{EncodeOptions, Encoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-rebuild", about = "Re-encode decoded UBX JSON/NDJSON records as a .ubx stream")]
+struct Args {
+    /// ubx-decode JSON or NDJSON output (stdin if omitted or '-')
+    input: Option<PathBuf>,
+
+    /// Output .ubx file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The records were decoded with --scale
+    #[arg(long)]
+    scaled: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-rebuild: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let encoder = Encoder::with_options(&schema, EncodeOptions { scaled: args.scaled });
+
+    let mut text = String::new();
+    match &args.input {
+        Some(path) if path.as_os_str() != "-" => File::open(path)?.read_to_string(&mut text)?,
+        _ => io::stdin().lock().read_to_string(&mut text)?,
+    };
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    // A JSON array as `-f json` writes, else one record per line.
+    let records: Vec<(usize, Json)> = if text.trim_start().starts_with('[') {
+        let Json::Array(items) = serde_json::from_str(&text)? else {
+            unreachable!("text starting with '[' parses to an array");
+        };
+        items.into_iter().enumerate().map(|(i, r)| (i + 1, r)).collect()
+    } else {
+        text.lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| {
+                let record = serde_json::from_str(l).map_err(|e| Error::Encode(format!("line {}: {e}", i + 1)))?;
+                Ok((i + 1, record))
+            })
+            .collect::<ubx_schema::Result<_>>()?
+    };
+    let (mut frames, mut raw) = (0, 0);
+    for (n, record) in &records {
+        let bytes = encoder
+            .encode_record(record)
+            .and_then(|frame| Ok(frame.try_to_bytes()?))
+            .map_err(|e| Error::Encode(format!("record {n}: {e}")))?;
+        out.write_all(&bytes)?;
+        frames += 1;
+        raw += usize::from(record.get("payload_raw").is_some_and(Json::is_string));
+    }
+    out.flush()?;
+    eprintln!("ubx-rebuild: {frames} frames, {raw} from raw payloads");
+    Ok(())
+}
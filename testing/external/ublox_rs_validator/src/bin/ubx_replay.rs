@@ -0,0 +1,152 @@
+//! Replay a recorded .ubx log at its original timing.
+//!
+//! Timing comes from the iTOW of each frame: the gap between consecutive epochs
+//! is slept (divided by `--speed`) before the frame is written. All bytes are
+//! replayed, including NMEA or other data between frames. `--faults` corrupts
+//! frames on the way out (see `ubx_schema::inject`).
+
+use std::io::{self, BufWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::inject::{FaultInjector, FaultRates};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::itow_delta;
+use ubx_schema::{Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-replay", about = "Replay a UBX log to a serial port, TCP socket or stdout")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Write to this serial port
+    #[arg(long, conflicts_with_all = ["connect", "listen"])]
+    serial: Option<String>,
+
+    /// Baud rate for --serial
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Connect to a TCP server at HOST:PORT
+    #[arg(long, conflicts_with = "listen")]
+    connect: Option<String>,
+
+    /// Listen on ADDR:PORT and replay to the first client
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Speed-up factor; 0 replays as fast as possible
+    #[arg(short, long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Longest pause between epochs, in seconds (caps gaps in the log)
+    #[arg(long, default_value_t = 10.0)]
+    max_gap: f64,
+
+    /// Replay the log repeatedly until interrupted
+    #[arg(long = "loop")]
+    repeat: bool,
+
+    /// Inject faults, e.g. flip=0.01,truncate=0.005,duplicate=0.01,garbage=0.02
+    #[arg(long, default_value_t = FaultRates::default(), hide_default_value = true)]
+    faults: FaultRates,
+
+    /// Seed for --faults
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        // A consumer closing the pipe or socket ends the replay.
+        Err(ubx_schema::Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-replay: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::new(&schema);
+    let input = LogFile::open(&args.input)?;
+
+    let mut out: Box<dyn Write> = if let Some(port) = &args.serial {
+        Box::new(serialport::new(port, args.baud).open().map_err(io::Error::from)?)
+    } else if let Some(addr) = &args.connect {
+        Box::new(TcpStream::connect(addr)?)
+    } else if let Some(addr) = &args.listen {
+        let listener = TcpListener::bind(addr)?;
+        eprintln!("ubx-replay: waiting for a client on {}", listener.local_addr()?);
+        let (stream, peer) = listener.accept()?;
+        eprintln!("ubx-replay: replaying to {peer}");
+        Box::new(stream)
+    } else {
+        Box::new(BufWriter::new(io::stdout().lock()))
+    };
+
+    let mut injector = FaultInjector::new(args.faults, args.seed);
+    loop {
+        replay(args, &decoder, &input, &mut injector, &mut out)?;
+        if !args.repeat {
+            break;
+        }
+    }
+    if !args.faults.is_none() {
+        eprintln!("ubx-replay: injected into {}", injector.counts);
+    }
+    Ok(())
+}
+
+fn replay(
+    args: &Args,
+    decoder: &Decoder<'_>,
+    input: &[u8],
+    injector: &mut FaultInjector,
+    out: &mut dyn Write,
+) -> ubx_schema::Result<()> {
+    let mut written = 0;
+    let mut buf = Vec::new();
+    let mut last_itow = None;
+    for frame in frames(input).flatten() {
+        if args.speed > 0.0 {
+            if let Some(itow) = decoder.decode_frame(&frame).itow() {
+                if let Some(prev) = last_itow {
+                    let delta = itow_delta(prev, itow);
+                    if delta > 0 {
+                        out.flush()?;
+                        let secs = (delta as f64 / 1000.0 / args.speed).min(args.max_gap);
+                        thread::sleep(Duration::from_secs_f64(secs));
+                    }
+                }
+                last_itow = Some(itow);
+            }
+        }
+        out.write_all(&input[written..frame.offset])?;
+        if args.faults.is_none() {
+            out.write_all(frame.raw)?;
+        } else {
+            buf.clear();
+            injector.frame(frame.raw, &mut buf);
+            out.write_all(&buf)?;
+        }
+        written = frame.offset + frame.raw.len();
+    }
+    out.write_all(&input[written..])?;
+    out.flush()?;
+    Ok(())
+}
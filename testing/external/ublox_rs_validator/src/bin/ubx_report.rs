@@ -0,0 +1,115 @@
+//! Render a UBX capture as a standalone HTML decode report.
+//!
+//! ```text
+//! ubx-report drive.ubx                  # writes drive.html
+//! ubx-report drive.ubx -o bug-1234.html --title "Cold start, no fix"
+//! ```
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use serde_json::Value as Json;
+use ubx_schema::frame::frames;
+use ubx_schema::html::{HtmlReport, DEFAULT_MAX_FRAMES};
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::semantic::Validator;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-report", about = "Render a UBX capture as a standalone HTML report")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output file [default: the input with an .html extension]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Page title [default: the input file name]
+    #[arg(long)]
+    title: Option<String>,
+
+    /// List at most this many frames and errors
+    #[arg(long, default_value_t = DEFAULT_MAX_FRAMES)]
+    max_frames: usize,
+
+    /// Skip the plausibility checks
+    #[arg(long)]
+    no_checks: bool,
+
+    /// Path to enumerations.json (default: next to the schema)
+    #[arg(long)]
+    enumerations: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-report: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: false,
+        },
+    );
+    let validator = if args.no_checks {
+        None
+    } else {
+        let path = args
+            .enumerations
+            .clone()
+            .unwrap_or_else(|| args.schema.with_file_name("enumerations.json"));
+        let enums: Option<Json> = fs::read_to_string(path).ok().and_then(|s| serde_json::from_str(&s).ok());
+        Some(Validator::new(&schema, enums.as_ref()))
+    };
+    let input = LogFile::open(&args.input)?;
+    let title = match &args.title {
+        Some(title) => title.clone(),
+        None => args.input.file_name().map_or("UBX capture".into(), |n| n.to_string_lossy().into_owned()),
+    };
+
+    let mut report = HtmlReport::new(&title, args.max_frames);
+    let mut iter = frames(&input);
+    while let Some(frame) = iter.next() {
+        match frame {
+            Ok(frame) => {
+                let msg = decoder.decode_frame(&frame);
+                let warnings = validator.as_ref().map(|v| v.check(&msg)).unwrap_or_default();
+                report.add_frame(&frame, &msg, &warnings);
+            }
+            // The scan resumes one byte past the bad frame's sync.
+            Err(e) => report.add_error(iter.position() - 1, &e),
+        }
+    }
+
+    let output = args.output.clone().unwrap_or_else(|| args.input.with_extension("html"));
+    report.write(&mut BufWriter::new(File::create(&output)?))?;
+    eprintln!(
+        "ubx-report: {} frames, {} checksum errors, {} unknown, {} warnings -> {}",
+        report.frames,
+        report.errors,
+        report.unknown,
+        report.warnings,
+        output.display()
+    );
+    Ok(())
+}
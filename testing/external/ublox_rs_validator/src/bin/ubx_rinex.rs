@@ -0,0 +1,144 @@
+//! Convert RXM-RAWX/RXM-SFRBX logs to RINEX 3.04 observation and navigation files.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::rinex::{NavigationBuilder, ObservationBuilder, RinexHeader};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::{parse_iso8601, unix_to_datetime, DateTime, WeekReference};
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-rinex", about = "Convert RAWX/SFRBX logs to RINEX 3.04")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Observation file (default: input with .obs extension)
+    #[arg(long)]
+    obs: Option<PathBuf>,
+
+    /// Navigation file (default: input with .nav extension)
+    #[arg(long)]
+    nav: Option<PathBuf>,
+
+    /// MARKER NAME header value
+    #[arg(long, default_value = "UNKNOWN")]
+    marker: String,
+
+    /// OBSERVER header value
+    #[arg(long, default_value = "")]
+    observer: String,
+
+    /// AGENCY header value
+    #[arg(long, default_value = "")]
+    agency: String,
+
+    /// ANT # / TYPE header value
+    #[arg(long, default_value = "")]
+    antenna: String,
+
+    /// Approximate marker position as ECEF X,Y,Z in metres
+    #[arg(long, value_delimiter = ',', num_args = 3)]
+    position: Option<Vec<f64>>,
+
+    /// Date in the era of the log (YYYY-MM-DD), for resolving 10-bit ephemeris week numbers
+    /// [default: from NAV-TIMEGPS/TIMEUTC/PVT, else RAWX]
+    #[arg(long, value_parser = parse_date)]
+    reference_date: Option<DateTime>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-rinex: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_date(s: &str) -> Result<DateTime, String> {
+    parse_iso8601(s).ok_or_else(|| format!("invalid date '{s}', expected YYYY-MM-DD"))
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut obs = ObservationBuilder::new();
+    let mut nav = NavigationBuilder::new();
+    let mut reference = args.reference_date.as_ref().map_or_else(WeekReference::new, WeekReference::at);
+    for frame in frames(&input).flatten() {
+        // NAV-PVT (0x07), NAV-TIMEGPS (0x20) and NAV-TIMEUTC (0x21) give the
+        // week era; RXM-RAWX (0x15) and RXM-SFRBX (0x13) the data.
+        match (frame.class, frame.id) {
+            (0x01, 0x07 | 0x20 | 0x21) => {
+                reference.observe(&decoder.decode_frame(&frame));
+                continue;
+            }
+            (0x02, 0x13 | 0x15) => {}
+            _ => continue,
+        }
+        let msg = decoder.decode_frame(&frame);
+        obs.push(&msg);
+        nav.push(&msg);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let t = unix_to_datetime(now);
+    let mut header = RinexHeader {
+        date: format!(
+            "{:04}{:02}{:02} {:02}{:02}{:02} UTC",
+            t.year, t.month, t.day, t.hour, t.minute, t.second as u8
+        ),
+        marker_name: args.marker.clone(),
+        observer: args.observer.clone(),
+        agency: args.agency.clone(),
+        antenna_type: args.antenna.clone(),
+        ..RinexHeader::default()
+    };
+    if let Some([x, y, z]) = args.position.as_deref() {
+        header.approx_position = [*x, *y, *z];
+    }
+
+    let obs_path = args.obs.clone().unwrap_or_else(|| args.input.with_extension("obs"));
+    let mut out = BufWriter::new(File::create(&obs_path)?);
+    obs.write(&header, &mut out)?;
+    out.flush()?;
+
+    let nav_path = args.nav.clone().unwrap_or_else(|| args.input.with_extension("nav"));
+    let mut out = BufWriter::new(File::create(&nav_path)?);
+    nav.write(&header, reference.week().or(obs.first_week()), &mut out)?;
+    out.flush()?;
+
+    eprintln!(
+        "ubx-rinex: {} epochs to {}, {} ephemerides to {}",
+        obs.epochs(),
+        obs_path.display(),
+        nav.ephemerides().len(),
+        nav_path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! RTK solution timeline of a .ubx log: time to first fix, float/fixed
+//! transitions and outages, from NAV-PVT and NAV-RELPOSNED.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::rtk::{CarrierState, RtkAnalyzer, Timeline};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-rtk", about = "RTK fix timeline and outage analysis for UBX logs")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// List every segment of constant carrier solution
+    #[arg(long)]
+    segments: bool,
+
+    /// List every outage of the fixed solution
+    #[arg(long)]
+    outages: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-rtk: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn seconds(ms: Option<i64>) -> String {
+    ms.map_or("-".to_string(), |ms| format!("{:.1} s", ms as f64 / 1_000.0))
+}
+
+fn report(name: &str, timeline: &Timeline, args: &Args) {
+    if timeline.epochs() == 0 {
+        return;
+    }
+    let duration = timeline.duration_ms();
+    let share = |state| {
+        let ms = timeline.time_in(state);
+        let pct = if duration > 0 { 100.0 * ms as f64 / duration as f64 } else { 0.0 };
+        format!("{} {:.1}%", state.label(), pct)
+    };
+    let outages = timeline.outages();
+    let longest = outages.iter().map(|o| o.duration_ms).max();
+    println!("{name}: {} epochs over {}", timeline.epochs(), seconds(Some(duration)));
+    println!(
+        "  time to float {}, to fixed {}",
+        seconds(timeline.time_to(CarrierState::Float)),
+        seconds(timeline.time_to(CarrierState::Fixed))
+    );
+    println!(
+        "  {}, {}, {}",
+        share(CarrierState::Fixed),
+        share(CarrierState::Float),
+        share(CarrierState::None)
+    );
+    println!(
+        "  float -> fixed {}, fixed -> float {}, fixed -> none {}",
+        timeline.transitions(CarrierState::Float, CarrierState::Fixed),
+        timeline.transitions(CarrierState::Fixed, CarrierState::Float),
+        timeline.transitions(CarrierState::Fixed, CarrierState::None)
+    );
+    println!("  {} outages, longest {}", outages.len(), seconds(longest));
+    if args.segments {
+        for segment in &timeline.segments {
+            println!("    {segment}");
+        }
+    }
+    if args.outages {
+        for outage in &outages {
+            let end = if outage.recovered { "" } else { " (not recovered)" };
+            println!("    outage at iTOW {}: {}{end}", outage.start, seconds(Some(outage.duration_ms)));
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut rtk = RtkAnalyzer::new();
+    for frame in frames(&input).flatten() {
+        rtk.push(&decoder.decode_frame(&frame));
+    }
+    report("NAV-PVT", &rtk.pvt, args);
+    report("NAV-RELPOSNED", &rtk.relposned, args);
+    if rtk.pvt.epochs() + rtk.relposned.epochs() == 0 {
+        eprintln!("ubx-rtk: no NAV-PVT or NAV-RELPOSNED in the log");
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Send one configuration message and wait for its ACK-ACK, resending on
+//! timeout or ACK-NAK with backoff. The exit status tells scripts whether
+//! the receiver accepted it.
+//!
+//! ```text
+//! ubx-send -p /dev/ttyACM0 CFG-RATE measRate=200 navRate=1 timeRef=1
+//! ubx-send -p /dev/ttyACM0 --set CFG-RATE-MEAS=200 --layers ram,bbr
+//! ubx-send -p /dev/ttyACM0 --raw "b5 62 06 08 06 00 c8 00 01 00 01 00 de 6a"
+//! ```
+
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use ubx_schema::config::{default_keys_path, valset_frames, ConfigKeys, Layers};
+use ubx_schema::frame::{parse_frame, Frame, FrameParser};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::shell::parse_fields;
+use ubx_schema::transact::{send_with_ack, RetryPolicy};
+use ubx_schema::{EncodeOptions, Encoder, Error, Schema};
+
+/// The CFG class; only these messages are acknowledged.
+const CFG_CLASS: u8 = 0x06;
+
+#[derive(Parser)]
+#[command(name = "ubx-send", about = "Send a CFG message and wait for its acknowledgment")]
+struct Args {
+    /// Message to encode, e.g. CFG-RATE
+    #[arg(required_unless_present_any = ["set", "raw"], conflicts_with_all = ["set", "raw"])]
+    message: Option<String>,
+
+    /// Field values as FIELD=VALUE; bitfield members as flags.bit=1
+    fields: Vec<String>,
+
+    /// Set configuration keys with CFG-VALSET instead, as KEY=VALUE; may be repeated
+    #[arg(long, conflicts_with = "raw")]
+    set: Vec<String>,
+
+    /// Layers for --set, e.g. ram,bbr,flash
+    #[arg(long, default_value = "ram")]
+    layers: String,
+
+    /// Send this complete frame, given as hex
+    #[arg(long)]
+    raw: Option<String>,
+
+    /// Serial port
+    #[arg(short, long)]
+    port: String,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Seconds to wait for each acknowledgment
+    #[arg(long, default_value_t = 1.0)]
+    timeout: f64,
+
+    /// Times to resend after a timeout or ACK-NAK
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Milliseconds before the first resend; doubled for each further one
+    #[arg(long, default_value_t = 250)]
+    backoff: u64,
+
+    /// Treat ACK-NAK as final instead of resending
+    #[arg(long)]
+    no_retry_nak: bool,
+
+    /// Field and key values are physical (scaled) quantities
+    #[arg(long)]
+    scaled: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-send: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn frames_to_send(args: &Args, schema: &Schema) -> ubx_schema::Result<Vec<Frame>> {
+    if let Some(hex_text) = &args.raw {
+        let digits: String = hex_text.chars().filter(|c| !c.is_whitespace()).collect();
+        let bytes = hex::decode(digits.trim_start_matches("0x")).map_err(|e| Error::Config(format!("invalid hex: {e}")))?;
+        return Ok(vec![parse_frame(&bytes)?.to_owned()]);
+    }
+    if !args.set.is_empty() {
+        let db = ConfigKeys::load(&args.keys)?;
+        let values = args
+            .set
+            .iter()
+            .map(|s| {
+                let (key, value) = s
+                    .split_once('=')
+                    .ok_or_else(|| Error::Config(format!("expected KEY=VALUE, got '{s}'")))?;
+                let value = serde_json::from_str(value).unwrap_or_else(|_| value.into());
+                db.key_value(key, &value, args.scaled)
+            })
+            .collect::<ubx_schema::Result<Vec<_>>>()?;
+        return Ok(valset_frames(&values, Layers::parse(&args.layers)?));
+    }
+    let Some(message) = &args.message else {
+        unreachable!("clap enforces a message, --set or --raw");
+    };
+    let encoder = Encoder::with_options(schema, EncodeOptions { scaled: args.scaled });
+    let fields = parse_fields(args.fields.iter().map(String::as_str))?;
+    Ok(vec![encoder.encode(message, None, &fields)?])
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let frames = frames_to_send(args, &schema)?;
+    if let Some(frame) = frames.iter().find(|f| f.class != CFG_CLASS) {
+        return Err(Error::Config(format!(
+            "class 0x{:02X} is not acknowledged by the receiver; only CFG messages get ACK-ACK/NAK",
+            frame.class
+        )));
+    }
+
+    let mut port = serialport::new(&args.port, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    let mut parser = FrameParser::new();
+    let policy = RetryPolicy {
+        timeout: Duration::from_secs_f64(args.timeout),
+        retries: args.retries,
+        backoff: Duration::from_millis(args.backoff),
+        retry_nak: !args.no_retry_nak,
+    };
+    for (i, frame) in frames.iter().enumerate() {
+        let report = send_with_ack(&mut *port, &mut parser, frame, &policy)?;
+        let label = if frames.len() > 1 { format!(" {}/{}", i + 1, frames.len()) } else { String::new() };
+        let name = schema
+            .messages_by_ids(frame.class, frame.id)
+            .next()
+            .map_or_else(|| format!("{:02X}-{:02X}", frame.class, frame.id), |m| m.short_name().to_string());
+        println!("{name}{label}: {report}");
+        if !report.is_ack() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,101 @@
+//! Serve the schema's decoder and encoder over HTTP.
+//!
+//! ```text
+//! ubx-serve -l 0.0.0.0:8080 --scale --expand-flags
+//! curl --data-binary @drive.ubx -H 'Content-Type: application/octet-stream' localhost:8080/decode
+//! curl -d '{"name": "CFG-RATE", "fields": {"measRate": 200, "navRate": 1}}' 'localhost:8080/encode?format=hex'
+//! ```
+
+use std::io::{BufReader, BufWriter};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+
+use clap::Parser;
+use ubx_schema::http::{DecodeService, Request, Response, DEFAULT_MAX_BODY};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-serve", about = "HTTP service decoding and encoding UBX with the message schema")]
+struct Args {
+    /// Listen address
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    listen: String,
+
+    /// Decode with scale factors applied unless a request says otherwise
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields unless a request says otherwise
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// Include reserved fields unless a request says otherwise
+    #[arg(long)]
+    include_reserved: bool,
+
+    /// Largest request body accepted, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY)]
+    max_body: usize,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-serve: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Answer requests on one connection until the client closes it.
+fn serve(service: &DecodeService<'_>, stream: TcpStream, max_body: usize) -> ubx_schema::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    loop {
+        match Request::read(&mut reader, max_body) {
+            Ok(Some(request)) => {
+                let close = request.wants_close();
+                service.handle(&request).write_to(&mut writer, close)?;
+                if close {
+                    return Ok(());
+                }
+            }
+            Ok(None) => return Ok(()),
+            Err(ubx_schema::Error::Http(e)) => return Response::error(400, &e).write_to(&mut writer, true),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        include_reserved: args.include_reserved,
+    };
+    let service = DecodeService::new(&schema, options);
+    let listener = TcpListener::bind(&args.listen)?;
+    eprintln!("ubx-serve: listening on http://{}", listener.local_addr()?);
+    thread::scope(|scope| {
+        for stream in listener.incoming().flatten() {
+            let service = &service;
+            scope.spawn(move || {
+                let peer = stream.peer_addr().map_or("?".into(), |a| a.to_string());
+                if let Err(e) = serve(service, stream, args.max_body) {
+                    eprintln!("ubx-serve: {peer}: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
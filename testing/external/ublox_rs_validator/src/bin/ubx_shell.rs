@@ -0,0 +1,317 @@
+//! Interactive shell for a live receiver: poll and send messages, set
+//! configuration keys and watch decoded output and ACK/NAK as it arrives.
+//!
+//! ```text
+//! ubx> poll MON-VER
+//! ubx> set CFG-RATE-MEAS 200 ram,bbr
+//! ubx> watch NAV-PVT
+//! ```
+
+use std::collections::BTreeSet;
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, ExternalPrinter, Helper};
+use ubx_schema::config::{default_keys_path, parse_ack, valset_frames, ConfigKeys};
+use ubx_schema::frame::{Frame, FrameParser};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::shell::{complete, parse_command, Command};
+use ubx_schema::{DecodeOptions, Decoder, EncodeOptions, Encoder, Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-shell", about = "Interactive UBX shell for a live receiver")]
+struct Args {
+    /// Serial port, e.g. /dev/ttyACM0 or COM3
+    port: String,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Values given to send and set are physical (scaled) quantities
+    #[arg(long)]
+    scaled: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+
+    /// Path to unified_config_keys.json
+    #[arg(long, default_value_os_t = default_keys_path())]
+    keys: PathBuf,
+}
+
+const HELP: &str = "\
+poll MESSAGE [PAYLOAD-HEX]      send a poll request and print the response
+send MESSAGE [FIELD=VALUE ...]  encode and send a message (bitfields as flags.bit=1)
+set KEY VALUE [LAYERS]          CFG-VALSET one key, layers e.g. ram,bbr,flash (default ram)
+raw HEX                         send bytes as they are
+watch [MESSAGE ...|*]           print these messages as they arrive; no argument lists them
+unwatch [MESSAGE ...]           stop printing these messages, or all of them
+help                            this text
+quit                            leave the shell";
+
+/// What the reader thread prints.
+#[derive(Default)]
+struct Watch {
+    all: bool,
+    names: BTreeSet<String>,
+    /// Class/ID pairs of polls still waiting for their response.
+    pending: Vec<(u8, u8)>,
+}
+
+struct ShellHelper {
+    schema: Arc<Schema>,
+    keys: Arc<ConfigKeys>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(&self.schema, &self.keys, line, pos))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+struct StdoutPrinter;
+
+impl ExternalPrinter for StdoutPrinter {
+    fn print(&mut self, msg: String) -> rustyline::Result<()> {
+        println!("{msg}");
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-shell: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn readline_error(e: ReadlineError) -> Error {
+    match e {
+        ReadlineError::Io(e) => Error::Io(e),
+        other => Error::Io(io::Error::other(other)),
+    }
+}
+
+fn message_name(schema: &Schema, class: u8, id: u8) -> String {
+    schema
+        .messages_by_ids(class, id)
+        .next()
+        .map(|m| m.short_name().to_string())
+        .unwrap_or_else(|| format!("{class:02X}-{id:02X}"))
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Arc::new(Schema::load(&args.schema)?);
+    let keys = Arc::new(ConfigKeys::load(&args.keys)?);
+    let mut port = serialport::new(&args.port, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    let reader = port.try_clone().map_err(io::Error::from)?;
+
+    let mut editor = Editor::new().map_err(readline_error)?;
+    editor.set_helper(Some(ShellHelper {
+        schema: Arc::clone(&schema),
+        keys: Arc::clone(&keys),
+    }));
+    // Without a terminal (input piped from a script) print directly.
+    let printer: Box<dyn ExternalPrinter + Send> = match editor.create_external_printer() {
+        Ok(printer) => Box::new(printer),
+        Err(_) => Box::new(StdoutPrinter),
+    };
+
+    let watch = Arc::new(Mutex::new(Watch::default()));
+    let running = Arc::new(AtomicBool::new(true));
+    let receiver = {
+        let (schema, watch, running) = (Arc::clone(&schema), Arc::clone(&watch), Arc::clone(&running));
+        thread::spawn(move || receive(reader, printer, &schema, &watch, &running))
+    };
+
+    let encoder = Encoder::with_options(&schema, EncodeOptions { scaled: args.scaled });
+    println!("ubx-shell: connected to {} at {} baud; 'help' lists commands", args.port, args.baud);
+    loop {
+        let line = match editor.readline("ubx> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(readline_error(e)),
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+        let command = match parse_command(&line) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        let frames = match command {
+            Command::Quit => break,
+            Command::Help => {
+                println!("{HELP}");
+                continue;
+            }
+            Command::Watch(names) => {
+                let mut watch = watch.lock().unwrap();
+                if names.is_empty() {
+                    let listed: Vec<&str> = watch.names.iter().map(|n| n.trim_start_matches("UBX-")).collect();
+                    println!("watching: {}", if watch.all { "*".to_string() } else { listed.join(" ") });
+                }
+                for name in names {
+                    if name == "*" {
+                        watch.all = true;
+                    } else if let Some(msg) = schema.message_by_name(&name) {
+                        watch.names.insert(msg.name.clone());
+                    } else {
+                        eprintln!("{}", Error::UnknownMessage(name));
+                    }
+                }
+                continue;
+            }
+            Command::Unwatch(names) => {
+                let mut watch = watch.lock().unwrap();
+                if names.is_empty() {
+                    watch.all = false;
+                    watch.names.clear();
+                }
+                for name in names {
+                    if name == "*" {
+                        watch.all = false;
+                    }
+                    if let Some(msg) = schema.message_by_name(&name) {
+                        watch.names.remove(&msg.name);
+                    }
+                }
+                continue;
+            }
+            Command::Poll { message, payload } => match schema.message_by_name(&message) {
+                Some(msg) => {
+                    watch.lock().unwrap().pending.push((msg.class_id, msg.message_id));
+                    vec![Frame::new(msg.class_id, msg.message_id, payload)]
+                }
+                None => {
+                    eprintln!("{}", Error::UnknownMessage(message));
+                    continue;
+                }
+            },
+            Command::Send { message, fields } => match encoder.encode(&message, None, &fields) {
+                Ok(frame) => vec![frame],
+                Err(e) => {
+                    eprintln!("{e}");
+                    continue;
+                }
+            },
+            Command::Set { key, value, layers } => match keys.key_value(&key, &value, args.scaled) {
+                Ok(kv) => valset_frames(&[kv], layers),
+                Err(e) => {
+                    eprintln!("{e}");
+                    continue;
+                }
+            },
+            Command::Raw(bytes) => {
+                port.write_all(&bytes)?;
+                port.flush()?;
+                continue;
+            }
+        };
+        for frame in frames {
+            match frame.try_to_bytes() {
+                Ok(bytes) => port.write_all(&bytes)?,
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        port.flush()?;
+    }
+
+    running.store(false, Ordering::Relaxed);
+    let _ = receiver.join();
+    Ok(())
+}
+
+/// Reader thread: decode incoming frames and print ACK/NAK, poll responses
+/// and watched messages above the prompt.
+fn receive(
+    mut port: Box<dyn serialport::SerialPort>,
+    mut printer: Box<dyn ExternalPrinter + Send>,
+    schema: &Schema,
+    watch: &Mutex<Watch>,
+    running: &AtomicBool,
+) {
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(schema, options);
+    let mut parser = FrameParser::new();
+    let mut buf = [0u8; 4096];
+    while running.load(Ordering::Relaxed) {
+        let n = match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+            Err(e) => {
+                let _ = printer.print(format!("ubx-shell: read failed: {e}"));
+                break;
+            }
+        };
+        parser.push(&buf[..n]);
+        while let Some(result) = parser.next_frame() {
+            let Ok((_, frame)) = result else {
+                continue;
+            };
+            let text = if let Some((class, id, accepted)) = parse_ack(&frame) {
+                let verdict = if accepted { "ACK-ACK" } else { "ACK-NAK" };
+                Some(format!("{verdict} {}", message_name(schema, class, id)))
+            } else {
+                let msg = decoder.decode(frame.class, frame.id, &frame.payload);
+                let mut watch = watch.lock().unwrap();
+                if let Some(i) = watch.pending.iter().position(|p| *p == (frame.class, frame.id)) {
+                    watch.pending.remove(i);
+                    let body = serde_json::to_string_pretty(&msg.fields).unwrap_or_default();
+                    Some(format!("{}\n{body}", msg.summary(0)))
+                } else if watch.all || watch.names.contains(&msg.name) {
+                    Some(msg.summary(8))
+                } else {
+                    None
+                }
+            };
+            if let Some(text) = text {
+                if printer.print(text).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Per-constellation, per-satellite and per-signal C/N0 statistics of a
+//! .ubx log, from NAV-SAT and NAV-SIG, as JSON or CSV.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::signal::SignalStats;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Parser)]
+#[command(name = "ubx-signals", about = "C/N0 and usage statistics per satellite and signal")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format (default: from the output extension, else json)
+    #[arg(short, long, value_enum)]
+    format: Option<ReportFormat>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-signals: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut stats = SignalStats::new();
+    let mut epochs = 0;
+    for frame in frames(&input).flatten() {
+        // NAV-SAT (0x35) and NAV-SIG (0x43)
+        if frame.class == 0x01 && matches!(frame.id, 0x35 | 0x43) && stats.push(&decoder.decode_frame(&frame)) {
+            epochs += 1;
+        }
+    }
+
+    let format = args.format.unwrap_or_else(|| {
+        let ext = args.output.as_ref().and_then(|p| p.extension()).and_then(|e| e.to_str());
+        match ext {
+            Some(e) if e.eq_ignore_ascii_case("csv") => ReportFormat::Csv,
+            _ => ReportFormat::Json,
+        }
+    });
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    match format {
+        ReportFormat::Json => stats.write_json(&mut out)?,
+        ReportFormat::Csv => stats.write_csv(&mut out)?,
+    }
+    out.flush()?;
+    if stats.is_empty() {
+        eprintln!("ubx-signals: no tracked satellites in NAV-SAT or NAV-SIG");
+    } else {
+        eprintln!("ubx-signals: {epochs} epochs, {} rows", stats.iter().count());
+    }
+    Ok(())
+}
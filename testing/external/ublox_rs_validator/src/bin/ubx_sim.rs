@@ -0,0 +1,194 @@
+//! Generate a synthetic UBX stream from a scenario file.
+//!
+//! ```text
+//! ubx-sim drive.toml --speed 0 -o drive.ubx          # as fast as possible, to a file
+//! ubx-sim drive.toml --listen 127.0.0.1:5000         # real time, to the first TCP client
+//! ubx-sim drive.toml --pty                           # real time, to a pseudo-terminal
+//! ubx-sim drive.toml -o bad.ubx --faults flip=0.01    # with corrupted frames
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use ubx_schema::inject::{FaultInjector, FaultRates};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sim::{Scenario, Simulator};
+use ubx_schema::Schema;
+
+#[derive(Parser)]
+#[command(name = "ubx-sim", about = "Simulate a receiver's UBX output from a scenario")]
+struct Args {
+    /// Scenario file (JSON, TOML or YAML)
+    scenario: PathBuf,
+
+    /// Write to a file
+    #[arg(short, long, conflicts_with_all = ["connect", "listen", "pty"])]
+    output: Option<PathBuf>,
+
+    /// Connect to a TCP server at HOST:PORT
+    #[arg(long, conflicts_with_all = ["listen", "pty"])]
+    connect: Option<String>,
+
+    /// Listen on ADDR:PORT and send to the first client
+    #[arg(long, conflicts_with = "pty")]
+    listen: Option<String>,
+
+    /// Create a pseudo-terminal and send to it; its path is printed
+    #[arg(long)]
+    pty: bool,
+
+    /// Speed-up factor; 0 generates as fast as possible [default: 1, or 0 with --output]
+    #[arg(short, long)]
+    speed: Option<f64>,
+
+    /// Repeat the scenario until interrupted
+    #[arg(long = "loop")]
+    repeat: bool,
+
+    /// Inject faults, e.g. flip=0.01,truncate=0.005,duplicate=0.01,garbage=0.02
+    #[arg(long, default_value_t = FaultRates::default(), hide_default_value = true)]
+    faults: FaultRates,
+
+    /// Seed for --faults
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        // A consumer closing the pipe or socket ends the simulation.
+        Err(ubx_schema::Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-sim: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let scenario = Scenario::load(&args.scenario)?;
+    // Fail on a bad scenario before waiting for a client.
+    Simulator::new(&schema, scenario.clone())?;
+    let speed = args.speed.unwrap_or(if args.output.is_some() { 0.0 } else { 1.0 });
+
+    let mut out: Box<dyn Write> = if let Some(path) = &args.output {
+        Box::new(BufWriter::new(File::create(path)?))
+    } else if let Some(addr) = &args.connect {
+        Box::new(TcpStream::connect(addr)?)
+    } else if let Some(addr) = &args.listen {
+        let listener = TcpListener::bind(addr)?;
+        eprintln!("ubx-sim: waiting for a client on {}", listener.local_addr()?);
+        let (stream, peer) = listener.accept()?;
+        eprintln!("ubx-sim: sending to {peer}");
+        Box::new(stream)
+    } else if args.pty {
+        let (master, path) = pty::open()?;
+        eprintln!("ubx-sim: pseudo-terminal at {path}");
+        Box::new(master)
+    } else {
+        Box::new(BufWriter::new(io::stdout().lock()))
+    };
+
+    let mut injector = FaultInjector::new(args.faults, args.seed);
+    let mut buf = Vec::new();
+    let (mut epochs, mut frames) = (0, 0);
+    loop {
+        let mut sim = Simulator::new(&schema, scenario.clone())?;
+        let started = Instant::now();
+        while let Some(epoch) = sim.next_epoch()? {
+            if speed > 0.0 {
+                let due = Duration::from_secs_f64(epoch.state.time / speed);
+                if let Some(wait) = due.checked_sub(started.elapsed()) {
+                    out.flush()?;
+                    thread::sleep(wait);
+                }
+            }
+            for frame in &epoch.frames {
+                if args.faults.is_none() {
+                    out.write_all(&frame.to_bytes())?;
+                } else {
+                    buf.clear();
+                    injector.frame(&frame.to_bytes(), &mut buf);
+                    out.write_all(&buf)?;
+                }
+            }
+            epochs += 1;
+            frames += epoch.frames.len();
+        }
+        out.flush()?;
+        if !args.repeat {
+            break;
+        }
+    }
+    eprintln!("ubx-sim: {epochs} epochs, {frames} frames");
+    if !args.faults.is_none() {
+        eprintln!("ubx-sim: injected into {}", injector.counts);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+mod pty {
+    use std::ffi::CStr;
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::FromRawFd;
+
+    /// Open a pseudo-terminal in raw mode, so UBX bytes pass through
+    /// unchanged. Returns the master end and the path of the slave end.
+    /// The slave stays open for the life of the process so writes don't
+    /// fail while no client has it open.
+    pub fn open() -> io::Result<(File, String)> {
+        // SAFETY: plain libc calls on descriptors owned here; `ptsname`'s
+        // buffer is copied before any other pty call.
+        unsafe {
+            let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            if master < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let master_file = File::from_raw_fd(master);
+            if libc::grantpt(master) != 0 || libc::unlockpt(master) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let name = libc::ptsname(master);
+            if name.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let path = CStr::from_ptr(name).to_string_lossy().into_owned();
+            let slave = libc::open(name, libc::O_RDWR | libc::O_NOCTTY);
+            if slave < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(slave, &mut termios) == 0 {
+                libc::cfmakeraw(&mut termios);
+                libc::tcsetattr(slave, libc::TCSANOW, &termios);
+            }
+            // `slave` is deliberately never closed.
+            Ok((master_file, path))
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod pty {
+    use std::fs::File;
+    use std::io;
+
+    pub fn open() -> io::Result<(File, String)> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "--pty needs a Unix system"))
+    }
+}
@@ -0,0 +1,197 @@
+//! Back up a receiver's BBR to flash before power-down and check the
+//! restore at startup with UPD-SOS, or run the whole cycle as a production
+//! test.
+
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::process::{Command as Shell, ExitCode};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{Parser, Subcommand};
+use serialport::SerialPort;
+use ubx_schema::frame::FrameParser;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sos::{RestoreStatus, Sos};
+use ubx_schema::transact::{send_with_ack, RetryPolicy};
+use ubx_schema::{Error, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-sos", about = "Back up BBR to flash and verify the restore with UPD-SOS")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Serial port of the receiver, e.g. /dev/ttyACM0 or COM3
+    #[arg(short, long)]
+    port: String,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Seconds to wait for each reply
+    #[arg(long, global = true, default_value_t = 2.0)]
+    timeout: f64,
+
+    /// Times to resend a command that was not answered
+    #[arg(long, global = true, default_value_t = 2)]
+    retries: u32,
+
+    /// Path to ubx_messages.json
+    #[arg(long, global = true, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Stop GNSS and create a backup in flash; power can be removed once it succeeds
+    Backup,
+    /// Delete the backup in flash
+    Clear,
+    /// Report the restore status after startup; exits non-zero unless restored
+    Status,
+    /// Save the configuration, back up, power cycle and check the restore; prints PASS or FAIL
+    Test {
+        /// Shell command that power cycles the receiver (e.g. a relay or USB hub toggle); default: a
+        /// hardware reset with the BBR cleared, so only the flash backup survives it
+        #[arg(long)]
+        power_cycle: Option<String>,
+
+        /// Ask the operator to power cycle the receiver and press Enter
+        #[arg(long, conflicts_with = "power_cycle")]
+        prompt: bool,
+
+        /// Don't save the configuration to BBR and flash first
+        #[arg(long)]
+        no_save: bool,
+
+        /// Seconds to wait for the port to come back after the power cycle
+        #[arg(long, default_value_t = 10.0)]
+        startup_timeout: f64,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-sos: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn open(args: &Args) -> io::Result<Box<dyn SerialPort>> {
+    serialport::new(&args.port, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)
+}
+
+/// Open the port again after a power cycle, which may take a USB receiver
+/// a few seconds to enumerate.
+fn reopen(args: &Args, timeout: Duration) -> ubx_schema::Result<Box<dyn SerialPort>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match open(args) {
+            Ok(port) => return Ok(port),
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(250)),
+            Err(e) => return Err(Error::Config(format!("{} did not come back: {e}", args.port))),
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let sos = Sos::new(&schema);
+    let timeout = Duration::from_secs_f64(args.timeout);
+    let policy = RetryPolicy {
+        timeout,
+        retries: args.retries,
+        ..RetryPolicy::default()
+    };
+    let mut port = open(args)?;
+    let mut parser = FrameParser::new();
+    match &args.command {
+        Command::Backup => Ok(report_backup(sos.create_backup(&mut *port, &mut parser, &policy)?)),
+        Command::Clear => {
+            let cleared = sos.clear_backup(&mut *port, &mut parser, &policy)?;
+            match cleared {
+                Some(true) => eprintln!("ubx-sos: backup cleared"),
+                Some(false) => eprintln!("ubx-sos: clear not acknowledged"),
+                None => eprintln!("ubx-sos: no UPD-SOS reply"),
+            }
+            Ok(cleared == Some(true))
+        }
+        Command::Status => Ok(report_status(sos.restore_status(&mut *port, &mut parser, timeout)?)),
+        Command::Test {
+            power_cycle,
+            prompt,
+            no_save,
+            startup_timeout,
+        } => {
+            if !no_save {
+                let report = send_with_ack(&mut *port, &mut parser, &sos.save_config_frame()?, &policy)?;
+                if !report.is_ack() {
+                    println!("FAIL: CFG-CFG save: {report}");
+                    return Ok(false);
+                }
+                eprintln!("ubx-sos: configuration saved");
+            }
+            if !report_backup(sos.create_backup(&mut *port, &mut parser, &policy)?) {
+                println!("FAIL: backup");
+                return Ok(false);
+            }
+
+            if let Some(command) = power_cycle {
+                drop(port);
+                let status = Shell::new("sh").arg("-c").arg(command).status()?;
+                if !status.success() {
+                    return Err(Error::Config(format!("power cycle command failed: {status}")));
+                }
+            } else if *prompt {
+                drop(port);
+                eprintln!("ubx-sos: power cycle the receiver, then press Enter");
+                io::stdin().lock().read_line(&mut String::new())?;
+            } else {
+                port.write_all(&sos.hardware_reset_frame(true)?.to_bytes())?;
+                port.flush()?;
+                drop(port);
+                // Let the old port disappear before looking for the new one.
+                thread::sleep(Duration::from_secs(1));
+            }
+
+            let mut port = reopen(args, Duration::from_secs_f64(*startup_timeout))?;
+            let mut parser = FrameParser::new();
+            // The startup report comes once the receiver is up; allow for the boot.
+            let wait = timeout.max(Duration::from_secs(3));
+            let status = sos.restore_status(&mut *port, &mut parser, wait)?;
+            match status {
+                Some(RestoreStatus::Restored) => println!("PASS: {}", RestoreStatus::Restored),
+                Some(status) => println!("FAIL: {status}"),
+                None => println!("FAIL: no restore status after startup"),
+            }
+            Ok(status == Some(RestoreStatus::Restored))
+        }
+    }
+}
+
+fn report_backup(ack: Option<bool>) -> bool {
+    match ack {
+        Some(true) => eprintln!("ubx-sos: backup created; the receiver can be powered down"),
+        Some(false) => eprintln!("ubx-sos: backup not acknowledged"),
+        None => eprintln!("ubx-sos: no UPD-SOS reply to the backup command"),
+    }
+    ack == Some(true)
+}
+
+fn report_status(status: Option<RestoreStatus>) -> bool {
+    match status {
+        Some(status) => println!("{status}"),
+        None => eprintln!("ubx-sos: no UPD-SOS restore status"),
+    }
+    status == Some(RestoreStatus::Restored)
+}
@@ -0,0 +1,134 @@
+//! Log a UBX capture into a SQLite database for SQL analysis.
+//!
+//! Writes every frame to `frames` and each decoded message to a table per
+//! message type, by piping SQL into the `sqlite3` shell. Appending to an
+//! existing database adds rows to the tables already there.
+//!
+//! ```text
+//! ubx-sqlite drive.ubx -o drive.db --scale --expand-flags
+//! sqlite3 drive.db "SELECT frames.itow, lat, lon FROM nav_pvt JOIN frames ON frames.id = nav_pvt.frame"
+//! ubx-sqlite drive.ubx --sql -o drive.sql
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitCode, Stdio};
+
+use clap::Parser;
+use ubx_schema::filter::Filter;
+use ubx_schema::frame::frames;
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sqlite::SqlWriter;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-sqlite", about = "Write decoded UBX frames into a SQLite database")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Database to create or append to; with --sql, the script ('-' for stdout)
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Write the SQL script instead of running sqlite3
+    #[arg(long)]
+    sql: bool,
+
+    /// sqlite3 command-line shell
+    #[arg(long, default_value = "sqlite3")]
+    sqlite3: PathBuf,
+
+    /// Only store these messages (e.g. NAV-PVT); may be repeated or comma separated.
+    /// Frames of other types are still logged to `frames`.
+    #[arg(short, long, value_delimiter = ',')]
+    message: Vec<String>,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into one column per member
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// ubx-capture --timestamps CSV of the input; fills frames.host_time
+    #[arg(short, long)]
+    timestamps: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ubx-sqlite: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<bool> {
+    let schema = Schema::load(&args.schema)?;
+    let options = DecodeOptions {
+        scale: args.scale,
+        expand_flags: args.expand_flags,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let filter = Filter::default().include(&args.message);
+    let input = LogFile::open(&args.input)?;
+    let host_times = args.timestamps.as_ref().map(HostTimes::load).transpose()?;
+
+    let write = |out: &mut dyn Write| -> ubx_schema::Result<usize> {
+        let mut writer = SqlWriter::new(&schema, options, out)?;
+        if let Some(times) = &host_times {
+            writer = writer.with_host_times(times.clone());
+        }
+        for frame in frames(&input).flatten() {
+            let msg = decoder.decode_frame(&frame);
+            if filter.type_matches(&msg) {
+                writer.write(&frame, &msg)?;
+            } else {
+                writer.write_frame(&frame, &msg)?;
+            }
+        }
+        let frames = writer.frames;
+        writer.finish()?;
+        Ok(frames)
+    };
+
+    if args.sql {
+        let count = match args.output.as_os_str().to_str() {
+            Some("-") => write(&mut io::stdout().lock())?,
+            _ => write(&mut BufWriter::new(File::create(&args.output)?))?,
+        };
+        eprintln!("ubx-sqlite: {count} frames written to {}", args.output.display());
+        return Ok(true);
+    }
+
+    let mut child = Command::new(&args.sqlite3)
+        .arg("-bail")
+        .arg(&args.output)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run {}: {e}", args.sqlite3.display())))?;
+    let mut stdin = BufWriter::new(child.stdin.take().expect("stdin is piped"));
+    let count = write(&mut stdin)?;
+    drop(stdin);
+    let status = child.wait()?;
+    if !status.success() {
+        eprintln!("ubx-sqlite: {} failed: {status}", args.sqlite3.display());
+        return Ok(false);
+    }
+    eprintln!("ubx-sqlite: {count} frames stored in {}", args.output.display());
+    Ok(true)
+}
@@ -0,0 +1,118 @@
+//! Message counts, rates, jitter and iTOW gaps for a .ubx log, and drops
+//! shown by the counters and time tags of LOG, ESF-MEAS and MON-COMMS.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::sequence::SequenceTracker;
+use ubx_schema::stats::LogStats;
+use ubx_schema::ucenter;
+use ubx_schema::{Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-stats", about = "Message rate and gap analysis for UBX logs")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// List every gap, not just the totals
+    #[arg(long)]
+    gaps: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-stats: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::new(&schema);
+    let input = LogFile::open(&args.input)?;
+
+    let mut stats = LogStats::new();
+    let mut sequences = SequenceTracker::new();
+    let mut iter = frames(&input);
+    for frame in iter.by_ref() {
+        match frame {
+            Ok(frame) => {
+                let msg = decoder.decode_frame(&frame);
+                stats.push(&msg, frame.raw.len());
+                sequences.push(&msg);
+            }
+            Err(e) => stats.push_error(&schema, &e),
+        }
+    }
+    stats.scan = iter.stats();
+
+    let opt = |v: Option<f64>, prec: usize| v.map_or("-".to_string(), |v| format!("{v:.prec$}"));
+    println!(
+        "{:<24} {:>8} {:>10} {:>8} {:>10} {:>9} {:>6} {:>8} {:>6}",
+        "message", "count", "bytes", "rate Hz", "mean ms", "jitter", "gaps", "missing", "cksum"
+    );
+    for m in stats.messages.values() {
+        println!(
+            "{:<24} {:>8} {:>10} {:>8} {:>10} {:>9} {:>6} {:>8} {:>6}",
+            m.name,
+            m.count,
+            m.bytes,
+            opt(m.rate_hz(), 2),
+            opt(m.mean_interval(), 1),
+            opt(m.jitter(), 2),
+            m.gaps().len(),
+            m.missing_epochs(),
+            m.checksum_errors
+        );
+        if args.gaps {
+            for gap in m.gaps() {
+                println!("    gap after iTOW {} until {}: {} missing", gap.after, gap.before, gap.missing);
+            }
+        }
+        if m.out_of_order > 0 {
+            println!("    {} messages with repeated or decreasing iTOW", m.out_of_order);
+        }
+    }
+    let reports = sequences.reports();
+    if !reports.is_empty() {
+        println!();
+        println!("{:<40} {:>8} {:>6} {:>10} {:>6}", "sequence", "count", "gaps", "missing", "resets");
+    }
+    for r in &reports {
+        println!("{:<40} {:>8} {:>6} {:>10} {:>6}", r.name, r.count, r.gaps.len(), r.missing(), r.resets);
+        if args.gaps {
+            for gap in &r.gaps {
+                println!("    gap after {} until {}: {} missing", gap.after, gap.before, gap.missing);
+            }
+        }
+    }
+    println!();
+    println!(
+        "{} frames over {:.1} s of receiver time, {} checksum errors, {} bytes outside frames",
+        stats.scan.frames,
+        stats.duration(),
+        stats.scan.checksum_errors,
+        stats.scan.skipped_bytes
+    );
+    let container = ucenter::scan(&input);
+    if container.container() != ucenter::Container::Ubx {
+        println!(
+            "between frames: {} NMEA sentences, {} RTCM messages",
+            container.nmea_sentences, container.rtcm_messages
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Timepulse quantization error, time source and UTC validity statistics
+//! of a .ubx log, from TIM-TP and NAV-TIMEUTC.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::timing::{Statistics, TimingAnalyzer};
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-timing", about = "Timepulse and UTC statistics for timing receivers")]
+struct Args {
+    /// Input .ubx file ('-' for stdin)
+    input: PathBuf,
+
+    /// List every change of the timepulse time source
+    #[arg(long)]
+    changes: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-timing: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn describe(stats: &Statistics, unit: &str) -> String {
+    match (stats.mean(), stats.std_dev(), stats.rms()) {
+        (Some(mean), Some(sd), Some(rms)) => format!(
+            "mean {mean:.1} {unit}, std dev {sd:.1} {unit}, rms {rms:.1} {unit}, min {} {unit}, max {} {unit}",
+            stats.min, stats.max
+        ),
+        _ => "-".to_string(),
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut timing = TimingAnalyzer::new();
+    for frame in frames(&input).flatten() {
+        // TIM-TP (0x0D 0x01) and NAV-TIMEUTC (0x01 0x21)
+        if matches!((frame.class, frame.id), (0x0D, 0x01) | (0x01, 0x21)) {
+            timing.push(&decoder.decode_frame(&frame));
+        }
+    }
+    let report = timing.report();
+
+    if report.pulses > 0 {
+        println!("TIM-TP: {} pulses, {} missed", report.pulses, report.missed_pulses);
+        println!("  qErr: {}", describe(&report.q_err, "ps"));
+        println!(
+            "  qErr invalid {}, not locked {}, RAIM active {}",
+            report.q_err_invalid, report.not_locked, report.raim_active
+        );
+        let sources: Vec<String> = report.sources.iter().map(|(s, n)| format!("{s} {n}")).collect();
+        println!("  sources: {}; {} changes", sources.join(", "), report.source_changes.len());
+        if args.changes {
+            for c in &report.source_changes {
+                println!("    week {} towMS {}: {} -> {}", c.week, c.tow_ms, c.from, c.to);
+            }
+        }
+    }
+    if report.utc_epochs > 0 {
+        let pct = 100.0 * report.utc_valid as f64 / report.utc_epochs as f64;
+        let ttu = report.time_to_utc_ms.map_or("-".to_string(), |ms| format!("{:.1} s", ms as f64 / 1_000.0));
+        println!("NAV-TIMEUTC: {} epochs, UTC valid {pct:.1}%", report.utc_epochs);
+        println!("  time to valid UTC {ttu}, {} losses", report.utc_losses);
+        println!("  tAcc: {}", describe(&report.t_acc, "ns"));
+    }
+    if report.pulses + report.utc_epochs == 0 {
+        eprintln!("ubx-timing: no TIM-TP or NAV-TIMEUTC in the log");
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Export NAV-PVT / NAV-HPPOSLLH positions from a .ubx log as GPX or KML.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use ubx_schema::frame::frames;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::track::{write_gpx, write_kml, TrackBuilder};
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TrackFormat {
+    Gpx,
+    Kml,
+}
+
+#[derive(Parser)]
+#[command(name = "ubx-track", about = "Export NAV-PVT tracks as GPX or KML")]
+struct Args {
+    /// Input .ubx file
+    input: PathBuf,
+
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format (default: from the output extension, else gpx)
+    #[arg(short, long, value_enum)]
+    format: Option<TrackFormat>,
+
+    /// Track name (default: input file name)
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Keep epochs without a valid position fix
+    #[arg(long)]
+    include_invalid: bool,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-track: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: false,
+        },
+    );
+    let input = LogFile::open(&args.input)?;
+
+    let mut track = TrackBuilder::new().include_invalid(args.include_invalid);
+    for frame in frames(&input).flatten() {
+        // NAV-PVT (0x07) and NAV-HPPOSLLH (0x14)
+        if frame.class == 0x01 && matches!(frame.id, 0x07 | 0x14) {
+            track.push(&decoder.decode_frame(&frame));
+        }
+    }
+
+    let format = args.format.unwrap_or_else(|| {
+        let ext = args.output.as_ref().and_then(|p| p.extension()).and_then(|e| e.to_str());
+        match ext.map(str::to_ascii_lowercase).as_deref() {
+            Some("kml") => TrackFormat::Kml,
+            _ => TrackFormat::Gpx,
+        }
+    });
+    let name = args.name.clone().unwrap_or_else(|| {
+        args.input
+            .file_stem()
+            .map_or_else(|| "track".into(), |s| s.to_string_lossy().into_owned())
+    });
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    match format {
+        TrackFormat::Gpx => write_gpx(&name, track.points(), &mut out)?,
+        TrackFormat::Kml => write_kml(&name, track.points(), &mut out)?,
+    }
+    out.flush()?;
+    eprintln!("ubx-track: {} points", track.points().len());
+    Ok(())
+}
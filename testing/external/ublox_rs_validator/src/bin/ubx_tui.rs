@@ -0,0 +1,246 @@
+//! Live terminal dashboard for a receiver on a serial port: position and fix,
+//! per-constellation satellite counts, C/N0 bars, message rates and errors.
+
+use std::io::{self, ErrorKind, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, List, ListItem, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use ubx_schema::config::parse_ack;
+use ubx_schema::dashboard::Dashboard;
+use ubx_schema::frame::FrameParser;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-tui", about = "Live terminal dashboard for a UBX serial stream")]
+struct Args {
+    /// Serial port, e.g. /dev/ttyACM0 or COM3
+    port: String,
+
+    /// Baud rate
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+/// What the serial reader thread hands to the UI.
+enum Input {
+    Data(Vec<u8>),
+    Failed(String),
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-tui: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let mut port = serialport::new(&args.port, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            let input = match port.read(&mut buf) {
+                Ok(0) => continue,
+                Ok(n) => Input::Data(buf[..n].to_vec()),
+                Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+                Err(e) => Input::Failed(format!("read failed: {e}")),
+            };
+            let failed = matches!(input, Input::Failed(_));
+            if tx.send(input).is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let title = format!("ubx-tui — {} @ {} baud (q to quit)", args.port, args.baud);
+    ratatui::run(|terminal| ui_loop(terminal, &schema, &rx, &title))?;
+    Ok(())
+}
+
+fn ui_loop(terminal: &mut DefaultTerminal, schema: &Schema, rx: &Receiver<Input>, title: &str) -> io::Result<()> {
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(schema, options);
+    let mut parser = FrameParser::new();
+    let mut dashboard = Dashboard::new();
+    let started = Instant::now();
+    let mut connected = true;
+    loop {
+        let now = started.elapsed().as_secs_f64();
+        while connected {
+            match rx.try_recv() {
+                Ok(Input::Data(bytes)) => parser.push(&bytes),
+                Ok(Input::Failed(e)) => dashboard.push_error(e, now),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => connected = false,
+            }
+        }
+        while let Some(result) = parser.next_frame() {
+            match result {
+                Ok((_, frame)) => {
+                    if let Some((class, id, false)) = parse_ack(&frame) {
+                        let name = schema
+                            .messages_by_ids(class, id)
+                            .next()
+                            .map_or_else(|| format!("{class:02X}-{id:02X}"), |m| m.short_name().to_string());
+                        dashboard.push_error(format!("ACK-NAK for {name}"), now);
+                    }
+                    dashboard.push(&decoder.decode(frame.class, frame.id, &frame.payload), now);
+                }
+                Err(e) => dashboard.push_error(e.to_string(), now),
+            }
+        }
+
+        terminal.draw(|frame| draw(frame, &dashboard, title, now))?;
+
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+                if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, title: &str, now: f64) {
+    let outer = Block::bordered().title(title);
+    let area = outer.inner(frame.area());
+    frame.render_widget(outer, frame.area());
+
+    let [top, middle, bottom] =
+        Layout::vertical([Constraint::Length(10), Constraint::Min(8), Constraint::Length(12)]).areas(area);
+    let [position_area, gnss_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(top);
+    let [rates_area, errors_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(bottom);
+
+    draw_position(frame, dashboard, position_area);
+    draw_constellations(frame, dashboard, gnss_area);
+    draw_cno(frame, dashboard, middle);
+    draw_rates(frame, dashboard, rates_area, now);
+    draw_errors(frame, dashboard, errors_area, now);
+}
+
+fn draw_position(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let lines = match &dashboard.position {
+        Some(p) => {
+            let time = p.time.map_or_else(|| "—".to_string(), |t| t.to_iso8601());
+            let pdop = p.pdop.map_or_else(|| "—".to_string(), |d| format!("{d:.2}"));
+            vec![
+                Line::from(format!("Fix      {}", p.fix.label())),
+                Line::from(format!("UTC      {time}")),
+                Line::from(format!("iTOW     {} ms", p.itow)),
+                Line::from(format!("Lat/Lon  {:.8}, {:.8}", p.lat, p.lon)),
+                Line::from(format!("Height   {:.3} m MSL", p.alt_msl)),
+                Line::from(format!("Acc      h {:.3} m  v {:.3} m", p.h_acc, p.v_acc)),
+                Line::from(format!("Speed    {:.2} m/s", p.speed)),
+                Line::from(format!("SVs      {}  pDOP {pdop}", p.num_sv)),
+            ]
+        }
+        None => vec![Line::from("waiting for NAV-PVT")],
+    };
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title("Position")), area);
+}
+
+fn draw_constellations(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let rows = dashboard.constellations().into_iter().map(|c| {
+        Row::new(vec![
+            c.gnss.name().to_string(),
+            c.tracked.to_string(),
+            c.used.to_string(),
+            format!("{:.1}", c.mean_cno),
+        ])
+    });
+    let widths = [Constraint::Length(9), Constraint::Length(8), Constraint::Length(6), Constraint::Length(10)];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["GNSS", "tracked", "used", "C/N0 dBHz"]).style(Style::new().fg(Color::Yellow)))
+        .block(Block::bordered().title("Satellites (NAV-SAT)"));
+    frame.render_widget(table, area);
+}
+
+/// Bar colour by C/N0: the usual u-center thresholds.
+fn cno_color(cno: u8, used: bool) -> Color {
+    match cno {
+        _ if !used => Color::Gray,
+        0..=19 => Color::Red,
+        20..=29 => Color::LightRed,
+        30..=39 => Color::Yellow,
+        _ => Color::Green,
+    }
+}
+
+fn draw_cno(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let bars: Vec<Bar> = dashboard
+        .satellites
+        .iter()
+        .filter(|s| s.cno > 0)
+        .map(|s| {
+            Bar::default()
+                .value(u64::from(s.cno))
+                .label(Line::from(s.label()))
+                .style(Style::new().fg(cno_color(s.cno, s.used)))
+        })
+        .collect();
+    let chart = BarChart::default()
+        .block(Block::bordered().title("C/N0 (grey: not used)"))
+        .bar_width(3)
+        .bar_gap(1)
+        .max(55)
+        .data(BarGroup::default().bars(&bars));
+    frame.render_widget(chart, area);
+}
+
+fn draw_rates(frame: &mut Frame, dashboard: &Dashboard, area: Rect, now: f64) {
+    let rows = dashboard
+        .rates(now)
+        .into_iter()
+        .map(|(name, rate)| Row::new(vec![name.to_string(), format!("{rate:.1}")]));
+    let table = Table::new(rows, [Constraint::Min(16), Constraint::Length(7)])
+        .header(Row::new(["message", "Hz"]).style(Style::new().fg(Color::Yellow)))
+        .block(Block::bordered().title(format!("Rates ({} frames)", dashboard.frames)));
+    frame.render_widget(table, area);
+}
+
+fn draw_errors(frame: &mut Frame, dashboard: &Dashboard, area: Rect, now: f64) {
+    let visible = usize::from(area.height.saturating_sub(2));
+    let items: Vec<ListItem> = dashboard
+        .errors
+        .iter()
+        .rev()
+        .take(visible)
+        .map(|(t, text)| ListItem::new(format!("{:>6.0}s ago  {text}", now - t)))
+        .collect();
+    let list = List::new(items)
+        .style(Style::new().fg(Color::LightRed))
+        .block(Block::bordered().title(format!("Recent errors ({})", dashboard.errors.len())));
+    frame.render_widget(list, area);
+}
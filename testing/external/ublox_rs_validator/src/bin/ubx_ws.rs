@@ -0,0 +1,194 @@
+//! Serve decoded UBX messages as JSON over WebSocket.
+//!
+//! Each client receives one text message per decoded frame, in the same
+//! shape `ubx-decode` produces. Clients choose what they receive through the
+//! URL query, e.g. `ws://localhost:8765/?messages=NAV-PVT,NAV-SAT`.
+
+use std::io::{self, ErrorKind, Read};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tungstenite::Message;
+use ubx_schema::broadcast::{parse_query, Broadcaster};
+use ubx_schema::filter::Filter;
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::mmap::LogFile;
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::time::itow_delta;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+#[derive(Parser)]
+#[command(name = "ubx-ws", about = "Stream decoded UBX messages to WebSocket clients")]
+struct Args {
+    /// Read from this serial port
+    #[arg(short, long, required_unless_present = "input", conflicts_with = "input")]
+    port: Option<String>,
+
+    /// Baud rate for --port
+    #[arg(short, long, default_value_t = 38_400)]
+    baud: u32,
+
+    /// Read from a .ubx file instead, paced by iTOW
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Speed-up factor for --input; 0 sends as fast as possible
+    #[arg(short, long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Replay --input repeatedly until interrupted
+    #[arg(long = "loop")]
+    repeat: bool,
+
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8765")]
+    listen: String,
+
+    /// Messages buffered per client before messages are dropped for it
+    #[arg(long, default_value_t = 1_024)]
+    buffer: usize,
+
+    /// Apply scale factors to produce physical values
+    #[arg(long)]
+    scale: bool,
+
+    /// Expand bitfields into named members
+    #[arg(long)]
+    expand_flags: bool,
+
+    /// ubx-capture --timestamps CSV of the input; adds each message's host_time
+    #[arg(short, long, requires = "input")]
+    timestamps: Option<PathBuf>,
+
+    /// Path to ubx_messages.json
+    #[arg(long, default_value_os_t = default_schema_path())]
+    schema: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ubx-ws: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> ubx_schema::Result<()> {
+    let schema = Schema::load(&args.schema)?;
+    let decoder = Decoder::with_options(
+        &schema,
+        DecodeOptions {
+            scale: args.scale,
+            expand_flags: args.expand_flags,
+            ..DecodeOptions::default()
+        },
+    );
+    let hub = Arc::new(Mutex::new(Broadcaster::new()));
+
+    let listener = TcpListener::bind(&args.listen)?;
+    eprintln!("ubx-ws: listening on ws://{}", listener.local_addr()?);
+    {
+        let (hub, buffer) = (Arc::clone(&hub), args.buffer);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let hub = Arc::clone(&hub);
+                thread::spawn(move || serve(stream, &hub, buffer));
+            }
+        });
+    }
+
+    let publish = |msg, host_time| {
+        hub.lock().unwrap().publish_timed(&msg, host_time);
+    };
+    if let Some(path) = &args.input {
+        let input = LogFile::open(path)?;
+        let host_times = args.timestamps.as_ref().map(HostTimes::load).transpose()?.unwrap_or_default();
+        loop {
+            // A file would be gone before anyone connected; wait for a client.
+            while hub.lock().unwrap().is_empty() {
+                thread::sleep(Duration::from_millis(100));
+            }
+            let mut last_itow = None;
+            for frame in frames(&input).flatten() {
+                let msg = decoder.decode_frame(&frame);
+                if let (Some(prev), Some(itow)) = (last_itow, msg.itow()) {
+                    let delta = itow_delta(prev, itow);
+                    if delta > 0 && args.speed > 0.0 {
+                        thread::sleep(Duration::from_secs_f64(delta as f64 / 1_000.0 / args.speed));
+                    }
+                }
+                last_itow = msg.itow().or(last_itow);
+                publish(msg, host_times.at(frame.offset));
+            }
+            if !args.repeat {
+                return Ok(());
+            }
+        }
+    }
+
+    let Some(port_name) = &args.port else {
+        unreachable!("clap enforces port or input");
+    };
+    let mut port = serialport::new(port_name, args.baud)
+        .timeout(Duration::from_millis(100))
+        .open()
+        .map_err(io::Error::from)?;
+    let mut parser = FrameParser::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = match port.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+            Err(e) => return Err(e.into()),
+        };
+        parser.push(&buf[..n]);
+        while let Some(result) = parser.next_frame() {
+            if let Ok((_, frame)) = result {
+                publish(decoder.decode(frame.class, frame.id, &frame.payload), None);
+            }
+        }
+    }
+}
+
+/// Handshake callback that takes the client's filter from the request URL.
+struct Subscribe<'a>(&'a mut Filter);
+
+impl Callback for Subscribe<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        *self.0 = parse_query(request.uri().query().unwrap_or(""));
+        Ok(response)
+    }
+}
+
+/// Handshake with one client and forward its subscription until it goes away.
+fn serve(stream: TcpStream, hub: &Mutex<Broadcaster>, buffer: usize) {
+    let peer = stream.peer_addr().map_or_else(|_| "?".to_string(), |a| a.to_string());
+    let mut filter = Filter::default();
+    let mut socket = match tungstenite::accept_hdr(stream, Subscribe(&mut filter)) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("ubx-ws: {peer}: handshake failed: {e}");
+            return;
+        }
+    };
+    let (id, rx) = hub.lock().unwrap().subscribe(filter, buffer);
+    eprintln!("ubx-ws: {peer} connected");
+    for text in rx {
+        if socket.send(Message::text(text.to_string())).is_err() {
+            break;
+        }
+    }
+    let dropped = hub.lock().unwrap().dropped(id).unwrap_or(0);
+    eprintln!("ubx-ws: {peer} disconnected ({dropped} messages dropped)");
+}
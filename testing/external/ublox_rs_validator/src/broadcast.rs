@@ -0,0 +1,108 @@
+//! Fan decoded messages out to subscribers, each with its own message filter.
+//!
+//! Used by `ubx-ws`: every WebSocket client owns a bounded channel of JSON
+//! texts. A message is serialized once, however many clients want it. A
+//! client that falls behind loses messages rather than stalling the source,
+//! and a client whose receiver is gone is dropped on the next publish.
+
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+
+use crate::decode::DecodedMessage;
+use crate::filter::Filter;
+use crate::host_time::Timed;
+
+/// Parse a subscription from a URL query such as
+/// `messages=NAV-PVT,NAV-SAT&exclude=NAV-SVIN`. Unknown parameters are ignored.
+pub fn parse_query(query: &str) -> Filter {
+    let mut filter = Filter::default();
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let names = value.split(',').filter(|n| !n.is_empty());
+        filter = match key {
+            "messages" | "message" | "m" => filter.include(names),
+            "exclude" | "x" => filter.exclude(names),
+            _ => filter,
+        };
+    }
+    filter
+}
+
+struct Client {
+    id: usize,
+    filter: Filter,
+    tx: SyncSender<Arc<str>>,
+    dropped: usize,
+}
+
+#[derive(Default)]
+pub struct Broadcaster {
+    clients: Vec<Client>,
+    next_id: usize,
+}
+
+impl Broadcaster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a subscriber that buffers up to `capacity` messages. Returns
+    /// its ID and the receiving end of its channel.
+    pub fn subscribe(&mut self, filter: Filter, capacity: usize) -> (usize, Receiver<Arc<str>>) {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.clients.push(Client {
+            id,
+            filter,
+            tx,
+            dropped: 0,
+        });
+        (id, rx)
+    }
+
+    /// Number of connected subscribers.
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Messages dropped so far for subscriber `id` because its buffer was full.
+    pub fn dropped(&self, id: usize) -> Option<usize> {
+        self.clients.iter().find(|c| c.id == id).map(|c| c.dropped)
+    }
+
+    /// Send `msg` as JSON to every subscriber whose filter accepts it and
+    /// return how many received it.
+    pub fn publish(&mut self, msg: &DecodedMessage) -> usize {
+        self.publish_timed(msg, None)
+    }
+
+    /// As [`Broadcaster::publish`], with the message's `host_time`.
+    pub fn publish_timed(&mut self, msg: &DecodedMessage, host_time: Option<f64>) -> usize {
+        let timed = Timed { host_time, message: msg };
+        let mut json: Option<Arc<str>> = None;
+        let mut delivered = 0;
+        self.clients.retain_mut(|client| {
+            if !client.filter.type_matches(msg) {
+                return true;
+            }
+            let text = json.get_or_insert_with(|| serde_json::to_string(&timed).unwrap_or_default().into());
+            match client.tx.try_send(Arc::clone(text)) {
+                Ok(()) => {
+                    delivered += 1;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    client.dropped += 1;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+        delivered
+    }
+}
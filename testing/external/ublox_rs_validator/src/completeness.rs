@@ -0,0 +1,165 @@
+//! Schema completeness against the official message index.
+//!
+//! `data/messages/message_index.json` lists every (class, id, name) the
+//! u-blox interface descriptions document, with the product generations
+//! whose manuals document it; `validation/scripts/build_message_index.py`
+//! rebuilds it from the PDF inventory. A documented message is covered when
+//! the schema defines it under that name or a variant alias, or splits it
+//! into variants (`UBX-CFG-DAT` as `UBX-CFG-DAT-GET` and `-SET`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value as Json;
+
+use crate::error::{Error, Result};
+use crate::schema::{normalize_name, parse_hex_id, Schema};
+
+pub fn default_index_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/messages/message_index.json")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub name: String,
+    pub class_id: u8,
+    pub message_id: u8,
+    /// Generations whose manuals document the message, oldest first.
+    pub generations: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Generation {
+    pub name: String,
+    pub manuals: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageIndex {
+    /// Oldest first.
+    pub generations: Vec<Generation>,
+    pub messages: Vec<IndexEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawIndex {
+    generations: Vec<Generation>,
+    messages: Vec<RawEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    name: String,
+    class_id: Json,
+    message_id: Json,
+    #[serde(default)]
+    generations: Vec<String>,
+}
+
+impl MessageIndex {
+    pub fn load_default() -> Result<Self> {
+        Self::load(default_index_path())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+        Self::from_json_str(&text)
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let raw: RawIndex = serde_json::from_str(text)?;
+        let messages = raw
+            .messages
+            .into_iter()
+            .map(|e| {
+                let id = |v: &Json| parse_hex_id(v).ok_or_else(|| Error::Schema(format!("{}: bad ID {v}", e.name)));
+                Ok(IndexEntry {
+                    class_id: id(&e.class_id)?,
+                    message_id: id(&e.message_id)?,
+                    name: normalize_name(&e.name),
+                    generations: e.generations,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            generations: raw.generations,
+            messages,
+        })
+    }
+}
+
+/// A documented message the schema defines with different IDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdMismatch {
+    pub name: String,
+    pub documented: (u8, u8),
+    pub schema: (u8, u8),
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationReport {
+    pub name: String,
+    pub manuals: usize,
+    pub documented: usize,
+    /// Documented messages the schema has no definition for.
+    pub missing: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Completeness {
+    pub generations: Vec<GenerationReport>,
+    pub id_mismatches: Vec<IdMismatch>,
+    /// Schema messages no manual in the index documents.
+    pub undocumented: Vec<String>,
+}
+
+impl Completeness {
+    pub fn is_complete(&self) -> bool {
+        self.id_mismatches.is_empty() && self.generations.iter().all(|g| g.missing.is_empty())
+    }
+}
+
+/// Compare the schema's definitions with the index.
+pub fn check(schema: &Schema, index: &MessageIndex) -> Completeness {
+    let mut report = Completeness::default();
+    let mut missing = Vec::new();
+    for entry in &index.messages {
+        if let Some(def) = schema.message_by_name(&entry.name) {
+            if (def.class_id, def.message_id) != (entry.class_id, entry.message_id) {
+                report.id_mismatches.push(IdMismatch {
+                    name: entry.name.clone(),
+                    documented: (entry.class_id, entry.message_id),
+                    schema: (def.class_id, def.message_id),
+                });
+            }
+        } else if !schema.messages.iter().any(|m| is_variant_of(&m.name, &entry.name)) {
+            missing.push(entry);
+        }
+    }
+
+    for generation in &index.generations {
+        let name = &generation.name;
+        report.generations.push(GenerationReport {
+            name: name.clone(),
+            manuals: generation.manuals.len(),
+            documented: index.messages.iter().filter(|e| e.generations.contains(name)).count(),
+            missing: missing.iter().filter(|e| e.generations.contains(name)).map(|&e| e.clone()).collect(),
+        });
+    }
+
+    let documented = |name: &str| index.messages.iter().any(|e| e.name == name || is_variant_of(name, &e.name));
+    for msg in &schema.messages {
+        if !documented(&msg.name) && !msg.variant_aliases.iter().any(|a| documented(&normalize_name(a))) {
+            report.undocumented.push(msg.name.clone());
+        }
+    }
+    report
+}
+
+/// Whether `name` is a variant split out of `base`, e.g. `UBX-CFG-DAT-GET`
+/// of `UBX-CFG-DAT`.
+fn is_variant_of(name: &str, base: &str) -> bool {
+    name.strip_prefix(base).is_some_and(|rest| rest.starts_with('-'))
+}
@@ -0,0 +1,651 @@
+//! Configuration key database (`data/config_keys/unified_config_keys.json`),
+//! CFG-VALSET generation and CFG-VALGET decoding for generation 9 and later
+//! receivers.
+//!
+//! Values are given as JSON in the natural shape for each key type: booleans
+//! for `L`, numbers for integer and float keys, constant names or numbers for
+//! `E` keys, and numbers or objects of bit names for `X` keys. Values read
+//! back from CFG-VALGET take the same shape.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value as Json};
+
+use crate::error::{Error, Result};
+use crate::frame::Frame;
+
+/// Default key database location, relative to this crate.
+pub fn default_keys_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/config_keys/unified_config_keys.json")
+}
+
+/// Maximum number of key/value pairs the receiver accepts in one CFG-VALSET.
+pub const MAX_KEYS_PER_VALSET: usize = 64;
+
+/// Configuration key value types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyType {
+    L,
+    U1,
+    U2,
+    U4,
+    U8,
+    I1,
+    I2,
+    I4,
+    I8,
+    E1,
+    E2,
+    E4,
+    X1,
+    X2,
+    X4,
+    X8,
+    R4,
+    R8,
+}
+
+impl KeyType {
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "L" => KeyType::L,
+            "U1" => KeyType::U1,
+            "U2" => KeyType::U2,
+            "U4" => KeyType::U4,
+            "U8" => KeyType::U8,
+            "I1" => KeyType::I1,
+            "I2" => KeyType::I2,
+            "I4" => KeyType::I4,
+            "I8" => KeyType::I8,
+            "E1" => KeyType::E1,
+            "E2" => KeyType::E2,
+            "E4" => KeyType::E4,
+            "X1" => KeyType::X1,
+            "X2" => KeyType::X2,
+            "X4" => KeyType::X4,
+            "X8" => KeyType::X8,
+            "R4" => KeyType::R4,
+            "R8" => KeyType::R8,
+            _ => return None,
+        })
+    }
+
+    /// Encoded size in bytes; `L` occupies one byte on the wire.
+    pub fn size(self) -> usize {
+        match self {
+            KeyType::L | KeyType::U1 | KeyType::I1 | KeyType::E1 | KeyType::X1 => 1,
+            KeyType::U2 | KeyType::I2 | KeyType::E2 | KeyType::X2 => 2,
+            KeyType::U4 | KeyType::I4 | KeyType::E4 | KeyType::X4 | KeyType::R4 => 4,
+            KeyType::U8 | KeyType::I8 | KeyType::X8 | KeyType::R8 => 8,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, KeyType::I1 | KeyType::I2 | KeyType::I4 | KeyType::I8)
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, KeyType::R4 | KeyType::R8)
+    }
+}
+
+impl std::fmt::Display for KeyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Storage size in bytes encoded in bits 28-30 of a key ID.
+pub fn key_id_size(key_id: u32) -> Option<usize> {
+    match (key_id >> 28) & 0x7 {
+        1 | 2 => Some(1),
+        3 => Some(2),
+        4 => Some(4),
+        5 => Some(8),
+        _ => None,
+    }
+}
+
+/// A named bit range within an `X` key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBits {
+    pub name: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigKey {
+    /// Full name, e.g. `CFG-RATE-MEAS`.
+    pub name: String,
+    pub key_id: u32,
+    pub data_type: KeyType,
+    pub unit: Option<String>,
+    /// Multiplier from the raw value to the physical quantity in `unit`.
+    pub scale: Option<f64>,
+    /// Named constants of `E` (and some `X`) keys.
+    pub constants: Vec<(String, i64)>,
+    pub bits: Vec<KeyBits>,
+    pub description: String,
+}
+
+impl ConfigKey {
+    /// Wire size of the value; taken from the key ID, which is authoritative
+    /// when it disagrees with the documented type.
+    pub fn size(&self) -> usize {
+        key_id_size(self.key_id).unwrap_or_else(|| self.data_type.size())
+    }
+
+    pub fn constant(&self, name: &str) -> Option<i64> {
+        self.constants
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+
+    /// Encode a value as little-endian bytes. With `scaled`, numbers for
+    /// keys with a scale factor are physical quantities.
+    pub fn encode_value(&self, value: &Json, scaled: bool) -> Result<Vec<u8>> {
+        let size = self.size();
+        let err = |msg: String| Error::Config(format!("{}: {msg}", self.name));
+        if self.data_type.is_float() {
+            let mut x = value.as_f64().ok_or_else(|| err(format!("expected a number, got {value}")))?;
+            if let (true, Some(scale)) = (scaled, self.scale) {
+                x /= scale;
+            }
+            return Ok(if size == 4 {
+                (x as f32).to_le_bytes().to_vec()
+            } else {
+                x.to_le_bytes().to_vec()
+            });
+        }
+
+        let raw: i128 = match (self.data_type, value) {
+            (KeyType::L, Json::Bool(b)) => i128::from(*b),
+            (_, Json::String(s)) => match self.constant(s).or_else(|| self.bits_mask(s)) {
+                Some(v) => i128::from(v),
+                None => parse_int(s).ok_or_else(|| err(format!("unknown constant '{s}'")))?,
+            },
+            (_, Json::Object(members)) if !self.bits.is_empty() => self.encode_bits(members)?,
+            (_, Json::Number(n)) => {
+                if let (true, Some(scale)) = (scaled, self.scale) {
+                    (n.as_f64().unwrap_or(0.0) / scale).round() as i128
+                } else if let Some(i) = n.as_i64() {
+                    i128::from(i)
+                } else if let Some(u) = n.as_u64() {
+                    i128::from(u)
+                } else {
+                    return Err(err(format!("expected an integer, got {n}")));
+                }
+            }
+            _ => return Err(err(format!("invalid value {value}"))),
+        };
+
+        let bits = size as u32 * 8;
+        let (min, max) = match self.data_type {
+            KeyType::L => (0, 1),
+            t if t.is_signed() => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+            _ => (0, (1i128 << bits) - 1),
+        };
+        if !(min..=max).contains(&raw) {
+            return Err(err(format!("value {raw} out of range {min}..={max}")));
+        }
+        Ok((raw as u64).to_le_bytes()[..size].to_vec())
+    }
+
+    /// Decode little-endian value bytes, the inverse of
+    /// [`encode_value`](Self::encode_value): constant names for `E` keys
+    /// where one matches, and an object of bit names for `X` keys that
+    /// define bits. With `scaled`, keys with a scale factor give physical
+    /// quantities.
+    pub fn decode_value(&self, bytes: &[u8], scaled: bool) -> Result<Json> {
+        let size = self.size();
+        if bytes.len() != size {
+            return Err(Error::Config(format!("{}: expected {size} value bytes, got {}", self.name, bytes.len())));
+        }
+        let scale = self.scale.filter(|_| scaled);
+        if self.data_type.is_float() {
+            let x = if size == 4 {
+                f64::from(f32::from_le_bytes(bytes.try_into().unwrap()))
+            } else {
+                f64::from_le_bytes(bytes.try_into().unwrap())
+            };
+            return Ok(Json::from(x * scale.unwrap_or(1.0)));
+        }
+
+        let raw = le_u64(bytes);
+        let signed = self.data_type.is_signed() && size < 8 && (raw >> (size * 8 - 1)) & 1 == 1;
+        let int = if signed { raw as i64 - (1i64 << (size * 8)) } else { raw as i64 };
+        Ok(match self.data_type {
+            KeyType::L => Json::Bool(raw != 0),
+            KeyType::E1 | KeyType::E2 | KeyType::E4 => match self.constants.iter().find(|(_, v)| *v == int) {
+                Some((name, _)) => Json::from(name.as_str()),
+                None => Json::from(int),
+            },
+            KeyType::X1 | KeyType::X2 | KeyType::X4 | KeyType::X8 if !self.bits.is_empty() => {
+                let bits = self.bits.iter().map(|b| {
+                    let v = raw >> b.start & mask(b.end - b.start + 1);
+                    let v = if b.start == b.end { Json::Bool(v == 1) } else { Json::from(v) };
+                    (b.name.clone(), v)
+                });
+                Json::Object(bits.collect())
+            }
+            _ => match scale {
+                Some(scale) if self.data_type.is_signed() => Json::from(int as f64 * scale),
+                Some(scale) => Json::from(raw as f64 * scale),
+                None if self.data_type.is_signed() => Json::from(int),
+                None => Json::from(raw),
+            },
+        })
+    }
+
+    /// Mask covering a named bit range, so `"PRN131"` can stand for its bit.
+    fn bits_mask(&self, name: &str) -> Option<i64> {
+        let b = self.bits.iter().find(|b| b.name.eq_ignore_ascii_case(name))?;
+        Some((mask(b.end - b.start + 1) << b.start) as i64)
+    }
+
+    fn encode_bits(&self, members: &Map<String, Json>) -> Result<i128> {
+        let mut raw = 0u64;
+        for (name, value) in members {
+            let bit = self
+                .bits
+                .iter()
+                .find(|b| b.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| Error::Config(format!("{}: unknown bit '{name}'", self.name)))?;
+            let v = match value {
+                Json::Bool(b) => u64::from(*b),
+                _ => value
+                    .as_u64()
+                    .ok_or_else(|| Error::Config(format!("{}.{name}: invalid value {value}", self.name)))?,
+            };
+            let width = bit.end - bit.start + 1;
+            if v > mask(width) {
+                return Err(Error::Config(format!("{}.{name}: value {v} exceeds {width} bits", self.name)));
+            }
+            raw = (raw & !(mask(width) << bit.start)) | (v << bit.start);
+        }
+        Ok(i128::from(raw))
+    }
+}
+
+fn mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << width) - 1
+    }
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |acc, &b| acc << 8 | u64::from(b))
+}
+
+fn parse_int(s: &str) -> Option<i128> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => i128::from_str_radix(&hex.replace('_', ""), 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// The loaded configuration key database.
+#[derive(Debug, Clone)]
+pub struct ConfigKeys {
+    keys: Vec<ConfigKey>,
+    by_name: HashMap<String, usize>,
+    by_id: HashMap<u32, usize>,
+}
+
+impl ConfigKeys {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| Error::Config(format!("cannot open key database {}: {e}", path.display())))?;
+        let json: Json = serde_json::from_reader(BufReader::new(file))?;
+        Self::from_json(&json)
+    }
+
+    pub fn load_default() -> Result<Self> {
+        Self::load(default_keys_path())
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self> {
+        let entries = json
+            .get("keys")
+            .and_then(Json::as_array)
+            .ok_or_else(|| Error::Config("key database has no 'keys' array".into()))?;
+        let mut db = Self {
+            keys: Vec::with_capacity(entries.len()),
+            by_name: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        for entry in entries {
+            let key = parse_key(entry)?;
+            db.by_name.insert(key.name.clone(), db.keys.len());
+            db.by_id.insert(key.key_id, db.keys.len());
+            db.keys.push(key);
+        }
+        Ok(db)
+    }
+
+    /// Look up a key by name; the `CFG-` prefix is optional and case is ignored.
+    pub fn get(&self, name: &str) -> Option<&ConfigKey> {
+        let upper = name.trim().to_ascii_uppercase();
+        let full = if upper.starts_with("CFG-") { upper } else { format!("CFG-{upper}") };
+        self.by_name.get(&full).map(|&i| &self.keys[i])
+    }
+
+    pub fn by_id(&self, key_id: u32) -> Option<&ConfigKey> {
+        self.by_id.get(&key_id).map(|&i| &self.keys[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ConfigKey> {
+        self.keys.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Resolve a key name and encode its value.
+    pub fn key_value(&self, name: &str, value: &Json, scaled: bool) -> Result<KeyValue> {
+        let key = self
+            .get(name)
+            .ok_or_else(|| Error::Config(format!("unknown configuration key: {name}")))?;
+        Ok(KeyValue {
+            name: key.name.clone(),
+            key_id: key.key_id,
+            value: key.encode_value(value, scaled)?,
+        })
+    }
+
+    /// Decode a CFG-VALGET response, resolving each key ID through the
+    /// database. Keys the database doesn't know are kept, with their size
+    /// taken from the key ID and their value as an unsigned number.
+    pub fn decode_valget(&self, frame: &Frame, scaled: bool) -> Result<ValGet> {
+        if (frame.class, frame.id) != (0x06, 0x8B) {
+            return Err(Error::Config(format!("{:02X}-{:02X} is not CFG-VALGET", frame.class, frame.id)));
+        }
+        let payload = &frame.payload;
+        if payload.len() < 4 {
+            return Err(Error::Config(format!("CFG-VALGET payload of {} bytes is too short", payload.len())));
+        }
+        if payload[0] != 1 {
+            return Err(Error::Config(format!("CFG-VALGET version {} is not a response", payload[0])));
+        }
+        let mut values = Vec::new();
+        let mut data = &payload[4..];
+        while !data.is_empty() {
+            let offset = payload.len() - data.len();
+            let (id, rest) = data
+                .split_first_chunk::<4>()
+                .ok_or_else(|| Error::Config(format!("CFG-VALGET: partial key ID at byte {offset}")))?;
+            let key_id = u32::from_le_bytes(*id);
+            let key = self.by_id(key_id);
+            let size = key
+                .map(ConfigKey::size)
+                .or_else(|| key_id_size(key_id))
+                .ok_or_else(|| Error::Config(format!("CFG-VALGET: key 0x{key_id:08X} has no valid size")))?;
+            if rest.len() < size {
+                return Err(Error::Config(format!("CFG-VALGET: value of key 0x{key_id:08X} is truncated")));
+            }
+            let (raw, rest) = rest.split_at(size);
+            let value = match key {
+                Some(key) => key.decode_value(raw, scaled)?,
+                None => Json::from(le_u64(raw)),
+            };
+            values.push(ConfigValue {
+                key_id,
+                name: key.map(|k| k.name.clone()),
+                data_type: key.map(|k| k.data_type),
+                unit: key.and_then(|k| k.unit.clone()),
+                raw: raw.to_vec(),
+                value,
+            });
+            data = rest;
+        }
+        Ok(ValGet {
+            layer: payload[1],
+            position: u16::from_le_bytes([payload[2], payload[3]]),
+            values,
+        })
+    }
+}
+
+/// A decoded CFG-VALGET response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValGet {
+    /// Layer the values were read from: 0 RAM, 1 BBR, 2 flash, 7 default.
+    pub layer: u8,
+    /// Number of values skipped before the first one in this message.
+    pub position: u16,
+    pub values: Vec<ConfigValue>,
+}
+
+impl ValGet {
+    pub fn layer_name(&self) -> Option<&'static str> {
+        Some(match self.layer {
+            0 => "ram",
+            1 => "bbr",
+            2 => "flash",
+            7 => "default",
+            _ => return None,
+        })
+    }
+
+    /// The value of a key by name, `CFG-` prefix optional.
+    pub fn get(&self, name: &str) -> Option<&ConfigValue> {
+        let upper = name.trim().to_ascii_uppercase();
+        let full = if upper.starts_with("CFG-") { upper } else { format!("CFG-{upper}") };
+        self.values.iter().find(|v| v.name.as_deref() == Some(full.as_str()))
+    }
+}
+
+/// One key/value pair read back with CFG-VALGET.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigValue {
+    pub key_id: u32,
+    /// Name, type and unit from the key database; `None` for unknown keys.
+    pub name: Option<String>,
+    pub data_type: Option<KeyType>,
+    pub unit: Option<String>,
+    pub raw: Vec<u8>,
+    pub value: Json,
+}
+
+impl ConfigValue {
+    /// `{"key", "key_id", "type", "unit", "value"}`, leaving out what the
+    /// database doesn't give.
+    pub fn to_json(&self) -> Json {
+        let mut out = Map::new();
+        if let Some(name) = &self.name {
+            out.insert("key".into(), Json::from(name.as_str()));
+        }
+        out.insert("key_id".into(), Json::from(format!("0x{:08X}", self.key_id)));
+        if let Some(data_type) = self.data_type {
+            out.insert("type".into(), Json::from(data_type.to_string()));
+        }
+        if let Some(unit) = &self.unit {
+            out.insert("unit".into(), Json::from(unit.as_str()));
+        }
+        out.insert("value".into(), self.value.clone());
+        Json::Object(out)
+    }
+}
+
+fn parse_key(entry: &Json) -> Result<ConfigKey> {
+    let str_field = |name: &str| entry.get(name).and_then(Json::as_str);
+    let name = str_field("name").ok_or_else(|| Error::Config("key without a name".into()))?;
+    let err = |msg: &str| Error::Config(format!("{name}: {msg}"));
+    let key_id = str_field("key_id")
+        .and_then(|s| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| err("invalid key_id"))?;
+    let data_type = str_field("data_type")
+        .and_then(KeyType::parse)
+        .ok_or_else(|| err("invalid data_type"))?;
+    let scale = entry.pointer("/scale/multiplier").and_then(Json::as_f64);
+
+    let mut constants: Vec<(String, i64)> = entry
+        .pointer("/inline_enum/values")
+        .and_then(Json::as_object)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|(n, v)| Some((n.clone(), v.get("value")?.as_i64()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    constants.sort_by_key(|(_, v)| *v);
+
+    let bits = entry
+        .pointer("/bitfield/bits")
+        .and_then(Json::as_array)
+        .map(|bits| {
+            bits.iter()
+                .filter_map(|b| {
+                    Some(KeyBits {
+                        name: b.get("name")?.as_str()?.to_string(),
+                        start: b.get("bit_start")?.as_u64()? as u32,
+                        end: b.get("bit_end")?.as_u64()? as u32,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ConfigKey {
+        name: name.to_string(),
+        key_id,
+        data_type,
+        unit: str_field("unit").map(String::from),
+        scale,
+        constants,
+        bits,
+        description: str_field("description").unwrap_or_default().to_string(),
+    })
+}
+
+/// An encoded key/value pair ready for CFG-VALSET.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValue {
+    pub name: String,
+    pub key_id: u32,
+    pub value: Vec<u8>,
+}
+
+/// Configuration layers targeted by CFG-VALSET.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layers {
+    pub ram: bool,
+    pub bbr: bool,
+    pub flash: bool,
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self::RAM
+    }
+}
+
+impl Layers {
+    pub const RAM: Layers = Layers { ram: true, bbr: false, flash: false };
+
+    /// Parse a comma separated list such as `ram,bbr,flash`.
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::from_names(s.split(','))
+    }
+
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut layers = Layers { ram: false, bbr: false, flash: false };
+        for name in names {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "ram" => layers.ram = true,
+                "bbr" => layers.bbr = true,
+                "flash" => layers.flash = true,
+                "" => {}
+                other => return Err(Error::Config(format!("unknown layer '{other}'"))),
+            }
+        }
+        if layers.bits() == 0 {
+            return Err(Error::Config("no configuration layer selected".into()));
+        }
+        Ok(layers)
+    }
+
+    pub fn bits(self) -> u8 {
+        u8::from(self.ram) | u8::from(self.bbr) << 1 | u8::from(self.flash) << 2
+    }
+}
+
+/// Build the CFG-VALSET frames that apply `values` to `layers`.
+///
+/// Up to [`MAX_KEYS_PER_VALSET`] values fit in a single version 0 message.
+/// Larger sets are split into a version 1 transaction so the receiver applies
+/// them atomically with the last message.
+pub fn valset_frames(values: &[KeyValue], layers: Layers) -> Vec<Frame> {
+    let chunks: Vec<&[KeyValue]> = values.chunks(MAX_KEYS_PER_VALSET).collect();
+    let transaction = chunks.len() > 1;
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            // Transaction action: 1 = begin, 2 = continue, 3 = apply.
+            let (version, action) = match (transaction, i) {
+                (false, _) => (0, 0),
+                (true, 0) => (1, 1),
+                (true, i) if i == chunks.len() - 1 => (1, 3),
+                (true, _) => (1, 2),
+            };
+            let mut payload = vec![version, layers.bits(), action, 0];
+            for kv in *chunk {
+                payload.extend_from_slice(&kv.key_id.to_le_bytes());
+                payload.extend_from_slice(&kv.value);
+            }
+            Frame::new(0x06, 0x8A, payload)
+        })
+        .collect()
+}
+
+/// Collect key/value pairs from a configuration document's `keys` object.
+///
+/// Keys may be written in full (`CFG-RATE-MEAS`) or nested under their group
+/// (`CFG-RATE: {MEAS: 100}`); an object whose name is itself a known key is a
+/// bitfield value instead.
+pub fn collect_keys(db: &ConfigKeys, keys: &Map<String, Json>, scaled: bool) -> Result<Vec<KeyValue>> {
+    let mut out = Vec::new();
+    collect_into(db, "", keys, scaled, &mut out)?;
+    Ok(out)
+}
+
+fn collect_into(
+    db: &ConfigKeys,
+    prefix: &str,
+    keys: &Map<String, Json>,
+    scaled: bool,
+    out: &mut Vec<KeyValue>,
+) -> Result<()> {
+    for (name, value) in keys {
+        let full = if prefix.is_empty() { name.clone() } else { format!("{prefix}-{name}") };
+        match value {
+            Json::Object(members) if db.get(&full).is_none() => collect_into(db, &full, members, scaled, out)?,
+            _ => out.push(db.key_value(&full, value, scaled)?),
+        }
+    }
+    Ok(())
+}
+
+/// Outcome of an ACK-ACK or ACK-NAK frame: the acknowledged class and ID and
+/// whether it was accepted.
+pub fn parse_ack(frame: &Frame) -> Option<(u8, u8, bool)> {
+    if frame.class != 0x05 || frame.id > 0x01 || frame.payload.len() < 2 {
+        return None;
+    }
+    Some((frame.payload[0], frame.payload[1], frame.id == 0x01))
+}
@@ -0,0 +1,239 @@
+//! Machine-readable conformance results: JUnit XML and a per-message
+//! summary in JSON.
+//!
+//! `ubx-corpus check` and `ubx-differential` collect their results as
+//! [`Suite`]s of [`Case`]s, one case per corpus vector or per sample and
+//! backend. JUnit XML is what CI servers show as test results; the summary
+//! is meant to be kept per run, so a dashboard can track each message's
+//! pass/fail history without scraping logs.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::corpus::{check_vector, Vector};
+use crate::decode::Decoder;
+use crate::differential::{Report, Sample};
+use crate::error::Result;
+use crate::frame::parse_frame;
+
+/// One test: a corpus vector, or a sample checked against one backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// Vector path or sample source.
+    pub name: String,
+    /// The schema's name for the message, or `unknown`.
+    pub message: String,
+    pub failures: Vec<String>,
+    /// Why the case did not run, e.g. an unavailable backend.
+    pub skipped: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suite {
+    /// `corpus` or `differential.<backend>`.
+    pub name: String,
+    pub cases: Vec<Case>,
+}
+
+impl Suite {
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|c| c.skipped.is_none() && !c.failures.is_empty()).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.cases.iter().filter(|c| c.skipped.is_some()).count()
+    }
+}
+
+/// Check every corpus vector; `vectors` as returned by
+/// [`load_corpus`](crate::corpus::load_corpus) from `dir`.
+pub fn corpus_suite(decoder: &Decoder, dir: &Path, vectors: &[(impl AsRef<Path>, Vector)]) -> Suite {
+    let cases = vectors
+        .iter()
+        .map(|(path, vector)| {
+            let path = path.as_ref();
+            Case {
+                name: path.strip_prefix(dir).unwrap_or(path).display().to_string(),
+                message: vector.expected.name.clone(),
+                failures: check_vector(decoder, vector).iter().map(ToString::to_string).collect(),
+                skipped: None,
+            }
+        })
+        .collect();
+    Suite {
+        name: "corpus".into(),
+        cases,
+    }
+}
+
+/// One suite per backend of a differential run over `samples`.
+pub fn differential_suites(decoder: &Decoder, samples: &[Sample], report: &Report) -> Vec<Suite> {
+    let messages: Vec<String> = samples
+        .iter()
+        .map(|s| match parse_frame(&s.frame) {
+            Ok(frame) => decoder.decode_frame(&frame).name,
+            Err(_) => "unknown".into(),
+        })
+        .collect();
+    report
+        .backends
+        .iter()
+        .map(|backend| {
+            let cases = samples
+                .iter()
+                .zip(&messages)
+                .map(|(sample, message)| Case {
+                    name: sample.source.clone(),
+                    message: message.clone(),
+                    failures: report
+                        .disagreements
+                        .iter()
+                        .filter(|d| d.backend == backend.name && d.source == sample.source)
+                        .map(|d| {
+                            let field = d.field.as_deref().map(|f| format!(".{f}")).unwrap_or_default();
+                            format!("{}{field}: {} vs schema {}", d.message, d.other, d.schema)
+                        })
+                        .collect(),
+                    skipped: backend.error.as_ref().map(|e| format!("{} unavailable: {e}", backend.name)),
+                })
+                .collect();
+            Suite {
+                name: format!("differential.{}", backend.name),
+                cases,
+            }
+        })
+        .collect()
+}
+
+/// JUnit XML for `suites`, with each case's message as its class name.
+pub fn junit_xml(suites: &[Suite]) -> String {
+    let tests: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let failures: usize = suites.iter().map(Suite::failed).sum();
+    let skipped: usize = suites.iter().map(Suite::skipped).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"ubx-conformance\" tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\">"
+    );
+    for suite in suites {
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" errors=\"0\">",
+            escape(&suite.name),
+            suite.cases.len(),
+            suite.failed(),
+            suite.skipped()
+        );
+        for case in &suite.cases {
+            let _ = write!(
+                out,
+                "    <testcase classname=\"{}\" name=\"{}\"",
+                escape(&case.message),
+                escape(&case.name)
+            );
+            if let Some(reason) = &case.skipped {
+                let _ = writeln!(out, ">\n      <skipped message=\"{}\"/>\n    </testcase>", escape(reason));
+            } else if case.failures.is_empty() {
+                out.push_str("/>\n");
+            } else {
+                let _ = writeln!(
+                    out,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                    escape(&case.failures[0]),
+                    escape(&case.failures.join("\n"))
+                );
+            }
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Not allowed in XML 1.0 at all.
+            c if c < ' ' && !matches!(c, '\t' | '\n' | '\r') => out.push('\u{FFFD}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Tally {
+    pub tests: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl Tally {
+    fn add(&mut self, case: &Case) {
+        self.tests += 1;
+        match (&case.skipped, case.failures.is_empty()) {
+            (Some(_), _) => self.skipped += 1,
+            (None, true) => self.passed += 1,
+            (None, false) => self.failed += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub schema_version: String,
+    pub total: Tally,
+    pub suites: BTreeMap<String, Tally>,
+    /// Per message, per suite.
+    pub messages: BTreeMap<String, BTreeMap<String, Tally>>,
+}
+
+impl Summary {
+    pub fn new(schema_version: &str, suites: &[Suite]) -> Self {
+        let mut summary = Summary {
+            schema_version: schema_version.to_string(),
+            ..Summary::default()
+        };
+        for suite in suites {
+            for case in &suite.cases {
+                summary.total.add(case);
+                summary.suites.entry(suite.name.clone()).or_default().add(case);
+                summary
+                    .messages
+                    .entry(case.message.clone())
+                    .or_default()
+                    .entry(suite.name.clone())
+                    .or_default()
+                    .add(case);
+            }
+        }
+        summary
+    }
+}
+
+/// Write whichever of the JUnit XML and the JSON summary were asked for.
+pub fn write_reports(
+    junit: Option<&Path>,
+    summary: Option<&Path>,
+    schema_version: &str,
+    suites: &[Suite],
+) -> Result<()> {
+    if let Some(path) = junit {
+        fs::write(path, junit_xml(suites))?;
+    }
+    if let Some(path) = summary {
+        fs::write(path, serde_json::to_string_pretty(&Summary::new(schema_version, suites))? + "\n")?;
+    }
+    Ok(())
+}
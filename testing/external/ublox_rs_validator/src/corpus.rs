@@ -0,0 +1,259 @@
+//! Golden test vectors: raw frames paired with their expected decode.
+//!
+//! The corpus lives in `testing/corpus/`, one JSON file per vector, grouped
+//! in a directory per message (`testing/corpus/NAV-PVT/3d-fix.json`):
+//!
+//! ```json
+//! {
+//!   "description": "3D fix, all validity flags set",
+//!   "frame": "b562010754...",
+//!   "expected": { "name": "UBX-NAV-PVT", "fields": { "iTOW": 1000, ... } },
+//!   "external": { "ublox-rs": "parsed" }
+//! }
+//! ```
+//!
+//! `expected.fields` is the complete decode with the default options: raw
+//! (unscaled) values, bitfields as integers, reserved fields left out. Every
+//! field must match and none may be missing or extra, so a renamed or moved
+//! field shows up as a failure. `external` records how other decoders are
+//! expected to treat the frame; a backend that is not listed is not checked.
+//! Vectors filed by `ubx-ingest` also carry the `receiver` they came from.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use ublox::PacketRef;
+
+use crate::decode::Decoder;
+use crate::error::{Error, Result};
+use crate::frame::parse_frame;
+use crate::receiver::ReceiverInfo;
+
+/// Name of the ublox crate backend in [`Vector::external`].
+pub const UBLOX_RS: &str = "ublox-rs";
+
+pub fn default_corpus_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../corpus")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vector {
+    pub description: String,
+    /// The complete frame, sync characters to checksum, as hex.
+    pub frame: String,
+    pub expected: Expected,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external: BTreeMap<String, Verdict>,
+    /// The receiver the frame was captured from, for ingested vectors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver: Option<ReceiverInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expected {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    pub fields: Map<String, Json>,
+}
+
+/// How a decoder treats a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Decoded as the same class and ID.
+    Parsed,
+    /// Recognised as a frame the decoder has no definition for.
+    Unsupported,
+    /// Refused, e.g. because its length doesn't match the decoder's layout.
+    Rejected,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::Parsed => "parsed",
+            Verdict::Unsupported => "unsupported",
+            Verdict::Rejected => "rejected",
+        })
+    }
+}
+
+impl Vector {
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(self.frame.replace(char::is_whitespace, ""))
+            .map_err(|e| Error::Corpus(format!("invalid frame hex: {e}")))
+    }
+
+    /// A vector expecting whatever `decoder` produces for `frame` today.
+    /// Check the output by hand before adding it to the corpus.
+    pub fn record(decoder: &Decoder, frame: &[u8], description: &str) -> Result<Self> {
+        let parsed = parse_frame(frame)?;
+        let msg = decoder.decode_frame(&parsed);
+        if !msg.parsed {
+            return Err(Error::Corpus(format!("{} does not decode with the schema", msg.name)));
+        }
+        let Json::Object(fields) = serde_json::to_value(&msg.fields)? else {
+            unreachable!("fields serialize as a map");
+        };
+        let (verdict, _) = ublox_verdict(frame);
+        Ok(Self {
+            description: description.to_string(),
+            frame: hex::encode(parsed.raw),
+            expected: Expected {
+                name: msg.name,
+                variant: msg.variant,
+                fields,
+            },
+            external: BTreeMap::from([(UBLOX_RS.to_string(), verdict)]),
+            receiver: None,
+        })
+    }
+}
+
+/// Every `*.json` vector under `dir`, sorted by path.
+pub fn load_corpus(dir: &Path) -> Result<Vec<(PathBuf, Vector)>> {
+    let mut paths = Vec::new();
+    collect_json(dir, &mut paths)?;
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let vector = serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| Error::Corpus(format!("{}: {e}", path.display())))?;
+            Ok((path, vector))
+        })
+        .collect()
+}
+
+fn collect_json(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json(&path, out)?;
+        } else if path.extension().is_some_and(|e| e == "json") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The ublox crate's verdict on a frame, with its error if it refused it.
+pub fn ublox_verdict(frame: &[u8]) -> (Verdict, Option<String>) {
+    let mut parser = ublox::Parser::default();
+    let mut packets = parser.consume(frame);
+    match packets.next() {
+        Some(Ok(PacketRef::Unknown(_))) => (Verdict::Unsupported, None),
+        Some(Ok(packet)) => {
+            let (class, id) = packet.class_and_msg_id();
+            match frame.get(2..4) {
+                Some(&[c, i]) if (c, i) == (class, id) => (Verdict::Parsed, None),
+                _ => (Verdict::Rejected, Some(format!("parsed as {class:02X}-{id:02X}"))),
+            }
+        }
+        Some(Err(e)) => (Verdict::Rejected, Some(e.to_string())),
+        None => (Verdict::Rejected, Some("no packet found".into())),
+    }
+}
+
+/// One disagreement between a backend and a vector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// `schema` or an external backend name.
+    pub backend: String,
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.backend, self.message)
+    }
+}
+
+/// Check `vector` against the schema decoder and the in-process external
+/// backends. An empty result means every backend agrees with it.
+pub fn check_vector(decoder: &Decoder, vector: &Vector) -> Vec<Failure> {
+    let failure = |backend: &str, message: String| Failure {
+        backend: backend.to_string(),
+        message,
+    };
+    let bytes = match vector.bytes() {
+        Ok(bytes) => bytes,
+        Err(e) => return vec![failure("corpus", e.to_string())],
+    };
+    let mut failures = Vec::new();
+    match parse_frame(&bytes) {
+        Err(e) => failures.push(failure("schema", e.to_string())),
+        Ok(frame) => {
+            let msg = decoder.decode_frame(&frame);
+            if msg.name != vector.expected.name || msg.variant != vector.expected.variant {
+                let shown = |name: &str, variant: &Option<String>| match variant {
+                    Some(v) => format!("{name} ({v})"),
+                    None => name.to_string(),
+                };
+                failures.push(failure(
+                    "schema",
+                    format!(
+                        "decoded as {}, expected {}",
+                        shown(&msg.name, &msg.variant),
+                        shown(&vector.expected.name, &vector.expected.variant)
+                    ),
+                ));
+            } else {
+                let decoded = serde_json::to_value(&msg.fields).unwrap_or(Json::Null);
+                let expected = Json::Object(vector.expected.fields.clone());
+                let mut diffs = Vec::new();
+                diff_json("fields", &expected, &decoded, &mut diffs);
+                failures.extend(diffs.into_iter().map(|d| failure("schema", d)));
+            }
+        }
+    }
+    if let Some(&expected) = vector.external.get(UBLOX_RS) {
+        let (verdict, detail) = ublox_verdict(&bytes);
+        if verdict != expected {
+            let detail = detail.map_or_else(String::new, |d| format!(" ({d})"));
+            failures.push(failure(UBLOX_RS, format!("{verdict}{detail}, expected {expected}")));
+        }
+    }
+    failures
+}
+
+/// Numbers compare by value, so `1` and `1.0` agree and floats may differ
+/// in the last few bits.
+fn numbers_agree(a: &Json, b: &Json) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y || (x - y).abs() <= 1e-9 * x.abs().max(y.abs()),
+        _ => false,
+    }
+}
+
+fn diff_json(path: &str, expected: &Json, actual: &Json, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Json::Object(e), Json::Object(a)) => {
+            for (name, value) in e {
+                match a.get(name) {
+                    Some(got) => diff_json(&format!("{path}.{name}"), value, got, out),
+                    None => out.push(format!("{path}.{name}: missing, expected {value}")),
+                }
+            }
+            for name in a.keys().filter(|k| !e.contains_key(*k)) {
+                out.push(format!("{path}.{name}: unexpected field = {}", a[name]));
+            }
+        }
+        (Json::Array(e), Json::Array(a)) => {
+            if e.len() != a.len() {
+                out.push(format!("{path}: {} items, expected {}", a.len(), e.len()));
+            }
+            for (i, (ev, av)) in e.iter().zip(a).enumerate() {
+                diff_json(&format!("{path}[{i}]"), ev, av, out);
+            }
+        }
+        (Json::Number(_), Json::Number(_)) if numbers_agree(expected, actual) => {}
+        _ if expected == actual => {}
+        _ => out.push(format!("{path}: {actual}, expected {expected}")),
+    }
+}
@@ -0,0 +1,250 @@
+//! Coverage statistics per protocol version and product generation.
+//!
+//! For each protocol version named in the schema's `supported_versions`,
+//! counts the messages defined for it and their fields; for each product
+//! generation in the message index, how many of the messages its manuals
+//! document the schema defines. A report serializes to JSON, so successive
+//! runs can be kept and compared with [`render_table`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::completeness::{self, MessageIndex};
+use crate::error::{Error, Result};
+use crate::schema::{DataType, FieldDef, MessageDef, Schema};
+
+pub fn default_metadata_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/manual_metadata.json")
+}
+
+/// Firmware and protocol version of one manual, from `manual_metadata.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ManualInfo {
+    pub firmware_version: String,
+    /// Protocol version times 100, e.g. 2731 for 27.31.
+    pub protocol_version: u32,
+}
+
+#[derive(Deserialize)]
+struct RawMetadata {
+    manuals: BTreeMap<String, ManualInfo>,
+}
+
+/// Load the per-manual metadata, keyed by manual name.
+pub fn load_metadata(path: impl AsRef<Path>) -> Result<BTreeMap<String, ManualInfo>> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+    let raw: RawMetadata = serde_json::from_str(&text)?;
+    Ok(raw.manuals)
+}
+
+/// Message and field counts over a set of messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counts {
+    pub messages: usize,
+    /// Non-reserved fields, including group members and every variant's.
+    pub fields: usize,
+    /// X-type fields.
+    pub bitfields: usize,
+    /// X-type fields with their bits defined.
+    pub bitfields_defined: usize,
+}
+
+impl Counts {
+    fn add(&mut self, msg: &MessageDef) {
+        self.messages += 1;
+        let payloads = msg.payload.iter().chain(msg.variants.iter().map(|v| &v.payload));
+        for payload in payloads {
+            self.add_fields(&payload.fields);
+            for group in &payload.repeated_groups {
+                self.add_fields(&group.fields);
+            }
+        }
+    }
+
+    fn add_fields(&mut self, fields: &[FieldDef]) {
+        for field in fields.iter().filter(|f| !f.reserved) {
+            self.fields += 1;
+            let base = match &field.data_type {
+                DataType::Scalar(base) => Some(*base),
+                DataType::Group { fields, .. } => {
+                    self.add_fields(fields);
+                    None
+                }
+                _ => None,
+            };
+            if base.is_some_and(|b| b.is_bitfield()) {
+                self.bitfields += 1;
+                self.bitfields_defined += usize::from(!field.bits.is_empty());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionCoverage {
+    /// e.g. `27.31`.
+    pub protocol_version: String,
+    /// Firmware of the manuals describing this version, e.g. `HPG 1.32`.
+    pub firmware: Vec<String>,
+    pub generations: Vec<String>,
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationCoverage {
+    pub generation: String,
+    /// Messages the generation's manuals document.
+    pub documented: usize,
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    pub schema_version: String,
+    pub total: Counts,
+    pub protocol_versions: Vec<VersionCoverage>,
+    pub generations: Vec<GenerationCoverage>,
+}
+
+/// Compute coverage of `schema`, using `index` for what each generation
+/// documents and `manuals` for the firmware behind each protocol version.
+pub fn coverage(schema: &Schema, index: &MessageIndex, manuals: &BTreeMap<String, ManualInfo>) -> Coverage {
+    let mut total = Counts::default();
+    let mut by_version: BTreeMap<u32, Counts> = BTreeMap::new();
+    for msg in &schema.messages {
+        total.add(msg);
+        for &version in &msg.supported_versions.protocol_versions {
+            by_version.entry(version).or_default().add(msg);
+        }
+    }
+
+    let generation_of: BTreeMap<&str, &str> = index
+        .generations
+        .iter()
+        .flat_map(|g| g.manuals.iter().map(move |m| (m.as_str(), g.name.as_str())))
+        .collect();
+    let protocol_versions = by_version
+        .into_iter()
+        .map(|(version, counts)| {
+            let mut firmware = BTreeSet::new();
+            let mut generations = Vec::new();
+            for (name, info) in manuals.iter().filter(|(_, info)| info.protocol_version == version) {
+                firmware.insert(info.firmware_version.clone());
+                if let Some(g) = generation_of.get(name.as_str()) {
+                    if !generations.iter().any(|known: &String| known == g) {
+                        generations.push(g.to_string());
+                    }
+                }
+            }
+            VersionCoverage {
+                protocol_version: format!("{}.{:02}", version / 100, version % 100),
+                firmware: firmware.into_iter().collect(),
+                generations,
+                counts,
+            }
+        })
+        .collect();
+
+    let gaps = completeness::check(schema, index);
+    let generations = index
+        .generations
+        .iter()
+        .zip(&gaps.generations)
+        .map(|(generation, gap)| {
+            let mut counts = Counts::default();
+            for msg in &schema.messages {
+                if msg.supported_versions.source_manuals.iter().any(|m| generation.manuals.contains(m)) {
+                    counts.add(msg);
+                }
+            }
+            GenerationCoverage {
+                generation: generation.name.clone(),
+                documented: gap.documented,
+                counts: Counts {
+                    messages: gap.documented - gap.missing.len(),
+                    ..counts
+                },
+            }
+        })
+        .collect();
+
+    Coverage {
+        schema_version: schema.schema_version.clone(),
+        total,
+        protocol_versions,
+        generations,
+    }
+}
+
+/// Markdown tables of `coverage`, with the change since `baseline` after
+/// each count that moved.
+pub fn render_table(coverage: &Coverage, baseline: Option<&Coverage>) -> String {
+    let mut out = String::new();
+    let base_version =
+        |v: &str| baseline.and_then(|b| b.protocol_versions.iter().find(|r| r.protocol_version == v));
+    let base_generation = |g: &str| baseline.and_then(|b| b.generations.iter().find(|r| r.generation == g));
+
+    out.push_str("| Generation | Documented | Defined | Coverage | Fields | Bitfields defined |\n");
+    out.push_str("|------------|------------|---------|----------|--------|-------------------|\n");
+    for row in &coverage.generations {
+        let old = base_generation(&row.generation).map(|r| &r.counts);
+        let percent = if row.documented == 0 {
+            100.0
+        } else {
+            100.0 * row.counts.messages as f64 / row.documented as f64
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {percent:.0}% | {} | {} |",
+            row.generation,
+            row.documented,
+            count(row.counts.messages, old.map(|c| c.messages)),
+            count(row.counts.fields, old.map(|c| c.fields)),
+            bitfields(&row.counts, old),
+        );
+    }
+
+    out.push_str("\n| Protocol | Firmware | Generation | Messages | Fields | Bitfields defined |\n");
+    out.push_str("|----------|----------|------------|----------|--------|-------------------|\n");
+    for row in &coverage.protocol_versions {
+        let old = base_version(&row.protocol_version).map(|r| &r.counts);
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} |",
+            row.protocol_version,
+            row.firmware.join(", "),
+            row.generations.join(", "),
+            count(row.counts.messages, old.map(|c| c.messages)),
+            count(row.counts.fields, old.map(|c| c.fields)),
+            bitfields(&row.counts, old),
+        );
+    }
+    let old = baseline.map(|b| &b.total);
+    let _ = writeln!(
+        out,
+        "| all | | | {} | {} | {} |",
+        count(coverage.total.messages, old.map(|c| c.messages)),
+        count(coverage.total.fields, old.map(|c| c.fields)),
+        bitfields(&coverage.total, old),
+    );
+    out
+}
+
+fn count(now: usize, before: Option<usize>) -> String {
+    match before {
+        Some(before) if before != now => format!("{now} ({:+})", now as i64 - before as i64),
+        _ => now.to_string(),
+    }
+}
+
+fn bitfields(now: &Counts, before: Option<&Counts>) -> String {
+    let defined = count(now.bitfields_defined, before.map(|c| c.bitfields_defined));
+    format!("{defined}/{}", now.bitfields)
+}
@@ -0,0 +1,201 @@
+//! Live receiver state for the `ubx-tui` dashboard.
+//!
+//! [`Dashboard`] folds a decoded message stream into what the screen shows:
+//! the latest NAV-PVT solution, the satellites of the latest NAV-SAT,
+//! per-message rates over a sliding window of host time and the most recent
+//! errors. Messages must be decoded with scaling and flag expansion enabled.
+//! It owns no terminal state, so the binary only has to draw it.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::decode::DecodedMessage;
+use crate::gnss::Gnss;
+use crate::time::DateTime;
+use crate::track::FixQuality;
+use crate::value::{Fields, Value};
+
+/// Seconds of arrivals kept for the message rate estimate.
+pub const RATE_WINDOW: f64 = 5.0;
+
+/// Number of errors kept for display.
+pub const MAX_ERRORS: usize = 50;
+
+/// The latest navigation solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub itow: u32,
+    /// UTC time, when NAV-PVT flagged date and time valid.
+    pub time: Option<DateTime>,
+    pub fix: FixQuality,
+    pub lat: f64,
+    pub lon: f64,
+    /// Height above mean sea level, metres.
+    pub alt_msl: f64,
+    /// Horizontal and vertical accuracy estimates, metres.
+    pub h_acc: f64,
+    pub v_acc: f64,
+    /// Ground speed, m/s.
+    pub speed: f64,
+    pub num_sv: u64,
+    pub pdop: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Satellite {
+    pub gnss_id: u8,
+    pub sv_id: u8,
+    /// Carrier-to-noise density, dBHz; 0 when not tracked.
+    pub cno: u8,
+    pub elev: i8,
+    /// Used in the navigation solution.
+    pub used: bool,
+}
+
+impl Satellite {
+    pub fn gnss(&self) -> Option<Gnss> {
+        Gnss::from_id(self.gnss_id)
+    }
+
+    /// Label such as `G12`, or `gnssId:svId` for systems without a RINEX code.
+    pub fn label(&self) -> String {
+        match self.gnss().and_then(|g| g.rinex_char().map(|c| (g, c))) {
+            Some((g, c)) => format!("{c}{:02}", g.rinex_prn(self.sv_id)),
+            None => format!("{}:{}", self.gnss_id, self.sv_id),
+        }
+    }
+}
+
+/// Satellites of one constellation in the latest NAV-SAT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstellationSummary {
+    pub gnss: Gnss,
+    /// Satellites with a non-zero C/N0.
+    pub tracked: usize,
+    pub used: usize,
+    /// Mean C/N0 of the tracked satellites, dBHz.
+    pub mean_cno: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dashboard {
+    pub position: Option<Position>,
+    pub satellites: Vec<Satellite>,
+    /// Host arrival times (seconds) within [`RATE_WINDOW`], per message name.
+    arrivals: BTreeMap<String, VecDeque<f64>>,
+    /// `(host time, text)`, newest last.
+    pub errors: VecDeque<(f64, String)>,
+    pub frames: usize,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold in a message that arrived at host time `now`, in seconds.
+    pub fn push(&mut self, msg: &DecodedMessage, now: f64) {
+        self.frames += 1;
+        let arrivals = self.arrivals.entry(msg.short_name().to_string()).or_default();
+        arrivals.push_back(now);
+        while arrivals.front().is_some_and(|t| *t < now - RATE_WINDOW) {
+            arrivals.pop_front();
+        }
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => self.position = position(msg).or(self.position.take()),
+            "UBX-NAV-SAT" => self.satellites = satellites(msg),
+            _ => {}
+        }
+    }
+
+    /// Record an error (checksum failure, NAK, ...) seen at `now`.
+    pub fn push_error(&mut self, text: impl Into<String>, now: f64) {
+        self.errors.push_back((now, text.into()));
+        while self.errors.len() > MAX_ERRORS {
+            self.errors.pop_front();
+        }
+    }
+
+    /// Messages per second over the last [`RATE_WINDOW`] seconds before
+    /// `now`, by message name.
+    pub fn rates(&self, now: f64) -> Vec<(&str, f64)> {
+        self.arrivals
+            .iter()
+            .map(|(name, times)| {
+                let recent = times.iter().filter(|t| **t >= now - RATE_WINDOW).count();
+                (name.as_str(), recent as f64 / RATE_WINDOW)
+            })
+            .collect()
+    }
+
+    /// Per-constellation counts for the latest NAV-SAT, in `gnssId` order.
+    pub fn constellations(&self) -> Vec<ConstellationSummary> {
+        Gnss::ALL
+            .into_iter()
+            .filter_map(|gnss| {
+                let sats: Vec<&Satellite> = self.satellites.iter().filter(|s| s.gnss() == Some(gnss)).collect();
+                let tracked: Vec<f64> = sats.iter().filter(|s| s.cno > 0).map(|s| f64::from(s.cno)).collect();
+                (!sats.is_empty()).then(|| ConstellationSummary {
+                    gnss,
+                    tracked: tracked.len(),
+                    used: sats.iter().filter(|s| s.used).count(),
+                    mean_cno: if tracked.is_empty() {
+                        0.0
+                    } else {
+                        tracked.iter().sum::<f64>() / tracked.len() as f64
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+fn position(msg: &DecodedMessage) -> Option<Position> {
+    let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+    let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+    let time = (int("valid.validDate") != 0 && int("valid.validTime") != 0).then(|| DateTime {
+        year: int("year") as i32,
+        month: int("month") as u8,
+        day: int("day") as u8,
+        hour: int("hour") as u8,
+        minute: int("min") as u8,
+        second: int("sec") as f64 + num("nano").unwrap_or(0.0) * 1e-9,
+    });
+    Some(Position {
+        itow: msg.itow()?,
+        time,
+        fix: FixQuality::from_pvt(int("fixType"), int("flags.diffSoln") != 0, int("flags.carrSoln")),
+        lat: num("lat")?,
+        lon: num("lon")?,
+        alt_msl: num("hMSL").unwrap_or(0.0) / 1_000.0,
+        h_acc: num("hAcc").unwrap_or(0.0) / 1_000.0,
+        v_acc: num("vAcc").unwrap_or(0.0) / 1_000.0,
+        speed: num("gSpeed").unwrap_or(0.0) / 1_000.0,
+        num_sv: int("numSV"),
+        pdop: num("pDOP"),
+    })
+}
+
+fn satellites(msg: &DecodedMessage) -> Vec<Satellite> {
+    let Some(items) = msg.get("satellites").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(Value::as_record)
+        .map(|sat: &Fields| {
+            let int = |name: &str| sat.get_path(name).and_then(Value::as_i64).unwrap_or(0);
+            // svUsed is bit 3 of flags when the schema has no member names.
+            let used = match sat.get_path("flags.svUsed") {
+                Some(v) => v.as_u64() == Some(1),
+                None => int("flags") & 0x08 != 0,
+            };
+            Satellite {
+                gnss_id: int("gnssId") as u8,
+                sv_id: int("svId") as u8,
+                cno: int("cno") as u8,
+                elev: int("elev") as i8,
+                used,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,96 @@
+//! Thin a log to every Nth epoch of each message type.
+//!
+//! Epochs are counted per type: a frame starts a new epoch of its type when
+//! its iTOW differs from the previous frame of that type, so several frames
+//! of one type in one epoch (RXM-SFRBX, ESF-MEAS) are kept or dropped
+//! together. Messages without an iTOW belong to the epoch of the most recent
+//! message that has one. Configuration, acknowledgment, information and
+//! version messages are always kept, since fixtures need them to be
+//! interpreted.
+
+use std::collections::HashMap;
+
+use crate::decode::Decoder;
+use crate::frame::{frames, FrameRef};
+use crate::schema::{normalize_name, Schema};
+
+/// Types kept regardless of decimation: whole classes or single messages.
+pub const ALWAYS_KEPT: &[&str] = &["CFG", "ACK", "INF", "MON-VER"];
+
+#[derive(Debug, Clone)]
+pub struct Decimation {
+    /// Keep one epoch in `every` for types without an override.
+    pub every: u32,
+    /// Per-type factors, by normalized name.
+    overrides: HashMap<String, u32>,
+    /// Normalized class prefixes (`UBX-CFG-`) and names always kept.
+    kept: Vec<String>,
+}
+
+impl Decimation {
+    pub fn new(every: u32) -> Self {
+        Self {
+            every: every.max(1),
+            overrides: HashMap::new(),
+            kept: Vec::new(),
+        }
+        .keep(ALWAYS_KEPT)
+    }
+
+    /// Use a different factor for one message type, e.g. `NAV-SAT` every 10th epoch.
+    /// A factor of 0 drops the type entirely.
+    pub fn every_for(mut self, name: &str, every: u32) -> Self {
+        self.overrides.insert(normalize_name(name), every);
+        self
+    }
+
+    /// Also keep every frame of these classes (`ESF`) or messages (`MON-HW`).
+    pub fn keep<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for name in names {
+            let name = normalize_name(name.as_ref());
+            // A bare class like `UBX-CFG` matches every message in it.
+            let is_class = name.matches('-').count() == 1;
+            self.kept.push(if is_class { format!("{name}-") } else { name });
+        }
+        self
+    }
+
+    fn is_kept(&self, name: &str) -> bool {
+        self.kept.iter().any(|k| if k.ends_with('-') { name.starts_with(k.as_str()) } else { k == name })
+    }
+
+    fn factor(&self, name: &str) -> u32 {
+        self.overrides.get(name).copied().unwrap_or(self.every)
+    }
+
+    /// Frames of `input` that survive, in order.
+    pub fn apply<'a>(&self, schema: &Schema, input: &'a [u8]) -> Vec<FrameRef<'a>> {
+        let decoder = Decoder::new(schema);
+        let mut epoch = None;
+        // Per type: the epoch of its last frame and how many epochs it has seen.
+        let mut seen: HashMap<String, (Option<u32>, u32)> = HashMap::new();
+        let mut kept = Vec::new();
+        for frame in frames(input).flatten() {
+            let msg = decoder.decode_frame(&frame);
+            epoch = msg.itow().or(epoch);
+            if self.is_kept(&msg.name) {
+                kept.push(frame);
+                continue;
+            }
+            let factor = self.factor(&msg.name);
+            let (last, count) = seen.entry(msg.name).or_insert((None, 0));
+            if *count == 0 || *last != epoch {
+                *count += 1;
+                *last = epoch;
+            }
+            if factor > 0 && (*count - 1) % factor == 0 {
+                kept.push(frame);
+            }
+        }
+        kept
+    }
+}
@@ -0,0 +1,339 @@
+//! Decode UBX payloads using schema definitions.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::frame::FrameRef;
+use crate::schema::{fields_extent, BaseOffset, BaseType, Count, DataType, FieldDef, PayloadDef, Schema};
+use crate::value::{Fields, Value};
+
+/// Output options for the decoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Apply schema scale factors, producing physical values.
+    pub scale: bool,
+    /// Expand X-type fields into their named bitfield members.
+    pub expand_flags: bool,
+    /// Keep reserved fields and reserved bitfield members in the output.
+    pub include_reserved: bool,
+}
+
+/// A decoded message, mirroring the dict returned by `parse_ubx_message`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedMessage {
+    pub name: String,
+    pub class_id: u8,
+    pub message_id: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    pub payload_length: usize,
+    pub fields: Fields,
+    /// False when no schema definition matched the frame.
+    pub parsed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_raw: Option<String>,
+}
+
+impl DecodedMessage {
+    /// Name without the `UBX-` prefix.
+    pub fn short_name(&self) -> &str {
+        self.name.strip_prefix("UBX-").unwrap_or(&self.name)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        self.fields.get_path(path)
+    }
+
+    /// GPS time of week in milliseconds, for messages that carry `iTOW`.
+    pub fn itow(&self) -> Option<u32> {
+        self.fields.get("iTOW")?.as_u64()?.try_into().ok()
+    }
+
+    /// The records of the first repeated group, else the top-level fields:
+    /// schemas that don't group a message's repeated block give its first
+    /// instance at the top level.
+    pub(crate) fn blocks(&self) -> Vec<&Fields> {
+        let group = self.fields.iter().find_map(|(_, value)| {
+            let items = value.as_array()?;
+            items.first()?.as_record()?;
+            Some(items.iter().filter_map(Value::as_record).collect())
+        });
+        group.unwrap_or_else(|| vec![&self.fields])
+    }
+
+    /// One-line summary: the short name followed by up to `max_fields`
+    /// top-level scalar fields, e.g. `NAV-PVT iTOW=1000 year=2024 ...`.
+    pub fn summary(&self, max_fields: usize) -> String {
+        let mut line = self.short_name().to_string();
+        if let Some(variant) = &self.variant {
+            line.push_str(&format!(" ({variant})"));
+        }
+        if !self.parsed {
+            line.push_str(&format!(" len={}", self.payload_length));
+            return line;
+        }
+        let scalars = self
+            .fields
+            .iter()
+            .filter(|(_, v)| !matches!(v, Value::Array(_) | Value::Record(_)));
+        for (name, value) in scalars.take(max_fields) {
+            line.push_str(&format!(" {name}={value}"));
+        }
+        line
+    }
+}
+
+/// Schema-driven payload decoder.
+#[derive(Debug, Clone, Copy)]
+pub struct Decoder<'s> {
+    schema: &'s Schema,
+    options: DecodeOptions,
+}
+
+impl<'s> Decoder<'s> {
+    pub fn new(schema: &'s Schema) -> Self {
+        Self::with_options(schema, DecodeOptions::default())
+    }
+
+    pub fn with_options(schema: &'s Schema, options: DecodeOptions) -> Self {
+        Self { schema, options }
+    }
+
+    pub fn schema(&self) -> &'s Schema {
+        self.schema
+    }
+
+    pub fn options(&self) -> DecodeOptions {
+        self.options
+    }
+
+    pub fn decode_frame(&self, frame: &FrameRef<'_>) -> DecodedMessage {
+        self.decode(frame.class, frame.id, frame.payload)
+    }
+
+    /// Decode a payload for the given class/ID.
+    ///
+    /// Unknown messages, and variant messages whose variant cannot be
+    /// determined, are returned with `parsed == false` and the raw payload.
+    pub fn decode(&self, class_id: u8, message_id: u8, payload: &[u8]) -> DecodedMessage {
+        let unparsed = |name: String| DecodedMessage {
+            name,
+            class_id,
+            message_id,
+            variant: None,
+            payload_length: payload.len(),
+            fields: Fields::new(),
+            parsed: false,
+            payload_raw: Some(hex::encode(payload)),
+        };
+
+        let Some(msg) = self.schema.message_for_payload(class_id, message_id, payload) else {
+            return unparsed(format!("UBX-{class_id:02X}-{message_id:02X}"));
+        };
+        let Some((def, variant)) = msg.payload_for(payload) else {
+            return unparsed(msg.name.clone());
+        };
+
+        DecodedMessage {
+            name: msg.name.clone(),
+            class_id,
+            message_id,
+            variant: variant.map(|v| v.name.clone()),
+            payload_length: payload.len(),
+            fields: self.decode_payload(def, payload),
+            parsed: true,
+            payload_raw: None,
+        }
+    }
+
+    /// Decode a payload against a specific definition.
+    pub fn decode_payload(&self, def: &PayloadDef, payload: &[u8]) -> Fields {
+        let mut raw = HashMap::new();
+        let mut out = Fields::new();
+        let end = self.decode_fields(&def.fields, payload, &mut raw, &mut out);
+
+        for group in &def.repeated_groups {
+            let base = match &group.base_offset {
+                BaseOffset::Fixed(n) => *n,
+                BaseOffset::Dynamic { base, terms } => terms.iter().fold(*base, |acc, (field, mult)| {
+                    acc + raw.get(field.as_str()).copied().unwrap_or(0) as usize * mult
+                }),
+                BaseOffset::AfterFields => end,
+            };
+            let size = if group.group_size > 0 {
+                group.group_size
+            } else {
+                fields_extent(&group.fields)
+            };
+            let items = self.decode_group(&group.fields, &group.count, size, &def.fields, payload, base, &raw);
+            out.push(group.name.clone(), Value::Array(items));
+        }
+        out
+    }
+
+    /// Decode `defs` relative to the start of `data`. Returns the end offset
+    /// of the last field decoded.
+    fn decode_fields(
+        &self,
+        defs: &[FieldDef],
+        data: &[u8],
+        raw: &mut HashMap<String, u64>,
+        out: &mut Fields,
+    ) -> usize {
+        let mut end = 0;
+        for field in defs {
+            // Fields at formula offsets can't be placed.
+            let Some(offset) = field.byte_offset else {
+                continue;
+            };
+            // Field beyond the payload (short variable-length message).
+            if offset >= data.len() {
+                continue;
+            }
+            let value = match &field.data_type {
+                DataType::Scalar(base) => {
+                    let Some(bytes) = data.get(offset..offset + base.size()) else {
+                        continue;
+                    };
+                    end = end.max(offset + base.size());
+                    let value = read_scalar(*base, bytes);
+                    if let Some(v) = value.as_u64() {
+                        raw.insert(field.name.clone(), v);
+                    }
+                    value
+                }
+                DataType::Array { base, count } => {
+                    let available = (data.len() - offset) / base.size();
+                    let n = resolve_count(count, defs, |name| raw.get(name).copied())
+                        .unwrap_or(available)
+                        .min(available);
+                    let bytes = &data[offset..offset + n * base.size()];
+                    end = end.max(offset + bytes.len());
+                    read_array(*base, bytes)
+                }
+                DataType::Group {
+                    fields,
+                    count,
+                    element_size,
+                } => {
+                    let items = self.decode_group(fields, count, *element_size, defs, data, offset, raw);
+                    end = end.max(offset + items.len() * element_size);
+                    Value::Array(items)
+                }
+                DataType::Unknown(_) => continue,
+            };
+            if field.reserved && !self.options.include_reserved {
+                continue;
+            }
+            out.push(field.name.clone(), self.present(field, value));
+        }
+        end
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_group(
+        &self,
+        fields: &[FieldDef],
+        count: &Count,
+        size: usize,
+        scope: &[FieldDef],
+        data: &[u8],
+        base: usize,
+        raw: &HashMap<String, u64>,
+    ) -> Vec<Value> {
+        if size == 0 || base >= data.len() {
+            return Vec::new();
+        }
+        let available = (data.len() - base) / size;
+        let n = resolve_count(count, scope, |name| raw.get(name).copied()).unwrap_or(available).min(available);
+        (0..n)
+            .map(|i| {
+                let start = base + i * size;
+                let mut inner_raw = raw.clone();
+                let mut record = Fields::new();
+                self.decode_fields(fields, &data[start..start + size], &mut inner_raw, &mut record);
+                Value::Record(record)
+            })
+            .collect()
+    }
+
+    /// Apply scaling and bitfield expansion to a raw value.
+    fn present(&self, field: &FieldDef, value: Value) -> Value {
+        if self.options.expand_flags && !field.bits.is_empty() {
+            if let Some(raw) = value.as_u64() {
+                let mut members = Fields::new();
+                for bit in &field.bits {
+                    if bit.reserved && !self.options.include_reserved {
+                        continue;
+                    }
+                    members.push(bit.name.clone(), Value::Unsigned(bit.extract(raw)));
+                }
+                return Value::Record(members);
+            }
+        }
+        if self.options.scale {
+            if let Some(mult) = field.multiplier() {
+                return scale_value(value, mult);
+            }
+        }
+        value
+    }
+}
+
+fn scale_value(value: Value, mult: f64) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| scale_value(v, mult)).collect()),
+        v => match v.as_f64() {
+            Some(x) => Value::Float(x * mult),
+            None => v,
+        },
+    }
+}
+
+/// Resolve a count against already-decoded raw values. Dotted names refer to
+/// bitfield members (`flags.numMeas`).
+pub(crate) fn resolve_count(count: &Count, scope: &[FieldDef], raw: impl Fn(&str) -> Option<u64>) -> Option<usize> {
+    match count {
+        Count::Fixed(n) => Some(*n),
+        Count::Remaining => None,
+        Count::Field { name, multiplier } => {
+            let value = match name.split_once('.') {
+                None => raw(name)?,
+                Some((field, member)) => {
+                    let whole = raw(field)?;
+                    let def = scope.iter().find(|f| f.name == field)?;
+                    def.bits.iter().find(|b| b.name == member)?.extract(whole)
+                }
+            };
+            Some(value as usize * multiplier)
+        }
+    }
+}
+
+/// Read one little-endian scalar. `bytes` must be exactly `base.size()` long.
+pub fn read_scalar(base: BaseType, bytes: &[u8]) -> Value {
+    let mut buf = [0u8; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    let u = u64::from_le_bytes(buf);
+    match base {
+        BaseType::U1 | BaseType::X1 | BaseType::U2 | BaseType::X2 | BaseType::U4 | BaseType::X4 => {
+            Value::Unsigned(u)
+        }
+        BaseType::I1 => Value::Signed(i64::from(bytes[0] as i8)),
+        BaseType::I2 => Value::Signed(i64::from(u as u16 as i16)),
+        BaseType::I4 => Value::Signed(i64::from(u as u32 as i32)),
+        BaseType::I8 => Value::Signed(u as i64),
+        BaseType::R4 => Value::Float(f64::from(f32::from_bits(u as u32))),
+        BaseType::R8 => Value::Float(f64::from_bits(u)),
+        BaseType::CH => Value::Text(char::from(bytes[0]).to_string()),
+    }
+}
+
+fn read_array(base: BaseType, bytes: &[u8]) -> Value {
+    if base == BaseType::CH {
+        let text: String = bytes.iter().map(|&b| char::from(b)).collect();
+        return Value::Text(text.trim_end_matches('\0').to_string());
+    }
+    Value::Array(bytes.chunks_exact(base.size()).map(|c| read_scalar(base, c)).collect())
+}
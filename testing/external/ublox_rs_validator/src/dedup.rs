@@ -0,0 +1,126 @@
+//! Removal of repeated frames.
+//!
+//! A logger that writes a buffer twice, or a poll answered while the same
+//! message is also output periodically, leaves byte-identical frames in a
+//! log. A frame is dropped when an identical one, found by hash and then
+//! compared byte for byte, was kept within the window before it. Time is the
+//! receiver's: the clock follows the iTOW of each message that has one, and
+//! messages without an iTOW take the time of the last one that does; frames
+//! before the first iTOW count as one instant. Everything that is not a
+//! repeated frame, NMEA and RTCM between frames included, is kept.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use crate::decode::Decoder;
+use crate::frame::{frames, FrameRef};
+use crate::schema::Schema;
+use crate::time::itow_delta;
+
+/// Default window, in milliseconds.
+pub const DEFAULT_WINDOW_MS: u32 = 1_000;
+
+/// Frames seen and repeats removed, overall and by message type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    pub frames: usize,
+    pub removed: usize,
+    pub removed_bytes: usize,
+    /// Bytes outside UBX frames (NMEA, RTCM, frames that fail their
+    /// checksum); [`Dedup::strip`] passes them through.
+    pub other_bytes: usize,
+    /// Removed frames by message name without the `UBX-` prefix.
+    pub by_type: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dedup {
+    /// How long after a frame an identical one counts as a repeat.
+    pub window_ms: u32,
+}
+
+impl Default for Dedup {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW_MS)
+    }
+}
+
+impl Dedup {
+    pub fn new(window_ms: u32) -> Self {
+        Self { window_ms }
+    }
+
+    /// Frames of `input` that are not repeats, in order.
+    pub fn apply<'a>(&self, schema: &Schema, input: &'a [u8]) -> (Vec<FrameRef<'a>>, DedupStats) {
+        let (frames, stats) = self.mark(schema, input);
+        (frames.into_iter().filter(|(_, repeat)| !repeat).map(|(frame, _)| frame).collect(), stats)
+    }
+
+    /// `input` without its repeated frames; all other bytes are kept in order.
+    pub fn strip(&self, schema: &Schema, input: &[u8]) -> (Vec<u8>, DedupStats) {
+        let (frames, stats) = self.mark(schema, input);
+        let mut out = Vec::with_capacity(input.len() - stats.removed_bytes);
+        let mut pos = 0;
+        for (frame, _) in frames.iter().filter(|(_, repeat)| *repeat) {
+            out.extend_from_slice(&input[pos..frame.offset]);
+            pos = frame.offset + frame.raw.len();
+        }
+        out.extend_from_slice(&input[pos..]);
+        (out, stats)
+    }
+
+    /// Every frame of `input`, and whether it is a repeat.
+    fn mark<'a>(&self, schema: &Schema, input: &'a [u8]) -> (Vec<(FrameRef<'a>, bool)>, DedupStats) {
+        let decoder = Decoder::new(schema);
+        let window = i64::from(self.window_ms);
+        let mut stats = DedupStats::default();
+        let (mut now, mut last_itow) = (0i64, None);
+        // Kept frames by hash, and the same in the order they were kept so
+        // ones older than the window can be forgotten.
+        let mut seen: HashMap<u64, Vec<(i64, &'a [u8])>> = HashMap::new();
+        let mut order: VecDeque<(i64, u64)> = VecDeque::new();
+        let mut marked = Vec::new();
+        let mut framed = 0;
+        for frame in frames(input).flatten() {
+            stats.frames += 1;
+            framed += frame.raw.len();
+            let msg = decoder.decode_frame(&frame);
+            if let Some(itow) = msg.itow() {
+                if let Some(prev) = last_itow {
+                    now += itow_delta(prev, itow);
+                }
+                last_itow = Some(itow);
+            }
+            while let Some(&(time, hash)) = order.front() {
+                if now - time <= window {
+                    break;
+                }
+                order.pop_front();
+                if let Some(entries) = seen.get_mut(&hash) {
+                    entries.retain(|(t, _)| now - t <= window);
+                    if entries.is_empty() {
+                        seen.remove(&hash);
+                    }
+                }
+            }
+
+            let mut hasher = DefaultHasher::new();
+            frame.raw.hash(&mut hasher);
+            let hash = hasher.finish();
+            let entries = seen.entry(hash).or_default();
+            let repeat = entries.iter().any(|(t, raw)| (now - t).abs() <= window && *raw == frame.raw);
+            if repeat {
+                stats.removed += 1;
+                stats.removed_bytes += frame.raw.len();
+                *stats.by_type.entry(msg.short_name().to_string()).or_default() += 1;
+            } else {
+                entries.push((now, frame.raw));
+                order.push_back((now, hash));
+            }
+            marked.push((frame, repeat));
+        }
+        stats.other_bytes = input.len() - framed;
+        (marked, stats)
+    }
+}
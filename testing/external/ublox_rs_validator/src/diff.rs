@@ -0,0 +1,187 @@
+//! Field-level comparison of decoded messages and captures.
+//!
+//! Messages of two captures are paired by name, variant and iTOW; messages
+//! without an iTOW, and repeats within one epoch, pair up in order of
+//! appearance.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::decode::DecodedMessage;
+use crate::value::Value;
+
+/// One field whose value differs; `None` where the field is absent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub path: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+/// Compare the flattened fields of two messages. Floats within `tolerance`
+/// of each other count as equal; scalar arrays are compared element-wise.
+pub fn diff_messages(left: &DecodedMessage, right: &DecodedMessage, tolerance: f64) -> Vec<FieldDiff> {
+    let a = expand_arrays(left.fields.flatten());
+    let b = expand_arrays(right.fields.flatten());
+    let lookup: HashMap<&str, &Value> = b.iter().map(|(p, v)| (p.as_str(), v)).collect();
+
+    let mut out = Vec::new();
+    for (path, value) in &a {
+        match lookup.get(path.as_str()) {
+            Some(other) if values_equal(value, other, tolerance) => {}
+            other => out.push(FieldDiff {
+                path: path.clone(),
+                left: Some(value.clone()),
+                right: other.map(|v| (*v).clone()),
+            }),
+        }
+    }
+    let seen: HashSet<&str> = a.iter().map(|(p, _)| p.as_str()).collect();
+    for (path, value) in &b {
+        if !seen.contains(path.as_str()) {
+            out.push(FieldDiff {
+                path: path.clone(),
+                left: None,
+                right: Some(value.clone()),
+            });
+        }
+    }
+    out
+}
+
+fn expand_arrays(flat: Vec<(String, Value)>) -> Vec<(String, Value)> {
+    let mut out = Vec::with_capacity(flat.len());
+    for (path, value) in flat {
+        match value {
+            Value::Array(items) => {
+                for (i, v) in items.into_iter().enumerate() {
+                    out.push((format!("{path}[{i}]"), v));
+                }
+            }
+            v => out.push((path, v)),
+        }
+    }
+    out
+}
+
+fn values_equal(a: &Value, b: &Value, tolerance: f64) -> bool {
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => (x - y).abs() <= tolerance || (x.is_nan() && y.is_nan()),
+        _ => a == b,
+    }
+}
+
+/// A message of a capture together with its original frame bytes.
+#[derive(Debug, Clone)]
+pub struct CaptureMessage {
+    pub offset: usize,
+    pub raw: Vec<u8>,
+    pub message: DecodedMessage,
+}
+
+/// Key two messages must share to be compared.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchKey {
+    pub name: String,
+    pub variant: Option<String>,
+    pub itow: Option<u32>,
+    /// Position among messages with the same name, variant and iTOW.
+    pub occurrence: usize,
+}
+
+impl MatchKey {
+    pub fn label(&self) -> String {
+        let mut label = self.name.strip_prefix("UBX-").unwrap_or(&self.name).to_string();
+        if let Some(variant) = &self.variant {
+            label.push_str(&format!(" ({variant})"));
+        }
+        if let Some(itow) = self.itow {
+            label.push_str(&format!(" iTOW={itow}"));
+        }
+        if self.occurrence > 0 || self.itow.is_none() {
+            label.push_str(&format!(" #{}", self.occurrence));
+        }
+        label
+    }
+}
+
+fn keys(messages: &[CaptureMessage]) -> Vec<MatchKey> {
+    let mut counts: HashMap<(String, Option<String>, Option<u32>), usize> = HashMap::new();
+    messages
+        .iter()
+        .map(|m| {
+            let base = (m.message.name.clone(), m.message.variant.clone(), m.message.itow());
+            let count = counts.entry(base.clone()).or_default();
+            let key = MatchKey {
+                name: base.0,
+                variant: base.1,
+                itow: base.2,
+                occurrence: *count,
+            };
+            *count += 1;
+            key
+        })
+        .collect()
+}
+
+/// A matched pair of messages; indices refer to the compared slices.
+#[derive(Debug, Clone)]
+pub struct MessageDiff {
+    pub key: MatchKey,
+    pub left: usize,
+    pub right: usize,
+    pub fields: Vec<FieldDiff>,
+    /// True when the original frames are byte-identical.
+    pub bytes_equal: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CaptureDiff {
+    /// Every matched pair, in left-capture order.
+    pub matched: Vec<MessageDiff>,
+    /// Unmatched messages, as indices into the left and right captures.
+    pub only_left: Vec<(MatchKey, usize)>,
+    pub only_right: Vec<(MatchKey, usize)>,
+}
+
+impl CaptureDiff {
+    /// Number of matched pairs whose decoded fields differ.
+    pub fn changed(&self) -> usize {
+        self.matched.iter().filter(|m| !m.fields.is_empty()).count()
+    }
+
+    /// Matched pairs that decode identically but differ in their bytes.
+    pub fn bytes_only(&self) -> usize {
+        self.matched
+            .iter()
+            .filter(|m| m.fields.is_empty() && !m.bytes_equal)
+            .count()
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.matched.iter().all(|m| m.bytes_equal)
+    }
+}
+
+/// Pair up and compare the messages of two captures.
+pub fn diff_captures(left: &[CaptureMessage], right: &[CaptureMessage], tolerance: f64) -> CaptureDiff {
+    let right_keys = keys(right);
+    let mut index: HashMap<&MatchKey, usize> = right_keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+
+    let mut diff = CaptureDiff::default();
+    for (i, key) in keys(left).into_iter().enumerate() {
+        match index.remove(&key) {
+            Some(j) => diff.matched.push(MessageDiff {
+                fields: diff_messages(&left[i].message, &right[j].message, tolerance),
+                bytes_equal: left[i].raw == right[j].raw,
+                key,
+                left: i,
+                right: j,
+            }),
+            None => diff.only_left.push((key, i)),
+        }
+    }
+    let mut rest: Vec<(MatchKey, usize)> = index.into_iter().map(|(k, j)| (k.clone(), j)).collect();
+    rest.sort_by_key(|(_, j)| *j);
+    diff.only_right = rest;
+    diff
+}
@@ -0,0 +1,434 @@
+//! Differential testing: run the same frames through the schema decoder and
+//! independent decoders, normalize what each reports and list where they
+//! disagree.
+//!
+//! The schema decoder is the reference. Every other backend reports an
+//! [`Observation`] per frame: a [`Verdict`], the message name where it has
+//! one and, for field-level backends, flat field values. Fields are compared
+//! in pyubx2's naming, the only other field-level backend: bitfield members
+//! under their own name (`gnssFixOK`) and repeated or array elements with a
+//! 1-based suffix (`cno_04`). A value agrees if it matches the reference's
+//! raw or scaled value, since backends differ in which fields they scale.
+//! Fields only one side knows are counted, not reported.
+//!
+//! A backend that has no definition for a frame the schema decodes is a
+//! coverage gap, not a disagreement; one that rejects it or decodes a frame
+//! the schema cannot is.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+
+use crate::corpus::{load_corpus, ublox_verdict, Verdict, UBLOX_RS};
+use crate::decode::{DecodeOptions, DecodedMessage, Decoder};
+use crate::error::{Error, Result};
+use crate::frame::{frames, parse_frame};
+use crate::schema::Schema;
+use crate::value::Value;
+
+/// Name of the pyubx2 backend.
+pub const PYUBX2: &str = "pyubx2";
+
+/// The pyubx2 batch script shipped next to this crate.
+pub fn default_pyubx2_script() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../pyubx2_batch.py")
+}
+
+/// One frame to check and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Corpus vector path or `log.ubx@offset`.
+    pub source: String,
+    pub frame: Vec<u8>,
+}
+
+/// The frames of every vector in a corpus directory.
+pub fn corpus_samples(dir: &Path) -> Result<Vec<Sample>> {
+    load_corpus(dir)?
+        .into_iter()
+        .map(|(path, vector)| {
+            Ok(Sample {
+                source: path.strip_prefix(dir).unwrap_or(&path).display().to_string(),
+                frame: vector.bytes()?,
+            })
+        })
+        .collect()
+}
+
+/// The valid frames of a log, labelled `label@offset`.
+pub fn log_samples(label: &str, data: &[u8]) -> Vec<Sample> {
+    frames(data)
+        .flatten()
+        .map(|f| Sample {
+            source: format!("{label}@{}", f.offset),
+            frame: f.raw.to_vec(),
+        })
+        .collect()
+}
+
+/// What one backend made of one frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub verdict: Verdict,
+    /// Message name without the `UBX-` prefix, if the backend reports one.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Flat field values in the normal form; empty for verdict-only backends.
+    #[serde(default)]
+    pub fields: BTreeMap<String, Json>,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl Observation {
+    fn verdict(verdict: Verdict, detail: Option<String>) -> Self {
+        Self {
+            verdict,
+            name: None,
+            fields: BTreeMap::new(),
+            detail,
+        }
+    }
+}
+
+/// An independent decoder to compare against the schema.
+pub trait Backend {
+    fn name(&self) -> &str;
+
+    /// One observation per sample, in order. An error means the backend
+    /// could not run at all, e.g. because it is not installed.
+    fn observe(&mut self, schema: &Schema, samples: &[Sample]) -> Result<Vec<Observation>>;
+}
+
+/// The ublox crate, in-process. It reports verdicts only.
+#[derive(Debug, Default)]
+pub struct UbloxBackend;
+
+impl Backend for UbloxBackend {
+    fn name(&self) -> &str {
+        UBLOX_RS
+    }
+
+    fn observe(&mut self, _schema: &Schema, samples: &[Sample]) -> Result<Vec<Observation>> {
+        Ok(samples
+            .iter()
+            .map(|s| {
+                let (verdict, detail) = ublox_verdict(&s.frame);
+                Observation::verdict(verdict, detail)
+            })
+            .collect())
+    }
+}
+
+/// A script speaking the line protocol of `pyubx2_batch.py`: one JSON
+/// request `{"frame", "message_type"}` per line in, one [`Observation`]
+/// per line out.
+#[derive(Debug, Clone)]
+pub struct ScriptBackend {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+impl ScriptBackend {
+    pub fn new(name: &str, program: impl Into<PathBuf>, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// pyubx2 through `python` running `script`.
+    pub fn pyubx2(python: &str, script: &Path) -> Self {
+        Self::new(PYUBX2, python, &[&script.display().to_string()])
+    }
+}
+
+impl Backend for ScriptBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn observe(&mut self, schema: &Schema, samples: &[Sample]) -> Result<Vec<Observation>> {
+        let fail = |msg: String| Error::Differential(format!("{}: {msg}", self.name));
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| fail(format!("cannot run {}: {e}", self.program.display())))?;
+
+        let requests: Vec<String> = samples
+            .iter()
+            .map(|s| {
+                let message_type = parse_frame(&s.frame)
+                    .ok()
+                    .and_then(|f| schema.message_for_payload(f.class, f.id, f.payload))
+                    .map(|m| m.message_type.clone());
+                json!({"frame": hex::encode(&s.frame), "message_type": message_type}).to_string()
+            })
+            .collect();
+        // Write from another thread so a full stdout pipe can't deadlock us.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || {
+            for line in requests {
+                if writeln!(stdin, "{line}").is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut observations = Vec::with_capacity(samples.len());
+        for line in BufReader::new(stdout).lines() {
+            let line = line?;
+            let observation = serde_json::from_str(&line).map_err(|e| fail(format!("bad reply '{line}': {e}")))?;
+            observations.push(observation);
+        }
+        let _ = writer.join();
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let status = output.status.code().map_or_else(|| "a signal".to_string(), |c| format!("status {c}"));
+            return Err(fail(format!("exited with {status}: {}", stderr.trim())));
+        }
+        if observations.len() != samples.len() {
+            return Err(fail(format!("{} replies for {} frames", observations.len(), samples.len())));
+        }
+        Ok(observations)
+    }
+}
+
+/// How the reference decode of one frame looks in the normal form.
+struct Reference {
+    message: DecodedMessage,
+    /// Names a backend may use for the message (`MGA-GPS`, `MGA-GPS-EPH`).
+    names: Vec<String>,
+    raw: BTreeMap<String, Json>,
+    scaled: BTreeMap<String, Json>,
+}
+
+fn reference(schema: &Schema, frame: &[u8]) -> Option<Reference> {
+    let frame = parse_frame(frame).ok()?;
+    let decode = |scale| {
+        let options = DecodeOptions {
+            scale,
+            expand_flags: true,
+            include_reserved: false,
+        };
+        Decoder::with_options(schema, options).decode_frame(&frame)
+    };
+    let message = decode(false);
+    let short = message.short_name().to_string();
+    let mut names = vec![short.clone()];
+    if let Some(variant) = &message.variant {
+        names.push(format!("{short}-{variant}"));
+    }
+    Some(Reference {
+        raw: normalize(&message),
+        scaled: normalize(&decode(true)),
+        names,
+        message,
+    })
+}
+
+/// Flatten a decode into the normal form described in the module docs.
+pub fn normalize(message: &DecodedMessage) -> BTreeMap<String, Json> {
+    let mut out = BTreeMap::new();
+    for (path, value) in message.fields.flatten() {
+        let (key, indices) = normal_key(&path);
+        match value {
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let mut indices = indices.clone();
+                    indices.push(i);
+                    out.insert(suffixed(&key, &indices), serde_json::to_value(item).unwrap_or(Json::Null));
+                }
+            }
+            value => {
+                out.insert(suffixed(&key, &indices), serde_json::to_value(&value).unwrap_or(Json::Null));
+            }
+        }
+    }
+    out
+}
+
+/// The leaf name of a flattened path and its element indices:
+/// `satellites[3].flags.qualityInd` is `qualityInd` at `[3]`.
+fn normal_key(path: &str) -> (String, Vec<usize>) {
+    let mut indices = Vec::new();
+    let mut leaf = path;
+    for part in path.split('.') {
+        let (name, rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        leaf = name;
+        indices.extend(rest.split(['[', ']']).filter_map(|i| i.parse::<usize>().ok()));
+    }
+    (leaf.to_string(), indices)
+}
+
+fn suffixed(key: &str, indices: &[usize]) -> String {
+    indices.iter().fold(key.to_string(), |k, i| format!("{k}_{:02}", i + 1))
+}
+
+fn agrees(a: &Json, b: &Json, tolerance: f64) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => (x - y).abs() <= tolerance * x.abs().max(y.abs()).max(1.0),
+        _ => a == b,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisagreementKind {
+    /// The backend rejected a frame the schema decodes, or decoded one the
+    /// schema cannot.
+    Verdict,
+    /// The backend named the message differently.
+    Name,
+    /// A field value differs.
+    Field,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Disagreement {
+    pub source: String,
+    /// The schema's name for the message.
+    pub message: String,
+    pub backend: String,
+    pub kind: DisagreementKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// The schema's value, verdict or name.
+    pub schema: Json,
+    /// The backend's.
+    pub other: Json,
+}
+
+/// Per-backend tallies.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackendSummary {
+    pub name: String,
+    /// False if the backend could not run; `error` says why.
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub parsed: usize,
+    pub unsupported: usize,
+    pub rejected: usize,
+    /// Fields both sides reported.
+    pub fields_compared: usize,
+    /// Fields only the backend reported, e.g. under a name the schema lacks.
+    pub fields_unmatched: usize,
+    pub disagreements: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Report {
+    pub samples: usize,
+    /// Frames the schema decoded.
+    pub schema_parsed: usize,
+    pub backends: Vec<BackendSummary>,
+    pub disagreements: Vec<Disagreement>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        self.disagreements.is_empty()
+    }
+}
+
+/// Run `samples` through every backend and compare each with the schema.
+/// Floats agree within the relative `tolerance`.
+pub fn run(schema: &Schema, samples: &[Sample], backends: &mut [Box<dyn Backend>], tolerance: f64) -> Report {
+    let references: Vec<Option<Reference>> = samples.iter().map(|s| reference(schema, &s.frame)).collect();
+    let mut report = Report {
+        samples: samples.len(),
+        schema_parsed: references.iter().flatten().filter(|r| r.message.parsed).count(),
+        ..Report::default()
+    };
+    for backend in backends.iter_mut() {
+        let mut summary = BackendSummary {
+            name: backend.name().to_string(),
+            ..BackendSummary::default()
+        };
+        let observations = match backend.observe(schema, samples) {
+            Ok(observations) => observations,
+            Err(e) => {
+                summary.error = Some(e.to_string());
+                report.backends.push(summary);
+                continue;
+            }
+        };
+        summary.available = true;
+        for ((sample, reference), observation) in samples.iter().zip(&references).zip(&observations) {
+            match observation.verdict {
+                Verdict::Parsed => summary.parsed += 1,
+                Verdict::Unsupported => summary.unsupported += 1,
+                Verdict::Rejected => summary.rejected += 1,
+            }
+            let Some(reference) = reference else {
+                continue;
+            };
+            let found = compare(reference, observation, tolerance, &mut summary);
+            summary.disagreements += found.len();
+            report.disagreements.extend(found.into_iter().map(|(kind, field, schema, other)| Disagreement {
+                source: sample.source.clone(),
+                message: reference.message.name.clone(),
+                backend: summary.name.clone(),
+                kind,
+                field,
+                schema,
+                other,
+            }));
+        }
+        report.backends.push(summary);
+    }
+    report
+}
+
+type Found = (DisagreementKind, Option<String>, Json, Json);
+
+fn compare(reference: &Reference, observation: &Observation, tolerance: f64, summary: &mut BackendSummary) -> Vec<Found> {
+    let schema_verdict = if reference.message.parsed { Verdict::Parsed } else { Verdict::Unsupported };
+    let verdict = |v: Verdict| Json::from(v.to_string());
+    match (schema_verdict, observation.verdict) {
+        (Verdict::Parsed, Verdict::Parsed) => {}
+        // The backend lacks the message: a coverage gap.
+        (Verdict::Parsed, Verdict::Unsupported) | (Verdict::Unsupported, Verdict::Unsupported | Verdict::Rejected) => {
+            return Vec::new()
+        }
+        (s, o) => {
+            let other = match &observation.detail {
+                Some(detail) => Json::from(format!("{o} ({detail})")),
+                None => verdict(o),
+            };
+            return vec![(DisagreementKind::Verdict, None, verdict(s), other)];
+        }
+    }
+
+    let mut found = Vec::new();
+    if let Some(name) = &observation.name {
+        let name = name.strip_prefix("UBX-").unwrap_or(name);
+        if !reference.names.iter().any(|n| n == name) {
+            found.push((DisagreementKind::Name, None, Json::from(reference.names[0].clone()), Json::from(name)));
+        }
+    }
+    for (key, value) in &observation.fields {
+        let (Some(raw), Some(scaled)) = (reference.raw.get(key), reference.scaled.get(key)) else {
+            summary.fields_unmatched += 1;
+            continue;
+        };
+        summary.fields_compared += 1;
+        if !agrees(raw, value, tolerance) && !agrees(scaled, value, tolerance) {
+            found.push((DisagreementKind::Field, Some(key.clone()), raw.clone(), value.clone()));
+        }
+    }
+    found
+}
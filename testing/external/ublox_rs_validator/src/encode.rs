@@ -0,0 +1,389 @@
+//! Encode UBX payloads from field values using schema definitions.
+//!
+//! Field values are given as JSON, in the same shape the decoder produces:
+//! numbers for scalars, arrays for arrays and repeated groups, strings for
+//! `CH` arrays and objects for bitfields (member name → value).
+
+use serde_json::{Map, Value as Json};
+
+use crate::error::{Error, Result};
+use crate::frame::Frame;
+use crate::schema::{
+    fields_extent, BaseOffset, BaseType, Count, DataType, Discriminator, FieldDef, MessageDef, PayloadDef,
+    PayloadLength, Schema,
+};
+
+/// Input options for the encoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Values are physical (scaled) quantities; divide by the schema scale
+    /// factor before encoding.
+    pub scaled: bool,
+}
+
+/// Schema-driven payload encoder.
+#[derive(Debug, Clone, Copy)]
+pub struct Encoder<'s> {
+    schema: &'s Schema,
+    options: EncodeOptions,
+}
+
+impl<'s> Encoder<'s> {
+    pub fn new(schema: &'s Schema) -> Self {
+        Self::with_options(schema, EncodeOptions::default())
+    }
+
+    pub fn with_options(schema: &'s Schema, options: EncodeOptions) -> Self {
+        Self { schema, options }
+    }
+
+    /// Encode a message description of the form
+    /// `{"name": "CFG-RATE", "variant": null, "fields": {...}}`.
+    pub fn encode_json(&self, json: &Json) -> Result<Frame> {
+        let name = json
+            .get("name")
+            .and_then(Json::as_str)
+            .ok_or_else(|| Error::Encode("message description needs a 'name'".into()))?;
+        let empty = Map::new();
+        let fields = match json.get("fields") {
+            Some(Json::Object(f)) => f,
+            None | Some(Json::Null) => &empty,
+            Some(_) => return Err(Error::Encode(format!("{name}: 'fields' must be an object"))),
+        };
+        let variant = json.get("variant").and_then(Json::as_str);
+        self.encode(name, variant, fields)
+    }
+
+    /// Encode a record as `ubx-decode` writes it in JSON or NDJSON, e.g. an
+    /// edited copy of a capture. Records the decoder couldn't parse are
+    /// rebuilt from `class_id`, `message_id` and `payload_raw`; other keys
+    /// than `name`, `variant` and `fields` are ignored.
+    pub fn encode_record(&self, json: &Json) -> Result<Frame> {
+        if let Some(raw) = json.get("payload_raw").and_then(Json::as_str) {
+            let id = |key: &str| {
+                json.get(key)
+                    .and_then(Json::as_u64)
+                    .and_then(|v| u8::try_from(v).ok())
+                    .ok_or_else(|| Error::Encode(format!("a record with payload_raw needs a '{key}'")))
+            };
+            let payload = hex::decode(raw).map_err(|e| Error::Encode(format!("payload_raw: {e}")))?;
+            return Ok(Frame::new(id("class_id")?, id("message_id")?, payload));
+        }
+        self.encode_json(json)
+    }
+
+    /// Encode a message by name. `variant` selects a variant of multi-variant
+    /// messages; variant aliases such as `MGA-GPS-EPH` also work.
+    pub fn encode(&self, name: &str, variant: Option<&str>, fields: &Map<String, Json>) -> Result<Frame> {
+        let msg = self
+            .schema
+            .message_by_name(name)
+            .ok_or_else(|| Error::UnknownMessage(name.to_string()))?;
+        let variant = variant.map(String::from).or_else(|| alias_variant(msg, name));
+        let payload = self.encode_payload(msg, variant.as_deref(), fields)?;
+        Ok(Frame::new(msg.class_id, msg.message_id, payload))
+    }
+
+    pub fn encode_payload(
+        &self,
+        msg: &MessageDef,
+        variant: Option<&str>,
+        fields: &Map<String, Json>,
+    ) -> Result<Vec<u8>> {
+        let (def, discriminator) = if msg.variants.is_empty() {
+            let def = msg
+                .payload
+                .as_ref()
+                .ok_or_else(|| Error::Encode(format!("{}: no payload definition", msg.name)))?;
+            (def, None)
+        } else {
+            let v = match variant {
+                Some(name) => msg
+                    .variant(name)
+                    .ok_or_else(|| Error::Encode(format!("{}: unknown variant '{name}'", msg.name)))?,
+                None => &msg.variants[0],
+            };
+            (&v.payload, Some(&v.discriminator))
+        };
+
+        let mut out = Vec::new();
+        let ctx = Context {
+            msg: &msg.name,
+            options: self.options,
+        };
+        ctx.encode_def(def, fields, &mut out)?;
+
+        if let Some(Discriminator::Field { byte_offset, value }) = discriminator {
+            put(&mut out, *byte_offset, &[*value as u8]);
+        }
+        if let PayloadLength::Fixed(n) = def.length {
+            if out.len() < n {
+                out.resize(n, 0);
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn alias_variant(msg: &MessageDef, requested: &str) -> Option<String> {
+    let requested = crate::schema::normalize_name(requested);
+    let suffix = requested.strip_prefix(&msg.name)?.strip_prefix('-')?;
+    msg.variant(suffix).map(|v| v.name.clone())
+}
+
+struct Context<'a> {
+    msg: &'a str,
+    options: EncodeOptions,
+}
+
+impl Context<'_> {
+    fn encode_def(&self, def: &PayloadDef, values: &Map<String, Json>, out: &mut Vec<u8>) -> Result<()> {
+        self.check_names(&def.fields, Some(def), values)?;
+        let values = with_counts(&def.fields, values, def);
+        let end = self.encode_fields(&def.fields, &values, out, 0)?;
+
+        for group in &def.repeated_groups {
+            let base = match &group.base_offset {
+                BaseOffset::Fixed(n) => *n,
+                BaseOffset::Dynamic { base, terms } => terms.iter().fold(*base, |acc, (field, mult)| {
+                    acc + values.get(field).and_then(Json::as_u64).unwrap_or(0) as usize * mult
+                }),
+                BaseOffset::AfterFields => end,
+            };
+            let items = match values.get(&group.name) {
+                None | Some(Json::Null) => continue,
+                Some(Json::Array(items)) => items,
+                Some(_) => return Err(self.error(&group.name, "expected an array of records")),
+            };
+            let size = if group.group_size > 0 {
+                group.group_size
+            } else {
+                fields_extent(&group.fields)
+            };
+            self.encode_records(&group.name, &group.fields, size, items, out, base)?;
+        }
+        Ok(())
+    }
+
+    fn check_names(&self, defs: &[FieldDef], def: Option<&PayloadDef>, values: &Map<String, Json>) -> Result<()> {
+        for name in values.keys() {
+            let known = defs.iter().any(|f| &f.name == name)
+                || def.is_some_and(|d| d.repeated_groups.iter().any(|g| &g.name == name));
+            if !known {
+                return Err(self.error(name, "no such field"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode fields relative to `base`, returning the end of the last one.
+    fn encode_fields(&self, defs: &[FieldDef], values: &Map<String, Json>, out: &mut Vec<u8>, base: usize) -> Result<usize> {
+        let mut end = base;
+        for field in defs {
+            let Some(offset) = field.byte_offset else {
+                continue;
+            };
+            let at = base + offset;
+            let value = match values.get(&field.name) {
+                Some(v) if !v.is_null() => Some(v),
+                _ => None,
+            };
+            let written = match &field.data_type {
+                DataType::Scalar(b) => {
+                    let bytes = match (value, field.fixed_value) {
+                        (Some(v), _) => self.scalar(field, *b, v)?,
+                        (None, Some(fixed)) => int_bytes(*b, fixed as i128).map_err(|e| self.error(&field.name, &e))?,
+                        (None, None) => vec![0; b.size()],
+                    };
+                    put(out, at, &bytes);
+                    bytes.len()
+                }
+                DataType::Array { base: b, count } => {
+                    let bytes = match value {
+                        Some(v) => self.array(field, *b, v)?,
+                        None => match count {
+                            Count::Fixed(n) => vec![0; n * b.size()],
+                            _ => Vec::new(),
+                        },
+                    };
+                    let len = match count {
+                        Count::Fixed(n) => n * b.size(),
+                        _ => bytes.len(),
+                    };
+                    let mut padded = bytes;
+                    padded.resize(len, 0);
+                    put(out, at, &padded);
+                    len
+                }
+                DataType::Group {
+                    fields, element_size, ..
+                } => match value {
+                    Some(Json::Array(items)) => {
+                        self.encode_records(&field.name, fields, *element_size, items, out, at)?
+                    }
+                    Some(_) => return Err(self.error(&field.name, "expected an array of records")),
+                    None => 0,
+                },
+                DataType::Unknown(_) => continue,
+            };
+            if written > 0 {
+                end = end.max(at + written);
+            } else if out.len() < at {
+                out.resize(at, 0);
+            }
+        }
+        Ok(end)
+    }
+
+    fn encode_records(
+        &self,
+        name: &str,
+        fields: &[FieldDef],
+        size: usize,
+        items: &[Json],
+        out: &mut Vec<u8>,
+        base: usize,
+    ) -> Result<usize> {
+        for (i, item) in items.iter().enumerate() {
+            let record = item
+                .as_object()
+                .ok_or_else(|| self.error(&format!("{name}[{i}]"), "expected an object"))?;
+            self.check_names(fields, None, record)?;
+            let start = base + i * size;
+            self.encode_fields(fields, record, out, start)?;
+            if out.len() < start + size {
+                out.resize(start + size, 0);
+            }
+        }
+        Ok(items.len() * size)
+    }
+
+    fn scalar(&self, field: &FieldDef, base: BaseType, value: &Json) -> Result<Vec<u8>> {
+        let err = |msg: &str| self.error(&field.name, msg);
+        match value {
+            Json::Object(members) => {
+                if field.bits.is_empty() {
+                    return Err(err("not a bitfield"));
+                }
+                let mut raw: u64 = 0;
+                for (member, v) in members {
+                    let bit = field
+                        .bits
+                        .iter()
+                        .find(|b| &b.name == member)
+                        .ok_or_else(|| err(&format!("no bitfield member '{member}'")))?;
+                    let v = v
+                        .as_u64()
+                        .ok_or_else(|| err(&format!("'{member}' must be a non-negative integer")))?;
+                    if bit.bit_width < 64 && v >> bit.bit_width != 0 {
+                        return Err(err(&format!("'{member}' = {v} does not fit in {} bits", bit.bit_width)));
+                    }
+                    raw |= v << bit.bit_offset;
+                }
+                int_bytes(base, raw as i128).map_err(|e| err(&e))
+            }
+            Json::String(s) if base == BaseType::CH => Ok(vec![s.bytes().next().unwrap_or(0)]),
+            Json::Number(_) => self.number(field, base, value).map_err(|e| err(&e)),
+            _ => Err(err("expected a number")),
+        }
+    }
+
+    fn number(&self, field: &FieldDef, base: BaseType, value: &Json) -> std::result::Result<Vec<u8>, String> {
+        let mult = if self.options.scaled { field.multiplier() } else { None };
+        if base.is_float() {
+            let v = value.as_f64().ok_or("expected a number")?;
+            let v = mult.map_or(v, |m| v / m);
+            return Ok(match base {
+                BaseType::R4 => (v as f32).to_le_bytes().to_vec(),
+                _ => v.to_le_bytes().to_vec(),
+            });
+        }
+        let int = match (mult, value.as_i64(), value.as_u64()) {
+            (Some(m), _, _) => (value.as_f64().ok_or("expected a number")? / m).round() as i128,
+            (None, Some(i), _) => i as i128,
+            (None, None, Some(u)) => u as i128,
+            (None, None, None) => {
+                let f = value.as_f64().ok_or("expected a number")?;
+                if f.fract() != 0.0 {
+                    return Err(format!("{f} is not an integer"));
+                }
+                f as i128
+            }
+        };
+        int_bytes(base, int)
+    }
+
+    fn array(&self, field: &FieldDef, base: BaseType, value: &Json) -> Result<Vec<u8>> {
+        match value {
+            Json::String(s) if base == BaseType::CH => Ok(s.as_bytes().to_vec()),
+            Json::Array(items) => {
+                let mut out = Vec::with_capacity(items.len() * base.size());
+                for item in items {
+                    out.extend(self.scalar(field, base, item)?);
+                }
+                Ok(out)
+            }
+            _ => Err(self.error(&field.name, "expected an array")),
+        }
+    }
+
+    fn error(&self, field: &str, msg: &str) -> Error {
+        Error::Encode(format!("{}.{field}: {msg}", self.msg))
+    }
+}
+
+/// Fill in count fields the caller left out from the length of the array or
+/// group they control.
+fn with_counts(defs: &[FieldDef], values: &Map<String, Json>, def: &PayloadDef) -> Map<String, Json> {
+    let mut inferred = Vec::new();
+    for field in defs {
+        let (count, len) = match (&field.data_type, values.get(&field.name)) {
+            (DataType::Array { base, count }, Some(Json::String(s))) if *base == BaseType::CH => (count, s.len()),
+            (DataType::Array { count, .. } | DataType::Group { count, .. }, Some(Json::Array(a))) => (count, a.len()),
+            _ => continue,
+        };
+        inferred.push((count, len));
+    }
+    for group in &def.repeated_groups {
+        if let Some(Json::Array(a)) = values.get(&group.name) {
+            inferred.push((&group.count, a.len()));
+        }
+    }
+
+    let mut values = values.clone();
+    for (count, len) in inferred {
+        if let Count::Field { name, multiplier } = count {
+            if !name.contains('.') && !values.contains_key(name) && *multiplier > 0 {
+                values.insert(name.clone(), Json::from(len / multiplier));
+            }
+        }
+    }
+    values
+}
+
+/// Little-endian bytes for an integer, checking it fits the type.
+fn int_bytes(base: BaseType, v: i128) -> std::result::Result<Vec<u8>, String> {
+    let (min, max): (i128, i128) = match base {
+        BaseType::U1 | BaseType::X1 | BaseType::CH => (0, u8::MAX.into()),
+        BaseType::I1 => (i8::MIN.into(), i8::MAX.into()),
+        BaseType::U2 | BaseType::X2 => (0, u16::MAX.into()),
+        BaseType::I2 => (i16::MIN.into(), i16::MAX.into()),
+        BaseType::U4 | BaseType::X4 => (0, u32::MAX.into()),
+        BaseType::I4 => (i32::MIN.into(), i32::MAX.into()),
+        BaseType::I8 => (i64::MIN.into(), i64::MAX.into()),
+        BaseType::R4 => return Ok((v as f32).to_le_bytes().to_vec()),
+        BaseType::R8 => return Ok((v as f64).to_le_bytes().to_vec()),
+    };
+    if v < min || v > max {
+        return Err(format!("{v} out of range for {}", base.as_str()));
+    }
+    Ok(v.to_le_bytes()[..base.size()].to_vec())
+}
+
+/// Write `bytes` at `offset`, growing the buffer as needed.
+fn put(out: &mut Vec<u8>, offset: usize, bytes: &[u8]) {
+    if out.len() < offset + bytes.len() {
+        out.resize(offset + bytes.len(), 0);
+    }
+    out[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
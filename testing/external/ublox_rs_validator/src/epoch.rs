@@ -0,0 +1,183 @@
+//! Consistency of the messages a receiver outputs for one navigation epoch.
+//!
+//! NAV-PVT, NAV-SAT, NAV-DOP and NAV-HPPOSLLH of an epoch share its iTOW and
+//! describe the same solution, so they must agree: the satellites NAV-SAT
+//! flags as used are NAV-PVT's `numSV`, both DOP messages report the same
+//! `pDOP`, the high-precision position rounds to the PVT one, and the ECEF
+//! position (NAV-POSECEF or NAV-HPPOSECEF) is the LLH one converted. A
+//! disagreement points at a receiver mixing epochs or a decoder reading a
+//! field wrong. Messages are grouped by consecutive iTOW; the checks run
+//! when the next epoch starts and at [`EpochChecker::finish`].
+//!
+//! Messages must be decoded with scaling and flag expansion enabled.
+
+use crate::decode::DecodedMessage;
+use crate::geo::{ecef_position, llh_position, llh_to_ecef, Ecef, Llh};
+use crate::semantic::{warn, Rule, Warning};
+use crate::value::Value;
+
+/// How far values of one epoch may differ before they are reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Between NAV-PVT and NAV-DOP `pDOP`, which both carry 0.01 steps.
+    pub dop: f64,
+    /// Between the NAV-PVT and NAV-HPPOSLLH positions, and between the LLH
+    /// and ECEF positions, metres.
+    pub position: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        // PVT rounds to 1e-7 deg, about 1.1 cm.
+        Self {
+            dop: 0.011,
+            position: 0.05,
+        }
+    }
+}
+
+/// What the epoch's messages said.
+#[derive(Debug, Default)]
+struct Epoch {
+    itow: u32,
+    pvt_num_sv: Option<u64>,
+    pvt_pdop: Option<f64>,
+    pvt_position: Option<(f64, f64)>,
+    sat_used: Option<u64>,
+    dop_pdop: Option<f64>,
+    hp_position: Option<(f64, f64)>,
+    /// The most precise LLH and ECEF positions, with the message each is from.
+    llh: Option<(String, Llh)>,
+    ecef: Option<(String, Ecef)>,
+}
+
+/// Groups a decoded message stream into epochs and checks each one.
+#[derive(Debug, Default)]
+pub struct EpochChecker {
+    tolerances: Tolerances,
+    epoch: Option<Epoch>,
+    epochs: usize,
+}
+
+impl EpochChecker {
+    pub fn new(tolerances: Tolerances) -> Self {
+        Self {
+            tolerances,
+            ..Self::default()
+        }
+    }
+
+    /// Epochs checked so far.
+    pub fn epochs(&self) -> usize {
+        self.epochs
+    }
+
+    /// Add a message; returns the warnings for the previous epoch when this
+    /// message starts a new one. Messages without an iTOW are ignored.
+    pub fn push(&mut self, msg: &DecodedMessage) -> Vec<Warning> {
+        let Some(itow) = msg.itow() else {
+            return Vec::new();
+        };
+        let mut warnings = Vec::new();
+        if self.epoch.as_ref().is_some_and(|e| e.itow != itow) {
+            warnings = self.finish();
+        }
+        let epoch = self.epoch.get_or_insert_with(|| Epoch {
+            itow,
+            ..Epoch::default()
+        });
+        let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => {
+                epoch.pvt_num_sv = msg.get("numSV").and_then(Value::as_u64);
+                epoch.pvt_pdop = num("pDOP");
+                epoch.pvt_position = num("lat").zip(num("lon"));
+            }
+            "UBX-NAV-SAT" => epoch.sat_used = Some(used_satellites(msg)),
+            "UBX-NAV-DOP" => epoch.dop_pdop = num("pDOP"),
+            "UBX-NAV-HPPOSLLH" => {
+                let lat = num("lat").map(|lat| lat + num("latHp").unwrap_or(0.0));
+                let lon = num("lon").map(|lon| lon + num("lonHp").unwrap_or(0.0));
+                epoch.hp_position = lat.zip(lon);
+            }
+            _ => {}
+        }
+        let name = msg.short_name();
+        if let Some(llh) = llh_position(msg) {
+            if name.contains("HP") || epoch.llh.is_none() {
+                epoch.llh = Some((name.to_string(), llh));
+            }
+        }
+        if let Some(ecef) = ecef_position(msg) {
+            if name.contains("HP") || epoch.ecef.is_none() {
+                epoch.ecef = Some((name.to_string(), ecef));
+            }
+        }
+        warnings
+    }
+
+    /// Check the epoch in progress, if any.
+    pub fn finish(&mut self) -> Vec<Warning> {
+        let Some(e) = self.epoch.take() else {
+            return Vec::new();
+        };
+        self.epochs += 1;
+        let mut warnings = Vec::new();
+        let itow = e.itow;
+        if let (Some(pvt), Some(sat)) = (e.pvt_num_sv, e.sat_used) {
+            if pvt != sat {
+                let message = format!("iTOW {itow}: NAV-PVT numSV {pvt}, NAV-SAT {sat} used");
+                warnings.push(warn(Rule::Epoch, "numSV", message));
+            }
+        }
+        if let (Some(pvt), Some(dop)) = (e.pvt_pdop, e.dop_pdop) {
+            if (pvt - dop).abs() > self.tolerances.dop {
+                let message = format!("iTOW {itow}: NAV-PVT pDOP {pvt}, NAV-DOP {dop}");
+                warnings.push(warn(Rule::Epoch, "pDOP", message));
+            }
+        }
+        if let (Some(pvt), Some(hp)) = (e.pvt_position, e.hp_position) {
+            let distance = separation(pvt, hp);
+            if distance > self.tolerances.position {
+                let message = format!("iTOW {itow}: NAV-HPPOSLLH is {distance:.3} m from NAV-PVT");
+                warnings.push(warn(Rule::Epoch, "lat/lon", message));
+            }
+        }
+        if let (Some((llh_name, llh)), Some((ecef_name, ecef))) = (&e.llh, &e.ecef) {
+            let distance = llh_to_ecef(*llh).distance(ecef);
+            if distance > self.tolerances.position {
+                let message = format!("iTOW {itow}: {ecef_name} is {distance:.3} m from {llh_name}");
+                warnings.push(warn(Rule::Epoch, "ecefX/ecefY/ecefZ", message));
+            }
+        }
+        warnings
+    }
+}
+
+/// Satellites NAV-SAT flags as used in the solution.
+fn used_satellites(msg: &DecodedMessage) -> u64 {
+    let satellites = msg.fields.iter().find_map(|(_, v)| v.as_array()).unwrap_or_default();
+    let used = satellites.iter().filter_map(Value::as_record).filter(|sat| {
+        // svUsed is bit 3 of flags when the schema has no member names.
+        match sat.get_path("flags.svUsed") {
+            Some(v) => v.as_u64() == Some(1),
+            None => sat.get("flags").and_then(Value::as_u64).unwrap_or(0) & 0x08 != 0,
+        }
+    });
+    used.count() as u64
+}
+
+/// Distance in metres between two positions on the ellipsoid.
+fn separation((lat_a, lon_a): (f64, f64), (lat_b, lon_b): (f64, f64)) -> f64 {
+    let a = llh_to_ecef(Llh {
+        lat: lat_a,
+        lon: lon_a,
+        height: 0.0,
+    });
+    let b = llh_to_ecef(Llh {
+        lat: lat_b,
+        lon: lon_b,
+        height: 0.0,
+    });
+    a.distance(&b)
+}
@@ -0,0 +1,91 @@
+//! Error types shared by the codec and the command-line tools.
+
+use std::io;
+
+/// Result alias used throughout the crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Top-level error type.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("schema error: {0}")]
+    Schema(String),
+
+    #[error("encode error: {0}")]
+    Encode(String),
+
+    #[error("configuration error: {0}")]
+    Config(String),
+
+    /// A malformed `ubx-shell` command; the message is a usage hint.
+    #[error("{0}")]
+    Command(String),
+
+    #[error("capture error: {0}")]
+    Capture(String),
+
+    #[error("corpus error: {0}")]
+    Corpus(String),
+
+    #[error("differential test error: {0}")]
+    Differential(String),
+
+    #[error("NTRIP error: {0}")]
+    Ntrip(String),
+
+    #[error("MQTT error: {0}")]
+    Mqtt(String),
+
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    #[error("index error: {0}")]
+    Index(String),
+
+    #[error("unknown message: {0}")]
+    UnknownMessage(String),
+
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+}
+
+/// Errors raised while framing or validating a single UBX frame.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FrameError {
+    #[error("frame too short: {0} bytes")]
+    TooShort(usize),
+
+    #[error("invalid sync characters: {0:02X} {1:02X}")]
+    InvalidSync(u8, u8),
+
+    #[error("frame truncated: expected {expected} bytes, got {actual}")]
+    Truncated { expected: usize, actual: usize },
+
+    #[error("checksum mismatch for {class:02X}-{id:02X}: expected {expected:02X?}, found {found:02X?}")]
+    Checksum {
+        class: u8,
+        id: u8,
+        expected: [u8; 2],
+        found: [u8; 2],
+    },
+
+    #[error("{class:02X}-{id:02X} declares a {length}-byte payload, over the {limit}-byte limit")]
+    Oversized {
+        class: u8,
+        id: u8,
+        length: usize,
+        limit: usize,
+    },
+
+    #[error("payload of {0} bytes exceeds the 65535-byte UBX limit")]
+    PayloadTooLong(usize),
+
+    #[error("buffer too small: frame needs {needed} bytes, {available} available")]
+    BufferTooSmall { needed: usize, available: usize },
+}
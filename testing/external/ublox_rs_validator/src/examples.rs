@@ -0,0 +1,138 @@
+//! Run the example frames embedded in message definitions.
+//!
+//! An example is a whole frame and the values it must decode to, scaled and
+//! with bitfields expanded the way a manual states them. It fails if the
+//! frame does not parse, is another message or variant than the one it is
+//! attached to, or any listed value differs, so an offset or scale typed
+//! wrongly into a definition shows up as soon as the definition is written.
+
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use crate::decode::{DecodeOptions, Decoder};
+use crate::frame::parse_frame;
+use crate::schema::{normalize_name, Example, MessageDef, Schema};
+
+/// Relative tolerance for floating-point values.
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleFailure {
+    pub message: String,
+    /// Position in the message's `examples`, from 0.
+    pub index: usize,
+    pub description: Option<String>,
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ExampleFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} example {}", self.message, self.index + 1)?;
+        if let Some(description) = &self.description {
+            write!(f, " ({description})")?;
+        }
+        write!(f, ": {}", self.problems.join("; "))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub messages: usize,
+    pub examples: usize,
+    pub failures: Vec<ExampleFailure>,
+}
+
+/// Run the examples of the messages named in `names`, or of every message if
+/// `names` is empty.
+pub fn run(schema: &Schema, names: &[String]) -> Report {
+    let wanted: Vec<String> = names.iter().map(|n| normalize_name(n)).collect();
+    let decoder = Decoder::with_options(
+        schema,
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: true,
+        },
+    );
+    let mut report = Report::default();
+    for msg in &schema.messages {
+        if msg.examples.is_empty() || !(wanted.is_empty() || wanted.contains(&msg.name)) {
+            continue;
+        }
+        report.messages += 1;
+        for (index, example) in msg.examples.iter().enumerate() {
+            report.examples += 1;
+            let problems = check(&decoder, msg, example);
+            if !problems.is_empty() {
+                report.failures.push(ExampleFailure {
+                    message: msg.name.clone(),
+                    index,
+                    description: example.description.clone(),
+                    problems,
+                });
+            }
+        }
+    }
+    report
+}
+
+/// What is wrong with one example of `msg`; empty if it passes.
+pub fn check(decoder: &Decoder<'_>, msg: &MessageDef, example: &Example) -> Vec<String> {
+    let bytes = match hex::decode(&example.frame) {
+        Ok(bytes) => bytes,
+        Err(e) => return vec![format!("frame is not hex: {e}")],
+    };
+    let frame = match parse_frame(&bytes) {
+        Ok(frame) => frame,
+        Err(e) => return vec![format!("bad frame: {e}")],
+    };
+    if frame.raw.len() != bytes.len() {
+        return vec![format!("{} bytes after the frame", bytes.len() - frame.raw.len())];
+    }
+    if (frame.class, frame.id) != (msg.class_id, msg.message_id) {
+        return vec![format!("frame is {:02X}-{:02X}", frame.class, frame.id)];
+    }
+    let decoded = decoder.decode_frame(&frame);
+    if !decoded.parsed {
+        return vec![format!("{}-byte payload does not decode", frame.payload.len())];
+    }
+    if decoded.variant != example.variant {
+        let name = |v: &Option<String>| v.clone().unwrap_or_else(|| "none".into());
+        return vec![format!("variant is {}, expected {}", name(&decoded.variant), name(&example.variant))];
+    }
+
+    let mut problems = Vec::new();
+    for (path, want) in &example.fields {
+        match decoded.fields.get_path(path) {
+            None => problems.push(format!("no field {path}")),
+            Some(got) => {
+                let got = serde_json::to_value(got).unwrap_or(Json::Null);
+                if !matches(want, &got) {
+                    problems.push(format!("{path} = {got}, expected {want}"));
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// Whether a decoded value is the expected one: numbers within
+/// [`TOLERANCE`], `true`/`false` for a 1/0 flag, anything else equal.
+fn matches(want: &Json, got: &Json) -> bool {
+    match (want, got) {
+        (Json::Number(w), Json::Number(g)) => match (w.as_i64(), g.as_i64()) {
+            (Some(w), Some(g)) => w == g,
+            _ => {
+                let (w, g) = (w.as_f64().unwrap_or(f64::NAN), g.as_f64().unwrap_or(f64::NAN));
+                (w - g).abs() <= TOLERANCE * w.abs().max(g.abs())
+            }
+        },
+        (Json::Bool(w), Json::Number(g)) => g.as_u64() == Some(u64::from(*w)),
+        (Json::Array(w), Json::Array(g)) => w.len() == g.len() && w.iter().zip(g).all(|(w, g)| matches(w, g)),
+        (Json::Object(w), Json::Object(g)) => {
+            w.len() == g.len() && w.iter().all(|(k, w)| g.get(k).is_some_and(|g| matches(w, g)))
+        }
+        _ => want == got,
+    }
+}
@@ -0,0 +1,237 @@
+//! Select frames from a log by message type and time window.
+//!
+//! Messages without an iTOW (RXM-SFRBX, MON-*, ...) belong to the epoch of
+//! the most recent message that has one. UTC windows are mapped onto iTOW
+//! using the first NAV-PVT or NAV-TIMEUTC with a valid date and time, or
+//! NAV-TIMEGPS with a valid week, converted with its leap seconds; frames
+//! before it are placed by extrapolating backwards.
+
+use std::collections::HashSet;
+
+use crate::decode::{DecodeOptions, DecodedMessage, Decoder};
+use crate::error::Result;
+use crate::frame::{frames, FrameRef};
+use crate::index::LogIndex;
+use crate::schema::{normalize_name, Schema};
+use crate::time::{itow_delta, DateTime, LeapSeconds};
+use crate::value::Value;
+
+/// A range of GPS time of week in milliseconds, `from` inclusive and `to`
+/// exclusive. A range with `from > to` wraps through the week boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItowRange {
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+}
+
+impl ItowRange {
+    pub fn contains(&self, itow: u32) -> bool {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => itow >= from || itow < to,
+            (from, to) => from.is_none_or(|f| itow >= f) && to.is_none_or(|t| itow < t),
+        }
+    }
+}
+
+/// Which frames to keep.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Message names to keep (all when empty), e.g. `NAV-PVT` or `MGA-GPS-EPH`.
+    pub include: Vec<String>,
+    /// Message names to drop.
+    pub exclude: Vec<String>,
+    pub itow: Option<ItowRange>,
+    /// UTC window as Unix seconds, `from` inclusive and `to` exclusive.
+    pub utc: Option<(Option<f64>, Option<f64>)>,
+}
+
+impl Filter {
+    pub fn include<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.include.extend(names.into_iter().map(|n| normalize_name(n.as_ref())));
+        self
+    }
+
+    pub fn exclude<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude.extend(names.into_iter().map(|n| normalize_name(n.as_ref())));
+        self
+    }
+
+    pub fn itow(mut self, range: ItowRange) -> Self {
+        self.itow = Some(range);
+        self
+    }
+
+    pub fn utc(mut self, from: Option<f64>, to: Option<f64>) -> Self {
+        self.utc = Some((from, to));
+        self
+    }
+
+    fn has_time_window(&self) -> bool {
+        self.itow.is_some() || self.utc.is_some()
+    }
+
+    /// Whether the message passes the include/exclude lists, ignoring time.
+    pub fn type_matches(&self, msg: &DecodedMessage) -> bool {
+        let alias = msg.variant.as_ref().map(|v| format!("{}-{v}", msg.name));
+        let is = |w: &String| *w == msg.name || Some(w) == alias.as_ref();
+        (self.include.is_empty() || self.include.iter().any(is)) && !self.exclude.iter().any(is)
+    }
+
+    /// Frames of `input` that pass the filter, in order; their `raw` bytes
+    /// are slices of the input.
+    pub fn apply<'a>(&self, schema: &Schema, input: &'a [u8]) -> Vec<FrameRef<'a>> {
+        let options = DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        };
+        let decoder = Decoder::with_options(schema, options);
+        let mut epoch = None;
+        let mut anchor = None;
+        let mut decoded = Vec::new();
+        for frame in frames(input).flatten() {
+            let msg = decoder.decode_frame(&frame);
+            epoch = msg.itow().or(epoch);
+            if anchor.is_none() && self.utc.is_some() {
+                anchor = utc_anchor(&msg);
+            }
+            if self.type_matches(&msg) {
+                decoded.push((frame, epoch));
+            }
+        }
+
+        decoded
+            .into_iter()
+            .filter(|(_, epoch)| {
+                if !self.has_time_window() {
+                    return true;
+                }
+                let Some(itow) = *epoch else {
+                    return false;
+                };
+                if self.itow.is_some_and(|r| !r.contains(itow)) {
+                    return false;
+                }
+                match (self.utc, anchor) {
+                    (Some((from, to)), Some((anchor_itow, anchor_unix))) => {
+                        let t = anchor_unix + itow_delta(anchor_itow, itow) as f64 / 1_000.0;
+                        from.is_none_or(|f| t >= f) && to.is_none_or(|e| t < e)
+                    }
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                }
+            })
+            .map(|(frame, _)| frame)
+            .collect()
+    }
+
+    /// [`Filter::apply`] through an index of `input`: only frames of the
+    /// included types, and with an iTOW window only those inside it, are
+    /// read and decoded.
+    pub fn apply_indexed<'a>(&self, schema: &Schema, input: &'a [u8], index: &LogIndex) -> Result<Vec<FrameRef<'a>>> {
+        let options = DecodeOptions {
+            expand_flags: true,
+            ..DecodeOptions::default()
+        };
+        let decoder = Decoder::with_options(schema, options);
+        let anchor = match self.utc {
+            Some(_) => {
+                let names = ["UBX-NAV-PVT", "UBX-NAV-TIMEUTC", "UBX-NAV-TIMEGPS"].map(String::from);
+                let sources = message_types(schema, &names);
+                let mut anchor = None;
+                for entry in index.select(sources.as_ref(), None) {
+                    anchor = utc_anchor(&decoder.decode_frame(&index.frame(entry, input)?));
+                    if anchor.is_some() {
+                        break;
+                    }
+                }
+                match anchor {
+                    Some(anchor) => Some(anchor),
+                    None => return Ok(Vec::new()),
+                }
+            }
+            None => None,
+        };
+
+        let types = message_types(schema, &self.include);
+        let mut out = Vec::new();
+        for entry in index.select(types.as_ref(), self.itow) {
+            if let (Some((from, to)), Some((anchor_itow, anchor_unix))) = (self.utc, anchor) {
+                let Some(itow) = entry.itow else {
+                    continue;
+                };
+                let t = anchor_unix + itow_delta(anchor_itow, itow) as f64 / 1_000.0;
+                if from.is_some_and(|f| t < f) || to.is_some_and(|e| t >= e) {
+                    continue;
+                }
+            }
+            let frame = index.frame(entry, input)?;
+            if self.type_matches(&decoder.decode_frame(&frame)) {
+                out.push(frame);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// `(class, id)` of the messages `names` can refer to, variants included;
+/// `None` for all when `names` is empty.
+fn message_types(schema: &Schema, names: &[String]) -> Option<HashSet<(u8, u8)>> {
+    if names.is_empty() {
+        return None;
+    }
+    let refers = |msg: &str, name: &str| {
+        name.strip_prefix(msg).is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    };
+    Some(
+        schema
+            .messages
+            .iter()
+            .filter(|m| names.iter().any(|n| refers(&m.name, n)))
+            .map(|m| (m.class_id, m.message_id))
+            .collect(),
+    )
+}
+
+/// `(iTOW, Unix time)` from a NAV-PVT or NAV-TIMEUTC with valid UTC, or a
+/// NAV-TIMEGPS with valid time of week and week. NAV-TIMEGPS is converted
+/// with its own leap seconds when they are valid, otherwise with the
+/// built-in table.
+pub fn utc_anchor(msg: &DecodedMessage) -> Option<(u32, f64)> {
+    let int = |name: &str| msg.get(name).and_then(Value::as_i64);
+    if msg.name == "UBX-NAV-TIMEGPS" {
+        if int("valid.towValid") != Some(1) || int("valid.weekValid") != Some(1) {
+            return None;
+        }
+        let itow = msg.itow()?;
+        let tow = f64::from(itow) / 1_000.0 + int("fTOW").unwrap_or(0) as f64 * 1e-9;
+        let mut leap = LeapSeconds::new();
+        leap.observe(msg);
+        let utc = leap.to_utc(u32::try_from(int("week")?).ok()?, tow);
+        return Some((itow, utc.time.to_unix()));
+    }
+    let valid = match msg.name.as_str() {
+        "UBX-NAV-PVT" => int("valid.validDate") == Some(1) && int("valid.validTime") == Some(1),
+        "UBX-NAV-TIMEUTC" => int("valid.validUTC") == Some(1),
+        _ => false,
+    };
+    if !valid {
+        return None;
+    }
+    let time = DateTime {
+        year: int("year")? as i32,
+        month: int("month")? as u8,
+        day: int("day")? as u8,
+        hour: int("hour")? as u8,
+        minute: int("min")? as u8,
+        second: int("sec")? as f64 + int("nano").unwrap_or(0) as f64 * 1e-9,
+    };
+    Some((msg.itow()?, time.to_unix()))
+}
@@ -0,0 +1,738 @@
+//! UBX framing: sync characters, length header and Fletcher checksum.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::FrameError;
+use crate::ntrip::RTCM_PREAMBLE;
+
+/// First UBX sync character.
+pub const SYNC_CHAR_1: u8 = 0xB5;
+/// Second UBX sync character.
+pub const SYNC_CHAR_2: u8 = 0x62;
+/// Sync(2) + class(1) + id(1) + length(2).
+pub const HEADER_LEN: usize = 6;
+/// Header plus the two checksum bytes.
+pub const FRAME_OVERHEAD: usize = HEADER_LEN + 2;
+
+/// Bytes per block in [`checksum`].
+const CHECKSUM_LANES: usize = 32;
+
+/// Calculate the UBX checksum (8-bit Fletcher) over class, id, length and payload.
+///
+/// Runs the Fletcher sums separately for each of 32 byte positions in a
+/// block, so the loop vectorizes instead of waiting on the previous byte,
+/// then combines them: over `n` bytes `ck_b` is the sum of each byte times
+/// its distance from the end. All arithmetic wraps at 256, so the lanes are
+/// `u8`. Short frames and the tail go through the byte loop of
+/// [`checksum_bytewise`], which gives the same result.
+pub fn checksum(data: &[u8]) -> [u8; 2] {
+    if data.len() < CHECKSUM_LANES {
+        return fletcher([0, 0], data);
+    }
+    let blocks = data.chunks_exact(CHECKSUM_LANES);
+    let tail = blocks.remainder();
+    let mut lane_a = [0u8; CHECKSUM_LANES];
+    let mut lane_b = [0u8; CHECKSUM_LANES];
+    for block in blocks {
+        for ((a, b), &byte) in lane_a.iter_mut().zip(lane_b.iter_mut()).zip(block) {
+            *a = a.wrapping_add(byte);
+            *b = b.wrapping_add(*a);
+        }
+    }
+    // Byte `j` of block `k` of `K` counts `CHECKSUM_LANES * (K - k) - j` times.
+    let (mut ck_a, mut ck_b) = (0u8, 0u8);
+    for (j, (&a, &b)) in lane_a.iter().zip(&lane_b).enumerate() {
+        ck_a = ck_a.wrapping_add(a);
+        ck_b = ck_b.wrapping_add(b.wrapping_mul(CHECKSUM_LANES as u8)).wrapping_sub(a.wrapping_mul(j as u8));
+    }
+    fletcher([ck_a, ck_b], tail)
+}
+
+/// The UBX checksum one byte at a time, as the interface description gives it.
+pub fn checksum_bytewise(data: &[u8]) -> [u8; 2] {
+    fletcher([0, 0], data)
+}
+
+fn fletcher([mut ck_a, mut ck_b]: [u8; 2], data: &[u8]) -> [u8; 2] {
+    for &byte in data {
+        ck_a = ck_a.wrapping_add(byte);
+        ck_b = ck_b.wrapping_add(ck_a);
+    }
+    [ck_a, ck_b]
+}
+
+/// An owned UBX frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub class: u8,
+    pub id: u8,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(class: u8, id: u8, payload: Vec<u8>) -> Self {
+        Self { class, id, payload }
+    }
+
+    /// Serialize to sync + header + payload + checksum.
+    ///
+    /// Panics if the payload is longer than [`MAX_PAYLOAD_LEN`]; frames
+    /// encoded from user input go through [`Frame::try_to_bytes`] instead.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_bytes().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// As [`Frame::to_bytes`], but a payload too long for the length field
+    /// is an error.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, FrameError> {
+        let mut out = vec![0; frame_len(self.payload.len())];
+        write_frame(&mut out, self.class, self.id, &self.payload)?;
+        Ok(out)
+    }
+
+    /// Serialize into the start of `buf`; returns the frame length.
+    pub fn write_into(&self, buf: &mut [u8]) -> Result<usize, FrameError> {
+        write_frame(buf, self.class, self.id, &self.payload)
+    }
+}
+
+/// Largest payload the 16-bit length field can describe.
+pub const MAX_PAYLOAD_LEN: usize = u16::MAX as usize;
+
+/// Length of a frame carrying `payload_len` payload bytes.
+pub const fn frame_len(payload_len: usize) -> usize {
+    FRAME_OVERHEAD + payload_len
+}
+
+/// Write the complete frame for `class`, `id` and `payload` to the start of
+/// `buf`: sync characters, header with the payload length, payload and
+/// checksum. Returns the frame length; nothing is allocated.
+pub fn write_frame(buf: &mut [u8], class: u8, id: u8, payload: &[u8]) -> Result<usize, FrameError> {
+    if payload.len() > MAX_PAYLOAD_LEN {
+        return Err(FrameError::PayloadTooLong(payload.len()));
+    }
+    let len = frame_len(payload.len());
+    if buf.len() < len {
+        return Err(FrameError::BufferTooSmall {
+            needed: len,
+            available: buf.len(),
+        });
+    }
+    buf[..4].copy_from_slice(&[SYNC_CHAR_1, SYNC_CHAR_2, class, id]);
+    buf[4..HEADER_LEN].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+    buf[HEADER_LEN..len - 2].copy_from_slice(payload);
+    let ck = checksum(&buf[2..len - 2]);
+    buf[len - 2..len].copy_from_slice(&ck);
+    Ok(len)
+}
+
+/// Writes frames one after another into a caller's buffer, for sending
+/// several messages (a poll sequence, a configuration batch) in one write
+/// without allocating.
+#[derive(Debug)]
+pub struct FrameWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> FrameWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Append one frame and return its bytes. If it doesn't fit, nothing
+    /// is written.
+    pub fn write(&mut self, class: u8, id: u8, payload: &[u8]) -> Result<&[u8], FrameError> {
+        let start = self.len;
+        let n = write_frame(&mut self.buf[start..], class, id, payload)?;
+        self.len += n;
+        Ok(&self.buf[start..self.len])
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<&[u8], FrameError> {
+        self.write(frame.class, frame.id, &frame.payload)
+    }
+
+    /// Everything written so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes still free in the buffer.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.len
+    }
+
+    /// Start again at the beginning of the buffer.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+/// A frame borrowed from a larger buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRef<'a> {
+    /// Byte offset of the first sync character within the scanned buffer.
+    pub offset: usize,
+    pub class: u8,
+    pub id: u8,
+    pub payload: &'a [u8],
+    /// The complete frame bytes, exactly as they appeared in the input.
+    pub raw: &'a [u8],
+}
+
+impl FrameRef<'_> {
+    pub fn to_owned(&self) -> Frame {
+        Frame::new(self.class, self.id, self.payload.to_vec())
+    }
+}
+
+/// Parse a single frame that starts at the beginning of `data`.
+///
+/// Trailing bytes after the frame are ignored.
+pub fn parse_frame(data: &[u8]) -> Result<FrameRef<'_>, FrameError> {
+    if data.len() < FRAME_OVERHEAD {
+        return Err(FrameError::TooShort(data.len()));
+    }
+    if data[0] != SYNC_CHAR_1 || data[1] != SYNC_CHAR_2 {
+        return Err(FrameError::InvalidSync(data[0], data[1]));
+    }
+    let payload_len = u16::from_le_bytes([data[4], data[5]]) as usize;
+    let expected = FRAME_OVERHEAD + payload_len;
+    if data.len() < expected {
+        return Err(FrameError::Truncated {
+            expected,
+            actual: data.len(),
+        });
+    }
+    let ck = checksum(&data[2..HEADER_LEN + payload_len]);
+    let found = [data[expected - 2], data[expected - 1]];
+    if ck != found {
+        return Err(FrameError::Checksum {
+            class: data[2],
+            id: data[3],
+            expected: ck,
+            found,
+        });
+    }
+    Ok(FrameRef {
+        offset: 0,
+        class: data[2],
+        id: data[3],
+        payload: &data[HEADER_LEN..HEADER_LEN + payload_len],
+        raw: &data[..expected],
+    })
+}
+
+/// Counters collected while scanning a buffer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    pub frames: usize,
+    pub checksum_errors: usize,
+    /// Bytes that were not part of any valid frame (NMEA, noise, partial frames).
+    pub skipped_bytes: usize,
+    /// Sync characters whose length field the [`LengthPolicy`] rejected.
+    pub oversized: usize,
+}
+
+/// What to do with a frame whose length field is implausible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OversizeAction {
+    /// Take the sync characters for a false sync and scan on from the next
+    /// byte.
+    #[default]
+    Resync,
+    /// Report [`FrameError::Oversized`] (or [`FrameError::Truncated`] at the
+    /// end of a buffer), then scan on from the next byte.
+    Error,
+    /// Trust the length and parse the frame once all of it has arrived. A
+    /// [`FrameIter`] stops at a frame that runs past the end of its buffer,
+    /// leaving the rest from [`FrameIter::position`] for the caller.
+    Wait,
+}
+
+impl OversizeAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            OversizeAction::Resync => "resync",
+            OversizeAction::Error => "error",
+            OversizeAction::Wait => "wait",
+        }
+    }
+}
+
+impl fmt::Display for OversizeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for OversizeAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "resync" | "skip" => Ok(OversizeAction::Resync),
+            "error" => Ok(OversizeAction::Error),
+            "wait" => Ok(OversizeAction::Wait),
+            other => Err(format!("unknown oversize action '{other}' (expected resync, error or wait)")),
+        }
+    }
+}
+
+/// How [`FrameIter`] and [`FrameParser`] treat length fields: a declared
+/// payload over `max_payload` bytes gets `action`, and so, when scanning
+/// a buffer, does a frame that runs past its end. Every framer uses the
+/// same default, resync with no cap beyond the 16-bit field, so tools
+/// agree on corrupted streams unless they choose otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LengthPolicy {
+    pub max_payload: usize,
+    pub action: OversizeAction,
+}
+
+impl Default for LengthPolicy {
+    fn default() -> Self {
+        Self {
+            max_payload: MAX_PAYLOAD_LEN,
+            action: OversizeAction::Resync,
+        }
+    }
+}
+
+impl LengthPolicy {
+    pub fn new(max_payload: usize, action: OversizeAction) -> Self {
+        Self { max_payload, action }
+    }
+
+    /// Whether a declared payload length is over the cap and the action
+    /// doesn't trust it anyway.
+    fn rejects(&self, payload_len: usize) -> bool {
+        payload_len > self.max_payload && self.action != OversizeAction::Wait
+    }
+}
+
+/// The class, ID and payload length of a header at the start of `data`.
+fn header(data: &[u8]) -> (u8, u8, usize) {
+    (data[2], data[3], usize::from(u16::from_le_bytes([data[4], data[5]])))
+}
+
+/// Iterator over the UBX frames in a byte buffer.
+///
+/// Non-UBX bytes are skipped. A frame with a bad checksum is reported as an
+/// error and scanning resumes one byte after its sync characters. Frames
+/// with an implausible length, and by default a frame that runs past the
+/// end of the buffer, are handled by the [`LengthPolicy`].
+pub struct FrameIter<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    policy: LengthPolicy,
+    stats: ScanStats,
+}
+
+impl<'a> FrameIter<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self::with_policy(buf, LengthPolicy::default())
+    }
+
+    pub fn with_policy(buf: &'a [u8], policy: LengthPolicy) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            policy,
+            stats: ScanStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ScanStats {
+        self.stats
+    }
+
+    /// Offset the scan has reached; with [`OversizeAction::Wait`], where
+    /// the unfinished frame starts once iteration ends.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Step over a rejected sync, reporting `err` for [`OversizeAction::Error`].
+    fn reject(&mut self, err: FrameError) -> Option<FrameError> {
+        self.pos += 1;
+        self.stats.skipped_bytes += 1;
+        self.stats.oversized += 1;
+        (self.policy.action == OversizeAction::Error).then_some(err)
+    }
+}
+
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = Result<FrameRef<'a>, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos + FRAME_OVERHEAD <= self.buf.len() {
+            let start = self.pos;
+            if self.buf[start] != SYNC_CHAR_1 || self.buf[start + 1] != SYNC_CHAR_2 {
+                self.pos += 1;
+                self.stats.skipped_bytes += 1;
+                continue;
+            }
+            let (class, id, payload_len) = header(&self.buf[start..]);
+            let expected = FRAME_OVERHEAD + payload_len;
+            if self.policy.rejects(payload_len) {
+                let limit = self.policy.max_payload;
+                match self.reject(FrameError::Oversized { class, id, length: payload_len, limit }) {
+                    Some(err) => return Some(Err(err)),
+                    None => continue,
+                }
+            }
+            if start + expected > self.buf.len() {
+                if self.policy.action == OversizeAction::Wait {
+                    return None;
+                }
+                let actual = self.buf.len() - start;
+                match self.reject(FrameError::Truncated { expected, actual }) {
+                    Some(err) => return Some(Err(err)),
+                    None => continue,
+                }
+            }
+            match parse_frame(&self.buf[start..]) {
+                Ok(mut frame) => {
+                    frame.offset = start;
+                    self.pos += frame.raw.len();
+                    self.stats.frames += 1;
+                    return Some(Ok(frame));
+                }
+                Err(err) => {
+                    self.pos += 1;
+                    self.stats.skipped_bytes += 1;
+                    self.stats.checksum_errors += 1;
+                    return Some(Err(err));
+                }
+            }
+        }
+        // Waiting keeps a trailing partial header for the caller too.
+        let rest = &self.buf[self.pos..];
+        let keep = match self.policy.action {
+            OversizeAction::Wait => rest
+                .windows(2)
+                .position(|w| w == [SYNC_CHAR_1, SYNC_CHAR_2])
+                .or_else(|| (rest.last() == Some(&SYNC_CHAR_1)).then(|| rest.len() - 1)),
+            _ => None,
+        };
+        let end = keep.map_or(self.buf.len(), |k| self.pos + k);
+        self.stats.skipped_bytes += end - self.pos;
+        self.pos = end;
+        None
+    }
+}
+
+/// Iterate over all frames in `buf`.
+pub fn frames(buf: &[u8]) -> FrameIter<'_> {
+    FrameIter::new(buf)
+}
+
+/// Incremental frame parser for byte streams (serial ports, sockets).
+///
+/// Unlike [`FrameIter`], a frame that runs past the end of the buffered data
+/// is kept until more bytes arrive, unless its length is over the
+/// [`LengthPolicy`] cap. Offsets count bytes since the stream start.
+#[derive(Debug, Default)]
+pub struct FrameParser {
+    buf: Vec<u8>,
+    /// Stream offset of `buf[0]`.
+    base: u64,
+    policy: LengthPolicy,
+    stats: ScanStats,
+}
+
+impl FrameParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy(policy: LengthPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Append received bytes.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Next complete frame and its stream offset, or `None` if more data is needed.
+    pub fn next_frame(&mut self) -> Option<Result<(u64, Frame), FrameError>> {
+        loop {
+            let Some(start) = self
+                .buf
+                .windows(2)
+                .position(|w| w == [SYNC_CHAR_1, SYNC_CHAR_2])
+            else {
+                // Keep a trailing first sync character, it may complete next time.
+                let keep = usize::from(self.buf.last() == Some(&SYNC_CHAR_1));
+                self.discard(self.buf.len() - keep);
+                return None;
+            };
+            self.discard(start);
+            if self.buf.len() >= HEADER_LEN {
+                let (class, id, payload_len) = header(&self.buf);
+                if self.policy.rejects(payload_len) {
+                    self.discard(1);
+                    self.stats.oversized += 1;
+                    if self.policy.action == OversizeAction::Error {
+                        let limit = self.policy.max_payload;
+                        return Some(Err(FrameError::Oversized { class, id, length: payload_len, limit }));
+                    }
+                    continue;
+                }
+            }
+            return match parse_frame(&self.buf) {
+                Ok(frame) => {
+                    let frame_len = frame.raw.len();
+                    let frame = frame.to_owned();
+                    let offset = self.base;
+                    self.consume(frame_len);
+                    self.stats.frames += 1;
+                    Some(Ok((offset, frame)))
+                }
+                Err(FrameError::TooShort(_) | FrameError::Truncated { .. }) => None,
+                Err(err) => {
+                    self.discard(1);
+                    self.stats.checksum_errors += 1;
+                    Some(Err(err))
+                }
+            };
+        }
+    }
+
+    /// Bytes buffered but not yet returned as a frame.
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn stats(&self) -> ScanStats {
+        self.stats
+    }
+
+    fn discard(&mut self, n: usize) {
+        self.stats.skipped_bytes += n;
+        self.consume(n);
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.buf.drain(..n);
+        self.base += n as u64;
+    }
+}
+
+/// Byte the receiver clocks out on DDC (I2C) and SPI when it has nothing to
+/// send.
+pub const IDLE_FILLER: u8 = 0xFF;
+
+/// Consecutive filler bytes after which an SPI receiver's output buffer
+/// counts as empty, and polling can wait for the next TX-ready.
+pub const SPI_IDLE_RUN: usize = 50;
+
+/// The interface a byte stream was read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Transport {
+    /// UART or USB: every byte is data.
+    #[default]
+    Uart,
+    /// DDC (I2C): reads of the 0xFF stream register return filler once the
+    /// receiver's buffer is drained.
+    Ddc,
+    /// SPI: full-duplex, so every byte the host clocks in while the
+    /// receiver has no data is filler, whether it reads in blocks on
+    /// TX-ready or a byte at a time.
+    Spi,
+}
+
+impl Transport {
+    pub fn name(self) -> &'static str {
+        match self {
+            Transport::Uart => "uart",
+            Transport::Ddc => "ddc",
+            Transport::Spi => "spi",
+        }
+    }
+
+    /// Whether idle reads return [`IDLE_FILLER`].
+    pub fn has_filler(self) -> bool {
+        self != Transport::Uart
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Transport {
+    type Err = String;
+
+    /// `uart` (or `usb`), `ddc` (or `i2c`), `spi`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "uart" | "usb" => Ok(Transport::Uart),
+            "ddc" | "i2c" => Ok(Transport::Ddc),
+            "spi" => Ok(Transport::Spi),
+            other => Err(format!("unknown transport '{other}' (expected uart, ddc or spi)")),
+        }
+    }
+}
+
+/// Counters of a [`FillerFilter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Chunks passed to [`FillerFilter::strip`].
+    pub reads: usize,
+    /// Reads that returned nothing but filler.
+    pub idle_reads: usize,
+    pub filler_bytes: usize,
+    /// Bytes passed on.
+    pub data_bytes: usize,
+    pub longest_filler_run: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FillState {
+    Between,
+    /// UBX header bytes seen so far.
+    Header(usize),
+    /// RTCM 3 header bytes (preamble and 10-bit length) seen so far.
+    RtcmHeader(usize),
+    /// Bytes left of the frame.
+    Frame(usize),
+}
+
+/// RTCM 3 preamble and length.
+const RTCM_HEADER_LEN: usize = 3;
+
+/// Removes the idle filler of DDC and SPI reads from a byte stream.
+///
+/// Filler only ever comes between messages, and 0xFF is a common byte in
+/// binary messages, so the filter follows the UBX and RTCM 3 frames it
+/// passes on: bytes within a frame, as its length field gives it (through
+/// the checksum or CRC), are kept whatever their value. Between frames
+/// (NMEA is ASCII and never contains 0xFF) every filler byte is dropped.
+/// State carries over from one read to the next, so reads may be any size
+/// down to single bytes. For [`Transport::Uart`] nothing is removed.
+#[derive(Debug, Clone)]
+pub struct FillerFilter {
+    transport: Transport,
+    state: FillState,
+    header: [u8; HEADER_LEN],
+    run: usize,
+    stats: TransportStats,
+}
+
+impl FillerFilter {
+    pub fn new(transport: Transport) -> Self {
+        Self {
+            transport,
+            state: FillState::Between,
+            header: [0; HEADER_LEN],
+            run: 0,
+            stats: TransportStats::default(),
+        }
+    }
+
+    pub fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// The data bytes of one read.
+    pub fn strip(&mut self, data: &[u8]) -> Vec<u8> {
+        self.stats.reads += 1;
+        if !self.transport.has_filler() {
+            self.stats.data_bytes += data.len();
+            return data.to_vec();
+        }
+        let mut out = Vec::with_capacity(data.len());
+        for &byte in data {
+            match self.state {
+                FillState::Frame(remaining) => {
+                    out.push(byte);
+                    self.run = 0;
+                    self.state = if remaining > 1 { FillState::Frame(remaining - 1) } else { FillState::Between };
+                }
+                FillState::Header(1) if byte != SYNC_CHAR_2 => {
+                    self.state = FillState::Between;
+                    self.between(byte, &mut out);
+                }
+                FillState::Header(n) => {
+                    out.push(byte);
+                    self.run = 0;
+                    self.header[n] = byte;
+                    self.state = if n + 1 < HEADER_LEN {
+                        FillState::Header(n + 1)
+                    } else {
+                        let len = u16::from_le_bytes([self.header[4], self.header[5]]);
+                        FillState::Frame(usize::from(len) + 2)
+                    };
+                }
+                // The six bits above the length are reserved as zero; anything else was no preamble.
+                FillState::RtcmHeader(1) if byte & 0xFC != 0 => {
+                    self.state = FillState::Between;
+                    self.between(byte, &mut out);
+                }
+                FillState::RtcmHeader(n) => {
+                    out.push(byte);
+                    self.run = 0;
+                    self.header[n] = byte;
+                    self.state = if n + 1 < RTCM_HEADER_LEN {
+                        FillState::RtcmHeader(n + 1)
+                    } else {
+                        let len = u16::from_be_bytes([self.header[1] & 0x03, self.header[2]]);
+                        FillState::Frame(usize::from(len) + 3)
+                    };
+                }
+                FillState::Between => self.between(byte, &mut out),
+            }
+        }
+        if !data.is_empty() && out.is_empty() {
+            self.stats.idle_reads += 1;
+        }
+        self.stats.data_bytes += out.len();
+        out
+    }
+
+    fn between(&mut self, byte: u8, out: &mut Vec<u8>) {
+        if byte == IDLE_FILLER {
+            self.run += 1;
+            self.stats.filler_bytes += 1;
+            self.stats.longest_filler_run = self.stats.longest_filler_run.max(self.run);
+            return;
+        }
+        self.run = 0;
+        out.push(byte);
+        if byte == SYNC_CHAR_1 {
+            self.header[0] = byte;
+            self.state = FillState::Header(1);
+        } else if byte == RTCM_PREAMBLE {
+            self.header[0] = byte;
+            self.state = FillState::RtcmHeader(1);
+        }
+    }
+
+    /// True once the stream has gone [`SPI_IDLE_RUN`] filler bytes without
+    /// data: the receiver has nothing more to send for now.
+    pub fn idle(&self) -> bool {
+        self.run >= SPI_IDLE_RUN
+    }
+
+    pub fn stats(&self) -> TransportStats {
+        self.stats
+    }
+}
@@ -0,0 +1,235 @@
+//! Sensor-fusion status of an ADR/UDR drive log, from ESF-STATUS and
+//! ESF-ALG.
+//!
+//! Validating an installation means checking that the fusion filter left
+//! initialization and stayed in fusion mode, that every sensor it needs
+//! became calibrated and stayed free of faults, and that the automatic
+//! IMU-mount alignment converged. [`FusionAnalyzer`] records fusion-mode
+//! and alignment-status changes with the time spent in each, per sensor
+//! type the calibration states, time to calibration and faults, and the
+//! mount angles while the alignment is fine: their spread is how well it
+//! converged. Time is receiver time from iTOW, counted from the first
+//! message of each kind. A schema that does not group ESF-STATUS's
+//! per-sensor block gives its first sensor only.
+//!
+//! Messages must be decoded with scaling and flag expansion enabled.
+
+use std::collections::BTreeMap;
+
+use crate::decode::DecodedMessage;
+use crate::time::itow_delta;
+use crate::timing::Statistics;
+use crate::value::Value;
+
+/// ESF-ALG `status` of a fine alignment.
+pub const FINE_ALIGNMENT: u8 = 4;
+
+/// ESF-STATUS `fusionMode` name.
+pub fn fusion_mode_label(mode: u8) -> &'static str {
+    match mode {
+        0 => "initialization",
+        1 => "fusion",
+        2 => "suspended",
+        3 => "disabled",
+        _ => "reserved",
+    }
+}
+
+/// ESF-ALG `status` name.
+pub fn alignment_label(status: u8) -> &'static str {
+    match status {
+        0 => "user-defined",
+        1 => "roll/pitch ongoing",
+        2 => "roll/pitch/yaw ongoing",
+        3 => "coarse",
+        FINE_ALIGNMENT => "fine",
+        _ => "reserved",
+    }
+}
+
+/// ESF-STATUS `calibStatus` name.
+pub fn calibration_label(status: u8) -> &'static str {
+    match status {
+        0 => "not calibrated",
+        1 => "calibrating",
+        _ => "calibrated",
+    }
+}
+
+/// ESF sensor data type name, as ESF-STATUS and ESF-MEAS number them.
+pub fn sensor_type_label(sensor_type: u8) -> &'static str {
+    match sensor_type {
+        0 => "none",
+        5 => "gyro z",
+        6 => "wheel ticks front-left",
+        7 => "wheel ticks front-right",
+        8 => "wheel ticks rear-left",
+        9 => "wheel ticks rear-right",
+        10 => "single tick",
+        11 => "speed",
+        12 => "gyro temperature",
+        13 => "gyro y",
+        14 => "gyro x",
+        16 => "accelerometer x",
+        17 => "accelerometer y",
+        18 => "accelerometer z",
+        _ => "unknown",
+    }
+}
+
+/// A new fusion mode or alignment status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusChange {
+    pub itow: u32,
+    pub from: u8,
+    pub to: u8,
+}
+
+/// A state that changes from epoch to epoch, with the time spent in each
+/// value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusHistory {
+    pub epochs: usize,
+    pub changes: Vec<StatusChange>,
+    /// Milliseconds in each value, from each epoch to the next.
+    pub time_in: BTreeMap<u8, i64>,
+    /// Milliseconds from the first epoch to the first in each value.
+    pub time_to: BTreeMap<u8, i64>,
+    first: Option<u32>,
+    last: Option<(u32, u8)>,
+}
+
+impl StatusHistory {
+    fn push(&mut self, itow: u32, value: u8) {
+        let first = *self.first.get_or_insert(itow);
+        if let Some((last, previous)) = self.last {
+            let d = itow_delta(last, itow);
+            if d <= 0 {
+                return;
+            }
+            *self.time_in.entry(previous).or_default() += d;
+            if previous != value {
+                self.changes.push(StatusChange {
+                    itow,
+                    from: previous,
+                    to: value,
+                });
+            }
+        }
+        self.time_to.entry(value).or_insert_with(|| itow_delta(first, itow));
+        self.epochs += 1;
+        self.last = Some((itow, value));
+    }
+
+    /// The latest value.
+    pub fn current(&self) -> Option<u8> {
+        self.last.map(|(_, value)| value)
+    }
+}
+
+/// Fault bits of ESF-STATUS, in report order.
+pub const FAULTS: [&str; 4] = ["badMeas", "badTTag", "missingMeas", "noisyMeas"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SensorReport {
+    pub epochs: usize,
+    pub used: usize,
+    pub ready: usize,
+    /// `calibStatus` with its history: 0 not calibrated, 1 calibrating,
+    /// 2 and 3 calibrated.
+    pub calibration: StatusHistory,
+    /// Epochs with each of [`FAULTS`] set.
+    pub faults: [usize; 4],
+    /// Latest reported data rate, Hz.
+    pub freq: u64,
+}
+
+impl SensorReport {
+    /// Milliseconds from the sensor's first report to its first
+    /// calibrated one.
+    pub fn time_to_calibrated(&self) -> Option<i64> {
+        self.calibration.time_to.range(2..).map(|(_, &ms)| ms).min()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlignmentReport {
+    pub status: StatusHistory,
+    /// Epochs with `tiltAlgError`, `yawAlgError` and `angleError` set.
+    pub errors: [usize; 3],
+    /// Mount angles, degrees, while fine; yaw unwrapped around its running
+    /// mean, so a mount near 0° doesn't spread over 360°.
+    pub yaw: Statistics,
+    pub pitch: Statistics,
+    pub roll: Statistics,
+    /// The latest yaw, pitch and roll.
+    pub last: Option<(f64, f64, f64)>,
+}
+
+/// Collects the fusion, sensor and alignment reports of a log.
+#[derive(Debug, Clone, Default)]
+pub struct FusionAnalyzer {
+    pub fusion: StatusHistory,
+    /// By sensor type.
+    pub sensors: BTreeMap<u8, SensorReport>,
+    pub alignment: AlignmentReport,
+}
+
+impl FusionAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, msg: &DecodedMessage) {
+        let Some(itow) = msg.itow().filter(|_| msg.parsed) else {
+            return;
+        };
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64);
+        match msg.name.as_str() {
+            "UBX-ESF-STATUS" => {
+                if let Some(mode) = int("fusionMode") {
+                    self.fusion.push(itow, mode as u8);
+                }
+                for sensor in msg.blocks() {
+                    let bit = |path: &str| sensor.get_path(path).and_then(Value::as_u64).unwrap_or(0);
+                    let Some(sensor_type) = sensor.get_path("sensStatus1.type").and_then(Value::as_u64) else {
+                        continue;
+                    };
+                    let report = self.sensors.entry(sensor_type as u8).or_default();
+                    report.epochs += 1;
+                    report.used += bit("sensStatus1.used") as usize;
+                    report.ready += bit("sensStatus1.ready") as usize;
+                    report.calibration.push(itow, bit("sensStatus2.calibStatus") as u8);
+                    for (count, fault) in report.faults.iter_mut().zip(FAULTS) {
+                        *count += bit(&format!("faults.{fault}")) as usize;
+                    }
+                    report.freq = bit("freq");
+                }
+            }
+            "UBX-ESF-ALG" => {
+                let alignment = &mut self.alignment;
+                let Some(status) = int("flags.status") else {
+                    return;
+                };
+                alignment.status.push(itow, status as u8);
+                for (count, error) in alignment.errors.iter_mut().zip(["tiltAlgError", "yawAlgError", "angleError"]) {
+                    *count += int(&format!("error.{error}")).unwrap_or(0) as usize;
+                }
+                let angle = |name: &str| msg.get(name).and_then(Value::as_f64);
+                let (Some(mut yaw), Some(pitch), Some(roll)) = (angle("yaw"), angle("pitch"), angle("roll")) else {
+                    return;
+                };
+                if status as u8 == FINE_ALIGNMENT {
+                    if let Some(reference) = alignment.yaw.mean() {
+                        yaw = reference + (yaw - reference + 180.0).rem_euclid(360.0) - 180.0;
+                    }
+                    alignment.yaw.add(yaw);
+                    alignment.pitch.add(pitch);
+                    alignment.roll.add(roll);
+                }
+                alignment.last = Some((yaw.rem_euclid(360.0), pitch, roll));
+            }
+            _ => {}
+        }
+    }
+}
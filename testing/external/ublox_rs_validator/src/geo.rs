@@ -0,0 +1,157 @@
+//! WGS-84 geodetic conversions.
+//!
+//! Besides the conversions themselves, [`ecef_position`], [`ecef_velocity`]
+//! and [`llh_position`] read positions and velocities from decoded NAV
+//! messages in metres and degrees, high-precision components included, so
+//! ECEF and LLH outputs of a receiver can be compared directly. Messages
+//! must be decoded with scaling and flag expansion enabled.
+
+use crate::decode::DecodedMessage;
+use crate::value::Value;
+
+/// Semi-major axis in metres.
+pub const WGS84_A: f64 = 6_378_137.0;
+/// Flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+const E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Geodetic latitude and longitude in degrees, ellipsoidal height in metres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Llh {
+    pub lat: f64,
+    pub lon: f64,
+    pub height: f64,
+}
+
+/// Earth-centred, Earth-fixed coordinates in metres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ecef {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Ecef {
+    /// Straight-line distance in metres.
+    pub fn distance(&self, other: &Ecef) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+}
+
+/// Local east, north and up components of an ECEF vector, in its units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Enu {
+    pub east: f64,
+    pub north: f64,
+    pub up: f64,
+}
+
+pub fn llh_to_ecef(p: Llh) -> Ecef {
+    let (lat, lon) = (p.lat.to_radians(), p.lon.to_radians());
+    let n = WGS84_A / (1.0 - E2 * lat.sin().powi(2)).sqrt();
+    Ecef {
+        x: (n + p.height) * lat.cos() * lon.cos(),
+        y: (n + p.height) * lat.cos() * lon.sin(),
+        z: (n * (1.0 - E2) + p.height) * lat.sin(),
+    }
+}
+
+/// Inverse of [`llh_to_ecef`], iterated to well below a millimetre.
+pub fn ecef_to_llh(p: Ecef) -> Llh {
+    let r = p.x.hypot(p.y);
+    let lon = p.y.atan2(p.x);
+    let mut lat = p.z.atan2(r * (1.0 - E2));
+    let mut height = 0.0;
+    for _ in 0..10 {
+        let n = WGS84_A / (1.0 - E2 * lat.sin().powi(2)).sqrt();
+        height = if lat.cos().abs() > 1e-9 {
+            r / lat.cos() - n
+        } else {
+            p.z.abs() - n * (1.0 - E2)
+        };
+        let next = p.z.atan2(r * (1.0 - E2 * n / (n + height)));
+        let done = (next - lat).abs() < 1e-14;
+        lat = next;
+        if done {
+            break;
+        }
+    }
+    Llh {
+        lat: lat.to_degrees(),
+        lon: lon.to_degrees(),
+        height,
+    }
+}
+
+/// Rotate an ECEF vector, such as a velocity or the difference of two
+/// positions, into the local frame at `origin`.
+pub fn ecef_to_enu(v: Ecef, origin: Llh) -> Enu {
+    let (lat, lon) = (origin.lat.to_radians(), origin.lon.to_radians());
+    let (sin_lat, cos_lat, sin_lon, cos_lon) = (lat.sin(), lat.cos(), lon.sin(), lon.cos());
+    Enu {
+        east: -sin_lon * v.x + cos_lon * v.y,
+        north: -sin_lat * cos_lon * v.x - sin_lat * sin_lon * v.y + cos_lat * v.z,
+        up: cos_lat * cos_lon * v.x + cos_lat * sin_lon * v.y + sin_lat * v.z,
+    }
+}
+
+/// Inverse of [`ecef_to_enu`].
+pub fn enu_to_ecef(v: Enu, origin: Llh) -> Ecef {
+    let (lat, lon) = (origin.lat.to_radians(), origin.lon.to_radians());
+    let (sin_lat, cos_lat, sin_lon, cos_lon) = (lat.sin(), lat.cos(), lon.sin(), lon.cos());
+    Ecef {
+        x: -sin_lon * v.east - sin_lat * cos_lon * v.north + cos_lat * cos_lon * v.up,
+        y: cos_lon * v.east - sin_lat * sin_lon * v.north + cos_lat * sin_lon * v.up,
+        z: cos_lat * v.north + sin_lat * v.up,
+    }
+}
+
+/// Position in metres from NAV-POSECEF, NAV2-POSECEF, NAV-SOL or
+/// NAV-HPPOSECEF, the latter with its 0.1 mm components; `None` for other
+/// messages and when NAV-HPPOSECEF flags it invalid.
+pub fn ecef_position(msg: &DecodedMessage) -> Option<Ecef> {
+    let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+    let hp = match msg.short_name() {
+        "NAV-POSECEF" | "NAV2-POSECEF" | "NAV-SOL" => false,
+        "NAV-HPPOSECEF" if num("flags.invalidEcef") != Some(1.0) => true,
+        _ => return None,
+    };
+    // Centimetres, plus millimetres in the high-precision fields.
+    let axis = |name: &str| {
+        let hp_mm = if hp { num(&format!("{name}Hp")).unwrap_or(0.0) } else { 0.0 };
+        Some(num(name)? / 100.0 + hp_mm / 1_000.0)
+    };
+    Some(Ecef { x: axis("ecefX")?, y: axis("ecefY")?, z: axis("ecefZ")? })
+}
+
+/// Velocity in m/s from NAV-VELECEF, NAV2-VELECEF or NAV-SOL.
+pub fn ecef_velocity(msg: &DecodedMessage) -> Option<Ecef> {
+    if !matches!(msg.short_name(), "NAV-VELECEF" | "NAV2-VELECEF" | "NAV-SOL") {
+        return None;
+    }
+    let axis = |name: &str| Some(msg.get(name)?.as_f64()? / 100.0);
+    Some(Ecef { x: axis("ecefVX")?, y: axis("ecefVY")?, z: axis("ecefVZ")? })
+}
+
+/// Position with ellipsoidal height from NAV-PVT, NAV2-PVT, NAV-POSLLH,
+/// NAV2-POSLLH or NAV-HPPOSLLH, the latter with its high-precision
+/// components; `None` for other messages and ones flagged `invalidLlh`.
+pub fn llh_position(msg: &DecodedMessage) -> Option<Llh> {
+    let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+    let (hp, invalid) = match msg.short_name() {
+        "NAV-PVT" | "NAV2-PVT" => (false, num("flags3.invalidLlh")),
+        "NAV-POSLLH" | "NAV2-POSLLH" => (false, None),
+        "NAV-HPPOSLLH" => (true, num("flags.invalidLlh")),
+        _ => return None,
+    };
+    if invalid == Some(1.0) {
+        return None;
+    }
+    let with_hp = |name: &str| Some(num(name)? + if hp { num(&format!("{name}Hp")).unwrap_or(0.0) } else { 0.0 });
+    Some(Llh {
+        lat: with_hp("lat")?,
+        lon: with_hp("lon")?,
+        height: with_hp("height")? / 1_000.0,
+    })
+}
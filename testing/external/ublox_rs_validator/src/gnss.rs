@@ -0,0 +1,142 @@
+//! GNSS and signal identifiers (`gnssId`, `svId`, `sigId`) as used in UBX.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+/// Constellation, from the UBX `gnssId` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Gnss {
+    Gps,
+    Sbas,
+    Galileo,
+    BeiDou,
+    Imes,
+    Qzss,
+    Glonass,
+    NavIc,
+}
+
+impl Gnss {
+    pub const ALL: [Gnss; 8] = [
+        Gnss::Gps,
+        Gnss::Sbas,
+        Gnss::Galileo,
+        Gnss::BeiDou,
+        Gnss::Imes,
+        Gnss::Qzss,
+        Gnss::Glonass,
+        Gnss::NavIc,
+    ];
+
+    pub fn from_id(gnss_id: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(gnss_id)).copied()
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Gnss::Gps => "GPS",
+            Gnss::Sbas => "SBAS",
+            Gnss::Galileo => "Galileo",
+            Gnss::BeiDou => "BeiDou",
+            Gnss::Imes => "IMES",
+            Gnss::Qzss => "QZSS",
+            Gnss::Glonass => "GLONASS",
+            Gnss::NavIc => "NavIC",
+        }
+    }
+
+    /// RINEX 3 satellite system identifier; IMES has none.
+    pub fn rinex_char(self) -> Option<char> {
+        match self {
+            Gnss::Gps => Some('G'),
+            Gnss::Sbas => Some('S'),
+            Gnss::Galileo => Some('E'),
+            Gnss::BeiDou => Some('C'),
+            Gnss::Imes => None,
+            Gnss::Qzss => Some('J'),
+            Gnss::Glonass => Some('R'),
+            Gnss::NavIc => Some('I'),
+        }
+    }
+
+    /// Satellite number within its system as used by RINEX (SBAS PRN - 100,
+    /// QZSS PRN - 192); UBX uses the PRN directly.
+    pub fn rinex_prn(self, sv_id: u8) -> u8 {
+        match self {
+            Gnss::Sbas if sv_id >= 100 => sv_id - 100,
+            Gnss::Qzss if sv_id >= 192 => sv_id - 192,
+            _ => sv_id,
+        }
+    }
+
+    /// RINEX 3 band and attribute (e.g. `1C`) of a UBX signal.
+    pub fn rinex_signal(self, sig_id: u8) -> Option<&'static str> {
+        let code = match (self, sig_id) {
+            (Gnss::Gps, 0) => "1C",
+            (Gnss::Gps, 3) => "2L",
+            (Gnss::Gps, 4) => "2S",
+            (Gnss::Gps, 6) => "5I",
+            (Gnss::Gps, 7) => "5Q",
+            (Gnss::Sbas, 0) => "1C",
+            (Gnss::Galileo, 0) => "1C",
+            (Gnss::Galileo, 1) => "1B",
+            (Gnss::Galileo, 3) => "5I",
+            (Gnss::Galileo, 4) => "5Q",
+            (Gnss::Galileo, 5) => "7I",
+            (Gnss::Galileo, 6) => "7Q",
+            (Gnss::Galileo, 8) => "6B",
+            (Gnss::Galileo, 9) => "6C",
+            (Gnss::Galileo, 10) => "6A",
+            (Gnss::BeiDou, 0 | 1) => "2I",
+            (Gnss::BeiDou, 2 | 3) => "7I",
+            (Gnss::BeiDou, 4 | 10) => "6I",
+            (Gnss::BeiDou, 5) => "1P",
+            (Gnss::BeiDou, 6) => "1D",
+            (Gnss::BeiDou, 7) => "5P",
+            (Gnss::BeiDou, 8) => "5D",
+            (Gnss::Qzss, 0) => "1C",
+            (Gnss::Qzss, 1) => "1Z",
+            (Gnss::Qzss, 4) => "2S",
+            (Gnss::Qzss, 5) => "2L",
+            (Gnss::Qzss, 8) => "5I",
+            (Gnss::Qzss, 9) => "5Q",
+            (Gnss::Glonass, 0) => "1C",
+            (Gnss::Glonass, 2) => "2C",
+            (Gnss::NavIc, 0) => "5A",
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+impl fmt::Display for Gnss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// As its name, e.g. `"Galileo"`.
+impl Serialize for Gnss {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for Gnss {
+    type Err = String;
+
+    /// Accepts the name (`GPS`, `galileo`, ...) or the RINEX letter (`E`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        Gnss::ALL
+            .into_iter()
+            .find(|g| g.name().to_ascii_uppercase() == upper || g.rinex_char().map(String::from) == Some(upper.clone()))
+            .ok_or_else(|| format!("unknown constellation '{}'", s.trim()))
+    }
+}
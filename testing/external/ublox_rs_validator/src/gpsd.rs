@@ -0,0 +1,338 @@
+//! gpsd JSON output, so gpsd clients can read decoded UBX.
+//!
+//! [`GpsdTranslator`] turns NAV-PVT into `TPV` reports and NAV-SAT into `SKY`
+//! reports, taking the DOPs of a NAV-DOP from the same epoch. The output
+//! follows what `gpspipe -w` prints: a `VERSION`, `DEVICES` and `WATCH`
+//! preamble, then one report per line. Messages must be decoded with scaling
+//! and flag expansion enabled.
+
+use serde::Serialize;
+use serde_json::{json, Value as Json};
+
+use crate::decode::DecodedMessage;
+use crate::gnss::Gnss;
+use crate::time::DateTime;
+use crate::value::{Fields, Value};
+
+/// gpsd release and protocol version the output corresponds to.
+pub const GPSD_RELEASE: &str = "3.25";
+pub const PROTO_MAJOR: u32 = 3;
+pub const PROTO_MINOR: u32 = 15;
+const REV: &str = concat!("ubx-schema ", env!("CARGO_PKG_VERSION"));
+
+/// TPV `mode`.
+pub const MODE_NO_FIX: u8 = 1;
+pub const MODE_2D: u8 = 2;
+pub const MODE_3D: u8 = 3;
+
+/// TPV `status`.
+pub const STATUS_GPS: u8 = 1;
+pub const STATUS_DGPS: u8 = 2;
+pub const STATUS_RTK_FIX: u8 = 3;
+pub const STATUS_RTK_FLT: u8 = 4;
+pub const STATUS_DR: u8 = 5;
+pub const STATUS_GNSSDR: u8 = 6;
+pub const STATUS_TIME: u8 = 7;
+
+/// A gpsd time-position-velocity report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tpv {
+    pub class: &'static str,
+    pub device: String,
+    pub mode: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// Time uncertainty, s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ept: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// Height above the ellipsoid, m.
+    #[serde(rename = "altHAE", skip_serializing_if = "Option::is_none")]
+    pub alt_hae: Option<f64>,
+    /// Height above mean sea level, m.
+    #[serde(rename = "altMSL", skip_serializing_if = "Option::is_none")]
+    pub alt_msl: Option<f64>,
+    /// Deprecated alias of `altMSL`, still read by older clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<f64>,
+    /// Horizontal and vertical position uncertainty, m.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eph: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epv: Option<f64>,
+    /// Course over ground, degrees from true north.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magvar: Option<f64>,
+    /// Ground speed, climb rate and their uncertainty, m/s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub climb: Option<f64>,
+    #[serde(rename = "velN", skip_serializing_if = "Option::is_none")]
+    pub vel_n: Option<f64>,
+    #[serde(rename = "velE", skip_serializing_if = "Option::is_none")]
+    pub vel_e: Option<f64>,
+    #[serde(rename = "velD", skip_serializing_if = "Option::is_none")]
+    pub vel_d: Option<f64>,
+}
+
+/// Dilutions of precision, from NAV-DOP.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Dops {
+    pub xdop: f64,
+    pub ydop: f64,
+    pub vdop: f64,
+    pub tdop: f64,
+    pub hdop: f64,
+    pub gdop: f64,
+    pub pdop: f64,
+}
+
+/// A gpsd satellite report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sky {
+    pub class: &'static str,
+    pub device: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub dops: Option<Dops>,
+    #[serde(rename = "nSat")]
+    pub n_sat: usize,
+    #[serde(rename = "uSat")]
+    pub u_sat: usize,
+    pub satellites: Vec<SkySatellite>,
+}
+
+/// One entry of `SKY.satellites`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SkySatellite {
+    #[serde(rename = "PRN")]
+    pub prn: u16,
+    pub gnssid: u8,
+    pub svid: u8,
+    /// Elevation and azimuth, degrees; absent when the receiver does not know them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub el: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub az: Option<f64>,
+    /// C/N0, dBHz.
+    pub ss: f64,
+    pub used: bool,
+    /// 0 unknown, 1 healthy, 2 unhealthy (as UBX).
+    pub health: u8,
+}
+
+/// gpsd's PRN for a UBX satellite: GPS and SBAS as is, GLONASS 65-96,
+/// Galileo 301-336, BeiDou 401-463, IMES 173-182, QZSS 193-202, NavIC 801-.
+pub fn gpsd_prn(gnss_id: u8, sv_id: u8) -> u16 {
+    let sv = u16::from(sv_id);
+    match Gnss::from_id(gnss_id) {
+        Some(Gnss::Gps | Gnss::Sbas) | None => sv,
+        Some(Gnss::Galileo) => 300 + sv,
+        Some(Gnss::BeiDou) => 400 + sv,
+        Some(Gnss::Imes) => 172 + sv,
+        Some(Gnss::Qzss) if sv >= 193 => sv,
+        Some(Gnss::Qzss) => 192 + sv,
+        Some(Gnss::Glonass) if sv == 255 => 0,
+        Some(Gnss::Glonass) => 64 + sv,
+        Some(Gnss::NavIc) => 800 + sv,
+    }
+}
+
+/// gpsd `mode` and `status` for a NAV-PVT solution.
+pub fn mode_status(fix_type: u64, fix_ok: bool, diff_soln: bool, carr_soln: u64) -> (u8, Option<u8>) {
+    match fix_type {
+        5 => (MODE_NO_FIX, Some(STATUS_TIME)),
+        _ if !fix_ok => (MODE_NO_FIX, None),
+        1 => (MODE_3D, Some(STATUS_DR)),
+        4 => (MODE_3D, Some(STATUS_GNSSDR)),
+        2 | 3 => {
+            let status = match carr_soln {
+                2 => STATUS_RTK_FIX,
+                1 => STATUS_RTK_FLT,
+                _ if diff_soln => STATUS_DGPS,
+                _ => STATUS_GPS,
+            };
+            (if fix_type == 2 { MODE_2D } else { MODE_3D }, Some(status))
+        }
+        _ => (MODE_NO_FIX, None),
+    }
+}
+
+/// Translates a decoded message stream into gpsd reports for one device.
+#[derive(Debug, Clone)]
+pub struct GpsdTranslator {
+    device: String,
+    /// iTOW and DOPs of the latest NAV-DOP.
+    dops: Option<(u32, Dops)>,
+    /// iTOW and time of the latest NAV-PVT with a valid date and time.
+    time: Option<(u32, String)>,
+}
+
+impl GpsdTranslator {
+    /// `device` is reported as the `device` of every report, e.g. the input path.
+    pub fn new(device: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            dops: None,
+            time: None,
+        }
+    }
+
+    /// The `VERSION`, `DEVICES` and `WATCH` reports a client sees on connecting.
+    pub fn preamble(&self) -> Vec<Json> {
+        vec![
+            json!({"class": "VERSION", "release": GPSD_RELEASE, "rev": REV,
+                   "proto_major": PROTO_MAJOR, "proto_minor": PROTO_MINOR}),
+            json!({"class": "DEVICES", "devices": [{"class": "DEVICE", "path": self.device, "driver": "u-blox"}]}),
+            json!({"class": "WATCH", "enable": true, "json": true}),
+        ]
+    }
+
+    /// The report for `msg`, if it produces one. NAV-DOP only updates the
+    /// DOPs of the next SKY.
+    pub fn translate(&mut self, msg: &DecodedMessage) -> Option<Json> {
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => serde_json::to_value(self.tpv(msg)).ok(),
+            "UBX-NAV-SAT" => serde_json::to_value(self.sky(msg)).ok(),
+            "UBX-NAV-DOP" => {
+                self.dops = msg.itow().map(|itow| (itow, dops(msg)));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn tpv(&mut self, msg: &DecodedMessage) -> Tpv {
+        let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+        let flag = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        let (mode, status) = mode_status(
+            flag("fixType"),
+            flag("flags.gnssFixOK") == 1,
+            flag("flags.diffSoln") == 1,
+            flag("flags.carrSoln"),
+        );
+        let time = pvt_time(msg);
+        self.time = msg.itow().zip(time.clone());
+
+        let horizontal = mode >= MODE_2D && flag("flags3.invalidLlh") == 0;
+        let vertical = horizontal && mode == MODE_3D;
+        let metres = |name: &str| num(name).map(|mm| round(mm / 1_000.0, 3));
+        let alt_msl = metres("hMSL").filter(|_| vertical);
+        Tpv {
+            class: "TPV",
+            device: self.device.clone(),
+            mode,
+            status,
+            time,
+            ept: num("tAcc").map(|ns| round(ns * 1e-9, 9)),
+            lat: num("lat").filter(|_| horizontal).map(|v| round(v, 9)),
+            lon: num("lon").filter(|_| horizontal).map(|v| round(v, 9)),
+            alt_hae: metres("height").filter(|_| vertical),
+            alt_msl,
+            alt: alt_msl,
+            eph: metres("hAcc").filter(|_| horizontal),
+            epv: metres("vAcc").filter(|_| vertical),
+            track: num("headMot").filter(|_| horizontal).map(|v| round(v, 5)),
+            epd: num("headAcc").filter(|_| horizontal).map(|v| round(v, 5)),
+            magvar: num("magDec").filter(|_| flag("valid.validMag") == 1).map(|v| round(v, 2)),
+            speed: metres("gSpeed").filter(|_| horizontal),
+            eps: metres("sAcc").filter(|_| horizontal),
+            climb: metres("velD").filter(|_| vertical).map(|v| -v),
+            vel_n: metres("velN").filter(|_| horizontal),
+            vel_e: metres("velE").filter(|_| horizontal),
+            vel_d: metres("velD").filter(|_| vertical),
+        }
+    }
+
+    pub fn sky(&mut self, msg: &DecodedMessage) -> Sky {
+        let itow = msg.itow();
+        let same_epoch = |epoch: u32| itow == Some(epoch);
+        let satellites: Vec<SkySatellite> = msg
+            .get("satellites")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Value::as_record)
+            .map(satellite)
+            .collect();
+        Sky {
+            class: "SKY",
+            device: self.device.clone(),
+            time: self.time.clone().filter(|(t, _)| same_epoch(*t)).map(|(_, time)| time),
+            dops: self.dops.filter(|(t, _)| same_epoch(*t)).map(|(_, dops)| dops),
+            n_sat: satellites.len(),
+            u_sat: satellites.iter().filter(|s| s.used).count(),
+            satellites,
+        }
+    }
+}
+
+/// UTC time of a NAV-PVT, when it flags date and time valid.
+fn pvt_time(msg: &DecodedMessage) -> Option<String> {
+    let int = |name: &str| msg.get(name).and_then(Value::as_i64);
+    if int("valid.validDate")? != 1 || int("valid.validTime")? != 1 {
+        return None;
+    }
+    let time = DateTime {
+        year: int("year")? as i32,
+        month: int("month")? as u8,
+        day: int("day")? as u8,
+        hour: int("hour")? as u8,
+        minute: int("min")? as u8,
+        second: int("sec")? as f64 + int("nano").unwrap_or(0) as f64 * 1e-9,
+    };
+    Some(time.to_iso8601())
+}
+
+fn dops(msg: &DecodedMessage) -> Dops {
+    let dop = |name: &str| round(msg.get(name).and_then(Value::as_f64).unwrap_or(0.0), 2);
+    Dops {
+        xdop: dop("eDOP"),
+        ydop: dop("nDOP"),
+        vdop: dop("vDOP"),
+        tdop: dop("tDOP"),
+        hdop: dop("hDOP"),
+        gdop: dop("gDOP"),
+        pdop: dop("pDOP"),
+    }
+}
+
+fn satellite(sat: &Fields) -> SkySatellite {
+    let int = |name: &str| sat.get_path(name).and_then(Value::as_i64).unwrap_or(0);
+    // svUsed is bit 3 and health bits 4-5 of flags when the schema has no member names.
+    let (used, health) = match (sat.get_path("flags.svUsed"), sat.get_path("flags.health")) {
+        (Some(used), Some(health)) => (used.as_u64() == Some(1), health.as_u64().unwrap_or(0) as u8),
+        _ => (int("flags") & 0x08 != 0, ((int("flags") >> 4) & 0x03) as u8),
+    };
+    let (gnss_id, sv_id, elev) = (int("gnssId") as u8, int("svId") as u8, int("elev"));
+    let known = (-90..=90).contains(&elev);
+    SkySatellite {
+        prn: gpsd_prn(gnss_id, sv_id),
+        gnssid: gnss_id,
+        svid: sv_id,
+        el: known.then_some(elev as f64),
+        az: known.then_some(int("azim") as f64),
+        ss: int("cno") as f64,
+        used,
+        health,
+    }
+}
+
+/// Round to `places` decimals, so scaled integers print as gpsd would.
+fn round(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
@@ -0,0 +1,85 @@
+//! Host receive times recorded beside a log.
+//!
+//! Neither UBX nor u-center's `.ubx` logs carry the time the host received
+//! a frame. `ubx-capture --timestamps` records it in a CSV beside the log,
+//! with a row of `offset,host_time,message` per frame: the frame's byte
+//! offset in the log, the Unix time in seconds, and the message name (for
+//! people; it is not read back). [`HostTimes`] holds such a file. The tools
+//! that write decoded frames take it as `-t/--timestamps` and report each
+//! frame's `host_time`; `ubx-merge` orders by it. `ubx-mqtt` and `ubx-ws`
+//! send each message as [`Timed`].
+//!
+//! Offsets are those of the log the CSV was written with, so a log that has
+//! since been filtered or converted no longer matches its times.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::decode::DecodedMessage;
+use crate::error::{Error, Result};
+
+/// Host receive time (Unix seconds) by frame offset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostTimes {
+    times: HashMap<usize, f64>,
+}
+
+impl HostTimes {
+    /// Parse the CSV written by `ubx-capture --timestamps`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(text.as_bytes());
+        let mut times = HashMap::new();
+        for (line, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| Error::Config(format!("timestamps: {e}")))?;
+            let parsed = match (record.get(0), record.get(1)) {
+                (Some(offset), Some(time)) => offset.parse().ok().zip(time.parse().ok()),
+                _ => None,
+            };
+            let Some((offset, time)) = parsed else {
+                return Err(Error::Config(format!("timestamps line {}: expected offset,host_time", line + 2)));
+            };
+            times.insert(offset, time);
+        }
+        Ok(Self { times })
+    }
+
+    /// Read and parse a timestamps CSV.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("timestamps {}: {e}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    /// The time of the frame at `offset`, if the CSV has one.
+    pub fn at(&self, offset: usize) -> Option<f64> {
+        self.times.get(&offset).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+}
+
+impl FromIterator<(usize, f64)> for HostTimes {
+    fn from_iter<I: IntoIterator<Item = (usize, f64)>>(iter: I) -> Self {
+        Self { times: iter.into_iter().collect() }
+    }
+}
+
+/// A decoded message with its host receive time: serialized as the message
+/// plus a `host_time` member, or as the message alone without a time.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Timed<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_time: Option<f64>,
+    #[serde(flatten)]
+    pub message: &'a DecodedMessage,
+}
@@ -0,0 +1,262 @@
+//! Standalone HTML decode reports.
+//!
+//! [`HtmlReport`] renders a capture as a single HTML file with no external
+//! resources, to attach to a ticket or send to someone without the tools
+//! installed. It lists every frame with its offset, message and length.
+//! Each one expands to a table of its decoded fields and a hex dump marking
+//! header, payload and checksum. Bitfields and repeated-group elements
+//! expand in turn. Checksum errors, messages the schema doesn't know and
+//! values with plausibility [`Warning`]s are highlighted. A filter box and
+//! an errors-only switch narrow the list in the browser. Frames past
+//! `max_frames` are counted in the summary but not rendered, which keeps
+//! the page usable on long logs.
+//!
+//! Messages should be decoded with scaling and flag expansion enabled.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::decode::DecodedMessage;
+use crate::error::{FrameError, Result};
+use crate::frame::{FrameRef, HEADER_LEN};
+use crate::semantic::Warning;
+use crate::value::{Fields, Value};
+
+/// Frames rendered by default.
+pub const DEFAULT_MAX_FRAMES: usize = 10_000;
+
+const STYLE: &str = "\
+body{font:14px system-ui,sans-serif;margin:1.5em;color:#222}\
+h1{font-size:1.4em}table{border-collapse:collapse;margin:.3em 0}\
+td,th{border:1px solid #ccc;padding:2px 6px;text-align:left;vertical-align:top}\
+th{background:#f3f3f3}.num{text-align:right}\
+details.frame{border-left:4px solid #8ab;margin:2px 0;padding:1px 6px}\
+details.frame>summary{cursor:pointer;font-family:monospace}\
+.unknown{border-left-color:#db4!important;background:#fff8e0}\
+.warning{border-left-color:#e80!important}tr.warning td{background:#fff0e0}\
+.error{border-left:4px solid #c33;background:#fde8e8;padding:2px 6px;margin:2px 0;font-family:monospace}\
+.hex{font-family:monospace;word-break:break-all;max-width:60em}\
+.hdr{color:#37a}.ck{color:#a3a}.hidden{display:none}\
+#controls{position:sticky;top:0;background:#fff;padding:.4em 0}";
+
+const SCRIPT: &str = "\
+const q=document.getElementById('filter'),e=document.getElementById('errors');\
+function apply(){const t=q.value.toUpperCase();\
+for(const f of document.querySelectorAll('.frame,.error')){\
+const bad=f.classList.contains('error')||f.classList.contains('unknown')||f.classList.contains('warning');\
+f.classList.toggle('hidden',!(f.dataset.name||'').includes(t)||(e.checked&&!bad));}}\
+q.oninput=apply;e.onchange=apply;\
+function expand(o){for(const d of document.querySelectorAll('details.frame'))\
+if(!d.classList.contains('hidden'))d.open=o;}";
+
+/// Builds the report page from decoded frames and framing errors.
+#[derive(Debug, Clone)]
+pub struct HtmlReport {
+    title: String,
+    max_frames: usize,
+    body: String,
+    rendered: usize,
+    pub frames: usize,
+    pub errors: usize,
+    pub unknown: usize,
+    pub warnings: usize,
+    /// Frames per message name.
+    pub counts: BTreeMap<String, usize>,
+}
+
+impl HtmlReport {
+    pub fn new(title: &str, max_frames: usize) -> Self {
+        Self {
+            title: title.to_string(),
+            max_frames,
+            body: String::new(),
+            rendered: 0,
+            frames: 0,
+            errors: 0,
+            unknown: 0,
+            warnings: 0,
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Add a frame, its decoded message and the warnings it raised.
+    pub fn add_frame(&mut self, frame: &FrameRef<'_>, msg: &DecodedMessage, warnings: &[Warning]) {
+        self.frames += 1;
+        self.warnings += warnings.len();
+        let name = if msg.parsed {
+            msg.short_name().to_string()
+        } else {
+            self.unknown += 1;
+            format!("{:02X}-{:02X}", frame.class, frame.id)
+        };
+        *self.counts.entry(name.clone()).or_default() += 1;
+        if !self.render() {
+            return;
+        }
+        let class = match (msg.parsed, warnings.is_empty()) {
+            (false, _) => "frame unknown",
+            (true, false) => "frame warning",
+            (true, true) => "frame",
+        };
+        let label = match &msg.variant {
+            Some(variant) => format!("{name} ({variant})"),
+            None => name.clone(),
+        };
+        let b = &mut self.body;
+        let _ = write!(
+            b,
+            "<details class=\"{class}\" data-name=\"{}\"><summary>{:08X} <b>{}</b> {} bytes",
+            escape(&name.to_ascii_uppercase()),
+            frame.offset,
+            escape(&label),
+            frame.raw.len()
+        );
+        if let Some(itow) = msg.itow() {
+            let _ = write!(b, " iTOW {itow}");
+        }
+        if !msg.parsed {
+            b.push_str(" &mdash; unknown message");
+        }
+        if !warnings.is_empty() {
+            let _ = write!(b, " &mdash; {} warning{}", warnings.len(), if warnings.len() == 1 { "" } else { "s" });
+        }
+        b.push_str("</summary>\n");
+        if !warnings.is_empty() {
+            b.push_str("<ul>");
+            for w in warnings {
+                let _ = write!(b, "<li>{}</li>", escape(&w.to_string()));
+            }
+            b.push_str("</ul>\n");
+        }
+        if !msg.fields.is_empty() {
+            let flagged: Vec<&str> = warnings.iter().map(|w| w.field.as_str()).collect();
+            fields_table(b, &msg.fields, "", &flagged);
+        }
+        let (header, rest) = frame.raw.split_at(HEADER_LEN.min(frame.raw.len()));
+        let (payload, checksum) = rest.split_at(rest.len().saturating_sub(2));
+        let _ = writeln!(
+            b,
+            "<p class=\"hex\"><span class=\"hdr\">{}</span> {} <span class=\"ck\">{}</span></p></details>",
+            spaced_hex(header),
+            spaced_hex(payload),
+            spaced_hex(checksum)
+        );
+    }
+
+    /// Add a framing error found at `offset`.
+    pub fn add_error(&mut self, offset: usize, error: &FrameError) {
+        self.errors += 1;
+        if !self.render() {
+            return;
+        }
+        let _ = writeln!(
+            self.body,
+            "<div class=\"error\" data-name=\"ERROR\">{offset:08X} {}</div>",
+            escape(&error.to_string())
+        );
+    }
+
+    /// Count one more rendered entry; false once the limit is reached.
+    fn render(&mut self) -> bool {
+        self.rendered += 1;
+        self.rendered <= self.max_frames
+    }
+
+    /// Write the page: summary, message counts and the frame list.
+    pub fn write(&self, out: &mut dyn Write) -> Result<()> {
+        let title = escape(&self.title);
+        write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>{title}</title>\
+             <style>{STYLE}</style></head><body>\n<h1>{title}</h1>\n"
+        )?;
+        writeln!(
+            out,
+            "<p>{} frames, {} checksum errors, {} unknown messages, {} warnings.</p>",
+            self.frames, self.errors, self.unknown, self.warnings
+        )?;
+        if self.rendered > self.max_frames {
+            writeln!(
+                out,
+                "<p><b>Only the first {} of {} entries are listed.</b></p>",
+                self.max_frames, self.rendered
+            )?;
+        }
+        writeln!(out, "<table><tr><th>Message</th><th>Frames</th></tr>")?;
+        for (name, n) in &self.counts {
+            writeln!(out, "<tr><td>{}</td><td class=\"num\">{n}</td></tr>", escape(name))?;
+        }
+        writeln!(out, "</table>")?;
+        writeln!(
+            out,
+            "<div id=\"controls\"><input id=\"filter\" placeholder=\"Filter messages\"> \
+             <label><input type=\"checkbox\" id=\"errors\"> errors and warnings only</label> \
+             <button onclick=\"expand(true)\">Expand all</button> \
+             <button onclick=\"expand(false)\">Collapse all</button></div>"
+        )?;
+        out.write_all(self.body.as_bytes())?;
+        writeln!(out, "<script>{SCRIPT}</script>\n</body></html>")?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// A table row per field; records and arrays of records nest.
+fn fields_table(b: &mut String, fields: &Fields, prefix: &str, flagged: &[&str]) {
+    b.push_str("<table>");
+    for (name, value) in fields.iter() {
+        let path = if prefix.is_empty() { name.to_string() } else { format!("{prefix}.{name}") };
+        let warned = flagged
+            .iter()
+            .any(|f| f.strip_prefix(path.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '['])));
+        let _ = write!(
+            b,
+            "<tr{}><th>{}</th><td>",
+            if warned { " class=\"warning\"" } else { "" },
+            escape(name)
+        );
+        value_cell(b, value, &path, flagged);
+        b.push_str("</td></tr>");
+    }
+    b.push_str("</table>\n");
+}
+
+fn value_cell(b: &mut String, value: &Value, path: &str, flagged: &[&str]) {
+    match value {
+        Value::Record(members) => {
+            let _ = write!(b, "<details><summary>{} members</summary>", members.len());
+            fields_table(b, members, path, flagged);
+            b.push_str("</details>");
+        }
+        Value::Array(items) if items.iter().any(|v| matches!(v, Value::Record(_))) => {
+            let _ = write!(b, "<details><summary>{} elements</summary>", items.len());
+            for (i, item) in items.iter().enumerate() {
+                let _ = write!(b, "<div>[{i}]</div>");
+                value_cell(b, item, &format!("{path}[{i}]"), flagged);
+            }
+            b.push_str("</details>");
+        }
+        value => b.push_str(&escape(&value.to_string())),
+    }
+}
+
+fn spaced_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+/// Escape text for HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
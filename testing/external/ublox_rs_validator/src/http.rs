@@ -0,0 +1,294 @@
+//! HTTP decode service: the schema's decoder and encoder behind two
+//! endpoints, for services that aren't written in Rust.
+//!
+//! `POST /decode` takes UBX bytes, either raw (`Content-Type:
+//! application/octet-stream`) or as hex text, and answers with the decoded
+//! frames shaped like `ubx-decode` JSON output plus the scan counters.
+//! `POST /encode` takes one message or an array of them in the encoder's
+//! JSON form (`{"name": "CFG-VALSET", "fields": {...}}`) and answers with
+//! the frames' bytes, or with their hex in JSON for `?format=hex`. Decoding
+//! takes `scale`, `expand_flags` and `include_reserved` query parameters
+//! that override the service defaults. `GET /health` answers `ok`.
+//!
+//! [`Request::read`] parses one HTTP/1.1 request from a connection and
+//! [`DecodeService::handle`] maps it to a [`Response`]; `ubx-serve` runs
+//! them on a thread per connection.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Read, Write};
+
+use serde_json::{json, Value as Json};
+
+use crate::decode::{DecodeOptions, Decoder};
+use crate::encode::Encoder;
+use crate::error::{Error, Result};
+use crate::frame::FrameIter;
+use crate::output::Record;
+use crate::schema::Schema;
+
+/// Default cap on request bodies, in bytes.
+pub const DEFAULT_MAX_BODY: usize = 16 << 20;
+
+/// Longest request or header line accepted.
+const MAX_LINE: usize = 8 << 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// The path without its query, e.g. `/decode`.
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    /// By lower-case name.
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read one request; `None` when the client closed the connection
+    /// before sending one. Bodies need a `Content-Length` of at most
+    /// `max_body` bytes.
+    pub fn read(reader: &mut impl BufRead, max_body: usize) -> Result<Option<Self>> {
+        let Some(line) = read_line(reader)? else {
+            return Ok(None);
+        };
+        let mut words = line.split_whitespace();
+        let (Some(method), Some(target), Some(_version)) = (words.next(), words.next(), words.next()) else {
+            return Err(Error::Http(format!("malformed request line '{line}'")));
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (name, value) = p.split_once('=').unwrap_or((p, ""));
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        let mut headers = BTreeMap::new();
+        loop {
+            let header = read_line(reader)?.ok_or_else(|| Error::Http("connection closed in headers".into()))?;
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header
+                .split_once(':')
+                .ok_or_else(|| Error::Http(format!("malformed header '{header}'")))?;
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+        if headers.get("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+            return Err(Error::Http("chunked request bodies are not supported".into()));
+        }
+        let length = match headers.get("content-length") {
+            Some(v) => v.parse().map_err(|_| Error::Http(format!("bad Content-Length '{v}'")))?,
+            None => 0,
+        };
+        if length > max_body {
+            return Err(Error::Http(format!("body of {length} bytes exceeds the {max_body}-byte limit")));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        Ok(Some(Self {
+            method: method.to_ascii_uppercase(),
+            path: path.to_string(),
+            query,
+            headers,
+            body,
+        }))
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Whether the client asked to close the connection after this request.
+    pub fn wants_close(&self) -> bool {
+        self.header("connection").is_some_and(|v| v.eq_ignore_ascii_case("close"))
+    }
+
+    /// A boolean query parameter: present without a value, `1`, `true` or
+    /// `yes` is true.
+    fn flag(&self, name: &str) -> Option<bool> {
+        self.query
+            .get(name)
+            .map(|v| matches!(v.to_ascii_lowercase().as_str(), "" | "1" | "true" | "yes"))
+    }
+}
+
+/// A line without its CRLF; `None` at end of stream.
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = Vec::new();
+    let n = reader.take(MAX_LINE as u64 + 1).read_until(b'\n', &mut line)?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with(b"\n") {
+        return Err(Error::Http("request line too long".into()));
+    }
+    let line = String::from_utf8(line).map_err(|_| Error::Http("request line is not UTF-8".into()))?;
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, body: &Json) -> Self {
+        let mut body = serde_json::to_vec_pretty(body).unwrap_or_default();
+        body.push(b'\n');
+        Self {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    /// A JSON `{"error": ...}` body.
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+
+    pub fn bytes(body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/octet-stream",
+            body,
+        }
+    }
+
+    pub fn text(status: u16, text: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{text}\n").into_bytes(),
+        }
+    }
+
+    pub fn write_to(&self, out: &mut dyn Write, close: bool) -> Result<()> {
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: {}\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len(),
+            if close { "close" } else { "keep-alive" }
+        )?;
+        out.write_all(&self.body)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Answers `/decode` and `/encode` requests from one schema.
+pub struct DecodeService<'s> {
+    schema: &'s Schema,
+    encoder: Encoder<'s>,
+    /// Used where a request doesn't say otherwise.
+    pub options: DecodeOptions,
+}
+
+impl<'s> DecodeService<'s> {
+    pub fn new(schema: &'s Schema, options: DecodeOptions) -> Self {
+        Self {
+            schema,
+            encoder: Encoder::new(schema),
+            options,
+        }
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/decode") => self.decode(request),
+            ("POST", "/encode") => self.encode(request),
+            ("GET", "/health") => Response::text(200, "ok"),
+            (_, "/decode" | "/encode" | "/health") => Response::error(405, "method not allowed"),
+            _ => Response::error(404, "not found"),
+        }
+    }
+
+    fn decode(&self, request: &Request) -> Response {
+        let raw = request
+            .header("content-type")
+            .is_some_and(|t| t.starts_with("application/octet-stream"));
+        let data = if raw {
+            request.body.clone()
+        } else {
+            let text: String = String::from_utf8_lossy(&request.body)
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            match hex::decode(&text) {
+                Ok(data) => data,
+                Err(e) => return Response::error(400, &format!("body is neither octet-stream nor hex: {e}")),
+            }
+        };
+        let options = DecodeOptions {
+            scale: request.flag("scale").unwrap_or(self.options.scale),
+            expand_flags: request.flag("expand_flags").unwrap_or(self.options.expand_flags),
+            include_reserved: request.flag("include_reserved").unwrap_or(self.options.include_reserved),
+        };
+        let decoder = Decoder::with_options(self.schema, options);
+        let mut iter = FrameIter::new(&data);
+        let messages: Vec<Json> = iter
+            .by_ref()
+            .flatten()
+            .map(|frame| {
+                let message = decoder.decode_frame(&frame);
+                serde_json::to_value(Record {
+                    offset: frame.offset,
+                    host_time: None,
+                    message: &message,
+                })
+                .unwrap_or_default()
+            })
+            .collect();
+        let stats = iter.stats();
+        Response::json(
+            200,
+            &json!({
+                "messages": messages,
+                "frames": stats.frames,
+                "checksum_errors": stats.checksum_errors,
+                "skipped_bytes": stats.skipped_bytes,
+            }),
+        )
+    }
+
+    fn encode(&self, request: &Request) -> Response {
+        let body: Json = match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(e) => return Response::error(400, &format!("invalid JSON: {e}")),
+        };
+        let messages = match &body {
+            Json::Array(messages) => messages.as_slice(),
+            message => std::slice::from_ref(message),
+        };
+        let mut frames = Vec::new();
+        for (i, message) in messages.iter().enumerate() {
+            match self.encoder.encode_json(message).and_then(|frame| Ok(frame.try_to_bytes()?)) {
+                Ok(bytes) => frames.push(bytes),
+                Err(e) => return Response::error(400, &format!("message {i}: {e}")),
+            }
+        }
+        if request.query.get("format").is_some_and(|f| f == "hex") {
+            let frames: Vec<String> = frames.iter().map(hex::encode).collect();
+            Response::json(200, &json!({ "frames": frames }))
+        } else {
+            Response::bytes(frames.concat())
+        }
+    }
+}
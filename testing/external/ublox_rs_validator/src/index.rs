@@ -0,0 +1,206 @@
+//! Frame index of a log, for random access by time and message type.
+//!
+//! One pass over a log records the offset, class, ID, length and epoch iTOW
+//! of every valid frame. Saved next to the log as `<log>.idx`, it lets the
+//! tools go straight to the frames of a time window or message type and
+//! decode only those, instead of rescanning and decoding the whole file.
+//!
+//! The sidecar is little-endian binary: the magic `UBXIDX\0\x01`, the log's
+//! length and a fingerprint of its first and last 4 KiB (to notice a log
+//! that changed), the entry count, then 16 bytes per frame: offset (u64),
+//! class, ID, payload length (u16) and iTOW (u32, `u32::MAX` for none).
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::filter::ItowRange;
+use crate::frame::{frames, parse_frame, FrameRef};
+use crate::schema::{BaseType, DataType, Schema};
+
+const MAGIC: &[u8; 8] = b"UBXIDX\0\x01";
+const HEADER_LEN: usize = 32;
+const ENTRY_LEN: usize = 16;
+/// Bytes at each end of the log hashed into the fingerprint.
+const FINGERPRINT_SPAN: usize = 4096;
+
+/// One frame of the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Offset of the first sync character.
+    pub offset: u64,
+    pub class: u8,
+    pub id: u8,
+    pub payload_len: u16,
+    /// iTOW of the frame's epoch: its own, or that of the last frame before
+    /// it that has one, as [`crate::filter`] assigns them.
+    pub itow: Option<u32>,
+}
+
+impl IndexEntry {
+    pub fn frame_len(&self) -> usize {
+        crate::frame::FRAME_OVERHEAD + self.payload_len as usize
+    }
+}
+
+/// The index of one log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogIndex {
+    pub log_len: u64,
+    pub fingerprint: u64,
+    pub entries: Vec<IndexEntry>,
+}
+
+/// `capture.ubx.idx` for `capture.ubx`.
+pub fn sidecar_path(log: &Path) -> PathBuf {
+    let mut name = log.as_os_str().to_owned();
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+/// FNV-1a over the first and last [`FINGERPRINT_SPAN`] bytes.
+fn fingerprint(data: &[u8]) -> u64 {
+    let head = &data[..data.len().min(FINGERPRINT_SPAN)];
+    let tail = &data[data.len().saturating_sub(FINGERPRINT_SPAN)..];
+    head.iter().chain(tail).fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// The frame's own `iTOW`, read where its definition places it.
+fn own_itow(schema: &Schema, frame: &FrameRef<'_>) -> Option<u32> {
+    let msg = schema.message_for_payload(frame.class, frame.id, frame.payload)?;
+    let (def, _) = msg.payload_for(frame.payload)?;
+    let field = def.field("iTOW")?;
+    let at = field.byte_offset?;
+    let b = frame.payload.get(at..at + 4)?;
+    let raw = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+    match field.data_type {
+        DataType::Scalar(BaseType::U4) => Some(raw),
+        DataType::Scalar(BaseType::I4) => (raw as i32 >= 0).then_some(raw),
+        _ => None,
+    }
+}
+
+impl LogIndex {
+    /// Index every valid frame in `data`.
+    pub fn build(schema: &Schema, data: &[u8]) -> Self {
+        let mut epoch = None;
+        let entries = frames(data)
+            .flatten()
+            .map(|frame| {
+                epoch = own_itow(schema, &frame).or(epoch);
+                IndexEntry {
+                    offset: frame.offset as u64,
+                    class: frame.class,
+                    id: frame.id,
+                    payload_len: frame.payload.len() as u16,
+                    itow: epoch,
+                }
+            })
+            .collect();
+        Self {
+            log_len: data.len() as u64,
+            fingerprint: fingerprint(data),
+            entries,
+        }
+    }
+
+    /// Whether this index was built from `data`.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.log_len == data.len() as u64 && self.fingerprint == fingerprint(data)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + ENTRY_LEN * self.entries.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&self.log_len.to_le_bytes());
+        out.extend_from_slice(&self.fingerprint.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        for entry in &self.entries {
+            out.extend_from_slice(&entry.offset.to_le_bytes());
+            out.extend_from_slice(&[entry.class, entry.id]);
+            out.extend_from_slice(&entry.payload_len.to_le_bytes());
+            out.extend_from_slice(&entry.itow.unwrap_or(u32::MAX).to_le_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let u64_at = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+        if data.len() < HEADER_LEN || &data[..8] != MAGIC {
+            return Err(Error::Index("not a UBX log index".into()));
+        }
+        let count = u64_at(24) as usize;
+        let body = &data[HEADER_LEN..];
+        if body.len() != count.saturating_mul(ENTRY_LEN) {
+            return Err(Error::Index(format!("expected {count} entries, found {} bytes", body.len())));
+        }
+        let entries = body
+            .chunks_exact(ENTRY_LEN)
+            .map(|e| {
+                let itow = u32::from_le_bytes([e[12], e[13], e[14], e[15]]);
+                IndexEntry {
+                    offset: u64::from_le_bytes(e[..8].try_into().unwrap()),
+                    class: e[8],
+                    id: e[9],
+                    payload_len: u16::from_le_bytes([e[10], e[11]]),
+                    itow: (itow != u32::MAX).then_some(itow),
+                }
+            })
+            .collect();
+        Ok(Self {
+            log_len: u64_at(8),
+            fingerprint: u64_at(16),
+            entries,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.to_bytes())?)
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// The sidecar of the log at `path` if it matches `data`; otherwise a
+    /// new index, saved as the sidecar when `save` is set. The flag is true
+    /// when the index was built.
+    pub fn load_or_build(schema: &Schema, path: &Path, data: &[u8], save: bool) -> Result<(Self, bool)> {
+        let sidecar = sidecar_path(path);
+        if let Ok(index) = Self::read(&sidecar) {
+            if index.matches(data) {
+                return Ok((index, false));
+            }
+        }
+        let index = Self::build(schema, data);
+        if save {
+            index.write(&sidecar)?;
+        }
+        Ok((index, true))
+    }
+
+    /// Entries of the given `(class, id)` types (all when `None`) whose epoch
+    /// falls in `window` (any, including none, when `None`).
+    pub fn select<'a>(
+        &'a self,
+        types: Option<&'a HashSet<(u8, u8)>>,
+        window: Option<ItowRange>,
+    ) -> impl Iterator<Item = &'a IndexEntry> + 'a {
+        self.entries.iter().filter(move |e| {
+            types.is_none_or(|t| t.contains(&(e.class, e.id)))
+                && window.is_none_or(|w| e.itow.is_some_and(|itow| w.contains(itow)))
+        })
+    }
+
+    /// The frame `entry` refers to in `data`, the log it was built from.
+    pub fn frame<'a>(&self, entry: &IndexEntry, data: &'a [u8]) -> Result<FrameRef<'a>> {
+        let start = usize::try_from(entry.offset).ok().filter(|&s| s <= data.len());
+        let start = start.ok_or_else(|| Error::Index(format!("offset {} is past the log", entry.offset)))?;
+        let mut frame = parse_frame(&data[start..])?;
+        frame.offset = start;
+        Ok(frame)
+    }
+}
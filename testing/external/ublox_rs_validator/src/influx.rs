@@ -0,0 +1,152 @@
+//! InfluxDB line protocol output, e.g. for Telegraf's `socket_listener` or
+//! `execd` inputs.
+//!
+//! Each message becomes a line with the message name as measurement
+//! (`NAV-PVT`) and its scalar fields as fields; messages with a `fixType`
+//! are tagged with the solution quality. Each instance of a repeated group
+//! becomes a line of its own, tagged with the group name and either its
+//! constellation and satellite (`gnss=GPS,sv=12`) or its index. Timestamps
+//! are UTC nanoseconds, from the iTOW and the latest NAV-PVT or NAV-TIMEUTC
+//! with valid UTC, or NAV-TIMEGPS with a valid week; lines before the first such message have none, so the
+//! database assigns arrival time. Messages must be decoded with scaling and
+//! flag expansion enabled.
+
+use std::fmt::Write as _;
+
+use crate::decode::DecodedMessage;
+use crate::filter::utc_anchor;
+use crate::gnss::Gnss;
+use crate::time::itow_delta;
+use crate::track::FixQuality;
+use crate::value::{Fields, Value};
+
+/// Tag value of a solution quality.
+pub fn fix_tag(fix: FixQuality) -> &'static str {
+    match fix {
+        FixQuality::NoFix => "no_fix",
+        FixQuality::DeadReckoning => "dead_reckoning",
+        FixQuality::TimeOnly => "time_only",
+        FixQuality::Fix2D => "2d",
+        FixQuality::Fix3D => "3d",
+        FixQuality::Dgnss => "dgnss",
+        FixQuality::RtkFloat => "rtk_float",
+        FixQuality::RtkFixed => "rtk_fixed",
+    }
+}
+
+/// Escape a measurement name, tag key or value, or field key.
+fn escape(s: &str, measurement: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == ',' || c == ' ' || (!measurement && c == '=') || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// A field value in line protocol, or `None` for values it cannot hold
+/// (scalar arrays, NaN).
+fn field_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Unsigned(v) => Some(match i64::try_from(*v) {
+            Ok(v) => format!("{v}i"),
+            Err(_) => format!("{v}u"),
+        }),
+        Value::Signed(v) => Some(format!("{v}i")),
+        // 15 significant digits hide the binary noise of applying scale factors.
+        Value::Float(v) if v.is_finite() => Some(format!("{}", format!("{v:.14e}").parse::<f64>().unwrap_or(*v))),
+        Value::Text(s) => Some(format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))),
+        _ => None,
+    }
+}
+
+/// Converts decoded messages to lines, tracking the UTC anchor for timestamps.
+#[derive(Debug, Clone, Default)]
+pub struct LineEncoder {
+    /// `(iTOW, Unix time)` of the latest message with valid UTC.
+    anchor: Option<(u32, f64)>,
+    /// Time of the latest message, for messages without an iTOW.
+    last: Option<f64>,
+}
+
+impl LineEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The lines for `msg`, without trailing newlines.
+    pub fn lines(&mut self, msg: &DecodedMessage) -> Vec<String> {
+        self.anchor = utc_anchor(msg).or(self.anchor);
+        let time = match (self.anchor, msg.itow()) {
+            (Some((anchor_itow, unix)), Some(itow)) => Some(unix + itow_delta(anchor_itow, itow) as f64 / 1_000.0),
+            _ => self.last,
+        };
+        self.last = time;
+        let timestamp = time.map(|t| format!(" {}", (t * 1e9).round() as i64)).unwrap_or_default();
+        let measurement = escape(msg.short_name(), true);
+
+        let mut tags = String::new();
+        if let Some(fix_type) = msg.get("fixType").and_then(Value::as_u64) {
+            let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+            let fix = FixQuality::from_pvt(fix_type, int("flags.diffSoln") == 1, int("flags.carrSoln"));
+            tags = format!(",fix={}", fix_tag(fix));
+        }
+
+        let mut lines = Vec::new();
+        let mut fields = Vec::new();
+        for (name, value) in msg.fields.iter() {
+            match value.as_array() {
+                Some(items) if items.iter().any(|v| v.as_record().is_some()) => {
+                    let records = items.iter().filter_map(Value::as_record);
+                    for (i, record) in records.enumerate() {
+                        let group_fields = field_set(&record.flatten());
+                        if !group_fields.is_empty() {
+                            let tags = format!("{tags},group={}{}", escape(name, false), instance_tags(record, i));
+                            lines.push(format!("{measurement}{tags} {group_fields}{timestamp}"));
+                        }
+                    }
+                }
+                _ => {
+                    let mut single = Fields::new();
+                    single.push(name, value.clone());
+                    fields.extend(single.flatten());
+                }
+            }
+        }
+        let fields = field_set(&fields);
+        if !fields.is_empty() {
+            lines.insert(0, format!("{measurement}{tags} {fields}{timestamp}"));
+        }
+        lines
+    }
+}
+
+/// `key=value,...` for the representable values.
+fn field_set(values: &[(String, Value)]) -> String {
+    let mut out = String::new();
+    for (key, value) in values {
+        if let Some(value) = field_value(value) {
+            let sep = if out.is_empty() { "" } else { "," };
+            let _ = write!(out, "{sep}{}={value}", escape(key, false));
+        }
+    }
+    out
+}
+
+/// Tags identifying one instance of a repeated group.
+fn instance_tags(record: &Fields, index: usize) -> String {
+    let int = |name: &str| record.get(name).and_then(Value::as_u64);
+    match (int("gnssId"), int("svId")) {
+        (Some(gnss_id), Some(sv_id)) => {
+            let gnss = Gnss::from_id(gnss_id as u8).map_or_else(|| gnss_id.to_string(), |g| g.name().to_string());
+            let mut tags = format!(",gnss={gnss},sv={sv_id}");
+            if let Some(sig_id) = int("sigId") {
+                let _ = write!(tags, ",sig={sig_id}");
+            }
+            tags
+        }
+        _ => format!(",index={index}"),
+    }
+}
@@ -0,0 +1,126 @@
+//! Turn raw receiver captures into corpus vectors.
+//!
+//! A capture is split into frames per message type. Byte-identical frames,
+//! within the capture and against the corpus, are dropped, and up to a
+//! chosen number of samples per type are kept, spread evenly over the
+//! capture so that start-up and steady-state epochs are both represented.
+//! Each sample is tagged with the receiver named by the most recent MON-VER
+//! in the capture (the first one, for frames before it).
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::corpus::Vector;
+use crate::decode::Decoder;
+use crate::error::Result;
+use crate::filter::Filter;
+use crate::frame::{frames, FrameRef};
+use crate::receiver::ReceiverInfo;
+
+/// A frame picked from a capture.
+#[derive(Debug, Clone)]
+pub struct Sample<'a> {
+    pub frame: FrameRef<'a>,
+    /// Message name without the `UBX-` prefix.
+    pub name: String,
+    pub receiver: Option<ReceiverInfo>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IngestStats {
+    pub frames: usize,
+    /// Frames the schema has no definition for.
+    pub unknown: usize,
+    /// Frames identical to an earlier one or to a corpus vector.
+    pub duplicates: usize,
+    /// Distinct frames not picked because their type had enough samples.
+    pub surplus: usize,
+}
+
+/// Pick up to `per_type` distinct samples of each message type in `data`
+/// that `filter` accepts, skipping frames whose bytes are in `known`.
+/// Samples are ordered by type, then by position in the capture.
+pub fn split<'a>(
+    decoder: &Decoder,
+    data: &'a [u8],
+    per_type: usize,
+    filter: &Filter,
+    known: &HashSet<Vec<u8>>,
+) -> (Vec<Sample<'a>>, IngestStats) {
+    let mut stats = IngestStats::default();
+    let mut seen: HashSet<&[u8]> = HashSet::new();
+    let mut first_receiver = None;
+    let mut current = None;
+    let mut by_type: BTreeMap<String, Vec<(FrameRef<'a>, Option<ReceiverInfo>)>> = BTreeMap::new();
+
+    for frame in frames(data).flatten() {
+        stats.frames += 1;
+        let msg = decoder.decode_frame(&frame);
+        if !msg.parsed {
+            stats.unknown += 1;
+            continue;
+        }
+        if let Some(info) = ReceiverInfo::from_mon_ver(&msg) {
+            first_receiver.get_or_insert_with(|| info.clone());
+            current = Some(info);
+        }
+        if !filter.type_matches(&msg) {
+            continue;
+        }
+        if !seen.insert(frame.raw) || known.contains(frame.raw) {
+            stats.duplicates += 1;
+            continue;
+        }
+        let name = msg.short_name().to_string();
+        by_type.entry(name).or_default().push((frame, current.clone()));
+    }
+
+    let mut samples = Vec::new();
+    for (name, candidates) in by_type {
+        let picked = spread(candidates.len(), per_type);
+        stats.surplus += candidates.len() - picked.len();
+        for i in picked {
+            let (frame, receiver) = candidates[i].clone();
+            samples.push(Sample {
+                frame,
+                name: name.clone(),
+                receiver: receiver.or_else(|| first_receiver.clone()),
+            });
+        }
+    }
+    (samples, stats)
+}
+
+/// `n` indices spread evenly over `0..len`, always including the first.
+fn spread(len: usize, n: usize) -> Vec<usize> {
+    if len <= n {
+        return (0..len).collect();
+    }
+    (0..n).map(|i| i * len / n).collect()
+}
+
+/// The vector for `sample`, and the path it should have under `corpus`:
+/// `<MSG>/<receiver>-<n>.json` with the first `n` that neither exists nor is
+/// in `taken`. The path is added to `taken`.
+pub fn vector_for(
+    decoder: &Decoder,
+    sample: &Sample,
+    source: &str,
+    corpus: &Path,
+    taken: &mut HashSet<PathBuf>,
+) -> Result<(PathBuf, Vector)> {
+    let receiver = sample.receiver.as_ref();
+    let from = receiver.map_or_else(|| "an unidentified receiver".to_string(), |r| r.to_string());
+    let description = format!("Captured from {from} ({source}@{})", sample.frame.offset);
+    let mut vector = Vector::record(decoder, sample.frame.raw, &description)?;
+    vector.receiver = sample.receiver.clone();
+
+    let dir = corpus.join(&sample.name);
+    let slug = receiver.map_or_else(|| "unknown".to_string(), ReceiverInfo::slug);
+    let path = (1..)
+        .map(|n| dir.join(format!("{slug}-{n}.json")))
+        .find(|p| !p.exists() && !taken.contains(p))
+        .expect("some index is free");
+    taken.insert(path.clone());
+    Ok((path, vector))
+}
@@ -0,0 +1,187 @@
+//! Fault injection into UBX streams.
+//!
+//! [`FaultInjector`] corrupts frames at configurable [`FaultRates`], so a
+//! consumer's resync and error handling can be exercised against known
+//! damage: a flipped bit anywhere in the frame, a frame cut short, a frame
+//! sent twice, or random garbage before a frame. Each rate is the chance per
+//! frame, and faults combine independently. The generator is seeded, so a
+//! run can be repeated exactly. `ubx-replay` and `ubx-sim` take the rates
+//! as `--faults flip=0.01,truncate=0.005,duplicate=0.01,garbage=0.02`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::frame::frames;
+
+/// Longest run of garbage inserted before a frame by default.
+pub const DEFAULT_MAX_GARBAGE: usize = 32;
+
+/// Chance per frame of each fault, from 0 to 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultRates {
+    pub flip: f64,
+    pub truncate: f64,
+    pub duplicate: f64,
+    pub garbage: f64,
+}
+
+impl FaultRates {
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl FromStr for FaultRates {
+    type Err = String;
+
+    /// `flip=0.01,truncate=0.005,duplicate=0.01,garbage=0.02`; faults left
+    /// out don't happen.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rates = Self::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected FAULT=RATE, not '{part}'"))?;
+            let rate: f64 = value.parse().map_err(|_| format!("invalid rate '{value}' for {name}"))?;
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("rate {rate} for {name} is outside 0..1"));
+            }
+            match name.to_ascii_lowercase().as_str() {
+                "flip" | "bitflip" => rates.flip = rate,
+                "truncate" => rates.truncate = rate,
+                "duplicate" | "dup" => rates.duplicate = rate,
+                "garbage" => rates.garbage = rate,
+                other => {
+                    return Err(format!("unknown fault '{other}' (expected flip, truncate, duplicate or garbage)"));
+                }
+            }
+        }
+        Ok(rates)
+    }
+}
+
+impl fmt::Display for FaultRates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "flip={},truncate={},duplicate={},garbage={}",
+            self.flip, self.truncate, self.duplicate, self.garbage
+        )
+    }
+}
+
+/// Faults injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultCounts {
+    pub frames: usize,
+    pub flipped: usize,
+    pub truncated: usize,
+    pub duplicated: usize,
+    /// Runs of garbage inserted, and their total length.
+    pub garbage: usize,
+    pub garbage_bytes: usize,
+}
+
+impl fmt::Display for FaultCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} frames: {} bit flips, {} truncated, {} duplicated, {} garbage runs ({} bytes)",
+            self.frames, self.flipped, self.truncated, self.duplicated, self.garbage, self.garbage_bytes
+        )
+    }
+}
+
+/// xorshift64*: small, fast and reproducible from a seed.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Any seed, 0 included, gives a nonzero state.
+        Self((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in (0, 1).
+    pub(crate) fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..n`; `n` must be nonzero.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn chance(&mut self, rate: f64) -> bool {
+        rate > 0.0 && self.uniform() < rate
+    }
+}
+
+/// Corrupts frames at the configured rates.
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    pub rates: FaultRates,
+    /// Longest garbage run; runs are 1 to this many bytes.
+    pub max_garbage: usize,
+    pub counts: FaultCounts,
+    rng: Rng,
+}
+
+impl FaultInjector {
+    pub fn new(rates: FaultRates, seed: u64) -> Self {
+        Self {
+            rates,
+            max_garbage: DEFAULT_MAX_GARBAGE,
+            counts: FaultCounts::default(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Append `frame`, as it should go out after injection, to `out`:
+    /// perhaps preceded by garbage, damaged, cut short or repeated.
+    pub fn frame(&mut self, frame: &[u8], out: &mut Vec<u8>) {
+        self.counts.frames += 1;
+        if self.rng.chance(self.rates.garbage) && self.max_garbage > 0 {
+            let len = 1 + self.rng.below(self.max_garbage);
+            out.extend((0..len).map(|_| self.rng.next_u64() as u8));
+            self.counts.garbage += 1;
+            self.counts.garbage_bytes += len;
+        }
+        let mut bytes = frame.to_vec();
+        if self.rng.chance(self.rates.flip) && !bytes.is_empty() {
+            let bit = self.rng.below(bytes.len() * 8);
+            bytes[bit / 8] ^= 1 << (bit % 8);
+            self.counts.flipped += 1;
+        }
+        if self.rng.chance(self.rates.truncate) && bytes.len() > 1 {
+            bytes.truncate(1 + self.rng.below(bytes.len() - 1));
+            self.counts.truncated += 1;
+        }
+        out.extend_from_slice(&bytes);
+        if self.rng.chance(self.rates.duplicate) {
+            out.extend_from_slice(&bytes);
+            self.counts.duplicated += 1;
+        }
+    }
+
+    /// `data` with every frame passed through [`frame`](Self::frame);
+    /// bytes between frames are kept as they are.
+    pub fn buffer(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len());
+        let mut written = 0;
+        for frame in frames(data).flatten() {
+            out.extend_from_slice(&data[written..frame.offset]);
+            self.frame(frame.raw, &mut out);
+            written = frame.offset + frame.raw.len();
+        }
+        out.extend_from_slice(&data[written..]);
+        out
+    }
+}
@@ -0,0 +1,221 @@
+//! Jamming and spoofing indicators of a capture, as a timeline.
+//!
+//! Three messages report interference: MON-RF the jamming state of each RF
+//! block, SEC-SIG the receiver's jamming and spoofing verdicts, and MON-SPAN
+//! a spectrum of each RF block. [`InterferenceMonitor`] turns them into the
+//! changes of every indicator, with the capture offset and the iTOW of the
+//! latest navigation message for when they happened, and a summary of each
+//! spectrum: its peak, its median level and whether the peak stands out as
+//! a narrowband interferer. How long each state lasted follows from the
+//! counts of reports per state.
+//!
+//! Messages must be decoded with flag expansion enabled.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::decode::DecodedMessage;
+use crate::value::Value;
+
+/// Peak above the median, in dB, that marks a narrowband interferer.
+pub const NARROWBAND_MARGIN_DB: f64 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Indicator {
+    Jamming,
+    Spoofing,
+}
+
+impl Indicator {
+    pub fn label(self) -> &'static str {
+        match self {
+            Indicator::Jamming => "jamming",
+            Indicator::Spoofing => "spoofing",
+        }
+    }
+
+    /// Name of `state` as MON-RF and SEC-SIG define it.
+    pub fn state_label(self, state: u8) -> &'static str {
+        match (self, state) {
+            (_, 0) => "unknown",
+            (Indicator::Jamming, 1) => "ok",
+            (Indicator::Jamming, 2) => "warning",
+            (Indicator::Jamming, 3) => "critical",
+            (Indicator::Spoofing, 1) => "none",
+            (Indicator::Spoofing, 2) => "indicated",
+            (Indicator::Spoofing, 3) => "affirmed",
+            _ => "reserved",
+        }
+    }
+}
+
+/// A new value of one indicator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateChange {
+    /// Capture offset of the reporting frame.
+    pub offset: usize,
+    /// iTOW of the latest navigation message before it.
+    pub itow: Option<u32>,
+    /// `MON-RF block 0`, `SEC-SIG`.
+    pub source: String,
+    pub indicator: Indicator,
+    /// `None` for the first report.
+    pub from: Option<u8>,
+    pub to: u8,
+    pub label: &'static str,
+}
+
+/// One RF block of a MON-SPAN.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectrumSnapshot {
+    pub offset: usize,
+    pub itow: Option<u32>,
+    pub block: usize,
+    pub center_hz: u64,
+    pub span_hz: u64,
+    /// Programmable gain amplifier setting, dB; it drops as the receiver
+    /// compensates for strong in-band power.
+    pub pga_db: u64,
+    /// Frequency and level of the strongest bin.
+    pub peak_hz: f64,
+    pub peak_db: f64,
+    pub median_db: f64,
+    /// The peak is [`NARROWBAND_MARGIN_DB`] or more above the median.
+    pub narrowband: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InterferenceReport {
+    pub changes: Vec<StateChange>,
+    pub spectra: Vec<SpectrumSnapshot>,
+    /// Reports per source, indicator and state label.
+    pub counts: BTreeMap<String, BTreeMap<&'static str, usize>>,
+}
+
+impl InterferenceReport {
+    /// The state changes that entered a jamming warning, critical
+    /// jamming, or indicated or affirmed spoofing.
+    pub fn events(&self) -> impl Iterator<Item = &StateChange> {
+        self.changes.iter().filter(|c| c.to >= 2)
+    }
+}
+
+/// Builds an [`InterferenceReport`] from a decoded message stream.
+#[derive(Debug, Clone, Default)]
+pub struct InterferenceMonitor {
+    report: InterferenceReport,
+    states: BTreeMap<(String, Indicator), u8>,
+    itow: Option<u32>,
+}
+
+impl InterferenceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the message of the frame at `offset`; messages with an iTOW
+    /// only advance the clock.
+    pub fn push(&mut self, offset: usize, msg: &DecodedMessage) {
+        if let Some(itow) = msg.itow() {
+            self.itow = Some(itow);
+        }
+        if !msg.parsed {
+            return;
+        }
+        match msg.name.as_str() {
+            "UBX-MON-RF" => {
+                let blocks = msg.get("rfBlocks").and_then(Value::as_array).unwrap_or_default();
+                for (i, block) in blocks.iter().filter_map(Value::as_record).enumerate() {
+                    let id = block.get("blockId").and_then(Value::as_u64).unwrap_or(i as u64);
+                    // flags has no member names in every schema version.
+                    let state = match block.get("flags") {
+                        Some(Value::Record(flags)) => flags.get("jammingState").and_then(Value::as_u64),
+                        Some(raw) => raw.as_u64().map(|v| v & 0x03),
+                        None => None,
+                    };
+                    if let Some(state) = state {
+                        self.record(offset, format!("MON-RF block {id}"), Indicator::Jamming, state as u8);
+                    }
+                }
+            }
+            "UBX-SEC-SIG" => {
+                let state = |paths: [&str; 2]| paths.iter().find_map(|p| msg.get(p)?.as_u64());
+                if let Some(jamming) = state(["jamFlags.jamState", "jamFlags.jammingState"]) {
+                    self.record(offset, "SEC-SIG".to_string(), Indicator::Jamming, jamming as u8);
+                }
+                if let Some(spoofing) = state(["spfFlags.spfState", "spfFlags.spoofingState"]) {
+                    self.record(offset, "SEC-SIG".to_string(), Indicator::Spoofing, spoofing as u8);
+                }
+            }
+            "UBX-MON-SPAN" => {
+                let blocks = msg.get("rfBlockData").and_then(Value::as_array).unwrap_or_default();
+                for (block, data) in blocks.iter().filter_map(Value::as_record).enumerate() {
+                    let int = |name: &str| data.get(name).and_then(Value::as_u64).unwrap_or(0);
+                    let spectrum: Vec<f64> = data
+                        .get("spectrum")
+                        .and_then(Value::as_array)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(Value::as_f64)
+                        .collect();
+                    if let Some(snapshot) = snapshot(&spectrum, int("center"), int("span"), int("res")) {
+                        self.report.spectra.push(SpectrumSnapshot {
+                            offset,
+                            itow: self.itow,
+                            block,
+                            pga_db: int("pga"),
+                            ..snapshot
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record(&mut self, offset: usize, source: String, indicator: Indicator, state: u8) {
+        let label = indicator.state_label(state);
+        let counts = self.report.counts.entry(format!("{source} {}", indicator.label())).or_default();
+        *counts.entry(label).or_default() += 1;
+        let from = self.states.insert((source.clone(), indicator), state);
+        if from != Some(state) {
+            self.report.changes.push(StateChange {
+                offset,
+                itow: self.itow,
+                source,
+                indicator,
+                from,
+                to: state,
+                label,
+            });
+        }
+    }
+
+    pub fn finish(self) -> InterferenceReport {
+        self.report
+    }
+}
+
+/// Peak and median of a spectrum whose bins are `res` Hz apart around
+/// `center`.
+fn snapshot(spectrum: &[f64], center: u64, span: u64, res: u64) -> Option<SpectrumSnapshot> {
+    let (peak_bin, &peak_db) = spectrum.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+    let mut sorted = spectrum.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median_db = sorted[sorted.len() / 2];
+    let half = spectrum.len() as f64 / 2.0;
+    Some(SpectrumSnapshot {
+        offset: 0,
+        itow: None,
+        block: 0,
+        center_hz: center,
+        span_hz: span,
+        pga_db: 0,
+        peak_hz: center as f64 + (peak_bin as f64 - half) * res as f64,
+        peak_db,
+        median_db,
+        narrowband: peak_db - median_db >= NARROWBAND_MARGIN_DB,
+    })
+}
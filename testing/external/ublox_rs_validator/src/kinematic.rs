@@ -0,0 +1,151 @@
+//! Kinematic plausibility of successive NAV-PVT solutions.
+//!
+//! Between two epochs the receiver's position should move by about its
+//! mean reported velocity times the interval. A displacement that misses
+//! that prediction by more than the reported accuracies allow is a jump:
+//! multipath, spoofing, or a decoder reading the position or velocity
+//! wrong. Speed and acceleration are bounded too, by default at the 515 m/s
+//! export limit and 4 g. Only consecutive GNSS fixes with `gnssFixOK` set
+//! are compared, horizontally, and only across short intervals, since the
+//! prediction assumes constant acceleration.
+//!
+//! Messages must be decoded with scaling and flag expansion enabled.
+
+use crate::decode::DecodedMessage;
+use crate::geo::WGS84_A;
+use crate::semantic::{warn, Rule, Warning};
+use crate::time::itow_delta;
+use crate::track::FixQuality;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    /// Largest plausible ground speed, m/s.
+    pub max_speed: f64,
+    /// Largest plausible horizontal acceleration, m/s².
+    pub max_acceleration: f64,
+    /// Multiple of the combined accuracy estimates a displacement may miss
+    /// its prediction by.
+    pub sigmas: f64,
+    /// Added to the allowed miss, metres, for receivers that understate
+    /// their accuracy.
+    pub margin: f64,
+    /// Longest interval, seconds, over which epochs are compared.
+    pub max_interval: f64,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            max_speed: 515.0,
+            max_acceleration: 4.0 * 9.806_65,
+            sigmas: 3.0,
+            margin: 1.0,
+            max_interval: 5.0,
+        }
+    }
+}
+
+/// One usable solution, in metres and metres per second.
+#[derive(Debug, Clone, Copy)]
+struct State {
+    itow: u32,
+    lat: f64,
+    lon: f64,
+    vel_n: f64,
+    vel_e: f64,
+    h_acc: f64,
+    s_acc: f64,
+}
+
+impl State {
+    fn from_pvt(msg: &DecodedMessage) -> Option<Self> {
+        let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        let fix = FixQuality::from_pvt(int("fixType"), int("flags.diffSoln") == 1, int("flags.carrSoln"));
+        if int("flags.gnssFixOK") != 1 || !fix.has_position() || fix == FixQuality::DeadReckoning {
+            return None;
+        }
+        Some(Self {
+            itow: msg.itow()?,
+            lat: num("lat")?,
+            lon: num("lon")?,
+            vel_n: num("velN")? / 1_000.0,
+            vel_e: num("velE")? / 1_000.0,
+            h_acc: num("hAcc").unwrap_or(0.0) / 1_000.0,
+            s_acc: num("sAcc").unwrap_or(0.0) / 1_000.0,
+        })
+    }
+}
+
+/// Compares each NAV-PVT with the one before it.
+#[derive(Debug, Default)]
+pub struct KinematicChecker {
+    bounds: Bounds,
+    last: Option<State>,
+}
+
+impl KinematicChecker {
+    pub fn new(bounds: Bounds) -> Self {
+        Self { bounds, last: None }
+    }
+
+    /// Check a message against the previous solution; other messages and
+    /// solutions without a usable fix are skipped, the latter also ending
+    /// the comparison chain.
+    pub fn push(&mut self, msg: &DecodedMessage) -> Vec<Warning> {
+        if msg.name != "UBX-NAV-PVT" {
+            return Vec::new();
+        }
+        let state = State::from_pvt(msg);
+        let mut warnings = Vec::new();
+        if let Some(s) = state {
+            let speed = s.vel_n.hypot(s.vel_e);
+            if speed > self.bounds.max_speed {
+                let message = format!("iTOW {}: {speed:.1} m/s exceeds {} m/s", s.itow, self.bounds.max_speed);
+                warnings.push(warn(Rule::Kinematic, "velN/velE", message));
+            }
+        }
+        if let (Some(prev), Some(next)) = (self.last, state) {
+            warnings.extend(self.compare(&prev, &next));
+        }
+        self.last = state;
+        warnings
+    }
+
+    fn compare(&self, prev: &State, next: &State) -> Vec<Warning> {
+        let dt = itow_delta(prev.itow, next.itow) as f64 / 1_000.0;
+        if dt <= 0.0 || dt > self.bounds.max_interval {
+            return Vec::new();
+        }
+        let mut warnings = Vec::new();
+        let itow = next.itow;
+
+        let acceleration = (next.vel_n - prev.vel_n).hypot(next.vel_e - prev.vel_e) / dt;
+        if acceleration > self.bounds.max_acceleration {
+            let limit = self.bounds.max_acceleration;
+            let message = format!("iTOW {itow}: {acceleration:.1} m/s² over {dt} s exceeds {limit:.1}");
+            warnings.push(warn(Rule::Kinematic, "velN/velE", message));
+        }
+
+        // Local north/east displacement; a sphere is close enough over a few seconds.
+        let north = (next.lat - prev.lat).to_radians() * WGS84_A;
+        let mut d_lon = next.lon - prev.lon;
+        if d_lon.abs() > 180.0 {
+            d_lon -= 360.0f64.copysign(d_lon);
+        }
+        let east = d_lon.to_radians() * WGS84_A * ((prev.lat + next.lat) / 2.0).to_radians().cos();
+        let predicted = ((prev.vel_n + next.vel_n) / 2.0 * dt, (prev.vel_e + next.vel_e) / 2.0 * dt);
+        let miss = (north - predicted.0).hypot(east - predicted.1);
+        let allowed = self.bounds.sigmas * (prev.h_acc.hypot(next.h_acc) + prev.s_acc.max(next.s_acc) * dt)
+            + self.bounds.margin;
+        if miss > allowed {
+            let moved = north.hypot(east);
+            let message = format!(
+                "iTOW {itow}: moved {moved:.1} m in {dt} s, {miss:.1} m off the prediction (allowed {allowed:.1} m)"
+            );
+            warnings.push(warn(Rule::Kinematic, "lat/lon", message));
+        }
+        warnings
+    }
+}
@@ -0,0 +1,86 @@
+//! Schema-driven UBX codec.
+//!
+//! This is the Rust counterpart of `testing/lib`: messages are described by
+//! `data/messages/ubx_messages.json` and decoded at runtime, so every message
+//! added to the schema is immediately usable by the tools in `src/bin`.
+
+pub mod anonymize;
+pub mod arena;
+pub mod arrow;
+pub mod batch;
+pub mod broadcast;
+pub mod completeness;
+pub mod config;
+pub mod conformance;
+pub mod corpus;
+pub mod coverage;
+pub mod dashboard;
+pub mod decimate;
+pub mod decode;
+pub mod dedup;
+pub mod diff;
+pub mod differential;
+pub mod encode;
+pub mod epoch;
+pub mod error;
+pub mod examples;
+pub mod filter;
+pub mod frame;
+pub mod fusion;
+pub mod geo;
+pub mod gnss;
+pub mod gpsd;
+pub mod host_time;
+pub mod html;
+pub mod http;
+pub mod index;
+pub mod influx;
+pub mod ingest;
+pub mod inject;
+pub mod interference;
+pub mod kinematic;
+pub mod lint;
+pub mod mavlink;
+pub mod merge;
+pub mod migrate;
+pub mod minimize;
+pub mod mmap;
+pub mod mqtt;
+pub mod mga;
+pub mod nav;
+pub mod nav_beidou;
+pub mod nav_galileo;
+pub mod nav_glonass;
+pub mod ntrip;
+pub mod output;
+pub mod parquet;
+pub mod pcap;
+pub mod profile;
+pub mod receiver;
+pub mod registry;
+pub mod rinex;
+pub mod rtk;
+pub mod ros2;
+pub mod schema;
+pub mod schema_diff;
+pub mod semantic;
+pub mod sequence;
+pub mod shell;
+pub mod signal;
+pub mod sim;
+pub mod sos;
+pub mod sqlite;
+pub mod stats;
+pub mod time;
+pub mod timing;
+pub mod track;
+pub mod transact;
+pub mod ucenter;
+pub mod value;
+
+pub use decode::{DecodeOptions, DecodedMessage, Decoder};
+pub use encode::{EncodeOptions, Encoder};
+pub use error::{Error, FrameError, Result};
+pub use frame::{Frame, FrameIter, FrameRef};
+pub use schema::{MessageDef, Schema};
+pub use value::{Fields, Value};
@@ -0,0 +1,354 @@
+//! Consistency checks over the schema files.
+//!
+//! Errors are definitions the codec cannot use correctly (overlapping fields,
+//! lengths that can never match, dangling references); warnings are gaps in
+//! the extracted data that do not affect decoding, such as a scaled field
+//! without a unit.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use crate::config::{key_id_size, KeyType};
+use crate::schema::{
+    fields_extent, BaseOffset, BitDef, Count, DataType, FieldDef, MessageDef, PayloadDef, PayloadLength, Schema,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Message, enumeration or key the issue was found in.
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{level}: {}: {}", self.location, self.message)
+    }
+}
+
+#[derive(Default)]
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    fn error(&mut self, location: &str, message: String) {
+        self.push(Severity::Error, location, message);
+    }
+
+    fn warning(&mut self, location: &str, message: String) {
+        self.push(Severity::Warning, location, message);
+    }
+
+    fn push(&mut self, severity: Severity, location: &str, message: String) {
+        self.0.push(Issue {
+            severity,
+            location: location.to_string(),
+            message,
+        });
+    }
+}
+
+/// Check message definitions: duplicates, field layout, group length math,
+/// units on scaled fields and enumeration references.
+pub fn lint_messages(schema: &Schema, enumerations: Option<&Json>) -> Vec<Issue> {
+    let mut issues = Issues::default();
+    let enum_names: Option<HashSet<&str>> =
+        enumerations.and_then(Json::as_object).map(|e| e.keys().map(String::as_str).collect());
+
+    let mut names = HashSet::new();
+    let mut by_ids: HashMap<(u8, u8), Vec<&MessageDef>> = HashMap::new();
+    for msg in &schema.messages {
+        if !names.insert(msg.name.as_str()) {
+            issues.error(&msg.name, "duplicate message name".into());
+        }
+        by_ids.entry((msg.class_id, msg.message_id)).or_default().push(msg);
+
+        if let Some(payload) = &msg.payload {
+            lint_payload(&mut issues, &msg.name, payload, enum_names.as_ref());
+        }
+        for variant in &msg.variants {
+            let location = format!("{} ({})", msg.name, variant.name);
+            lint_payload(&mut issues, &location, &variant.payload, enum_names.as_ref());
+        }
+        if msg.payload.is_none() && msg.variants.is_empty() {
+            issues.error(&msg.name, "no payload definition".into());
+        }
+    }
+
+    let mut shared: Vec<_> = by_ids.into_iter().filter(|(_, m)| m.len() > 1).collect();
+    shared.sort_by_key(|(ids, _)| *ids);
+    for ((class, id), msgs) in shared {
+        for (i, a) in msgs.iter().enumerate() {
+            for b in &msgs[i + 1..] {
+                if !distinguishable(a, b) {
+                    issues.warning(
+                        &a.name,
+                        format!("shares class/ID 0x{class:02X} 0x{id:02X} with {} and cannot be told apart", b.name),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(enums) = enumerations.and_then(Json::as_object) {
+        for (name, def) in enums {
+            let location = format!("enumeration {name}");
+            let referenced = def.get("messages").and_then(Json::as_array).into_iter().flatten();
+            for msg_name in referenced.filter_map(Json::as_str) {
+                match schema.message_by_name(msg_name) {
+                    None => issues.error(&location, format!("references unknown message {msg_name}")),
+                    Some(msg) if !has_field(msg, name) => {
+                        issues.warning(&location, format!("{msg_name} has no field named '{name}'"))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+    issues.0
+}
+
+/// Messages sharing a class/ID pair must differ in a fixed field value, a
+/// variant discriminator or their payload length.
+fn distinguishable(a: &MessageDef, b: &MessageDef) -> bool {
+    if !a.variants.is_empty() || !b.variants.is_empty() {
+        return true;
+    }
+    let (Some(pa), Some(pb)) = (&a.payload, &b.payload) else {
+        return true;
+    };
+    let fixed = |p: &PayloadDef| -> HashMap<usize, i64> {
+        p.fields
+            .iter()
+            .filter_map(|f| Some((f.byte_offset?, f.fixed_value?)))
+            .collect()
+    };
+    let (fa, fb) = (fixed(pa), fixed(pb));
+    if fa.iter().any(|(offset, v)| fb.get(offset).is_some_and(|w| w != v)) {
+        return true;
+    }
+    match (&pa.length, &pb.length) {
+        (PayloadLength::Fixed(x), PayloadLength::Fixed(y)) => x != y,
+        (PayloadLength::Fixed(n), other) | (other, PayloadLength::Fixed(n)) => !other.accepts(*n),
+        _ => false,
+    }
+}
+
+fn has_field(msg: &MessageDef, name: &str) -> bool {
+    fn search(fields: &[FieldDef], name: &str) -> bool {
+        fields.iter().any(|f| {
+            f.name == name
+                || f.bits.iter().any(|b| b.name == name)
+                || matches!(&f.data_type, DataType::Group { fields, .. } if search(fields, name))
+        })
+    }
+    let payloads = msg.payload.iter().chain(msg.variants.iter().map(|v| &v.payload));
+    payloads
+        .into_iter()
+        .any(|p| search(&p.fields, name) || p.repeated_groups.iter().any(|g| search(&g.fields, name)))
+}
+
+fn lint_payload(issues: &mut Issues, location: &str, payload: &PayloadDef, enums: Option<&HashSet<&str>>) {
+    lint_fields(issues, location, &payload.fields, enums);
+    let extent = fields_extent(&payload.fields);
+
+    match payload.length {
+        PayloadLength::Fixed(len) if extent > len && payload.repeated_groups.is_empty() => {
+            issues.error(location, format!("fields span {extent} bytes but the payload is {len}"))
+        }
+        PayloadLength::Variable { base, .. } if extent > base && payload.repeated_groups.is_empty() => {
+            issues.warning(location, format!("fields span {extent} bytes, past the base length {base} (unmarked repeated block?)"))
+        }
+        _ => {}
+    }
+
+    for group in &payload.repeated_groups {
+        let group_location = format!("{location} group {}", group.name);
+        lint_fields(issues, &group_location, &group.fields, enums);
+        if group.group_size == 0 {
+            issues.error(&group_location, "group size is zero".into());
+            continue;
+        }
+        let inner = fields_extent(&group.fields);
+        if inner > group.group_size {
+            issues.error(
+                &group_location,
+                format!("fields span {inner} bytes but the group size is {}", group.group_size),
+            );
+        }
+        let start = match group.base_offset {
+            BaseOffset::Fixed(n) => n,
+            BaseOffset::AfterFields => extent,
+            BaseOffset::Dynamic { .. } => continue,
+        };
+        match (&payload.length, &group.count) {
+            (PayloadLength::Fixed(len), Count::Fixed(n)) if start + n * group.group_size > *len => issues.error(
+                &group_location,
+                format!(
+                    "{n} x {} bytes from offset {start} exceed the {len}-byte payload",
+                    group.group_size
+                ),
+            ),
+            (PayloadLength::Fixed(len), Count::Field { .. } | Count::Remaining)
+                if *len < start || (len - start) % group.group_size != 0 =>
+            {
+                issues.error(
+                    &group_location,
+                    format!(
+                        "payload length {len} is not {start} + a multiple of {}",
+                        group.group_size
+                    ),
+                )
+            }
+            (PayloadLength::Variable { base, .. }, _) if payload.repeated_groups.len() == 1 && start != *base => {
+                issues.error(&group_location, format!("group starts at {start} but the base length is {base}"))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn lint_fields(issues: &mut Issues, location: &str, fields: &[FieldDef], enums: Option<&HashSet<&str>>) {
+    let mut names = HashSet::new();
+    let mut previous: Option<(&FieldDef, usize)> = None;
+    for field in fields {
+        let field_location = format!("{location}.{}", field.name);
+        if !field.reserved && !field.name.is_empty() && !names.insert(field.name.as_str()) {
+            issues.error(location, format!("duplicate field '{}'", field.name));
+        }
+        if let DataType::Unknown(t) = &field.data_type {
+            issues.error(&field_location, format!("unrecognized data type {t}"));
+        }
+        if field.scale.is_some() && field.unit.is_none() {
+            issues.warning(&field_location, "scaled field has no unit".into());
+        }
+        if let (Some(name), Some(enums)) = (&field.enumeration, enums) {
+            if !enums.contains(name.as_str()) {
+                issues.error(&field_location, format!("undefined enumeration '{name}'"));
+            }
+        }
+        if let DataType::Group {
+            fields: inner,
+            element_size,
+            ..
+        } = &field.data_type
+        {
+            lint_fields(issues, &field_location, inner, enums);
+            if fields_extent(inner) > *element_size {
+                issues.error(
+                    &field_location,
+                    format!("members span {} bytes but the element size is {element_size}", fields_extent(inner)),
+                );
+            }
+        }
+        lint_bits(issues, &field_location, field);
+
+        let (Some(offset), Some(size)) = (field.byte_offset, field.data_type.fixed_size()) else {
+            continue;
+        };
+        if let Some((prev, end)) = previous {
+            if offset < end {
+                issues.error(
+                    location,
+                    format!("fields '{}' and '{}' overlap at offset {offset}", prev.name, field.name),
+                );
+            }
+        }
+        if previous.is_none_or(|(_, end)| offset + size > end) {
+            previous = Some((field, offset + size));
+        }
+    }
+}
+
+fn lint_bits(issues: &mut Issues, location: &str, field: &FieldDef) {
+    if field.bits.is_empty() {
+        return;
+    }
+    let width = field.data_type.base().map_or(64, |b| b.size() as u32 * 8);
+    let mut bits: Vec<&BitDef> = field.bits.iter().collect();
+    bits.sort_by_key(|b| b.bit_offset);
+    let mut end = 0;
+    for b in bits {
+        if b.bit_offset + b.bit_width > width {
+            issues.error(
+                location,
+                format!("bit member '{}' extends past the {width}-bit field", b.name),
+            );
+        }
+        if b.bit_offset < end && !b.reserved {
+            issues.error(location, format!("bit member '{}' overlaps the previous member", b.name));
+        }
+        end = end.max(b.bit_offset + b.bit_width);
+    }
+}
+
+/// Check the configuration key database: duplicate names and IDs, key ID
+/// size bits against the data type, and group IDs. Names can legitimately map
+/// to different IDs on different firmware, so duplicates are only warnings.
+pub fn lint_config_keys(json: &Json) -> Vec<Issue> {
+    let mut issues = Issues::default();
+    let groups: HashMap<&str, u32> = json
+        .get("groups")
+        .and_then(Json::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, g)| {
+            let id = g.get("group_id")?.as_str()?.trim_start_matches("0x");
+            Some((name.as_str(), u32::from_str_radix(id, 16).ok()?))
+        })
+        .collect();
+
+    let mut names = HashSet::new();
+    let mut ids: HashMap<u32, &str> = HashMap::new();
+    let keys = json.get("keys").and_then(Json::as_array).into_iter().flatten();
+    for key in keys {
+        let name = key.get("name").and_then(Json::as_str).unwrap_or("<unnamed>");
+        if !names.insert(name) {
+            issues.warning(name, "key name listed more than once".into());
+        }
+        let Some(key_id) = key
+            .get("key_id")
+            .and_then(Json::as_str)
+            .and_then(|s| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        else {
+            issues.error(name, "invalid key_id".into());
+            continue;
+        };
+        if let Some(other) = ids.insert(key_id, name) {
+            issues.warning(name, format!("key ID 0x{key_id:08X} also listed for {other}"));
+        }
+        match key.get("data_type").and_then(Json::as_str).and_then(KeyType::parse) {
+            None => issues.error(name, "invalid data_type".into()),
+            Some(t) => match key_id_size(key_id) {
+                None => issues.warning(name, format!("key ID 0x{key_id:08X} has no size bits")),
+                Some(size) if size != t.size() => issues.error(
+                    name,
+                    format!("key ID size {size} does not match data type {t:?}"),
+                ),
+                Some(_) => {}
+            },
+        }
+        let group = key.get("group").and_then(Json::as_str);
+        if let Some(&group_id) = group.and_then(|g| groups.get(g)) {
+            if (key_id >> 16) & 0xFF != group_id {
+                issues.warning(name, format!("key ID is not in group 0x{group_id:02X}"));
+            }
+        }
+        if key.get("scale").is_some() && key.get("unit").is_none() {
+            issues.warning(name, "scaled key has no unit".into());
+        }
+    }
+    issues.0
+}
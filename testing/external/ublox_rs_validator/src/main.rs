@@ -52,31 +52,31 @@ fn main() {
     match it.next() {
         Some(Ok(packet)) => {
             match packet {
-                PacketRef::NavPvt(msg) => {
+                PacketRef::NavPvt(_) => {
                     result.parsed = true;
                     result.message_class = Some(0x01);
                     result.message_id = Some(0x07);
                     result.payload_len = Some(92);
                 }
-                PacketRef::NavPosLlh(msg) => {
+                PacketRef::NavPosLlh(_) => {
                     result.parsed = true;
                     result.message_class = Some(0x01);
                     result.message_id = Some(0x02);
                     result.payload_len = Some(28);
                 }
-                PacketRef::NavStatus(msg) => {
+                PacketRef::NavStatus(_) => {
                     result.parsed = true;
                     result.message_class = Some(0x01);
                     result.message_id = Some(0x03);
                     result.payload_len = Some(16);
                 }
-                PacketRef::AckAck(msg) => {
+                PacketRef::AckAck(_) => {
                     result.parsed = true;
                     result.message_class = Some(0x05);
                     result.message_id = Some(0x01);
                     result.payload_len = Some(2);
                 }
-                PacketRef::AckNak(msg) => {
+                PacketRef::AckNak(_) => {
                     result.parsed = true;
                     result.message_class = Some(0x05);
                     result.message_id = Some(0x00);
@@ -0,0 +1,398 @@
+//! MAVLink GPS messages from decoded UBX navigation solutions.
+//!
+//! [`GpsBridge`] turns NAV-PVT into `GPS_RAW_INT` and NAV-SAT into
+//! `GPS_STATUS`, using the DOPs of the latest NAV-DOP for `eph`/`epv`, and
+//! sends a `HEARTBEAT` once a second so ground stations list the receiver
+//! as a GPS component. Packets are MAVLink 2 by default; MAVLink 1 packets
+//! leave out the `GPS_RAW_INT` extension fields (ellipsoid height and
+//! accuracies). Messages must be decoded with scaling and flag expansion
+//! enabled.
+
+use crate::decode::DecodedMessage;
+use crate::filter::utc_anchor;
+use crate::gnss::Gnss;
+use crate::time::itow_delta;
+use crate::track::FixQuality;
+use crate::value::{Fields, Value};
+
+pub const HEARTBEAT: u32 = 0;
+pub const GPS_RAW_INT: u32 = 24;
+pub const GPS_STATUS: u32 = 25;
+
+/// `MAV_TYPE_GPS`.
+pub const MAV_TYPE_GPS: u8 = 41;
+/// `MAV_AUTOPILOT_INVALID`: not a flight controller.
+pub const MAV_AUTOPILOT_INVALID: u8 = 8;
+/// `MAV_STATE_ACTIVE`.
+pub const MAV_STATE_ACTIVE: u8 = 4;
+/// `MAV_COMP_ID_GPS`.
+pub const MAV_COMP_ID_GPS: u8 = 220;
+
+// GPS_FIX_TYPE values.
+pub const GPS_FIX_TYPE_NO_FIX: u8 = 1;
+pub const GPS_FIX_TYPE_2D_FIX: u8 = 2;
+pub const GPS_FIX_TYPE_3D_FIX: u8 = 3;
+pub const GPS_FIX_TYPE_DGPS: u8 = 4;
+pub const GPS_FIX_TYPE_RTK_FLOAT: u8 = 5;
+pub const GPS_FIX_TYPE_RTK_FIXED: u8 = 6;
+
+/// Satellites reported per `GPS_STATUS`.
+pub const GPS_STATUS_SATELLITES: usize = 20;
+
+/// `(CRC_EXTRA, MAVLink 1 payload length, full payload length)` of the
+/// messages this module knows.
+fn message_info(msg_id: u32) -> Option<(u8, usize, usize)> {
+    match msg_id {
+        HEARTBEAT => Some((50, 9, 9)),
+        GPS_RAW_INT => Some((24, 30, 52)),
+        GPS_STATUS => Some((23, 101, 101)),
+        _ => None,
+    }
+}
+
+/// The X.25 CRC MAVLink uses, continuing from `crc`.
+pub fn crc_x25(data: &[u8], mut crc: u16) -> u16 {
+    for &byte in data {
+        let mut tmp = byte ^ (crc as u8);
+        tmp ^= tmp << 4;
+        crc = (crc >> 8) ^ (u16::from(tmp) << 8) ^ (u16::from(tmp) << 3) ^ (u16::from(tmp) >> 4);
+    }
+    crc
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Version {
+    V1,
+    #[default]
+    V2,
+}
+
+/// Frames payloads as MAVLink packets from one system and component.
+#[derive(Debug, Clone)]
+pub struct PacketWriter {
+    pub version: Version,
+    pub system_id: u8,
+    pub component_id: u8,
+    seq: u8,
+}
+
+impl PacketWriter {
+    pub fn new(version: Version, system_id: u8, component_id: u8) -> Self {
+        Self {
+            version,
+            system_id,
+            component_id,
+            seq: 0,
+        }
+    }
+
+    /// A packet for a full-length payload of a known message.
+    pub fn packet(&mut self, msg_id: u32, payload: &[u8]) -> Vec<u8> {
+        let (crc_extra, v1_len, _) = message_info(msg_id).expect("packet() takes known message IDs");
+        let mut packet = match self.version {
+            Version::V1 => {
+                let payload = &payload[..v1_len.min(payload.len())];
+                let mut packet = vec![0xFE, payload.len() as u8, self.seq, self.system_id, self.component_id];
+                packet.push(msg_id as u8);
+                packet.extend_from_slice(payload);
+                packet
+            }
+            Version::V2 => {
+                // MAVLink 2 drops trailing zero bytes, keeping at least one.
+                let len = payload.iter().rposition(|&b| b != 0).map_or(1, |i| i + 1);
+                let mut packet = vec![0xFD, len as u8, 0, 0, self.seq, self.system_id, self.component_id];
+                packet.extend_from_slice(&msg_id.to_le_bytes()[..3]);
+                packet.extend_from_slice(&payload[..len]);
+                packet
+            }
+        };
+        let crc = crc_x25(&[crc_extra], crc_x25(&packet[1..], 0xFFFF));
+        packet.extend_from_slice(&crc.to_le_bytes());
+        self.seq = self.seq.wrapping_add(1);
+        packet
+    }
+}
+
+/// A received packet, with its payload zero-extended to full length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub seq: u8,
+    pub system_id: u8,
+    pub component_id: u8,
+    pub msg_id: u32,
+    pub payload: Vec<u8>,
+}
+
+/// Parse one packet of a known message from the start of `data`, checking
+/// its CRC. Returns the packet and its length in bytes.
+pub fn parse_packet(data: &[u8]) -> Option<(Packet, usize)> {
+    let (header_len, len) = match data.first()? {
+        0xFE => (6, usize::from(*data.get(1)?)),
+        0xFD => (10, usize::from(*data.get(1)?)),
+        _ => return None,
+    };
+    let total = header_len + len + 2;
+    let packet = data.get(..total)?;
+    let (seq, system_id, component_id, msg_id) = if header_len == 6 {
+        (packet[2], packet[3], packet[4], u32::from(packet[5]))
+    } else {
+        let id = u32::from_le_bytes([packet[7], packet[8], packet[9], 0]);
+        (packet[4], packet[5], packet[6], id)
+    };
+    let (crc_extra, _, full_len) = message_info(msg_id)?;
+    let crc = crc_x25(&[crc_extra], crc_x25(&packet[1..total - 2], 0xFFFF));
+    if crc.to_le_bytes() != packet[total - 2..] {
+        return None;
+    }
+    let mut payload = packet[header_len..total - 2].to_vec();
+    payload.resize(full_len.max(payload.len()), 0);
+    Some((Packet { seq, system_id, component_id, msg_id, payload }, total))
+}
+
+/// `GPS_RAW_INT` (#24).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpsRawInt {
+    /// UTC, microseconds since the Unix epoch; 0 when unknown.
+    pub time_usec: u64,
+    /// degE7.
+    pub lat: i32,
+    pub lon: i32,
+    /// Above mean sea level, mm.
+    pub alt: i32,
+    /// HDOP * 100, `u16::MAX` when unknown.
+    pub eph: u16,
+    /// VDOP * 100, `u16::MAX` when unknown.
+    pub epv: u16,
+    /// Ground speed, cm/s.
+    pub vel: u16,
+    /// Course over ground, cdeg; `u16::MAX` when unknown.
+    pub cog: u16,
+    pub fix_type: u8,
+    pub satellites_visible: u8,
+    /// Above the ellipsoid, mm.
+    pub alt_ellipsoid: i32,
+    /// mm.
+    pub h_acc: u32,
+    pub v_acc: u32,
+    /// mm/s.
+    pub vel_acc: u32,
+    /// degE5.
+    pub hdg_acc: u32,
+    /// Heading of vehicle, cdeg with north as 36000; 0 when unknown.
+    pub yaw: u16,
+}
+
+impl GpsRawInt {
+    pub fn payload(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(52);
+        out.extend_from_slice(&self.time_usec.to_le_bytes());
+        for v in [self.lat, self.lon, self.alt] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [self.eph, self.epv, self.vel, self.cog] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&[self.fix_type, self.satellites_visible]);
+        out.extend_from_slice(&self.alt_ellipsoid.to_le_bytes());
+        for v in [self.h_acc, self.v_acc, self.vel_acc, self.hdg_acc] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&self.yaw.to_le_bytes());
+        out
+    }
+
+    /// Read a full-length payload, as returned by [`parse_packet`].
+    pub fn from_payload(p: &[u8]) -> Option<Self> {
+        let p = p.get(..52)?;
+        let u16_at = |i: usize| u16::from_le_bytes([p[i], p[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([p[i], p[i + 1], p[i + 2], p[i + 3]]);
+        Some(Self {
+            time_usec: u64::from_le_bytes(p[..8].try_into().ok()?),
+            lat: u32_at(8) as i32,
+            lon: u32_at(12) as i32,
+            alt: u32_at(16) as i32,
+            eph: u16_at(20),
+            epv: u16_at(22),
+            vel: u16_at(24),
+            cog: u16_at(26),
+            fix_type: p[28],
+            satellites_visible: p[29],
+            alt_ellipsoid: u32_at(30) as i32,
+            h_acc: u32_at(34),
+            v_acc: u32_at(38),
+            vel_acc: u32_at(42),
+            hdg_acc: u32_at(46),
+            yaw: u16_at(50),
+        })
+    }
+}
+
+/// `GPS_STATUS` (#25), for up to [`GPS_STATUS_SATELLITES`] satellites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpsStatus {
+    pub satellites_visible: u8,
+    pub prn: [u8; GPS_STATUS_SATELLITES],
+    /// 1 if used in the solution.
+    pub used: [u8; GPS_STATUS_SATELLITES],
+    /// Degrees.
+    pub elevation: [u8; GPS_STATUS_SATELLITES],
+    /// 0-255 for 0-360 degrees.
+    pub azimuth: [u8; GPS_STATUS_SATELLITES],
+    /// C/N0, dBHz.
+    pub snr: [u8; GPS_STATUS_SATELLITES],
+}
+
+impl GpsStatus {
+    pub fn payload(&self) -> Vec<u8> {
+        let mut out = vec![self.satellites_visible];
+        for array in [&self.prn, &self.used, &self.elevation, &self.azimuth, &self.snr] {
+            out.extend_from_slice(array);
+        }
+        out
+    }
+}
+
+/// PRN for `GPS_STATUS`, in NMEA numbering where it fits a byte: GPS and
+/// SBAS as is, GLONASS 65-96, QZSS 193-202; other systems by satellite ID.
+pub fn mavlink_prn(gnss_id: u8, sv_id: u8) -> u8 {
+    match Gnss::from_id(gnss_id) {
+        Some(Gnss::Glonass) if sv_id <= 32 => 64 + sv_id,
+        Some(Gnss::Qzss) if sv_id <= 10 => 192 + sv_id,
+        _ => sv_id,
+    }
+}
+
+/// `GPS_FIX_TYPE` for a NAV-PVT solution.
+pub fn fix_type(fix_type: u64, fix_ok: bool, diff_soln: bool, carr_soln: u64) -> u8 {
+    if !fix_ok {
+        return GPS_FIX_TYPE_NO_FIX;
+    }
+    match FixQuality::from_pvt(fix_type, diff_soln, carr_soln) {
+        FixQuality::Fix2D => GPS_FIX_TYPE_2D_FIX,
+        FixQuality::Fix3D => GPS_FIX_TYPE_3D_FIX,
+        FixQuality::Dgnss => GPS_FIX_TYPE_DGPS,
+        FixQuality::RtkFloat => GPS_FIX_TYPE_RTK_FLOAT,
+        FixQuality::RtkFixed => GPS_FIX_TYPE_RTK_FIXED,
+        FixQuality::NoFix | FixQuality::DeadReckoning | FixQuality::TimeOnly => GPS_FIX_TYPE_NO_FIX,
+    }
+}
+
+/// Translates a decoded message stream into MAVLink packets.
+#[derive(Debug, Clone)]
+pub struct GpsBridge {
+    writer: PacketWriter,
+    /// HDOP and VDOP of the latest NAV-DOP.
+    dop: Option<(f64, f64)>,
+    /// iTOW of the latest heartbeat.
+    heartbeat: Option<u32>,
+}
+
+impl GpsBridge {
+    pub fn new(writer: PacketWriter) -> Self {
+        Self {
+            writer,
+            dop: None,
+            heartbeat: None,
+        }
+    }
+
+    /// The packets for `msg`: a `GPS_RAW_INT` for NAV-PVT, preceded by a
+    /// heartbeat once a second, and a `GPS_STATUS` for NAV-SAT.
+    pub fn translate(&mut self, msg: &DecodedMessage) -> Vec<Vec<u8>> {
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => {
+                let mut packets = Vec::new();
+                if let Some(itow) = msg.itow() {
+                    if self.heartbeat.is_none_or(|last| itow_delta(last, itow).abs() >= 1_000) {
+                        self.heartbeat = Some(itow);
+                        packets.push(self.heartbeat());
+                    }
+                }
+                let payload = self.gps_raw_int(msg).payload();
+                packets.push(self.writer.packet(GPS_RAW_INT, &payload));
+                packets
+            }
+            "UBX-NAV-SAT" => {
+                let payload = gps_status(msg).payload();
+                vec![self.writer.packet(GPS_STATUS, &payload)]
+            }
+            "UBX-NAV-DOP" => {
+                let dop = |name: &str| msg.get(name).and_then(Value::as_f64);
+                self.dop = dop("hDOP").zip(dop("vDOP"));
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn heartbeat(&mut self) -> Vec<u8> {
+        let payload = [0, 0, 0, 0, MAV_TYPE_GPS, MAV_AUTOPILOT_INVALID, 0, MAV_STATE_ACTIVE, 3];
+        self.writer.packet(HEARTBEAT, &payload)
+    }
+
+    pub fn gps_raw_int(&self, msg: &DecodedMessage) -> GpsRawInt {
+        let num = |name: &str| msg.get(name).and_then(Value::as_f64).unwrap_or(0.0);
+        let flag = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        let fix_type = fix_type(
+            flag("fixType"),
+            flag("flags.gnssFixOK") == 1,
+            flag("flags.diffSoln") == 1,
+            flag("flags.carrSoln"),
+        );
+        let dop = |v: f64| (v * 100.0).round().min(f64::from(u16::MAX - 1)) as u16;
+        let (eph, epv) = self.dop.map_or((u16::MAX, u16::MAX), |(h, v)| (dop(h), dop(v)));
+        let moving = fix_type >= GPS_FIX_TYPE_2D_FIX;
+        let yaw = if flag("flags.headVehValid") == 1 {
+            match (num("headVeh") * 100.0).round().rem_euclid(36_000.0) as u16 {
+                0 => 36_000,
+                cdeg => cdeg,
+            }
+        } else {
+            0
+        };
+        GpsRawInt {
+            time_usec: utc_anchor(msg).map_or(0, |(_, unix)| (unix * 1e6).round() as u64),
+            lat: (num("lat") * 1e7).round() as i32,
+            lon: (num("lon") * 1e7).round() as i32,
+            alt: num("hMSL") as i32,
+            eph,
+            epv,
+            vel: (num("gSpeed") / 10.0).round().clamp(0.0, f64::from(u16::MAX - 1)) as u16,
+            cog: if moving { (num("headMot") * 100.0).round().rem_euclid(36_000.0) as u16 } else { u16::MAX },
+            fix_type,
+            satellites_visible: flag("numSV").min(254) as u8,
+            alt_ellipsoid: num("height") as i32,
+            h_acc: num("hAcc") as u32,
+            v_acc: num("vAcc") as u32,
+            vel_acc: num("sAcc") as u32,
+            hdg_acc: (num("headAcc") * 1e5).round() as u32,
+            yaw,
+        }
+    }
+}
+
+/// `GPS_STATUS` for a NAV-SAT, with the first satellites it lists.
+pub fn gps_status(msg: &DecodedMessage) -> GpsStatus {
+    let satellites: Vec<&Fields> = msg
+        .get("satellites")
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Value::as_record)
+        .collect();
+    let mut status = GpsStatus {
+        satellites_visible: satellites.len().min(255) as u8,
+        ..GpsStatus::default()
+    };
+    for (i, sat) in satellites.iter().take(GPS_STATUS_SATELLITES).enumerate() {
+        let int = |name: &str| sat.get_path(name).and_then(Value::as_i64).unwrap_or(0);
+        // svUsed is bit 3 of flags when the schema has no member names.
+        let used = sat.get_path("flags.svUsed").map_or(int("flags") & 0x08 != 0, |v| v.as_u64() == Some(1));
+        status.prn[i] = mavlink_prn(int("gnssId") as u8, int("svId") as u8);
+        status.used[i] = u8::from(used);
+        status.elevation[i] = int("elev").clamp(0, 90) as u8;
+        status.azimuth[i] = (int("azim").rem_euclid(360) as f64 * 255.0 / 360.0).round() as u8;
+        status.snr[i] = int("cno").clamp(0, 255) as u8;
+    }
+    status
+}
@@ -0,0 +1,125 @@
+//! Merge several logs into one time-ordered stream, remembering where each
+//! frame came from.
+//!
+//! Typical inputs are two ports of one receiver, or the base and rover of an
+//! RTK session. Frames are ordered either by iTOW, which every receiver
+//! shares because it is GPS time, or by the host receive times that
+//! `ubx-capture --timestamps` records ([`crate::host_time`]). Frames without
+//! a time of their own (ACKs, MON, RXM-RTCM, ...) take the time of the frame
+//! before them in the same log, so they stay with their epoch. Within a log
+//! the original order is always kept; ties between logs go to the earlier
+//! source.
+
+use std::str::FromStr;
+
+use crate::decode::Decoder;
+use crate::error::{Error, Result};
+use crate::frame::{frames, FrameRef};
+use crate::host_time::HostTimes;
+use crate::schema::Schema;
+use crate::time::itow_delta;
+
+/// What to order merged frames by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// iTOW, unwrapped across week rollovers.
+    Itow,
+    /// Host receive time from a `ubx-capture --timestamps` CSV.
+    Host,
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "itow" => Ok(Order::Itow),
+            "host" => Ok(Order::Host),
+            other => Err(format!("unknown order '{other}' (expected itow or host)")),
+        }
+    }
+}
+
+/// One input log.
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// How frames from this log are annotated, e.g. `base`.
+    pub label: String,
+    pub data: Vec<u8>,
+    /// Needed for [`Order::Host`].
+    pub host_times: Option<HostTimes>,
+}
+
+/// A frame of the merged stream.
+#[derive(Debug, Clone, Copy)]
+pub struct MergedFrame<'a> {
+    /// Index of the source in the slice passed to [`merge`].
+    pub source: usize,
+    /// Ordering time in seconds: unwrapped iTOW or Unix host time. `None`
+    /// only for a log in which no frame has a time.
+    pub time: Option<f64>,
+    pub frame: FrameRef<'a>,
+}
+
+/// Times of every frame in one log, carried forward from the last timed frame
+/// and back-filled for the frames before the first.
+fn frame_times<'a>(decoder: &Decoder, source: &'a Source, order: Order) -> Result<Vec<(Option<f64>, FrameRef<'a>)>> {
+    let host_times = match (order, &source.host_times) {
+        (Order::Host, None) => {
+            return Err(Error::Config(format!("{}: host ordering needs a timestamps CSV", source.label)));
+        }
+        (_, times) => times.as_ref(),
+    };
+    let mut out = Vec::new();
+    let mut itow: Option<(u32, f64)> = None;
+    let mut last = None;
+    for frame in frames(&source.data).flatten() {
+        let time = match order {
+            Order::Host => host_times.and_then(|t| t.at(frame.offset)),
+            Order::Itow => decoder.decode_frame(&frame).itow().map(|now| {
+                let ms = itow.map_or(f64::from(now), |(prev, ms)| ms + itow_delta(prev, now) as f64);
+                itow = Some((now, ms));
+                ms / 1_000.0
+            }),
+        };
+        last = time.or(last);
+        out.push((last, frame));
+    }
+    let first = out.iter().find_map(|(t, _)| *t);
+    for (t, _) in out.iter_mut().take_while(|(t, _)| t.is_none()) {
+        *t = first;
+    }
+    Ok(out)
+}
+
+/// Merge `sources` into one stream ordered by `order`.
+pub fn merge<'a>(schema: &Schema, sources: &'a [Source], order: Order) -> Result<Vec<MergedFrame<'a>>> {
+    let decoder = Decoder::new(schema);
+    let timed = sources
+        .iter()
+        .map(|s| frame_times(&decoder, s, order))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut next = vec![0; timed.len()];
+    let mut merged = Vec::with_capacity(timed.iter().map(Vec::len).sum());
+    loop {
+        // Earliest head; an untimed log sorts last. Strict `<` keeps ties on the earlier source.
+        let mut best: Option<(usize, f64)> = None;
+        for (source, frames) in timed.iter().enumerate() {
+            let Some((time, _)) = frames.get(next[source]) else {
+                continue;
+            };
+            let key = time.unwrap_or(f64::INFINITY);
+            if best.is_none_or(|(_, t)| key < t) {
+                best = Some((source, key));
+            }
+        }
+        let Some((source, _)) = best else {
+            break;
+        };
+        let (time, frame) = timed[source][next[source]];
+        next[source] += 1;
+        merged.push(MergedFrame { source, time, frame });
+    }
+    Ok(merged)
+}
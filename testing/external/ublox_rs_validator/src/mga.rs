@@ -0,0 +1,151 @@
+//! AssistNow Offline data: split an `.mgaoffline` file into its MGA frames,
+//! select them by date and constellation, and match MGA-ACK replies.
+//!
+//! The file is a plain concatenation of UBX-MGA-ANO frames (and, for some
+//! services, other MGA messages). Each ANO frame carries one satellite's
+//! orbit for one day; uploading only the days around the current date keeps
+//! the transfer short. The receiver acknowledges every MGA message with an
+//! MGA-ACK when aiding acknowledgment is enabled (`CFG-NAVSPG-ACKAIDING` on
+//! generation 9+, `CFG-NAVX5` on M8).
+
+use crate::decode::Decoder;
+use crate::error::{Error, FrameError, Result};
+use crate::frame::{frames, Frame};
+use crate::gnss::Gnss;
+use crate::schema::Schema;
+use crate::time::DateTime;
+use crate::value::Value;
+
+/// UBX class of the MGA messages.
+pub const MGA_CLASS: u8 = 0x13;
+
+/// One MGA frame from an assistance file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssistFrame {
+    pub frame: Frame,
+    /// Short message name, e.g. `MGA-ANO`.
+    pub name: String,
+    pub gnss_id: Option<u8>,
+    pub sv_id: Option<u8>,
+    /// Day the data is valid for (MGA-ANO only), at 00:00.
+    pub date: Option<DateTime>,
+}
+
+impl AssistFrame {
+    pub fn gnss(&self) -> Option<Gnss> {
+        self.gnss_id.and_then(Gnss::from_id)
+    }
+}
+
+/// Split assistance data into MGA frames, decoding each through the schema.
+///
+/// A checksum failure is an error; the file is corrupt and uploading the
+/// rest would leave gaps. Frames outside the MGA class are skipped.
+pub fn split(schema: &Schema, data: &[u8]) -> Result<Vec<AssistFrame>> {
+    let decoder = Decoder::new(schema);
+    let mut out = Vec::new();
+    for frame in frames(data) {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(e @ FrameError::Checksum { .. }) => return Err(e.into()),
+            Err(_) => continue,
+        };
+        if frame.class != MGA_CLASS {
+            continue;
+        }
+        let msg = decoder.decode_frame(&frame);
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64);
+        let date = match (msg.name.as_str(), int("year"), int("month"), int("day")) {
+            ("UBX-MGA-ANO", Some(year), Some(month), Some(day)) => Some(DateTime {
+                year: 2000 + year as i32,
+                month: month as u8,
+                day: day as u8,
+                hour: 0,
+                minute: 0,
+                second: 0.0,
+            }),
+            _ => None,
+        };
+        out.push(AssistFrame {
+            frame: frame.to_owned(),
+            name: msg.short_name().to_string(),
+            gnss_id: int("gnssId").map(|v| v as u8),
+            sv_id: int("svId").map(|v| v as u8),
+            date,
+        });
+    }
+    if out.is_empty() {
+        return Err(Error::Config("no MGA frames in the assistance data".into()));
+    }
+    Ok(out)
+}
+
+/// Which frames of an assistance file to upload.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// First day to keep, with the number of days from it; all days if `None`.
+    pub days: Option<(DateTime, u32)>,
+    /// Constellations to keep; all if empty.
+    pub gnss: Vec<Gnss>,
+}
+
+impl Selection {
+    /// Frames without a date or constellation (e.g. MGA-INI) always match.
+    pub fn matches(&self, frame: &AssistFrame) -> bool {
+        let in_days = match (self.days, frame.date) {
+            (Some((first, days)), Some(date)) => {
+                let offset = ((date.to_unix() - first.to_unix()) / 86_400.0).round();
+                offset >= 0.0 && offset < f64::from(days)
+            }
+            _ => true,
+        };
+        let in_gnss = self.gnss.is_empty() || frame.gnss().is_none_or(|g| self.gnss.contains(&g));
+        in_days && in_gnss
+    }
+}
+
+/// A decoded UBX-MGA-ACK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MgaAck {
+    pub accepted: bool,
+    pub info_code: u8,
+    /// Message ID of the acknowledged MGA message.
+    pub msg_id: u8,
+    /// First four payload bytes of the acknowledged message.
+    pub payload_start: [u8; 4],
+}
+
+impl MgaAck {
+    /// Whether this acknowledges `sent`.
+    pub fn acknowledges(&self, sent: &Frame) -> bool {
+        sent.class == MGA_CLASS && sent.id == self.msg_id && sent.payload.get(..4) == Some(&self.payload_start[..])
+    }
+
+    /// Meaning of `info_code`, from the interface description.
+    pub fn info(&self) -> &'static str {
+        match self.info_code {
+            0 => "accepted",
+            1 => "receiver does not know the time (send MGA-INI-TIME first)",
+            2 => "message version not supported",
+            3 => "message size does not match the version",
+            4 => "data could not be stored to the database",
+            5 => "receiver not ready to use the data",
+            6 => "message type unknown",
+            _ => "unknown reason",
+        }
+    }
+}
+
+/// Parse an MGA-ACK frame.
+pub fn parse_mga_ack(frame: &Frame) -> Option<MgaAck> {
+    if frame.class != MGA_CLASS || frame.id != 0x60 || frame.payload.len() != 8 {
+        return None;
+    }
+    let p = &frame.payload;
+    Some(MgaAck {
+        accepted: p[0] == 1,
+        info_code: p[2],
+        msg_id: p[3],
+        payload_start: [p[4], p[5], p[6], p[7]],
+    })
+}
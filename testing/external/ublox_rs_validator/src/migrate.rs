@@ -0,0 +1,216 @@
+//! Migration of legacy configuration messages to configuration keys.
+//!
+//! Receivers before generation 9 are configured with one message per
+//! subsystem (CFG-PRT, CFG-MSG, CFG-RATE, CFG-NAV5, ...); later ones with
+//! CFG-VALSET key/value pairs. The `config_migration` metadata of a message
+//! definition says which key each field's raw value moves to, for which
+//! port, and under which mask bit. [`Migrator`] applies it to frames from a
+//! capture of poll responses or from a saved u-center configuration
+//! ([`read_config_frames`]), and collects the resulting key set; a key
+//! set again later in the input replaces the earlier value.
+
+use serde_json::Value as Json;
+
+use crate::config::{ConfigKeys, KeyValue};
+use crate::decode::{DecodeOptions, Decoder};
+use crate::error::{Error, Result};
+use crate::frame::{frames, Frame};
+use crate::schema::{fields_extent, MigrationKey, Schema};
+use crate::value::{Fields, Value};
+
+/// The key set migrated from legacy messages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Migration {
+    /// In the order each key was first set.
+    pub values: Vec<KeyValue>,
+    /// Frames of messages with a mapping.
+    pub frames: usize,
+    /// Settings that have no key, or a value the key can't take.
+    pub skipped: Vec<String>,
+}
+
+/// Collects key/value pairs from legacy CFG frames.
+pub struct Migrator<'a> {
+    decoder: Decoder<'a>,
+    keys: &'a ConfigKeys,
+    migration: Migration,
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(schema: &'a Schema, keys: &'a ConfigKeys) -> Self {
+        let options = DecodeOptions { scale: false, expand_flags: true, include_reserved: false };
+        Self {
+            decoder: Decoder::with_options(schema, options),
+            keys,
+            migration: Migration::default(),
+        }
+    }
+
+    /// Migrate one frame; returns the number of keys it set. Frames of
+    /// messages without a mapping set none.
+    pub fn push(&mut self, frame: &Frame) -> usize {
+        let schema = self.decoder.schema();
+        let Some(msg) = schema.message_for_payload(frame.class, frame.id, &frame.payload) else {
+            return 0;
+        };
+        let (Some(migration), Some((def, _))) = (&msg.config_migration, msg.payload_for(&frame.payload)) else {
+            return 0;
+        };
+        self.migration.frames += 1;
+        let name = msg.short_name().to_string();
+        if frame.payload.len() < fields_extent(&def.fields) {
+            // Polls and the short CFG-MSG form for the current port.
+            self.skip(format!("{name}: {}-byte payload is a poll or short form", frame.payload.len()));
+            return 0;
+        }
+        let fields = self.decoder.decode(frame.class, frame.id, &frame.payload).fields;
+
+        let message = match &migration.message {
+            Some((class, id)) => {
+                let (class, id) = (int_at(&fields, class).unwrap_or(0), int_at(&fields, id).unwrap_or(0));
+                let target = u8::try_from(class)
+                    .ok()
+                    .zip(u8::try_from(id).ok())
+                    .and_then(|(class, id)| schema.messages_by_ids(class, id).next());
+                match target {
+                    Some(target) => Some(target.name.replace('-', "_")),
+                    None => {
+                        self.skip(format!("{name}: message {class:02X}-{id:02X} is not in the schema"));
+                        return 0;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut set = 0;
+        for key in &migration.keys {
+            if key.when.as_deref().is_some_and(|flag| int_at(&fields, flag).unwrap_or(0) == 0) {
+                continue;
+            }
+            let Some(value) = fields.get_path(&key.field) else {
+                continue;
+            };
+            let port = migration.port.as_ref();
+            match (port, value.as_array()) {
+                // Fields indexed by port, as CFG-MSG's rates.
+                (Some(port), Some(items)) if port.field.is_none() => {
+                    for (i, item) in items.iter().enumerate() {
+                        if let Some(Some(port)) = port.names.get(i) {
+                            set += self.migrate(&name, key, item, Some(port), message.as_deref());
+                        }
+                    }
+                }
+                (Some(port), _) => {
+                    let number = port.field.as_deref().and_then(|f| int_at(&fields, f));
+                    match number.and_then(|n| port.names.get(usize::try_from(n).ok()?)?.as_deref()) {
+                        Some(port) => set += self.migrate(&name, key, value, Some(port), message.as_deref()),
+                        None => {
+                            self.skip(format!("{name}: port {} has no configuration keys", number.unwrap_or(-1)));
+                            break;
+                        }
+                    }
+                }
+                (None, _) => set += self.migrate(&name, key, value, None, message.as_deref()),
+            }
+        }
+        set
+    }
+
+    /// Set the key for one field value; returns 1 if it was set.
+    fn migrate(
+        &mut self,
+        name: &str,
+        key: &MigrationKey,
+        value: &Value,
+        port: Option<&str>,
+        message: Option<&str>,
+    ) -> usize {
+        if port.is_some_and(|port| !key.ports.is_empty() && !key.ports.iter().any(|p| p == port)) {
+            return 0;
+        }
+        let key_name = key
+            .key
+            .replace("{port}", port.unwrap_or_default())
+            .replace("{message}", message.unwrap_or_default());
+        let field = &key.field;
+        let Some(raw) = value.as_i64() else {
+            self.skip(format!("{name}.{field}: {value} is not an integer"));
+            return 0;
+        };
+        if self.keys.get(&key_name).is_none() {
+            self.skip(format!("{name}.{field}: no key {key_name}"));
+            return 0;
+        }
+        let value = if key.values.is_empty() {
+            Json::from(raw)
+        } else {
+            match key.values.iter().find(|(legacy, _)| *legacy == raw) {
+                Some((_, value)) => value.clone(),
+                None => {
+                    self.skip(format!("{name}.{field}: {raw} has no equivalent for {key_name}"));
+                    return 0;
+                }
+            }
+        };
+        match self.keys.key_value(&key_name, &value, false) {
+            Ok(kv) => {
+                let values = &mut self.migration.values;
+                match values.iter_mut().find(|v| v.key_id == kv.key_id) {
+                    Some(existing) => *existing = kv,
+                    None => values.push(kv),
+                }
+                1
+            }
+            Err(e) => {
+                self.skip(format!("{name}.{field}: {e}"));
+                0
+            }
+        }
+    }
+
+    fn skip(&mut self, note: String) {
+        if !self.migration.skipped.contains(&note) {
+            self.migration.skipped.push(note);
+        }
+    }
+
+    pub fn finish(self) -> Migration {
+        self.migration
+    }
+}
+
+fn int_at(fields: &Fields, path: &str) -> Option<i64> {
+    fields.get_path(path)?.as_i64()
+}
+
+/// Frames of a saved configuration: either UBX frames, as in a capture, or
+/// the text u-center saves, one message per line as `CFG-RATE - 06 08 06 00
+/// E8 03 01 00 01 00` (class, ID, length and payload).
+pub fn read_config_frames(data: &[u8]) -> Result<Vec<Frame>> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) if text.lines().any(|l| l.contains(" - ")) => text,
+        _ => return Ok(frames(data).flatten().map(|f| f.to_owned()).collect()),
+    };
+    let mut out = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let Some((_, bytes)) = line.split_once(" - ") else {
+            continue;
+        };
+        let err = |msg: &str| Error::Config(format!("line {}: {msg}", n + 1));
+        let bytes = bytes
+            .split_whitespace()
+            .map(|b| u8::from_str_radix(b, 16))
+            .collect::<std::result::Result<Vec<u8>, _>>()
+            .map_err(|_| err("invalid hex byte"))?;
+        if bytes.len() < 4 {
+            return Err(err("expected class, ID and length"));
+        }
+        let len = usize::from(u16::from_le_bytes([bytes[2], bytes[3]]));
+        if bytes.len() != 4 + len {
+            return Err(err(&format!("length {len} doesn't match {} payload bytes", bytes.len() - 4)));
+        }
+        out.push(Frame::new(bytes[0], bytes[1], bytes[4..].to_vec()));
+    }
+    Ok(out)
+}
@@ -0,0 +1,221 @@
+//! Shrink a capture to the shortest byte sequence that still fails.
+//!
+//! Minimization runs delta debugging (ddmin) three times, each pass keeping
+//! only the reductions for which the caller's test still fails: first over
+//! whole frames and the bytes between them, then over each remaining frame's
+//! payload, re-framed with a fixed-up length and checksum, and last over
+//! single bytes. [`failures`] lists the decode failures in a capture, for a
+//! test that asks whether a particular one is still there.
+
+use std::any::Any;
+use std::fmt;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::decode::Decoder;
+use crate::differential::{self, Backend, DisagreementKind, Sample};
+use crate::error::FrameError;
+use crate::frame::{frames, Frame};
+use crate::schema::Schema;
+
+/// Shrink `data` while `fails` holds. Returns `None` if `data` itself does
+/// not fail.
+pub fn minimize(data: &[u8], mut fails: impl FnMut(&[u8]) -> bool) -> Option<Vec<u8>> {
+    if !fails(data) {
+        return None;
+    }
+
+    let parts: Vec<&[u8]> = units(data).into_iter().map(|r| &data[r]).collect();
+    let kept = ddmin(parts, &mut |parts: &[&[u8]]| fails(&parts.concat()));
+    let mut data = kept.concat();
+
+    let mut i = 0;
+    while let Some(frame) = frames(&data).flatten().nth(i) {
+        let (start, end) = (frame.offset, frame.offset + frame.raw.len());
+        let (class, id) = (frame.class, frame.id);
+        let payload = frame.payload.to_vec();
+        let rebuild = |payload: &[u8]| {
+            let mut out = data[..start].to_vec();
+            out.extend(Frame::new(class, id, payload.to_vec()).to_bytes());
+            out.extend(&data[end..]);
+            out
+        };
+        let kept = ddmin(payload, &mut |payload: &[u8]| fails(&rebuild(payload)));
+        data = rebuild(&kept);
+        i += 1;
+    }
+
+    let bytes = ddmin(data, &mut |bytes: &[u8]| fails(bytes));
+    Some(bytes)
+}
+
+/// The frames of `data` and the runs of bytes between them, in order.
+fn units(data: &[u8]) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    for frame in frames(data).flatten() {
+        if frame.offset > pos {
+            out.push(pos..frame.offset);
+        }
+        pos = frame.offset + frame.raw.len();
+        out.push(frame.offset..pos);
+    }
+    if pos < data.len() {
+        out.push(pos..data.len());
+    }
+    out
+}
+
+/// Zeller's ddmin: a subsequence of `items` for which `test` holds and from
+/// which no single item can be removed.
+fn ddmin<T: Clone>(mut items: Vec<T>, test: &mut impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut n = 2;
+    while !items.is_empty() {
+        let size = items.len().div_ceil(n);
+        let chunks: Vec<Range<usize>> = (0..items.len()).step_by(size).map(|s| s..(s + size).min(items.len())).collect();
+        let subset = chunks.iter().find(|c| c.len() < items.len() && test(&items[(*c).clone()]));
+        if let Some(c) = subset {
+            items = items[c.clone()].to_vec();
+            n = 2;
+            continue;
+        }
+        let complement = chunks.iter().find_map(|c| {
+            let rest: Vec<T> = items[..c.start].iter().chain(&items[c.end..]).cloned().collect();
+            test(&rest).then_some(rest)
+        });
+        if let Some(rest) = complement {
+            items = rest;
+            n = (n - 1).max(2);
+            continue;
+        }
+        if size == 1 {
+            break;
+        }
+        n = (n * 2).min(items.len());
+    }
+    items
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The schema decoder panicked.
+    Panic,
+    /// A frame with a bad checksum.
+    Checksum,
+    /// A frame the schema has no definition for, or whose variant it cannot tell.
+    Unknown,
+    /// A backend disagrees with the schema about a frame.
+    Disagreement {
+        backend: String,
+        kind: DisagreementKind,
+        field: Option<String>,
+    },
+}
+
+/// One decode failure in a capture. Two failures are the same failure when
+/// kind and class/ID match; `detail` is for the report only.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub class: u8,
+    pub id: u8,
+    pub detail: String,
+}
+
+impl PartialEq for Failure {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.kind, self.class, self.id) == (&other.kind, other.class, other.id)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match &self.kind {
+            FailureKind::Panic => "decoder panic".to_string(),
+            FailureKind::Checksum => "checksum error".to_string(),
+            FailureKind::Unknown => "not decoded".to_string(),
+            FailureKind::Disagreement { backend, kind, field } => {
+                let what = match kind {
+                    DisagreementKind::Verdict => "verdict",
+                    DisagreementKind::Name => "name",
+                    DisagreementKind::Field => "field",
+                };
+                let field = field.as_deref().map(|f| format!(" {f}")).unwrap_or_default();
+                format!("{backend} {what}{field} disagreement")
+            }
+        };
+        write!(f, "{what} in {:02X}-{:02X}: {}", self.class, self.id, self.detail)
+    }
+}
+
+/// Every decode failure in `data`: decoder panics, checksum errors, frames
+/// the schema cannot decode and disagreements with each of `backends`, whose
+/// floats agree within the relative `tolerance`.
+pub fn failures(schema: &Schema, data: &[u8], backends: &mut [Box<dyn Backend>], tolerance: f64) -> Vec<Failure> {
+    let decoder = Decoder::new(schema);
+    let mut out = Vec::new();
+    let mut samples = Vec::new();
+    for frame in frames(data) {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(FrameError::Checksum { class, id, expected, found }) => {
+                out.push(Failure {
+                    kind: FailureKind::Checksum,
+                    class,
+                    id,
+                    detail: format!("expected {expected:02X?}, found {found:02X?}"),
+                });
+                continue;
+            }
+            Err(_) => continue,
+        };
+        let failure = |kind, detail| Failure {
+            kind,
+            class: frame.class,
+            id: frame.id,
+            detail,
+        };
+        // A frame that panics the decoder would panic every backend comparison too.
+        match panic::catch_unwind(AssertUnwindSafe(|| decoder.decode_frame(&frame))) {
+            Ok(msg) => {
+                if !msg.parsed {
+                    out.push(failure(FailureKind::Unknown, format!("{}, {} byte payload", msg.name, msg.payload_length)));
+                }
+                samples.push(Sample {
+                    source: frame.offset.to_string(),
+                    frame: frame.raw.to_vec(),
+                });
+            }
+            Err(payload) => out.push(failure(FailureKind::Panic, panic_message(payload.as_ref()))),
+        }
+    }
+
+    if backends.is_empty() {
+        return out;
+    }
+    let report = differential::run(schema, &samples, backends, tolerance);
+    for d in report.disagreements {
+        let Some(sample) = samples.iter().find(|s| s.source == d.source) else {
+            continue;
+        };
+        out.push(Failure {
+            class: sample.frame[2],
+            id: sample.frame[3],
+            detail: format!("{}, schema {} vs {}", d.message, d.schema, d.other),
+            kind: FailureKind::Disagreement {
+                backend: d.backend,
+                kind: d.kind,
+                field: d.field,
+            },
+        });
+    }
+    out
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+        (Some(s), _) => s.to_string(),
+        (_, Some(s)) => s.clone(),
+        _ => "panic".into(),
+    }
+}
@@ -0,0 +1,133 @@
+//! Log files mapped into memory.
+//!
+//! The tools frame and decode a whole log from one byte slice. [`LogFile`]
+//! provides that slice by mapping the file read-only instead of reading it,
+//! so a multi-gigabyte capture is paged in as it is scanned rather than
+//! copied into RAM, and frames stay zero-copy views of the mapping. Where
+//! mapping is not possible (stdin, pipes, empty files, non-Unix targets)
+//! the input is read into memory as before.
+//!
+//! A file truncated by another process while it is mapped makes later
+//! reads fault, as with any mapping; don't map a log that is still being
+//! rotated.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::Path;
+
+/// The bytes of a log, mapped or read.
+#[derive(Debug)]
+pub struct LogFile {
+    data: Data,
+}
+
+#[derive(Debug)]
+enum Data {
+    #[cfg(unix)]
+    Mapped(unix::Mapping),
+    Read(Vec<u8>),
+}
+
+impl LogFile {
+    /// Map `path`, or read stdin for `-`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if path.as_os_str() == "-" {
+            return Self::read(io::stdin().lock());
+        }
+        let file = File::open(path)?;
+        #[cfg(unix)]
+        if let Some(mapping) = unix::Mapping::new(&file)? {
+            return Ok(Self {
+                data: Data::Mapped(mapping),
+            });
+        }
+        Self::read(file)
+    }
+
+    /// Read all of `reader` into memory.
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(Self { data: Data::Read(data) })
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.data, Data::Read(_))
+    }
+}
+
+impl Deref for LogFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.data {
+            #[cfg(unix)]
+            Data::Mapped(mapping) => mapping.bytes(),
+            Data::Read(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for LogFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::ptr;
+    use std::slice;
+
+    /// A read-only private mapping of a whole file.
+    #[derive(Debug)]
+    pub struct Mapping {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    // The mapping is read-only and owned, like a `Box<[u8]>`.
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Mapping {
+        /// `None` for files that cannot be mapped: empty, or not regular.
+        pub fn new(file: &File) -> io::Result<Option<Self>> {
+            let metadata = file.metadata()?;
+            let Ok(len) = usize::try_from(metadata.len()) else {
+                return Ok(None);
+            };
+            if !metadata.is_file() || len == 0 {
+                return Ok(None);
+            }
+            // SAFETY: a fresh read-only mapping of an open file; checked below.
+            let ptr = unsafe {
+                libc::mmap(ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+            };
+            if ptr == libc::MAP_FAILED {
+                return Ok(None);
+            }
+            // Logs are scanned front to back. Only a hint, so the result is ignored.
+            // SAFETY: `ptr` and `len` describe the mapping just made.
+            unsafe { libc::madvise(ptr, len, libc::MADV_SEQUENTIAL) };
+            Ok(Some(Self { ptr, len }))
+        }
+
+        pub fn bytes(&self) -> &[u8] {
+            // SAFETY: the mapping is `len` readable bytes and lives as long as `self`.
+            unsafe { slice::from_raw_parts(self.ptr.cast::<u8>(), self.len) }
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: unmapping the region mapped in `new`, once.
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Publish decoded messages to an MQTT broker.
+//!
+//! [`Topics`] routes each message to a topic (`ubx/NAV-PVT` unless mapped
+//! otherwise) and a QoS. [`MqttClient`] is a small MQTT 3.1.1 publisher over
+//! TCP: it connects, publishes at QoS 0, 1 or 2 and waits for each
+//! acknowledgement, and keeps the connection alive with PINGREQ. It never
+//! subscribes, so anything else the broker sends is ignored.
+
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::decode::DecodedMessage;
+use crate::error::{Error, Result};
+use crate::host_time::Timed;
+use crate::schema::normalize_name;
+
+/// Port MQTT brokers listen on for plain TCP.
+pub const DEFAULT_PORT: u16 = 1883;
+
+/// Delivery guarantee of a PUBLISH.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QoS {
+    #[default]
+    AtMostOnce = 0,
+    AtLeastOnce = 1,
+    ExactlyOnce = 2,
+}
+
+impl FromStr for QoS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "0" => Ok(QoS::AtMostOnce),
+            "1" => Ok(QoS::AtLeastOnce),
+            "2" => Ok(QoS::ExactlyOnce),
+            other => Err(Error::Mqtt(format!("invalid QoS '{other}' (expected 0, 1 or 2)"))),
+        }
+    }
+}
+
+/// Topic and QoS for each message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    /// Messages without a mapping go to `PREFIX/NAV-PVT` and so on.
+    pub prefix: String,
+    pub qos: QoS,
+    /// Topic per normalized message name, e.g. `UBX-NAV-PVT`.
+    topics: BTreeMap<String, String>,
+    qos_by_message: BTreeMap<String, QoS>,
+}
+
+impl Default for Topics {
+    fn default() -> Self {
+        Self::new("ubx", QoS::AtMostOnce)
+    }
+}
+
+impl Topics {
+    pub fn new(prefix: impl Into<String>, qos: QoS) -> Self {
+        Self {
+            prefix: prefix.into().trim_end_matches('/').to_string(),
+            qos,
+            topics: BTreeMap::new(),
+            qos_by_message: BTreeMap::new(),
+        }
+    }
+
+    /// Publish `message` (e.g. `NAV-PVT`) to `topic` instead.
+    pub fn map(mut self, message: &str, topic: impl Into<String>) -> Self {
+        self.topics.insert(normalize_name(message), topic.into());
+        self
+    }
+
+    /// Publish `message` at `qos` instead of the default.
+    pub fn map_qos(mut self, message: &str, qos: QoS) -> Self {
+        self.qos_by_message.insert(normalize_name(message), qos);
+        self
+    }
+
+    /// Topic and QoS for `msg`.
+    pub fn route(&self, msg: &DecodedMessage) -> (String, QoS) {
+        let topic = self
+            .topics
+            .get(&msg.name)
+            .cloned()
+            .unwrap_or_else(|| format!("{}/{}", self.prefix, msg.short_name()));
+        (topic, self.qos_by_message.get(&msg.name).copied().unwrap_or(self.qos))
+    }
+}
+
+/// Parse `MESSAGE=VALUE`, as given to `--topic` and `--message-qos`.
+pub fn parse_mapping(s: &str) -> Result<(String, String)> {
+    let (message, value) = s
+        .split_once('=')
+        .ok_or_else(|| Error::Mqtt(format!("expected MESSAGE=VALUE, got '{s}'")))?;
+    Ok((message.trim().to_string(), value.trim().to_string()))
+}
+
+/// CONNECT parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectOptions {
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Seconds of silence the broker allows before dropping the client; 0 disables.
+    pub keep_alive: u16,
+    pub clean_session: bool,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            client_id: format!("ubx-schema-{}", std::process::id()),
+            username: None,
+            password: None,
+            keep_alive: 60,
+            clean_session: true,
+        }
+    }
+}
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const PUBREC: u8 = 0x50;
+const PUBREL: u8 = 0x62;
+const PUBCOMP: u8 = 0x70;
+const PINGREQ: u8 = 0xC0;
+const DISCONNECT: u8 = 0xE0;
+
+/// Append an MQTT variable-length integer.
+fn put_remaining_length(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn put_str(out: &mut Vec<u8>, s: &[u8]) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s);
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![header];
+    put_remaining_length(&mut out, body.len());
+    out.extend_from_slice(body);
+    out
+}
+
+/// A CONNECT packet.
+pub fn connect_packet(options: &ConnectOptions) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_str(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+    let mut flags = u8::from(options.clean_session) << 1;
+    if options.username.is_some() {
+        flags |= 0x80;
+        if options.password.is_some() {
+            flags |= 0x40;
+        }
+    }
+    body.push(flags);
+    body.extend_from_slice(&options.keep_alive.to_be_bytes());
+    put_str(&mut body, options.client_id.as_bytes());
+    if let Some(username) = &options.username {
+        put_str(&mut body, username.as_bytes());
+        if let Some(password) = &options.password {
+            put_str(&mut body, password.as_bytes());
+        }
+    }
+    packet(CONNECT, &body)
+}
+
+/// A PUBLISH packet; `packet_id` is ignored at QoS 0.
+pub fn publish_packet(topic: &str, payload: &[u8], qos: QoS, retain: bool, packet_id: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_str(&mut body, topic.as_bytes());
+    if qos != QoS::AtMostOnce {
+        body.extend_from_slice(&packet_id.to_be_bytes());
+    }
+    body.extend_from_slice(payload);
+    packet(PUBLISH | (qos as u8) << 1 | u8::from(retain), &body)
+}
+
+/// A connected publisher.
+pub struct MqttClient {
+    stream: TcpStream,
+    timeout: Duration,
+    keep_alive: Option<Duration>,
+    last_sent: Instant,
+    next_id: u16,
+    pub published: u64,
+}
+
+impl MqttClient {
+    /// Connect to `address` (`host[:port]`) and wait for CONNACK. `timeout`
+    /// bounds the connection and each acknowledgement.
+    pub fn connect(address: &str, options: &ConnectOptions, timeout: Duration) -> Result<Self> {
+        let address = if address.contains(':') { address.to_string() } else { format!("{address}:{DEFAULT_PORT}") };
+        let socket = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::Mqtt(format!("cannot resolve {address}")))?;
+        let mut stream = TcpStream::connect_timeout(&socket, timeout)?;
+        stream.set_nodelay(true)?;
+        stream.write_all(&connect_packet(options))?;
+        let mut client = Self {
+            stream,
+            timeout,
+            keep_alive: (options.keep_alive > 0).then(|| Duration::from_secs(u64::from(options.keep_alive))),
+            last_sent: Instant::now(),
+            next_id: 1,
+            published: 0,
+        };
+        let (_, body) = client.expect(CONNACK, None)?;
+        match body.get(1) {
+            Some(0) => Ok(client),
+            Some(code) => Err(Error::Mqtt(format!("broker refused the connection: {}", connack_reason(*code)))),
+            None => Err(Error::Mqtt("malformed CONNACK".into())),
+        }
+    }
+
+    /// Publish `payload` to `topic`, waiting for the acknowledgements `qos` requires.
+    pub fn publish(&mut self, topic: &str, payload: &[u8], qos: QoS, retain: bool) -> Result<()> {
+        let id = self.next_id;
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        self.send(&publish_packet(topic, payload, qos, retain, id))?;
+        match qos {
+            QoS::AtMostOnce => {}
+            QoS::AtLeastOnce => {
+                self.expect(PUBACK, Some(id))?;
+            }
+            QoS::ExactlyOnce => {
+                self.expect(PUBREC, Some(id))?;
+                self.send(&packet(PUBREL, &id.to_be_bytes()))?;
+                self.expect(PUBCOMP, Some(id))?;
+            }
+        }
+        self.published += 1;
+        Ok(())
+    }
+
+    /// Publish `msg` as JSON, routed by `topics`.
+    pub fn publish_message(&mut self, msg: &DecodedMessage, topics: &Topics, retain: bool) -> Result<()> {
+        self.publish_timed(msg, None, topics, retain)
+    }
+
+    /// As [`MqttClient::publish_message`], with the message's `host_time`.
+    pub fn publish_timed(
+        &mut self,
+        msg: &DecodedMessage,
+        host_time: Option<f64>,
+        topics: &Topics,
+        retain: bool,
+    ) -> Result<()> {
+        let (topic, qos) = topics.route(msg);
+        let payload = serde_json::to_vec(&Timed { host_time, message: msg })?;
+        self.publish(&topic, &payload, qos, retain)
+    }
+
+    /// Send PINGREQ if the connection has been idle for half the keep-alive.
+    pub fn keep_alive(&mut self) -> Result<()> {
+        if self.keep_alive.is_some_and(|k| self.last_sent.elapsed() >= k / 2) {
+            self.send(&[PINGREQ, 0])?;
+        }
+        Ok(())
+    }
+
+    /// Send DISCONNECT and close the connection.
+    pub fn disconnect(mut self) -> Result<()> {
+        self.send(&[DISCONNECT, 0])?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        self.stream.write_all(bytes)?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// Read packets until one of type `kind` (for `id`) arrives or the timeout passes.
+    fn expect(&mut self, kind: u8, id: Option<u16>) -> Result<(u8, Vec<u8>)> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Mqtt(format!("no {} from the broker", packet_name(kind))));
+            }
+            self.stream.set_read_timeout(Some(remaining))?;
+            let (header, body) = match self.read_packet() {
+                Ok(packet) => packet,
+                Err(Error::Io(e)) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => continue,
+                Err(Error::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                    return Err(Error::Mqtt("broker closed the connection".into()))
+                }
+                Err(e) => return Err(e),
+            };
+            let matches_id = id.is_none_or(|id| body.get(..2) == Some(&id.to_be_bytes()[..]));
+            if header & 0xF0 == kind & 0xF0 && matches_id {
+                return Ok((header, body));
+            }
+        }
+    }
+
+    fn read_packet(&mut self) -> Result<(u8, Vec<u8>)> {
+        let mut byte = [0u8; 1];
+        self.stream.read_exact(&mut byte)?;
+        let header = byte[0];
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            self.stream.read_exact(&mut byte)?;
+            len |= usize::from(byte[0] & 0x7F) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            if shift > 21 {
+                return Err(Error::Mqtt("malformed remaining length".into()));
+            }
+        }
+        let mut body = vec![0u8; len];
+        self.stream.read_exact(&mut body)?;
+        Ok((header, body))
+    }
+}
+
+fn packet_name(kind: u8) -> &'static str {
+    match kind {
+        CONNACK => "CONNACK",
+        PUBACK => "PUBACK",
+        PUBREC => "PUBREC",
+        PUBCOMP => "PUBCOMP",
+        _ => "reply",
+    }
+}
+
+fn connack_reason(code: u8) -> &'static str {
+    match code {
+        1 => "unacceptable protocol version",
+        2 => "client identifier rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
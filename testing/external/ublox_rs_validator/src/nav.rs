@@ -0,0 +1,448 @@
+//! Broadcast navigation data from RXM-SFRBX subframes.
+//!
+//! GPS (and QZSS) L1 C/A LNAV subframes 1-3 are assembled per satellite into
+//! ephemerides, and GPS subframe 4 and 5 pages read as almanacs, following
+//! IS-GPS-200. [`NavDecoder`] adds Galileo I/NAV ([`crate::nav_galileo`]),
+//! BeiDou D1/D2 ([`crate::nav_beidou`]) and GLONASS strings
+//! ([`crate::nav_glonass`]) behind one interface. Parity of GPS, BeiDou and
+//! GLONASS words is not checked; u-blox receivers only output words that
+//! passed it. Galileo pages carry a CRC, which is.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::Serialize;
+
+use crate::decode::DecodedMessage;
+use crate::gnss::Gnss;
+use crate::nav_beidou::{BeidouDecoder, BeidouEphemeris};
+use crate::nav_galileo::{GalileoAlmanac, GalileoEphemeris, InavDecoder};
+use crate::nav_glonass::{GlonassAlmanac, GlonassDecoder, GlonassEphemeris};
+use crate::value::Value;
+
+/// Broadcast ephemeris and clock parameters from LNAV subframes 1-3.
+///
+/// Angles are in radians, times in seconds of the GPS week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GpsEphemeris {
+    pub gnss: Gnss,
+    pub sv_id: u8,
+    /// Transmitted (10-bit) week number.
+    pub week: u16,
+    pub code_on_l2: u8,
+    pub ura_index: u8,
+    pub health: u8,
+    pub iodc: u16,
+    pub l2_p_flag: u8,
+    pub tgd: f64,
+    pub toc: f64,
+    pub af0: f64,
+    pub af1: f64,
+    pub af2: f64,
+    pub iode: u8,
+    pub crs: f64,
+    pub delta_n: f64,
+    pub m0: f64,
+    pub cuc: f64,
+    pub e: f64,
+    pub cus: f64,
+    pub sqrt_a: f64,
+    pub toe: f64,
+    pub fit_interval: u8,
+    pub cic: f64,
+    pub omega0: f64,
+    pub cis: f64,
+    pub i0: f64,
+    pub crc: f64,
+    pub omega: f64,
+    pub omega_dot: f64,
+    pub idot: f64,
+    /// Time of week of the earliest subframe used, from its HOW.
+    pub transmit_time: f64,
+}
+
+impl GpsEphemeris {
+    /// User range accuracy in metres for the transmitted URA index.
+    pub fn ura_meters(&self) -> f64 {
+        const URA: [f64; 16] = [
+            2.4, 3.4, 4.85, 6.85, 9.65, 13.65, 24.0, 48.0, 96.0, 192.0, 384.0, 768.0, 1536.0, 3072.0,
+            6144.0, 6144.0,
+        ];
+        URA[usize::from(self.ura_index & 0x0F)]
+    }
+}
+
+/// Almanac parameters from a GPS LNAV subframe 4 or 5 page.
+///
+/// Angles are in radians; `delta_i` is the inclination less 0.3 semicircles
+/// (54 degrees).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GpsAlmanac {
+    pub gnss: Gnss,
+    /// The satellite described, not the one transmitting the page.
+    pub sv_id: u8,
+    pub e: f64,
+    pub toa: f64,
+    pub delta_i: f64,
+    pub omega_dot: f64,
+    pub health: u8,
+    pub sqrt_a: f64,
+    pub omega0: f64,
+    pub omega: f64,
+    pub m0: f64,
+    pub af0: f64,
+    pub af1: f64,
+}
+
+/// The almanac in an LNAV subframe 4 or 5 page; `None` for other
+/// subframes and for pages with other data (SV IDs above 32).
+pub fn lnav_almanac(gnss: Gnss, words: &[u32]) -> Option<GpsAlmanac> {
+    let bits = Bits::from_words(words)?;
+    if gnss != Gnss::Gps || bits.u(0, 8) != 0x8B || !matches!(bits.u(43, 3), 4 | 5) {
+        return None;
+    }
+    // Data ID 1 is the GPS almanac format.
+    let sv_id = bits.u(50, 6) as u8;
+    if bits.u(48, 2) != 1 || !(1..=32).contains(&sv_id) {
+        return None;
+    }
+    // af0 is split around af1 in word 10.
+    let af0 = (bits.u(216, 8) << 3) | bits.u(235, 3);
+    Some(GpsAlmanac {
+        gnss,
+        sv_id,
+        e: f64::from(bits.u(56, 16)) * p2(-21),
+        toa: f64::from(bits.u(72, 8)) * 4_096.0,
+        delta_i: f64::from(bits.s(80, 16)) * p2(-19) * PI,
+        omega_dot: f64::from(bits.s(96, 16)) * p2(-38) * PI,
+        health: bits.u(112, 8) as u8,
+        sqrt_a: f64::from(bits.u(120, 24)) * p2(-11),
+        omega0: f64::from(bits.s(144, 24)) * p2(-23) * PI,
+        omega: f64::from(bits.s(168, 24)) * p2(-23) * PI,
+        m0: f64::from(bits.s(192, 24)) * p2(-23) * PI,
+        af0: f64::from(((af0 << 21) as i32) >> 21) * p2(-20),
+        af1: f64::from(bits.s(224, 11)) * p2(-38),
+    })
+}
+
+/// Bit reader over LNAV words packed as 24 data bits each.
+struct Bits([u32; 10]);
+
+impl Bits {
+    /// `dwrd` values carry a 30-bit word with the parity in the low six bits.
+    fn from_words(words: &[u32]) -> Option<Self> {
+        let mut data = [0u32; 10];
+        for (d, w) in data.iter_mut().zip(words.get(..10)?) {
+            *d = (w >> 6) & 0x00FF_FFFF;
+        }
+        Some(Self(data))
+    }
+
+    fn u(&self, pos: usize, len: usize) -> u32 {
+        (pos..pos + len).fold(0, |acc, bit| {
+            let word = self.0[bit / 24];
+            (acc << 1) | ((word >> (23 - bit % 24)) & 1)
+        })
+    }
+
+    fn s(&self, pos: usize, len: usize) -> i32 {
+        let raw = self.u(pos, len);
+        ((raw << (32 - len)) as i32) >> (32 - len)
+    }
+
+    /// A 32-bit value split as 8 bits at `hi` and 24 bits at `lo`.
+    fn u32_split(&self, hi: usize, lo: usize) -> u32 {
+        (self.u(hi, 8) << 24) | self.u(lo, 24)
+    }
+}
+
+pub(crate) fn p2(exp: i32) -> f64 {
+    2f64.powi(exp)
+}
+
+/// Most-significant-first reader over the bits of navigation words.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BitReader(Vec<u8>);
+
+impl BitReader {
+    /// The low `width` bits of each word, in order.
+    pub(crate) fn from_words(words: &[u32], width: usize) -> Self {
+        let bits = words
+            .iter()
+            .flat_map(|w| (0..width).rev().map(move |b| (w >> b & 1) as u8))
+            .collect();
+        Self(bits)
+    }
+
+    /// `len` bits at each of `ranges`, joined.
+    pub(crate) fn select(&self, ranges: &[(usize, usize)]) -> Self {
+        Self(ranges.iter().flat_map(|&(pos, len)| self.0[pos..pos + len].iter().copied()).collect())
+    }
+
+    pub(crate) fn bits(&self, pos: usize, len: usize) -> &[u8] {
+        &self.0[pos..pos + len]
+    }
+
+    pub(crate) fn u(&self, pos: usize, len: usize) -> u64 {
+        self.u_parts(&[(pos, len)])
+    }
+
+    /// Two's complement.
+    pub(crate) fn s(&self, pos: usize, len: usize) -> i64 {
+        self.s_parts(&[(pos, len)])
+    }
+
+    /// Sign and magnitude, as GLONASS sends signed values.
+    pub(crate) fn sm(&self, pos: usize, len: usize) -> i64 {
+        let magnitude = self.u(pos + 1, len - 1) as i64;
+        if self.u(pos, 1) == 1 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// An unsigned value split over several ranges, most significant first.
+    pub(crate) fn u_parts(&self, parts: &[(usize, usize)]) -> u64 {
+        parts
+            .iter()
+            .flat_map(|&(pos, len)| &self.0[pos..pos + len])
+            .fold(0, |acc, &bit| (acc << 1) | u64::from(bit))
+    }
+
+    /// A two's complement value split over several ranges.
+    pub(crate) fn s_parts(&self, parts: &[(usize, usize)]) -> i64 {
+        let len: usize = parts.iter().map(|&(_, len)| len).sum();
+        let raw = self.u_parts(parts);
+        ((raw << (64 - len)) as i64) >> (64 - len)
+    }
+}
+
+#[derive(Default)]
+struct SvSubframes {
+    sf: [Option<(Bits, f64)>; 3],
+}
+
+/// Collects LNAV subframes per satellite and yields ephemerides once a
+/// consistent set of subframes 1-3 has been received.
+#[derive(Default)]
+pub struct LnavDecoder {
+    pending: HashMap<(Gnss, u8), SvSubframes>,
+    last: HashMap<(Gnss, u8), (u16, u8)>,
+}
+
+impl LnavDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a decoded RXM-SFRBX message. Returns a new ephemeris when this
+    /// subframe completes one that differs from the last reported for the SV.
+    pub fn push(&mut self, msg: &DecodedMessage) -> Option<GpsEphemeris> {
+        let field = |name| msg.get(name).and_then(|v| v.as_u64());
+        let gnss = Gnss::from_id(field("gnssId")? as u8)?;
+        // LNAV is carried on L1 C/A (sigId 0) of GPS and QZSS.
+        if !matches!(gnss, Gnss::Gps | Gnss::Qzss) || field("sigId").unwrap_or(0) != 0 {
+            return None;
+        }
+        let sv_id = field("svId")? as u8;
+        let words: Vec<u32> = msg
+            .get("dwrd")?
+            .as_array()?
+            .iter()
+            .filter_map(|w| w.as_u64().map(|w| w as u32))
+            .collect();
+        self.push_words(gnss, sv_id, &words)
+    }
+
+    /// Feed the ten words of one LNAV subframe.
+    pub fn push_words(&mut self, gnss: Gnss, sv_id: u8, words: &[u32]) -> Option<GpsEphemeris> {
+        let bits = Bits::from_words(words)?;
+        // Preamble 0x8B in the TLM word.
+        if bits.u(0, 8) != 0x8B {
+            return None;
+        }
+        let tow = f64::from(bits.u(24, 17)) * 6.0 - 6.0;
+        let id = bits.u(43, 3) as usize;
+        if !(1..=3).contains(&id) {
+            return None;
+        }
+        let entry = self.pending.entry((gnss, sv_id)).or_default();
+        entry.sf[id - 1] = Some((bits, tow));
+
+        let [Some((sf1, t1)), Some((sf2, t2)), Some((sf3, t3))] = &entry.sf else {
+            return None;
+        };
+        let iodc = ((sf1.u(70, 2) << 8) | sf1.u(168, 8)) as u16;
+        let iode2 = sf2.u(48, 8) as u8;
+        let iode3 = sf3.u(216, 8) as u8;
+        // All three subframes must belong to the same issue of data.
+        if iode2 != iode3 || (iodc & 0xFF) as u8 != iode2 {
+            return None;
+        }
+        if self.last.get(&(gnss, sv_id)) == Some(&(iodc, iode2)) {
+            return None;
+        }
+        let eph = GpsEphemeris {
+            gnss,
+            sv_id,
+            week: sf1.u(48, 10) as u16,
+            code_on_l2: sf1.u(58, 2) as u8,
+            ura_index: sf1.u(60, 4) as u8,
+            health: sf1.u(64, 6) as u8,
+            iodc,
+            l2_p_flag: sf1.u(72, 1) as u8,
+            tgd: f64::from(sf1.s(160, 8)) * p2(-31),
+            toc: f64::from(sf1.u(176, 16)) * 16.0,
+            af2: f64::from(sf1.s(192, 8)) * p2(-55),
+            af1: f64::from(sf1.s(200, 16)) * p2(-43),
+            af0: f64::from(sf1.s(216, 22)) * p2(-31),
+            iode: iode2,
+            crs: f64::from(sf2.s(56, 16)) * p2(-5),
+            delta_n: f64::from(sf2.s(72, 16)) * p2(-43) * PI,
+            m0: f64::from(sf2.u32_split(88, 96) as i32) * p2(-31) * PI,
+            cuc: f64::from(sf2.s(120, 16)) * p2(-29),
+            e: f64::from(sf2.u32_split(136, 144)) * p2(-33),
+            cus: f64::from(sf2.s(168, 16)) * p2(-29),
+            sqrt_a: f64::from(sf2.u32_split(184, 192)) * p2(-19),
+            toe: f64::from(sf2.u(216, 16)) * 16.0,
+            fit_interval: sf2.u(232, 1) as u8,
+            cic: f64::from(sf3.s(48, 16)) * p2(-29),
+            omega0: f64::from(sf3.u32_split(64, 72) as i32) * p2(-31) * PI,
+            cis: f64::from(sf3.s(96, 16)) * p2(-29),
+            i0: f64::from(sf3.u32_split(112, 120) as i32) * p2(-31) * PI,
+            crc: f64::from(sf3.s(144, 16)) * p2(-5),
+            omega: f64::from(sf3.u32_split(160, 168) as i32) * p2(-31) * PI,
+            omega_dot: f64::from(sf3.s(192, 24)) * p2(-43) * PI,
+            idot: f64::from(sf3.s(224, 14)) * p2(-43) * PI,
+            transmit_time: t1.min(*t2).min(*t3),
+        };
+        self.last.insert((gnss, sv_id), (iodc, iode2));
+        Some(eph)
+    }
+}
+
+/// A value split across words of different subframes or pages, as
+/// `(reader, position, length)` parts, most significant first.
+pub(crate) fn join_u(parts: &[(&BitReader, usize, usize)]) -> u64 {
+    parts.iter().fold(0, |acc, &(r, pos, len)| (acc << len) | r.u(pos, len))
+}
+
+/// Two's complement [`join_u`].
+pub(crate) fn join_s(parts: &[(&BitReader, usize, usize)]) -> i64 {
+    let len: usize = parts.iter().map(|&(_, _, len)| len).sum();
+    ((join_u(parts) << (64 - len)) as i64) >> (64 - len)
+}
+
+/// Navigation data decoded from one or more RXM-SFRBX messages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NavData {
+    GpsEphemeris(GpsEphemeris),
+    GpsAlmanac(GpsAlmanac),
+    GalileoEphemeris(GalileoEphemeris),
+    GalileoAlmanac(GalileoAlmanac),
+    BeidouEphemeris(BeidouEphemeris),
+    GlonassEphemeris(GlonassEphemeris),
+    GlonassAlmanac(GlonassAlmanac),
+}
+
+impl NavData {
+    /// The `type` tag, e.g. `galileo_almanac`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NavData::GpsEphemeris(_) => "gps_ephemeris",
+            NavData::GpsAlmanac(_) => "gps_almanac",
+            NavData::GalileoEphemeris(_) => "galileo_ephemeris",
+            NavData::GalileoAlmanac(_) => "galileo_almanac",
+            NavData::BeidouEphemeris(_) => "beidou_ephemeris",
+            NavData::GlonassEphemeris(_) => "glonass_ephemeris",
+            NavData::GlonassAlmanac(_) => "glonass_almanac",
+        }
+    }
+
+    pub fn gnss(&self) -> Gnss {
+        match self {
+            NavData::GpsEphemeris(e) => e.gnss,
+            NavData::GpsAlmanac(a) => a.gnss,
+            NavData::GalileoEphemeris(_) | NavData::GalileoAlmanac(_) => Gnss::Galileo,
+            NavData::BeidouEphemeris(_) => Gnss::BeiDou,
+            NavData::GlonassEphemeris(_) | NavData::GlonassAlmanac(_) => Gnss::Glonass,
+        }
+    }
+
+    /// The satellite described: for almanacs not the transmitting one.
+    pub fn sv_id(&self) -> u8 {
+        match self {
+            NavData::GpsEphemeris(e) => e.sv_id,
+            NavData::GpsAlmanac(a) => a.sv_id,
+            NavData::GalileoEphemeris(e) => e.sv_id,
+            NavData::GalileoAlmanac(a) => a.sv_id,
+            NavData::BeidouEphemeris(e) => e.sv_id,
+            NavData::GlonassEphemeris(e) => e.slot,
+            NavData::GlonassAlmanac(a) => a.slot,
+        }
+    }
+
+    pub fn is_almanac(&self) -> bool {
+        matches!(self, NavData::GpsAlmanac(_) | NavData::GalileoAlmanac(_) | NavData::GlonassAlmanac(_))
+    }
+}
+
+/// Decodes the navigation messages of every supported constellation from
+/// RXM-SFRBX, reporting each ephemeris or almanac when it first appears or
+/// changes.
+#[derive(Default)]
+pub struct NavDecoder {
+    lnav: LnavDecoder,
+    inav: InavDecoder,
+    beidou: BeidouDecoder,
+    glonass: GlonassDecoder,
+    last: HashMap<(&'static str, Gnss, u8), NavData>,
+}
+
+impl NavDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a decoded RXM-SFRBX message; returns what it completed.
+    pub fn push(&mut self, msg: &DecodedMessage) -> Vec<NavData> {
+        let field = |name| msg.get(name).and_then(Value::as_u64);
+        let (Some(gnss), Some(sv_id)) = (field("gnssId").and_then(|id| Gnss::from_id(id as u8)), field("svId")) else {
+            return Vec::new();
+        };
+        let sv_id = sv_id as u8;
+        let sig_id = field("sigId").unwrap_or(0);
+        let words: Vec<u32> = match msg.get("dwrd").and_then(Value::as_array) {
+            Some(words) => words.iter().filter_map(|w| w.as_u64().map(|w| w as u32)).collect(),
+            None => return Vec::new(),
+        };
+        let decoded = match gnss {
+            Gnss::Gps | Gnss::Qzss if sig_id == 0 => {
+                let eph = self.lnav.push_words(gnss, sv_id, &words).map(NavData::GpsEphemeris);
+                let alm = lnav_almanac(gnss, &words).map(NavData::GpsAlmanac);
+                eph.into_iter().chain(alm).collect()
+            }
+            // F/NAV on E5a (sigId 3 and 4) has another page layout.
+            Gnss::Galileo if !matches!(sig_id, 3 | 4) => self.inav.push_words(sv_id, &words),
+            Gnss::BeiDou => self.beidou.push_words(sv_id, &words).into_iter().map(NavData::BeidouEphemeris).collect(),
+            Gnss::Glonass => {
+                let channel = field("freqId").map(|f| f as i8 - 7);
+                self.glonass.push_words(sv_id, channel, &words)
+            }
+            _ => Vec::new(),
+        };
+        decoded
+            .into_iter()
+            .filter(|data| {
+                let key = (data.kind(), data.gnss(), data.sv_id());
+                if self.last.get(&key) == Some(data) {
+                    return false;
+                }
+                self.last.insert(key, data.clone());
+                true
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,200 @@
+//! BeiDou D1 and D2 navigation messages from RXM-SFRBX, following the
+//! BeiDou SIS ICD (B1I).
+//!
+//! Each SFRBX carries one 300-bit subframe as ten 30-bit words, the data
+//! bits of each word ahead of its parity. MEO and IGSO satellites (PRN 6-58)
+//! send D1, with the ephemeris in subframes 1-3; GEO satellites (PRN 1-5
+//! and 59-63) send D2, with the ephemeris spread over pages 1-10 of
+//! subframe 1. Positions below are bit offsets into the subframe, parity
+//! included, so a field split across words is read in parts.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::Serialize;
+
+use crate::nav::{join_s, join_u, p2, BitReader};
+
+/// Preamble at the start of every subframe, `11100010010`.
+const PREAMBLE: u64 = 0x712;
+
+/// Ephemeris and clock parameters from D1 subframes 1-3 or D2 pages 1-10.
+///
+/// Angles are in radians, times in seconds of the BDT week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BeidouEphemeris {
+    pub sv_id: u8,
+    /// From D2 (GEO) rather than D1.
+    pub geo: bool,
+    /// BDT week number.
+    pub week: u16,
+    /// Autonomous health, 0 = healthy.
+    pub sat_h1: u8,
+    /// Age of data, clock and ephemeris.
+    pub aodc: u8,
+    pub aode: u8,
+    pub urai: u8,
+    pub toc: f64,
+    pub tgd1: f64,
+    pub tgd2: f64,
+    pub af0: f64,
+    pub af1: f64,
+    pub af2: f64,
+    pub toe: f64,
+    pub sqrt_a: f64,
+    pub e: f64,
+    pub omega: f64,
+    pub delta_n: f64,
+    pub m0: f64,
+    pub omega0: f64,
+    pub omega_dot: f64,
+    pub i0: f64,
+    pub idot: f64,
+    pub cuc: f64,
+    pub cus: f64,
+    pub crc: f64,
+    pub crs: f64,
+    pub cic: f64,
+    pub cis: f64,
+    /// Seconds of week at the start of the first subframe or page used.
+    pub transmit_time: f64,
+}
+
+/// Collects D1 subframes and D2 pages per satellite.
+#[derive(Default)]
+pub struct BeidouDecoder {
+    d1: HashMap<u8, [Option<BitReader>; 3]>,
+    d2: HashMap<u8, [Option<BitReader>; 10]>,
+}
+
+impl BeidouDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a PRN is a GEO satellite, which sends D2.
+    pub fn is_geo(sv_id: u8) -> bool {
+        matches!(sv_id, 1..=5 | 59..=63)
+    }
+
+    /// Feed the ten words of one subframe; returns the ephemeris once a
+    /// consecutive set of subframes (D1) or pages (D2) is complete.
+    pub fn push_words(&mut self, sv_id: u8, words: &[u32]) -> Option<BeidouEphemeris> {
+        let sf = BitReader::from_words(words.get(..10)?, 30);
+        if sf.u(0, 11) != PREAMBLE {
+            return None;
+        }
+        let id = sf.u(15, 3) as usize;
+        if Self::is_geo(sv_id) {
+            let page = sf.u(42, 4) as usize;
+            if id != 1 || !(1..=10).contains(&page) {
+                return None;
+            }
+            let pages = self.d2.entry(sv_id).or_default();
+            pages[page - 1] = Some(sf);
+            (page == 10).then(|| d2(sv_id, pages)).flatten()
+        } else {
+            if !(1..=3).contains(&id) {
+                return None;
+            }
+            let subframes = self.d1.entry(sv_id).or_default();
+            subframes[id - 1] = Some(sf);
+            (id == 3).then(|| d1(sv_id, subframes)).flatten()
+        }
+    }
+}
+
+fn sow(sf: &BitReader) -> u64 {
+    sf.u_parts(&[(18, 8), (30, 12)])
+}
+
+/// Subframes 1-3 sent in sequence, 6 s apart.
+fn d1(sv_id: u8, subframes: &[Option<BitReader>; 3]) -> Option<BeidouEphemeris> {
+    let [Some(sf1), Some(sf2), Some(sf3)] = subframes else {
+        return None;
+    };
+    let t = sow(sf1);
+    if sow(sf2) != t + 6 || sow(sf3) != t + 12 {
+        return None;
+    }
+    let sc = |raw: i64, exp: i32| raw as f64 * p2(exp) * PI;
+    Some(BeidouEphemeris {
+        sv_id,
+        geo: false,
+        week: sf1.u(60, 13) as u16,
+        sat_h1: sf1.u(42, 1) as u8,
+        aodc: sf1.u(43, 5) as u8,
+        aode: sf1.u(287, 5) as u8,
+        urai: sf1.u(48, 4) as u8,
+        toc: sf1.u_parts(&[(73, 9), (90, 8)]) as f64 * 8.0,
+        tgd1: sf1.s(98, 10) as f64 * 0.1e-9,
+        tgd2: sf1.s_parts(&[(108, 4), (120, 6)]) as f64 * 0.1e-9,
+        af2: sf1.s(214, 11) as f64 * p2(-66),
+        af0: sf1.s_parts(&[(225, 7), (240, 17)]) as f64 * p2(-33),
+        af1: sf1.s_parts(&[(257, 5), (270, 17)]) as f64 * p2(-50),
+        delta_n: sc(sf2.s_parts(&[(42, 10), (60, 6)]), -43),
+        cuc: sf2.s_parts(&[(66, 16), (90, 2)]) as f64 * p2(-31),
+        m0: sc(sf2.s_parts(&[(92, 20), (120, 12)]), -31),
+        e: sf2.u_parts(&[(132, 10), (150, 22)]) as f64 * p2(-33),
+        cus: sf2.s(180, 18) as f64 * p2(-31),
+        crc: sf2.s_parts(&[(198, 4), (210, 14)]) as f64 * p2(-6),
+        crs: sf2.s_parts(&[(224, 8), (240, 10)]) as f64 * p2(-6),
+        sqrt_a: sf2.u_parts(&[(250, 12), (270, 20)]) as f64 * p2(-19),
+        // toe's two most significant bits end subframe 2.
+        toe: join_u(&[(sf2, 290, 2), (sf3, 42, 10), (sf3, 60, 5)]) as f64 * 8.0,
+        i0: sc(sf3.s_parts(&[(65, 17), (90, 15)]), -31),
+        cic: sf3.s_parts(&[(105, 7), (120, 11)]) as f64 * p2(-31),
+        omega_dot: sc(sf3.s_parts(&[(131, 11), (150, 13)]), -43),
+        cis: sf3.s_parts(&[(163, 9), (180, 9)]) as f64 * p2(-31),
+        idot: sc(sf3.s_parts(&[(189, 13), (210, 1)]), -43),
+        omega0: sc(sf3.s_parts(&[(211, 21), (240, 11)]), -31),
+        omega: sc(sf3.s_parts(&[(251, 11), (270, 21)]), -31),
+        transmit_time: t as f64,
+    })
+}
+
+/// Pages 1-10 of subframe 1 from consecutive 3 s frames. Parameters split
+/// across pages have their high part in the earlier page.
+fn d2(sv_id: u8, pages: &[Option<BitReader>; 10]) -> Option<BeidouEphemeris> {
+    let pages: Vec<&BitReader> = pages.iter().flatten().collect();
+    let [p1, _, p3, p4, p5, p6, p7, p8, p9, p10] = pages[..] else {
+        return None;
+    };
+    let t = sow(p1);
+    if pages.iter().zip(0..).any(|(p, n)| sow(p) != t + 3 * n) {
+        return None;
+    }
+    let sc = |raw: i64, exp: i32| raw as f64 * p2(exp) * PI;
+    Some(BeidouEphemeris {
+        sv_id,
+        geo: true,
+        week: p1.u(64, 13) as u16,
+        sat_h1: p1.u(46, 1) as u8,
+        aodc: p1.u(47, 5) as u8,
+        aode: p4.u(91, 5) as u8,
+        urai: p1.u(60, 4) as u8,
+        toc: p1.u_parts(&[(77, 5), (90, 12)]) as f64 * 8.0,
+        tgd1: p1.s(102, 10) as f64 * 0.1e-9,
+        tgd2: p1.s(120, 10) as f64 * 0.1e-9,
+        af0: p3.s_parts(&[(100, 12), (120, 12)]) as f64 * p2(-33),
+        af1: join_s(&[(p3, 132, 4), (p4, 46, 6), (p4, 60, 12)]) as f64 * p2(-50),
+        af2: p4.s_parts(&[(72, 10), (90, 1)]) as f64 * p2(-66),
+        delta_n: sc(p4.s(96, 16), -43),
+        cuc: join_s(&[(p4, 120, 14), (p5, 46, 4)]) as f64 * p2(-31),
+        m0: sc(p5.s_parts(&[(50, 2), (60, 22), (90, 8)]), -31),
+        cus: p5.s_parts(&[(98, 14), (120, 4)]) as f64 * p2(-31),
+        e: join_u(&[(p5, 124, 10), (p6, 46, 6), (p6, 60, 16)]) as f64 * p2(-33),
+        sqrt_a: p6.u_parts(&[(76, 6), (90, 22), (120, 4)]) as f64 * p2(-19),
+        cic: join_s(&[(p6, 124, 10), (p7, 46, 6), (p7, 60, 2)]) as f64 * p2(-31),
+        cis: p7.s(62, 18) as f64 * p2(-31),
+        toe: p7.u_parts(&[(80, 2), (90, 15)]) as f64 * 8.0,
+        i0: sc(join_s(&[(p7, 105, 7), (p7, 120, 14), (p8, 46, 6), (p8, 60, 5)]), -31),
+        crc: p8.s_parts(&[(65, 17), (90, 1)]) as f64 * p2(-6),
+        crs: p8.s(91, 18) as f64 * p2(-6),
+        omega_dot: sc(join_s(&[(p8, 109, 3), (p8, 120, 16), (p9, 46, 5)]), -43),
+        omega0: sc(p9.s_parts(&[(51, 1), (60, 22), (90, 9)]), -31),
+        omega: sc(join_s(&[(p9, 99, 13), (p9, 120, 14), (p10, 46, 5)]), -31),
+        idot: sc(p10.s_parts(&[(51, 1), (60, 13)]), -43),
+        transmit_time: t as f64,
+    })
+}
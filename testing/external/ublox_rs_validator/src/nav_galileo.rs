@@ -0,0 +1,244 @@
+//! Galileo I/NAV from RXM-SFRBX, following the Galileo OS SIS ICD.
+//!
+//! Each SFRBX carries one nominal page from E1-B or E5b-I: the even part in
+//! words 0-3 and the odd part in words 4-7, each 120 bits from the top. The
+//! 112 + 16 data bits of the two parts form one 128-bit word, checked
+//! against the page's CRC-24Q. Word types 1-5 give the ephemeris, clock,
+//! group delays and health; types 7-10 the almanacs of three satellites.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::Serialize;
+
+use crate::nav::{p2, BitReader, NavData};
+
+/// Ephemeris, clock correction and health from I/NAV words 1-5.
+///
+/// Angles are in radians, times in seconds of the GST week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GalileoEphemeris {
+    pub sv_id: u8,
+    pub iod_nav: u16,
+    /// GST week number from word 5.
+    pub week: u16,
+    pub toe: f64,
+    pub m0: f64,
+    pub e: f64,
+    pub sqrt_a: f64,
+    pub omega0: f64,
+    pub i0: f64,
+    pub omega: f64,
+    pub idot: f64,
+    pub omega_dot: f64,
+    pub delta_n: f64,
+    pub cuc: f64,
+    pub cus: f64,
+    pub crc: f64,
+    pub crs: f64,
+    pub cic: f64,
+    pub cis: f64,
+    /// Signal-in-space accuracy index.
+    pub sisa: u8,
+    pub toc: f64,
+    pub af0: f64,
+    pub af1: f64,
+    pub af2: f64,
+    pub bgd_e1_e5a: f64,
+    pub bgd_e1_e5b: f64,
+    /// Signal health (0 = OK) and data validity (0 = valid) of E5b and E1-B.
+    pub e5b_hs: u8,
+    pub e1b_hs: u8,
+    pub e5b_dvs: u8,
+    pub e1b_dvs: u8,
+}
+
+/// Almanac of one satellite from I/NAV words 7-10.
+///
+/// `delta_sqrt_a` is relative to the nominal 29 600 km orbit and `delta_i`
+/// to 56 degrees; angles are in radians.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GalileoAlmanac {
+    pub sv_id: u8,
+    pub iod_a: u8,
+    /// Two least significant bits of the almanac reference week.
+    pub week: u8,
+    pub toa: f64,
+    pub delta_sqrt_a: f64,
+    pub e: f64,
+    pub omega: f64,
+    pub delta_i: f64,
+    pub omega0: f64,
+    pub omega_dot: f64,
+    pub m0: f64,
+    pub af0: f64,
+    pub af1: f64,
+    pub e5b_hs: u8,
+    pub e1b_hs: u8,
+}
+
+/// Collects I/NAV words per satellite.
+#[derive(Default)]
+pub struct InavDecoder {
+    /// The last word of each type 0-10, per transmitting satellite.
+    words: HashMap<u8, [Option<BitReader>; 11]>,
+}
+
+impl InavDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the eight words of one page; returns the ephemeris or almanacs
+    /// it completes. Alert pages and pages failing the CRC are ignored.
+    pub fn push_words(&mut self, sv_id: u8, words: &[u32]) -> Vec<NavData> {
+        let Some(word) = data_word(words) else {
+            return Vec::new();
+        };
+        let word_type = word.u(0, 6) as usize;
+        if !(1..=10).contains(&word_type) {
+            return Vec::new();
+        }
+        let stored = self.words.entry(sv_id).or_default();
+        stored[word_type] = Some(word);
+        match word_type {
+            1..=5 => ephemeris(sv_id, stored).map(NavData::GalileoEphemeris).into_iter().collect(),
+            7..=10 => almanacs(stored).into_iter().map(NavData::GalileoAlmanac).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The 128-bit data word of a nominal page that passes its CRC.
+fn data_word(words: &[u32]) -> Option<BitReader> {
+    let page = BitReader::from_words(words.get(..8)?, 32);
+    let odd = 128;
+    // Even part first, then odd; page type 1 is an alert page.
+    if page.u(0, 1) != 0 || page.u(odd, 1) != 1 || page.u(1, 1) != 0 || page.u(odd + 1, 1) != 0 {
+        return None;
+    }
+    // The CRC covers the even part without its tail and the odd part up to the CRC.
+    let checked = page.select(&[(0, 114), (odd, 82)]);
+    if crc24q(checked.bits(0, 196)) != page.u(odd + 82, 24) as u32 {
+        return None;
+    }
+    Some(page.select(&[(2, 112), (odd + 2, 16)]))
+}
+
+/// CRC-24Q, as used by Galileo I/NAV, over bits most significant first.
+fn crc24q(bits: &[u8]) -> u32 {
+    bits.iter().fold(0u32, |crc, &bit| {
+        let top = (crc >> 23 & 1) ^ u32::from(bit);
+        let crc = (crc << 1) & 0x00FF_FFFF;
+        if top == 1 {
+            crc ^ 0x0086_4CFB
+        } else {
+            crc
+        }
+    })
+}
+
+/// Words 1-4 of one issue of data, with the latest word 5.
+fn ephemeris(sv_id: u8, words: &[Option<BitReader>; 11]) -> Option<GalileoEphemeris> {
+    let [_, Some(w1), Some(w2), Some(w3), Some(w4), Some(w5), ..] = words else {
+        return None;
+    };
+    let iod_nav = w1.u(6, 10);
+    if [w2, w3, w4].iter().any(|w| w.u(6, 10) != iod_nav) {
+        return None;
+    }
+    let sc = |raw: i64, exp: i32| raw as f64 * p2(exp) * PI;
+    Some(GalileoEphemeris {
+        sv_id,
+        iod_nav: iod_nav as u16,
+        week: w5.u(73, 12) as u16,
+        toe: w1.u(16, 14) as f64 * 60.0,
+        m0: sc(w1.s(30, 32), -31),
+        e: w1.u(62, 32) as f64 * p2(-33),
+        sqrt_a: w1.u(94, 32) as f64 * p2(-19),
+        omega0: sc(w2.s(16, 32), -31),
+        i0: sc(w2.s(48, 32), -31),
+        omega: sc(w2.s(80, 32), -31),
+        idot: sc(w2.s(112, 14), -43),
+        omega_dot: sc(w3.s(16, 24), -43),
+        delta_n: sc(w3.s(40, 16), -43),
+        cuc: w3.s(56, 16) as f64 * p2(-29),
+        cus: w3.s(72, 16) as f64 * p2(-29),
+        crc: w3.s(88, 16) as f64 * p2(-5),
+        crs: w3.s(104, 16) as f64 * p2(-5),
+        sisa: w3.u(120, 8) as u8,
+        cic: w4.s(22, 16) as f64 * p2(-29),
+        cis: w4.s(38, 16) as f64 * p2(-29),
+        toc: w4.u(54, 14) as f64 * 60.0,
+        af0: w4.s(68, 31) as f64 * p2(-34),
+        af1: w4.s(99, 21) as f64 * p2(-46),
+        af2: w4.s(120, 6) as f64 * p2(-59),
+        bgd_e1_e5a: w5.s(47, 10) as f64 * p2(-32),
+        bgd_e1_e5b: w5.s(57, 10) as f64 * p2(-32),
+        e5b_hs: w5.u(67, 2) as u8,
+        e1b_hs: w5.u(69, 2) as u8,
+        e5b_dvs: w5.u(71, 1) as u8,
+        e1b_dvs: w5.u(72, 1) as u8,
+    })
+}
+
+/// The almanacs words 7-10 complete: the first satellite's from words 7
+/// and 8, the second's from 8 and 9, the third's from 9 and 10, each pair
+/// of the same issue of almanac.
+fn almanacs(words: &[Option<BitReader>; 11]) -> Vec<GalileoAlmanac> {
+    let [.., w7, w8, w9, w10] = words;
+    let iod = |w: &BitReader| w.u(6, 4);
+    let pair = |a: &Option<BitReader>, b: &Option<BitReader>| match (a, b) {
+        (Some(a), Some(b)) if iod(a) == iod(b) => Some((a.clone(), b.clone())),
+        _ => None,
+    };
+    let mut out = Vec::new();
+    if let Some((w7, w8)) = pair(w7, w8) {
+        let sv = w7.u(22, 6);
+        out.push(almanac(sv, &w7, (&w7, 28), (&w7, 79), (&w7, 106), (&w8, 10)));
+    }
+    if let Some((w8, w9)) = pair(w8, w9) {
+        let sv = w8.u(43, 6);
+        out.push(almanac(sv, &w9, (&w8, 49), (&w8, 100), (&w9, 22), (&w9, 38)));
+    }
+    if let Some((w9, w10)) = pair(w9, w10) {
+        let sv = w9.u(71, 6);
+        out.push(almanac(sv, &w9, (&w9, 77), (&w10, 10), (&w10, 37), (&w10, 53)));
+    }
+    // SVID 0 marks an empty slot.
+    out.into_iter().flatten().collect()
+}
+
+/// One almanac from where its parts sit: the issue, week and reference time
+/// in `header` (word 7 or 9), then the orbit shape, the node, the mean
+/// anomaly and the clock.
+fn almanac(
+    sv_id: u64,
+    header: &BitReader,
+    (shape, s): (&BitReader, usize),
+    (node, n): (&BitReader, usize),
+    (anomaly, m): (&BitReader, usize),
+    (clock, c): (&BitReader, usize),
+) -> Option<GalileoAlmanac> {
+    if sv_id == 0 {
+        return None;
+    }
+    let sc = |raw: i64, exp: i32| raw as f64 * p2(exp) * PI;
+    Some(GalileoAlmanac {
+        sv_id: sv_id as u8,
+        iod_a: header.u(6, 4) as u8,
+        week: header.u(10, 2) as u8,
+        toa: header.u(12, 10) as f64 * 600.0,
+        delta_sqrt_a: shape.s(s, 13) as f64 * p2(-9),
+        e: shape.u(s + 13, 11) as f64 * p2(-16),
+        omega: sc(shape.s(s + 24, 16), -15),
+        delta_i: sc(shape.s(s + 40, 11), -14),
+        omega0: sc(node.s(n, 16), -15),
+        omega_dot: sc(node.s(n + 16, 11), -33),
+        m0: sc(anomaly.s(m, 16), -15),
+        af0: clock.s(c, 16) as f64 * p2(-19),
+        af1: clock.s(c + 16, 13) as f64 * p2(-38),
+        e5b_hs: clock.u(c + 29, 2) as u8,
+        e1b_hs: clock.u(c + 31, 2) as u8,
+    })
+}
@@ -0,0 +1,201 @@
+//! GLONASS L1/L2 navigation strings from RXM-SFRBX, following the GLONASS
+//! ICD (edition 5.1).
+//!
+//! Each SFRBX carries one 85-bit string in the top of words 0-2, idle bit
+//! first. Strings 1-4 give the immediate data (ephemeris and clock) of the
+//! transmitting satellite and string 5 the time scale corrections; strings
+//! 6-15 hold almanacs in pairs, each even string naming the slot it
+//! describes. Strings 14 and 15 of frame 5 carry other data, whose slot
+//! field reads 0; they are skipped. Signed values are sign and magnitude.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::Serialize;
+
+use crate::nav::{p2, BitReader, NavData};
+
+/// Immediate data of one satellite from strings 1-4, with the time scale
+/// corrections of the last string 5 if one has been received.
+///
+/// Positions are PZ-90 in metres, velocities in m/s and accelerations in
+/// m/s²; times of day are Moscow time in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GlonassEphemeris {
+    /// Orbital slot, from string 4.
+    pub slot: u8,
+    /// Frequency channel from the SFRBX `freqId`.
+    pub frequency_channel: Option<i8>,
+    /// Start of the frame within the day.
+    pub tk: f64,
+    /// Reference time of the immediate data within the day.
+    pub tb: f64,
+    /// Health flag Bn; bit 2 set means unhealthy.
+    pub health: u8,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+    pub acceleration: [f64; 3],
+    /// Relative frequency offset γn.
+    pub gamma_n: f64,
+    /// Clock offset τn from GLONASS time, seconds.
+    pub tau_n: f64,
+    /// L1 to L2 delay Δτn, seconds.
+    pub delta_tau_n: f64,
+    /// Age of the immediate data in days.
+    pub age: u8,
+    /// Accuracy index FT.
+    pub ft: u8,
+    /// Day within the four-year interval.
+    pub nt: u16,
+    /// Satellite type: 0 GLONASS, 1 GLONASS-M.
+    pub m: u8,
+    /// Four-year interval since 1996, from string 5.
+    pub n4: Option<u8>,
+    /// GLONASS to UTC(SU) correction τc, seconds, from string 5.
+    pub tau_c: Option<f64>,
+    /// GPS to GLONASS fractional offset τGPS, seconds, from string 5.
+    pub tau_gps: Option<f64>,
+}
+
+/// Almanac of one slot from an even/odd string pair.
+///
+/// `delta_i` is relative to 63 degrees and `delta_t` to a 43 200 s draconic
+/// period; angles are in radians.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GlonassAlmanac {
+    pub slot: u8,
+    /// Frequency channel HnA.
+    pub frequency_channel: i8,
+    /// Cn: the satellite is usable.
+    pub healthy: bool,
+    pub m: u8,
+    pub tau: f64,
+    /// Longitude of the first ascending node within the day λnA.
+    pub lambda: f64,
+    pub delta_i: f64,
+    pub e: f64,
+    pub omega: f64,
+    /// Time of that node crossing, seconds of day.
+    pub t_lambda: f64,
+    pub delta_t: f64,
+    pub delta_t_dot: f64,
+}
+
+/// Collects strings per satellite.
+#[derive(Default)]
+pub struct GlonassDecoder {
+    /// Strings 1-5 of the current frame, per slot as reported by the receiver.
+    immediate: HashMap<u8, [Option<BitReader>; 5]>,
+    /// The last even almanac string, waiting for its odd partner.
+    almanac: HashMap<u8, BitReader>,
+}
+
+impl GlonassDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the words of one string; returns the ephemeris once strings 1-4
+    /// of a frame are in, or the almanac an odd string completes.
+    pub fn push_words(&mut self, sv_id: u8, frequency_channel: Option<i8>, words: &[u32]) -> Vec<NavData> {
+        let Some(words) = words.get(..3) else {
+            return Vec::new();
+        };
+        let s = BitReader::from_words(words, 32);
+        let m = s.u(1, 4) as usize;
+        if s.u(0, 1) != 0 {
+            return Vec::new();
+        }
+        match m {
+            1..=5 => {
+                let strings = self.immediate.entry(sv_id).or_default();
+                if m == 1 {
+                    // A new frame: strings 2-4 must follow this string 1.
+                    strings[1..4].fill(None);
+                }
+                strings[m - 1] = Some(s);
+                (m == 4)
+                    .then(|| ephemeris(strings, frequency_channel))
+                    .flatten()
+                    .map(NavData::GlonassEphemeris)
+                    .into_iter()
+                    .collect()
+            }
+            6..=15 if m.is_multiple_of(2) => {
+                self.almanac.insert(sv_id, s);
+                Vec::new()
+            }
+            6..=15 => match self.almanac.remove(&sv_id) {
+                Some(even) if even.u(1, 4) as usize == m - 1 => {
+                    almanac(&even, &s).map(NavData::GlonassAlmanac).into_iter().collect()
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn ephemeris(strings: &[Option<BitReader>; 5], frequency_channel: Option<i8>) -> Option<GlonassEphemeris> {
+    let [Some(s1), Some(s2), Some(s3), Some(s4), s5] = strings else {
+        return None;
+    };
+    let km = |s: &BitReader, pos: usize| {
+        (
+            s.sm(pos + 29, 27) as f64 * p2(-11) * 1e3,
+            s.sm(pos, 24) as f64 * p2(-20) * 1e3,
+            s.sm(pos + 24, 5) as f64 * p2(-30) * 1e3,
+        )
+    };
+    let (x, vx, ax) = km(s1, 21);
+    let (y, vy, ay) = km(s2, 21);
+    let (z, vz, az) = km(s3, 21);
+    let slot = s4.u(70, 5) as u8;
+    if slot == 0 {
+        return None;
+    }
+    Some(GlonassEphemeris {
+        slot,
+        frequency_channel,
+        tk: (s1.u(9, 5) * 3_600 + s1.u(14, 6) * 60 + s1.u(20, 1) * 30) as f64,
+        tb: s2.u(9, 7) as f64 * 900.0,
+        health: s2.u(5, 3) as u8,
+        position: [x, y, z],
+        velocity: [vx, vy, vz],
+        acceleration: [ax, ay, az],
+        gamma_n: s3.sm(6, 11) as f64 * p2(-40),
+        tau_n: s4.sm(5, 22) as f64 * p2(-30),
+        delta_tau_n: s4.sm(27, 5) as f64 * p2(-30),
+        age: s4.u(32, 5) as u8,
+        ft: s4.u(52, 4) as u8,
+        nt: s4.u(59, 11) as u16,
+        m: s4.u(75, 2) as u8,
+        n4: s5.as_ref().map(|s5| s5.u(49, 5) as u8),
+        tau_c: s5.as_ref().map(|s5| s5.sm(16, 32) as f64 * p2(-31)),
+        tau_gps: s5.as_ref().map(|s5| s5.sm(54, 22) as f64 * p2(-30)),
+    })
+}
+
+fn almanac(even: &BitReader, odd: &BitReader) -> Option<GlonassAlmanac> {
+    let slot = even.u(8, 5) as u8;
+    if !(1..=24).contains(&slot) {
+        return None;
+    }
+    let sc = |raw: i64, exp: i32| raw as f64 * p2(exp) * PI;
+    // HnA 25-31 are channels -7 to -1.
+    let h = odd.u(71, 5) as i8;
+    Some(GlonassAlmanac {
+        slot,
+        frequency_channel: if h >= 25 { h - 32 } else { h },
+        healthy: even.u(5, 1) == 1,
+        m: even.u(6, 2) as u8,
+        tau: even.sm(13, 10) as f64 * p2(-18),
+        lambda: sc(even.sm(23, 21), -20),
+        delta_i: sc(even.sm(44, 18), -20),
+        e: even.u(62, 15) as f64 * p2(-20),
+        omega: sc(odd.sm(5, 16), -15),
+        t_lambda: odd.u(21, 21) as f64 * p2(-5),
+        delta_t: odd.sm(42, 22) as f64 * p2(-9),
+        delta_t_dot: odd.sm(64, 7) as f64 * p2(-14),
+    })
+}
@@ -0,0 +1,516 @@
+//! NTRIP client and correction monitoring for RTK bring-up.
+//!
+//! [`Caster`] names an `ntrip://` mountpoint and [`Caster::connect`] opens
+//! its RTCM3 stream over NTRIP 1 or 2, decoding chunked transfer. The bytes
+//! go to the receiver unchanged; [`RtcmParser`] only frames them so each
+//! forwarded message type can be counted. [`gga`] builds the position report
+//! VRS casters need, and [`CorrectionMonitor`] folds the receiver's RXM-RTCM
+//! and NAV-PVT output into per-type acceptance counts and fix-type
+//! transitions. Messages must be decoded with scaling and flag expansion
+//! enabled.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::decode::DecodedMessage;
+use crate::error::{Error, Result};
+use crate::track::FixQuality;
+use crate::value::Value;
+
+/// Port NTRIP casters listen on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 2101;
+
+/// RTCM3 preamble byte.
+pub const RTCM_PREAMBLE: u8 = 0xD3;
+
+const USER_AGENT: &str = concat!("NTRIP ubx-schema/", env!("CARGO_PKG_VERSION"));
+
+/// A caster mountpoint: `ntrip://[user:password@]host[:port]/MOUNTPOINT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caster {
+    pub host: String,
+    pub port: u16,
+    pub mountpoint: String,
+    pub credentials: Option<(String, String)>,
+    /// Speak NTRIP 1 (HTTP/1.0, `ICY 200 OK`) instead of NTRIP 2.
+    pub ntrip1: bool,
+}
+
+impl FromStr for Caster {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rest = s.strip_prefix("ntrip://").unwrap_or(s);
+        let (authority, mountpoint) = rest.split_once('/').unwrap_or((rest, ""));
+        let (credentials, address) = match authority.rsplit_once('@') {
+            Some((userinfo, address)) => {
+                let (user, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+                (Some((user.to_string(), password.to_string())), address)
+            }
+            None => (None, authority),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| Error::Ntrip(format!("invalid port in '{s}'")))?;
+                (host, port)
+            }
+            None => (address, DEFAULT_PORT),
+        };
+        if host.is_empty() {
+            return Err(Error::Ntrip(format!("no caster host in '{s}'")));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            mountpoint: mountpoint.trim_end_matches('/').to_string(),
+            credentials,
+            ntrip1: false,
+        })
+    }
+}
+
+impl fmt::Display for Caster {
+    /// The URL without the password.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ntrip://")?;
+        if let Some((user, _)) = &self.credentials {
+            write!(f, "{user}@")?;
+        }
+        write!(f, "{}:{}/{}", self.host, self.port, self.mountpoint)
+    }
+}
+
+impl Caster {
+    /// The HTTP request for the mountpoint, with `gga` as the initial
+    /// position if the caster needs one.
+    pub fn request(&self, gga: Option<&str>) -> String {
+        let mut request = if self.ntrip1 {
+            format!("GET /{} HTTP/1.0\r\nUser-Agent: {USER_AGENT}\r\n", self.mountpoint)
+        } else {
+            format!(
+                "GET /{} HTTP/1.1\r\nHost: {}:{}\r\nNtrip-Version: Ntrip/2.0\r\nUser-Agent: {USER_AGENT}\r\n",
+                self.mountpoint, self.host, self.port
+            )
+        };
+        if let Some((user, password)) = &self.credentials {
+            request += &format!("Authorization: Basic {}\r\n", base64(format!("{user}:{password}").as_bytes()));
+        }
+        match gga {
+            Some(gga) if !self.ntrip1 => request += &format!("Ntrip-GGA: {}\r\n", gga.trim_end()),
+            _ => {}
+        }
+        if !self.ntrip1 {
+            request += "Connection: close\r\n";
+        }
+        request += "\r\n";
+        if let (Some(gga), true) = (gga, self.ntrip1) {
+            request += &format!("{}\r\n", gga.trim_end());
+        }
+        request
+    }
+
+    /// Connect and request the mountpoint. `timeout` bounds the connection
+    /// and each later read, so the stream can be polled.
+    pub fn connect(&self, gga: Option<&str>, timeout: Duration) -> Result<NtripStream> {
+        if self.mountpoint.is_empty() {
+            return Err(Error::Ntrip("no mountpoint given; use sourcetable() to list them".into()));
+        }
+        let mut stream = self.open(gga, timeout)?;
+        let status = stream.status_line()?;
+        if status.starts_with("SOURCETABLE") {
+            return Err(Error::Ntrip(format!("caster has no mountpoint '{}'", self.mountpoint)));
+        }
+        stream.accept(&status)?;
+        Ok(stream)
+    }
+
+    /// The caster's source table (its `STR;`, `CAS;` and `NET;` lines).
+    pub fn sourcetable(&self, timeout: Duration) -> Result<Vec<String>> {
+        let mut caster = self.clone();
+        caster.mountpoint.clear();
+        let mut stream = caster.open(None, timeout)?;
+        let status = stream.status_line()?;
+        stream.accept(&status)?;
+        let mut text = String::new();
+        stream.read_to_string(&mut text)?;
+        Ok(text
+            .lines()
+            .take_while(|line| !line.starts_with("ENDSOURCETABLE"))
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn open(&self, gga: Option<&str>, timeout: Duration) -> Result<NtripStream> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::Ntrip(format!("cannot resolve {}", self.host)))?;
+        let mut tcp = TcpStream::connect_timeout(&address, timeout)?;
+        tcp.set_read_timeout(Some(timeout))?;
+        tcp.write_all(self.request(gga).as_bytes())?;
+        Ok(NtripStream {
+            reader: BufReader::new(tcp),
+            chunk: None,
+            line: Vec::new(),
+        })
+    }
+}
+
+/// Chunked transfer state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chunk {
+    Size,
+    Data(usize),
+    DataEnd,
+    Done,
+}
+
+/// The correction stream of a mountpoint, with any chunked transfer
+/// encoding removed. Reads time out as configured in [`Caster::connect`].
+pub struct NtripStream {
+    reader: BufReader<TcpStream>,
+    /// `None` unless the response is chunked.
+    chunk: Option<Chunk>,
+    /// A partly read line, kept across timed-out reads.
+    line: Vec<u8>,
+}
+
+impl NtripStream {
+    /// Send a GGA sentence, e.g. the receiver's latest position.
+    pub fn send_gga(&mut self, gga: &str) -> Result<()> {
+        let tcp = self.reader.get_mut();
+        tcp.write_all(format!("{}\r\n", gga.trim_end()).as_bytes())?;
+        Ok(())
+    }
+
+    fn status_line(&mut self) -> Result<String> {
+        self.line.clear();
+        self.reader.read_until(b'\n', &mut self.line)?;
+        let line = String::from_utf8_lossy(&self.line).trim().to_string();
+        self.line.clear();
+        Ok(line)
+    }
+
+    /// Check the status line and read the headers that follow it.
+    fn accept(&mut self, status: &str) -> Result<()> {
+        let mut words = status.split_whitespace();
+        let (protocol, code) = (words.next().unwrap_or(""), words.next().unwrap_or(""));
+        if code != "200" {
+            let reason = if code == "401" { "authorization failed" } else { "request refused" };
+            return Err(Error::Ntrip(format!("{reason}: {status}")));
+        }
+        if protocol == "ICY" {
+            // NTRIP 1 casters may send the data straight after the status line.
+            if self.reader.fill_buf()?.starts_with(b"\r\n") {
+                self.reader.consume(2);
+            }
+            return Ok(());
+        }
+        loop {
+            let header = self.status_line()?;
+            if header.is_empty() {
+                return Ok(());
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("transfer-encoding") && value.trim().eq_ignore_ascii_case("chunked") {
+                    self.chunk = Some(Chunk::Size);
+                }
+            }
+        }
+    }
+}
+
+impl Read for NtripStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.chunk {
+                None => return self.reader.read(buf),
+                Some(Chunk::Done) => return Ok(0),
+                Some(Chunk::Size | Chunk::DataEnd) => {
+                    if self.reader.read_until(b'\n', &mut self.line)? == 0 && !self.line.ends_with(b"\n") {
+                        return Ok(0);
+                    }
+                    if self.chunk == Some(Chunk::DataEnd) {
+                        self.chunk = Some(Chunk::Size);
+                    } else {
+                        let text = String::from_utf8_lossy(&self.line);
+                        let digits = text.split(';').next().unwrap_or("").trim();
+                        let size = usize::from_str_radix(digits, 16).map_err(|_| {
+                            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("bad chunk size '{digits}'"))
+                        })?;
+                        self.chunk = Some(if size == 0 { Chunk::Done } else { Chunk::Data(size) });
+                    }
+                    self.line.clear();
+                }
+                Some(Chunk::Data(remaining)) => {
+                    let len = remaining.min(buf.len());
+                    let n = self.reader.read(&mut buf[..len])?;
+                    if n == 0 {
+                        return Ok(0);
+                    }
+                    self.chunk = Some(if n == remaining { Chunk::DataEnd } else { Chunk::Data(remaining - n) });
+                    return Ok(n);
+                }
+            }
+        }
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// CRC-24Q as used by RTCM3, over the header and payload.
+pub fn crc24q(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= 0x0186_4CFB;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+/// One RTCM3 message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RtcmFrame {
+    /// DF002, the first 12 bits of the payload.
+    pub message_type: u16,
+    pub payload: Vec<u8>,
+}
+
+impl RtcmFrame {
+    /// The framed message: preamble, length, payload and CRC.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.payload.len();
+        let mut out = vec![RTCM_PREAMBLE, (len >> 8) as u8 & 0x03, len as u8];
+        out.extend_from_slice(&self.payload);
+        out.extend_from_slice(&crc24q(&out).to_be_bytes()[1..]);
+        out
+    }
+}
+
+/// Incremental RTCM3 framer; bytes between frames and frames failing their
+/// CRC are skipped and counted.
+#[derive(Debug, Default)]
+pub struct RtcmParser {
+    buf: Vec<u8>,
+    pub frames: u64,
+    pub crc_errors: u64,
+    pub skipped_bytes: u64,
+}
+
+impl RtcmParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Next complete frame, or `None` if more data is needed.
+    pub fn next_frame(&mut self) -> Option<RtcmFrame> {
+        loop {
+            let start = self.buf.iter().position(|b| *b == RTCM_PREAMBLE).unwrap_or(self.buf.len());
+            self.discard(start);
+            if self.buf.len() < 3 {
+                return None;
+            }
+            // The six bits above the length are reserved and zero.
+            let len = usize::from(self.buf[1] & 0x03) << 8 | usize::from(self.buf[2]);
+            if self.buf[1] & 0xFC != 0 || len < 2 {
+                self.discard(1);
+                continue;
+            }
+            if self.buf.len() < len + 6 {
+                return None;
+            }
+            let crc = u32::from_be_bytes([0, self.buf[len + 3], self.buf[len + 4], self.buf[len + 5]]);
+            if crc24q(&self.buf[..len + 3]) != crc {
+                self.crc_errors += 1;
+                self.discard(1);
+                continue;
+            }
+            let payload = self.buf[3..len + 3].to_vec();
+            self.buf.drain(..len + 6);
+            self.frames += 1;
+            return Some(RtcmFrame {
+                message_type: u16::from(payload[0]) << 4 | u16::from(payload[1] >> 4),
+                payload,
+            });
+        }
+    }
+
+    fn discard(&mut self, n: usize) {
+        self.skipped_bytes += n as u64;
+        self.buf.drain(..n);
+    }
+}
+
+/// A `$GPGGA` sentence with the position of a NAV-PVT, or `None` without a
+/// fix. NAV-PVT has no HDOP, so its PDOP is reported instead.
+pub fn gga(msg: &DecodedMessage) -> Option<String> {
+    let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+    let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+    let fix = FixQuality::from_pvt(int("fixType"), int("flags.diffSoln") == 1, int("flags.carrSoln"));
+    if !fix.has_position() || int("flags.gnssFixOK") != 1 {
+        return None;
+    }
+    let quality = match fix {
+        FixQuality::DeadReckoning => 6,
+        FixQuality::Dgnss => 2,
+        FixQuality::RtkFixed => 4,
+        FixQuality::RtkFloat => 5,
+        _ => 1,
+    };
+    let (lat, lon) = (num("lat")?, num("lon")?);
+    let (height, msl) = (num("height")? / 1_000.0, num("hMSL")? / 1_000.0);
+    let seconds = int("sec") as f64 + num("nano").unwrap_or(0.0) * 1e-9;
+    let body = format!(
+        "GPGGA,{:02}{:02}{:05.2},{},{},{},{:02},{:.1},{msl:.3},M,{:.3},M,,",
+        int("hour"),
+        int("min"),
+        seconds.max(0.0),
+        nmea_angle(lat, 2, 'N', 'S'),
+        nmea_angle(lon, 3, 'E', 'W'),
+        quality,
+        int("numSV"),
+        num("pDOP").unwrap_or(99.99),
+        height - msl,
+    );
+    Some(format!("${body}*{:02X}", nmea_checksum(&body)))
+}
+
+/// `ddmm.mmmmm,N` with `degree_digits` digits of degrees.
+fn nmea_angle(degrees: f64, degree_digits: usize, positive: char, negative: char) -> String {
+    let hemisphere = if degrees < 0.0 { negative } else { positive };
+    let degrees = degrees.abs();
+    let mut whole = degrees.trunc();
+    let mut minutes = ((degrees - whole) * 60.0 * 100_000.0).round() / 100_000.0;
+    if minutes >= 60.0 {
+        whole += 1.0;
+        minutes -= 60.0;
+    }
+    format!("{:0width$}{minutes:08.5},{hemisphere}", whole as u32, width = degree_digits)
+}
+
+/// XOR of the characters between `$` and `*`.
+pub fn nmea_checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
+
+/// What the receiver made of one RTCM message type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RtcmCounts {
+    /// Messages sent to the receiver.
+    pub forwarded: u64,
+    /// RXM-RTCM reports by `msgUsed`.
+    pub used: u64,
+    pub not_used: u64,
+    pub unknown: u64,
+    /// RXM-RTCM reports with `crcFailed` set.
+    pub crc_failed: u64,
+}
+
+/// A change of solution quality between two NAV-PVT epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixTransition {
+    pub itow: u32,
+    /// `None` for the first epoch seen.
+    pub from: Option<FixQuality>,
+    pub to: FixQuality,
+}
+
+impl fmt::Display for FixTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.from {
+            Some(from) => write!(f, "iTOW {}: {} -> {}", self.itow, from.label(), self.to.label()),
+            None => write!(f, "iTOW {}: {}", self.itow, self.to.label()),
+        }
+    }
+}
+
+/// Correction acceptance and fix history of an RTK session.
+#[derive(Debug, Clone, Default)]
+pub struct CorrectionMonitor {
+    /// Counts by RTCM message type.
+    pub types: BTreeMap<u16, RtcmCounts>,
+    pub transitions: Vec<FixTransition>,
+    fix: Option<FixQuality>,
+}
+
+impl CorrectionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a message sent to the receiver.
+    pub fn forwarded(&mut self, frame: &RtcmFrame) {
+        self.types.entry(frame.message_type).or_default().forwarded += 1;
+    }
+
+    /// Fold in a receiver message; returns the transition a NAV-PVT made, if any.
+    pub fn push(&mut self, msg: &DecodedMessage) -> Option<FixTransition> {
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        match msg.name.as_str() {
+            "UBX-RXM-RTCM" => {
+                let counts = self.types.entry(int("msgType") as u16).or_default();
+                match (int("flags.crcFailed"), int("flags.msgUsed")) {
+                    (1, _) => counts.crc_failed += 1,
+                    (_, 2) => counts.used += 1,
+                    (_, 1) => counts.not_used += 1,
+                    _ => counts.unknown += 1,
+                }
+                None
+            }
+            "UBX-NAV-PVT" => {
+                let fix = FixQuality::from_pvt(int("fixType"), int("flags.diffSoln") == 1, int("flags.carrSoln"));
+                if self.fix == Some(fix) {
+                    return None;
+                }
+                let transition = FixTransition {
+                    itow: msg.itow().unwrap_or(0),
+                    from: self.fix.replace(fix),
+                    to: fix,
+                };
+                self.transitions.push(transition);
+                Some(transition)
+            }
+            _ => None,
+        }
+    }
+
+    /// The solution quality of the latest NAV-PVT.
+    pub fn fix(&self) -> Option<FixQuality> {
+        self.fix
+    }
+
+    /// True once any epoch reached an RTK fixed solution.
+    pub fn reached_fixed(&self) -> bool {
+        self.transitions.iter().any(|t| t.to == FixQuality::RtkFixed)
+    }
+}
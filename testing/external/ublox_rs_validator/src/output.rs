@@ -0,0 +1,278 @@
+//! Writers for decoded messages: JSON array, NDJSON, CSV, gpsd JSON and
+//! InfluxDB line protocol.
+//!
+//! CSV output is long by default, a row per field. With a list of
+//! [`CsvField`]s it is wide instead: a row per message of the listed types
+//! and a column per listed field, e.g. `NAV-PVT.lat`, for spreadsheets.
+//!
+//! Given the host receive times `ubx-capture --timestamps` recorded
+//! ([`MessageWriter::with_host_times`]), JSON and NDJSON records carry a
+//! `host_time` in Unix seconds and long CSV a `host_time` row per message.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::decode::{DecodeOptions, DecodedMessage};
+use crate::error::{Error, Result};
+use crate::gpsd::GpsdTranslator;
+use crate::host_time::HostTimes;
+use crate::influx::LineEncoder;
+use crate::schema::{normalize_name, Schema};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A single JSON array.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+    /// Long-format CSV: one row per (frame, field).
+    Csv,
+    /// gpsd `TPV` and `SKY` reports, one per line (see [`crate::gpsd`]).
+    Gpsd,
+    /// InfluxDB line protocol (see [`crate::influx`]).
+    Influx,
+}
+
+impl Format {
+    /// `options` adjusted to what the format needs: gpsd reports and line
+    /// protocol are built from scaled values and named flag members.
+    pub fn decode_options(self, options: DecodeOptions) -> DecodeOptions {
+        match self {
+            Format::Gpsd | Format::Influx => DecodeOptions {
+                scale: true,
+                expand_flags: true,
+                ..options
+            },
+            _ => options,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            "csv" => Ok(Format::Csv),
+            "gpsd" => Ok(Format::Gpsd),
+            "influx" | "line-protocol" => Ok(Format::Influx),
+            other => Err(format!("unknown format '{other}' (expected json, ndjson, csv, gpsd or influx)")),
+        }
+    }
+}
+
+/// A column of wide CSV output: `MESSAGE.path`, e.g. `NAV-PVT.lat`,
+/// `NAV-PVT.flags.gnssFixOK` or `NAV-SAT.satellites[0].cno`. A variant
+/// alias such as `MGA-GPS-EPH.sqrtA` selects one variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvField {
+    /// Normalized message or variant alias name, e.g. `UBX-NAV-PVT`.
+    pub message: String,
+    pub path: String,
+}
+
+impl CsvField {
+    /// The column header, e.g. `NAV-PVT.lat`.
+    pub fn header(&self) -> String {
+        format!("{}.{}", self.message.strip_prefix("UBX-").unwrap_or(&self.message), self.path)
+    }
+
+    /// Check that the schema defines the message and the path's top-level
+    /// field, so a typo fails up front rather than as an empty column.
+    pub fn check(&self, schema: &Schema) -> Result<()> {
+        let def = schema
+            .message_by_name(&self.message)
+            .ok_or_else(|| Error::UnknownMessage(self.message.clone()))?;
+        let top = self.path.split(['.', '[']).next().unwrap_or_default();
+        let defined = def
+            .payload
+            .iter()
+            .chain(def.variants.iter().map(|v| &v.payload))
+            .any(|p| p.field(top).is_some() || p.repeated_groups.iter().any(|g| g.name == top));
+        if defined {
+            Ok(())
+        } else {
+            Err(Error::Schema(format!("{} has no field {top}", def.short_name())))
+        }
+    }
+
+    fn matches(&self, message: &DecodedMessage) -> bool {
+        self.message == message.name
+            || message.variant.as_ref().is_some_and(|v| self.message == format!("{}-{v}", message.name))
+    }
+}
+
+impl FromStr for CsvField {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().split_once('.') {
+            Some((message, path)) if !message.is_empty() && !path.is_empty() => Ok(Self {
+                message: normalize_name(message),
+                path: path.to_string(),
+            }),
+            _ => Err(format!("expected MESSAGE.field, e.g. NAV-PVT.lat, not '{s}'")),
+        }
+    }
+}
+
+/// A decoded message together with its position in the input.
+#[derive(Serialize)]
+pub(crate) struct Record<'a> {
+    pub(crate) offset: usize,
+    /// Host receive time, when the log has a timestamps CSV.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) host_time: Option<f64>,
+    #[serde(flatten)]
+    pub(crate) message: &'a DecodedMessage,
+}
+
+enum Inner<W: Write> {
+    Json { out: W, count: usize },
+    Ndjson(W),
+    Csv(Box<csv::Writer<W>>),
+    CsvFields { csv: Box<csv::Writer<W>>, fields: Vec<CsvField> },
+    Gpsd { out: W, translator: GpsdTranslator },
+    Influx { out: W, encoder: LineEncoder },
+}
+
+/// Streams decoded messages to `W` in the chosen format.
+pub struct MessageWriter<W: Write> {
+    inner: Inner<W>,
+    host_times: HostTimes,
+}
+
+impl<W: Write> MessageWriter<W> {
+    pub fn new(format: Format, out: W) -> Result<Self> {
+        let inner = match format {
+            Format::Json => Inner::Json { out, count: 0 },
+            Format::Ndjson => Inner::Ndjson(out),
+            Format::Csv => {
+                let mut csv = Box::new(csv::Writer::from_writer(out));
+                csv.write_record(["offset", "message", "field", "value"])
+                    .map_err(csv_error)?;
+                Inner::Csv(csv)
+            }
+            Format::Gpsd => return Self::gpsd("ubx", out),
+            Format::Influx => Inner::Influx {
+                out,
+                encoder: LineEncoder::new(),
+            },
+        };
+        Ok(Self { inner, host_times: HostTimes::default() })
+    }
+
+    /// A wide CSV writer: `offset`, `message`, then a column per field.
+    /// Messages without any of the fields are left out; cells of fields
+    /// the message doesn't have are empty.
+    pub fn csv_fields(fields: Vec<CsvField>, out: W) -> Result<Self> {
+        let mut csv = Box::new(csv::Writer::from_writer(out));
+        let header = ["offset".to_string(), "message".to_string()]
+            .into_iter()
+            .chain(fields.iter().map(CsvField::header));
+        csv.write_record(header).map_err(csv_error)?;
+        Ok(Self {
+            inner: Inner::CsvFields { csv, fields },
+            host_times: HostTimes::default(),
+        })
+    }
+
+    /// A [`Format::Gpsd`] writer reporting `device` as the source, starting
+    /// with the `VERSION`, `DEVICES` and `WATCH` preamble.
+    pub fn gpsd(device: &str, mut out: W) -> Result<Self> {
+        let translator = GpsdTranslator::new(device);
+        for report in translator.preamble() {
+            serde_json::to_writer(&mut out, &report)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(Self {
+            inner: Inner::Gpsd { out, translator },
+            host_times: HostTimes::default(),
+        })
+    }
+
+    /// Report the host receive time of each message from `host_times`,
+    /// looked up by the offset passed to [`MessageWriter::write`].
+    pub fn with_host_times(mut self, host_times: HostTimes) -> Self {
+        self.host_times = host_times;
+        self
+    }
+
+    pub fn write(&mut self, offset: usize, message: &DecodedMessage) -> Result<()> {
+        let host_time = self.host_times.at(offset);
+        let record = Record { offset, host_time, message };
+        match &mut self.inner {
+            Inner::Json { out, count } => {
+                out.write_all(if *count == 0 { b"[\n" } else { b",\n" })?;
+                serde_json::to_writer_pretty(&mut *out, &record)?;
+                *count += 1;
+            }
+            Inner::Ndjson(out) => {
+                serde_json::to_writer(&mut *out, &record)?;
+                out.write_all(b"\n")?;
+            }
+            Inner::Csv(csv) => {
+                let offset = offset.to_string();
+                if let Some(raw) = &message.payload_raw {
+                    csv.write_record([offset.as_str(), &message.name, "payload_raw", raw])
+                        .map_err(csv_error)?;
+                }
+                if let Some(time) = host_time {
+                    csv.write_record([offset.as_str(), &message.name, "host_time", &format!("{time:.6}")])
+                        .map_err(csv_error)?;
+                }
+                for (path, value) in message.fields.flatten() {
+                    csv.write_record([offset.as_str(), &message.name, &path, &value.to_string()])
+                        .map_err(csv_error)?;
+                }
+            }
+            Inner::CsvFields { csv, fields } => {
+                if !fields.iter().any(|f| f.matches(message)) {
+                    return Ok(());
+                }
+                let cells = fields.iter().map(|f| match f.matches(message).then(|| message.get(&f.path)).flatten() {
+                    Some(value) => value.to_string(),
+                    None => String::new(),
+                });
+                let row = [offset.to_string(), message.short_name().to_string()].into_iter().chain(cells);
+                csv.write_record(row).map_err(csv_error)?;
+            }
+            Inner::Gpsd { out, translator } => {
+                if let Some(report) = translator.translate(message) {
+                    serde_json::to_writer(&mut *out, &report)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+            Inner::Influx { out, encoder } => {
+                for line in encoder.lines(message) {
+                    writeln!(out, "{line}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Close any open structure and flush.
+    pub fn finish(self) -> Result<()> {
+        match self.inner {
+            Inner::Json { mut out, count } => {
+                out.write_all(if count == 0 { b"[]\n" } else { b"\n]\n" })?;
+                out.flush()?;
+            }
+            Inner::Ndjson(mut out) | Inner::Gpsd { mut out, .. } | Inner::Influx { mut out, .. } => out.flush()?,
+            Inner::Csv(mut csv) | Inner::CsvFields { mut csv, .. } => csv.flush()?,
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn csv_error(err: csv::Error) -> crate::error::Error {
+    match err.into_kind() {
+        csv::ErrorKind::Io(e) => e.into(),
+        other => std::io::Error::other(format!("{other:?}")).into(),
+    }
+}
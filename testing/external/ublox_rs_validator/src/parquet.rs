@@ -0,0 +1,717 @@
+//! Parquet export of decoded logs.
+//!
+//! [`ParquetWriter`] writes one Parquet file. To stay dependency-free it
+//! writes uncompressed, PLAIN-encoded data pages with one page per column
+//! chunk. Definition levels are RLE-encoded, and the footer uses Thrift's
+//! compact protocol. pandas, Polars, DuckDB and Spark all read that.
+//!
+//! [`ParquetExporter`] lays a decoded log out in the tables of
+//! [`sqlite`](crate::sqlite), in one of two [`Layout`]s:
+//!
+//! - [`Layout::PerMessage`] writes a directory with one file per message
+//!   type and variant (`nav_pvt.parquet`). Repeated groups get a file of
+//!   their own (`nav_sat_satellites.parquet`) with one row per element.
+//!   Rows are keyed by the frame's byte `offset`, plus `idx` for group
+//!   elements, so they join like the SQLite tables. Rows are written in
+//!   groups of [`ROW_GROUP_ROWS`].
+//! - [`Layout::Wide`] writes one file with a row per message. Columns are
+//!   `offset`, `message` (and `variant`), then every message's columns
+//!   prefixed with its table name (`nav_pvt_lat`). Repeated groups are JSON
+//!   text. A type's columns are null in other types' rows. The column set is
+//!   only known once the log has been read, so this layout holds the log in
+//!   memory.
+//!
+//! With [`ParquetExporter::with_host_times`] every table gets a `host_time`
+//! column after `offset` with the frame's host receive time
+//! ([`crate::host_time`]).
+//!
+//! Integers are `INT32`, or `INT64` for 32-bit unsigned and 64-bit fields.
+//! Floats and scaled values are `DOUBLE`, strings are `UTF8` and nested
+//! values are `JSON`. Every column except the keys is optional.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::decode::{DecodeOptions, DecodedMessage};
+use crate::error::{Error, Result};
+use crate::host_time::HostTimes;
+use crate::schema::{BaseType, Schema};
+use crate::sqlite::{Column, SqlType, Table};
+use crate::value::{Fields, Value};
+
+/// File magic at both ends of a Parquet file.
+pub const MAGIC: &[u8; 4] = b"PAR1";
+
+/// Rows per row group in [`Layout::PerMessage`] files.
+pub const ROW_GROUP_ROWS: usize = 65_536;
+
+/// Column types, each a Parquet physical type with its annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+    Int32,
+    Int64,
+    Double,
+    /// `BYTE_ARRAY` annotated `UTF8`.
+    Utf8,
+    /// `BYTE_ARRAY` annotated `JSON`.
+    Json,
+}
+
+impl ColumnType {
+    /// The type for a column of a [`Table`].
+    pub fn for_column(column: &Column) -> Self {
+        match (column.sql_type, column.base) {
+            (SqlType::Real, _) => ColumnType::Double,
+            (SqlType::Text, Some(_)) => ColumnType::Utf8,
+            (SqlType::Text, None) => ColumnType::Json,
+            (SqlType::Integer, Some(BaseType::U4 | BaseType::X4 | BaseType::I8) | None) => ColumnType::Int64,
+            (SqlType::Integer, Some(_)) => ColumnType::Int32,
+        }
+    }
+
+    /// Thrift `Type`.
+    fn physical(self) -> i32 {
+        match self {
+            ColumnType::Int32 => 1,
+            ColumnType::Int64 => 2,
+            ColumnType::Double => 5,
+            ColumnType::Utf8 | ColumnType::Json => 6,
+        }
+    }
+
+    /// Thrift `ConvertedType`.
+    fn converted(self) -> Option<i32> {
+        match self {
+            ColumnType::Utf8 => Some(0),
+            ColumnType::Json => Some(19),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetColumn {
+    pub name: String,
+    pub column_type: ColumnType,
+    /// Required columns never hold nulls; the others are optional.
+    pub required: bool,
+}
+
+impl ParquetColumn {
+    pub fn new(name: &str, column_type: ColumnType) -> Self {
+        Self {
+            name: name.to_string(),
+            column_type,
+            required: false,
+        }
+    }
+
+    pub fn required(name: &str, column_type: ColumnType) -> Self {
+        Self {
+            required: true,
+            ..Self::new(name, column_type)
+        }
+    }
+}
+
+/// One value of a column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Int(i64),
+    Double(f64),
+    Text(String),
+}
+
+impl Cell {
+    /// A decoded value as a cell of `column_type`; `None` when it has no
+    /// representation there.
+    pub fn from_value(value: &Value, column_type: ColumnType) -> Option<Self> {
+        match column_type {
+            ColumnType::Int32 | ColumnType::Int64 => value.as_i64().map(Cell::Int),
+            ColumnType::Double => value.as_f64().map(Cell::Double),
+            ColumnType::Utf8 => Some(Cell::Text(value.to_string())),
+            ColumnType::Json => serde_json::to_string(value).ok().map(Cell::Text),
+        }
+    }
+}
+
+/// Writer of Thrift compact-protocol structs.
+struct Thrift {
+    buf: Vec<u8>,
+    /// Last field ID of each open struct.
+    last: Vec<i16>,
+}
+
+impl Thrift {
+    const I32: u8 = 5;
+    const I64: u8 = 6;
+    const BINARY: u8 = 8;
+    const LIST: u8 = 9;
+    const STRUCT: u8 = 12;
+
+    fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            last: vec![0],
+        }
+    }
+
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    fn zigzag(&mut self, v: i64) {
+        self.varint(((v << 1) ^ (v >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, field_type: u8) {
+        let last = self.last.last_mut().expect("open struct");
+        match id - *last {
+            delta @ 1..=15 => self.buf.push((delta as u8) << 4 | field_type),
+            _ => {
+                self.buf.push(field_type);
+                let id = i64::from(id);
+                self.zigzag(id);
+            }
+        }
+        *self.last.last_mut().expect("open struct") = id;
+    }
+
+    fn i32(&mut self, id: i16, v: i32) {
+        self.field(id, Self::I32);
+        self.zigzag(i64::from(v));
+    }
+
+    fn i64(&mut self, id: i16, v: i64) {
+        self.field(id, Self::I64);
+        self.zigzag(v);
+    }
+
+    fn binary(&mut self, id: i16, bytes: &[u8]) {
+        self.field(id, Self::BINARY);
+        self.bytes(bytes);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn list(&mut self, id: i16, element_type: u8, len: usize) {
+        self.field(id, Self::LIST);
+        if len < 15 {
+            self.buf.push((len as u8) << 4 | element_type);
+        } else {
+            self.buf.push(0xF0 | element_type);
+            self.varint(len as u64);
+        }
+    }
+
+    /// Open a struct field, or with `None` a struct element of a list.
+    fn begin(&mut self, id: Option<i16>) {
+        if let Some(id) = id {
+            self.field(id, Self::STRUCT);
+        }
+        self.last.push(0);
+    }
+
+    fn end(&mut self) {
+        self.buf.push(0);
+        self.last.pop();
+    }
+
+    /// Close the top-level struct.
+    fn finish(mut self) -> Vec<u8> {
+        self.buf.push(0);
+        self.buf
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChunkMeta {
+    offset: u64,
+    size: u64,
+    values: usize,
+}
+
+#[derive(Debug, Clone)]
+struct RowGroupMeta {
+    chunks: Vec<ChunkMeta>,
+    rows: usize,
+}
+
+/// Writes one Parquet file, a row group at a time.
+pub struct ParquetWriter<W: Write> {
+    out: W,
+    columns: Vec<ParquetColumn>,
+    position: u64,
+    row_groups: Vec<RowGroupMeta>,
+    pub rows: usize,
+}
+
+impl<W: Write> ParquetWriter<W> {
+    pub fn new(mut out: W, columns: Vec<ParquetColumn>) -> Result<Self> {
+        out.write_all(MAGIC)?;
+        Ok(Self {
+            out,
+            columns,
+            position: MAGIC.len() as u64,
+            row_groups: Vec::new(),
+            rows: 0,
+        })
+    }
+
+    pub fn columns(&self) -> &[ParquetColumn] {
+        &self.columns
+    }
+
+    /// Write a row group from one vector of cells per column, all of the
+    /// same length. A cell that doesn't match its column's type is null.
+    pub fn write_row_group(&mut self, data: &[Vec<Option<Cell>>]) -> Result<()> {
+        if data.len() != self.columns.len() {
+            return Err(Error::Encode(format!(
+                "row group has {} columns, the file {}",
+                data.len(),
+                self.columns.len()
+            )));
+        }
+        let rows = data.first().map_or(0, Vec::len);
+        if data.iter().any(|c| c.len() != rows) {
+            return Err(Error::Encode("row group columns differ in length".into()));
+        }
+        if rows == 0 {
+            return Ok(());
+        }
+        let mut chunks = Vec::with_capacity(data.len());
+        for (column, cells) in self.columns.iter().zip(data) {
+            let page = data_page(column, cells)?;
+            let mut header = Thrift::new();
+            header.i32(1, 0); // DATA_PAGE
+            header.i32(2, page.len() as i32);
+            header.i32(3, page.len() as i32);
+            header.begin(Some(5));
+            header.i32(1, rows as i32);
+            header.i32(2, 0); // PLAIN
+            header.i32(3, 3); // RLE
+            header.i32(4, 3);
+            header.end();
+            let header = header.finish();
+            self.out.write_all(&header)?;
+            self.out.write_all(&page)?;
+            let size = (header.len() + page.len()) as u64;
+            chunks.push(ChunkMeta {
+                offset: self.position,
+                size,
+                values: rows,
+            });
+            self.position += size;
+        }
+        self.row_groups.push(RowGroupMeta { chunks, rows });
+        self.rows += rows;
+        Ok(())
+    }
+
+    /// Write the footer and flush.
+    pub fn finish(mut self) -> Result<W> {
+        let mut meta = Thrift::new();
+        meta.i32(1, 1);
+        meta.list(2, Thrift::STRUCT, self.columns.len() + 1);
+        meta.begin(None);
+        meta.binary(4, b"schema");
+        meta.i32(5, self.columns.len() as i32);
+        meta.end();
+        for column in &self.columns {
+            meta.begin(None);
+            meta.i32(1, column.column_type.physical());
+            meta.i32(3, i32::from(!column.required));
+            meta.binary(4, column.name.as_bytes());
+            if let Some(converted) = column.column_type.converted() {
+                meta.i32(6, converted);
+            }
+            meta.end();
+        }
+        meta.i64(3, self.rows as i64);
+        meta.list(4, Thrift::STRUCT, self.row_groups.len());
+        for group in &self.row_groups {
+            meta.begin(None);
+            meta.list(1, Thrift::STRUCT, group.chunks.len());
+            for (column, chunk) in self.columns.iter().zip(&group.chunks) {
+                meta.begin(None);
+                meta.i64(2, chunk.offset as i64);
+                meta.begin(Some(3));
+                meta.i32(1, column.column_type.physical());
+                meta.list(2, Thrift::I32, 2);
+                meta.zigzag(0); // PLAIN
+                meta.zigzag(3); // RLE
+                meta.list(3, Thrift::BINARY, 1);
+                meta.bytes(column.name.as_bytes());
+                meta.i32(4, 0); // UNCOMPRESSED
+                meta.i64(5, chunk.values as i64);
+                meta.i64(6, chunk.size as i64);
+                meta.i64(7, chunk.size as i64);
+                meta.i64(9, chunk.offset as i64);
+                meta.end();
+                meta.end();
+            }
+            meta.i64(2, group.chunks.iter().map(|c| c.size as i64).sum());
+            meta.i64(3, group.rows as i64);
+            meta.end();
+        }
+        meta.binary(6, concat!("ubx_schema ", env!("CARGO_PKG_VERSION")).as_bytes());
+        let meta = meta.finish();
+        self.out.write_all(&meta)?;
+        self.out.write_all(&(meta.len() as u32).to_le_bytes())?;
+        self.out.write_all(MAGIC)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// A data page: definition levels for optional columns, then the PLAIN
+/// values of the non-null cells.
+fn data_page(column: &ParquetColumn, cells: &[Option<Cell>]) -> Result<Vec<u8>> {
+    let mut values = Vec::new();
+    let mut present = Vec::with_capacity(cells.len());
+    for cell in cells {
+        let ok = match (column.column_type, cell) {
+            (ColumnType::Int32, Some(Cell::Int(v))) => match i32::try_from(*v) {
+                Ok(v) => {
+                    values.extend_from_slice(&v.to_le_bytes());
+                    true
+                }
+                Err(_) => false,
+            },
+            (ColumnType::Int64, Some(Cell::Int(v))) => {
+                values.extend_from_slice(&v.to_le_bytes());
+                true
+            }
+            (ColumnType::Double, Some(Cell::Double(v))) => {
+                values.extend_from_slice(&v.to_le_bytes());
+                true
+            }
+            (ColumnType::Double, Some(Cell::Int(v))) => {
+                values.extend_from_slice(&(*v as f64).to_le_bytes());
+                true
+            }
+            (ColumnType::Utf8 | ColumnType::Json, Some(Cell::Text(s))) => {
+                values.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                values.extend_from_slice(s.as_bytes());
+                true
+            }
+            _ => false,
+        };
+        if !ok && column.required {
+            return Err(Error::Encode(format!("null in required column {}", column.name)));
+        }
+        present.push(ok);
+    }
+    if column.required {
+        return Ok(values);
+    }
+    // RLE runs of bit-width 1: a varint run length shifted left once, then
+    // the level in one byte.
+    let mut levels = Vec::new();
+    let mut i = 0;
+    while i < present.len() {
+        let run = present[i..].iter().take_while(|&&p| p == present[i]).count();
+        let mut header = (run as u64) << 1;
+        while header >= 0x80 {
+            levels.push(header as u8 | 0x80);
+            header >>= 7;
+        }
+        levels.push(header as u8);
+        levels.push(u8::from(present[i]));
+        i += run;
+    }
+    let mut page = Vec::with_capacity(4 + levels.len() + values.len());
+    page.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+    page.extend_from_slice(&levels);
+    page.extend_from_slice(&values);
+    Ok(page)
+}
+
+/// How [`ParquetExporter`] lays out a log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Layout {
+    #[default]
+    PerMessage,
+    Wide,
+}
+
+impl Layout {
+    pub fn name(self) -> &'static str {
+        match self {
+            Layout::PerMessage => "per-message",
+            Layout::Wide => "wide",
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "per-message" | "message" | "tables" => Ok(Layout::PerMessage),
+            "wide" | "unified" => Ok(Layout::Wide),
+            other => Err(format!("unknown layout '{other}' (expected per-message or wide)")),
+        }
+    }
+}
+
+/// The buffered rows of one output file.
+struct TableFile {
+    path: PathBuf,
+    columns: Vec<ParquetColumn>,
+    /// Column-major.
+    data: Vec<Vec<Option<Cell>>>,
+    writer: Option<ParquetWriter<BufWriter<File>>>,
+}
+
+impl TableFile {
+    fn new(path: PathBuf, columns: Vec<ParquetColumn>) -> Self {
+        let data = vec![Vec::new(); columns.len()];
+        Self {
+            path,
+            columns,
+            data,
+            writer: None,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.data.first().map_or(0, Vec::len)
+    }
+
+    fn push(&mut self, row: Vec<Option<Cell>>) {
+        for (column, cell) in self.data.iter_mut().zip(row) {
+            column.push(cell);
+        }
+    }
+
+    /// Write the buffered rows as row groups of at most [`ROW_GROUP_ROWS`].
+    fn flush(&mut self) -> Result<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let out = BufWriter::new(File::create(&self.path)?);
+                self.writer.insert(ParquetWriter::new(out, self.columns.clone())?)
+            }
+        };
+        while !self.data[0].is_empty() {
+            let n = self.data[0].len().min(ROW_GROUP_ROWS);
+            let group: Vec<Vec<Option<Cell>>> = self.data.iter_mut().map(|c| c.drain(..n).collect()).collect();
+            writer.write_row_group(&group)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<PathBuf> {
+        self.flush()?;
+        if let Some(writer) = self.writer {
+            writer.finish()?;
+        }
+        Ok(self.path)
+    }
+}
+
+/// Cells for `columns` from a message's or group element's fields.
+fn cells<'a>(columns: &'a [Column], fields: &'a Fields) -> impl Iterator<Item = Option<Cell>> + 'a {
+    columns
+        .iter()
+        .map(|c| fields.get_path(&c.path).and_then(|v| Cell::from_value(v, ColumnType::for_column(c))))
+}
+
+fn parquet_columns(columns: &[Column], prefix: &str) -> Vec<ParquetColumn> {
+    columns
+        .iter()
+        .map(|c| ParquetColumn::new(&format!("{prefix}{}", c.name), ColumnType::for_column(c)))
+        .collect()
+}
+
+/// Writes a decoded log to Parquet files.
+pub struct ParquetExporter<'s> {
+    schema: &'s Schema,
+    options: DecodeOptions,
+    layout: Layout,
+    output: PathBuf,
+    /// By message name and variant; `None` for messages without a table.
+    tables: BTreeMap<(String, Option<String>), Option<Table>>,
+    /// Per-message layout: by table name.
+    files: BTreeMap<String, TableFile>,
+    /// Wide layout: the one table, and where each message table's columns
+    /// start in it.
+    wide: Option<TableFile>,
+    wide_start: BTreeMap<String, usize>,
+    host_times: Option<HostTimes>,
+    pub messages: usize,
+}
+
+impl<'s> ParquetExporter<'s> {
+    /// Export to `output`: a directory for [`Layout::PerMessage`], created
+    /// if needed, or a file for [`Layout::Wide`]. `options` must be those
+    /// the messages are decoded with.
+    pub fn new(schema: &'s Schema, options: DecodeOptions, layout: Layout, output: &Path) -> Result<Self> {
+        let wide = match layout {
+            Layout::PerMessage => {
+                fs::create_dir_all(output)?;
+                None
+            }
+            Layout::Wide => Some(TableFile::new(
+                output.to_path_buf(),
+                vec![
+                    ParquetColumn::required("offset", ColumnType::Int64),
+                    ParquetColumn::required("message", ColumnType::Utf8),
+                    ParquetColumn::new("variant", ColumnType::Utf8),
+                ],
+            )),
+        };
+        Ok(Self {
+            schema,
+            options,
+            layout,
+            output: output.to_path_buf(),
+            tables: BTreeMap::new(),
+            files: BTreeMap::new(),
+            wide,
+            wide_start: BTreeMap::new(),
+            host_times: None,
+            messages: 0,
+        })
+    }
+
+    /// Add a `host_time` column of the frames' times in `host_times`,
+    /// looked up by the offset passed to [`ParquetExporter::write`]. Call
+    /// before writing.
+    pub fn with_host_times(mut self, host_times: HostTimes) -> Self {
+        if let (None, Some(wide)) = (&self.host_times, self.wide.as_mut()) {
+            wide.columns.insert(1, ParquetColumn::new("host_time", ColumnType::Double));
+            wide.data.insert(1, Vec::new());
+        }
+        self.host_times = Some(host_times);
+        self
+    }
+
+    /// `offset`, then `host_time` if there is one.
+    fn key_columns(&self) -> Vec<ParquetColumn> {
+        let mut columns = vec![ParquetColumn::required("offset", ColumnType::Int64)];
+        if self.host_times.is_some() {
+            columns.push(ParquetColumn::new("host_time", ColumnType::Double));
+        }
+        columns
+    }
+
+    /// Add a decoded message found at byte `offset`; messages the schema
+    /// did not parse are skipped.
+    pub fn write(&mut self, offset: usize, msg: &DecodedMessage) -> Result<()> {
+        if !msg.parsed {
+            return Ok(());
+        }
+        let key = (msg.name.clone(), msg.variant.clone());
+        let key_columns = self.key_columns();
+        let (schema, options) = (self.schema, self.options);
+        let table = self
+            .tables
+            .entry(key)
+            .or_insert_with(|| Table::for_message(schema, msg, options));
+        let Some(table) = table else {
+            return Ok(());
+        };
+        let mut keys = vec![Some(Cell::Int(offset as i64))];
+        if let Some(times) = &self.host_times {
+            keys.push(times.at(offset).map(Cell::Double));
+        }
+        match self.layout {
+            Layout::PerMessage => {
+                let file = self.files.entry(table.name.clone()).or_insert_with(|| {
+                    let mut columns = key_columns.clone();
+                    columns.extend(parquet_columns(&table.columns, ""));
+                    TableFile::new(self.output.join(format!("{}.parquet", table.name)), columns)
+                });
+                file.push(keys.iter().cloned().chain(cells(&table.columns, &msg.fields)).collect());
+                if file.rows() >= ROW_GROUP_ROWS {
+                    file.flush()?;
+                }
+                for (group, child) in &table.groups {
+                    let Some(items) = msg.get(group).and_then(Value::as_array) else {
+                        continue;
+                    };
+                    let file = self.files.entry(child.name.clone()).or_insert_with(|| {
+                        let mut columns = key_columns.clone();
+                        columns.push(ParquetColumn::required("idx", ColumnType::Int32));
+                        columns.extend(parquet_columns(&child.columns, ""));
+                        TableFile::new(self.output.join(format!("{}.parquet", child.name)), columns)
+                    });
+                    for (i, record) in items.iter().filter_map(Value::as_record).enumerate() {
+                        let key = keys.iter().cloned().chain([Some(Cell::Int(i as i64))]);
+                        file.push(key.chain(cells(&child.columns, record)).collect());
+                    }
+                    if file.rows() >= ROW_GROUP_ROWS {
+                        file.flush()?;
+                    }
+                }
+            }
+            Layout::Wide => {
+                let wide = self.wide.as_mut().expect("wide layout");
+                let start = *self.wide_start.entry(table.name.clone()).or_insert_with(|| {
+                    let start = wide.columns.len();
+                    let prefix = format!("{}_", table.name);
+                    let mut columns = parquet_columns(&table.columns, &prefix);
+                    columns.extend(
+                        table
+                            .groups
+                            .iter()
+                            .map(|(_, child)| ParquetColumn::new(&child.name, ColumnType::Json)),
+                    );
+                    let rows = wide.rows();
+                    for column in columns {
+                        wide.columns.push(column);
+                        wide.data.push(vec![None; rows]);
+                    }
+                    start
+                });
+                let mut row = vec![None; wide.columns.len()];
+                let n = keys.len();
+                row[..n].clone_from_slice(&keys);
+                row[n] = Some(Cell::Text(msg.short_name().to_string()));
+                row[n + 1] = msg.variant.clone().map(Cell::Text);
+                let group_cells = table.groups.iter().map(|(group, _)| {
+                    msg.get(group)
+                        .and_then(|v| Cell::from_value(v, ColumnType::Json))
+                });
+                for (slot, cell) in row[start..].iter_mut().zip(cells(&table.columns, &msg.fields).chain(group_cells)) {
+                    *slot = cell;
+                }
+                wide.push(row);
+            }
+        }
+        self.messages += 1;
+        Ok(())
+    }
+
+    /// Write the remaining rows and the footers; the files written.
+    pub fn finish(self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for (_, file) in self.files {
+            paths.push(file.finish()?);
+        }
+        if let Some(wide) = self.wide {
+            paths.push(wide.finish()?);
+        }
+        Ok(paths)
+    }
+}
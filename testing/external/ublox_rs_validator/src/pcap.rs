@@ -0,0 +1,460 @@
+//! Extract UBX byte streams from pcap and pcapng captures.
+//!
+//! Supported link layers are Ethernet, Linux cooked (SLL/SLL2), BSD loopback
+//! and raw IP carrying TCP or UDP, plus USB captures from Linux usbmon and
+//! Windows USBPcap. Each TCP/UDP flow or USB endpoint is a separate stream;
+//! TCP segments are reordered by sequence number before framing.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::error::{Error, Result};
+use crate::frame::{Frame, FrameParser};
+
+const LINKTYPE_NULL: u16 = 0;
+const LINKTYPE_ETHERNET: u16 = 1;
+const LINKTYPE_RAW: u16 = 101;
+const LINKTYPE_LINUX_SLL: u16 = 113;
+const LINKTYPE_USB_LINUX: u16 = 189;
+const LINKTYPE_USB_LINUX_MMAPPED: u16 = 220;
+const LINKTYPE_IPV4: u16 = 228;
+const LINKTYPE_IPV6: u16 = 229;
+const LINKTYPE_USBPCAP: u16 = 249;
+const LINKTYPE_LINUX_SLL2: u16 = 276;
+
+/// Out-of-order TCP segments held per flow before a gap is skipped.
+const MAX_PENDING_SEGMENTS: usize = 64;
+
+/// One captured packet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    /// Capture time in seconds since the Unix epoch.
+    pub timestamp: f64,
+    pub link_type: u16,
+    pub data: Vec<u8>,
+}
+
+/// Identifies the byte stream a packet's payload belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamKey {
+    Tcp { src: SocketAddr, dst: SocketAddr },
+    Udp { src: SocketAddr, dst: SocketAddr },
+    /// `endpoint` includes the direction bit (0x80 = device to host).
+    Usb { bus: u16, device: u16, endpoint: u8 },
+}
+
+impl fmt::Display for StreamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamKey::Tcp { src, dst } => write!(f, "tcp {src} -> {dst}"),
+            StreamKey::Udp { src, dst } => write!(f, "udp {src} -> {dst}"),
+            StreamKey::Usb { bus, device, endpoint } => {
+                let dir = if endpoint & 0x80 != 0 { "in" } else { "out" };
+                write!(f, "usb {bus}.{device} ep {:#04x} {dir}", endpoint)
+            }
+        }
+    }
+}
+
+/// Transport payload of a packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub key: StreamKey,
+    /// TCP sequence number of the first payload byte.
+    pub seq: Option<u32>,
+    pub data: &'a [u8],
+}
+
+/// Read all packets from a pcap or pcapng file.
+pub fn read_packets(data: &[u8]) -> Result<Vec<Packet>> {
+    match data.get(..4) {
+        Some([0x0A, 0x0D, 0x0D, 0x0A]) => read_pcapng(data),
+        Some(_) => read_pcap(data),
+        None => Err(capture_error("file too short")),
+    }
+}
+
+fn capture_error(msg: impl Into<String>) -> Error {
+    Error::Capture(msg.into())
+}
+
+/// Little- or big-endian integer reads.
+#[derive(Clone, Copy)]
+struct Endian(bool);
+
+impl Endian {
+    fn u16(self, b: &[u8], at: usize) -> Option<u16> {
+        let v: [u8; 2] = b.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.0 { u16::from_le_bytes(v) } else { u16::from_be_bytes(v) })
+    }
+
+    fn u32(self, b: &[u8], at: usize) -> Option<u32> {
+        let v: [u8; 4] = b.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.0 { u32::from_le_bytes(v) } else { u32::from_be_bytes(v) })
+    }
+}
+
+fn read_pcap(data: &[u8]) -> Result<Vec<Packet>> {
+    let (endian, nanos) = match data[..4] {
+        [0xD4, 0xC3, 0xB2, 0xA1] => (Endian(true), false),
+        [0xA1, 0xB2, 0xC3, 0xD4] => (Endian(false), false),
+        [0x4D, 0x3C, 0xB2, 0xA1] => (Endian(true), true),
+        [0xA1, 0xB2, 0x3C, 0x4D] => (Endian(false), true),
+        _ => return Err(capture_error("not a pcap or pcapng file")),
+    };
+    let link_type = endian
+        .u32(data, 20)
+        .ok_or_else(|| capture_error("truncated pcap header"))? as u16;
+    let divisor = if nanos { 1e9 } else { 1e6 };
+
+    let mut packets = Vec::new();
+    let mut pos = 24;
+    while pos + 16 <= data.len() {
+        let sec = endian.u32(data, pos).unwrap_or(0);
+        let frac = endian.u32(data, pos + 4).unwrap_or(0);
+        let caplen = endian.u32(data, pos + 8).unwrap_or(0) as usize;
+        let start = pos + 16;
+        let Some(bytes) = data.get(start..start + caplen) else {
+            break; // truncated final record
+        };
+        packets.push(Packet {
+            timestamp: f64::from(sec) + f64::from(frac) / divisor,
+            link_type,
+            data: bytes.to_vec(),
+        });
+        pos = start + caplen;
+    }
+    Ok(packets)
+}
+
+struct Interface {
+    link_type: u16,
+    /// Timestamp units per second.
+    resolution: f64,
+}
+
+fn read_pcapng(data: &[u8]) -> Result<Vec<Packet>> {
+    let mut packets = Vec::new();
+    let mut interfaces: Vec<Interface> = Vec::new();
+    let mut endian = Endian(true);
+    let mut pos = 0;
+    while pos + 12 <= data.len() {
+        let block_type = endian.u32(data, pos).unwrap_or(0);
+        if block_type == 0x0A0D_0D0A {
+            // Section header: byte-order magic decides endianness for the section.
+            endian = match data.get(pos + 8..pos + 12) {
+                Some([0x4D, 0x3C, 0x2B, 0x1A]) => Endian(true),
+                Some([0x1A, 0x2B, 0x3C, 0x4D]) => Endian(false),
+                _ => return Err(capture_error("bad pcapng byte-order magic")),
+            };
+            interfaces.clear();
+        }
+        let len = endian.u32(data, pos + 4).unwrap_or(0) as usize;
+        if len < 12 || pos + len > data.len() {
+            break;
+        }
+        let block = &data[pos + 8..pos + len - 4];
+        match block_type {
+            // Interface description
+            1 => interfaces.push(Interface {
+                link_type: endian.u16(block, 0).unwrap_or(0),
+                resolution: if_tsresol(endian, block.get(8..).unwrap_or_default()),
+            }),
+            // Enhanced packet
+            6 => {
+                let iface = endian.u32(block, 0).unwrap_or(0) as usize;
+                let high = endian.u32(block, 4).unwrap_or(0);
+                let low = endian.u32(block, 8).unwrap_or(0);
+                let caplen = endian.u32(block, 12).unwrap_or(0) as usize;
+                let (link_type, resolution) = interfaces
+                    .get(iface)
+                    .map_or((LINKTYPE_ETHERNET, 1e6), |i| (i.link_type, i.resolution));
+                if let Some(bytes) = block.get(20..20 + caplen) {
+                    let ticks = (u64::from(high) << 32) | u64::from(low);
+                    packets.push(Packet {
+                        timestamp: ticks as f64 / resolution,
+                        link_type,
+                        data: bytes.to_vec(),
+                    });
+                }
+            }
+            // Simple packet: no timestamp, always interface 0
+            3 => {
+                let origlen = endian.u32(block, 0).unwrap_or(0) as usize;
+                let link_type = interfaces.first().map_or(LINKTYPE_ETHERNET, |i| i.link_type);
+                if let Some(bytes) = block.get(4..(4 + origlen).min(block.len())) {
+                    packets.push(Packet {
+                        timestamp: 0.0,
+                        link_type,
+                        data: bytes.to_vec(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        pos += len;
+    }
+    Ok(packets)
+}
+
+/// Decode the `if_tsresol` option of an interface description block.
+fn if_tsresol(endian: Endian, mut options: &[u8]) -> f64 {
+    while options.len() >= 4 {
+        let code = endian.u16(options, 0).unwrap_or(0);
+        let len = endian.u16(options, 2).unwrap_or(0) as usize;
+        if code == 0 {
+            break;
+        }
+        if code == 9 && len >= 1 && options.len() > 4 {
+            let v = options[4];
+            let exp = i32::from(v & 0x7F);
+            return if v & 0x80 != 0 { 2f64.powi(exp) } else { 10f64.powi(exp) };
+        }
+        options = options.get(4 + len.div_ceil(4) * 4..).unwrap_or_default();
+    }
+    1e6
+}
+
+fn be16(d: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(d.get(at..at + 2)?.try_into().ok()?))
+}
+
+/// Locate the transport payload of a packet, if it carries one we understand.
+pub fn segment(packet: &Packet) -> Option<Segment<'_>> {
+    let d = packet.data.as_slice();
+    match packet.link_type {
+        LINKTYPE_ETHERNET => {
+            let mut ethertype = be16(d, 12)?;
+            let mut at = 14;
+            while ethertype == 0x8100 || ethertype == 0x88A8 {
+                ethertype = be16(d, at + 2)?;
+                at += 4;
+            }
+            ip(ethertype, d.get(at..)?)
+        }
+        LINKTYPE_LINUX_SLL => ip(be16(d, 14)?, d.get(16..)?),
+        LINKTYPE_LINUX_SLL2 => ip(be16(d, 0)?, d.get(20..)?),
+        LINKTYPE_NULL => {
+            // Address family in the capturing host's byte order.
+            let family = u32::from_le_bytes(d.get(..4)?.try_into().ok()?);
+            let family = if family > 0xFFFF { family.swap_bytes() } else { family };
+            let ethertype = if family == 2 { 0x0800 } else { 0x86DD };
+            ip(ethertype, d.get(4..)?)
+        }
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => match d.first()? >> 4 {
+            4 => ip(0x0800, d),
+            6 => ip(0x86DD, d),
+            _ => None,
+        },
+        LINKTYPE_USB_LINUX | LINKTYPE_USB_LINUX_MMAPPED => usbmon(packet.link_type, d),
+        LINKTYPE_USBPCAP => usbpcap(d),
+        _ => None,
+    }
+}
+
+fn ip(ethertype: u16, d: &[u8]) -> Option<Segment<'_>> {
+    let (src, dst, protocol, body) = match ethertype {
+        0x0800 => {
+            let ihl = usize::from(d.first()? & 0x0F) * 4;
+            let total = usize::from(be16(d, 2)?);
+            // Fragments other than the first can't be attributed to a port.
+            let frag_offset = be16(d, 6)? & 0x1FFF;
+            if frag_offset != 0 {
+                return None;
+            }
+            let src = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(d.get(12..16)?).ok()?));
+            let dst = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(d.get(16..20)?).ok()?));
+            (src, dst, *d.get(9)?, d.get(ihl..total.min(d.len()))?)
+        }
+        0x86DD => {
+            let payload_len = usize::from(be16(d, 4)?);
+            let src = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(d.get(8..24)?).ok()?));
+            let dst = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(d.get(24..40)?).ok()?));
+            (src, dst, *d.get(6)?, d.get(40..(40 + payload_len).min(d.len()))?)
+        }
+        _ => return None,
+    };
+    let port = |at: usize| be16(body, at);
+    let src = SocketAddr::new(src, port(0)?);
+    let dst = SocketAddr::new(dst, port(2)?);
+    match protocol {
+        6 => {
+            let seq = u32::from_be_bytes(body.get(4..8)?.try_into().ok()?);
+            let header = usize::from(body.get(12)? >> 4) * 4;
+            Some(Segment {
+                key: StreamKey::Tcp { src, dst },
+                seq: Some(seq),
+                data: body.get(header..)?,
+            })
+        }
+        17 => Some(Segment {
+            key: StreamKey::Udp { src, dst },
+            seq: None,
+            data: body.get(8..)?,
+        }),
+        _ => None,
+    }
+}
+
+/// Linux usbmon header (48 bytes, or 64 in the memory-mapped variant).
+fn usbmon(link_type: u16, d: &[u8]) -> Option<Segment<'_>> {
+    let header = if link_type == LINKTYPE_USB_LINUX_MMAPPED { 64 } else { 48 };
+    let transfer = *d.get(9)?;
+    // Only bulk and interrupt transfers carry the serial data.
+    if transfer != 1 && transfer != 3 {
+        return None;
+    }
+    let caplen = u32::from_le_bytes(d.get(36..40)?.try_into().ok()?) as usize;
+    Some(Segment {
+        key: StreamKey::Usb {
+            bus: u16::from_le_bytes([*d.get(12)?, *d.get(13)?]),
+            device: u16::from(*d.get(11)?),
+            endpoint: *d.get(10)?,
+        },
+        seq: None,
+        data: d.get(header..(header + caplen).min(d.len()))?,
+    })
+}
+
+/// Windows USBPcap header.
+fn usbpcap(d: &[u8]) -> Option<Segment<'_>> {
+    let header = usize::from(u16::from_le_bytes([*d.first()?, *d.get(1)?]));
+    let transfer = *d.get(22)?;
+    if transfer != 1 && transfer != 3 {
+        return None;
+    }
+    Some(Segment {
+        key: StreamKey::Usb {
+            bus: u16::from_le_bytes([*d.get(17)?, *d.get(18)?]),
+            device: u16::from_le_bytes([*d.get(19)?, *d.get(20)?]),
+            endpoint: *d.get(21)?,
+        },
+        seq: None,
+        data: d.get(header..)?,
+    })
+}
+
+/// A UBX frame recovered from a capture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedFrame {
+    /// Index into [`Extractor::streams`].
+    pub stream: usize,
+    /// Capture time of the packet that completed the frame.
+    pub timestamp: f64,
+    /// Byte offset of the frame within its reassembled stream.
+    pub offset: u64,
+    pub frame: Frame,
+}
+
+struct Stream {
+    key: StreamKey,
+    parser: FrameParser,
+    next_seq: Option<u32>,
+    pending: Vec<(u32, Vec<u8>)>,
+}
+
+impl Stream {
+    /// Deliver TCP payload in sequence order, dropping retransmitted bytes.
+    fn push_tcp(&mut self, seq: u32, data: &[u8]) {
+        let Some(next) = self.next_seq else {
+            self.next_seq = Some(seq.wrapping_add(data.len() as u32));
+            self.parser.push(data);
+            return;
+        };
+        let ahead = seq.wrapping_sub(next) as i32;
+        if ahead > 0 {
+            self.pending.push((seq, data.to_vec()));
+            if self.pending.len() > MAX_PENDING_SEGMENTS {
+                // The missing segment was never captured: skip the gap.
+                let (seq, _) = *self
+                    .pending
+                    .iter()
+                    .min_by_key(|(s, _)| s.wrapping_sub(next))
+                    .expect("pending is not empty");
+                self.next_seq = Some(seq);
+            }
+        } else {
+            let skip = ahead.unsigned_abs() as usize;
+            if skip < data.len() {
+                self.parser.push(&data[skip..]);
+                self.next_seq = Some(next.wrapping_add((data.len() - skip) as u32));
+            }
+        }
+        self.drain_pending();
+    }
+
+    fn drain_pending(&mut self) {
+        while let Some(next) = self.next_seq {
+            let Some(i) = self
+                .pending
+                .iter()
+                .position(|(s, _)| s.wrapping_sub(next) as i32 <= 0)
+            else {
+                break;
+            };
+            let (seq, data) = self.pending.swap_remove(i);
+            let skip = next.wrapping_sub(seq) as usize;
+            if skip < data.len() {
+                self.parser.push(&data[skip..]);
+                self.next_seq = Some(next.wrapping_add((data.len() - skip) as u32));
+            }
+        }
+    }
+}
+
+/// Reassembles per-stream byte streams and splits them into UBX frames.
+#[derive(Default)]
+pub struct Extractor {
+    streams: Vec<Stream>,
+    index: HashMap<StreamKey, usize>,
+}
+
+impl Extractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keys of the streams seen so far, in order of first appearance.
+    pub fn streams(&self) -> impl Iterator<Item = &StreamKey> {
+        self.streams.iter().map(|s| &s.key)
+    }
+
+    /// Feed one packet, returning the frames it completes.
+    pub fn push(&mut self, packet: &Packet) -> Vec<ExtractedFrame> {
+        let Some(seg) = segment(packet) else {
+            return Vec::new();
+        };
+        if seg.data.is_empty() {
+            return Vec::new();
+        }
+        let next = self.streams.len();
+        let stream_index = *self.index.entry(seg.key).or_insert(next);
+        if stream_index == next {
+            self.streams.push(Stream {
+                key: seg.key,
+                parser: FrameParser::new(),
+                next_seq: None,
+                pending: Vec::new(),
+            });
+        }
+        let stream = &mut self.streams[stream_index];
+        match seg.seq {
+            Some(seq) => stream.push_tcp(seq, seg.data),
+            None => stream.parser.push(seg.data),
+        }
+
+        let mut out = Vec::new();
+        while let Some(result) = stream.parser.next_frame() {
+            if let Ok((offset, frame)) = result {
+                out.push(ExtractedFrame {
+                    stream: stream_index,
+                    timestamp: packet.timestamp,
+                    offset,
+                    frame,
+                });
+            }
+        }
+        out
+    }
+}
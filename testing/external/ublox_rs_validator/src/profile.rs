@@ -0,0 +1,125 @@
+//! Product-generation profiles of the schema (M8, M9, M10, F9, F10, X20).
+//!
+//! Every message records the manuals it was extracted from, and the message
+//! index groups those manuals by generation. A [`Profile`] is the part of
+//! the schema one generation documents: decoding with it accepts only that
+//! generation's messages, so frames of the others decode as unknown, and
+//! class/ID pairs shared by several definitions resolve to the layouts the
+//! generation actually uses (MGA-ACK-DATA0 is M8-only, for instance, so
+//! with any later profile 0x13 0x60 always decodes as MGA-ACK).
+//!
+//! Live tools don't know the receiver up front: [`AutoProfile`] decodes with
+//! the whole schema until a MON-VER names the generation, then narrows to
+//! its profile.
+
+use std::collections::HashSet;
+
+use crate::completeness::MessageIndex;
+use crate::decode::DecodedMessage;
+use crate::error::{Error, Result};
+use crate::receiver::{GnssSupport, ReceiverInfo};
+use crate::schema::{MessageDef, Schema};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// Generation name as the index spells it.
+    pub name: String,
+    manuals: HashSet<String>,
+}
+
+impl Profile {
+    /// The profile of `generation` (case-insensitive) in `index`.
+    pub fn new(index: &MessageIndex, generation: &str) -> Result<Self> {
+        let found = index.generations.iter().find(|g| g.name.eq_ignore_ascii_case(generation));
+        let Some(found) = found else {
+            let known: Vec<&str> = index.generations.iter().map(|g| g.name.as_str()).collect();
+            return Err(Error::Schema(format!(
+                "unknown generation '{generation}' (known: {})",
+                known.join(", ")
+            )));
+        };
+        Ok(Self {
+            name: found.name.clone(),
+            manuals: found.manuals.iter().cloned().collect(),
+        })
+    }
+
+    /// The profile of the generation `receiver` identifies, if the index has it.
+    pub fn for_receiver(index: &MessageIndex, receiver: &ReceiverInfo) -> Option<Self> {
+        Self::new(index, receiver.generation()?).ok()
+    }
+
+    /// Every generation in `index`, oldest first.
+    pub fn all(index: &MessageIndex) -> Vec<Self> {
+        index.generations.iter().map(|g| Self::new(index, &g.name).unwrap()).collect()
+    }
+
+    /// Whether one of this generation's manuals documents `msg`.
+    pub fn supports(&self, msg: &MessageDef) -> bool {
+        msg.supported_versions.source_manuals.iter().any(|m| self.manuals.contains(m))
+    }
+
+    /// The schema restricted to this generation's messages.
+    pub fn apply(&self, schema: &Schema) -> Schema {
+        let messages = schema.messages.iter().filter(|m| self.supports(m)).cloned().collect();
+        Schema::from_messages(schema.schema_version.clone(), messages)
+    }
+}
+
+/// A schema that narrows to the connected receiver's profile.
+#[derive(Debug, Clone)]
+pub struct AutoProfile {
+    full: Schema,
+    index: MessageIndex,
+    receiver: Option<ReceiverInfo>,
+    active: Option<(Profile, Schema)>,
+}
+
+impl AutoProfile {
+    pub fn new(schema: Schema, index: MessageIndex) -> Self {
+        Self {
+            full: schema,
+            index,
+            receiver: None,
+            active: None,
+        }
+    }
+
+    /// The schema to decode with: the profile's once one is known.
+    pub fn schema(&self) -> &Schema {
+        self.active.as_ref().map_or(&self.full, |(_, schema)| schema)
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.active.as_ref().map(|(profile, _)| profile)
+    }
+
+    /// The receiver the last MON-VER described, with MON-GNSS details.
+    pub fn receiver(&self) -> Option<&ReceiverInfo> {
+        self.receiver.as_ref()
+    }
+
+    /// Look at a decoded message; true when it switched profiles. A MON-VER
+    /// naming another generation than the active one switches; a MON-VER
+    /// that names none keeps the current schema.
+    pub fn observe(&mut self, msg: &DecodedMessage) -> bool {
+        if let Some(gnss) = GnssSupport::from_mon_gnss(msg) {
+            self.receiver.get_or_insert_with(ReceiverInfo::default).gnss = Some(gnss);
+            return false;
+        }
+        let Some(mut info) = ReceiverInfo::from_mon_ver(msg) else {
+            return false;
+        };
+        info.gnss = self.receiver.take().and_then(|r| r.gnss);
+        let profile = Profile::for_receiver(&self.index, &info);
+        self.receiver = Some(info);
+        match profile {
+            Some(profile) if self.profile() != Some(&profile) => {
+                let schema = profile.apply(&self.full);
+                self.active = Some((profile, schema));
+                true
+            }
+            _ => false,
+        }
+    }
+}
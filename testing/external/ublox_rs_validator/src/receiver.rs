@@ -0,0 +1,175 @@
+//! Receiver identity as reported by MON-VER, and the constellations it
+//! supports as reported by MON-GNSS.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::decode::DecodedMessage;
+
+/// Model and firmware of a receiver, from the MON-VER version strings and
+/// `KEY=value` extensions (`MOD=ZED-F9P`, `FWVER=HPG 1.32`, `PROTVER=27.31`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiverInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    pub sw_version: String,
+    pub hw_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gnss: Option<GnssSupport>,
+}
+
+/// Product lines whose firmware identifies the generation, by `FWVER` prefix.
+const FIRMWARE_GENERATIONS: &[(&str, &str)] = &[
+    ("SPG 3.", "M8"),
+    ("ADR 4.", "M8"),
+    ("UDR 1.", "M8"),
+    ("FTS 1.", "M8"),
+    ("TIM 1.", "M8"),
+    ("SPG 4.", "M9"),
+    ("MDR 2.", "M9"),
+    ("ADR 5.", "M9"),
+    ("SPG 5.", "M10"),
+    ("HPG 1.", "F9"),
+    ("HPS 1.", "F9"),
+    ("LAP 1.", "F9"),
+    ("TIM 2.", "F9"),
+    ("DBD 1.", "F9"),
+    ("HDG 1.", "F9"),
+    ("SPG 6.", "F10"),
+    ("HPG 2.", "X20"),
+];
+
+/// Generations named in module designations such as `ZED-F9P` or `MAX-M10S`.
+const MODEL_GENERATIONS: &[&str] = &["M8", "M9", "M10", "F9", "F10", "X20"];
+
+/// Constellations a receiver can track and has enabled, from MON-GNSS.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GnssSupport {
+    pub supported: Vec<String>,
+    pub enabled: Vec<String>,
+    /// How many major constellations it can track at once.
+    pub simultaneous: u8,
+}
+
+impl GnssSupport {
+    /// Read a MON-GNSS; `None` for any other message. The schema may not
+    /// define MON-GNSS, so undecoded payloads are read directly.
+    pub fn from_mon_gnss(msg: &DecodedMessage) -> Option<Self> {
+        if (msg.class_id, msg.message_id) != (0x0A, 0x28) {
+            return None;
+        }
+        let (supported, enabled, simultaneous) = if msg.parsed {
+            let byte = |name: &str| msg.get(name).and_then(|v| v.as_u64()).map(|v| v as u8);
+            (byte("supported")?, byte("enabled")?, byte("simultaneous")?)
+        } else {
+            let payload = hex::decode(msg.payload_raw.as_ref()?).ok()?;
+            (*payload.get(1)?, *payload.get(3)?, *payload.get(4)?)
+        };
+        let names = |mask: u8| {
+            ["GPS", "GLONASS", "BeiDou", "Galileo"]
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| mask & (1 << bit) != 0)
+                .map(|(_, name)| name.to_string())
+                .collect()
+        };
+        Some(Self {
+            supported: names(supported),
+            enabled: names(enabled),
+            simultaneous,
+        })
+    }
+}
+
+impl ReceiverInfo {
+    /// Read a decoded MON-VER; `None` for any other message.
+    pub fn from_mon_ver(msg: &DecodedMessage) -> Option<Self> {
+        if msg.name != "UBX-MON-VER" || !msg.parsed {
+            return None;
+        }
+        let text = |name: &str| msg.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let mut info = Self {
+            sw_version: text("swVersion"),
+            hw_version: text("hwVersion"),
+            ..Self::default()
+        };
+        let extensions = msg.get("extensionStrings").and_then(|v| v.as_array()).unwrap_or_default();
+        for ext in extensions {
+            let Some(ext) = ext.as_record().and_then(|r| r.get("extension")).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Some((key, value)) = ext.split_once('=') else {
+                continue;
+            };
+            let slot = match key {
+                "MOD" => &mut info.model,
+                "FWVER" => &mut info.firmware,
+                "PROTVER" => &mut info.protocol,
+                _ => continue,
+            };
+            *slot = Some(value.trim().to_string());
+        }
+        Some(info)
+    }
+
+    /// The product generation (`M8`, ..., `X20`, as the message index names
+    /// them), from the module designation, firmware, hardware version or,
+    /// for M8, the protocol version; `None` if none of them tells.
+    pub fn generation(&self) -> Option<&'static str> {
+        let from_model = self.model.as_deref().and_then(|model| {
+            model.split('-').find_map(|part| {
+                let digits = part.find(|c: char| !c.is_ascii_alphabetic())?;
+                let end = part[digits..].find(|c: char| !c.is_ascii_digit()).map_or(part.len(), |n| digits + n);
+                MODEL_GENERATIONS.iter().copied().find(|&g| g == &part[..end])
+            })
+        });
+        let from_firmware = || {
+            let firmware = self.firmware.as_deref()?;
+            FIRMWARE_GENERATIONS.iter().find(|(prefix, _)| firmware.starts_with(prefix)).map(|&(_, g)| g)
+        };
+        let from_hardware = || match self.hw_version.as_str() {
+            "00080000" => Some("M8"),
+            "000A0000" => Some("M10"),
+            _ => None,
+        };
+        let from_protocol = || {
+            let major: u32 = self.protocol.as_deref()?.split('.').next()?.trim().parse().ok()?;
+            (major < 24).then_some("M8")
+        };
+        from_model.or_else(from_firmware).or_else(from_hardware).or_else(from_protocol)
+    }
+
+    /// A file-name friendly tag, e.g. `zed-f9p-hpg-1.32`; `unknown` if
+    /// neither model nor firmware is known.
+    pub fn slug(&self) -> String {
+        let parts: Vec<&str> = [self.model.as_deref(), self.firmware.as_deref()].into_iter().flatten().collect();
+        if parts.is_empty() {
+            return "unknown".to_string();
+        }
+        let mut slug = String::new();
+        for c in parts.join(" ").chars() {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        slug.trim_matches('-').to_string()
+    }
+}
+
+impl fmt::Display for ReceiverInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let model = self.model.as_deref().unwrap_or("unknown receiver");
+        match (&self.firmware, &self.protocol) {
+            (Some(fw), Some(p)) => write!(f, "{model} {fw} (protocol {p})"),
+            (Some(fw), None) => write!(f, "{model} {fw}"),
+            (None, _) => write!(f, "{model} ({})", self.sw_version),
+        }
+    }
+}
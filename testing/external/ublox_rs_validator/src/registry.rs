@@ -0,0 +1,407 @@
+//! Released versions of the schema set and what changed in each.
+//!
+//! `data/schema_versions.json` lists every release of the message and
+//! configuration key definitions as a semantic version, with the messages
+//! and keys it added, changed compatibly and broke (as classified by
+//! [`schema_diff`](crate::schema_diff)), and a fingerprint of each
+//! definition's wire layout:
+//!
+//! ```json
+//! {
+//!   "current": "1.1.0",
+//!   "releases": [
+//!     {
+//!       "version": "1.1.0",
+//!       "date": "2026-11-02",
+//!       "breaking": [],
+//!       "added": ["UBX-NAV-PL"],
+//!       "changed": ["UBX-NAV-PVT"],
+//!       "messages": { "UBX-ACK-ACK": "9f1c2e4b0a6d3875", ... },
+//!       "config_keys": { "CFG-RATE-MEAS": "4c0e7b5d21f9a368", ... }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A release that breaks anything bumps the major version, one that adds or
+//! compatibly changes definitions the minor version, and one that only
+//! rewords descriptions the patch version. Consumers pin a version and ask
+//! [`Manifest::stability`] whether the definitions they use held between two
+//! releases.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::{ConfigKey, ConfigKeys};
+use crate::error::{Error, Result};
+use crate::schema::{DataType, FieldDef, MessageDef, PayloadDef, Schema};
+use crate::schema_diff::{
+    base_offset_str, bits_str, count_str, discriminator_str, length_str, offset_str, type_str, unit_str, Change,
+    Impact,
+};
+
+pub fn default_manifest_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/schema_versions.json")
+}
+
+/// `major.minor.patch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version { major, minor, patch }
+    }
+
+    /// The next version after a release with `bump`.
+    pub fn bumped(self, bump: Bump) -> Self {
+        match bump {
+            Bump::Major => Version::new(self.major + 1, 0, 0),
+            Bump::Minor => Version::new(self.major, self.minor + 1, 0),
+            Bump::Patch => Version::new(self.major, self.minor, self.patch + 1),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().trim_start_matches('v').split('.').collect();
+        let number = |p: &str| p.parse::<u32>().ok();
+        match parts.as_slice() {
+            [a, b, c] => match (number(a), number(b), number(c)) {
+                (Some(a), Some(b), Some(c)) => Ok(Version::new(a, b, c)),
+                _ => Err(Error::Schema(format!("invalid version '{s}'"))),
+            },
+            _ => Err(Error::Schema(format!("invalid version '{s}', expected MAJOR.MINOR.PATCH"))),
+        }
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which part of the version a set of changes requires bumping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: Version,
+    pub date: String,
+    /// Messages and keys with a breaking change since the previous release.
+    #[serde(default)]
+    pub breaking: Vec<String>,
+    /// Messages and keys new in this release.
+    #[serde(default)]
+    pub added: Vec<String>,
+    /// Messages and keys changed compatibly, e.g. a new field or bit.
+    #[serde(default)]
+    pub changed: Vec<String>,
+    /// Layout fingerprint by message name.
+    pub messages: BTreeMap<String, String>,
+    /// Layout fingerprint by key name.
+    #[serde(default)]
+    pub config_keys: BTreeMap<String, String>,
+}
+
+impl Release {
+    /// A release of `schema` and `keys`, with the `changes` since `previous`.
+    pub fn new(
+        version: Version,
+        date: &str,
+        schema: &Schema,
+        keys: Option<&ConfigKeys>,
+        previous: Option<&Release>,
+        changes: &[Change],
+    ) -> Self {
+        let mut release = Release {
+            version,
+            date: date.to_string(),
+            breaking: Vec::new(),
+            added: Vec::new(),
+            changed: Vec::new(),
+            messages: schema.messages.iter().map(|m| (m.name.clone(), message_fingerprint(m))).collect(),
+            config_keys: keys
+                .map(|k| k.iter().map(|k| (k.name.clone(), key_fingerprint(k))).collect())
+                .unwrap_or_default(),
+        };
+        for change in changes {
+            let existed = previous.is_some_and(|p| p.fingerprint(&change.item).is_some());
+            let list = match change.impact {
+                Impact::Breaking => &mut release.breaking,
+                _ if !existed => &mut release.added,
+                _ => &mut release.changed,
+            };
+            if !list.contains(&change.item) {
+                list.push(change.item.clone());
+            }
+        }
+        // An item that breaks is not also merely changed.
+        let breaking = release.breaking.clone();
+        release.changed.retain(|item| !breaking.contains(item));
+        release.breaking.sort();
+        release.added.sort();
+        release.changed.sort();
+        release
+    }
+
+    /// The bump this release's changes require over the previous one.
+    pub fn bump(&self) -> Bump {
+        if !self.breaking.is_empty() {
+            Bump::Major
+        } else if !self.added.is_empty() || !self.changed.is_empty() {
+            Bump::Minor
+        } else {
+            Bump::Patch
+        }
+    }
+
+    fn fingerprint(&self, item: &str) -> Option<&str> {
+        self.messages.get(item).or_else(|| self.config_keys.get(item)).map(String::as_str)
+    }
+}
+
+/// How a message or key definition fared between two releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stability {
+    /// The same layout in both, never touched in between.
+    Unchanged,
+    /// Changed only in ways that keep old decoders working.
+    Compatible { changed_in: Vec<Version> },
+    /// Broken first in this release.
+    Breaking { since: Version },
+    /// Not defined in the earlier release; first defined in `in_version`.
+    Added { in_version: Version },
+    /// Not defined in the later release.
+    Removed,
+}
+
+impl Stability {
+    /// Whether a consumer of the earlier definition can use the later one.
+    pub fn is_stable(&self) -> bool {
+        matches!(self, Stability::Unchanged | Stability::Compatible { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub current: Version,
+    /// Oldest first.
+    pub releases: Vec<Release>,
+}
+
+impl Manifest {
+    pub fn load_default() -> Result<Self> {
+        Self::load(default_manifest_path())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+        let manifest: Manifest = serde_json::from_str(&text)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        for pair in self.releases.windows(2) {
+            if pair[0].version >= pair[1].version {
+                return Err(Error::Schema(format!("release {} listed after {}", pair[1].version, pair[0].version)));
+            }
+        }
+        match self.latest() {
+            Some(latest) if latest.version == self.current => Ok(()),
+            _ => Err(Error::Schema(format!("current version {} is not the latest release", self.current))),
+        }
+    }
+
+    pub fn latest(&self) -> Option<&Release> {
+        self.releases.last()
+    }
+
+    pub fn release(&self, version: Version) -> Option<&Release> {
+        self.releases.iter().find(|r| r.version == version)
+    }
+
+    /// Add `release` as the new current version, if its version is at least
+    /// the bump its own changes require.
+    pub fn push(&mut self, release: Release) -> Result<()> {
+        if let Some(latest) = self.latest() {
+            let minimum = latest.version.bumped(release.bump());
+            if release.version < minimum {
+                return Err(Error::Schema(format!(
+                    "{} is too small a step from {}: these changes need at least {minimum}",
+                    release.version, latest.version
+                )));
+            }
+        }
+        self.current = release.version;
+        self.releases.push(release);
+        Ok(())
+    }
+
+    /// Whether the definition of `item` (a message or key name) held from
+    /// release `from` to release `to`.
+    pub fn stability(&self, item: &str, from: Version, to: Version) -> Result<Stability> {
+        let missing = |v: Version| Error::Schema(format!("no release {v}"));
+        let start = self.release(from).ok_or_else(|| missing(from))?;
+        let end = self.release(to).ok_or_else(|| missing(to))?;
+        if from > to {
+            return Err(Error::Schema(format!("{from} is later than {to}")));
+        }
+        let between: Vec<&Release> = self.releases.iter().filter(|r| r.version > from && r.version <= to).collect();
+        if start.fingerprint(item).is_none() {
+            return match between.iter().find(|r| r.fingerprint(item).is_some()) {
+                Some(r) if end.fingerprint(item).is_some() => Ok(Stability::Added { in_version: r.version }),
+                _ => Err(Error::Schema(format!("{item} is in neither {from} nor {to}"))),
+            };
+        }
+        if end.fingerprint(item).is_none() {
+            return Ok(Stability::Removed);
+        }
+        if let Some(r) = between.iter().find(|r| r.breaking.iter().any(|b| b == item)) {
+            return Ok(Stability::Breaking { since: r.version });
+        }
+        let changed_in: Vec<Version> = between
+            .iter()
+            .filter(|r| r.changed.iter().any(|c| c == item))
+            .map(|r| r.version)
+            .collect();
+        if changed_in.is_empty() && start.fingerprint(item) == end.fingerprint(item) {
+            Ok(Stability::Unchanged)
+        } else {
+            Ok(Stability::Compatible { changed_in })
+        }
+    }
+
+    /// Messages and keys whose layout in `schema` and `keys` differs from
+    /// the current release, with `+` for new and `-` for removed ones.
+    pub fn unreleased(&self, schema: &Schema, keys: Option<&ConfigKeys>) -> Vec<String> {
+        let Some(latest) = self.latest() else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        let mut compare = |released: &BTreeMap<String, String>, now: BTreeMap<String, String>| {
+            for (name, fingerprint) in &now {
+                match released.get(name) {
+                    None => out.push(format!("+{name}")),
+                    Some(f) if f != fingerprint => out.push(name.clone()),
+                    Some(_) => {}
+                }
+            }
+            out.extend(released.keys().filter(|n| !now.contains_key(*n)).map(|n| format!("-{n}")));
+        };
+        compare(&latest.messages, schema.messages.iter().map(|m| (m.name.clone(), message_fingerprint(m))).collect());
+        if let Some(keys) = keys {
+            compare(&latest.config_keys, keys.iter().map(|k| (k.name.clone(), key_fingerprint(k))).collect());
+        }
+        out
+    }
+}
+
+/// A hash of what decoding a message depends on: IDs, lengths, variants and
+/// each field's name, offset, type, scale, unit and bits. Descriptions and
+/// reserved fields are left out.
+pub fn message_fingerprint(msg: &MessageDef) -> String {
+    let mut layout = format!("{:02X}-{:02X}", msg.class_id, msg.message_id);
+    if let Some(payload) = &msg.payload {
+        payload_layout(payload, &mut layout);
+    }
+    for variant in &msg.variants {
+        layout.push_str(&format!("|variant {} {}", variant.name, discriminator_str(&variant.discriminator)));
+        payload_layout(&variant.payload, &mut layout);
+    }
+    fnv1a(&layout)
+}
+
+/// A hash of a key's ID, type, scale, unit, constants and bits.
+pub fn key_fingerprint(key: &ConfigKey) -> String {
+    let mut layout = format!("{:08X} {:?} {:?} {}", key.key_id, key.data_type, key.scale, unit_str(&key.unit));
+    for (name, value) in &key.constants {
+        layout.push_str(&format!("|{name}={value}"));
+    }
+    for bits in &key.bits {
+        layout.push_str(&format!("|{}:{}..{}", bits.name, bits.start, bits.end));
+    }
+    fnv1a(&layout)
+}
+
+fn payload_layout(payload: &PayloadDef, out: &mut String) {
+    out.push_str(&format!("|length {}", length_str(&payload.length)));
+    fields_layout(&payload.fields, out);
+    for group in &payload.repeated_groups {
+        out.push_str(&format!(
+            "|group {} {} {} {}",
+            group.name,
+            count_str(&group.count),
+            group.group_size,
+            base_offset_str(&group.base_offset)
+        ));
+        fields_layout(&group.fields, out);
+    }
+}
+
+fn fields_layout(fields: &[FieldDef], out: &mut String) {
+    for field in fields.iter().filter(|f| !f.reserved) {
+        out.push_str(&format!(
+            "|{}@{} {} {:?} {}",
+            field.name,
+            offset_str(field.byte_offset),
+            type_str(&field.data_type),
+            field.multiplier(),
+            unit_str(&field.unit)
+        ));
+        for bit in field.bits.iter().filter(|b| !b.reserved) {
+            out.push_str(&format!(" {}:{}", bit.name, bits_str(bit.bit_offset, bit.bit_width)));
+        }
+        if let DataType::Group { fields, .. } = &field.data_type {
+            out.push('{');
+            fields_layout(fields, out);
+            out.push('}');
+        }
+    }
+}
+
+/// 64-bit FNV-1a, as 16 hex digits: short, stable across platforms and
+/// Rust versions, and enough to tell layouts apart.
+fn fnv1a(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
@@ -0,0 +1,424 @@
+//! RINEX 3.04 observation and navigation files from RXM-RAWX and RXM-SFRBX.
+//!
+//! Observations are collected per epoch, then written once the set of
+//! observed signals (needed for the header) is known. Navigation files carry
+//! the GPS/QZSS LNAV ephemerides assembled by [`LnavDecoder`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+
+use crate::decode::DecodedMessage;
+use crate::error::Result;
+use crate::gnss::Gnss;
+use crate::nav::{GpsEphemeris, LnavDecoder};
+use crate::time::{gps_to_datetime, resolve_week};
+use crate::value::Value;
+
+const RINEX_VERSION: f64 = 3.04;
+
+/// Header metadata that can't be derived from the UBX stream.
+#[derive(Debug, Clone)]
+pub struct RinexHeader {
+    pub program: String,
+    pub run_by: String,
+    /// File creation time, e.g. `20240115 120000 UTC`.
+    pub date: String,
+    pub marker_name: String,
+    pub observer: String,
+    pub agency: String,
+    pub receiver_type: String,
+    pub receiver_version: String,
+    pub antenna_type: String,
+    /// Approximate marker position, ECEF metres.
+    pub approx_position: [f64; 3],
+}
+
+impl Default for RinexHeader {
+    fn default() -> Self {
+        Self {
+            program: "ubx-rinex".into(),
+            run_by: String::new(),
+            date: String::new(),
+            marker_name: "UNKNOWN".into(),
+            observer: String::new(),
+            agency: String::new(),
+            receiver_type: "U-BLOX".into(),
+            receiver_version: String::new(),
+            antenna_type: String::new(),
+            approx_position: [0.0; 3],
+        }
+    }
+}
+
+/// One signal observed in an epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SignalObs {
+    pseudorange: Option<f64>,
+    phase: Option<f64>,
+    doppler: f64,
+    cno: f64,
+    /// RINEX loss-of-lock indicator for the phase.
+    lli: u8,
+}
+
+impl SignalObs {
+    /// RINEX signal strength indicator, 1-9.
+    fn ssi(&self) -> u8 {
+        (self.cno / 6.0).clamp(1.0, 9.0) as u8
+    }
+}
+
+type Satellite = (char, u8);
+
+#[derive(Debug, Clone)]
+struct Epoch {
+    week: u32,
+    tow: f64,
+    sats: BTreeMap<Satellite, BTreeMap<&'static str, SignalObs>>,
+}
+
+/// Collects RXM-RAWX epochs for a RINEX observation file.
+#[derive(Debug, Default)]
+pub struct ObservationBuilder {
+    epochs: Vec<Epoch>,
+    signals: BTreeMap<char, BTreeSet<&'static str>>,
+    glonass_slots: BTreeMap<u8, i8>,
+    leap_seconds: Option<i8>,
+    locktime: HashMap<(Satellite, &'static str), u64>,
+}
+
+impl ObservationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn epochs(&self) -> usize {
+        self.epochs.len()
+    }
+
+    /// GPS week of the first epoch, used to resolve ephemeris week numbers.
+    pub fn first_week(&self) -> Option<u32> {
+        self.epochs.first().map(|e| e.week)
+    }
+
+    /// Add a decoded RXM-RAWX message; other messages are ignored.
+    /// Returns true if an epoch was added.
+    pub fn push(&mut self, msg: &DecodedMessage) -> bool {
+        if msg.name != "UBX-RXM-RAWX" || !msg.parsed {
+            return false;
+        }
+        let (Some(tow), Some(week)) = (
+            msg.get("rcvTow").and_then(Value::as_f64),
+            msg.get("week").and_then(Value::as_u64),
+        ) else {
+            return false;
+        };
+        if flag(msg.get("recStat"), "leapSec", 0) {
+            self.leap_seconds = msg.get("leaps").and_then(Value::as_i64).map(|l| l as i8);
+        }
+        let clock_reset = flag(msg.get("recStat"), "clkReset", 1);
+
+        let mut epoch = Epoch {
+            week: week as u32,
+            tow,
+            sats: BTreeMap::new(),
+        };
+        let measurements = msg.get("repeated_group").and_then(Value::as_array).unwrap_or_default();
+        for meas in measurements.iter().filter_map(Value::as_record) {
+            let int = |name| meas.get(name).and_then(Value::as_u64).unwrap_or(0);
+            let real = |name| meas.get(name).and_then(Value::as_f64).unwrap_or(0.0);
+            let Some(gnss) = Gnss::from_id(int("gnssId") as u8) else {
+                continue;
+            };
+            let (Some(sys), Some(code)) = (gnss.rinex_char(), gnss.rinex_signal(int("sigId") as u8)) else {
+                continue;
+            };
+            let sv_id = int("svId") as u8;
+            let sat = (sys, gnss.rinex_prn(sv_id));
+            if gnss == Gnss::Glonass {
+                self.glonass_slots.insert(sat.1, int("freqId") as i8 - 7);
+            }
+
+            let trk = meas.get("trkStat");
+            let locktime = int("locktime");
+            let previous = self.locktime.insert((sat, code), locktime);
+            let mut lli = 0;
+            if clock_reset || previous.is_some_and(|p| locktime < p) || (previous.is_none() && locktime == 0) {
+                lli |= 1;
+            }
+            if !flag(trk, "halfCyc", 2) {
+                lli |= 2;
+            }
+            let obs = SignalObs {
+                pseudorange: flag(trk, "prValid", 0).then(|| real("prMes")),
+                phase: flag(trk, "cpValid", 1).then(|| real("cpMes")),
+                doppler: real("doMes"),
+                cno: real("cno"),
+                lli,
+            };
+            self.signals.entry(sys).or_default().insert(code);
+            epoch.sats.entry(sat).or_default().insert(code, obs);
+        }
+        self.epochs.push(epoch);
+        true
+    }
+
+    /// Observation types per system, in header order.
+    fn obs_types(&self) -> BTreeMap<char, Vec<String>> {
+        self.signals
+            .iter()
+            .map(|(sys, codes)| {
+                let types = codes
+                    .iter()
+                    .flat_map(|c| ["C", "L", "D", "S"].map(|t| format!("{t}{c}")))
+                    .collect();
+                (*sys, types)
+            })
+            .collect()
+    }
+
+    pub fn write(&self, header: &RinexHeader, out: &mut dyn Write) -> Result<()> {
+        let types = self.obs_types();
+        let system = match types.len() {
+            1 => types.keys().next().copied().unwrap_or('M'),
+            _ => 'M',
+        };
+        let mut h = String::new();
+        h += &line(
+            &format!("{RINEX_VERSION:9.2}{:11}{:<20}{:<20}", "", "OBSERVATION DATA", system),
+            "RINEX VERSION / TYPE",
+        );
+        h += &line(
+            &format!("{:<20}{:<20}{:<20}", header.program, header.run_by, header.date),
+            "PGM / RUN BY / DATE",
+        );
+        h += &line(&header.marker_name, "MARKER NAME");
+        h += &line("NON_GEODETIC", "MARKER TYPE");
+        h += &line(&format!("{:<20}{:<40}", header.observer, header.agency), "OBSERVER / AGENCY");
+        h += &line(
+            &format!("{:<20}{:<20}{:<20}", "", header.receiver_type, header.receiver_version),
+            "REC # / TYPE / VERS",
+        );
+        h += &line(&format!("{:<20}{:<20}", "", header.antenna_type), "ANT # / TYPE");
+        let [x, y, z] = header.approx_position;
+        h += &line(&format!("{x:14.4}{y:14.4}{z:14.4}"), "APPROX POSITION XYZ");
+        h += &line(&format!("{:14.4}{:14.4}{:14.4}", 0.0, 0.0, 0.0), "ANTENNA: DELTA H/E/N");
+        for (sys, list) in &types {
+            for (i, chunk) in list.chunks(13).enumerate() {
+                let lead = if i == 0 {
+                    format!("{sys}  {:3}", list.len())
+                } else {
+                    " ".repeat(6)
+                };
+                let codes: String = chunk.iter().map(|c| format!(" {c}")).collect();
+                h += &line(&format!("{lead}{codes}"), "SYS / # / OBS TYPES");
+            }
+        }
+        h += &line("DBHZ", "SIGNAL STRENGTH UNIT");
+        if let Some(first) = self.epochs.first() {
+            let t = gps_to_datetime(first.week, first.tow);
+            h += &line(
+                &format!(
+                    "{:6}{:6}{:6}{:6}{:6}{:13.7}{:5}{:<3}",
+                    t.year, t.month, t.day, t.hour, t.minute, t.second, "", "GPS"
+                ),
+                "TIME OF FIRST OBS",
+            );
+        }
+        for sys in types.keys() {
+            h += &line(&sys.to_string(), "SYS / PHASE SHIFT");
+        }
+        if types.contains_key(&'R') {
+            let slots: Vec<_> = self.glonass_slots.iter().collect();
+            for (i, chunk) in slots.chunks(8).enumerate() {
+                let lead = if i == 0 {
+                    format!("{:3} ", slots.len())
+                } else {
+                    " ".repeat(4)
+                };
+                let entries: String = chunk.iter().map(|(prn, k)| format!("R{prn:02} {k:2} ")).collect();
+                h += &line(&format!("{lead}{entries}"), "GLONASS SLOT / FRQ #");
+            }
+            h += &line(
+                " C1C    0.000 C1P    0.000 C2C    0.000 C2P    0.000",
+                "GLONASS COD/PHS/BIS",
+            );
+        }
+        if let Some(leap) = self.leap_seconds {
+            h += &line(&format!("{leap:6}"), "LEAP SECONDS");
+        }
+        h += &line("", "END OF HEADER");
+        out.write_all(h.as_bytes())?;
+
+        for epoch in &self.epochs {
+            let t = gps_to_datetime(epoch.week, epoch.tow);
+            writeln!(
+                out,
+                "> {:4} {:02} {:02} {:02} {:02}{:11.7}  0{:3}",
+                t.year,
+                t.month,
+                t.day,
+                t.hour,
+                t.minute,
+                t.second,
+                epoch.sats.len()
+            )?;
+            for ((sys, prn), signals) in &epoch.sats {
+                let mut record = format!("{sys}{prn:02}");
+                for obs_type in &types[sys] {
+                    let (kind, code) = obs_type.split_at(1);
+                    let Some(obs) = signals.get(code) else {
+                        record += &" ".repeat(16);
+                        continue;
+                    };
+                    let (value, lli) = match kind {
+                        "C" => (obs.pseudorange, 0),
+                        "L" => (obs.phase, obs.lli),
+                        "D" => (Some(obs.doppler), 0),
+                        _ => (Some(obs.cno), 0),
+                    };
+                    match value {
+                        Some(v) => {
+                            let lli = if lli == 0 { ' ' } else { char::from(b'0' + lli) };
+                            record += &format!("{v:14.3}{lli}{}", obs.ssi());
+                        }
+                        None => record += &" ".repeat(16),
+                    }
+                }
+                writeln!(out, "{}", record.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a bitfield member from an expanded record or a raw integer.
+fn flag(value: Option<&Value>, member: &str, bit: u32) -> bool {
+    match value {
+        Some(Value::Record(fields)) => fields.get(member).and_then(Value::as_u64).unwrap_or(0) != 0,
+        Some(v) => v.as_u64().is_some_and(|raw| raw >> bit & 1 != 0),
+        None => false,
+    }
+}
+
+/// Collects RXM-SFRBX subframes for a RINEX navigation file.
+#[derive(Default)]
+pub struct NavigationBuilder {
+    decoder: LnavDecoder,
+    ephemerides: Vec<GpsEphemeris>,
+    leap_seconds: Option<i8>,
+}
+
+impl NavigationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ephemerides(&self) -> &[GpsEphemeris] {
+        &self.ephemerides
+    }
+
+    /// Add a decoded RXM-SFRBX (subframes) or RXM-RAWX (leap seconds) message.
+    pub fn push(&mut self, msg: &DecodedMessage) -> bool {
+        match msg.name.as_str() {
+            "UBX-RXM-SFRBX" => match self.decoder.push(msg) {
+                Some(eph) => {
+                    self.ephemerides.push(eph);
+                    true
+                }
+                None => false,
+            },
+            "UBX-RXM-RAWX" => {
+                if flag(msg.get("recStat"), "leapSec", 0) {
+                    self.leap_seconds = msg.get("leaps").and_then(Value::as_i64).map(|l| l as i8);
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Write the navigation file. `reference_week` (e.g. from the observation
+    /// epochs) resolves the 10-bit broadcast week numbers.
+    pub fn write(&self, header: &RinexHeader, reference_week: Option<u32>, out: &mut dyn Write) -> Result<()> {
+        let systems: BTreeSet<char> = self
+            .ephemerides
+            .iter()
+            .filter_map(|e| e.gnss.rinex_char())
+            .collect();
+        let system = if systems.len() == 1 {
+            systems.first().copied().unwrap_or('M')
+        } else {
+            'M'
+        };
+        let mut h = String::new();
+        h += &line(
+            &format!("{RINEX_VERSION:9.2}{:11}{:<20}{:<20}", "", "NAVIGATION DATA", system),
+            "RINEX VERSION / TYPE",
+        );
+        h += &line(
+            &format!("{:<20}{:<20}{:<20}", header.program, header.run_by, header.date),
+            "PGM / RUN BY / DATE",
+        );
+        if let Some(leap) = self.leap_seconds {
+            h += &line(&format!("{leap:6}"), "LEAP SECONDS");
+        }
+        h += &line("", "END OF HEADER");
+        out.write_all(h.as_bytes())?;
+
+        // Without observations, assume the current 1024-week era (2019-2038).
+        let reference = reference_week.unwrap_or(2048);
+        for eph in &self.ephemerides {
+            let Some(sys) = eph.gnss.rinex_char() else {
+                continue;
+            };
+            let week = resolve_week(u32::from(eph.week), 10, reference);
+            let t = gps_to_datetime(week, eph.toc);
+            writeln!(
+                out,
+                "{sys}{:02} {:4} {:02} {:02} {:02} {:02} {:02}{}{}{}",
+                eph.gnss.rinex_prn(eph.sv_id),
+                t.year,
+                t.month,
+                t.day,
+                t.hour,
+                t.minute,
+                t.second.round() as u8,
+                d19(eph.af0),
+                d19(eph.af1),
+                d19(eph.af2)
+            )?;
+            let fit_hours = if eph.fit_interval == 0 { 4.0 } else { 6.0 };
+            let orbits: [&[f64]; 7] = [
+                &[f64::from(eph.iode), eph.crs, eph.delta_n, eph.m0],
+                &[eph.cuc, eph.e, eph.cus, eph.sqrt_a],
+                &[eph.toe, eph.cic, eph.omega0, eph.cis],
+                &[eph.i0, eph.crc, eph.omega, eph.omega_dot],
+                &[eph.idot, f64::from(eph.code_on_l2), f64::from(week), f64::from(eph.l2_p_flag)],
+                &[eph.ura_meters(), f64::from(eph.health), eph.tgd, f64::from(eph.iodc)],
+                &[eph.transmit_time, fit_hours],
+            ];
+            for values in orbits {
+                let fields: String = values.iter().map(|v| d19(*v)).collect();
+                writeln!(out, "    {fields}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A header line: content in columns 1-60, label in 61-80.
+fn line(content: &str, label: &str) -> String {
+    let content: String = content.chars().take(60).collect();
+    format!("{content:<60}{label}\n")
+}
+
+/// Fortran `D19.12` formatting, e.g. ` 1.234567890123D-04`.
+fn d19(x: f64) -> String {
+    let s = format!("{:.12E}", x.abs());
+    let (mantissa, exp) = s.split_once('E').unwrap_or((&s, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let sign = if x.is_sign_negative() && x != 0.0 { '-' } else { ' ' };
+    let exp_sign = if exp < 0 { '-' } else { '+' };
+    format!("{sign}{mantissa}D{exp_sign}{:02}", exp.abs())
+}
@@ -0,0 +1,467 @@
+//! ROS 2 interface package generation.
+//!
+//! Builds a `.msg` definition for every message type and variant (`NavPvt`,
+//! `MgaGpsEph`) and for every repeated group (`NavSatSatellites`), plus the
+//! `package.xml` and `CMakeLists.txt` that `colcon build` needs. ROS field
+//! names must be snake case, so each definition comes with a mapping from
+//! decoded field paths to ROS fields (`flags.gnssFixOK` -> `flags_gnss_fix_ok`),
+//! written as `mapping.json` and applied by [`Package::convert`].
+//!
+//! Field types follow the decoder's output for the same [`DecodeOptions`]:
+//! scaled fields are `float64`, expanded bitfield members are one field each
+//! (`bool` for single bits), CH arrays are `string`, and groups are arrays of
+//! their element type, bounded only when their count is fixed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::decode::{DecodeOptions, DecodedMessage};
+use crate::error::Result;
+use crate::schema::{normalize_name, BaseType, Count, DataType, FieldDef, MessageDef, PayloadDef, Schema};
+use crate::value::{Fields, Value};
+
+/// One field of a generated definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgField {
+    /// ROS type, e.g. `uint32`, `float64[3]` or `NavSatSatellites[]`.
+    pub ros_type: String,
+    pub name: String,
+    /// Path of the value in the decoded fields, e.g. `flags.gnssFixOK`.
+    pub path: String,
+    /// Element type, for group fields.
+    pub nested: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// A generated `.msg` definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgDef {
+    /// ROS type name, e.g. `NavPvt`.
+    pub name: String,
+    /// `(message name, variant)` for message types; `None` for group elements.
+    pub source: Option<(String, Option<String>)>,
+    /// `CLASS_ID` and `MESSAGE_ID` constants, for message types.
+    pub ids: Option<(u8, u8)>,
+    pub fields: Vec<MsgField>,
+}
+
+impl MsgDef {
+    /// The `.msg` file contents.
+    pub fn to_msg(&self, header: bool) -> String {
+        let mut out = match &self.source {
+            Some((name, Some(variant))) => format!("# {name} ({variant})\n"),
+            Some((name, None)) => format!("# {name}\n"),
+            None => String::new(),
+        };
+        if let Some((class_id, message_id)) = self.ids {
+            let _ = writeln!(out, "uint8 CLASS_ID={class_id}\nuint8 MESSAGE_ID={message_id}\n");
+            if header {
+                out.push_str("std_msgs/Header header\n");
+            }
+        }
+        for field in &self.fields {
+            let _ = write!(out, "{} {}", field.ros_type, field.name);
+            if let Some(comment) = &field.comment {
+                let _ = write!(out, "  # {comment}");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A ROS 2 interface package for a schema.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    pub options: DecodeOptions,
+    /// Add a `std_msgs/Header` to each message type.
+    pub header: bool,
+    pub messages: Vec<MsgDef>,
+}
+
+impl Package {
+    /// Definitions for the messages in `schema` (all of them if `only` is
+    /// empty) whose values are decoded with `options`.
+    pub fn generate(schema: &Schema, name: &str, only: &[String], options: DecodeOptions, header: bool) -> Self {
+        let only: Vec<String> = only.iter().map(|n| normalize_name(n)).collect();
+        let mut messages = Vec::new();
+        for msg in schema.messages.iter().filter(|m| only.is_empty() || only.contains(&m.name)) {
+            if let Some(payload) = &msg.payload {
+                add_message(&mut messages, msg, None, payload, options);
+            }
+            for variant in &msg.variants {
+                add_message(&mut messages, msg, Some(&variant.name), &variant.payload, options);
+            }
+        }
+        Self {
+            name: name.to_string(),
+            options,
+            header,
+            messages,
+        }
+    }
+
+    /// Type names defined more than once, which `rosidl` would reject.
+    pub fn duplicate_types(&self) -> Vec<String> {
+        let mut seen = BTreeMap::new();
+        for m in &self.messages {
+            *seen.entry(m.name.as_str()).or_insert(0) += 1;
+        }
+        seen.into_iter().filter(|(_, n)| *n > 1).map(|(name, _)| name.to_string()).collect()
+    }
+
+    pub fn find(&self, type_name: &str) -> Option<&MsgDef> {
+        self.messages.iter().find(|m| m.name == type_name)
+    }
+
+    /// The definition for a decoded message.
+    pub fn definition(&self, msg: &DecodedMessage) -> Option<&MsgDef> {
+        self.messages
+            .iter()
+            .find(|m| m.source.as_ref().is_some_and(|(name, variant)| *name == msg.name && *variant == msg.variant))
+    }
+
+    /// Convert a decoded message to a ROS message in JSON form, keyed by ROS
+    /// field names, e.g. for `ros2 topic pub` or a bridge node. The header
+    /// is left for the publisher to fill in.
+    pub fn convert(&self, msg: &DecodedMessage) -> Option<Json> {
+        self.definition(msg).map(|def| self.convert_fields(def, &msg.fields))
+    }
+
+    fn convert_fields(&self, def: &MsgDef, fields: &Fields) -> Json {
+        let mut out = Map::new();
+        for field in &def.fields {
+            let Some(value) = fields.get_path(&field.path) else {
+                continue;
+            };
+            let json = match (&field.nested, value.as_array()) {
+                (Some(nested), Some(items)) => {
+                    let element = self.find(nested).expect("group types are generated with their parent");
+                    let records = items.iter().filter_map(Value::as_record);
+                    Json::Array(records.map(|r| self.convert_fields(element, r)).collect())
+                }
+                _ if field.ros_type == "bool" => Json::Bool(value.as_u64().is_some_and(|v| v != 0)),
+                _ => serde_json::to_value(value).unwrap_or(Json::Null),
+            };
+            out.insert(field.name.clone(), json);
+        }
+        Json::Object(out)
+    }
+
+    /// `mapping.json`: the ROS type of each message and the ROS field of
+    /// each decoded field path.
+    pub fn mapping(&self) -> Json {
+        let messages: Vec<Json> = self
+            .messages
+            .iter()
+            .filter_map(|m| {
+                let (name, variant) = m.source.as_ref()?;
+                let (class_id, message_id) = m.ids?;
+                Some(json!({
+                    "message": name,
+                    "variant": variant,
+                    "class_id": class_id,
+                    "message_id": message_id,
+                    "type": format!("{}/msg/{}", self.name, m.name),
+                }))
+            })
+            .collect();
+        let types: Map<String, Json> = self
+            .messages
+            .iter()
+            .map(|m| {
+                let fields = m
+                    .fields
+                    .iter()
+                    .map(|f| json!({"path": f.path, "field": f.name, "type": f.ros_type}))
+                    .collect();
+                (m.name.clone(), Json::Array(fields))
+            })
+            .collect();
+        json!({
+            "package": self.name,
+            "scale": self.options.scale,
+            "expand_flags": self.options.expand_flags,
+            "messages": messages,
+            "types": types,
+        })
+    }
+
+    /// Every file of the package, by path relative to its directory.
+    pub fn files(&self) -> Vec<(String, String)> {
+        let mut files: Vec<(String, String)> = self
+            .messages
+            .iter()
+            .map(|m| (format!("msg/{}.msg", m.name), m.to_msg(self.header)))
+            .collect();
+        files.push(("package.xml".into(), self.package_xml()));
+        files.push(("CMakeLists.txt".into(), self.cmake_lists()));
+        let mapping = serde_json::to_string_pretty(&self.mapping()).unwrap_or_default();
+        files.push(("mapping.json".into(), mapping + "\n"));
+        files
+    }
+
+    /// Write the package into `dir`, replacing its `msg` directory.
+    pub fn write(&self, dir: &Path) -> Result<usize> {
+        let msg_dir = dir.join("msg");
+        if msg_dir.exists() {
+            fs::remove_dir_all(&msg_dir)?;
+        }
+        fs::create_dir_all(&msg_dir)?;
+        let files = self.files();
+        for (path, contents) in &files {
+            fs::write(dir.join(path), contents)?;
+        }
+        Ok(files.len())
+    }
+
+    fn package_xml(&self) -> String {
+        let header = if self.header { "  <depend>std_msgs</depend>\n" } else { "" };
+        format!(
+            r#"<?xml version="1.0"?>
+<?xml-model href="http://download.ros.org/schema/package_format3.xsd" schematypens="http://www.w3.org/2001/XMLSchema"?>
+<package format="3">
+  <name>{}</name>
+  <version>0.1.0</version>
+  <description>u-blox UBX messages, generated from the UBX protocol schema</description>
+  <maintainer email="maintainer@example.com">maintainer</maintainer>
+  <license>MIT</license>
+
+  <buildtool_depend>ament_cmake</buildtool_depend>
+  <buildtool_depend>rosidl_default_generators</buildtool_depend>
+{header}  <exec_depend>rosidl_default_runtime</exec_depend>
+  <member_of_group>rosidl_interface_packages</member_of_group>
+
+  <export>
+    <build_type>ament_cmake</build_type>
+  </export>
+</package>
+"#,
+            self.name
+        )
+    }
+
+    fn cmake_lists(&self) -> String {
+        let mut out = format!(
+            "cmake_minimum_required(VERSION 3.8)\nproject({})\n\nfind_package(ament_cmake REQUIRED)\n\
+             find_package(rosidl_default_generators REQUIRED)\n",
+            self.name
+        );
+        if self.header {
+            out.push_str("find_package(std_msgs REQUIRED)\n");
+        }
+        out.push_str("\nrosidl_generate_interfaces(${PROJECT_NAME}\n");
+        for m in &self.messages {
+            let _ = writeln!(out, "  \"msg/{}.msg\"", m.name);
+        }
+        if self.header {
+            out.push_str("  DEPENDENCIES std_msgs\n");
+        }
+        out.push_str(")\n\nament_package()\n");
+        out
+    }
+}
+
+fn add_message(
+    out: &mut Vec<MsgDef>,
+    msg: &MessageDef,
+    variant: Option<&str>,
+    payload: &PayloadDef,
+    options: DecodeOptions,
+) {
+    let mut name = type_name(msg.short_name());
+    if let Some(variant) = variant {
+        name += &type_name(variant);
+    }
+    let mut def = MsgDef {
+        name: name.clone(),
+        source: Some((msg.name.clone(), variant.map(str::to_string))),
+        ids: Some((msg.class_id, msg.message_id)),
+        fields: Vec::new(),
+    };
+    let mut children = Vec::new();
+    let mut used = BTreeSet::new();
+    for field in &payload.fields {
+        def.fields.extend(fields_for(&name, field, options, &mut used, &mut children));
+    }
+    for group in &payload.repeated_groups {
+        let element = format!("{name}{}", type_name(&group.name));
+        add_group(&mut children, &element, &group.fields, options);
+        def.fields.push(MsgField {
+            ros_type: format!("{element}[]"),
+            name: unique(field_name(&group.name), &mut used),
+            path: group.name.clone(),
+            nested: Some(element),
+            comment: None,
+        });
+    }
+    out.push(def);
+    out.extend(children);
+}
+
+fn add_group(out: &mut Vec<MsgDef>, name: &str, fields: &[FieldDef], options: DecodeOptions) {
+    let mut def = MsgDef {
+        name: name.to_string(),
+        source: None,
+        ids: None,
+        fields: Vec::new(),
+    };
+    let mut children = Vec::new();
+    let mut used = BTreeSet::new();
+    for field in fields {
+        def.fields.extend(fields_for(name, field, options, &mut used, &mut children));
+    }
+    out.push(def);
+    out.extend(children);
+}
+
+/// The ROS fields for one schema field; group element types go to `children`.
+fn fields_for(
+    parent: &str,
+    field: &FieldDef,
+    options: DecodeOptions,
+    used: &mut BTreeSet<String>,
+    children: &mut Vec<MsgDef>,
+) -> Vec<MsgField> {
+    if field.reserved && !options.include_reserved {
+        return Vec::new();
+    }
+    let scaled = options.scale && field.multiplier().is_some();
+    let base_type = |base: BaseType| if scaled { "float64" } else { ros_base(base) };
+    let bounds = |count: &Count| match count {
+        Count::Fixed(n) => format!("[{n}]"),
+        _ => "[]".into(),
+    };
+    let simple = |ros_type: String, used: &mut BTreeSet<String>| MsgField {
+        ros_type,
+        name: unique(field_name(&field.name), used),
+        path: field.name.clone(),
+        nested: None,
+        comment: comment(field, scaled),
+    };
+    match &field.data_type {
+        DataType::Scalar(_) if options.expand_flags && !field.bits.is_empty() => field
+            .bits
+            .iter()
+            .filter(|bit| !bit.reserved || options.include_reserved)
+            .map(|bit| MsgField {
+                ros_type: match bit.bit_width {
+                    1 => "bool",
+                    2..=8 => "uint8",
+                    9..=16 => "uint16",
+                    17..=32 => "uint32",
+                    _ => "uint64",
+                }
+                .into(),
+                name: unique(format!("{}_{}", field_name(&field.name), field_name(&bit.name)), used),
+                path: format!("{}.{}", field.name, bit.name),
+                nested: None,
+                comment: bit.description.as_deref().and_then(first_line),
+            })
+            .collect(),
+        DataType::Scalar(base) => vec![simple(base_type(*base).into(), used)],
+        DataType::Array { base: BaseType::CH, .. } => vec![simple("string".into(), used)],
+        DataType::Array { base, count } => vec![simple(format!("{}{}", base_type(*base), bounds(count)), used)],
+        DataType::Group { fields, count, .. } => {
+            let element = format!("{parent}{}", type_name(&field.name));
+            add_group(children, &element, fields, options);
+            let mut group = simple(format!("{element}{}", bounds(count)), used);
+            group.nested = Some(element);
+            vec![group]
+        }
+        DataType::Unknown(_) => Vec::new(),
+    }
+}
+
+fn ros_base(base: BaseType) -> &'static str {
+    match base {
+        BaseType::U1 | BaseType::X1 => "uint8",
+        BaseType::I1 => "int8",
+        BaseType::U2 | BaseType::X2 => "uint16",
+        BaseType::I2 => "int16",
+        BaseType::U4 | BaseType::X4 => "uint32",
+        BaseType::I4 => "int32",
+        BaseType::I8 => "int64",
+        BaseType::R4 => "float32",
+        BaseType::R8 => "float64",
+        BaseType::CH => "string",
+    }
+}
+
+fn comment(field: &FieldDef, scaled: bool) -> Option<String> {
+    let description = field.description.as_deref().and_then(first_line);
+    let unit = field.unit.as_deref().filter(|u| !u.is_empty() && *u != "-");
+    let unit = match (unit, field.scale.as_ref().and_then(|s| s.raw.as_deref())) {
+        (Some(unit), Some(raw)) if !scaled => Some(format!("{unit} * {raw}")),
+        (unit, _) => unit.map(str::to_string),
+    };
+    match (description, unit) {
+        (Some(d), Some(u)) => Some(format!("{d} [{u}]")),
+        (Some(d), None) => Some(d),
+        (None, Some(u)) => Some(format!("[{u}]")),
+        (None, None) => None,
+    }
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string)
+}
+
+/// `NAV-HPPOSLLH` -> `NavHpposllh`, `satellites` -> `Satellites`.
+pub fn type_name(name: &str) -> String {
+    let mut out = String::new();
+    for word in field_name(name.trim_start_matches("UBX-")).split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars);
+        }
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, 'T');
+    }
+    out
+}
+
+/// A schema field name as a ROS field name: `iTOW` -> `i_tow`,
+/// `gnssFixOK` -> `gnss_fix_ok`, `UTCStandard` -> `utc_standard`.
+pub fn field_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            out.push('_');
+            continue;
+        }
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if prev.is_ascii_lowercase() || prev.is_ascii_digit() || (prev.is_ascii_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    // ROS names have no leading, trailing or doubled underscores.
+    let mut name = out.split('_').filter(|w| !w.is_empty()).collect::<Vec<_>>().join("_");
+    if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        name.insert_str(0, "f_");
+    }
+    name
+}
+
+/// `name`, or `name_2` and so on when bitfield members collide with fields.
+fn unique(name: String, used: &mut BTreeSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{name}_{n}");
+        n += 1;
+    }
+    candidate
+}
@@ -0,0 +1,210 @@
+//! RTK carrier-solution timeline of a log.
+//!
+//! Follows `flags.carrSoln` of NAV-PVT and of NAV-RELPOSNED (moving-base and
+//! base/rover setups report the relative solution there, with
+//! `relPosValid`) epoch by epoch, and derives what RTK evaluations quote:
+//! time to first float and first fixed solution, float/fixed transitions,
+//! and outages, the stretches from losing a fixed solution to regaining
+//! it. Time is receiver time, from the epochs' iTOW.
+//!
+//! Messages must be decoded with flag expansion enabled.
+
+use std::fmt;
+
+use crate::decode::DecodedMessage;
+use crate::time::itow_delta;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CarrierState {
+    None,
+    Float,
+    Fixed,
+}
+
+impl CarrierState {
+    /// From a `carrSoln` value; reserved values count as none.
+    pub fn from_carr_soln(value: u64) -> Self {
+        match value {
+            1 => CarrierState::Float,
+            2 => CarrierState::Fixed,
+            _ => CarrierState::None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CarrierState::None => "none",
+            CarrierState::Float => "float",
+            CarrierState::Fixed => "fixed",
+        }
+    }
+}
+
+/// Consecutive epochs in one state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub state: CarrierState,
+    /// iTOW of the first epoch of the segment.
+    pub start: u32,
+    /// Milliseconds from the first epoch of the log to the segment's start.
+    pub offset_ms: i64,
+    /// Until the next segment starts, or the last epoch of the log.
+    pub duration_ms: i64,
+    pub epochs: usize,
+}
+
+/// A fixed solution lost and, if `recovered`, regained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outage {
+    /// iTOW of the first epoch without a fixed solution.
+    pub start: u32,
+    pub duration_ms: i64,
+    /// False when the log ends before the fix is regained.
+    pub recovered: bool,
+}
+
+/// The timeline of one message's carrier solution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub segments: Vec<Segment>,
+    /// iTOW and offset of the latest epoch.
+    last: Option<(u32, i64)>,
+}
+
+impl Timeline {
+    fn push(&mut self, itow: u32, state: CarrierState) {
+        let offset = match self.last {
+            None => 0,
+            Some((last, offset)) => {
+                let d = itow_delta(last, itow);
+                if d <= 0 {
+                    return;
+                }
+                offset + d
+            }
+        };
+        self.last = Some((itow, offset));
+        match self.segments.last_mut() {
+            Some(segment) if segment.state == state => {
+                segment.epochs += 1;
+                segment.duration_ms = offset - segment.offset_ms;
+            }
+            previous => {
+                if let Some(previous) = previous {
+                    previous.duration_ms = offset - previous.offset_ms;
+                }
+                self.segments.push(Segment {
+                    state,
+                    start: itow,
+                    offset_ms: offset,
+                    duration_ms: 0,
+                    epochs: 1,
+                });
+            }
+        }
+    }
+
+    pub fn epochs(&self) -> usize {
+        self.segments.iter().map(|s| s.epochs).sum()
+    }
+
+    /// Milliseconds from the first to the last epoch.
+    pub fn duration_ms(&self) -> i64 {
+        self.last.map_or(0, |(_, offset)| offset)
+    }
+
+    /// Milliseconds from the first epoch to the first in `state` or better.
+    pub fn time_to(&self, state: CarrierState) -> Option<i64> {
+        self.segments.iter().find(|s| s.state >= state).map(|s| s.offset_ms)
+    }
+
+    /// Milliseconds spent in `state`.
+    pub fn time_in(&self, state: CarrierState) -> i64 {
+        self.segments.iter().filter(|s| s.state == state).map(|s| s.duration_ms).sum()
+    }
+
+    /// Number of changes from `from` to `to`.
+    pub fn transitions(&self, from: CarrierState, to: CarrierState) -> usize {
+        self.segments.windows(2).filter(|w| w[0].state == from && w[1].state == to).count()
+    }
+
+    /// Every loss of a fixed solution after the first fix.
+    pub fn outages(&self) -> Vec<Outage> {
+        let mut outages = Vec::new();
+        let mut open: Option<&Segment> = None;
+        for (prev, next) in self.segments.iter().zip(self.segments.iter().skip(1)) {
+            match (prev.state, next.state) {
+                (CarrierState::Fixed, _) => open = Some(next),
+                (_, CarrierState::Fixed) => {
+                    if let Some(start) = open.take() {
+                        outages.push(Outage {
+                            start: start.start,
+                            duration_ms: next.offset_ms - start.offset_ms,
+                            recovered: true,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = open {
+            outages.push(Outage {
+                start: start.start,
+                duration_ms: self.duration_ms() - start.offset_ms,
+                recovered: false,
+            });
+        }
+        outages
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iTOW {}: {} for {:.1} s ({} epochs)",
+            self.start,
+            self.state.label(),
+            self.duration_ms as f64 / 1_000.0,
+            self.epochs
+        )
+    }
+}
+
+/// Timelines of the NAV-PVT and NAV-RELPOSNED carrier solutions.
+#[derive(Debug, Clone, Default)]
+pub struct RtkAnalyzer {
+    pub pvt: Timeline,
+    pub relposned: Timeline,
+}
+
+impl RtkAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, msg: &DecodedMessage) {
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        let Some(itow) = msg.itow() else {
+            return;
+        };
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => {
+                let state = match int("flags.gnssFixOK") {
+                    1 => CarrierState::from_carr_soln(int("flags.carrSoln")),
+                    _ => CarrierState::None,
+                };
+                self.pvt.push(itow, state);
+            }
+            "UBX-NAV-RELPOSNED" => {
+                let state = match int("flags.relPosValid") {
+                    1 => CarrierState::from_carr_soln(int("flags.carrSoln")),
+                    _ => CarrierState::None,
+                };
+                self.relposned.push(itow, state);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,988 @@
+//! Load and query the UBX message schema (`data/messages/ubx_messages.json`).
+//!
+//! The extracted data is not perfectly uniform: arrays, repeated groups and
+//! bitfields appear in several shapes depending on which extraction pass
+//! produced them. Everything is normalized here so the decoder and encoder
+//! only ever see one representation.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value as Json};
+
+use crate::error::{Error, Result};
+
+/// Default schema location, relative to this crate.
+pub fn default_schema_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/messages/ubx_messages.json")
+}
+
+/// Scalar UBX data types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseType {
+    U1,
+    I1,
+    X1,
+    U2,
+    I2,
+    X2,
+    U4,
+    I4,
+    X4,
+    R4,
+    R8,
+    I8,
+    CH,
+}
+
+impl BaseType {
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "U1" | "RU1_3" => BaseType::U1,
+            "I1" => BaseType::I1,
+            "X1" => BaseType::X1,
+            "U2" | "RU2_5" => BaseType::U2,
+            "I2" => BaseType::I2,
+            "X2" => BaseType::X2,
+            "U4" => BaseType::U4,
+            "I4" => BaseType::I4,
+            "X4" => BaseType::X4,
+            "R4" => BaseType::R4,
+            "R8" => BaseType::R8,
+            "I8" => BaseType::I8,
+            "CH" => BaseType::CH,
+            _ => return None,
+        })
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            BaseType::U1 | BaseType::I1 | BaseType::X1 | BaseType::CH => 1,
+            BaseType::U2 | BaseType::I2 | BaseType::X2 => 2,
+            BaseType::U4 | BaseType::I4 | BaseType::X4 | BaseType::R4 => 4,
+            BaseType::R8 | BaseType::I8 => 8,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, BaseType::I1 | BaseType::I2 | BaseType::I4 | BaseType::I8)
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, BaseType::R4 | BaseType::R8)
+    }
+
+    /// X-types carry bitfields.
+    pub fn is_bitfield(self) -> bool {
+        matches!(self, BaseType::X1 | BaseType::X2 | BaseType::X4)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BaseType::U1 => "U1",
+            BaseType::I1 => "I1",
+            BaseType::X1 => "X1",
+            BaseType::U2 => "U2",
+            BaseType::I2 => "I2",
+            BaseType::X2 => "X2",
+            BaseType::U4 => "U4",
+            BaseType::I4 => "I4",
+            BaseType::X4 => "X4",
+            BaseType::R4 => "R4",
+            BaseType::R8 => "R8",
+            BaseType::I8 => "I8",
+            BaseType::CH => "CH",
+        }
+    }
+}
+
+/// How many elements an array or repeated group has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Count {
+    Fixed(usize),
+    /// Taken from an earlier field (possibly a dotted bitfield member),
+    /// multiplied by `multiplier` (e.g. `numKeys * 8`).
+    Field { name: String, multiplier: usize },
+    /// As many as fit in the rest of the payload.
+    Remaining,
+}
+
+impl Count {
+    fn parse(value: &Json) -> Count {
+        match value {
+            Json::Number(n) => n.as_u64().map_or(Count::Remaining, |n| Count::Fixed(n as usize)),
+            Json::String(s) => Count::parse_str(s),
+            _ => Count::Remaining,
+        }
+    }
+
+    fn parse_str(s: &str) -> Count {
+        let s = s.trim();
+        if let Ok(n) = s.parse() {
+            return Count::Fixed(n);
+        }
+        let parts: Vec<&str> = s.split('*').map(str::trim).collect();
+        match parts.as_slice() {
+            [name] if is_identifier(name) && *name != "N" => Count::Field {
+                name: name.to_string(),
+                multiplier: 1,
+            },
+            [a, b] => match (a.parse::<usize>(), b.parse::<usize>()) {
+                (Err(_), Ok(m)) if is_identifier(a) => Count::Field {
+                    name: a.to_string(),
+                    multiplier: m,
+                },
+                (Ok(m), Err(_)) if is_identifier(b) => Count::Field {
+                    name: b.to_string(),
+                    multiplier: m,
+                },
+                _ => Count::Remaining,
+            },
+            _ => Count::Remaining,
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// A field's data type after normalization.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataType {
+    Scalar(BaseType),
+    Array { base: BaseType, count: Count },
+    /// An inline array of structures (e.g. NAV-SAT satellites).
+    Group {
+        fields: Vec<FieldDef>,
+        count: Count,
+        element_size: usize,
+    },
+    /// Anything the normalizer could not interpret; skipped when decoding.
+    Unknown(String),
+}
+
+impl DataType {
+    /// Size in bytes, if it does not depend on the payload.
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            DataType::Scalar(base) => Some(base.size()),
+            DataType::Array {
+                base,
+                count: Count::Fixed(n),
+            } => Some(base.size() * n),
+            DataType::Group {
+                count: Count::Fixed(n),
+                element_size,
+                ..
+            } => Some(element_size * n),
+            _ => None,
+        }
+    }
+
+    pub fn base(&self) -> Option<BaseType> {
+        match self {
+            DataType::Scalar(base) | DataType::Array { base, .. } => Some(*base),
+            _ => None,
+        }
+    }
+}
+
+/// Scale factor converting a raw integer to a physical value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale {
+    pub raw: Option<String>,
+    pub multiplier: Option<f64>,
+}
+
+/// One member of a bitfield.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitDef {
+    pub name: String,
+    pub bit_offset: u32,
+    pub bit_width: u32,
+    pub description: Option<String>,
+    pub reserved: bool,
+}
+
+impl BitDef {
+    /// Extract this member from a raw field value.
+    pub fn extract(&self, raw: u64) -> u64 {
+        if self.bit_offset >= 64 {
+            return 0;
+        }
+        let shifted = raw >> self.bit_offset;
+        if self.bit_width >= 64 {
+            shifted
+        } else {
+            shifted & ((1u64 << self.bit_width) - 1)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDef {
+    pub name: String,
+    /// `None` for fields whose position is a formula.
+    pub byte_offset: Option<usize>,
+    pub data_type: DataType,
+    pub description: Option<String>,
+    pub unit: Option<String>,
+    pub scale: Option<Scale>,
+    pub reserved: bool,
+    pub fixed_value: Option<i64>,
+    pub enumeration: Option<String>,
+    pub bits: Vec<BitDef>,
+}
+
+impl FieldDef {
+    /// Multiplier to apply when scaling is requested.
+    pub fn multiplier(&self) -> Option<f64> {
+        self.scale.as_ref().and_then(|s| s.multiplier)
+    }
+}
+
+/// Where a repeated group starts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BaseOffset {
+    Fixed(usize),
+    /// `base + sum(field * multiplier)`.
+    Dynamic {
+        base: usize,
+        terms: Vec<(String, usize)>,
+    },
+    /// Not given; the group follows the last fixed field.
+    AfterFields,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatedGroup {
+    pub name: String,
+    pub count: Count,
+    pub group_size: usize,
+    pub base_offset: BaseOffset,
+    pub fields: Vec<FieldDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadLength {
+    Fixed(usize),
+    Variable { base: usize, min: Option<usize>, max: Option<usize> },
+    Alternatives(Vec<usize>),
+    Unspecified,
+}
+
+impl PayloadLength {
+    /// Whether a payload of `len` bytes is consistent with this definition.
+    pub fn accepts(&self, len: usize) -> bool {
+        match self {
+            PayloadLength::Fixed(n) => *n == len,
+            PayloadLength::Variable { base, min, max } => {
+                len >= min.unwrap_or(*base) && max.is_none_or(|m| len <= m)
+            }
+            PayloadLength::Alternatives(lens) => lens.contains(&len),
+            PayloadLength::Unspecified => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadDef {
+    pub length: PayloadLength,
+    /// Fields sorted by byte offset; fields without an integer offset last.
+    pub fields: Vec<FieldDef>,
+    pub repeated_groups: Vec<RepeatedGroup>,
+}
+
+impl PayloadDef {
+    pub fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discriminator {
+    Field { byte_offset: usize, value: i64 },
+    Length(usize),
+    LengthRange { min: usize, max: Option<usize> },
+    None,
+}
+
+impl Discriminator {
+    pub fn matches(&self, payload: &[u8]) -> bool {
+        match *self {
+            Discriminator::Field { byte_offset, value } => {
+                payload.get(byte_offset).is_some_and(|&b| i64::from(b) == value)
+            }
+            Discriminator::Length(n) => payload.len() == n,
+            Discriminator::LengthRange { min, max } => {
+                payload.len() >= min && max.is_none_or(|m| payload.len() <= m)
+            }
+            Discriminator::None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub description: Option<String>,
+    pub discriminator: Discriminator,
+    pub payload: PayloadDef,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SupportedVersions {
+    pub protocol_versions: Vec<u32>,
+    pub min_protocol_version: Option<u32>,
+    pub max_protocol_version: Option<u32>,
+    pub source_manuals: Vec<String>,
+}
+
+/// An example frame embedded in a message definition, with the values it
+/// must decode to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub description: Option<String>,
+    /// The whole frame as hex.
+    pub frame: String,
+    pub variant: Option<String>,
+    /// Expected values by [`Fields::flatten`](crate::Fields::flatten) path,
+    /// scaled and with bitfields expanded.
+    pub fields: Map<String, Json>,
+}
+
+/// How a legacy CFG message maps onto generation 9 configuration keys.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigMigration {
+    pub port: Option<MigrationPort>,
+    /// Fields holding the class and ID of the message a key configures.
+    pub message: Option<(String, String)>,
+    pub keys: Vec<MigrationKey>,
+}
+
+/// Port names substituted for `{port}` in key names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationPort {
+    /// Field holding the port number; `None` when mapped fields are arrays
+    /// indexed by port.
+    pub field: Option<String>,
+    /// Name by port number; `None` for ports without keys.
+    pub names: Vec<Option<String>>,
+}
+
+/// One field and the configuration key its raw value moves to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationKey {
+    /// Field path, with bitfields expanded.
+    pub field: String,
+    /// Key name, with `{port}` and `{message}` placeholders.
+    pub key: String,
+    /// Flag that must be set for the field to apply.
+    pub when: Option<String>,
+    /// Ports the field applies to; empty for all of them.
+    pub ports: Vec<String>,
+    /// Legacy raw values and the key values they become, where the two
+    /// encodings differ; values not listed are not migrated.
+    pub values: Vec<(i64, Json)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDef {
+    pub name: String,
+    pub class_id: u8,
+    pub message_id: u8,
+    pub message_type: String,
+    pub description: Option<String>,
+    pub payload: Option<PayloadDef>,
+    pub variants: Vec<Variant>,
+    pub variant_aliases: Vec<String>,
+    pub supported_versions: SupportedVersions,
+    pub examples: Vec<Example>,
+    pub config_migration: Option<ConfigMigration>,
+}
+
+impl MessageDef {
+    /// Name without the `UBX-` prefix, e.g. `NAV-PVT`.
+    pub fn short_name(&self) -> &str {
+        self.name.strip_prefix("UBX-").unwrap_or(&self.name)
+    }
+
+    /// Select the payload definition for a given payload, handling variants.
+    pub fn payload_for(&self, payload: &[u8]) -> Option<(&PayloadDef, Option<&Variant>)> {
+        if self.variants.is_empty() {
+            return self.payload.as_ref().map(|p| (p, None));
+        }
+        self.variants
+            .iter()
+            .find(|v| v.discriminator.matches(payload))
+            .map(|v| (&v.payload, Some(v)))
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&Variant> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+}
+
+/// The loaded message schema with lookup indices.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub schema_version: String,
+    pub messages: Vec<MessageDef>,
+    by_ids: HashMap<(u8, u8), Vec<usize>>,
+    by_name: HashMap<String, usize>,
+}
+
+impl Schema {
+    /// Load the schema from the repository's default location.
+    pub fn load_default() -> Result<Self> {
+        Self::load(default_schema_path())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Schema(format!("{}: {e}", path.display())))?;
+        Self::from_json_str(&text)
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let json: Json = serde_json::from_str(text)?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self> {
+        let messages = json
+            .get("messages")
+            .and_then(Json::as_array)
+            .ok_or_else(|| Error::Schema("missing 'messages' array".into()))?
+            .iter()
+            .map(parse_message)
+            .collect::<Result<Vec<_>>>()?;
+        let schema_version = json
+            .get("schema_version")
+            .and_then(Json::as_str)
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self::from_messages(schema_version, messages))
+    }
+
+    pub fn from_messages(schema_version: String, messages: Vec<MessageDef>) -> Self {
+        let mut by_ids: HashMap<(u8, u8), Vec<usize>> = HashMap::new();
+        let mut by_name = HashMap::new();
+        for (i, msg) in messages.iter().enumerate() {
+            by_ids.entry((msg.class_id, msg.message_id)).or_default().push(i);
+            by_name.insert(normalize_name(&msg.name), i);
+            for alias in &msg.variant_aliases {
+                by_name.entry(normalize_name(alias)).or_insert(i);
+            }
+        }
+        Self {
+            schema_version,
+            messages,
+            by_ids,
+            by_name,
+        }
+    }
+
+    /// Look up a message by name. Accepts `UBX-NAV-PVT`, `NAV-PVT`,
+    /// `nav_pvt` and variant aliases such as `UBX-MGA-GPS-EPH`.
+    pub fn message_by_name(&self, name: &str) -> Option<&MessageDef> {
+        self.by_name.get(&normalize_name(name)).map(|&i| &self.messages[i])
+    }
+
+    /// All definitions sharing a class/ID pair.
+    pub fn messages_by_ids(&self, class_id: u8, message_id: u8) -> impl Iterator<Item = &MessageDef> {
+        self.by_ids
+            .get(&(class_id, message_id))
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.messages[i])
+    }
+
+    /// Pick the definition that best matches a payload.
+    ///
+    /// Several messages share a class/ID pair (CFG-DAT, MGA-GPS-*, ...). They
+    /// are told apart by `fixed_value` fields first and payload length second.
+    pub fn message_for_payload(&self, class_id: u8, message_id: u8, payload: &[u8]) -> Option<&MessageDef> {
+        let candidates: Vec<&MessageDef> = self.messages_by_ids(class_id, message_id).collect();
+        if candidates.len() <= 1 {
+            return candidates.first().copied();
+        }
+        candidates
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|&(i, msg)| (match_score(msg, payload), std::cmp::Reverse(i)))
+            .map(|(_, msg)| msg)
+    }
+}
+
+fn match_score(msg: &MessageDef, payload: &[u8]) -> i32 {
+    let Some((def, _)) = msg.payload_for(payload) else {
+        return -10;
+    };
+    let mut score = 0;
+    for field in &def.fields {
+        if let (Some(expected), Some(offset), DataType::Scalar(base)) =
+            (field.fixed_value, field.byte_offset, &field.data_type)
+        {
+            if let Some(bytes) = payload.get(offset..offset + base.size()) {
+                let mut raw = [0u8; 8];
+                raw[..bytes.len()].copy_from_slice(bytes);
+                if u64::from_le_bytes(raw) as i64 == expected {
+                    score += 2;
+                } else {
+                    score -= 4;
+                }
+            }
+        }
+    }
+    match def.length {
+        PayloadLength::Fixed(n) if n == payload.len() => score += 3,
+        ref length if length.accepts(payload.len()) => score += 1,
+        _ => score -= 1,
+    }
+    score
+}
+
+/// Canonical form used for name lookups: upper case, `-` separated, with the
+/// `UBX-` prefix.
+pub fn normalize_name(name: &str) -> String {
+    let upper = name.trim().to_ascii_uppercase().replace(['_', ' '], "-");
+    if upper.starts_with("UBX-") {
+        upper
+    } else {
+        format!("UBX-{upper}")
+    }
+}
+
+/// Parse a hex string (`"0x01"`) or integer id.
+pub fn parse_hex_id(value: &Json) -> Option<u8> {
+    match value {
+        Json::String(s) => {
+            let digits = s.trim_start_matches("0x").trim_start_matches("0X");
+            u8::from_str_radix(digits, 16).ok()
+        }
+        Json::Number(n) => n.as_u64().and_then(|n| u8::try_from(n).ok()),
+        _ => None,
+    }
+}
+
+fn parse_message(json: &Json) -> Result<MessageDef> {
+    let name = str_field(json, "name")
+        .ok_or_else(|| Error::Schema("message without a name".into()))?
+        .to_string();
+    let class_id = json
+        .get("class_id")
+        .and_then(parse_hex_id)
+        .ok_or_else(|| Error::Schema(format!("{name}: invalid class_id")))?;
+    let message_id = json
+        .get("message_id")
+        .and_then(parse_hex_id)
+        .ok_or_else(|| Error::Schema(format!("{name}: invalid message_id")))?;
+
+    let variants = json
+        .get("variants")
+        .and_then(Json::as_array)
+        .map(|vs| vs.iter().map(parse_variant).collect())
+        .unwrap_or_default();
+
+    Ok(MessageDef {
+        class_id,
+        message_id,
+        message_type: str_field(json, "message_type").unwrap_or_default().to_string(),
+        description: str_field(json, "description").map(String::from),
+        payload: json.get("payload").map(parse_payload),
+        variants,
+        variant_aliases: string_list(json.get("variant_aliases")),
+        supported_versions: parse_versions(json.get("supported_versions")),
+        examples: json
+            .get("examples")
+            .and_then(Json::as_array)
+            .map(|es| es.iter().map(parse_example).collect())
+            .unwrap_or_default(),
+        config_migration: json.get("config_migration").map(parse_migration),
+        name,
+    })
+}
+
+fn parse_versions(json: Option<&Json>) -> SupportedVersions {
+    let Some(json) = json else {
+        return SupportedVersions::default();
+    };
+    let as_u32 = |key: &str| json.get(key).and_then(Json::as_u64).map(|v| v as u32);
+    SupportedVersions {
+        protocol_versions: json
+            .get("protocol_versions")
+            .and_then(Json::as_array)
+            .map(|vs| vs.iter().filter_map(Json::as_u64).map(|v| v as u32).collect())
+            .unwrap_or_default(),
+        min_protocol_version: as_u32("min_protocol_version"),
+        max_protocol_version: as_u32("max_protocol_version"),
+        source_manuals: string_list(json.get("source_manuals")),
+    }
+}
+
+fn parse_example(json: &Json) -> Example {
+    Example {
+        description: str_field(json, "description").map(String::from),
+        frame: str_field(json, "frame").unwrap_or_default().to_string(),
+        variant: str_field(json, "variant").map(String::from),
+        fields: json.get("fields").and_then(Json::as_object).cloned().unwrap_or_default(),
+    }
+}
+
+fn parse_migration(json: &Json) -> ConfigMigration {
+    let port = json.get("port").map(|port| MigrationPort {
+        field: str_field(port, "field").map(String::from),
+        names: port
+            .get("names")
+            .and_then(Json::as_array)
+            .map(|names| names.iter().map(|n| n.as_str().map(String::from)).collect())
+            .unwrap_or_default(),
+    });
+    let message = json.get("message").and_then(|m| {
+        Some((str_field(m, "class")?.to_string(), str_field(m, "id")?.to_string()))
+    });
+    let keys = json
+        .get("keys")
+        .and_then(Json::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(|k| {
+                    Some(MigrationKey {
+                        field: str_field(k, "field")?.to_string(),
+                        key: str_field(k, "key")?.to_string(),
+                        when: str_field(k, "when").map(String::from),
+                        ports: string_list(k.get("ports")),
+                        values: k
+                            .get("values")
+                            .and_then(Json::as_object)
+                            .map(|values| {
+                                values
+                                    .iter()
+                                    .filter_map(|(raw, v)| Some((raw.parse().ok()?, v.clone())))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    ConfigMigration { port, message, keys }
+}
+
+fn parse_variant(json: &Json) -> Variant {
+    let disc = json.get("discriminator");
+    let discriminator = match disc {
+        Some(d) if d.get("byte_offset").is_some() && d.get("value").is_some() => Discriminator::Field {
+            byte_offset: d["byte_offset"].as_u64().unwrap_or(0) as usize,
+            value: json_int(&d["value"]).unwrap_or(0),
+        },
+        Some(d) if d.get("payload_length").is_some() => {
+            Discriminator::Length(d["payload_length"].as_u64().unwrap_or(0) as usize)
+        }
+        Some(d) if d.get("payload_length_range").is_some() => {
+            let r = &d["payload_length_range"];
+            Discriminator::LengthRange {
+                min: r.get("min").and_then(Json::as_u64).unwrap_or(0) as usize,
+                max: r.get("max").and_then(Json::as_u64).map(|v| v as usize),
+            }
+        }
+        _ => Discriminator::None,
+    };
+    Variant {
+        name: str_field(json, "name").unwrap_or_default().to_string(),
+        description: str_field(json, "description").map(String::from),
+        discriminator,
+        payload: json.get("payload").map(parse_payload).unwrap_or(PayloadDef {
+            length: PayloadLength::Unspecified,
+            fields: Vec::new(),
+            repeated_groups: Vec::new(),
+        }),
+    }
+}
+
+fn parse_payload(json: &Json) -> PayloadDef {
+    let length = match json.get("length") {
+        Some(Json::Number(n)) => PayloadLength::Fixed(n.as_u64().unwrap_or(0) as usize),
+        Some(l) if l.get("fixed").is_some() => PayloadLength::Fixed(l["fixed"].as_u64().unwrap_or(0) as usize),
+        Some(l) if l.get("variable").is_some() => {
+            let v = &l["variable"];
+            let get = |k: &str| v.get(k).and_then(Json::as_u64).map(|n| n as usize);
+            PayloadLength::Variable {
+                base: get("base").unwrap_or(0),
+                min: get("min"),
+                max: get("max"),
+            }
+        }
+        Some(l) if l.get("alternatives").is_some() => PayloadLength::Alternatives(
+            l["alternatives"]
+                .as_array()
+                .map(|a| a.iter().filter_map(Json::as_u64).map(|n| n as usize).collect())
+                .unwrap_or_default(),
+        ),
+        _ => PayloadLength::Unspecified,
+    };
+    let repeated_groups = json
+        .get("repeated_groups")
+        .and_then(Json::as_array)
+        .map(|gs| gs.iter().map(parse_repeated_group).collect())
+        .unwrap_or_default();
+    PayloadDef {
+        length,
+        fields: parse_fields(json.get("fields")),
+        repeated_groups,
+    }
+}
+
+fn parse_repeated_group(json: &Json) -> RepeatedGroup {
+    let count = match str_field(json, "repetition_type").unwrap_or_default() {
+        "count_field" => json
+            .get("count_field")
+            .map(Count::parse)
+            .unwrap_or(Count::Remaining),
+        "fixed_count" | "constant" => json
+            .get("count")
+            .or_else(|| json.get("constant_count"))
+            .map(Count::parse)
+            .unwrap_or(Count::Remaining),
+        _ => Count::Remaining,
+    };
+    let base_offset = match json.get("base_offset") {
+        Some(Json::Number(n)) => BaseOffset::Fixed(n.as_u64().unwrap_or(0) as usize),
+        Some(Json::Object(obj)) => BaseOffset::Dynamic {
+            base: obj.get("base").and_then(Json::as_u64).unwrap_or(0) as usize,
+            terms: obj
+                .get("add_field_products")
+                .and_then(Json::as_array)
+                .map(|terms| {
+                    terms
+                        .iter()
+                        .filter_map(|t| {
+                            let field = str_field(t, "field")?.trim_matches(['(', ')']).to_string();
+                            let mult = t.get("multiplier").and_then(Json::as_u64).unwrap_or(1) as usize;
+                            Some((field, mult))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        _ => BaseOffset::AfterFields,
+    };
+    RepeatedGroup {
+        name: str_field(json, "name").unwrap_or("group").to_string(),
+        count,
+        group_size: json.get("group_size_bytes").and_then(Json::as_u64).unwrap_or(0) as usize,
+        base_offset,
+        fields: parse_fields(json.get("fields")),
+    }
+}
+
+fn parse_fields(json: Option<&Json>) -> Vec<FieldDef> {
+    let mut fields: Vec<FieldDef> = json
+        .and_then(Json::as_array)
+        .map(|fs| fs.iter().filter_map(|f| f.as_object()).map(parse_field).collect())
+        .unwrap_or_default();
+    // Integer offsets first, in order; formula offsets keep their relative order.
+    fields.sort_by_key(|f| f.byte_offset.map_or((1, 0), |o| (0, o)));
+    fields
+}
+
+fn parse_field(obj: &Map<String, Json>) -> FieldDef {
+    let json = Json::Object(obj.clone());
+    let scale = obj.get("scale").or_else(|| obj.get("scaling")).and_then(|s| {
+        let multiplier = s.get("multiplier").and_then(Json::as_f64);
+        let raw = str_field(s, "raw").map(String::from);
+        (multiplier.is_some() || raw.is_some()).then_some(Scale { raw, multiplier })
+    });
+    FieldDef {
+        name: str_field(&json, "name").unwrap_or_default().to_string(),
+        byte_offset: obj.get("byte_offset").and_then(Json::as_u64).map(|o| o as usize),
+        data_type: parse_data_type(obj),
+        description: str_field(&json, "description").map(String::from),
+        unit: str_field(&json, "unit").map(String::from),
+        scale,
+        reserved: obj.get("reserved").and_then(Json::as_bool).unwrap_or(false),
+        fixed_value: obj.get("fixed_value").and_then(json_int),
+        enumeration: str_field(&json, "enumeration").map(String::from),
+        bits: parse_bits(obj),
+    }
+}
+
+fn parse_data_type(obj: &Map<String, Json>) -> DataType {
+    // Sibling keys that describe an inline structure array.
+    let sibling_count = || {
+        ["count_field", "num_repeats_field", "num_elements_field"]
+            .iter()
+            .find_map(|k| obj.get(*k))
+            .map(Count::parse)
+            .unwrap_or(Count::Remaining)
+    };
+    for key in ["repeated_block", "array_of_fields"] {
+        if let Some(block) = obj.get(key) {
+            let count = block
+                .get("count_field")
+                .map(Count::parse)
+                .unwrap_or(Count::Remaining);
+            let size = block.get("block_length").and_then(Json::as_u64).map(|n| n as usize);
+            return group(parse_fields(block.get("fields")), count, size);
+        }
+    }
+    if let Some(repeats) = obj.get("repeats").filter(|r| r.is_array()) {
+        return group(parse_fields(Some(repeats)), sibling_count(), None);
+    }
+    // A member list without a real data type (absent or "block") is an inline group.
+    let placeholder = obj.get("data_type").is_none_or(|t| t.as_str() == Some("block"));
+    if let Some(fields) = obj.get("fields").filter(|f| placeholder && f.is_array()) {
+        return group(parse_fields(Some(fields)), sibling_count(), None);
+    }
+    if let Some(array_of) = obj.get("array_of") {
+        // Array definition placed next to data_type rather than inside it.
+        return parse_array(array_of, obj, sibling_count());
+    }
+
+    match obj.get("data_type") {
+        Some(Json::String(s)) => {
+            if let Some(base) = BaseType::parse(s) {
+                return DataType::Scalar(base);
+            }
+            if let Some((base, rest)) = s.split_once('[') {
+                if let Some(base) = BaseType::parse(base) {
+                    let count = Count::parse_str(rest.trim_end_matches(']'));
+                    let count = match count {
+                        Count::Field { ref name, .. } if name == "N" => Count::Remaining,
+                        c => c,
+                    };
+                    return DataType::Array { base, count };
+                }
+            }
+            if s.eq_ignore_ascii_case("group") {
+                if let Some(fields) = obj.get("group_fields") {
+                    return group(parse_fields(Some(fields)), sibling_count(), None);
+                }
+            }
+            DataType::Unknown(s.clone())
+        }
+        Some(Json::Object(dt)) => {
+            if let Some(elements) = dt.get("elements") {
+                let count = dt
+                    .get("num_elements_field")
+                    .map(Count::parse)
+                    .unwrap_or(Count::Remaining);
+                let size = dt.get("element_size").and_then(Json::as_u64).map(|n| n as usize);
+                return group(parse_fields(Some(elements)), count, size);
+            }
+            match dt.get("array_of") {
+                Some(array_of) => {
+                    let count = dt
+                        .get("count")
+                        .or_else(|| dt.get("count_field"))
+                        .map(Count::parse)
+                        .unwrap_or_else(sibling_count);
+                    parse_array(array_of, obj, count)
+                }
+                None => dt
+                    .get("type")
+                    .and_then(Json::as_str)
+                    .and_then(BaseType::parse)
+                    .map_or_else(|| DataType::Unknown(Json::Object(dt.clone()).to_string()), DataType::Scalar),
+            }
+        }
+        Some(other) => DataType::Unknown(other.to_string()),
+        None => DataType::Unknown("null".into()),
+    }
+}
+
+fn parse_array(array_of: &Json, obj: &Map<String, Json>, count: Count) -> DataType {
+    match array_of {
+        Json::String(s) => match BaseType::parse(s) {
+            Some(base) => DataType::Array { base, count },
+            // "object" / "group" with the member list as a sibling key.
+            None => match obj.get("fields") {
+                Some(fields) => group(parse_fields(Some(fields)), count, None),
+                None => DataType::Unknown(s.clone()),
+            },
+        },
+        Json::Object(inner) => group(parse_fields(inner.get("fields")), count, None),
+        other => DataType::Unknown(other.to_string()),
+    }
+}
+
+/// Bytes spanned by a list of fixed-size fields.
+pub fn fields_extent(fields: &[FieldDef]) -> usize {
+    fields
+        .iter()
+        .filter_map(|f| Some(f.byte_offset? + f.data_type.fixed_size()?))
+        .max()
+        .unwrap_or(0)
+}
+
+fn group(fields: Vec<FieldDef>, count: Count, element_size: Option<usize>) -> DataType {
+    let element_size = element_size.unwrap_or_else(|| fields_extent(&fields));
+    DataType::Group {
+        fields,
+        count,
+        element_size,
+    }
+}
+
+fn parse_bits(obj: &Map<String, Json>) -> Vec<BitDef> {
+    let list = match obj.get("bitfield") {
+        Some(Json::Array(items)) => Some(items),
+        Some(Json::Object(b)) => b.get("bits").and_then(Json::as_array),
+        _ => None,
+    }
+    .or_else(|| {
+        ["bitfields", "bits", "sub_fields", "bit_fields", "bitfield_members"]
+            .iter()
+            .find_map(|k| obj.get(*k).and_then(Json::as_array))
+    });
+    let Some(list) = list else {
+        return Vec::new();
+    };
+    list.iter()
+        .filter_map(|b| {
+            let get = |keys: &[&str]| keys.iter().find_map(|k| b.get(*k).and_then(Json::as_u64));
+            let name = str_field(b, "name")?.to_string();
+            let bit_offset = get(&["bit_offset", "bit_start", "position"])?;
+            let bit_width = get(&["bit_width", "bit_length", "size", "length"])
+                .or_else(|| get(&["bit_end"]).map(|end| end.saturating_sub(bit_offset) + 1))
+                .unwrap_or(1);
+            Some(BitDef {
+                reserved: b.get("reserved").and_then(Json::as_bool).unwrap_or(false)
+                    || name.starts_with("reserved"),
+                description: str_field(b, "description").map(String::from),
+                bit_offset: bit_offset as u32,
+                bit_width: bit_width as u32,
+                name,
+            })
+        })
+        .collect()
+}
+
+fn str_field<'a>(json: &'a Json, key: &str) -> Option<&'a str> {
+    json.get(key).and_then(Json::as_str)
+}
+
+fn string_list(json: Option<&Json>) -> Vec<String> {
+    json.and_then(Json::as_array)
+        .map(|a| a.iter().filter_map(Json::as_str).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Integer from a JSON number or a decimal/hex string.
+fn json_int(value: &Json) -> Option<i64> {
+    match value {
+        Json::Number(n) => n.as_i64(),
+        Json::String(s) => match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => i64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,460 @@
+//! Differences between two versions of the schema set, for release notes.
+//!
+//! Messages are paired by name, fields by name within a payload, variant
+//! or group, and configuration keys by name. A change is breaking if a
+//! decoder built against the old version would misread data or lose a
+//! name: removals, moves, new types and scales, and renames. A field that
+//! was reserved can be named, moved or dropped without breaking anything.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{ConfigKey, ConfigKeys};
+use crate::error::{Error, Result};
+use crate::schema::{
+    BaseOffset, Count, DataType, Discriminator, FieldDef, MessageDef, PayloadDef, PayloadLength, Schema,
+};
+
+/// The message schema, relative to the root of a checkout.
+pub const MESSAGES_FILE: &str = "data/messages/ubx_messages.json";
+/// The configuration key database, relative to the root of a checkout.
+pub const KEYS_FILE: &str = "data/config_keys/unified_config_keys.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    Breaking,
+    Added,
+    Changed,
+}
+
+impl Impact {
+    /// Changelog section heading.
+    pub fn heading(self) -> &'static str {
+        match self {
+            Impact::Breaking => "Breaking",
+            Impact::Added => "Added",
+            Impact::Changed => "Changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub impact: Impact,
+    /// Message or configuration key name.
+    pub item: String,
+    /// What changed, e.g. "field `lat` moves from offset 28 to 24".
+    pub detail: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.item, self.detail)
+    }
+}
+
+struct Changes<'a> {
+    item: &'a str,
+    out: &'a mut Vec<Change>,
+}
+
+impl Changes<'_> {
+    fn push(&mut self, impact: Impact, detail: String) {
+        self.out.push(Change {
+            impact,
+            item: self.item.to_string(),
+            detail,
+        });
+    }
+}
+
+/// Message changes from `old` to `new`, in the order of `new`'s messages
+/// with removed messages last.
+pub fn diff_messages(old: &Schema, new: &Schema) -> Vec<Change> {
+    let mut out = Vec::new();
+    for msg in &new.messages {
+        let mut changes = Changes {
+            item: &msg.name,
+            out: &mut out,
+        };
+        match old.message_by_name(&msg.name) {
+            None => changes.push(Impact::Added, format!("new message ({:02X}-{:02X})", msg.class_id, msg.message_id)),
+            Some(before) => diff_message(before, msg, &mut changes),
+        }
+    }
+    for msg in &old.messages {
+        if new.message_by_name(&msg.name).is_none() {
+            out.push(Change {
+                impact: Impact::Breaking,
+                item: msg.name.clone(),
+                detail: "message removed".into(),
+            });
+        }
+    }
+    out
+}
+
+fn diff_message(old: &MessageDef, new: &MessageDef, changes: &mut Changes) {
+    if (old.class_id, old.message_id) != (new.class_id, new.message_id) {
+        changes.push(
+            Impact::Breaking,
+            format!(
+                "ID changes from {:02X}-{:02X} to {:02X}-{:02X}",
+                old.class_id, old.message_id, new.class_id, new.message_id
+            ),
+        );
+    }
+    match (&old.payload, &new.payload) {
+        (Some(a), Some(b)) => diff_payload(a, b, "", changes),
+        (Some(_), None) if !new.variants.is_empty() => {
+            changes.push(Impact::Changed, "split into variants".into());
+        }
+        (None, Some(_)) if !old.variants.is_empty() => {
+            changes.push(Impact::Breaking, "variants merged into one payload".into());
+        }
+        _ => {}
+    }
+    for variant in &new.variants {
+        let Some(before) = old.variant(&variant.name) else {
+            changes.push(Impact::Added, format!("new variant `{}`", variant.name));
+            continue;
+        };
+        if before.discriminator != variant.discriminator {
+            changes.push(
+                Impact::Breaking,
+                format!(
+                    "variant `{}` is selected by {} instead of {}",
+                    variant.name,
+                    discriminator_str(&variant.discriminator),
+                    discriminator_str(&before.discriminator)
+                ),
+            );
+        }
+        diff_payload(&before.payload, &variant.payload, &format!("variant `{}`: ", variant.name), changes);
+    }
+    for variant in &old.variants {
+        if new.variant(&variant.name).is_none() {
+            changes.push(Impact::Breaking, format!("variant `{}` removed", variant.name));
+        }
+    }
+}
+
+fn diff_payload(old: &PayloadDef, new: &PayloadDef, context: &str, changes: &mut Changes) {
+    if old.length != new.length {
+        changes.push(
+            Impact::Breaking,
+            format!("{context}payload length {} instead of {}", length_str(&new.length), length_str(&old.length)),
+        );
+    }
+    diff_fields(&old.fields, &new.fields, context, "", changes);
+    for group in &new.repeated_groups {
+        let prefix = format!("{}.", group.name);
+        let Some(before) = old.repeated_groups.iter().find(|g| g.name == group.name) else {
+            changes.push(Impact::Added, format!("{context}new repeated group `{}`", group.name));
+            continue;
+        };
+        if before.count != group.count {
+            let detail = format!("{}, was {}", count_str(&group.count), count_str(&before.count));
+            changes.push(Impact::Breaking, format!("{context}group `{}` count {detail}", group.name));
+        }
+        if before.group_size != group.group_size {
+            let detail = format!("{} bytes, was {}", group.group_size, before.group_size);
+            changes.push(Impact::Breaking, format!("{context}group `{}` element size {detail}", group.name));
+        }
+        if before.base_offset != group.base_offset {
+            let (now, was) = (base_offset_str(&group.base_offset), base_offset_str(&before.base_offset));
+            changes.push(Impact::Breaking, format!("{context}group `{}` starts at {now}, was {was}", group.name));
+        }
+        diff_fields(&before.fields, &group.fields, context, &prefix, changes);
+    }
+    for group in &old.repeated_groups {
+        if !new.repeated_groups.iter().any(|g| g.name == group.name) {
+            changes.push(Impact::Breaking, format!("{context}repeated group `{}` removed", group.name));
+        }
+    }
+}
+
+fn diff_fields(old: &[FieldDef], new: &[FieldDef], context: &str, prefix: &str, changes: &mut Changes) {
+    let find = |fields: &'_ [FieldDef], name: &str| fields.iter().position(|f| f.name == name);
+    for field in new {
+        let name = format!("{prefix}{}", field.name);
+        match find(old, &field.name) {
+            Some(i) => diff_field(&old[i], field, context, &name, changes),
+            // New reserved space, or a renamed field (reported below).
+            None if field.reserved || renamed_from(old, new, field).is_some() => {}
+            None => changes.push(Impact::Added, format!("{context}new field `{name}`{}", at(field))),
+        }
+    }
+    for field in old {
+        if field.reserved || find(new, &field.name).is_some() {
+            continue;
+        }
+        let name = format!("{prefix}{}", field.name);
+        match new.iter().find(|f| renamed_from(old, new, f).is_some_and(|o| o.name == field.name)) {
+            Some(renamed) => {
+                let detail = format!("field `{name}` renamed to `{prefix}{}`", renamed.name);
+                changes.push(Impact::Breaking, format!("{context}{detail}"));
+            }
+            None => changes.push(Impact::Breaking, format!("{context}field `{name}` removed")),
+        }
+    }
+}
+
+/// The old field `field` replaces under a new name: one of the same type at
+/// the same offset that `new` no longer has.
+fn renamed_from<'a>(old: &'a [FieldDef], new: &[FieldDef], field: &FieldDef) -> Option<&'a FieldDef> {
+    if field.byte_offset.is_none() || old.iter().any(|f| f.name == field.name) {
+        return None;
+    }
+    old.iter().find(|f| {
+        !f.reserved
+            && f.byte_offset == field.byte_offset
+            && f.data_type == field.data_type
+            && !new.iter().any(|n| n.name == f.name)
+    })
+}
+
+fn diff_field(old: &FieldDef, new: &FieldDef, context: &str, name: &str, changes: &mut Changes) {
+    // Reserved space may be rearranged freely.
+    if old.reserved && new.reserved {
+        return;
+    }
+    if old.reserved {
+        changes.push(Impact::Added, format!("{context}reserved `{name}` is now a field"));
+    } else if new.reserved {
+        changes.push(Impact::Breaking, format!("{context}field `{name}` is now reserved"));
+        return;
+    }
+    if old.byte_offset != new.byte_offset {
+        let (now, was) = (offset_str(new.byte_offset), offset_str(old.byte_offset));
+        changes.push(Impact::Breaking, format!("{context}field `{name}` moves from offset {was} to {now}"));
+    }
+    match (&old.data_type, &new.data_type) {
+        (DataType::Group { fields: a, .. }, DataType::Group { fields: b, .. }) => {
+            diff_fields(a, b, context, &format!("{name}."), changes);
+        }
+        (a, b) if a != b => {
+            let detail = format!("type {} instead of {}", type_str(b), type_str(a));
+            changes.push(Impact::Breaking, format!("{context}field `{name}` {detail}"));
+        }
+        _ => {}
+    }
+    if old.multiplier() != new.multiplier() {
+        let (now, was) = (scale_str(new), scale_str(old));
+        changes.push(Impact::Breaking, format!("{context}field `{name}` scale {now} instead of {was}"));
+    }
+    if old.unit != new.unit {
+        let (now, was) = (unit_str(&new.unit), unit_str(&old.unit));
+        changes.push(Impact::Changed, format!("{context}field `{name}` unit {now} instead of {was}"));
+    }
+    if let Some(value) = new.fixed_value.filter(|v| old.fixed_value != Some(*v)) {
+        changes.push(Impact::Breaking, format!("{context}field `{name}` must now be {value}"));
+    }
+    for bit in &new.bits {
+        let Some(before) = old.bits.iter().find(|b| b.name == bit.name) else {
+            if !bit.reserved {
+                changes.push(Impact::Added, format!("{context}new bit `{name}.{}`", bit.name));
+            }
+            continue;
+        };
+        if (before.bit_offset, before.bit_width) != (bit.bit_offset, bit.bit_width) && !before.reserved {
+            let detail = format!(
+                "bits {} instead of {}",
+                bits_str(bit.bit_offset, bit.bit_width),
+                bits_str(before.bit_offset, before.bit_width)
+            );
+            changes.push(Impact::Breaking, format!("{context}bit `{name}.{}` {detail}", bit.name));
+        }
+    }
+    for bit in &old.bits {
+        if !bit.reserved && !new.bits.iter().any(|b| b.name == bit.name) {
+            changes.push(Impact::Breaking, format!("{context}bit `{name}.{}` removed", bit.name));
+        }
+    }
+}
+
+/// Configuration key changes from `old` to `new`, in the order of `new`
+/// with removed keys last.
+pub fn diff_config_keys(old: &ConfigKeys, new: &ConfigKeys) -> Vec<Change> {
+    let mut out = Vec::new();
+    for key in new.iter() {
+        let mut changes = Changes {
+            item: &key.name,
+            out: &mut out,
+        };
+        match old.get(&key.name) {
+            None => changes.push(Impact::Added, format!("new configuration key ({:#010x})", key.key_id)),
+            Some(before) => diff_key(before, key, &mut changes),
+        }
+    }
+    for key in old.iter() {
+        if new.get(&key.name).is_none() {
+            out.push(Change {
+                impact: Impact::Breaking,
+                item: key.name.clone(),
+                detail: "configuration key removed".into(),
+            });
+        }
+    }
+    out
+}
+
+fn diff_key(old: &ConfigKey, new: &ConfigKey, changes: &mut Changes) {
+    if old.key_id != new.key_id {
+        changes.push(Impact::Breaking, format!("key ID {:#010x} instead of {:#010x}", new.key_id, old.key_id));
+    }
+    if old.data_type != new.data_type {
+        changes.push(Impact::Breaking, format!("type {:?} instead of {:?}", new.data_type, old.data_type));
+    }
+    if old.scale != new.scale {
+        let scale = |s: Option<f64>| s.map_or("none".to_string(), |s| s.to_string());
+        changes.push(Impact::Breaking, format!("scale {} instead of {}", scale(new.scale), scale(old.scale)));
+    }
+    if old.unit != new.unit {
+        changes.push(Impact::Changed, format!("unit {} instead of {}", unit_str(&new.unit), unit_str(&old.unit)));
+    }
+    for (name, value) in &old.constants {
+        match new.constant(name) {
+            None => changes.push(Impact::Breaking, format!("constant `{name}` removed")),
+            Some(v) if v != *value => changes.push(Impact::Breaking, format!("constant `{name}` is {v}, was {value}")),
+            Some(_) => {}
+        }
+    }
+    for (name, _) in &new.constants {
+        if old.constant(name).is_none() {
+            changes.push(Impact::Added, format!("new constant `{name}`"));
+        }
+    }
+}
+
+/// The text of the file at `relative` in the checkout `spec`, or of the
+/// working tree file `working` as of git revision `spec`.
+pub fn read_version(spec: &str, working: &Path, relative: &str) -> Result<String> {
+    let checkout = Path::new(spec);
+    if checkout.is_dir() {
+        let path = checkout.join(relative);
+        return fs::read_to_string(&path).map_err(|e| Error::Schema(format!("{}: {e}", path.display())));
+    }
+    let dir = working.parent().unwrap_or(Path::new("."));
+    let name = working.file_name().unwrap_or_default().to_string_lossy();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("{spec}:./{name}"))
+        .output()
+        .map_err(|e| Error::Schema(format!("git: {e}")))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Schema(format!("{spec}: {}", message.trim())));
+    }
+    String::from_utf8(output.stdout).map_err(|e| Error::Schema(format!("{spec}:./{name}: {e}")))
+}
+
+/// A Markdown changelog fragment: a section per impact, an entry per change,
+/// grouped by item.
+pub fn changelog(title: &str, changes: &[Change]) -> String {
+    let mut sections: BTreeMap<Impact, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+    for change in changes {
+        sections
+            .entry(change.impact)
+            .or_default()
+            .entry(&change.item)
+            .or_default()
+            .push(&change.detail);
+    }
+    let mut out = format!("## {title}\n");
+    if changes.is_empty() {
+        out.push_str("\nNo changes.\n");
+    }
+    for (impact, items) in sections {
+        out.push_str(&format!("\n### {}\n\n", impact.heading()));
+        for (item, details) in items {
+            out.push_str(&format!("- **{item}**: {}\n", details.join("; ")));
+        }
+    }
+    out
+}
+
+fn at(field: &FieldDef) -> String {
+    field.byte_offset.map(|o| format!(" at offset {o}")).unwrap_or_default()
+}
+
+pub(crate) fn offset_str(offset: Option<usize>) -> String {
+    offset.map_or("(computed)".to_string(), |o| o.to_string())
+}
+
+pub(crate) fn unit_str(unit: &Option<String>) -> String {
+    unit.as_deref().map_or("none".to_string(), |u| format!("`{u}`"))
+}
+
+pub(crate) fn scale_str(field: &FieldDef) -> String {
+    match &field.scale {
+        Some(scale) => scale.raw.clone().unwrap_or_else(|| format!("{}", field.multiplier().unwrap_or(1.0))),
+        None => "none".into(),
+    }
+}
+
+pub(crate) fn bits_str(offset: u32, width: u32) -> String {
+    if width <= 1 {
+        offset.to_string()
+    } else {
+        format!("{}..{}", offset, offset + width - 1)
+    }
+}
+
+pub(crate) fn count_str(count: &Count) -> String {
+    match count {
+        Count::Fixed(n) => n.to_string(),
+        Count::Field { name, multiplier: 1 } => format!("`{name}`"),
+        Count::Field { name, multiplier } => format!("`{name}` * {multiplier}"),
+        Count::Remaining => "the rest of the payload".into(),
+    }
+}
+
+pub(crate) fn type_str(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Scalar(base) => base.as_str().to_string(),
+        DataType::Array { base, count } => format!("{}[{}]", base.as_str(), count_str(count)),
+        DataType::Group { count, element_size, .. } => {
+            format!("{element_size}-byte group * {}", count_str(count))
+        }
+        DataType::Unknown(s) => s.clone(),
+    }
+}
+
+pub(crate) fn length_str(length: &PayloadLength) -> String {
+    match length {
+        PayloadLength::Fixed(n) => n.to_string(),
+        PayloadLength::Variable { base, min, max } => match max {
+            Some(max) => format!("{}..{max}", min.unwrap_or(*base)),
+            None => format!("{} or more", min.unwrap_or(*base)),
+        },
+        PayloadLength::Alternatives(lens) => lens.iter().map(ToString::to_string).collect::<Vec<_>>().join(" or "),
+        PayloadLength::Unspecified => "unspecified".into(),
+    }
+}
+
+pub(crate) fn discriminator_str(discriminator: &Discriminator) -> String {
+    match discriminator {
+        Discriminator::Field { byte_offset, value } => format!("byte {byte_offset} = {value}"),
+        Discriminator::Length(n) => format!("length {n}"),
+        Discriminator::LengthRange { min, max: Some(max) } => format!("length {min}..{max}"),
+        Discriminator::LengthRange { min, max: None } => format!("length {min} or more"),
+        Discriminator::None => "nothing".into(),
+    }
+}
+
+pub(crate) fn base_offset_str(offset: &BaseOffset) -> String {
+    match offset {
+        BaseOffset::Fixed(n) => n.to_string(),
+        BaseOffset::Dynamic { base, terms } => {
+            let terms: Vec<String> = terms.iter().map(|(f, m)| format!(" + {f} * {m}")).collect();
+            format!("{base}{}", terms.concat())
+        }
+        BaseOffset::AfterFields => "the end of the fields".into(),
+    }
+}
@@ -0,0 +1,225 @@
+//! Semantic plausibility checks on decoded messages.
+//!
+//! A frame that decodes is well formed; it can still say something no
+//! receiver would. These checks look at the values: fields the schema fixes
+//! to a constant, enumerated fields holding a value their enumeration does
+//! not define, latitudes and longitudes off the globe, carrier-to-noise
+//! ratios above the 63 dBHz the receivers can report, and accuracy estimates
+//! that contradict the fix type. Findings are [`Warning`]s, kept apart from
+//! structural errors such as bad checksums or unknown messages: one odd
+//! value is a receiver quirk, a log full of them is misdecoded or corrupt.
+//!
+//! Messages must be decoded with scaling and flag expansion enabled.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use crate::decode::DecodedMessage;
+use crate::schema::{DataType, FieldDef, Schema};
+use crate::track::FixQuality;
+use crate::value::Value;
+
+/// Highest C/N0 in dBHz that fits the receivers' 6-bit reporting range.
+pub const MAX_CNO: f64 = 63.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// A field the schema gives a fixed value holds another.
+    FixedValue,
+    /// An enumerated field holds a value its enumeration does not define.
+    Enumeration,
+    /// A value outside its physical range: latitude, longitude, C/N0.
+    Range,
+    /// A horizontal accuracy estimate worse than the fix type allows.
+    Accuracy,
+    /// Messages of one epoch disagree; see [`crate::epoch`].
+    Epoch,
+    /// Successive solutions move faster or further than physically
+    /// plausible; see [`crate::kinematic`].
+    Kinematic,
+}
+
+impl Rule {
+    pub fn label(self) -> &'static str {
+        match self {
+            Rule::FixedValue => "fixed-value",
+            Rule::Enumeration => "enumeration",
+            Rule::Range => "range",
+            Rule::Accuracy => "accuracy",
+            Rule::Epoch => "epoch",
+            Rule::Kinematic => "kinematic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub rule: Rule,
+    /// Field path as [`Fields::flatten`](crate::Fields::flatten) gives it.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.rule.label(), self.field, self.message)
+    }
+}
+
+/// Loosest horizontal accuracy, in metres, a solution of this quality can
+/// claim. Generous, so only solutions that contradict their own fix type
+/// trip it, and never looser for a better fix.
+pub fn h_acc_ceiling(fix: FixQuality) -> Option<f64> {
+    match fix {
+        FixQuality::RtkFixed => Some(1.0),
+        FixQuality::RtkFloat => Some(10.0),
+        FixQuality::Dgnss => Some(50.0),
+        _ => None,
+    }
+}
+
+/// Checks decoded messages against the schema and the enumerations file.
+#[derive(Debug, Clone)]
+pub struct Validator<'s> {
+    schema: &'s Schema,
+    /// Defined values by message name (or `name-variant` alias) and field.
+    enumerations: HashMap<(String, String), Vec<i64>>,
+}
+
+impl<'s> Validator<'s> {
+    /// `enumerations` is the parsed `data/messages/enumerations.json`;
+    /// without it, enumerated fields are not checked.
+    pub fn new(schema: &'s Schema, enumerations: Option<&Json>) -> Self {
+        let mut table = HashMap::new();
+        for (field, def) in enumerations.and_then(Json::as_object).into_iter().flatten() {
+            let Some(values) = def.get("values").and_then(Json::as_array) else {
+                continue;
+            };
+            let values: Vec<i64> = values.iter().filter_map(|v| v.get("value")?.as_i64()).collect();
+            let messages = def.get("messages").and_then(Json::as_array).into_iter().flatten();
+            for msg in messages.filter_map(Json::as_str) {
+                table.insert((msg.to_string(), field.clone()), values.clone());
+            }
+        }
+        Self {
+            schema,
+            enumerations: table,
+        }
+    }
+
+    /// Every warning for `msg`; none for messages the schema did not parse.
+    pub fn check(&self, msg: &DecodedMessage) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if !msg.parsed {
+            return warnings;
+        }
+        let fixed = self.fixed_values(msg);
+        let alias = msg.variant.as_ref().map(|v| format!("{}-{v}", msg.name));
+        for (path, value) in msg.fields.flatten() {
+            let name = leaf(&path);
+            for value in scalars(&value) {
+                if let Some(&expected) = fixed.get(name) {
+                    if value.as_i64().is_some_and(|v| v != expected) {
+                        warnings.push(warn(Rule::FixedValue, &path, format!("{value}, expected {expected}")));
+                    }
+                }
+                let defined = [Some(&msg.name), alias.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .find_map(|m| self.enumerations.get(&(m.clone(), name.to_string())));
+                if let (Some(defined), Some(v)) = (defined, value.as_i64()) {
+                    if !defined.contains(&v) {
+                        warnings.push(warn(Rule::Enumeration, &path, format!("undefined value {v}")));
+                    }
+                }
+                if let Some(message) = out_of_range(name, value) {
+                    warnings.push(warn(Rule::Range, &path, message));
+                }
+            }
+        }
+        if let Some(warning) = accuracy(msg) {
+            warnings.push(warning);
+        }
+        warnings
+    }
+
+    /// Fixed values of `msg`'s unscaled fields, by name.
+    fn fixed_values(&self, msg: &DecodedMessage) -> HashMap<&'s str, i64> {
+        let mut fixed = HashMap::new();
+        let Some(def) = self.schema.message_by_name(&msg.name) else {
+            return fixed;
+        };
+        let payload = match &msg.variant {
+            Some(name) => def.variant(name).map(|v| &v.payload),
+            None => def.payload.as_ref(),
+        };
+        if let Some(payload) = payload {
+            collect_fixed(&payload.fields, &mut fixed);
+            for group in &payload.repeated_groups {
+                collect_fixed(&group.fields, &mut fixed);
+            }
+        }
+        fixed
+    }
+}
+
+fn collect_fixed<'s>(fields: &'s [FieldDef], out: &mut HashMap<&'s str, i64>) {
+    for field in fields {
+        if let DataType::Group { fields, .. } = &field.data_type {
+            collect_fixed(fields, out);
+        }
+        if let (Some(value), None) = (field.fixed_value, field.multiplier()) {
+            out.insert(&field.name, value);
+        }
+    }
+}
+
+pub(crate) fn warn(rule: Rule, field: &str, message: String) -> Warning {
+    Warning {
+        rule,
+        field: field.to_string(),
+        message,
+    }
+}
+
+/// `svs[3].cno` -> `cno`.
+fn leaf(path: &str) -> &str {
+    let name = path.rsplit('.').next().unwrap_or(path);
+    name.split('[').next().unwrap_or(name)
+}
+
+/// The value itself, or the items of an array of scalars.
+fn scalars(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    }
+}
+
+fn out_of_range(name: &str, value: &Value) -> Option<String> {
+    let v = value.as_f64()?;
+    let limit = match name {
+        "lat" => 90.0,
+        "lon" => 180.0,
+        "cno" | "cNo" => return (v > MAX_CNO).then(|| format!("{v} dBHz exceeds {MAX_CNO}")),
+        _ => return None,
+    };
+    (v.abs() > limit).then(|| format!("{v} deg is outside ±{limit}"))
+}
+
+/// `hAcc` against the ceiling for the message's fix quality.
+fn accuracy(msg: &DecodedMessage) -> Option<Warning> {
+    let int = |name: &str| msg.get(name).and_then(Value::as_u64);
+    let fix = FixQuality::from_pvt(int("fixType")?, int("flags.diffSoln") == Some(1), int("flags.carrSoln")?);
+    let h_acc = msg.get("hAcc")?.as_f64()? / 1_000.0;
+    let ceiling = h_acc_ceiling(fix)?;
+    (h_acc > ceiling).then(|| {
+        warn(
+            Rule::Accuracy,
+            "hAcc",
+            format!("{h_acc} m with {} fix (at most {ceiling} m expected)", fix.label()),
+        )
+    })
+}
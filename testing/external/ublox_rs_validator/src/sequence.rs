@@ -0,0 +1,171 @@
+//! Dropped-frame detection from the counters and time tags messages carry.
+//!
+//! iTOW gaps ([`crate::stats`]) miss messages that have no iTOW or are not
+//! tied to navigation epochs. Three other kinds of sequence reveal drops:
+//!
+//! - indices: LOG-RETRIEVEPOS, -POSEXTRA and -STRING number the entries of
+//!   one retrieval in a single `entryIndex` sequence, so a skipped index is
+//!   a lost entry;
+//! - time tags: ESF-MEAS `timeTag` (per data provider) advances by the
+//!   sensor's interval, judged like iTOW intervals: more than 1.5 times the
+//!   median is a gap;
+//! - error counters: MON-COMMS `overrunErrs` and `skipped` only grow when
+//!   the receiver itself dropped input, so every increase is reported.
+//!
+//! A sequence that goes backwards (a new retrieval, a receiver restart)
+//! counts as a reset, not a gap.
+
+use std::collections::BTreeMap;
+
+use crate::decode::DecodedMessage;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceKind {
+    /// Consecutive integers.
+    Index,
+    /// A wrapping 32-bit time tag at a steady interval.
+    TimeTag,
+    /// A cumulative error count.
+    Counter,
+}
+
+/// A jump in one sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    /// Value before the jump.
+    pub after: u64,
+    /// Value after it.
+    pub before: u64,
+    /// Entries or intervals lost; for counters, the increase.
+    pub missing: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceReport {
+    /// Message, source and field, e.g. `ESF-MEAS id 3 timeTag`.
+    pub name: String,
+    pub kind: SequenceKind,
+    /// Values seen.
+    pub count: usize,
+    pub gaps: Vec<SequenceGap>,
+    pub resets: usize,
+}
+
+impl SequenceReport {
+    pub fn missing(&self) -> u64 {
+        self.gaps.iter().map(|g| g.missing).sum()
+    }
+}
+
+/// Collects the sequences of a decoded message stream.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    series: BTreeMap<String, (SequenceKind, Vec<u64>)>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, msg: &DecodedMessage) {
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64);
+        match msg.name.as_str() {
+            "UBX-LOG-RETRIEVEPOS" | "UBX-LOG-RETRIEVEPOSEXTRA" | "UBX-LOG-RETRIEVESTRING" => {
+                self.record("LOG-RETRIEVE entryIndex", SequenceKind::Index, int("entryIndex"));
+            }
+            "UBX-ESF-MEAS" => {
+                let name = format!("ESF-MEAS id {} timeTag", int("id").unwrap_or(0));
+                self.record(&name, SequenceKind::TimeTag, int("timeTag"));
+            }
+            "UBX-MON-COMMS" => {
+                let port = int("portID").unwrap_or(0);
+                for field in ["overrunErrs", "skipped"] {
+                    let name = format!("MON-COMMS port 0x{port:04X} {field}");
+                    self.record(&name, SequenceKind::Counter, int(field));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record(&mut self, name: &str, kind: SequenceKind, value: Option<u64>) {
+        let Some(value) = value else {
+            return;
+        };
+        let (_, values) = self.series.entry(name.to_string()).or_insert_with(|| (kind, Vec::new()));
+        values.push(value);
+    }
+
+    /// One report per sequence, by name.
+    pub fn reports(&self) -> Vec<SequenceReport> {
+        self.series
+            .iter()
+            .map(|(name, (kind, values))| {
+                let (gaps, resets) = match kind {
+                    SequenceKind::Index => steps(values, |d| (d > 1).then(|| d as u64 - 1)),
+                    SequenceKind::Counter => steps(values, |d| (d > 0).then_some(d as u64)),
+                    SequenceKind::TimeTag => time_tag_gaps(values),
+                };
+                SequenceReport {
+                    name: name.clone(),
+                    kind: *kind,
+                    count: values.len(),
+                    gaps,
+                    resets,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Gaps where `missing` maps a step to a loss, and the backward steps.
+fn steps(values: &[u64], missing: impl Fn(i128) -> Option<u64>) -> (Vec<SequenceGap>, usize) {
+    let mut gaps = Vec::new();
+    let mut resets = 0;
+    for pair in values.windows(2) {
+        let d = i128::from(pair[1]) - i128::from(pair[0]);
+        if d < 0 {
+            resets += 1;
+        } else if let Some(missing) = missing(d) {
+            gaps.push(SequenceGap {
+                after: pair[0],
+                before: pair[1],
+                missing,
+            });
+        }
+    }
+    (gaps, resets)
+}
+
+/// Intervals of more than 1.5 times the median, with the wrap of a 32-bit
+/// tag taken into account. Repeated tags (several messages for one sample)
+/// are not intervals; a step of more than half the range is a reset.
+fn time_tag_gaps(values: &[u64]) -> (Vec<SequenceGap>, usize) {
+    let mut resets = 0;
+    let mut intervals = Vec::new();
+    for pair in values.windows(2) {
+        let d = (pair[1] as u32).wrapping_sub(pair[0] as u32);
+        match d {
+            0 => {}
+            d if d > u32::MAX / 2 => resets += 1,
+            d => intervals.push((pair[0], pair[1], u64::from(d))),
+        }
+    }
+    let mut sorted: Vec<u64> = intervals.iter().map(|&(_, _, d)| d).collect();
+    sorted.sort_unstable();
+    let Some(&nominal) = sorted.get(sorted.len() / 2) else {
+        return (Vec::new(), resets);
+    };
+    let gaps = intervals
+        .into_iter()
+        .filter(|&(_, _, d)| d * 2 > nominal * 3)
+        .map(|(after, before, d)| SequenceGap {
+            after,
+            before,
+            missing: ((d as f64 / nominal as f64).round() as u64).saturating_sub(1).max(1),
+        })
+        .collect();
+    (gaps, resets)
+}
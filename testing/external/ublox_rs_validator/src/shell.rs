@@ -0,0 +1,184 @@
+//! Command parsing and tab completion for `ubx-shell`.
+//!
+//! The line editor and serial I/O live in the binary; this module turns
+//! input lines into [`Command`]s and computes completion candidates so both
+//! can be exercised without a terminal or a receiver.
+
+use serde_json::{Map, Value as Json};
+
+use crate::config::{ConfigKeys, Layers};
+use crate::error::{Error, Result};
+use crate::schema::Schema;
+
+/// Command words, in the order `help` lists them.
+pub const COMMANDS: &[&str] = &["poll", "send", "set", "raw", "watch", "unwatch", "help", "quit"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Send a message with an empty payload, or the given payload bytes
+    /// (e.g. the port ID of a CFG-PRT poll).
+    Poll { message: String, payload: Vec<u8> },
+    /// Encode a message from `field=value` pairs and send it.
+    Send { message: String, fields: Map<String, Json> },
+    /// Set a configuration key with CFG-VALSET.
+    Set { key: String, value: Json, layers: Layers },
+    /// Send bytes as they are, e.g. a complete frame copied from a log.
+    Raw(Vec<u8>),
+    /// Print these messages as they arrive; `*` prints everything.
+    Watch(Vec<String>),
+    /// Stop printing these messages, or all of them when empty.
+    Unwatch(Vec<String>),
+    Help,
+    Quit,
+}
+
+fn usage(msg: impl Into<String>) -> Error {
+    Error::Command(msg.into())
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let digits: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    hex::decode(digits.trim_start_matches("0x")).map_err(|e| usage(format!("invalid hex: {e}")))
+}
+
+/// A bare token as a JSON value: numbers, booleans and JSON literals are
+/// taken as such, anything else as a string (enum constant names).
+fn parse_value(s: &str) -> Json {
+    serde_json::from_str(s).unwrap_or_else(|_| Json::String(s.to_string()))
+}
+
+/// Insert `value` at a dotted path, so `valid.validDate=1` builds the
+/// bitfield object the encoder expects.
+fn insert_path(fields: &mut Map<String, Json>, path: &str, value: Json) -> Result<()> {
+    match path.split_once('.') {
+        None => {
+            fields.insert(path.to_string(), value);
+            Ok(())
+        }
+        Some((head, rest)) => {
+            let entry = fields.entry(head).or_insert_with(|| Json::Object(Map::new()));
+            let Json::Object(inner) = entry else {
+                return Err(usage(format!("'{head}' is set both as a value and as a bitfield")));
+            };
+            insert_path(inner, rest, value)
+        }
+    }
+}
+
+/// Build an encoder field map from `FIELD=VALUE` tokens; dotted names set
+/// bitfield members.
+pub fn parse_fields<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<Map<String, Json>> {
+    let mut fields = Map::new();
+    for arg in args {
+        let (name, value) = arg
+            .split_once('=')
+            .ok_or_else(|| usage(format!("expected FIELD=VALUE, got '{arg}'")))?;
+        insert_path(&mut fields, name, parse_value(value))?;
+    }
+    Ok(fields)
+}
+
+/// Parse one input line; blank lines and `#` comments give `None`.
+pub fn parse_command(line: &str) -> Result<Option<Command>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let command = match word.to_ascii_lowercase().as_str() {
+        "poll" => {
+            let message = args.first().ok_or_else(|| usage("usage: poll MESSAGE [PAYLOAD-HEX]"))?;
+            Command::Poll {
+                message: message.to_string(),
+                payload: parse_hex(&args[1..].concat())?,
+            }
+        }
+        "send" => {
+            let message = args.first().ok_or_else(|| usage("usage: send MESSAGE [FIELD=VALUE ...]"))?;
+            Command::Send {
+                message: message.to_string(),
+                fields: parse_fields(args[1..].iter().copied())?,
+            }
+        }
+        "set" => {
+            let (key, value) = match args.as_slice() {
+                [key, value] | [key, value, _] => (key, value),
+                _ => return Err(usage("usage: set KEY VALUE [LAYERS]")),
+            };
+            let layers = match args.get(2) {
+                Some(layers) => Layers::parse(layers)?,
+                None => Layers::default(),
+            };
+            Command::Set {
+                key: key.to_string(),
+                value: parse_value(value),
+                layers,
+            }
+        }
+        "raw" => Command::Raw(parse_hex(rest)?),
+        "watch" => Command::Watch(args.iter().map(|s| s.to_string()).collect()),
+        "unwatch" => Command::Unwatch(args.iter().map(|s| s.to_string()).collect()),
+        "help" | "?" => Command::Help,
+        "quit" | "exit" => Command::Quit,
+        other => return Err(usage(format!("unknown command '{other}' (try 'help')"))),
+    };
+    Ok(Some(command))
+}
+
+fn matching<'a>(prefix: &str, words: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let prefix = prefix.to_ascii_uppercase();
+    let mut out: Vec<String> = words
+        .into_iter()
+        .filter(|w| w.to_ascii_uppercase().starts_with(&prefix))
+        .map(String::from)
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn message_names(schema: &Schema) -> impl Iterator<Item = &str> {
+    schema.messages.iter().flat_map(|m| {
+        std::iter::once(m.short_name()).chain(m.variant_aliases.iter().map(|a| a.strip_prefix("UBX-").unwrap_or(a)))
+    })
+}
+
+/// Completion candidates for the word under the cursor: the byte offset the
+/// word starts at and the replacements for it.
+///
+/// Command words complete first, then message names (`poll`, `send`,
+/// `watch`, `unwatch`), configuration key names (`set`) and `FIELD=` names of
+/// the message being sent.
+pub fn complete(schema: &Schema, keys: &ConfigKeys, line: &str, pos: usize) -> (usize, Vec<String>) {
+    let head = &line[..pos];
+    let start = head.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &head[start..];
+    let before: Vec<&str> = head[..start].split_whitespace().collect();
+
+    let Some(command) = before.first().map(|w| w.to_ascii_lowercase()) else {
+        let lower = word.to_ascii_lowercase();
+        let commands = COMMANDS.iter().filter(|c| c.starts_with(&lower)).map(|c| c.to_string());
+        return (start, commands.collect());
+    };
+    let candidates = match (command.as_str(), before.len()) {
+        ("poll" | "send", 1) | ("watch" | "unwatch", _) => matching(word, message_names(schema)),
+        ("set", 1) => matching(word, keys.iter().map(|k| k.name.as_str())),
+        ("send", _) if !word.contains('=') => {
+            let Some(msg) = schema.message_by_name(before[1]) else {
+                return (start, Vec::new());
+            };
+            let payloads = msg.payload.iter().chain(msg.variants.iter().map(|v| &v.payload));
+            let fields = payloads.flat_map(|p| p.fields.iter()).filter(|f| !f.reserved);
+            let mut names: Vec<String> = fields
+                .filter(|f| f.name.to_ascii_uppercase().starts_with(&word.to_ascii_uppercase()))
+                .map(|f| format!("{}=", f.name))
+                .collect();
+            names.sort();
+            names.dedup();
+            names
+        }
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
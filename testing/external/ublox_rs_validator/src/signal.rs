@@ -0,0 +1,254 @@
+//! Carrier-to-noise statistics per constellation, satellite and signal.
+//!
+//! Collects the C/N0 NAV-SAT reports per satellite and NAV-SIG per signal:
+//! mean, minimum and maximum over the epochs a satellite was tracked, and
+//! how long it was used in the solution. Compared across antenna positions
+//! or over a day, they show obstructions, cable losses and interference
+//! without a spectrum analyzer. Entries with a C/N0 of 0 are satellites
+//! the receiver lists but does not track, and are left out.
+//!
+//! Usage is `svUsed` for NAV-SAT and `sigFlags.prUsed` for NAV-SIG (bit 3 of
+//! the raw flags when the schema has no member names). A schema without
+//! `sigFlags` counts NAV-SIG signals as used from `qualityInd` 4 on, code
+//! locked and time synchronized. Usage time is the epoch interval summed
+//! over the epochs of use; a constellation counts as used in an epoch when
+//! any of its satellites or signals was. A schema that does not group
+//! NAV-SIG's repeated block gives its first signal only.
+//!
+//! Messages must be decoded with flag expansion enabled.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::decode::DecodedMessage;
+use crate::error::Result;
+use crate::gnss::Gnss;
+use crate::output::csv_error;
+use crate::time::itow_delta;
+use crate::value::{Fields, Value};
+
+/// Longest epoch interval, in milliseconds, counted towards usage time;
+/// longer ones are gaps in the log.
+pub const MAX_EPOCH_INTERVAL_MS: i64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Source {
+    NavSat,
+    NavSig,
+}
+
+impl Source {
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::NavSat => "NAV-SAT",
+            Source::NavSig => "NAV-SIG",
+        }
+    }
+}
+
+/// What one set of statistics covers: a constellation when `sv_id` is
+/// `None`, else a satellite (NAV-SAT) or one of its signals (NAV-SIG).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignalKey {
+    pub source: Source,
+    pub gnss_id: u8,
+    pub sv_id: Option<u8>,
+    pub sig_id: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CnoStats {
+    /// Epochs tracked.
+    pub epochs: usize,
+    sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub used_epochs: usize,
+    pub used_ms: i64,
+}
+
+impl CnoStats {
+    fn add(&mut self, cno: f64) {
+        if self.epochs == 0 {
+            self.min = cno;
+            self.max = cno;
+        }
+        self.epochs += 1;
+        self.sum += cno;
+        self.min = self.min.min(cno);
+        self.max = self.max.max(cno);
+    }
+
+    fn count_use(&mut self, interval_ms: i64) {
+        self.used_epochs += 1;
+        self.used_ms += interval_ms;
+    }
+
+    /// Mean C/N0 in dBHz; `None` before any sample.
+    pub fn mean(&self) -> Option<f64> {
+        (self.epochs > 0).then(|| self.sum / self.epochs as f64)
+    }
+}
+
+/// One row of the report, as written to JSON and CSV.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalSummary {
+    pub source: &'static str,
+    pub gnss: String,
+    pub sv_id: Option<u8>,
+    pub sig_id: Option<u8>,
+    /// RINEX band and attribute of the signal, when known.
+    pub signal: Option<&'static str>,
+    pub epochs: usize,
+    pub mean_cno: f64,
+    pub min_cno: f64,
+    pub max_cno: f64,
+    pub used_epochs: usize,
+    pub used_s: f64,
+}
+
+/// Accumulates NAV-SAT and NAV-SIG C/N0 statistics.
+#[derive(Debug, Clone, Default)]
+pub struct SignalStats {
+    stats: BTreeMap<SignalKey, CnoStats>,
+    last: BTreeMap<Source, u32>,
+}
+
+impl SignalStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a NAV-SAT or NAV-SIG; other messages and repeated epochs are
+    /// ignored. True when the message was counted.
+    pub fn push(&mut self, msg: &DecodedMessage) -> bool {
+        let source = match msg.name.as_str() {
+            "UBX-NAV-SAT" => Source::NavSat,
+            "UBX-NAV-SIG" => Source::NavSig,
+            _ => return false,
+        };
+        let Some(itow) = msg.itow().filter(|_| msg.parsed) else {
+            return false;
+        };
+        let interval = match self.last.get(&source) {
+            Some(&last) => match itow_delta(last, itow) {
+                0 => return false,
+                d if d > 0 && d <= MAX_EPOCH_INTERVAL_MS => d,
+                _ => 0,
+            },
+            None => 0,
+        };
+        self.last.insert(source, itow);
+
+        let mut used_constellations = BTreeSet::new();
+        for entry in msg.blocks() {
+            let int = |name: &str| entry.get_path(name).and_then(Value::as_u64);
+            let (Some(gnss_id), Some(sv_id), Some(cno)) = (int("gnssId"), int("svId"), int("cno")) else {
+                continue;
+            };
+            if cno == 0 {
+                continue;
+            }
+            let (sig_id, used) = match source {
+                Source::NavSat => (None, flag(entry, "flags", "svUsed", 3)),
+                Source::NavSig => (
+                    int("sigId").map(|s| s as u8),
+                    match entry.get("sigFlags") {
+                        Some(_) => flag(entry, "sigFlags", "prUsed", 3),
+                        None => int("qualityInd").is_some_and(|q| q >= 4),
+                    },
+                ),
+            };
+            let gnss_id = gnss_id as u8;
+            let keys = [
+                SignalKey {
+                    source,
+                    gnss_id,
+                    sv_id: Some(sv_id as u8),
+                    sig_id,
+                },
+                SignalKey {
+                    source,
+                    gnss_id,
+                    sv_id: None,
+                    sig_id: None,
+                },
+            ];
+            for key in keys {
+                self.stats.entry(key).or_default().add(cno as f64);
+            }
+            let sat = self.stats.get_mut(&keys[0]).unwrap();
+            if used {
+                sat.count_use(interval);
+                used_constellations.insert(keys[1]);
+            }
+        }
+        for key in used_constellations {
+            self.stats.get_mut(&key).unwrap().count_use(interval);
+        }
+        true
+    }
+
+    pub fn get(&self, key: &SignalKey) -> Option<&CnoStats> {
+        self.stats.get(key)
+    }
+
+    /// Statistics by key: NAV-SAT before NAV-SIG, each constellation
+    /// before its satellites.
+    pub fn iter(&self) -> impl Iterator<Item = (&SignalKey, &CnoStats)> {
+        self.stats.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    /// The report rows, in [`iter`](Self::iter) order.
+    pub fn summaries(&self) -> Vec<SignalSummary> {
+        self.stats
+            .iter()
+            .map(|(key, stats)| {
+                let gnss = Gnss::from_id(key.gnss_id);
+                SignalSummary {
+                    source: key.source.label(),
+                    gnss: gnss.map_or_else(|| format!("gnssId {}", key.gnss_id), |g| g.name().to_string()),
+                    sv_id: key.sv_id,
+                    sig_id: key.sig_id,
+                    signal: gnss.zip(key.sig_id).and_then(|(g, s)| g.rinex_signal(s)),
+                    epochs: stats.epochs,
+                    mean_cno: stats.mean().unwrap_or(0.0),
+                    min_cno: stats.min,
+                    max_cno: stats.max,
+                    used_epochs: stats.used_epochs,
+                    used_s: stats.used_ms as f64 / 1_000.0,
+                }
+            })
+            .collect()
+    }
+
+    pub fn write_json(&self, out: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *out, &self.summaries())?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    pub fn write_csv(&self, out: &mut dyn Write) -> Result<()> {
+        let mut csv = csv::Writer::from_writer(out);
+        for row in self.summaries() {
+            csv.serialize(row).map_err(csv_error)?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+/// `field.member`, or `bit` of the raw `field` when it has no members.
+fn flag(entry: &Fields, field: &str, member: &str, bit: u32) -> bool {
+    match entry.get(field) {
+        Some(Value::Record(members)) => members.get(member).and_then(Value::as_u64) == Some(1),
+        Some(raw) => raw.as_u64().is_some_and(|v| v >> bit & 1 == 1),
+        None => false,
+    }
+}
@@ -0,0 +1,220 @@
+//! Backup of battery-backed RAM to flash with UPD-SOS.
+//!
+//! A receiver without a backup battery loses its BBR (almanac, ephemerides,
+//! last position and time, and configuration held there) at power-down.
+//! UPD-SOS saves it to flash instead: the host stops GNSS with CFG-RST
+//! (controlled GNSS stop), commands the backup and waits for the UPD-SOS
+//! acknowledge (`cmd` 2) before removing power. At the next start the
+//! receiver restores the BBR from flash, deletes the backup and reports the
+//! result with UPD-SOS `cmd` 3, which a poll also returns.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::decode::Decoder;
+use crate::encode::Encoder;
+use crate::error::Result;
+use crate::frame::{Frame, FrameParser};
+use crate::schema::Schema;
+use crate::transact::{wait_for, RetryPolicy};
+use crate::value::Value;
+
+/// CFG-RST reset modes.
+const HARDWARE_RESET: u8 = 0x00;
+const GNSS_STOP: u8 = 0x08;
+
+/// Pause after the GNSS stop, so it has taken effect before the receiver is
+/// asked to write flash.
+const STOP_SETTLE: Duration = Duration::from_millis(100);
+
+/// What the receiver reports about the last restore (`response` of `cmd` 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreStatus {
+    Unknown,
+    Failed,
+    Restored,
+    /// No backup was found.
+    NotRestored,
+}
+
+impl RestoreStatus {
+    fn from_response(response: u64) -> Self {
+        match response {
+            1 => RestoreStatus::Failed,
+            2 => RestoreStatus::Restored,
+            3 => RestoreStatus::NotRestored,
+            _ => RestoreStatus::Unknown,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RestoreStatus::Unknown => "unknown",
+            RestoreStatus::Failed => "failed restoring from backup",
+            RestoreStatus::Restored => "restored from backup",
+            RestoreStatus::NotRestored => "not restored (no backup)",
+        }
+    }
+}
+
+impl fmt::Display for RestoreStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A UPD-SOS output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SosReply {
+    /// Answer to a backup or clear command: whether it was carried out.
+    Ack(bool),
+    Restore(RestoreStatus),
+}
+
+/// UPD-SOS commands and the CFG messages around them, encoded and decoded
+/// with the schema.
+#[derive(Debug, Clone, Copy)]
+pub struct Sos<'s> {
+    encoder: Encoder<'s>,
+    decoder: Decoder<'s>,
+}
+
+impl<'s> Sos<'s> {
+    pub fn new(schema: &'s Schema) -> Self {
+        Self {
+            encoder: Encoder::new(schema),
+            decoder: Decoder::new(schema),
+        }
+    }
+
+    /// The UPD-SOS output in `frame`, if that is what it is.
+    pub fn parse(&self, frame: &Frame) -> Option<SosReply> {
+        let msg = self.decoder.decode(frame.class, frame.id, &frame.payload);
+        if msg.short_name() != "UPD-SOS" {
+            return None;
+        }
+        let response = msg.get("response").and_then(Value::as_u64)?;
+        match msg.get("cmd").and_then(Value::as_u64)? {
+            2 => Some(SosReply::Ack(response == 1)),
+            3 => Some(SosReply::Restore(RestoreStatus::from_response(response))),
+            _ => None,
+        }
+    }
+
+    /// UPD-SOS create backup in flash.
+    pub fn create_backup_frame(&self) -> Result<Frame> {
+        self.encoder.encode_json(&json!({"name": "UPD-SOS", "variant": "CREATE"}))
+    }
+
+    /// UPD-SOS clear backup in flash.
+    pub fn clear_backup_frame(&self) -> Result<Frame> {
+        self.encoder.encode_json(&json!({"name": "UPD-SOS", "variant": "CLEAR"}))
+    }
+
+    /// UPD-SOS poll of the restore status.
+    pub fn poll_frame(&self) -> Result<Frame> {
+        self.encoder.encode_json(&json!({"name": "UPD-SOS", "variant": "POLL"}))
+    }
+
+    /// CFG-RST controlled GNSS stop, which keeps the BBR as it is.
+    pub fn stop_gnss_frame(&self) -> Result<Frame> {
+        self.cfg_rst(0, GNSS_STOP)
+    }
+
+    /// CFG-RST hardware reset, clearing the whole BBR with `clear_bbr` so that
+    /// what survives it came from the flash backup.
+    pub fn hardware_reset_frame(&self, clear_bbr: bool) -> Result<Frame> {
+        self.cfg_rst(if clear_bbr { 0xFFFF } else { 0 }, HARDWARE_RESET)
+    }
+
+    fn cfg_rst(&self, nav_bbr_mask: u16, reset_mode: u8) -> Result<Frame> {
+        self.encoder.encode_json(&json!({
+            "name": "CFG-RST",
+            "fields": {"navBbrMask": nav_bbr_mask, "resetMode": reset_mode},
+        }))
+    }
+
+    /// CFG-CFG saving the current configuration of every section to BBR and
+    /// flash.
+    pub fn save_config_frame(&self) -> Result<Frame> {
+        self.encoder.encode_json(&json!({
+            "name": "CFG-CFG",
+            // devBBR, devFlash
+            "fields": {"clearMask": 0, "saveMask": 0xFFFF, "loadMask": 0, "deviceMask": 0x03},
+        }))
+    }
+
+    /// Stop GNSS and command a backup; returns the acknowledgment, or `None`
+    /// if there was none after `policy`'s resends. Power can be removed once
+    /// it is `Some(true)`.
+    pub fn create_backup<P: Read + Write + ?Sized>(
+        &self,
+        port: &mut P,
+        parser: &mut FrameParser,
+        policy: &RetryPolicy,
+    ) -> Result<Option<bool>> {
+        port.write_all(&self.stop_gnss_frame()?.to_bytes())?;
+        port.flush()?;
+        thread::sleep(STOP_SETTLE);
+        self.command(port, parser, &self.create_backup_frame()?, policy)
+    }
+
+    /// Delete the backup in flash; returns the acknowledgment as [`Sos::create_backup`].
+    pub fn clear_backup<P: Read + Write + ?Sized>(
+        &self,
+        port: &mut P,
+        parser: &mut FrameParser,
+        policy: &RetryPolicy,
+    ) -> Result<Option<bool>> {
+        self.command(port, parser, &self.clear_backup_frame()?, policy)
+    }
+
+    fn command<P: Read + Write + ?Sized>(
+        &self,
+        port: &mut P,
+        parser: &mut FrameParser,
+        frame: &Frame,
+        policy: &RetryPolicy,
+    ) -> Result<Option<bool>> {
+        let bytes = frame.to_bytes();
+        for attempt in 0..=policy.retries {
+            if attempt > 0 {
+                thread::sleep(policy.delay(attempt));
+            }
+            port.write_all(&bytes)?;
+            port.flush()?;
+            let ack = wait_for(port, parser, policy.timeout, |reply| match self.parse(reply) {
+                Some(SosReply::Ack(ack)) => Some(ack),
+                _ => None,
+            })?;
+            if ack.is_some() {
+                return Ok(ack);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The restore status after a start: the report the receiver sends on its
+    /// own if it arrives within `timeout`, otherwise the answer to a poll.
+    pub fn restore_status<P: Read + Write + ?Sized>(
+        &self,
+        port: &mut P,
+        parser: &mut FrameParser,
+        timeout: Duration,
+    ) -> Result<Option<RestoreStatus>> {
+        let restore = |reply: &Frame| match self.parse(reply) {
+            Some(SosReply::Restore(status)) => Some(status),
+            _ => None,
+        };
+        if let Some(status) = wait_for(port, parser, timeout, restore)? {
+            return Ok(Some(status));
+        }
+        port.write_all(&self.poll_frame()?.to_bytes())?;
+        port.flush()?;
+        wait_for(port, parser, timeout, restore)
+    }
+}
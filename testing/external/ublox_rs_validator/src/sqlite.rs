@@ -0,0 +1,451 @@
+//! SQLite logging: decoded frames as SQL statements, one table per message type.
+//!
+//! Every frame becomes a row of `frames` with its raw bytes. Each message
+//! type (and each variant, e.g. `mga_gps_eph`) gets a table named after it
+//! whose columns are generated from the schema, matching what the decoder
+//! produces with the same [`DecodeOptions`]: one column per field, or per
+//! bitfield member (`flags_carrSoln`) when flags are expanded. Repeated
+//! groups get a child table such as `nav_sat_satellites` with one row per
+//! element. Message rows reference their frame by `frame`, so a log can be
+//! queried with joins. Tables are created when their first message arrives.
+//! `frames.host_time` is the host receive time, when the log has a
+//! timestamps CSV ([`SqlWriter::with_host_times`]).
+//!
+//! [`SqlWriter`] only writes SQL text; `ubx-sqlite` pipes it into the
+//! `sqlite3` command-line shell, which builds the database.
+//!
+//! The same tables can be provisioned ahead of ingestion: [`schema_ddl`]
+//! gives the `CREATE TABLE` statements for every message of the schema, in
+//! the SQLite or PostgreSQL [`Dialect`]. PostgreSQL columns are typed from
+//! the field's base type: the narrowest integer that holds it, `REAL` or
+//! `DOUBLE PRECISION` for floats and scaled values, and `JSONB` for arrays
+//! and nested records.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::decode::{DecodeOptions, DecodedMessage};
+use crate::error::Result;
+use crate::frame::FrameRef;
+use crate::host_time::HostTimes;
+use crate::schema::{normalize_name, BaseType, DataType, FieldDef, MessageDef, PayloadDef, Schema};
+use crate::value::Value;
+
+/// Frames written per transaction.
+pub const BATCH_SIZE: usize = 10_000;
+
+/// SQLite storage class of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl SqlType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SqlType::Integer => "INTEGER",
+            SqlType::Real => "REAL",
+            SqlType::Text => "TEXT",
+        }
+    }
+}
+
+/// SQL dialect of generated statements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    #[default]
+    Sqlite,
+    Postgres,
+}
+
+impl Dialect {
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::Sqlite => "sqlite",
+            Dialect::Postgres => "postgres",
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sqlite" | "sqlite3" => Ok(Dialect::Sqlite),
+            "postgres" | "postgresql" | "pg" => Ok(Dialect::Postgres),
+            other => Err(format!("unknown SQL dialect '{other}' (expected sqlite or postgres)")),
+        }
+    }
+}
+
+/// One column and the decoded value it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    /// Path of the value in the decoded fields, e.g. `flags.carrSoln`.
+    pub path: String,
+    pub sql_type: SqlType,
+    /// Type of the raw value (of a string's characters); for a bitfield
+    /// member, the smallest unsigned type that holds it. `None` for JSON text.
+    pub base: Option<BaseType>,
+}
+
+impl Column {
+    /// The column type in `dialect`.
+    pub fn type_name(&self, dialect: Dialect) -> &'static str {
+        if dialect == Dialect::Sqlite {
+            return self.sql_type.as_str();
+        }
+        match (self.sql_type, self.base) {
+            (SqlType::Real, Some(BaseType::R4)) => "REAL",
+            (SqlType::Real, _) => "DOUBLE PRECISION",
+            (SqlType::Text, Some(_)) => "TEXT",
+            (SqlType::Text, None) => "JSONB",
+            (SqlType::Integer, Some(BaseType::U1 | BaseType::I1 | BaseType::X1 | BaseType::I2)) => "SMALLINT",
+            (SqlType::Integer, Some(BaseType::U2 | BaseType::X2 | BaseType::I4)) => "INTEGER",
+            (SqlType::Integer, _) => "BIGINT",
+        }
+    }
+}
+
+/// The table for one message type or variant, with child tables for its
+/// repeated groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+    /// `(group field name, child table)`.
+    pub groups: Vec<(String, Table)>,
+}
+
+impl Table {
+    /// The table for `payload` of `msg`, or of its variant `variant`.
+    pub fn for_payload(
+        msg: &MessageDef,
+        variant: Option<&str>,
+        payload: &PayloadDef,
+        options: DecodeOptions,
+    ) -> Self {
+        let mut name = table_name(msg.short_name());
+        if let Some(variant) = variant {
+            name = format!("{name}_{}", table_name(variant));
+        }
+        let mut groups = Vec::new();
+        let mut columns = Vec::new();
+        for field in &payload.fields {
+            match &field.data_type {
+                DataType::Group { fields, .. } if !field.reserved => {
+                    groups.push((field.name.clone(), child_table(&name, &field.name, fields, options)));
+                }
+                _ => columns.extend(field_columns(field, options)),
+            }
+        }
+        for group in &payload.repeated_groups {
+            groups.push((group.name.clone(), child_table(&name, &group.name, &group.fields, options)));
+        }
+        Self { name, columns, groups }
+    }
+
+    /// The table for a decoded message's type and variant, if the schema
+    /// defines its payload.
+    pub fn for_message(schema: &Schema, msg: &DecodedMessage, options: DecodeOptions) -> Option<Self> {
+        let def = schema.message_by_name(&msg.name)?;
+        let payload = match &msg.variant {
+            Some(variant) => &def.variant(variant)?.payload,
+            None => def.payload.as_ref()?,
+        };
+        Some(Self::for_payload(def, msg.variant.as_deref(), payload, options))
+    }
+
+    /// `CREATE TABLE` statements for this table and its child tables.
+    pub fn ddl(&self) -> String {
+        self.ddl_for(Dialect::Sqlite)
+    }
+
+    /// [`ddl`](Self::ddl) in `dialect`.
+    pub fn ddl_for(&self, dialect: Dialect) -> String {
+        let frame = match dialect {
+            Dialect::Sqlite => "INTEGER",
+            Dialect::Postgres => "BIGINT",
+        };
+        let mut sql = create(
+            &self.name,
+            &format!("frame {frame} PRIMARY KEY REFERENCES frames(id)"),
+            &self.columns,
+            dialect,
+        );
+        for (_, child) in &self.groups {
+            sql += &create(
+                &child.name,
+                &format!("frame {frame} NOT NULL REFERENCES {}(frame), idx INTEGER NOT NULL", self.name),
+                &child.columns,
+                dialect,
+            );
+            let _ = writeln!(sql, "CREATE INDEX IF NOT EXISTS {0}_frame ON {0}(frame, idx);", child.name);
+        }
+        sql
+    }
+}
+
+/// `CREATE TABLE` statement for `frames`, which holds every frame of a log.
+pub fn frames_ddl(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::Sqlite => {
+            "CREATE TABLE IF NOT EXISTS frames (id INTEGER PRIMARY KEY, offset INTEGER NOT NULL, \
+             class INTEGER NOT NULL, msg_id INTEGER NOT NULL, message TEXT, itow INTEGER, host_time REAL, \
+             raw BLOB NOT NULL);\n"
+        }
+        Dialect::Postgres => {
+            "CREATE TABLE IF NOT EXISTS frames (id BIGSERIAL PRIMARY KEY, \"offset\" BIGINT NOT NULL, \
+             class SMALLINT NOT NULL, msg_id SMALLINT NOT NULL, message TEXT, itow BIGINT, \
+             host_time DOUBLE PRECISION, raw BYTEA NOT NULL);\n"
+        }
+    }
+}
+
+/// The tables of every message and variant in `schema`, or of the
+/// messages in `only`, for messages decoded with `options`.
+pub fn schema_tables(schema: &Schema, only: &[String], options: DecodeOptions) -> Vec<Table> {
+    let only: Vec<String> = only.iter().map(|n| normalize_name(n)).collect();
+    let mut tables = Vec::new();
+    for msg in schema.messages.iter().filter(|m| only.is_empty() || only.contains(&m.name)) {
+        if let Some(payload) = &msg.payload {
+            tables.push(Table::for_payload(msg, None, payload, options));
+        }
+        for variant in &msg.variants {
+            tables.push(Table::for_payload(msg, Some(&variant.name), &variant.payload, options));
+        }
+    }
+    tables
+}
+
+/// `frames` and the [`schema_tables`] as one script.
+pub fn schema_ddl(schema: &Schema, only: &[String], options: DecodeOptions, dialect: Dialect) -> String {
+    let mut sql = frames_ddl(dialect).to_string();
+    for table in schema_tables(schema, only, options) {
+        sql += &table.ddl_for(dialect);
+    }
+    sql
+}
+
+fn create(name: &str, key: &str, columns: &[Column], dialect: Dialect) -> String {
+    let mut sql = format!("CREATE TABLE IF NOT EXISTS {name} ({key}");
+    for column in columns {
+        let _ = write!(sql, ", {} {}", quote_ident(&column.name), column.type_name(dialect));
+    }
+    sql + ");\n"
+}
+
+fn child_table(parent: &str, group: &str, fields: &[FieldDef], options: DecodeOptions) -> Table {
+    Table {
+        name: format!("{parent}_{}", table_name(group)),
+        columns: fields.iter().flat_map(|f| field_columns(f, options)).collect(),
+        groups: Vec::new(),
+    }
+}
+
+/// Columns for one field; nested groups are kept as JSON text.
+fn field_columns(field: &FieldDef, options: DecodeOptions) -> Vec<Column> {
+    if field.reserved && !options.include_reserved {
+        return Vec::new();
+    }
+    let column = |name: String, path: String, sql_type, base| Column {
+        name,
+        path,
+        sql_type,
+        base,
+    };
+    match &field.data_type {
+        DataType::Scalar(_) if options.expand_flags && !field.bits.is_empty() => field
+            .bits
+            .iter()
+            .filter(|bit| !bit.reserved || options.include_reserved)
+            .map(|bit| {
+                let path = format!("{}.{}", field.name, bit.name);
+                let base = match bit.bit_width {
+                    0..=8 => BaseType::U1,
+                    9..=16 => BaseType::U2,
+                    _ => BaseType::U4,
+                };
+                column(format!("{}_{}", field.name, bit.name), path, SqlType::Integer, Some(base))
+            })
+            .collect(),
+        DataType::Scalar(base) => {
+            let sql_type = if base.is_float() || (options.scale && field.multiplier().is_some()) {
+                SqlType::Real
+            } else if *base == BaseType::CH {
+                SqlType::Text
+            } else {
+                SqlType::Integer
+            };
+            vec![column(field.name.clone(), field.name.clone(), sql_type, Some(*base))]
+        }
+        DataType::Array { base: BaseType::CH, .. } => {
+            vec![column(field.name.clone(), field.name.clone(), SqlType::Text, Some(BaseType::CH))]
+        }
+        DataType::Array { .. } | DataType::Group { .. } => {
+            vec![column(field.name.clone(), field.name.clone(), SqlType::Text, None)]
+        }
+        DataType::Unknown(_) => Vec::new(),
+    }
+}
+
+/// `NAV-PVT` -> `nav_pvt`.
+pub fn table_name(name: &str) -> String {
+    name.trim_start_matches("UBX-")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A value as an SQL literal; arrays and records become JSON text.
+fn literal(value: Option<&Value>) -> String {
+    match value {
+        None => "NULL".into(),
+        Some(Value::Unsigned(v)) if i64::try_from(*v).is_ok() => v.to_string(),
+        Some(Value::Unsigned(v)) => format!("{}.0", v),
+        Some(Value::Signed(v)) => v.to_string(),
+        Some(Value::Float(v)) if v.is_finite() => format!("{v:?}"),
+        Some(Value::Float(_)) => "NULL".into(),
+        Some(Value::Text(s)) => text(s),
+        Some(v @ (Value::Array(_) | Value::Record(_))) => text(&serde_json::to_string(v).unwrap_or_default()),
+    }
+}
+
+fn text(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Streams frames and their decoded messages to `W` as SQL.
+pub struct SqlWriter<'s, W: Write> {
+    schema: &'s Schema,
+    options: DecodeOptions,
+    out: W,
+    /// Tables created so far, by message name and variant.
+    tables: BTreeMap<(String, Option<String>), Table>,
+    in_batch: usize,
+    host_times: Option<HostTimes>,
+    pub frames: usize,
+}
+
+impl<'s, W: Write> SqlWriter<'s, W> {
+    /// Start a transaction and create the `frames` table. `options` must be
+    /// those the messages are decoded with.
+    pub fn new(schema: &'s Schema, options: DecodeOptions, mut out: W) -> Result<Self> {
+        writeln!(out, "PRAGMA foreign_keys = ON;")?;
+        writeln!(out, "BEGIN;")?;
+        out.write_all(frames_ddl(Dialect::Sqlite).as_bytes())?;
+        Ok(Self {
+            schema,
+            options,
+            out,
+            tables: BTreeMap::new(),
+            in_batch: 0,
+            host_times: None,
+            frames: 0,
+        })
+    }
+
+    /// Fill `frames.host_time` from `host_times`, by frame offset. Without
+    /// it the column is left out of the inserts, so databases created
+    /// before it existed can still be appended to.
+    pub fn with_host_times(mut self, host_times: HostTimes) -> Self {
+        self.host_times = Some(host_times);
+        self
+    }
+
+    /// Insert `frame` and, if it decoded, its message.
+    pub fn write(&mut self, frame: &FrameRef<'_>, msg: &DecodedMessage) -> Result<()> {
+        self.write_frame(frame, msg)?;
+        if msg.parsed {
+            self.insert_message(msg)?;
+        }
+        Ok(())
+    }
+
+    /// Insert `frame` into `frames` only.
+    pub fn write_frame(&mut self, frame: &FrameRef<'_>, msg: &DecodedMessage) -> Result<()> {
+        if self.in_batch >= BATCH_SIZE {
+            writeln!(self.out, "COMMIT;\nBEGIN;")?;
+            self.in_batch = 0;
+        }
+        let name = if msg.parsed { text(&msg.name) } else { "NULL".into() };
+        let (host_column, host_time) = match &self.host_times {
+            Some(times) => {
+                let time = times.at(frame.offset).map_or("NULL".into(), |t| t.to_string());
+                (", host_time", format!(", {time}"))
+            }
+            None => ("", String::new()),
+        };
+        writeln!(
+            self.out,
+            "INSERT INTO frames (offset, class, msg_id, message, itow{host_column}, raw) \
+             VALUES ({}, {}, {}, {name}, {}{host_time}, X'{}');",
+            frame.offset,
+            frame.class,
+            frame.id,
+            msg.itow().map_or("NULL".into(), |t| t.to_string()),
+            hex::encode(frame.raw)
+        )?;
+        self.frames += 1;
+        self.in_batch += 1;
+        Ok(())
+    }
+
+    fn insert_message(&mut self, msg: &DecodedMessage) -> Result<()> {
+        let key = (msg.name.clone(), msg.variant.clone());
+        if !self.tables.contains_key(&key) {
+            let Some(table) = Table::for_message(self.schema, msg, self.options) else {
+                return Ok(());
+            };
+            self.out.write_all(table.ddl().as_bytes())?;
+            self.tables.insert(key.clone(), table);
+        }
+        let table = &self.tables[&key];
+
+        let frame_id = "(SELECT max(id) FROM frames)";
+        let names: Vec<String> = table.columns.iter().map(|c| quote_ident(&c.name)).collect();
+        let values: Vec<String> = table.columns.iter().map(|c| literal(msg.fields.get_path(&c.path))).collect();
+        writeln!(
+            self.out,
+            "INSERT INTO {} (frame{}) VALUES ({frame_id}{});",
+            table.name,
+            names.iter().map(|n| format!(", {n}")).collect::<String>(),
+            values.iter().map(|v| format!(", {v}")).collect::<String>()
+        )?;
+        for (group, child) in &table.groups {
+            let Some(items) = msg.get(group).and_then(Value::as_array) else {
+                continue;
+            };
+            let names: String = child.columns.iter().map(|c| format!(", {}", quote_ident(&c.name))).collect();
+            for (i, record) in items.iter().filter_map(Value::as_record).enumerate() {
+                let values: String =
+                    child.columns.iter().map(|c| format!(", {}", literal(record.get_path(&c.path)))).collect();
+                writeln!(self.out, "INSERT INTO {} (frame, idx{names}) VALUES ({frame_id}, {i}{values});", child.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Commit the open transaction and flush.
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.out, "COMMIT;")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
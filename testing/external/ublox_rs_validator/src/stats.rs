@@ -0,0 +1,187 @@
+//! Per-message counts, rates, jitter and iTOW gaps for a log.
+//!
+//! Intervals are measured on the receiver's iTOW, so host-side buffering does
+//! not show up as jitter. The nominal interval of a message is the median of
+//! its intervals; an interval of more than 1.5 times the nominal one is a gap.
+
+use std::collections::BTreeMap;
+
+use crate::decode::DecodedMessage;
+use crate::error::FrameError;
+use crate::frame::ScanStats;
+use crate::schema::Schema;
+use crate::time::{itow_delta, MS_PER_WEEK};
+
+/// A run of missing epochs between two consecutive messages of one type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// iTOW of the last message before the gap.
+    pub after: u32,
+    /// iTOW of the first message after the gap.
+    pub before: u32,
+    /// Estimated number of epochs lost.
+    pub missing: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageStats {
+    pub name: String,
+    pub count: usize,
+    /// Frame bytes including sync, header and checksum.
+    pub bytes: usize,
+    pub checksum_errors: usize,
+    pub first_itow: Option<u32>,
+    pub last_itow: Option<u32>,
+    /// iTOW intervals in ms between consecutive messages.
+    pub intervals: Vec<i64>,
+    /// Messages whose iTOW repeated or went backwards.
+    pub out_of_order: usize,
+}
+
+impl MessageStats {
+    /// Median interval in ms.
+    pub fn nominal_interval(&self) -> Option<i64> {
+        let mut positive: Vec<i64> = self.intervals.iter().copied().filter(|d| *d > 0).collect();
+        if positive.is_empty() {
+            return None;
+        }
+        positive.sort_unstable();
+        Some(positive[positive.len() / 2])
+    }
+
+    /// Messages per second at the nominal interval.
+    pub fn rate_hz(&self) -> Option<f64> {
+        self.nominal_interval().map(|ms| 1_000.0 / ms as f64)
+    }
+
+    pub fn mean_interval(&self) -> Option<f64> {
+        let positive: Vec<f64> = self.intervals.iter().filter(|d| **d > 0).map(|d| *d as f64).collect();
+        (!positive.is_empty()).then(|| positive.iter().sum::<f64>() / positive.len() as f64)
+    }
+
+    /// Standard deviation of the intervals that are not gaps, in ms.
+    pub fn jitter(&self) -> Option<f64> {
+        let nominal = self.nominal_interval()? as f64;
+        let regular: Vec<f64> = self
+            .intervals
+            .iter()
+            .map(|d| *d as f64)
+            .filter(|d| *d > 0.0 && *d <= nominal * 1.5)
+            .collect();
+        let mean = regular.iter().sum::<f64>() / regular.len() as f64;
+        let var = regular.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / regular.len() as f64;
+        Some(var.sqrt())
+    }
+
+    pub fn gaps(&self) -> Vec<Gap> {
+        let Some(nominal) = self.nominal_interval() else {
+            return Vec::new();
+        };
+        let Some(first) = self.first_itow else {
+            return Vec::new();
+        };
+        let mut gaps = Vec::new();
+        let mut itow = i64::from(first);
+        for &d in &self.intervals {
+            let next = itow + d;
+            if d * 2 > nominal * 3 {
+                let missing = ((d as f64 / nominal as f64).round() as u32).saturating_sub(1).max(1);
+                gaps.push(Gap {
+                    after: wrap(itow),
+                    before: wrap(next),
+                    missing,
+                });
+            }
+            itow = next;
+        }
+        gaps
+    }
+
+    pub fn missing_epochs(&self) -> u32 {
+        self.gaps().iter().map(|g| g.missing).sum()
+    }
+}
+
+fn wrap(itow: i64) -> u32 {
+    itow.rem_euclid(i64::from(MS_PER_WEEK)) as u32
+}
+
+/// Accumulates statistics over a decoded message stream.
+#[derive(Debug, Clone, Default)]
+pub struct LogStats {
+    /// Keyed by message name (with variant, if any).
+    pub messages: BTreeMap<String, MessageStats>,
+    pub scan: ScanStats,
+    last_itow: Option<u32>,
+    span_ms: i64,
+}
+
+impl LogStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a decoded message whose frame was `frame_len` bytes long.
+    pub fn push(&mut self, msg: &DecodedMessage, frame_len: usize) {
+        let mut key = msg.short_name().to_string();
+        if let Some(variant) = &msg.variant {
+            key.push_str(&format!(" ({variant})"));
+        }
+        let entry = self.messages.entry(key.clone()).or_insert_with(|| MessageStats {
+            name: key,
+            ..MessageStats::default()
+        });
+        entry.count += 1;
+        entry.bytes += frame_len;
+
+        let Some(itow) = msg.itow() else {
+            return;
+        };
+        if let Some(last) = entry.last_itow {
+            let d = itow_delta(last, itow);
+            if d <= 0 {
+                entry.out_of_order += 1;
+            } else {
+                entry.intervals.push(d);
+                entry.last_itow = Some(itow);
+            }
+        } else {
+            entry.first_itow = Some(itow);
+            entry.last_itow = Some(itow);
+        }
+
+        match self.last_itow {
+            Some(last) => {
+                let d = itow_delta(last, itow);
+                if d > 0 {
+                    self.span_ms += d;
+                    self.last_itow = Some(itow);
+                }
+            }
+            None => self.last_itow = Some(itow),
+        }
+    }
+
+    /// Record a frame that failed its checksum, attributing it to the
+    /// message its class and ID belong to.
+    pub fn push_error(&mut self, schema: &Schema, err: &FrameError) {
+        let FrameError::Checksum { class, id, .. } = err else {
+            return;
+        };
+        let key = schema
+            .messages_by_ids(*class, *id)
+            .next()
+            .map(|m| m.short_name().to_string())
+            .unwrap_or_else(|| format!("{class:02X}-{id:02X}"));
+        let entry = self.messages.entry(key.clone()).or_insert_with(|| MessageStats {
+            name: key,
+            ..MessageStats::default()
+        });
+        entry.checksum_errors += 1;
+    }
+
+    /// Receiver time covered by the log, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.span_ms as f64 / 1_000.0
+    }
+}
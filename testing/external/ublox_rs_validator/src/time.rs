@@ -0,0 +1,445 @@
+//! GPS time-of-week helpers.
+//!
+//! Week numbers broadcast in navigation data, and reported by some older
+//! receivers, are truncated (10 bits for GPS LNAV, 8 bits in almanacs and
+//! UTC parameters, 13 bits for BeiDou) and roll over every few years.
+//! [`WeekReference`] holds the era to resolve them in: a known date, or the
+//! full week picked up from NAV-TIMEGPS, NAV-TIMEUTC or NAV-PVT in the same
+//! log.
+//!
+//! GPS time runs ahead of UTC by the leap seconds inserted since 1980.
+//! [`LeapSeconds`] takes the offset, and any announced change, from
+//! NAV-TIMELS, NAV-TIMEGPS or RXM-RAWX, and falls back to a built-in table of
+//! past leap seconds; a converted [`UtcTime`] says which offset was applied
+//! and whether the receiver vouched for it.
+
+use crate::decode::DecodedMessage;
+use crate::value::Value;
+
+/// Milliseconds in a GPS week.
+pub const MS_PER_WEEK: u32 = 604_800_000;
+
+/// Signed difference `next - prev` between two iTOW values in milliseconds,
+/// taking the shorter way around the week boundary.
+pub fn itow_delta(prev: u32, next: u32) -> i64 {
+    let week = i64::from(MS_PER_WEEK);
+    let mut delta = i64::from(next) - i64::from(prev);
+    if delta > week / 2 {
+        delta -= week;
+    } else if delta < -week / 2 {
+        delta += week;
+    }
+    delta
+}
+
+/// Seconds in a GPS week.
+pub const SECS_PER_WEEK: f64 = 604_800.0;
+
+/// Days from 1970-01-01 to the GPS epoch, 1980-01-06.
+const GPS_EPOCH_UNIX_DAYS: i64 = 3_657;
+
+/// A calendar date and time of day, in whatever time scale it was derived from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: f64,
+}
+
+impl DateTime {
+    /// Seconds since the Unix epoch, treating the fields as UTC.
+    pub fn to_unix(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        days as f64 * 86_400.0 + f64::from(self.hour) * 3_600.0 + f64::from(self.minute) * 60.0 + self.second
+    }
+
+    /// ISO 8601 with millisecond precision, e.g. `2024-01-15T12:00:00.000Z`.
+    pub fn to_iso8601(&self) -> String {
+        if self.second >= 60.0 {
+            // A leap second, 23:59:60, which Unix time can't hold.
+            return format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:06.3}Z",
+                self.year,
+                self.month,
+                self.day,
+                self.hour,
+                self.minute,
+                (self.second * 1_000.0).floor() / 1_000.0
+            );
+        }
+        // Round through Unix time so 59.9996 s carries into the next minute.
+        let t = unix_to_datetime((self.to_unix() * 1_000.0).round() / 1_000.0);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:06.3}Z",
+            t.year, t.month, t.day, t.hour, t.minute, t.second
+        )
+    }
+}
+
+/// Parse `YYYY-MM-DD[THH:MM[:SS[.fff]]][Z]` (a space may replace the `T`).
+pub fn parse_iso8601(s: &str) -> Option<DateTime> {
+    let s = s.trim().trim_end_matches(['Z', 'z']);
+    let (date, time) = match s.split_once(['T', 't', ' ']) {
+        Some((d, t)) => (d, t),
+        None => (s, ""),
+    };
+    let mut d = date.splitn(3, '-');
+    let year = d.next()?.parse().ok()?;
+    let month: u8 = d.next()?.parse().ok()?;
+    let day: u8 = d.next()?.parse().ok()?;
+    let mut t = time.splitn(3, ':');
+    let hour: u8 = t.next().filter(|h| !h.is_empty()).map_or(Some(0), |h| h.parse().ok())?;
+    let minute: u8 = t.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second: f64 = t.next().map_or(Some(0.0), |s| s.parse().ok())?;
+    let valid = (1..=12).contains(&month) && (1..=31).contains(&day) && hour < 24 && minute < 60;
+    (valid && (0.0..61.0).contains(&second)).then_some(DateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+    })
+}
+
+/// Calendar time for a full GPS week number and time of week in seconds.
+///
+/// The result is in the GPS time scale; no leap seconds are applied.
+pub fn gps_to_datetime(week: u32, tow: f64) -> DateTime {
+    let day_of_week = (tow / 86_400.0).floor();
+    let mut secs = tow - day_of_week * 86_400.0;
+    let mut days = GPS_EPOCH_UNIX_DAYS + i64::from(week) * 7 + day_of_week as i64;
+    // Rounding of fractional input can land exactly on the next day.
+    if secs >= 86_400.0 {
+        secs -= 86_400.0;
+        days += 1;
+    }
+    let (year, month, day) = civil_from_days(days);
+    let hour = (secs / 3_600.0).floor();
+    let minute = ((secs - hour * 3_600.0) / 60.0).floor();
+    DateTime {
+        year,
+        month,
+        day,
+        hour: hour as u8,
+        minute: minute as u8,
+        second: secs - hour * 3_600.0 - minute * 60.0,
+    }
+}
+
+/// UTC calendar time for seconds since the Unix epoch.
+pub fn unix_to_datetime(secs: f64) -> DateTime {
+    let days = (secs / 86_400.0).floor();
+    let rem = secs - days * 86_400.0;
+    let (year, month, day) = civil_from_days(days as i64);
+    let hour = (rem / 3_600.0).floor();
+    let minute = ((rem - hour * 3_600.0) / 60.0).floor();
+    DateTime {
+        year,
+        month,
+        day,
+        hour: hour as u8,
+        minute: minute as u8,
+        second: rem - hour * 3_600.0 - minute * 60.0,
+    }
+}
+
+/// Resolve a truncated week number (10-bit for GPS LNAV) to the full week
+/// closest to `reference`.
+pub fn resolve_week(week: u32, bits: u32, reference: u32) -> u32 {
+    let modulus = 1i64 << bits;
+    let week = i64::from(week) % modulus;
+    let reference = i64::from(reference);
+    let base = reference - reference.rem_euclid(modulus);
+    let candidates = [base - modulus + week, base + week, base + modulus + week];
+    candidates
+        .into_iter()
+        .filter(|w| *w >= 0)
+        .min_by_key(|w| (w - reference).abs())
+        .unwrap_or(week) as u32
+}
+
+/// Full GPS week and time of week in seconds for a calendar time in the GPS
+/// time scale; the inverse of [`gps_to_datetime`].
+pub fn datetime_to_gps(t: &DateTime) -> (u32, f64) {
+    let secs = t.to_unix() - GPS_EPOCH_UNIX_DAYS as f64 * 86_400.0;
+    let week = (secs / SECS_PER_WEEK).floor().max(0.0);
+    (week as u32, secs - week * SECS_PER_WEEK)
+}
+
+/// The era truncated week numbers are resolved in, as a full GPS week.
+///
+/// A reference from a known date is fixed; otherwise the most recent valid
+/// week or date from NAV-TIMEGPS, NAV-TIMEUTC or NAV-PVT passed to
+/// [`observe`](Self::observe) is used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeekReference {
+    week: Option<u32>,
+    fixed: bool,
+}
+
+impl WeekReference {
+    /// No reference yet; [`observe`](Self::observe) sets one.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fixed reference week, which messages don't override.
+    pub fn fixed(week: u32) -> Self {
+        Self { week: Some(week), fixed: true }
+    }
+
+    /// A fixed reference at a calendar date.
+    pub fn at(t: &DateTime) -> Self {
+        Self::fixed(datetime_to_gps(t).0)
+    }
+
+    pub fn week(&self) -> Option<u32> {
+        self.week
+    }
+
+    /// Take the week from a time message with a valid week or date; returns
+    /// whether the reference changed.
+    pub fn observe(&mut self, msg: &DecodedMessage) -> bool {
+        if self.fixed {
+            return false;
+        }
+        let int = |path: &str| int(msg, path);
+        let week = match msg.short_name() {
+            "NAV-TIMEGPS" if flag(msg, "valid", "weekValid", 1) => int("week").and_then(|w| u32::try_from(w).ok()),
+            "NAV-TIMEUTC" if flag(msg, "valid", "validUTC", 2) => self.date_week(&int),
+            "NAV-PVT" if flag(msg, "valid", "validDate", 0) => self.date_week(&int),
+            _ => None,
+        };
+        match week {
+            Some(week) if self.week != Some(week) => {
+                self.week = Some(week);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn date_week(&self, int: &dyn Fn(&str) -> Option<i64>) -> Option<u32> {
+        let t = DateTime {
+            year: i32::try_from(int("year")?).ok()?,
+            month: u8::try_from(int("month")?).ok()?,
+            day: u8::try_from(int("day")?).ok()?,
+            hour: 0,
+            minute: 0,
+            second: 0.0,
+        };
+        (t.year >= 1980 && (1..=12).contains(&t.month)).then(|| datetime_to_gps(&t).0)
+    }
+
+    /// The full week for the lower `bits` of `week`, closest to the
+    /// reference; `None` without a reference.
+    pub fn resolve(&self, week: u32, bits: u32) -> Option<u32> {
+        Some(resolve_week(week, bits, self.week?))
+    }
+
+    /// Move a GPS-scale date from a receiver with a rollover fault by whole
+    /// 1024-week eras to the one closest to the reference.
+    pub fn correct(&self, t: &DateTime) -> DateTime {
+        let (week, tow) = datetime_to_gps(t);
+        match self.resolve(week, 10) {
+            Some(resolved) if resolved != week => gps_to_datetime(resolved, tow),
+            _ => *t,
+        }
+    }
+}
+
+/// GPS − UTC from each leap second since the GPS epoch: the first of the
+/// month (UTC) from which it applies, and the new offset.
+const LEAP_SECONDS: [(i32, u8, i32); 18] = [
+    (1981, 7, 1),
+    (1982, 7, 2),
+    (1983, 7, 3),
+    (1985, 7, 4),
+    (1988, 1, 5),
+    (1990, 1, 6),
+    (1991, 1, 7),
+    (1992, 7, 8),
+    (1993, 7, 9),
+    (1994, 7, 10),
+    (1996, 1, 11),
+    (1997, 7, 12),
+    (1999, 1, 13),
+    (2006, 1, 14),
+    (2009, 1, 15),
+    (2012, 7, 16),
+    (2015, 7, 17),
+    (2017, 1, 18),
+];
+
+/// A UTC time converted from GPS time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtcTime {
+    /// Seconds reach 60 during an inserted leap second.
+    pub time: DateTime,
+    /// GPS − UTC applied, in seconds.
+    pub leap_seconds: i32,
+    /// The offset came from the receiver's valid leap second information.
+    /// Otherwise it is from the built-in table, which is right for past
+    /// dates but knows no leap second announced after 2017.
+    pub valid: bool,
+}
+
+/// A change of GPS − UTC from `before` to `after` at `gps`, in seconds
+/// since the GPS epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LeapEvent {
+    gps: f64,
+    before: i32,
+    after: i32,
+}
+
+impl LeapEvent {
+    /// The table entry around `gps`: the next leap second, or after the last
+    /// one a change that never comes.
+    fn from_table(gps: f64) -> Self {
+        let mut before = 0;
+        for &(year, month, after) in &LEAP_SECONDS {
+            let days = days_from_civil(year, month, 1) - GPS_EPOCH_UNIX_DAYS;
+            let event = days as f64 * 86_400.0 + f64::from(after);
+            if gps < event {
+                return Self { gps: event, before, after };
+            }
+            before = after;
+        }
+        Self { gps: f64::INFINITY, before, after: before }
+    }
+
+    fn utc(&self, gps: f64) -> (DateTime, i32) {
+        let unix = |offset: i32| gps + GPS_EPOCH_UNIX_DAYS as f64 * 86_400.0 - f64::from(offset);
+        if gps >= self.gps {
+            return (unix_to_datetime(unix(self.after)), self.after);
+        }
+        let inserted = self.after - self.before;
+        if inserted > 0 && gps >= self.gps - f64::from(inserted) {
+            // 23:59:60: the new offset puts this in 23:59:59 or earlier.
+            let mut t = unix_to_datetime(unix(self.after));
+            t.second += f64::from(inserted);
+            return (t, self.before);
+        }
+        (unix_to_datetime(unix(self.before)), self.before)
+    }
+}
+
+/// GPS − UTC as known from the receiver, for converting GPS time to UTC.
+///
+/// NAV-TIMELS gives the current offset and the time and size of an
+/// announced change; NAV-TIMEGPS and RXM-RAWX the current offset once the
+/// receiver has decoded it. Without either the built-in table is used.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LeapSeconds {
+    current: Option<i32>,
+    event: Option<LeapEvent>,
+}
+
+impl LeapSeconds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A known current offset, as from a NAV-TIMEGPS `leaps`.
+    pub fn with_current(leap_seconds: i32) -> Self {
+        Self { current: Some(leap_seconds), event: None }
+    }
+
+    /// The current offset reported by the receiver.
+    pub fn current(&self) -> Option<i32> {
+        self.current
+    }
+
+    /// Take the offset, and from NAV-TIMELS an announced change, from a
+    /// message that has valid ones; returns whether anything changed.
+    pub fn observe(&mut self, msg: &DecodedMessage) -> bool {
+        let before = *self;
+        let int = |path: &str| int(msg, path);
+        let current = match msg.short_name() {
+            "NAV-TIMELS" if flag(msg, "valid", "validCurrLs", 0) => int("currLs"),
+            "NAV-TIMEGPS" if flag(msg, "valid", "leapSValid", 2) => int("leaps"),
+            "RXM-RAWX" if flag(msg, "recStat", "leapSec", 0) => int("leaps"),
+            _ => None,
+        };
+        let Some(current) = current.and_then(|c| i32::try_from(c).ok()) else {
+            return false;
+        };
+        // An event observed earlier stays until the offset no longer fits it.
+        if self.event.is_some_and(|e| current != e.before && current != e.after) {
+            self.event = None;
+        }
+        self.current = Some(current);
+
+        let change = int("lsChange").unwrap_or(0);
+        if msg.short_name() == "NAV-TIMELS" && change != 0 && flag(msg, "valid", "validTimeToLsEvent", 1) {
+            if let (Some(itow), Some(to_event), Some(week)) = (msg.itow(), int("timeToLsEvent"), int("dateOfLsGpsWn")) {
+                // The event is in week `dateOfLsGpsWn`; its time of week
+                // follows from now plus the countdown.
+                let tow = (i64::from(itow / 1_000) + to_event).rem_euclid(SECS_PER_WEEK as i64);
+                self.event = Some(LeapEvent {
+                    gps: week as f64 * SECS_PER_WEEK + tow as f64,
+                    before: current,
+                    after: current + change as i32,
+                });
+            }
+        }
+        *self != before
+    }
+
+    /// UTC for a full GPS week and time of week in seconds.
+    pub fn to_utc(&self, week: u32, tow: f64) -> UtcTime {
+        let gps = f64::from(week) * SECS_PER_WEEK + tow;
+        let (event, valid) = match (self.event, self.current) {
+            (Some(event), _) => (event, true),
+            (None, Some(current)) => (LeapEvent { gps: f64::INFINITY, before: current, after: current }, true),
+            (None, None) => (LeapEvent::from_table(gps), false),
+        };
+        let (time, leap_seconds) = event.utc(gps);
+        UtcTime { time, leap_seconds, valid }
+    }
+}
+
+/// UTC for a full GPS week and time of week in seconds, with the offset from
+/// the built-in leap second table.
+pub fn gps_to_utc(week: u32, tow: f64) -> UtcTime {
+    LeapSeconds::new().to_utc(week, tow)
+}
+
+fn int(msg: &DecodedMessage, path: &str) -> Option<i64> {
+    msg.get(path).and_then(Value::as_i64)
+}
+
+/// Bit `name` of flags `field`, expanded or not.
+fn flag(msg: &DecodedMessage, field: &str, name: &str, bit: u32) -> bool {
+    int(msg, &format!("{field}.{name}")).or_else(|| int(msg, field).map(|v| v >> bit & 1)) == Some(1)
+}
+
+/// Proleptic Gregorian date for a count of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,223 @@
+//! Timepulse and UTC statistics for timing receivers.
+//!
+//! Time-server qualification asks how good the pulse is and how steadily
+//! the receiver delivered it: the distribution of the TIM-TP quantization
+//! error (the offset of the next pulse from the ideal edge, which a
+//! disciplined clock corrects for), how often that error was invalid or the
+//! pulse not locked, pulses missing from the TIM-TP sequence, and every
+//! change of the time the pulse is aligned to. NAV-TIMEUTC adds the UTC
+//! side: time to the first valid UTC, how often it was lost, and the
+//! reported time accuracy while valid.
+//!
+//! Messages must be decoded with flag expansion enabled.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::decode::DecodedMessage;
+use crate::time::{itow_delta, MS_PER_WEEK};
+use crate::value::Value;
+
+/// Count, extremes and moments of a series of samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Statistics {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Statistics {
+    pub fn add(&mut self, x: f64) {
+        if self.count == 0 {
+            self.min = x;
+            self.max = x;
+        }
+        self.count += 1;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.sum += x;
+        self.sum_sq += x * x;
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    pub fn rms(&self) -> Option<f64> {
+        (self.count > 0).then(|| (self.sum_sq / self.count as f64).sqrt())
+    }
+
+    /// Population standard deviation.
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        Some((self.sum_sq / self.count as f64 - mean * mean).max(0.0).sqrt())
+    }
+}
+
+/// What the timepulse is aligned to, from TIM-TP `flags.timeBase` and
+/// `refInfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeSource {
+    /// GNSS system time, by `timeRefGnss`.
+    Gnss(u8),
+    /// UTC, by `utcStandard`.
+    Utc(u8),
+}
+
+impl fmt::Display for TimeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const GNSS: [&str; 5] = ["GPS", "GLONASS", "BeiDou", "Galileo", "NavIC"];
+        const UTC: [&str; 9] = ["", "CRL", "NIST", "USNO", "BIPM", "EU", "SU", "NTSC", "NPLI"];
+        match *self {
+            TimeSource::Gnss(id) => match GNSS.get(usize::from(id)) {
+                Some(name) => write!(f, "{name} time"),
+                None => write!(f, "GNSS {id} time"),
+            },
+            TimeSource::Utc(0) => f.write_str("UTC"),
+            TimeSource::Utc(std) => match UTC.get(usize::from(std)) {
+                Some(name) => write!(f, "UTC({name})"),
+                None => write!(f, "UTC (standard {std})"),
+            },
+        }
+    }
+}
+
+/// A new timepulse time source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceChange {
+    pub week: u32,
+    /// `towMS` of the first pulse on the new source.
+    pub tow_ms: u32,
+    pub from: TimeSource,
+    pub to: TimeSource,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimingReport {
+    /// TIM-TP messages.
+    pub pulses: usize,
+    /// Quantization error, ps, of the pulses where it is valid.
+    pub q_err: Statistics,
+    pub q_err_invalid: usize,
+    pub not_locked: usize,
+    pub raim_active: usize,
+    /// Pulses missing from the TIM-TP sequence, judged against its median
+    /// interval.
+    pub missed_pulses: u64,
+    pub sources: BTreeMap<TimeSource, usize>,
+    pub source_changes: Vec<SourceChange>,
+    /// NAV-TIMEUTC messages, and how many had `validUTC` set.
+    pub utc_epochs: usize,
+    pub utc_valid: usize,
+    /// Milliseconds from the first NAV-TIMEUTC to the first with valid UTC.
+    pub time_to_utc_ms: Option<i64>,
+    /// Changes from valid to invalid UTC.
+    pub utc_losses: usize,
+    /// `tAcc`, ns, while UTC is valid.
+    pub t_acc: Statistics,
+}
+
+/// Collects a [`TimingReport`] from TIM-TP and NAV-TIMEUTC.
+#[derive(Debug, Clone, Default)]
+pub struct TimingAnalyzer {
+    report: TimingReport,
+    /// Pulse time in ms since the GPS epoch, and source, of the last TIM-TP.
+    last_pulse: Option<(u64, TimeSource)>,
+    intervals: Vec<u64>,
+    first_utc: Option<u32>,
+    last_valid: Option<bool>,
+}
+
+impl TimingAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, msg: &DecodedMessage) {
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64);
+        match msg.name.as_str() {
+            "UBX-TIM-TP" => {
+                let (Some(tow_ms), Some(week)) = (int("towMS"), int("week")) else {
+                    return;
+                };
+                let report = &mut self.report;
+                report.pulses += 1;
+                if int("flags.qErrInvalid") == Some(1) {
+                    report.q_err_invalid += 1;
+                } else if let Some(q_err) = msg.get("qErr").and_then(Value::as_f64) {
+                    report.q_err.add(q_err);
+                }
+                if int("flags.TpNotLocked") == Some(1) {
+                    report.not_locked += 1;
+                }
+                if int("flags.raim") == Some(2) {
+                    report.raim_active += 1;
+                }
+                let source = match int("flags.timeBase") {
+                    Some(1) => TimeSource::Utc(int("refInfo.utcStandard").unwrap_or(0) as u8),
+                    _ => TimeSource::Gnss(int("refInfo.timeRefGnss").unwrap_or(0) as u8),
+                };
+                *report.sources.entry(source).or_default() += 1;
+
+                let at = week * u64::from(MS_PER_WEEK) + tow_ms;
+                if let Some((last, from)) = self.last_pulse {
+                    if at > last {
+                        self.intervals.push(at - last);
+                    }
+                    if from != source {
+                        report.source_changes.push(SourceChange {
+                            week: week as u32,
+                            tow_ms: tow_ms as u32,
+                            from,
+                            to: source,
+                        });
+                    }
+                }
+                self.last_pulse = Some((at, source));
+            }
+            "UBX-NAV-TIMEUTC" => {
+                let Some(itow) = msg.itow() else {
+                    return;
+                };
+                let report = &mut self.report;
+                let valid = int("valid.validUTC") == Some(1);
+                report.utc_epochs += 1;
+                let first = *self.first_utc.get_or_insert(itow);
+                if valid {
+                    report.utc_valid += 1;
+                    if report.time_to_utc_ms.is_none() {
+                        report.time_to_utc_ms = Some(itow_delta(first, itow));
+                    }
+                    if let Some(t_acc) = msg.get("tAcc").and_then(Value::as_f64) {
+                        report.t_acc.add(t_acc);
+                    }
+                }
+                if self.last_valid == Some(true) && !valid {
+                    report.utc_losses += 1;
+                }
+                self.last_valid = Some(valid);
+            }
+            _ => {}
+        }
+    }
+
+    /// The report so far.
+    pub fn report(&self) -> TimingReport {
+        let mut sorted = self.intervals.clone();
+        sorted.sort_unstable();
+        let missed = match sorted.get(sorted.len() / 2) {
+            Some(&nominal) => sorted
+                .iter()
+                .filter(|&&d| d * 2 > nominal * 3)
+                .map(|&d| ((d as f64 / nominal as f64).round() as u64).saturating_sub(1).max(1))
+                .sum(),
+            None => 0,
+        };
+        TimingReport {
+            missed_pulses: missed,
+            ..self.report.clone()
+        }
+    }
+}
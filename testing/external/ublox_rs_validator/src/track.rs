@@ -0,0 +1,289 @@
+//! GPX and KML track export from NAV-PVT and NAV-HPPOSLLH.
+//!
+//! Messages must be decoded with scaling and flag expansion enabled. When a
+//! NAV-HPPOSLLH shares the iTOW of the preceding NAV-PVT, its high-precision
+//! position replaces the PVT position for that point.
+
+use std::io::Write;
+
+use crate::decode::DecodedMessage;
+use crate::error::Result;
+use crate::time::DateTime;
+use crate::value::Value;
+
+/// Solution quality of a track point, from `fixType`, `diffSoln` and `carrSoln`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FixQuality {
+    NoFix,
+    DeadReckoning,
+    TimeOnly,
+    Fix2D,
+    Fix3D,
+    Dgnss,
+    RtkFloat,
+    RtkFixed,
+}
+
+impl FixQuality {
+    pub fn from_pvt(fix_type: u64, diff_soln: bool, carr_soln: u64) -> Self {
+        match (fix_type, carr_soln) {
+            (0, _) => FixQuality::NoFix,
+            (1, _) => FixQuality::DeadReckoning,
+            (5, _) => FixQuality::TimeOnly,
+            (_, 2) => FixQuality::RtkFixed,
+            (_, 1) => FixQuality::RtkFloat,
+            _ if diff_soln => FixQuality::Dgnss,
+            (2, _) => FixQuality::Fix2D,
+            _ => FixQuality::Fix3D,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FixQuality::NoFix => "No fix",
+            FixQuality::DeadReckoning => "Dead reckoning",
+            FixQuality::TimeOnly => "Time only",
+            FixQuality::Fix2D => "2D fix",
+            FixQuality::Fix3D => "3D fix",
+            FixQuality::Dgnss => "DGNSS",
+            FixQuality::RtkFloat => "RTK float",
+            FixQuality::RtkFixed => "RTK fixed",
+        }
+    }
+
+    /// True if the point has a usable position.
+    pub fn has_position(self) -> bool {
+        !matches!(self, FixQuality::NoFix | FixQuality::TimeOnly)
+    }
+
+    /// GPX `<fix>` value.
+    fn gpx_fix(self) -> &'static str {
+        match self {
+            FixQuality::NoFix | FixQuality::TimeOnly => "none",
+            FixQuality::Fix2D => "2d",
+            FixQuality::Dgnss | FixQuality::RtkFloat | FixQuality::RtkFixed => "dgps",
+            FixQuality::DeadReckoning | FixQuality::Fix3D => "3d",
+        }
+    }
+
+    /// Line colour as RGB hex: red (poor) through green (RTK fixed).
+    fn rgb(self) -> &'static str {
+        match self {
+            FixQuality::NoFix | FixQuality::TimeOnly => "808080",
+            FixQuality::DeadReckoning => "ff00ff",
+            FixQuality::Fix2D => "ff0000",
+            FixQuality::Fix3D => "ff8000",
+            FixQuality::Dgnss => "ffff00",
+            FixQuality::RtkFloat => "00ffff",
+            FixQuality::RtkFixed => "00ff00",
+        }
+    }
+
+    /// Garmin GPX extension colour name.
+    fn garmin_color(self) -> &'static str {
+        match self {
+            FixQuality::NoFix | FixQuality::TimeOnly => "DarkGray",
+            FixQuality::DeadReckoning => "Magenta",
+            FixQuality::Fix2D => "Red",
+            FixQuality::Fix3D => "DarkYellow",
+            FixQuality::Dgnss => "Yellow",
+            FixQuality::RtkFloat => "Cyan",
+            FixQuality::RtkFixed => "Green",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackPoint {
+    pub itow: u32,
+    /// UTC time, when NAV-PVT flagged date and time valid.
+    pub time: Option<DateTime>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Height above mean sea level, metres.
+    pub alt_msl: f64,
+    pub fix: FixQuality,
+    pub num_sv: u64,
+    pub pdop: Option<f64>,
+    /// Horizontal accuracy estimate, metres.
+    pub h_acc: f64,
+}
+
+/// Collects track points from a decoded message stream.
+#[derive(Debug, Default)]
+pub struct TrackBuilder {
+    points: Vec<TrackPoint>,
+    include_invalid: bool,
+}
+
+impl TrackBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep points without a position fix (they are dropped by default).
+    pub fn include_invalid(mut self, include: bool) -> Self {
+        self.include_invalid = include;
+        self
+    }
+
+    pub fn points(&self) -> &[TrackPoint] {
+        &self.points
+    }
+
+    pub fn push(&mut self, msg: &DecodedMessage) {
+        let num = |name: &str| msg.get(name).and_then(Value::as_f64);
+        let int = |name: &str| msg.get(name).and_then(Value::as_u64).unwrap_or(0);
+        match msg.name.as_str() {
+            "UBX-NAV-PVT" => {
+                let Some(itow) = msg.itow() else {
+                    return;
+                };
+                let fix = FixQuality::from_pvt(int("fixType"), int("flags.diffSoln") != 0, int("flags.carrSoln"));
+                let valid = int("flags.gnssFixOK") != 0 && fix.has_position();
+                if !valid && !self.include_invalid {
+                    return;
+                }
+                let (Some(lat), Some(lon)) = (num("lat"), num("lon")) else {
+                    return;
+                };
+                let time = (int("valid.validDate") != 0 && int("valid.validTime") != 0).then(|| DateTime {
+                    year: int("year") as i32,
+                    month: int("month") as u8,
+                    day: int("day") as u8,
+                    hour: int("hour") as u8,
+                    minute: int("min") as u8,
+                    second: int("sec") as f64 + num("nano").unwrap_or(0.0) * 1e-9,
+                });
+                self.points.push(TrackPoint {
+                    itow,
+                    time,
+                    lat,
+                    lon,
+                    alt_msl: num("hMSL").unwrap_or(0.0) / 1_000.0,
+                    fix,
+                    num_sv: int("numSV"),
+                    pdop: num("pDOP"),
+                    h_acc: num("hAcc").unwrap_or(0.0) / 1_000.0,
+                });
+            }
+            "UBX-NAV-HPPOSLLH" => {
+                let Some(point) = self.points.last_mut() else {
+                    return;
+                };
+                if msg.itow() != Some(point.itow) || int("flags.invalidLlh") != 0 {
+                    return;
+                }
+                let (Some(lat), Some(lon), Some(msl)) = (num("lat"), num("lon"), num("hMSL")) else {
+                    return;
+                };
+                point.lat = lat + num("latHp").unwrap_or(0.0);
+                point.lon = lon + num("lonHp").unwrap_or(0.0);
+                point.alt_msl = (msl + num("hMSLHp").unwrap_or(0.0)) / 1_000.0;
+                if let Some(h_acc) = num("hAcc") {
+                    point.h_acc = h_acc / 1_000.0;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split points into runs of equal fix quality. Consecutive runs share their
+/// boundary point so the drawn line stays continuous.
+fn segments(points: &[TrackPoint]) -> Vec<(FixQuality, &[TrackPoint])> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for i in 1..=points.len() {
+        if i == points.len() || points[i].fix != points[start].fix {
+            let end = (i + 1).min(points.len());
+            out.push((points[start].fix, &points[start..end]));
+            start = i;
+        }
+    }
+    out
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Write a GPX 1.1 document; each fix-quality run is a separate `<trk>`
+/// coloured with the Garmin track extension.
+pub fn write_gpx(name: &str, points: &[TrackPoint], out: &mut dyn Write) -> Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<gpx version="1.1" creator="ubx-track" xmlns="http://www.topografix.com/GPX/1/1" xmlns:gpxx="http://www.garmin.com/xmlschemas/GpxExtensions/v3">"#
+    )?;
+    for (fix, run) in segments(points) {
+        writeln!(out, "  <trk>")?;
+        writeln!(out, "    <name>{} ({})</name>", xml_escape(name), fix.label())?;
+        writeln!(
+            out,
+            "    <extensions><gpxx:TrackExtension><gpxx:DisplayColor>{}</gpxx:DisplayColor></gpxx:TrackExtension></extensions>",
+            fix.garmin_color()
+        )?;
+        writeln!(out, "    <trkseg>")?;
+        for p in run {
+            writeln!(out, r#"      <trkpt lat="{:.9}" lon="{:.9}">"#, p.lat, p.lon)?;
+            writeln!(out, "        <ele>{:.4}</ele>", p.alt_msl)?;
+            if let Some(time) = &p.time {
+                writeln!(out, "        <time>{}</time>", time.to_iso8601())?;
+            }
+            writeln!(out, "        <fix>{}</fix>", p.fix.gpx_fix())?;
+            writeln!(out, "        <sat>{}</sat>", p.num_sv)?;
+            if let Some(pdop) = p.pdop {
+                writeln!(out, "        <pdop>{pdop:.2}</pdop>")?;
+            }
+            writeln!(out, "      </trkpt>")?;
+        }
+        writeln!(out, "    </trkseg>")?;
+        writeln!(out, "  </trk>")?;
+    }
+    writeln!(out, "</gpx>")?;
+    Ok(())
+}
+
+/// Write a KML 2.2 document with one styled line per fix-quality run.
+pub fn write_kml(name: &str, points: &[TrackPoint], out: &mut dyn Write) -> Result<()> {
+    let mut qualities: Vec<FixQuality> = points.iter().map(|p| p.fix).collect();
+    qualities.sort();
+    qualities.dedup();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(out, "<Document>")?;
+    writeln!(out, "  <name>{}</name>", xml_escape(name))?;
+    for fix in &qualities {
+        // KML colours are aabbggrr.
+        let rgb = fix.rgb();
+        let abgr = format!("ff{}{}{}", &rgb[4..6], &rgb[2..4], &rgb[0..2]);
+        writeln!(
+            out,
+            r#"  <Style id="{fix:?}"><LineStyle><color>{abgr}</color><width>3</width></LineStyle></Style>"#
+        )?;
+    }
+    for (fix, run) in segments(points) {
+        writeln!(out, "  <Placemark>")?;
+        writeln!(out, "    <name>{}</name>", fix.label())?;
+        if let (Some(begin), Some(end)) = (&run[0].time, &run[run.len() - 1].time) {
+            writeln!(
+                out,
+                "    <TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>",
+                begin.to_iso8601(),
+                end.to_iso8601()
+            )?;
+        }
+        writeln!(out, "    <styleUrl>#{fix:?}</styleUrl>")?;
+        writeln!(out, "    <LineString><altitudeMode>absolute</altitudeMode><coordinates>")?;
+        for p in run {
+            writeln!(out, "      {:.9},{:.9},{:.4}", p.lon, p.lat, p.alt_msl)?;
+        }
+        writeln!(out, "    </coordinates></LineString>")?;
+        writeln!(out, "  </Placemark>")?;
+    }
+    writeln!(out, "</Document>")?;
+    writeln!(out, "</kml>")?;
+    Ok(())
+}
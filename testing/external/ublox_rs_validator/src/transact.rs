@@ -0,0 +1,160 @@
+//! Send a frame to a receiver and wait for its acknowledgment, resending on
+//! timeout or ACK-NAK with exponential backoff.
+//!
+//! This is the request/acknowledge exchange every configuration tool needs.
+//! The port is anything readable and writable with a short read timeout,
+//! such as a `serialport` handle opened with a 100 ms timeout; reads that
+//! time out are retried until the deadline.
+
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::parse_ack;
+use crate::error::Result;
+use crate::frame::{Frame, FrameParser};
+
+/// How long to wait for each acknowledgment and how often to resend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Time to wait for a reply to each attempt.
+    pub timeout: Duration,
+    /// Resends after the first attempt.
+    pub retries: u32,
+    /// Pause before the first resend; doubled for each further one.
+    pub backoff: Duration,
+    /// Also resend after ACK-NAK, e.g. when the receiver was busy.
+    pub retry_nak: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(1),
+            retries: 2,
+            backoff: Duration::from_millis(250),
+            retry_nak: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Pause before resend number `retry` (1-based).
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(1 << (retry - 1).min(16))
+    }
+}
+
+/// Final result of a send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ack,
+    Nak,
+    /// Neither ACK-ACK nor ACK-NAK arrived for any attempt.
+    NoReply,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    pub outcome: Outcome,
+    /// Times the frame was sent.
+    pub attempts: u32,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn is_ack(&self) -> bool {
+        self.outcome == Outcome::Ack
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.outcome {
+            Outcome::Ack => "ACK-ACK",
+            Outcome::Nak => "ACK-NAK",
+            Outcome::NoReply => "no acknowledgment",
+        };
+        let plural = if self.attempts == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{outcome} after {} attempt{plural} ({:.2} s)",
+            self.attempts,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Read frames until `matches` picks one or `timeout` elapses. Frames that
+/// don't match are discarded; end of input counts as a timeout.
+pub fn wait_for<T, P: Read + ?Sized>(
+    port: &mut P,
+    parser: &mut FrameParser,
+    timeout: Duration,
+    mut matches: impl FnMut(&Frame) -> Option<T>,
+) -> Result<Option<T>> {
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 1024];
+    loop {
+        while let Some(result) = parser.next_frame() {
+            if let Ok((_, frame)) = result {
+                if let Some(found) = matches(&frame) {
+                    return Ok(Some(found));
+                }
+            }
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        let n = match port.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        parser.push(&buf[..n]);
+    }
+}
+
+/// Send `frame` and wait for the ACK-ACK or ACK-NAK naming its class and ID,
+/// resending as `policy` allows. I/O errors end the exchange; an
+/// unacknowledged or rejected frame is reported, not an error.
+pub fn send_with_ack<P: Read + Write + ?Sized>(
+    port: &mut P,
+    parser: &mut FrameParser,
+    frame: &Frame,
+    policy: &RetryPolicy,
+) -> Result<Report> {
+    let started = Instant::now();
+    let bytes = frame.to_bytes();
+    let mut attempts = 0;
+    loop {
+        if attempts > 0 {
+            thread::sleep(policy.delay(attempts));
+        }
+        port.write_all(&bytes)?;
+        port.flush()?;
+        attempts += 1;
+        let ack = wait_for(port, parser, policy.timeout, |reply| {
+            parse_ack(reply).filter(|(c, i, _)| (*c, *i) == (frame.class, frame.id))
+        })?;
+        let outcome = match ack {
+            Some((_, _, true)) => Outcome::Ack,
+            Some((_, _, false)) => Outcome::Nak,
+            None => Outcome::NoReply,
+        };
+        let retry = match outcome {
+            Outcome::Ack => false,
+            Outcome::Nak => policy.retry_nak,
+            Outcome::NoReply => true,
+        };
+        if !retry || attempts > policy.retries {
+            return Ok(Report {
+                outcome,
+                attempts,
+                elapsed: started.elapsed(),
+            });
+        }
+    }
+}
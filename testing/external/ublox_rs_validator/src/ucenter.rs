@@ -0,0 +1,144 @@
+//! u-center log containers.
+//!
+//! u-center records whatever the receiver sends, so its `.ubx` logs
+//! interleave UBX frames with NMEA sentences, and with RTCM 3 messages when
+//! the receiver outputs them; the framer skips the foreign bytes.
+//!
+//! [`detect`] and [`scan`] tell a plain UBX log from an interleaved one.
+//! u-center has no per-frame host timestamps of its own; host receive
+//! times come from `ubx-capture --timestamps` (see [`crate::host_time`]).
+
+use crate::frame::parse_frame;
+use crate::ntrip::{crc24q, nmea_checksum, RTCM_PREAMBLE};
+
+/// NMEA 0183 limits sentences to 82 characters; allow for longer
+/// proprietary ones.
+const MAX_NMEA_LEN: usize = 256;
+
+/// What a log holds besides UBX.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// UBX frames only.
+    Ubx,
+    /// UBX interleaved with NMEA or RTCM, as u-center records it.
+    Interleaved,
+}
+
+impl Container {
+    pub fn name(self) -> &'static str {
+        match self {
+            Container::Ubx => "ubx",
+            Container::Interleaved => "interleaved",
+        }
+    }
+}
+
+/// Records found in a log, by protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContainerStats {
+    pub ubx_frames: usize,
+    pub nmea_sentences: usize,
+    pub rtcm_messages: usize,
+    /// Bytes that are none of the above.
+    pub other_bytes: usize,
+}
+
+impl ContainerStats {
+    pub fn container(&self) -> Container {
+        if self.nmea_sentences + self.rtcm_messages > 0 {
+            Container::Interleaved
+        } else {
+            Container::Ubx
+        }
+    }
+}
+
+/// The kind of log; scans all of it, as a u-center log may start with UBX
+/// only and switch on NMEA later.
+pub fn detect(data: &[u8]) -> Container {
+    scan(data).container()
+}
+
+/// Count the records of a whole log.
+pub fn scan(data: &[u8]) -> ContainerStats {
+    let mut stats = ContainerStats::default();
+    for (record, range) in Records::new(data) {
+        match record {
+            Record::Ubx => stats.ubx_frames += 1,
+            Record::Nmea => stats.nmea_sentences += 1,
+            Record::Rtcm => stats.rtcm_messages += 1,
+            Record::Other => stats.other_bytes += range.len(),
+        }
+    }
+    stats
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record {
+    Ubx,
+    Nmea,
+    Rtcm,
+    Other,
+}
+
+/// Splits a log into records, coalescing runs of unrecognised bytes.
+struct Records<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Records<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// The record starting at `pos`, and its length.
+    fn record_at(&self, pos: usize) -> Option<(Record, usize)> {
+        let rest = &self.data[pos..];
+        match rest.first()? {
+            0xB5 => parse_frame(rest).ok().map(|frame| (Record::Ubx, frame.raw.len())),
+            b'$' => nmea_len(rest).map(|len| (Record::Nmea, len)),
+            &RTCM_PREAMBLE => rtcm_len(rest).map(|len| (Record::Rtcm, len)),
+            _ => None,
+        }
+    }
+}
+
+impl Iterator for Records<'_> {
+    type Item = (Record, std::ops::Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        if start >= self.data.len() {
+            return None;
+        }
+        if let Some((record, len)) = self.record_at(start) {
+            self.pos += len;
+            return Some((record, start..self.pos));
+        }
+        self.pos += 1;
+        while self.pos < self.data.len() && self.record_at(self.pos).is_none() {
+            self.pos += 1;
+        }
+        Some((Record::Other, start..self.pos))
+    }
+}
+
+/// Length of a `$...*hh` sentence with a valid checksum, including its line end.
+fn nmea_len(data: &[u8]) -> Option<usize> {
+    let end = data.iter().take(MAX_NMEA_LEN).position(|&b| b == b'\n')?;
+    let line = data[..end].strip_suffix(b"\r").unwrap_or(&data[..end]);
+    let star = line.len().checked_sub(3).filter(|&i| line[i] == b'*')?;
+    let body = std::str::from_utf8(&line[1..star]).ok()?;
+    let sum = u8::from_str_radix(std::str::from_utf8(&line[star + 1..]).ok()?, 16).ok()?;
+    (nmea_checksum(body) == sum).then_some(end + 1)
+}
+
+/// Length of an RTCM 3 message whose CRC matches.
+fn rtcm_len(data: &[u8]) -> Option<usize> {
+    let header = data.get(..3)?;
+    let len = usize::from(u16::from_be_bytes([header[1] & 0x03, header[2]]));
+    let frame = data.get(..len + 6)?;
+    let crc = u32::from_be_bytes([0, frame[len + 3], frame[len + 4], frame[len + 5]]);
+    (header[1] & 0xFC == 0 && crc24q(&frame[..len + 3]) == crc).then_some(len + 6)
+}
@@ -0,0 +1,199 @@
+//! Decoded field values.
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// A single decoded value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+    Array(Vec<Value>),
+    /// Named members: an expanded bitfield or one instance of a repeated group.
+    Record(Fields),
+}
+
+impl Value {
+    /// Numeric view of the value, if it has one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Unsigned(v) => Some(v as f64),
+            Value::Signed(v) => Some(v as f64),
+            Value::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Integer view of the value, if it is integral.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Unsigned(v) => i64::try_from(v).ok(),
+            Value::Signed(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Unsigned(v) => Some(v),
+            Value::Signed(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_record(&self) -> Option<&Fields> {
+        match self {
+            Value::Record(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Unsigned(v) => write!(f, "{v}"),
+            Value::Signed(v) => write!(f, "{v}"),
+            Value::Float(v) => write!(f, "{v}"),
+            Value::Text(s) => f.write_str(s),
+            Value::Array(_) | Value::Record(_) => {
+                f.write_str(&serde_json::to_string(self).map_err(|_| std::fmt::Error)?)
+            }
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Unsigned(v) => serializer.serialize_u64(*v),
+            Value::Signed(v) => serializer.serialize_i64(*v),
+            Value::Float(v) => serializer.serialize_f64(*v),
+            Value::Text(s) => serializer.serialize_str(s),
+            Value::Array(items) => items.serialize(serializer),
+            Value::Record(fields) => fields.serialize(serializer),
+        }
+    }
+}
+
+/// Ordered list of named values, in payload order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, name: impl Into<String>, value: Value) {
+        self.0.push((name.into(), value));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Look up a nested path as produced by [`Fields::flatten`], e.g.
+    /// `flags.gnssFixOK` or `satellites[3].cno`.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.');
+        let mut current = index_path(self.get_indexed(parts.next()?)?)?;
+        for part in parts {
+            current = index_path(current.as_record()?.get_indexed(part)?)?;
+        }
+        Some(current)
+    }
+
+    /// `name` or `name[i][j]`, returning the named value and the index suffix.
+    fn get_indexed<'a, 'p>(&'a self, part: &'p str) -> Option<(&'a Value, &'p str)> {
+        let (name, rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        Some((self.get(name)?, rest))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Flatten nested records and arrays into `(path, scalar)` pairs, e.g.
+    /// `flags.gnssFixOK` or `satellites[3].cno`.
+    pub fn flatten(&self) -> Vec<(String, Value)> {
+        let mut out = Vec::new();
+        for (name, value) in &self.0 {
+            flatten_into(name, value, &mut out);
+        }
+        out
+    }
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Record(fields) => {
+            for (name, v) in &fields.0 {
+                flatten_into(&format!("{prefix}.{name}"), v, out);
+            }
+        }
+        Value::Array(items) if items.iter().any(|v| matches!(v, Value::Record(_))) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten_into(&format!("{prefix}[{i}]"), v, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn index_path<'a>((mut value, mut rest): (&'a Value, &str)) -> Option<&'a Value> {
+    while let Some(inner) = rest.strip_prefix('[') {
+        let (index, tail) = inner.split_once(']')?;
+        value = value.as_array()?.get(index.parse::<usize>().ok()?)?;
+        rest = tail;
+    }
+    rest.is_empty().then_some(value)
+}
+
+impl FromIterator<(String, Value)> for Fields {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Fields {
+    type Item = (String, Value);
+    type IntoIter = std::vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Serialize for Fields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,115 @@
+//! Position anonymization and the geodetic conversions behind it.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::anonymize::{Anonymizer, Offset};
+use ubx_schema::geo::{ecef_to_llh, llh_to_ecef, Llh};
+use ubx_schema::{Decoder, Encoder, Schema};
+
+fn encode(encoder: &Encoder, name: &str, fields: Json) -> Vec<u8> {
+    encoder.encode_json(&json!({"name": name, "fields": fields})).unwrap().to_bytes()
+}
+
+fn decoded(schema: &Schema, log: &[u8]) -> Vec<ubx_schema::DecodedMessage> {
+    let decoder = Decoder::new(schema);
+    ubx_schema::frame::frames(log).flatten().map(|f| decoder.decode_frame(&f)).collect()
+}
+
+fn int(msg: &ubx_schema::DecodedMessage, name: &str) -> i64 {
+    msg.get(name).and_then(|v| v.as_i64()).unwrap()
+}
+
+#[test]
+fn shifts_positions_consistently_and_drops_nmea() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let home = Llh { lat: 52.123_456_7, lon: 4.987_654_3, height: 45.0 };
+    let ecef = llh_to_ecef(home);
+    let back = ecef_to_llh(ecef);
+    assert!((back.lat - home.lat).abs() < 1e-10 && (back.lon - home.lon).abs() < 1e-10);
+    assert!((back.height - home.height).abs() < 1e-6);
+
+    let mut log = encode(&encoder, "NAV-PVT", json!({"iTOW": 0}));
+    let nmea = b"$GNGGA,120000.00,5207.40740,N,00459.25926,E,1,12,0.8,45.0,M,47.0,M,,*5C\r\n";
+    log.extend(nmea);
+    log.extend(encode(
+        &encoder,
+        "NAV-PVT",
+        json!({"iTOW": 1_000, "lat": 521_234_567, "lon": 49_876_543, "height": 45_000, "hMSL": -2_000}),
+    ));
+    log.extend(encode(
+        &encoder,
+        "NAV-HPPOSLLH",
+        json!({"iTOW": 1_000, "lat": 521_234_567, "latHp": 9, "lon": 49_876_543, "lonHp": -3,
+               "height": 45_000, "heightHp": 7, "hMSL": -2_000}),
+    ));
+    log.extend(encode(
+        &encoder,
+        "NAV-POSECEF",
+        json!({"iTOW": 1_000, "ecefX": (ecef.x * 100.0).round(), "ecefY": (ecef.y * 100.0).round(),
+               "ecefZ": (ecef.z * 100.0).round()}),
+    ));
+    log.extend(encode(&encoder, "NAV-RELPOSNED", json!({"iTOW": 1_000, "relPosN": 120, "relPosE": -40})));
+
+    let offset = Offset { lat: -10.5, lon: 100.25, height: 3.0 };
+    let (out, stats) = Anonymizer::new(&schema, offset).anonymize(&log);
+    assert_eq!(stats.frames, 5);
+    assert_eq!(stats.dropped_bytes, nmea.len());
+    assert_eq!(stats.rewritten.keys().collect::<Vec<_>>(), ["NAV-HPPOSLLH", "NAV-POSECEF", "NAV-PVT"]);
+    assert_eq!(out.len(), log.len() - nmea.len());
+
+    let msgs = decoded(&schema, &out);
+    assert_eq!(int(&msgs[0], "lat"), 0, "positions without a fix stay at zero");
+    let pvt = &msgs[1];
+    assert_eq!(int(pvt, "lat"), 416_234_567);
+    assert_eq!(int(pvt, "lon"), 1_052_376_543);
+    assert_eq!((int(pvt, "height"), int(pvt, "hMSL")), (48_000, 1_000));
+    let hp = &msgs[2];
+    assert_eq!((int(hp, "lat"), int(hp, "latHp")), (416_234_567, 9));
+    assert_eq!((int(hp, "lon"), int(hp, "lonHp")), (1_052_376_542, 97));
+    assert_eq!((int(hp, "height"), int(hp, "heightHp")), (48_000, 7));
+    let moved = ecef_to_llh(ubx_schema::geo::Ecef {
+        x: int(&msgs[3], "ecefX") as f64 / 100.0,
+        y: int(&msgs[3], "ecefY") as f64 / 100.0,
+        z: int(&msgs[3], "ecefZ") as f64 / 100.0,
+    });
+    assert!((moved.lat - (home.lat + offset.lat)).abs() < 1e-6);
+    assert!((moved.lon - (home.lon + offset.lon)).abs() < 1e-6);
+    assert!((moved.height - 48.0).abs() < 0.02);
+    assert_eq!(int(&msgs[4], "relPosN"), 120, "a constant offset leaves the baseline alone");
+}
+
+#[test]
+fn jitter_is_per_epoch_and_reproducible() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let fix = |itow: u32| json!({"iTOW": itow, "lat": 100_000_000, "lon": 200_000_000, "height": 10_000});
+    let mut log = Vec::new();
+    for itow in [1_000, 2_000] {
+        log.extend(encode(&encoder, "NAV-PVT", fix(itow)));
+        log.extend(encode(&encoder, "NAV-POSLLH", fix(itow)));
+        log.extend(encode(
+            &encoder,
+            "NAV-RELPOSNED",
+            json!({"iTOW": itow, "relPosN": 300, "relPosE": 400, "relPosLength": 500}),
+        ));
+    }
+
+    let anonymizer = Anonymizer::new(&schema, Offset::default()).jitter(5.0, 42);
+    let (out, _) = anonymizer.anonymize(&log);
+    assert_eq!(out, anonymizer.anonymize(&log).0);
+    assert_ne!(out, Anonymizer::new(&schema, Offset::default()).jitter(5.0, 43).anonymize(&log).0);
+
+    let msgs = decoded(&schema, &out);
+    for epoch in msgs.chunks(3) {
+        let (pvt, posllh, rel) = (&epoch[0], &epoch[1], &epoch[2]);
+        assert_eq!(int(pvt, "lat"), int(posllh, "lat"));
+        assert_eq!(int(pvt, "lon"), int(posllh, "lon"));
+        assert_eq!(int(pvt, "height"), int(posllh, "height"));
+        assert!((int(pvt, "lat") - 100_000_000).abs() <= 450);
+        assert!((int(pvt, "height") - 10_000).abs() <= 5_000);
+        let [n, e, d] = ["relPosN", "relPosE", "relPosD"].map(|f| int(rel, f) as f64);
+        assert!((int(rel, "relPosLength") as f64 - (n * n + e * e + d * d).sqrt()).abs() <= 1.0);
+        assert!((n - 300.0).abs() <= 500.0 && n != 300.0);
+    }
+    assert_ne!(int(&msgs[0], "lat"), int(&msgs[3], "lat"));
+}
@@ -0,0 +1,85 @@
+//! Arena decoding gives the messages the allocating decoder does.
+
+mod common;
+
+use common::{payloads, Generator, Source};
+use serde_json::Value as Json;
+use ubx_schema::arena::Arena;
+use ubx_schema::schema::BaseType;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+/// Values that exercise signs, fractions, text and two-element groups.
+struct Mixed(u64);
+
+impl Source for Mixed {
+    fn scalar(&mut self, base: BaseType) -> Json {
+        self.0 += 7;
+        match base {
+            BaseType::I1 | BaseType::I2 | BaseType::I4 | BaseType::I8 => Json::from(-((self.0 % 90) as i64)),
+            BaseType::R4 | BaseType::R8 => Json::from(self.0 as f64 / 4.0),
+            BaseType::CH => Json::from("Z"),
+            _ => Json::from(self.0 % 250),
+        }
+    }
+
+    fn count(&mut self) -> u64 {
+        2
+    }
+
+    fn text(&mut self, n: u64) -> String {
+        "ABC".chars().cycle().take(n as usize).collect()
+    }
+}
+
+#[test]
+fn matches_the_allocating_decoder_for_every_message_and_option() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let mut frames = Vec::new();
+    for msg in &schema.messages {
+        for (variant, def, skip) in payloads(msg) {
+            let fields = Generator { source: &mut Mixed(0), room: 1 }.payload(def, skip);
+            frames.push(encoder.encode(&msg.name, variant, &fields).unwrap());
+        }
+    }
+    assert!(frames.len() > 100);
+
+    // One arena for everything, as in a log-processing loop.
+    let mut arena = Arena::new();
+    for bits in 0..8 {
+        let options = DecodeOptions {
+            scale: bits & 1 != 0,
+            expand_flags: bits & 2 != 0,
+            include_reserved: bits & 4 != 0,
+        };
+        let decoder = Decoder::with_options(&schema, options);
+        for frame in &frames {
+            let expected = decoder.decode(frame.class, frame.id, &frame.payload);
+            let msg = decoder.decode_in(&mut arena, frame.class, frame.id, &frame.payload);
+            assert_eq!(msg.to_message(), expected, "{} {options:?}", expected.name);
+            assert_eq!(serde_json::to_value(msg).unwrap(), serde_json::to_value(&expected).unwrap());
+        }
+    }
+}
+
+#[test]
+fn unknown_and_short_payloads() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let mut arena = Arena::new();
+
+    let msg = decoder.decode_in(&mut arena, 0x7F, 0x01, &[0xDE, 0xAD]);
+    assert_eq!((msg.name, msg.parsed, msg.payload_raw), ("UBX-7F-01", false, Some("dead")));
+    assert_eq!(msg.to_message(), decoder.decode(0x7F, 0x01, &[0xDE, 0xAD]));
+
+    // NAV-SAT header claiming more satellites than the payload holds.
+    let mut payload = vec![0xE8, 0x03, 0, 0, 1, 5, 0, 0];
+    payload.extend([0, 12, 40, 30, 90, 0, 0, 0, 0, 0, 0, 0]);
+    let msg = decoder.decode_in(&mut arena, 0x01, 0x35, &payload);
+    assert_eq!(msg.itow(), Some(1000));
+    let satellites = msg.fields.iter().find_map(|(_, v)| v.as_array()).unwrap();
+    assert_eq!(satellites.len(), 1);
+    let sat = satellites.get(0).unwrap().as_record().unwrap();
+    assert_eq!((sat.get("svId").unwrap().as_u64(), sat.get("cno").unwrap().as_u64()), (Some(12), Some(40)));
+    assert_eq!(msg.to_message(), decoder.decode(0x01, 0x35, &payload));
+}
@@ -0,0 +1,92 @@
+//! Arrow record batches and the IPC stream writer.
+
+use serde_json::json;
+use ubx_schema::arrow::{decode_record_batch, RecordBatchBuilder, StreamWriter};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn rawx(tow: f64, cnos: &[u64]) -> serde_json::Value {
+    let meas: Vec<_> = cnos
+        .iter()
+        .enumerate()
+        .map(|(i, &cno)| json!({"prMes": 2.1e7 + i as f64, "gnssId": 0, "svId": i + 1, "cno": cno}))
+        .collect();
+    json!({"name": "RXM-RAWX", "fields": {"rcvTow": tow, "week": 2297, "version": 1, "repeated_group": meas}})
+}
+
+#[test]
+fn batches_columns_and_group_rows() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::with_options(&schema, DecodeOptions::default());
+    let mut log = Vec::new();
+    let pvt = json!({"name": "NAV-PVT", "fields": {"iTOW": 1_000, "lat": -337_000_000, "numSV": 12}});
+    for m in [rawx(1.0, &[40, 41]), pvt, rawx(2.0, &[]), rawx(3.0, &[45])] {
+        log.extend(encoder.encode_json(&m).unwrap().to_bytes());
+    }
+
+    let batch = decode_record_batch(&decoder, &log, 2, "RXM-RAWX", Some("repeated_group")).unwrap();
+    assert_eq!(batch.num_rows(), 3);
+    let offset = batch.column("offset").unwrap();
+    let (first, third) = (offset.i64_at(0).unwrap(), offset.i64_at(2).unwrap());
+    assert_eq!((first, offset.i64_at(1)), (0, Some(0)));
+    assert!(third > first);
+    let idx = batch.column("idx").unwrap();
+    assert_eq!((0..3).map(|i| idx.i64_at(i).unwrap()).collect::<Vec<_>>(), [0, 1, 0]);
+    let cno = batch.column("cno").unwrap();
+    assert_eq!((cno.i64_at(0), cno.i64_at(2), cno.null_count()), (Some(40), Some(45), 0));
+    assert_eq!(batch.column("prMes").unwrap().f64_at(1), Some(2.1e7 + 1.0));
+
+    let mut builder = RecordBatchBuilder::new(&decoder, "NAV-PVT", None).unwrap();
+    for frame in ubx_schema::frame::frames(&log).flatten() {
+        builder.push(frame.offset, &decoder.decode_frame(&frame));
+    }
+    let pvt = builder.finish();
+    assert!(builder.is_empty());
+    assert_eq!(pvt.num_rows(), 1);
+    let lat = pvt.column("lat").unwrap();
+    assert_eq!(lat.values(), (-337_000_000i32).to_le_bytes());
+    assert_eq!((lat.validity(), pvt.column("numSV").unwrap().i64_at(0)), (&[1u8][..], Some(12)));
+    assert!(RecordBatchBuilder::new(&decoder, "NAV-PVT", Some("repeated_group")).is_err());
+    assert!(RecordBatchBuilder::new(&decoder, "MGA-GPS", None).is_err());
+    assert!(RecordBatchBuilder::new(&decoder, "MGA-GPS-EPH", None).is_ok());
+}
+
+#[test]
+fn ipc_stream_framing() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::with_options(&schema, DecodeOptions::default());
+    let mut builder = RecordBatchBuilder::new(&decoder, "RXM-RAWX", None).unwrap();
+    let frame = encoder.encode_json(&rawx(1.5, &[40])).unwrap();
+    assert!(builder.push(0, &decoder.decode(frame.class, frame.id, &frame.payload)));
+
+    let mut writer = StreamWriter::new(Vec::new(), builder.fields()).unwrap();
+    writer.write(&builder.finish()).unwrap();
+    let out = writer.finish().unwrap();
+
+    // Schema message, one record batch, then the end-of-stream marker.
+    let mut pos = 0;
+    let mut bodies = Vec::new();
+    while pos < out.len() {
+        assert_eq!(out[pos..pos + 4], [0xFF; 4]);
+        let len = u32::from_le_bytes(out[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        pos += 8;
+        if len == 0 {
+            break;
+        }
+        assert_eq!(len % 8, 0);
+        let meta = &out[pos..pos + len];
+        let root = u32::from_le_bytes(meta[..4].try_into().unwrap()) as usize;
+        let vtable = root - i32::from_le_bytes(meta[root..root + 4].try_into().unwrap()) as usize;
+        let slot = |i: usize| u16::from_le_bytes(meta[vtable + 4 + 2 * i..][..2].try_into().unwrap()) as usize;
+        assert_eq!(i16::from_le_bytes(meta[root + slot(0)..][..2].try_into().unwrap()), 4);
+        let body = i64::from_le_bytes(meta[root + slot(3)..][..8].try_into().unwrap()) as usize;
+        assert_eq!(body % 8, 0);
+        bodies.push((meta[root + slot(1)], body));
+        pos += len + body;
+    }
+    assert_eq!(pos, out.len());
+    assert_eq!(bodies.len(), 2);
+    assert_eq!((bodies[0], bodies[1].0), ((1, 0), 3));
+    assert!(bodies[1].1 > 0);
+}
@@ -0,0 +1,40 @@
+//! Parallel decoding returns what a serial pass does, in order.
+
+use serde_json::json;
+use ubx_schema::batch::{decode_buffer, decode_frames, CHUNK_FRAMES};
+use ubx_schema::frame::frames;
+use ubx_schema::{Decoder, Encoder, Schema};
+
+#[test]
+fn parallel_decoding_preserves_order_and_stats() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let mut log = Vec::new();
+    for i in 0..(CHUNK_FRAMES as u32 * 5 + 17) {
+        let msg = match i % 3 {
+            0 => json!({"name": "NAV-PVT", "fields": {"iTOW": i, "numSV": i % 30}}),
+            1 => json!({"name": "NAV-SAT", "fields": {"iTOW": i, "satellites": [{"svId": i % 32}]}}),
+            _ => json!({"name": "NAV-TIMEGPS", "fields": {"iTOW": i}}),
+        };
+        let mut frame = encoder.encode_json(&msg).unwrap().to_bytes();
+        if i == 100 {
+            *frame.last_mut().unwrap() ^= 0xFF;
+        }
+        log.extend(frame);
+        log.extend(b"\r\n");
+    }
+
+    let decoder = Decoder::new(&schema);
+    let mut iter = frames(&log);
+    let serial: Vec<_> = iter.by_ref().flatten().map(|f| (f.offset, decoder.decode_frame(&f))).collect();
+    for threads in [0, 1, 4] {
+        let (parallel, stats) = decode_buffer(&decoder, &log, threads);
+        assert_eq!(stats, iter.stats());
+        assert_eq!(parallel.len(), serial.len());
+        for ((frame, msg), (offset, expected)) in parallel.iter().zip(&serial) {
+            assert_eq!((frame.offset, msg), (*offset, expected));
+        }
+    }
+    assert_eq!(serial[1000].1.itow(), Some(1001));
+    assert!(decode_frames(&decoder, &[], 8).is_empty());
+}
@@ -0,0 +1,46 @@
+//! Subscription filters and fan-out for ubx-ws.
+
+use serde_json::Value as Json;
+use ubx_schema::broadcast::{parse_query, Broadcaster};
+use ubx_schema::{Decoder, Schema};
+
+#[test]
+fn delivers_to_matching_subscribers() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let pvt = decoder.decode(0x01, 0x07, &[0; 92]);
+    let sat = decoder.decode(0x01, 0x35, &[0; 8]);
+
+    let mut hub = Broadcaster::new();
+    let (_, all) = hub.subscribe(parse_query(""), 8);
+    let (_, pvt_only) = hub.subscribe(parse_query("messages=nav-pvt&foo=bar"), 8);
+    let (_, no_pvt) = hub.subscribe(parse_query("exclude=NAV-PVT"), 8);
+
+    assert_eq!(hub.publish(&pvt), 2);
+    assert_eq!(hub.publish(&sat), 2);
+
+    let names = |rx: &std::sync::mpsc::Receiver<std::sync::Arc<str>>| -> Vec<String> {
+        rx.try_iter()
+            .map(|text| serde_json::from_str::<Json>(&text).unwrap()["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(names(&all), ["UBX-NAV-PVT", "UBX-NAV-SAT"]);
+    assert_eq!(names(&pvt_only), ["UBX-NAV-PVT"]);
+    assert_eq!(names(&no_pvt), ["UBX-NAV-SAT"]);
+}
+
+#[test]
+fn drops_for_slow_subscribers_and_forgets_closed_ones() {
+    let schema = Schema::load_default().unwrap();
+    let pvt = Decoder::new(&schema).decode(0x01, 0x07, &[0; 92]);
+
+    let mut hub = Broadcaster::new();
+    let (slow, _slow_rx) = hub.subscribe(parse_query(""), 1);
+    let (_, closed) = hub.subscribe(parse_query(""), 1);
+    drop(closed);
+
+    assert_eq!(hub.publish(&pvt), 1);
+    assert_eq!(hub.len(), 1);
+    assert_eq!(hub.publish(&pvt), 0);
+    assert_eq!(hub.dropped(slow), Some(1));
+}
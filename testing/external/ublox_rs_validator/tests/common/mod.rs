@@ -0,0 +1,157 @@
+//! Payload generation shared by the property and snapshot tests: field
+//! values for any payload definition, in the encoder's JSON shape, drawn
+//! from a [`Source`].
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value as Json};
+use ubx_schema::schema::{fields_extent, BaseOffset, BaseType, Count, DataType, Discriminator, FieldDef, PayloadDef};
+use ubx_schema::MessageDef;
+
+/// Where generated values come from.
+pub trait Source {
+    /// A value of `base`; bitfields too are given as raw integers.
+    fn scalar(&mut self, base: BaseType) -> Json;
+    /// Element count of a variable-length array or group.
+    fn count(&mut self) -> u64;
+    /// `n` printable characters, for a `CH` array.
+    fn text(&mut self, n: u64) -> String;
+}
+
+/// Values for one payload definition.
+pub struct Generator<'a, S: Source> {
+    pub source: &'a mut S,
+    /// Arrays sized by the rest of the payload that may still be filled in.
+    /// Only one can be, or the decoder could not tell where it ends.
+    pub room: usize,
+}
+
+impl<S: Source> Generator<'_, S> {
+    fn length(&mut self, count: &Count, counts: &mut HashMap<String, u64>) -> Option<u64> {
+        match count {
+            Count::Fixed(n) => Some(*n as u64),
+            Count::Field { name, multiplier } => {
+                let n = *counts.entry(name.clone()).or_insert_with(|| self.source.count());
+                Some(n * *multiplier as u64)
+            }
+            Count::Remaining if self.room > 0 => {
+                self.room -= 1;
+                Some(self.source.count())
+            }
+            Count::Remaining => None,
+        }
+    }
+
+    fn array(&mut self, base: BaseType, n: u64) -> Json {
+        if base == BaseType::CH {
+            // Decoded text drops trailing NULs, so generate none.
+            return Json::from(self.source.text(n));
+        }
+        Json::Array((0..n).map(|_| self.source.scalar(base)).collect())
+    }
+
+    /// Values for `defs`, leaving out fields the encoder fills in itself and
+    /// fields that overlap one already generated (two views of the same
+    /// bytes, such as CFG-TXSLOT's `end0` and `timeSlots`). Returns the byte
+    /// ranges that were filled in.
+    fn fields(&mut self, defs: &[FieldDef], skip_offset: Option<usize>, values: &mut Map<String, Json>) -> Vec<(usize, usize)> {
+        let mut counts = HashMap::new();
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        for field in defs {
+            let Some(offset) = field.byte_offset else {
+                continue;
+            };
+            if Some(offset) == skip_offset || field.fixed_value.is_some() {
+                continue;
+            }
+            let (value, size) = match &field.data_type {
+                DataType::Scalar(base) => (self.source.scalar(*base), base.size()),
+                DataType::Array { base, count } => match self.length(count, &mut counts) {
+                    Some(n) => (self.array(*base, n), n as usize * base.size()),
+                    None => continue,
+                },
+                DataType::Group {
+                    fields, count, element_size,
+                } => match self.length(count, &mut counts) {
+                    Some(n) => (Json::Array((0..n).map(|_| self.record(fields)).collect()), n as usize * element_size),
+                    None => continue,
+                },
+                DataType::Unknown(_) => continue,
+            };
+            let range = (offset, offset + size);
+            if taken.iter().any(|&(start, end)| range.0 < end && start < range.1) {
+                continue;
+            }
+            taken.push(range);
+            values.insert(field.name.clone(), value);
+        }
+        set_counts(defs, &counts, values);
+        taken
+    }
+
+    fn record(&mut self, defs: &[FieldDef]) -> Json {
+        let mut values = Map::new();
+        self.fields(defs, None, &mut values);
+        Json::Object(values)
+    }
+
+    pub fn payload(&mut self, def: &PayloadDef, skip_offset: Option<usize>) -> Map<String, Json> {
+        let mut values = Map::new();
+        let taken = self.fields(&def.fields, skip_offset, &mut values);
+        let mut counts = HashMap::new();
+        for group in &def.repeated_groups {
+            let Some(n) = self.length(&group.count, &mut counts) else {
+                continue;
+            };
+            if let BaseOffset::Fixed(base) = group.base_offset {
+                let size = if group.group_size > 0 { group.group_size } else { fields_extent(&group.fields) };
+                let end = base + n as usize * size;
+                if taken.iter().any(|&(s, e)| base < e && s < end) {
+                    continue;
+                }
+            }
+            let items = (0..n).map(|_| self.record(&group.fields)).collect();
+            values.insert(group.name.clone(), Json::Array(items));
+        }
+        set_counts(&def.fields, &counts, &mut values);
+        values
+    }
+}
+
+/// Store the element counts chosen for arrays and groups in the fields that
+/// hold them, including bitfield members such as `flags.numMeas`.
+fn set_counts(defs: &[FieldDef], counts: &HashMap<String, u64>, values: &mut Map<String, Json>) {
+    for (name, &n) in counts {
+        match name.split_once('.') {
+            None => {
+                values.insert(name.clone(), Json::from(n));
+            }
+            Some((field, member)) => {
+                let Some(bit) = defs.iter().find(|f| f.name == field).and_then(|f| f.bits.iter().find(|b| b.name == member)) else {
+                    continue;
+                };
+                let mask = ((1u64 << bit.bit_width) - 1) << bit.bit_offset;
+                let raw = values.get(field).and_then(Json::as_u64).unwrap_or(0);
+                values.insert(field.to_string(), Json::from((raw & !mask) | (n << bit.bit_offset)));
+            }
+        }
+    }
+}
+
+/// Payload definitions of `msg` with the variant name and the offset of the
+/// discriminating byte, which the encoder sets.
+pub fn payloads(msg: &MessageDef) -> Vec<(Option<&str>, &PayloadDef, Option<usize>)> {
+    if msg.variants.is_empty() {
+        return msg.payload.iter().map(|p| (None, p, None)).collect();
+    }
+    msg.variants
+        .iter()
+        .map(|v| {
+            let skip = match v.discriminator {
+                Discriminator::Field { byte_offset, .. } => Some(byte_offset),
+                _ => None,
+            };
+            (Some(v.name.as_str()), &v.payload, skip)
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+//! The schema covers every message in the official index.
+
+use serde_json::json;
+use ubx_schema::completeness::{check, IdMismatch, MessageIndex};
+use ubx_schema::Schema;
+
+#[test]
+fn schema_defines_every_documented_message() {
+    let schema = Schema::load_default().unwrap();
+    let index = MessageIndex::load_default().unwrap();
+    assert!(index.messages.len() >= 230);
+    let report = check(&schema, &index);
+    for generation in &report.generations {
+        assert!(generation.missing.is_empty(), "{}: {:?}", generation.name, generation.missing);
+    }
+    assert_eq!(report.id_mismatches, []);
+    assert!(report.is_complete());
+}
+
+#[test]
+fn reports_missing_messages_per_generation() {
+    let message = |name: &str, class: &str, id: &str| {
+        json!({"name": name, "class_id": class, "message_id": id, "message_type": "output", "payload": {"fields": []}})
+    };
+    let schema = Schema::from_json(&json!({
+        "schema_version": "test",
+        "messages": [
+            message("UBX-ACK-ACK", "0x05", "0x01"),
+            message("UBX-CFG-DAT-GET", "0x06", "0x06"),
+            message("UBX-NAV-PVT", "0x01", "0x08"),
+            message("UBX-NAV-TIMEGPS", "0x01", "0x21"),
+        ]
+    }))
+    .unwrap();
+    let index = MessageIndex::from_json_str(
+        &json!({
+            "generations": [{"name": "M8", "manuals": ["m8"]}, {"name": "F9", "manuals": ["f9-hpg", "f9-tim"]}],
+            "messages": [
+                {"name": "UBX-ACK-ACK", "class_id": "0x05", "message_id": "0x01", "generations": ["M8", "F9"]},
+                {"name": "UBX-CFG-DAT", "class_id": "0x06", "message_id": "0x06", "generations": ["M8"]},
+                {"name": "UBX-NAV-PVT", "class_id": "0x01", "message_id": "0x07", "generations": ["M8", "F9"]},
+                {"name": "UBX-AID-ALM", "class_id": "0x0B", "message_id": "0x30", "generations": ["M8"]},
+                {"name": "UBX-NAV-RELPOSNED", "class_id": "0x01", "message_id": "0x3C", "generations": ["F9"]},
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let report = check(&schema, &index);
+    let summary: Vec<(&str, usize, usize, Vec<&str>)> = report
+        .generations
+        .iter()
+        .map(|g| (g.name.as_str(), g.manuals, g.documented, g.missing.iter().map(|e| e.name.as_str()).collect()))
+        .collect();
+    assert_eq!(summary, [("M8", 1, 4, vec!["UBX-AID-ALM"]), ("F9", 2, 3, vec!["UBX-NAV-RELPOSNED"])]);
+    assert_eq!(
+        report.id_mismatches,
+        [IdMismatch {
+            name: "UBX-NAV-PVT".into(),
+            documented: (0x01, 0x07),
+            schema: (0x01, 0x08),
+        }]
+    );
+    assert_eq!(report.undocumented, ["UBX-NAV-TIMEGPS"]);
+    assert!(!report.is_complete());
+}
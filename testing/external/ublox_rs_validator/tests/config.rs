@@ -0,0 +1,115 @@
+//! Configuration key database and CFG-VALSET generation.
+
+use serde_json::json;
+use ubx_schema::config::{collect_keys, parse_ack, valset_frames, ConfigKeys, KeyValue, Layers};
+use ubx_schema::{Error, Frame};
+
+fn keys() -> ConfigKeys {
+    ConfigKeys::load_default().expect("key database loads")
+}
+
+#[test]
+fn encodes_values_by_key_type() {
+    let db = keys();
+    let encode = |name: &str, value| db.key_value(name, &value, false).map(|kv| hex::encode(kv.value));
+
+    assert_eq!(encode("CFG-RATE-MEAS", json!(100)).unwrap(), "6400");
+    assert_eq!(encode("rate-meas", json!(100)).unwrap(), "6400");
+    assert_eq!(encode("CFG-NAVSPG-DYNMODEL", json!("automot")).unwrap(), "04");
+    assert_eq!(encode("CFG-UART1-BAUDRATE", json!(115_200)).unwrap(), "00c20100");
+    assert_eq!(encode("CFG-SBAS-USE_RANGING", json!(true)).unwrap(), "01");
+    assert_eq!(
+        encode("CFG-SBAS-PRNSCANMASK", json!({"PRN120": true, "PRN123": 1})).unwrap(),
+        "0900000000000000"
+    );
+
+    let scaled = db.key_value("CFG-RATE-MEAS", &json!(0.2), true).unwrap();
+    assert_eq!(scaled.value, vec![0xC8, 0x00]);
+
+    assert!(matches!(encode("CFG-RATE-MEAS", json!(70_000)), Err(Error::Config(_))));
+    assert!(matches!(encode("CFG-NAVSPG-DYNMODEL", json!("SUBMARINE")), Err(Error::Config(_))));
+    assert!(matches!(encode("CFG-NO-SUCH_KEY", json!(1)), Err(Error::Config(_))));
+}
+
+#[test]
+fn builds_single_valset() {
+    let db = keys();
+    let doc = json!({"CFG-RATE": {"MEAS": 100}, "CFG-MSGOUT-UBX_NAV_PVT_USB": 1});
+    let values = collect_keys(&db, doc.as_object().unwrap(), false).unwrap();
+    assert_eq!(values.len(), 2);
+
+    let layers = Layers::parse("ram,flash").unwrap();
+    assert_eq!(layers.bits(), 0b101);
+    let frames = valset_frames(&values, layers);
+    assert_eq!(frames.len(), 1);
+    assert_eq!((frames[0].class, frames[0].id), (0x06, 0x8A));
+    assert_eq!(hex::encode(&frames[0].payload), "000500000900912001010021306400");
+}
+
+#[test]
+fn splits_large_sets_into_transaction() {
+    let values: Vec<KeyValue> = (0..130)
+        .map(|i| KeyValue {
+            name: format!("K{i}"),
+            key_id: 0x2091_0000 + i,
+            value: vec![1],
+        })
+        .collect();
+    let frames = valset_frames(&values, Layers::RAM);
+    assert_eq!(frames.len(), 3);
+    let header: Vec<&[u8]> = frames.iter().map(|f| &f.payload[..4]).collect();
+    assert_eq!(header, [&[1, 1, 1, 0][..], &[1, 1, 2, 0], &[1, 1, 3, 0]]);
+    assert_eq!(frames[0].payload.len(), 4 + 64 * 5);
+    assert_eq!(frames[2].payload.len(), 4 + 2 * 5);
+}
+
+#[test]
+fn parses_ack_frames() {
+    assert_eq!(parse_ack(&Frame::new(0x05, 0x01, vec![0x06, 0x8A])), Some((0x06, 0x8A, true)));
+    assert_eq!(parse_ack(&Frame::new(0x05, 0x00, vec![0x06, 0x8A])), Some((0x06, 0x8A, false)));
+    assert_eq!(parse_ack(&Frame::new(0x01, 0x07, vec![0x06, 0x8A])), None);
+}
+
+#[test]
+fn decodes_valget_response() {
+    let db = keys();
+    let set = [
+        ("CFG-RATE-MEAS", json!(100)),
+        ("CFG-NAVSPG-DYNMODEL", json!("AUTOMOT")),
+        ("CFG-GEOFENCE-FENCE1_LAT", json!(-335_000_000)),
+        ("CFG-SBAS-USE_RANGING", json!(false)),
+        ("CFG-SBAS-PRNSCANMASK", json!({"PRN120": true, "PRN123": true})),
+    ];
+    let mut payload = vec![0x01, 0x02, 0x05, 0x00];
+    for (name, value) in &set {
+        let kv = db.key_value(name, value, false).unwrap();
+        payload.extend_from_slice(&kv.key_id.to_le_bytes());
+        payload.extend_from_slice(&kv.value);
+    }
+    // A key the database doesn't know, one byte wide by its ID.
+    payload.extend_from_slice(&[0x01, 0x00, 0xFF, 0x20, 0x2A]);
+
+    let valget = db.decode_valget(&Frame::new(0x06, 0x8B, payload.clone()), false).unwrap();
+    assert_eq!((valget.layer_name(), valget.position, valget.values.len()), (Some("flash"), 5, 6));
+    let meas = valget.get("rate-meas").unwrap();
+    assert_eq!((meas.value.clone(), meas.unit.as_deref()), (json!(100), Some("s")));
+    assert_eq!(valget.get("CFG-NAVSPG-DYNMODEL").unwrap().value, json!("AUTOMOT"));
+    assert_eq!(valget.get("CFG-GEOFENCE-FENCE1_LAT").unwrap().value, json!(-335_000_000));
+    assert_eq!(valget.get("CFG-SBAS-USE_RANGING").unwrap().value, json!(false));
+    let mask = &valget.get("CFG-SBAS-PRNSCANMASK").unwrap().value;
+    assert_eq!((mask["PRN120"].clone(), mask["PRN121"].clone()), (json!(true), json!(false)));
+    assert_eq!(valget.values[5].to_json(), json!({"key_id": "0x20FF0001", "value": 42}));
+    // Decoded values encode back to the same bytes.
+    for value in &valget.values[..5] {
+        let name = value.name.as_deref().unwrap();
+        assert_eq!(db.key_value(name, &value.value, false).unwrap().value, value.raw, "{name}");
+    }
+
+    let scaled = db.decode_valget(&Frame::new(0x06, 0x8B, payload[..10].to_vec()), true).unwrap();
+    let meas = json!({"key": "CFG-RATE-MEAS", "key_id": "0x30210001", "type": "U2", "unit": "s", "value": 0.1});
+    assert_eq!(scaled.values[0].to_json(), meas);
+    assert!(db.decode_valget(&Frame::new(0x06, 0x8B, payload[..9].to_vec()), false).is_err());
+    let mut poll = payload.clone();
+    poll[0] = 0;
+    assert!(db.decode_valget(&Frame::new(0x06, 0x8B, poll), false).is_err());
+}
@@ -0,0 +1,61 @@
+//! Conformance results come out as JUnit XML and per-message counts.
+
+use std::path::Path;
+
+use ubx_schema::conformance::{differential_suites, junit_xml, Case, Suite, Summary};
+use ubx_schema::differential::{run, Backend, Sample, ScriptBackend, UbloxBackend};
+use ubx_schema::{Decoder, Frame, Schema};
+
+fn case(name: &str, message: &str, failures: &[&str]) -> Case {
+    Case {
+        name: name.into(),
+        message: message.into(),
+        failures: failures.iter().map(|f| f.to_string()).collect(),
+        skipped: None,
+    }
+}
+
+#[test]
+fn writes_junit_and_a_per_message_summary() {
+    let mut skipped = case("c.json", "UBX-ACK-ACK", &[]);
+    skipped.skipped = Some("no python".into());
+    let suites = [Suite {
+        name: "corpus".into(),
+        cases: vec![
+            case("a.json", "UBX-ACK-ACK", &[]),
+            case("b.json", "UBX-ACK-ACK", &["schema: fields.msgID: 0, expected \"<1>\" & more"]),
+            skipped,
+        ],
+    }];
+
+    let xml = junit_xml(&suites);
+    assert!(xml.contains("<testsuites name=\"ubx-conformance\" tests=\"3\" failures=\"1\" skipped=\"1\">"));
+    assert!(xml.contains("<testcase classname=\"UBX-ACK-ACK\" name=\"a.json\"/>"));
+    assert!(xml.contains("<failure message=\"schema: fields.msgID: 0, expected &quot;&lt;1&gt;&quot; &amp; more\">"));
+    assert!(xml.contains("<skipped message=\"no python\"/>"));
+
+    let summary = serde_json::to_value(Summary::new("1.5", &suites)).unwrap();
+    let counts = serde_json::json!({"tests": 3, "passed": 1, "failed": 1, "skipped": 1});
+    assert_eq!(summary["total"], counts);
+    assert_eq!(summary["messages"]["UBX-ACK-ACK"]["corpus"], counts);
+}
+
+#[test]
+fn a_differential_suite_per_backend() {
+    let schema = Schema::load_default().unwrap();
+    let samples = vec![Sample {
+        source: "ack".into(),
+        frame: Frame::new(0x05, 0x01, vec![0x06, 0x00]).to_bytes(),
+    }];
+    let mut backends: Vec<Box<dyn Backend>> = vec![
+        Box::new(UbloxBackend),
+        Box::new(ScriptBackend::pyubx2("/nonexistent/python", Path::new("/nonexistent.py"))),
+    ];
+    let report = run(&schema, &samples, &mut backends, 1e-6);
+    let suites = differential_suites(&Decoder::new(&schema), &samples, &report);
+
+    let names: Vec<_> = suites.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["differential.ublox-rs", "differential.pyubx2"]);
+    assert_eq!(suites[0].cases, [case("ack", "UBX-ACK-ACK", &[])]);
+    assert_eq!(suites[1].skipped(), 1);
+}
@@ -0,0 +1,47 @@
+//! The golden corpus passes, and disagreements are reported precisely.
+
+use serde_json::json;
+use ubx_schema::corpus::{check_vector, default_corpus_path, load_corpus, Vector, Verdict, UBLOX_RS};
+use ubx_schema::{Decoder, Frame, Schema};
+
+#[test]
+fn every_corpus_vector_passes() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let vectors = load_corpus(&default_corpus_path()).unwrap();
+    assert!(vectors.len() >= 9);
+    for (path, vector) in &vectors {
+        let failures = check_vector(&decoder, vector);
+        assert!(failures.is_empty(), "{}: {failures:?}", path.display());
+    }
+}
+
+#[test]
+fn reports_field_and_backend_disagreements() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let frame = Frame::new(0x05, 0x01, vec![0x06, 0x00]).to_bytes();
+    let mut vector = Vector::record(&decoder, &frame, "ACK-ACK for CFG-PRT").unwrap();
+    assert_eq!(vector.expected.fields, *json!({"clsID": 6, "msgID": 0}).as_object().unwrap());
+    assert_eq!(vector.external[UBLOX_RS], Verdict::Parsed);
+    assert!(check_vector(&decoder, &vector).is_empty());
+
+    vector.expected.fields.insert("msgID".into(), json!(1.0));
+    vector.expected.fields.insert("gone".into(), json!(3));
+    vector.expected.fields.remove("clsID");
+    vector.external.insert(UBLOX_RS.into(), Verdict::Unsupported);
+    let failures: Vec<String> = check_vector(&decoder, &vector).iter().map(|f| f.to_string()).collect();
+    assert_eq!(
+        failures,
+        [
+            "schema: fields.gone: missing, expected 3",
+            "schema: fields.msgID: 0, expected 1.0",
+            "schema: fields.clsID: unexpected field = 6",
+            "ublox-rs: parsed, expected unsupported",
+        ]
+    );
+
+    vector.expected.name = "UBX-ACK-NAK".into();
+    assert_eq!(check_vector(&decoder, &vector)[0].message, "decoded as UBX-ACK-ACK, expected UBX-ACK-NAK");
+
+}
@@ -0,0 +1,95 @@
+//! Coverage counts per protocol version and generation, and their rendering.
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+use ubx_schema::completeness::MessageIndex;
+use ubx_schema::coverage::{coverage, default_metadata_path, load_metadata, render_table, ManualInfo};
+use ubx_schema::Schema;
+
+#[test]
+fn default_schema_coverage_is_consistent() {
+    let schema = Schema::load_default().unwrap();
+    let index = MessageIndex::load_default().unwrap();
+    let manuals = load_metadata(default_metadata_path()).unwrap();
+    let report = coverage(&schema, &index, &manuals);
+    assert_eq!(report.total.messages, schema.messages.len());
+    assert!(report.protocol_versions.iter().any(|v| v.protocol_version == "27.31"));
+    for row in &report.protocol_versions {
+        assert!(row.counts.messages <= report.total.messages, "{row:?}");
+        assert!(!row.firmware.is_empty(), "{} has no manual", row.protocol_version);
+    }
+    for row in &report.generations {
+        assert!(row.counts.messages <= row.documented, "{row:?}");
+        assert!(row.counts.bitfields_defined <= row.counts.bitfields, "{row:?}");
+    }
+}
+
+#[test]
+fn counts_fields_and_renders_changes() {
+    let schema = Schema::from_json(&json!({
+        "schema_version": "test",
+        "messages": [
+            {
+                "name": "UBX-NAV-STATUS", "class_id": "0x01", "message_id": "0x03", "message_type": "output",
+                "supported_versions": {"protocol_versions": [1800, 2731], "source_manuals": ["m8"]},
+                "payload": {"length": {"fixed": 8}, "fields": [
+                    {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+                    {"name": "flags", "byte_offset": 4, "data_type": "X1",
+                     "bitfield": {"bits": [{"name": "gpsFixOk", "bit_start": 0, "bit_end": 0, "data_type": "U"}]}},
+                    {"name": "fixStat", "byte_offset": 5, "data_type": "X1"},
+                    {"name": "reserved0", "byte_offset": 6, "data_type": "U2", "reserved": true},
+                ]},
+            },
+            {
+                "name": "UBX-NAV-SAT", "class_id": "0x01", "message_id": "0x35", "message_type": "output",
+                "supported_versions": {"protocol_versions": [2731], "source_manuals": ["f9"]},
+                "payload": {"length": {"variable": {"base": 8}}, "fields": [
+                    {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+                    {"name": "numSvs", "byte_offset": 5, "data_type": "U1"},
+                ], "repeated_groups": [{"name": "svs", "count_field": "numSvs", "group_size": 2, "fields": [
+                    {"name": "gnssId", "byte_offset": 0, "data_type": "U1"},
+                    {"name": "svId", "byte_offset": 1, "data_type": "U1"},
+                ]}]},
+            },
+        ]
+    }))
+    .unwrap();
+    let index = MessageIndex::from_json_str(
+        &json!({
+            "generations": [{"name": "M8", "manuals": ["m8"]}, {"name": "F9", "manuals": ["f9"]}],
+            "messages": [
+                {"name": "UBX-NAV-STATUS", "class_id": "0x01", "message_id": "0x03", "generations": ["M8", "F9"]},
+                {"name": "UBX-NAV-SAT", "class_id": "0x01", "message_id": "0x35", "generations": ["F9"]},
+                {"name": "UBX-NAV-SIG", "class_id": "0x01", "message_id": "0x43", "generations": ["F9"]},
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let info = |fw: &str, version| ManualInfo {
+        firmware_version: fw.into(),
+        protocol_version: version,
+    };
+    let manuals = BTreeMap::from([("m8".to_string(), info("SPG 3.01", 1800)), ("f9".to_string(), info("HPG 1.32", 2731))]);
+
+    let report = coverage(&schema, &index, &manuals);
+    let versions: Vec<(&str, &str, usize, usize)> = report
+        .protocol_versions
+        .iter()
+        .map(|v| (v.protocol_version.as_str(), v.firmware[0].as_str(), v.counts.messages, v.counts.fields))
+        .collect();
+    assert_eq!(versions, [("18.00", "SPG 3.01", 1, 3), ("27.31", "HPG 1.32", 2, 7)]);
+    assert_eq!((report.total.bitfields, report.total.bitfields_defined), (2, 1));
+    let generations: Vec<(&str, usize, usize)> =
+        report.generations.iter().map(|g| (g.generation.as_str(), g.documented, g.counts.messages)).collect();
+    assert_eq!(generations, [("M8", 1, 1), ("F9", 3, 2)]);
+
+    let mut baseline = report.clone();
+    baseline.total.fields -= 2;
+    baseline.generations[1].counts.messages = 1;
+    let table = render_table(&report, Some(&baseline));
+    assert!(table.contains("| F9 | 3 | 2 (+1) | 67% | 4 | 0/0 |"), "{table}");
+    assert!(table.contains("| 18.00 | SPG 3.01 | M8 | 1 | 3 | 1/2 |"), "{table}");
+    assert!(table.contains("| all | | | 2 | 7 (+2) | 1/2 |"), "{table}");
+}
@@ -0,0 +1,43 @@
+//! Wide CSV output with selected fields.
+
+use serde_json::json;
+use ubx_schema::output::{CsvField, MessageWriter};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+#[test]
+fn selected_fields_make_a_row_per_message() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let spec = "NAV-PVT.iTOW,nav_pvt.lat,NAV-PVT.flags.gnssFixOK,NAV-SAT.satellites[1].cno,NAV-PVT.missing";
+    let fields: Vec<CsvField> = spec.split(',').map(|f| f.parse().unwrap()).collect();
+    assert_eq!(fields[1].header(), "NAV-PVT.lat");
+    assert!(fields[..4].iter().all(|f| f.check(&schema).is_ok()));
+    assert!(fields[4].check(&schema).is_err());
+    assert!("NAV-PVT".parse::<CsvField>().is_err());
+
+    let mut out = Vec::new();
+    let mut writer = MessageWriter::csv_fields(fields, &mut out).unwrap();
+    let messages = [
+        json!({"name": "NAV-PVT", "fields": {"iTOW": 1_000, "lat": 515_000_000, "flags": {"gnssFixOK": 1}}}),
+        json!({"name": "NAV-CLOCK", "fields": {"iTOW": 1_000}}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1_000, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 3, "cno": 40}, {"gnssId": 0, "svId": 9, "cno": 31}]}}),
+    ];
+    for (i, m) in messages.iter().enumerate() {
+        let frame = encoder.encode_json(m).unwrap();
+        writer.write(i * 100, &decoder.decode(frame.class, frame.id, &frame.payload)).unwrap();
+    }
+    writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "offset,message,NAV-PVT.iTOW,NAV-PVT.lat,NAV-PVT.flags.gnssFixOK,NAV-SAT.satellites[1].cno,NAV-PVT.missing\n\
+         0,NAV-PVT,1000,51.5,1,,\n\
+         200,NAV-SAT,,,,31,\n"
+    );
+}
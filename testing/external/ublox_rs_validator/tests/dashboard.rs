@@ -0,0 +1,74 @@
+//! Live dashboard state.
+
+use serde_json::json;
+use ubx_schema::dashboard::{Dashboard, MAX_ERRORS};
+use ubx_schema::gnss::Gnss;
+use ubx_schema::track::FixQuality;
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Encoder, Schema};
+
+fn decode(schema: &Schema, json: serde_json::Value) -> DecodedMessage {
+    let frame = Encoder::new(schema).encode_json(&json).unwrap();
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    Decoder::with_options(schema, options).decode(frame.class, frame.id, &frame.payload)
+}
+
+#[test]
+fn tracks_position_satellites_and_rates() {
+    let schema = Schema::load_default().unwrap();
+    let pvt = decode(
+        &schema,
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": 1_000, "fixType": 3, "flags": {"gnssFixOK": 1}, "numSV": 9,
+            "lat": 515_000_000, "lon": -1_200_000, "hMSL": 45_500, "hAcc": 1_500, "gSpeed": 2_000
+        }}),
+    );
+    let sat = |gnss: u8, sv: u8, cno: u8, used: bool| {
+        json!({"gnssId": gnss, "svId": sv, "cno": cno, "elev": 30, "flags": if used { 0x0F } else { 0x04 }})
+    };
+    let nav_sat = decode(
+        &schema,
+        json!({"name": "NAV-SAT", "fields": {
+            "iTOW": 1_000, "version": 1, "numSvs": 4,
+            "satellites": [sat(0, 5, 42, true), sat(0, 12, 30, false), sat(2, 7, 38, true), sat(6, 3, 0, false)]
+        }}),
+    );
+
+    let mut dashboard = Dashboard::new();
+    for i in 0..10 {
+        dashboard.push(&pvt, f64::from(i) * 0.5);
+    }
+    dashboard.push(&nav_sat, 4.5);
+
+    let position = dashboard.position.as_ref().unwrap();
+    assert_eq!(position.fix, FixQuality::Fix3D);
+    assert!((position.lat - 51.5).abs() < 1e-9);
+    assert!((position.lon + 0.12).abs() < 1e-9);
+    assert!((position.alt_msl - 45.5).abs() < 1e-9);
+    assert!((position.speed - 2.0).abs() < 1e-9);
+
+    let labels: Vec<String> = dashboard.satellites.iter().map(|s| s.label()).collect();
+    assert_eq!(labels, ["G05", "G12", "E07", "R03"]);
+    let gnss = dashboard.constellations();
+    assert_eq!(gnss.len(), 3);
+    assert_eq!((gnss[0].gnss, gnss[0].tracked, gnss[0].used), (Gnss::Gps, 2, 1));
+    assert!((gnss[0].mean_cno - 36.0).abs() < 1e-9);
+    assert_eq!((gnss[2].gnss, gnss[2].tracked, gnss[2].used), (Gnss::Glonass, 0, 0));
+
+    let rates = dashboard.rates(4.5);
+    assert_eq!(rates, [("NAV-PVT", 2.0), ("NAV-SAT", 0.2)]);
+    assert_eq!(dashboard.frames, 11);
+}
+
+#[test]
+fn keeps_the_most_recent_errors() {
+    let mut dashboard = Dashboard::new();
+    for i in 0..MAX_ERRORS + 5 {
+        dashboard.push_error(format!("error {i}"), i as f64);
+    }
+    assert_eq!(dashboard.errors.len(), MAX_ERRORS);
+    assert_eq!(dashboard.errors.front().unwrap().1, "error 5");
+}
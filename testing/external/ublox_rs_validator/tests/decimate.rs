@@ -0,0 +1,68 @@
+//! Epoch decimation per message type.
+
+use serde_json::json;
+use ubx_schema::decimate::Decimation;
+use ubx_schema::{Decoder, Encoder, Frame, Schema};
+
+#[test]
+fn keeps_every_nth_epoch_per_type_and_all_configuration() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let encode = |name: &str, itow: u32| {
+        encoder
+            .encode_json(&json!({"name": name, "fields": {"iTOW": itow}}))
+            .unwrap()
+            .to_bytes()
+    };
+    let mut log = Frame::new(0x0A, 0x04, vec![0; 40]).to_bytes();
+    for epoch in 0..12u32 {
+        let itow = 1_000 * epoch;
+        log.extend(encode("NAV-PVT", itow));
+        // Two subframes per epoch, without an iTOW of their own.
+        log.extend(Frame::new(0x02, 0x13, vec![0; 8]).to_bytes());
+        log.extend(Frame::new(0x02, 0x13, vec![0; 8]).to_bytes());
+        if epoch % 2 == 0 {
+            log.extend(encode("NAV-SAT", itow));
+        }
+        if epoch == 5 {
+            log.extend(Frame::new(0x05, 0x01, vec![0x06, 0x8A]).to_bytes());
+            log.extend(Frame::new(0x0A, 0x09, vec![0; 60]).to_bytes());
+        }
+    }
+
+    let decoder = Decoder::new(&schema);
+    let names = |decimation: &Decimation| -> Vec<(String, Option<u32>)> {
+        let mut epoch = None;
+        decimation
+            .apply(&schema, &log)
+            .iter()
+            .map(|f| {
+                let msg = decoder.decode_frame(f);
+                epoch = msg.itow().or(epoch);
+                (msg.short_name().to_string(), epoch)
+            })
+            .collect()
+    };
+
+    let kept = names(&Decimation::new(5).every_for("NAV-SAT", 2).every_for("RXM-SFRBX", 0));
+    let entry = |name: &str, itow: u32| (name.to_string(), Some(itow));
+    assert_eq!(
+        kept,
+        [
+            ("MON-VER".to_string(), None),
+            entry("NAV-PVT", 0),
+            entry("NAV-SAT", 0),
+            entry("NAV-SAT", 4_000),
+            entry("NAV-PVT", 5_000),
+            entry("ACK-ACK", 5_000),
+            entry("MON-HW", 5_000),
+            entry("NAV-SAT", 8_000),
+            entry("NAV-PVT", 10_000),
+        ]
+    );
+
+    let with_sfrbx = names(&Decimation::new(6).keep(["MON-HW"]));
+    let count = |name: &str| with_sfrbx.iter().filter(|(n, _)| n == name).count();
+    assert_eq!((count("NAV-PVT"), count("RXM-SFRBX"), count("NAV-SAT")), (2, 4, 1));
+    assert_eq!((count("MON-VER"), count("ACK-ACK"), count("MON-HW")), (1, 1, 1));
+}
@@ -0,0 +1,128 @@
+//! Decoder tests against the repository schema.
+
+use ubx_schema::frame::Frame;
+use ubx_schema::{DecodeOptions, Decoder, Schema, Value};
+
+fn schema() -> Schema {
+    Schema::load_default().expect("schema loads")
+}
+
+fn nav_posllh_payload() -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&123_456_000u32.to_le_bytes()); // iTOW
+    p.extend_from_slice(&85_123_456i32.to_le_bytes()); // lon
+    p.extend_from_slice(&473_977_418i32.to_le_bytes()); // lat
+    p.extend_from_slice(&500_000i32.to_le_bytes()); // height
+    p.extend_from_slice(&450_000i32.to_le_bytes()); // hMSL
+    p.extend_from_slice(&1_500u32.to_le_bytes()); // hAcc
+    p.extend_from_slice(&2_500u32.to_le_bytes()); // vAcc
+    p
+}
+
+#[test]
+fn lookup_by_name_accepts_short_forms() {
+    let schema = schema();
+    for name in ["UBX-NAV-PVT", "NAV-PVT", "nav_pvt", "ubx-nav-pvt"] {
+        assert_eq!(schema.message_by_name(name).unwrap().name, "UBX-NAV-PVT");
+    }
+    assert_eq!(schema.message_by_name("UBX-MGA-GPS-EPH").unwrap().name, "UBX-MGA-GPS");
+    assert!(schema.message_by_name("NAV-NOPE").is_none());
+}
+
+#[test]
+fn decodes_raw_and_scaled_values() {
+    let schema = schema();
+    let payload = nav_posllh_payload();
+
+    let raw = Decoder::new(&schema).decode(0x01, 0x02, &payload);
+    assert!(raw.parsed);
+    assert_eq!(raw.name, "UBX-NAV-POSLLH");
+    assert_eq!(raw.get("iTOW"), Some(&Value::Unsigned(123_456_000)));
+    assert_eq!(raw.get("lat"), Some(&Value::Signed(473_977_418)));
+
+    let options = DecodeOptions {
+        scale: true,
+        ..Default::default()
+    };
+    let scaled = Decoder::with_options(&schema, options).decode(0x01, 0x02, &payload);
+    let lat = scaled.get("lat").and_then(Value::as_f64).unwrap();
+    assert!((lat - 47.397_741_8).abs() < 1e-9);
+    // Fields without a scale factor are left untouched.
+    assert_eq!(scaled.get("hAcc"), Some(&Value::Unsigned(1_500)));
+}
+
+#[test]
+fn expands_bitfields() {
+    let schema = schema();
+    let mut payload = vec![0u8; 92];
+    payload[21] = 0b1000_0001; // gnssFixOK, carrSoln = 2
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..Default::default()
+    };
+    let msg = Decoder::with_options(&schema, options).decode(0x01, 0x07, &payload);
+    assert_eq!(msg.get("flags.gnssFixOK"), Some(&Value::Unsigned(1)));
+    assert_eq!(msg.get("flags.carrSoln"), Some(&Value::Unsigned(2)));
+    assert_eq!(msg.get("flags.psmState"), Some(&Value::Unsigned(0)));
+    assert!(msg.get("reserved1").is_none());
+}
+
+#[test]
+fn decodes_inline_repeated_groups() {
+    let schema = schema();
+    let mut payload = vec![0u8; 8];
+    payload[4] = 1; // version
+    payload[5] = 2; // numSvs
+    for (sv, cno) in [(5u8, 40u8), (12, 33)] {
+        let mut sat = vec![0u8; 12];
+        sat[1] = sv;
+        sat[2] = cno;
+        payload.extend_from_slice(&sat);
+    }
+    let msg = Decoder::new(&schema).decode(0x01, 0x35, &payload);
+    let sats = msg.get("satellites").and_then(Value::as_array).unwrap();
+    assert_eq!(sats.len(), 2);
+    let second = sats[1].as_record().unwrap();
+    assert_eq!(second.get("svId"), Some(&Value::Unsigned(12)));
+    assert_eq!(second.get("cno"), Some(&Value::Unsigned(33)));
+}
+
+#[test]
+fn group_count_is_bounded_by_payload() {
+    let schema = schema();
+    let mut payload = vec![0u8; 8 + 12];
+    payload[5] = 200; // numSvs claims far more than present
+    let msg = Decoder::new(&schema).decode(0x01, 0x35, &payload);
+    assert_eq!(msg.get("satellites").and_then(Value::as_array).unwrap().len(), 1);
+}
+
+#[test]
+fn selects_variant_by_discriminator() {
+    let schema = schema();
+    let mut payload = vec![0u8; 68];
+    payload[0] = 1; // type = EPH
+    payload[2] = 7; // svId
+    let msg = Decoder::new(&schema).decode(0x13, 0x00, &payload);
+    assert_eq!(msg.name, "UBX-MGA-GPS");
+    assert_eq!(msg.variant.as_deref(), Some("EPH"));
+    assert_eq!(msg.get("svId"), Some(&Value::Unsigned(7)));
+}
+
+#[test]
+fn selects_among_shared_ids_by_length() {
+    let schema = schema();
+    let get = Decoder::new(&schema).decode(0x06, 0x06, &[0u8; 52]);
+    let set = Decoder::new(&schema).decode(0x06, 0x06, &[0u8; 44]);
+    assert_eq!(get.name, "UBX-CFG-DAT-GET");
+    assert_eq!(set.name, "UBX-CFG-DAT-SET");
+}
+
+#[test]
+fn unknown_messages_keep_raw_payload() {
+    let schema = schema();
+    let frame = Frame::new(0xAA, 0xBB, vec![1, 2, 3]);
+    let msg = Decoder::new(&schema).decode(frame.class, frame.id, &frame.payload);
+    assert!(!msg.parsed);
+    assert_eq!(msg.name, "UBX-AA-BB");
+    assert_eq!(msg.payload_raw.as_deref(), Some("010203"));
+}
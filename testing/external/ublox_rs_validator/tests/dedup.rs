@@ -0,0 +1,59 @@
+//! Repeated frame removal within a receiver-time window.
+
+use serde_json::json;
+use ubx_schema::dedup::Dedup;
+use ubx_schema::{Encoder, Frame, Schema};
+
+#[test]
+fn removes_repeats_within_window() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let encode = |name: &str, itow: u32| {
+        encoder
+            .encode_json(&json!({"name": name, "fields": {"iTOW": itow}}))
+            .unwrap()
+            .to_bytes()
+    };
+    let ack = Frame::new(0x05, 0x01, vec![0x06, 0x8A]).to_bytes();
+    let mut log = Vec::new();
+    for epoch in 0..10u32 {
+        // Half-second epochs across the week boundary.
+        let itow = (604_800_000 - 2_000 + 500 * epoch) % 604_800_000;
+        let pvt = encode("NAV-PVT", itow);
+        log.extend(&pvt);
+        match epoch {
+            // The logger wrote the epoch twice.
+            2 => log.extend(&pvt),
+            // An ACK, repeated half a second later and again three seconds later.
+            3 | 4 | 9 => log.extend(&ack),
+            _ => {}
+        }
+    }
+
+    let (kept, stats) = Dedup::default().apply(&schema, &log);
+    assert_eq!((stats.frames, stats.removed, kept.len()), (14, 2, 12));
+    assert_eq!(stats.removed_bytes, 100 + ack.len());
+    let by_type: Vec<(&str, usize)> = stats.by_type.iter().map(|(n, c)| (n.as_str(), *c)).collect();
+    assert_eq!(by_type, [("ACK-ACK", 1), ("NAV-PVT", 1)]);
+    let output: Vec<u8> = kept.iter().flat_map(|f| f.raw.iter().copied()).collect();
+    assert_eq!(Dedup::default().apply(&schema, &output).1.removed, 0);
+
+    // With no window only repeats at the same instant go.
+    let (_, stats) = Dedup::new(0).apply(&schema, &log);
+    assert_eq!(stats.by_type.into_iter().collect::<Vec<_>>(), [("NAV-PVT".to_string(), 1)]);
+}
+
+#[test]
+fn keeps_nmea_and_corrupt_frames_in_place() {
+    let schema = Schema::load_default().unwrap();
+    let ack = Frame::new(0x05, 0x01, vec![0x06, 0x8A]).to_bytes();
+    let nmea = b"$GNGGA,120000.00,,,,,0,00,99.99,,,,,,*7A\r\n".to_vec();
+    let mut corrupt = Frame::new(0x05, 0x01, vec![0x06, 0x8B]).to_bytes();
+    *corrupt.last_mut().unwrap() ^= 1;
+    let log = [ack.clone(), nmea.clone(), ack.clone(), corrupt.clone(), nmea.clone()].concat();
+
+    let (output, stats) = Dedup::default().strip(&schema, &log);
+    assert_eq!((stats.frames, stats.removed), (2, 1));
+    assert_eq!(stats.other_bytes, 2 * nmea.len() + corrupt.len());
+    assert_eq!(output, [ack, nmea.clone(), corrupt, nmea].concat());
+}
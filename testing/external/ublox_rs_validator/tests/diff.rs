@@ -0,0 +1,61 @@
+//! Field-level capture comparison.
+
+use serde_json::json;
+use ubx_schema::diff::{diff_captures, diff_messages, CaptureMessage};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema, Value};
+
+fn message(schema: &Schema, fields: serde_json::Value, offset: usize) -> CaptureMessage {
+    let frame = Encoder::new(schema).encode_json(&fields).unwrap();
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    CaptureMessage {
+        offset,
+        raw: frame.to_bytes(),
+        message: Decoder::with_options(schema, options).decode(frame.class, frame.id, &frame.payload),
+    }
+}
+
+#[test]
+fn reports_changed_fields() {
+    let schema = Schema::load_default().unwrap();
+    let a = message(&schema, json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "lat": 10}}), 0);
+    let b = message(
+        &schema,
+        json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "lat": 12, "flags": {"gnssFixOK": 1}}}),
+        0,
+    );
+    let diffs = diff_messages(&a.message, &b.message, 0.0);
+    let paths: Vec<&str> = diffs.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(paths, ["flags.gnssFixOK", "lat"]);
+    assert_eq!(diffs[1].left, Some(Value::Signed(10)));
+    assert_eq!(diffs[1].right, Some(Value::Signed(12)));
+    assert!(diff_messages(&a.message, &a.message, 0.0).is_empty());
+}
+
+#[test]
+fn pairs_messages_by_epoch() {
+    let schema = Schema::load_default().unwrap();
+    let pvt = |itow: u32, lat: i32| json!({"name": "NAV-PVT", "fields": {"iTOW": itow, "lat": lat}});
+    let left = vec![
+        message(&schema, pvt(1000, 1), 0),
+        message(&schema, pvt(2000, 2), 100),
+        message(&schema, pvt(3000, 3), 200),
+    ];
+    // The right capture dropped the 2000 epoch and changed the 3000 one.
+    let right = vec![
+        message(&schema, pvt(1000, 1), 0),
+        message(&schema, pvt(3000, 4), 100),
+        message(&schema, pvt(4000, 5), 200),
+    ];
+    let diff = diff_captures(&left, &right, 0.0);
+    assert_eq!(diff.matched.len(), 2);
+    assert_eq!(diff.changed(), 1);
+    assert_eq!(diff.bytes_only(), 0);
+    assert_eq!(diff.only_left.len(), 1);
+    assert_eq!(diff.only_left[0].0.itow, Some(2000));
+    assert_eq!(diff.only_right[0].0.itow, Some(4000));
+    assert!(!diff.is_identical());
+    assert!(diff_captures(&left, &left, 0.0).is_identical());
+}
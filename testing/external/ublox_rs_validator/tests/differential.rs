@@ -0,0 +1,72 @@
+//! Differential runs against scripted backends and the ublox crate.
+
+use serde_json::json;
+use ubx_schema::corpus::default_corpus_path;
+use ubx_schema::differential::{corpus_samples, run, Backend, DisagreementKind, Sample, ScriptBackend, UbloxBackend};
+use ubx_schema::{Encoder, Frame, Schema};
+
+/// Answers every request with the same observation.
+fn canned(reply: serde_json::Value) -> ScriptBackend {
+    let code = format!("import sys\nfor line in sys.stdin:\n    print({:?}, flush=True)", reply.to_string());
+    ScriptBackend::new("fake", "python3", &["-c", &code])
+}
+
+#[test]
+fn normalizes_fields_and_reports_disagreements() {
+    let schema = Schema::load_default().unwrap();
+    let nav_sat = Encoder::new(&schema)
+        .encode_json(&json!({
+            "name": "NAV-SAT",
+            "fields": {"iTOW": 1000, "satellites": [{"svId": 3, "cno": 40, "flags": 4}, {"svId": 9, "cno": 22}]}
+        }))
+        .unwrap();
+    let samples = [
+        Sample {
+            source: "sat".into(),
+            frame: nav_sat.to_bytes(),
+        },
+        Sample {
+            source: "unknown".into(),
+            frame: Frame::new(0x7F, 0x01, vec![1, 2]).to_bytes(),
+        },
+    ];
+    let reply = json!({
+        "verdict": "parsed",
+        "name": "NAV-SAT",
+        "fields": {"iTOW": 1000, "svId_01": 3, "cno_02": 23, "flags_01": 4, "numSvs": 2, "extra": 1}
+    });
+    let mut backends: Vec<Box<dyn Backend>> = vec![Box::new(canned(reply))];
+    let report = run(&schema, &samples, &mut backends, 1e-6);
+
+    let summary = &report.backends[0];
+    assert!(summary.available);
+    assert_eq!((summary.parsed, summary.fields_compared, summary.fields_unmatched), (2, 5, 1));
+    let found: Vec<_> = report
+        .disagreements
+        .iter()
+        .map(|d| (d.source.as_str(), d.kind, d.field.as_deref(), d.schema.clone(), d.other.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("sat", DisagreementKind::Field, Some("cno_02"), json!(22), json!(23)),
+            ("unknown", DisagreementKind::Verdict, None, json!("unsupported"), json!("parsed")),
+        ]
+    );
+}
+
+#[test]
+fn corpus_agrees_with_ublox_and_missing_backends_are_reported() {
+    let schema = Schema::load_default().unwrap();
+    let samples = corpus_samples(&default_corpus_path()).unwrap();
+    let mut backends: Vec<Box<dyn Backend>> = vec![
+        Box::new(UbloxBackend),
+        Box::new(ScriptBackend::new("missing", "/nonexistent/decoder", &[])),
+    ];
+    let report = run(&schema, &samples, &mut backends, 1e-6);
+    assert!(report.is_clean(), "{:?}", report.disagreements);
+    assert_eq!(report.schema_parsed, samples.len());
+    assert!(report.backends[0].parsed > 0);
+    assert!(!report.backends[1].available);
+    assert!(report.backends[1].error.as_deref().unwrap().contains("cannot run"));
+}
@@ -0,0 +1,133 @@
+//! Encoder tests: every encoded frame must decode back to the same values.
+
+use serde_json::json;
+use ubx_schema::{DecodeOptions, Decoder, EncodeOptions, Encoder, Error, Schema, Value};
+
+fn schema() -> Schema {
+    Schema::load_default().expect("schema loads")
+}
+
+#[test]
+fn encodes_known_frame() {
+    let schema = schema();
+    let frame = Encoder::new(&schema)
+        .encode_json(&json!({"name": "CFG-RATE", "fields": {"measRate": 100, "navRate": 1, "timeRef": 1}}))
+        .unwrap();
+    assert_eq!(hex::encode(frame.to_bytes()), "b562060806006400010001007a12");
+}
+
+#[test]
+fn round_trips_scalars_and_bitfields() {
+    let schema = schema();
+    let frame = Encoder::new(&schema)
+        .encode_json(&json!({
+            "name": "NAV-PVT",
+            "fields": {"iTOW": 5000, "lat": -473_977_418, "flags": {"gnssFixOK": 1, "carrSoln": 2}}
+        }))
+        .unwrap();
+    assert_eq!(frame.payload.len(), 92);
+
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let msg = Decoder::with_options(&schema, options).decode(frame.class, frame.id, &frame.payload);
+    assert_eq!(msg.get("iTOW"), Some(&Value::Unsigned(5000)));
+    assert_eq!(msg.get("lat"), Some(&Value::Signed(-473_977_418)));
+    assert_eq!(msg.get("flags.gnssFixOK"), Some(&Value::Unsigned(1)));
+    assert_eq!(msg.get("flags.carrSoln"), Some(&Value::Unsigned(2)));
+}
+
+#[test]
+fn fills_group_counts_from_array_length() {
+    let schema = schema();
+    let frame = Encoder::new(&schema)
+        .encode_json(&json!({
+            "name": "NAV-SAT",
+            "fields": {"iTOW": 1, "satellites": [{"svId": 3, "cno": 40}, {"svId": 9, "cno": 22}]}
+        }))
+        .unwrap();
+    let msg = Decoder::new(&schema).decode(frame.class, frame.id, &frame.payload);
+    assert_eq!(msg.get("numSvs"), Some(&Value::Unsigned(2)));
+    assert_eq!(msg.get("satellites[1].cno"), Some(&Value::Unsigned(22)));
+}
+
+#[test]
+fn scaled_input_is_converted_to_raw() {
+    let schema = schema();
+    let encoder = Encoder::with_options(&schema, EncodeOptions { scaled: true });
+    let frame = encoder
+        .encode_json(&json!({"name": "NAV-POSLLH", "fields": {"lon": 8.5123456, "lat": 47.3977418}}))
+        .unwrap();
+    let msg = Decoder::new(&schema).decode(frame.class, frame.id, &frame.payload);
+    assert_eq!(msg.get("lon"), Some(&Value::Signed(85_123_456)));
+    assert_eq!(msg.get("lat"), Some(&Value::Signed(473_977_418)));
+}
+
+#[test]
+fn variant_alias_sets_discriminator() {
+    let schema = schema();
+    let frame = Encoder::new(&schema)
+        .encode_json(&json!({"name": "MGA-GPS-EPH", "fields": {"svId": 7}}))
+        .unwrap();
+    let msg = Decoder::new(&schema).decode(frame.class, frame.id, &frame.payload);
+    assert_eq!(msg.variant.as_deref(), Some("EPH"));
+    assert_eq!(msg.get("svId"), Some(&Value::Unsigned(7)));
+}
+
+#[test]
+fn rejects_bad_input() {
+    let schema = schema();
+    let encoder = Encoder::new(&schema);
+    let unknown_field = encoder.encode_json(&json!({"name": "NAV-PVT", "fields": {"bogus": 1}}));
+    assert!(matches!(unknown_field, Err(Error::Encode(_))));
+    let out_of_range = encoder.encode_json(&json!({"name": "CFG-RATE", "fields": {"navRate": 70000}}));
+    assert!(matches!(out_of_range, Err(Error::Encode(_))));
+    let unknown_message = encoder.encode_json(&json!({"name": "NAV-NOPE", "fields": {}}));
+    assert!(matches!(unknown_message, Err(Error::UnknownMessage(_))));
+}
+
+#[test]
+fn decoded_records_re_encode_to_the_same_frames() {
+    let schema = schema();
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: true,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let pvt = Encoder::new(&schema)
+        .encode_json(&json!({"name": "NAV-PVT", "fields": {"iTOW": 1_000, "lat": 515_000_123, "flags": 1}}))
+        .unwrap();
+    let unknown = ubx_schema::Frame::new(0x7F, 0x01, vec![0xAA, 0xBB]);
+    let encoder = Encoder::with_options(&schema, EncodeOptions { scaled: true });
+    for frame in [pvt, unknown] {
+        let record = serde_json::to_value(decoder.decode(frame.class, frame.id, &frame.payload)).unwrap();
+        assert_eq!(encoder.encode_record(&record).unwrap(), frame);
+    }
+
+    let mut record = json!({"name": "UBX-7F-01", "parsed": false, "payload_raw": "aabb", "class_id": 127});
+    assert!(encoder.encode_record(&record).is_err());
+    record["message_id"] = json!(1);
+    assert_eq!(encoder.encode_record(&record).unwrap().payload, [0xAA, 0xBB]);
+}
+
+#[test]
+fn oversized_payloads_are_errors_not_panics() {
+    let schema = schema();
+    let message = json!({"name": "RXM-SFRBX", "fields": {"numWords": 255, "dwrd": vec![0; 20_000]}});
+    let frame = Encoder::new(&schema).encode_json(&message).unwrap();
+    assert!(matches!(frame.try_to_bytes(), Err(ubx_schema::FrameError::PayloadTooLong(80_008))));
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_ubx-encode"))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::io::Write::write_all(&mut child.stdin.take().unwrap(), message.to_string().as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(stderr.starts_with("ubx-encode: ") && stderr.contains("65535"), "{stderr}");
+}
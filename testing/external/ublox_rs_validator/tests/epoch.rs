@@ -0,0 +1,88 @@
+//! Cross-message consistency within navigation epochs.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::epoch::{EpochChecker, Tolerances};
+use ubx_schema::geo::{llh_to_ecef, Llh};
+use ubx_schema::semantic::Rule;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn epoch(itow: u32, num_sv: u64, used: usize, dop_pdop: u64, lat_hp: i64) -> Vec<Json> {
+    let satellites: Vec<Json> = (0..4)
+        .map(|i| json!({"gnssId": 0, "svId": i + 1, "cno": 40, "flags": if i < used { 0x08 } else { 0 }}))
+        .collect();
+    vec![
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": itow, "fixType": 3, "numSV": num_sv, "pDOP": 150, "lon": 85_123_456, "lat": 473_977_418
+        }}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": itow, "satellites": satellites}}),
+        json!({"name": "NAV-DOP", "fields": {"iTOW": itow, "pDOP": dop_pdop}}),
+        json!({"name": "NAV-HPPOSLLH", "fields": {
+            "iTOW": itow, "lon": 85_123_456, "lat": 473_977_418, "lonHp": 12, "latHp": lat_hp
+        }}),
+    ]
+}
+
+fn check(messages: &[Json]) -> (Vec<(Rule, String)>, usize) {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut checker = EpochChecker::new(Tolerances::default());
+    let mut warnings = Vec::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        warnings.extend(checker.push(&decoder.decode(frame.class, frame.id, &frame.payload)));
+    }
+    warnings.extend(checker.finish());
+    let warnings = warnings.into_iter().map(|w| (w.rule, w.message)).collect();
+    (warnings, checker.epochs())
+}
+
+#[test]
+fn agreeing_epochs_pass() {
+    let messages = [epoch(1000, 3, 3, 150, -34), epoch(2000, 4, 4, 151, 99)].concat();
+    assert_eq!(check(&messages), (vec![], 2));
+}
+
+#[test]
+fn reports_each_disagreement_once_per_epoch() {
+    // latHp 99 is 9.9e-8 deg, about 1.1 cm and within tolerance; the last
+    // epoch's HP latitude is 1e-5 deg, about 1.1 m, off.
+    let mut messages = [epoch(1000, 4, 3, 150, 0), epoch(2000, 4, 4, 175, 99)].concat();
+    messages.extend(epoch(3000, 4, 4, 150, 0));
+    messages[11]["fields"]["lat"] = json!(473_977_518);
+    let (warnings, epochs) = check(&messages);
+    assert_eq!(epochs, 3);
+    assert_eq!(
+        warnings,
+        [
+            (Rule::Epoch, "iTOW 1000: NAV-PVT numSV 4, NAV-SAT 3 used".to_string()),
+            (Rule::Epoch, "iTOW 2000: NAV-PVT pDOP 1.5, NAV-DOP 1.75".to_string()),
+            (Rule::Epoch, "iTOW 3000: NAV-HPPOSLLH is 1.112 m from NAV-PVT".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn ecef_position_must_match_llh() {
+    let p = llh_to_ecef(Llh { lat: 47.397_741_8, lon: 8.512_345_612, height: 0.0 });
+    let posecef = |itow: u32, dx_cm: f64| {
+        let cm = |m: f64| (m * 100.0).round();
+        json!({"name": "NAV-POSECEF", "fields": {
+            "iTOW": itow, "ecefX": cm(p.x) + dx_cm, "ecefY": cm(p.y), "ecefZ": cm(p.z)
+        }})
+    };
+    let mut messages = epoch(1000, 4, 4, 150, 0);
+    messages.push(posecef(1000, 0.0));
+    messages.extend(epoch(2000, 4, 4, 150, 0));
+    messages.push(posecef(2000, 100.0));
+    let (warnings, _) = check(&messages);
+    assert_eq!(
+        warnings,
+        [(Rule::Epoch, "iTOW 2000: NAV-POSECEF is 1.004 m from NAV-HPPOSLLH".to_string())]
+    );
+}
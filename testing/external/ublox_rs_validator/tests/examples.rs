@@ -0,0 +1,57 @@
+//! The schema's embedded example frames decode to their expected values, and
+//! a definition that disagrees with its example is caught.
+
+use serde_json::json;
+use ubx_schema::examples::run;
+use ubx_schema::{Frame, Schema};
+
+#[test]
+fn schema_examples_pass() {
+    let schema = Schema::load_default().unwrap();
+    let report = run(&schema, &[]);
+    assert!(report.examples >= 5, "{report:?}");
+    let failures: Vec<String> = report.failures.iter().map(ToString::to_string).collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn wrong_offset_or_scale_fails_its_example() {
+    let frame = Frame::new(0x01, 0x02, [5000u32.to_le_bytes(), (-123_456_789i32).to_le_bytes()].concat()).to_bytes();
+    let schema_with = |lat_offset: u64, multiplier: f64| {
+        Schema::from_json(&json!({
+            "schema_version": "1.5",
+            "messages": [{
+                "name": "UBX-NAV-POSLLH",
+                "class_id": "0x01",
+                "message_id": "0x02",
+                "message_type": "periodic_polled",
+                "payload": {
+                    "length": {"fixed": 8},
+                    "fields": [
+                        {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+                        {"name": "lat", "byte_offset": lat_offset, "data_type": "I4", "scale": {"multiplier": multiplier}}
+                    ]
+                },
+                "examples": [{
+                    "description": "southern latitude",
+                    "frame": hex::encode(&frame),
+                    "fields": {"iTOW": 5000, "lat": -12.3456789}
+                }]
+            }]
+        }))
+        .unwrap()
+    };
+
+    assert_eq!(run(&schema_with(4, 1e-7), &[]).failures, []);
+
+    let report = run(&schema_with(4, 1e-5), &["nav-posllh".into()]);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(
+        report.failures[0].to_string(),
+        "UBX-NAV-POSLLH example 1 (southern latitude): lat = -1234.56789, expected -12.3456789"
+    );
+
+    let report = run(&schema_with(0, 1e-7), &[]);
+    assert_eq!(report.failures[0].problems, ["lat = 0.0005, expected -12.3456789"]);
+    assert_eq!(run(&schema_with(0, 1e-7), &["NAV-PVT".into()]).examples, 0);
+}
@@ -0,0 +1,66 @@
+//! Frame selection by message type and time window.
+
+use serde_json::json;
+use ubx_schema::filter::{Filter, ItowRange};
+use ubx_schema::time::parse_iso8601;
+use ubx_schema::{Encoder, Schema};
+
+/// NAV-PVT epochs at 1 s intervals from 12:00:00 UTC, each followed by an
+/// RXM-SFRBX (no iTOW). The last frame carries trailing junk that must not
+/// leak into the output.
+fn log(schema: &Schema) -> Vec<u8> {
+    let encoder = Encoder::new(schema);
+    let mut out = Vec::new();
+    for i in 0..4u32 {
+        let pvt = json!({"name": "NAV-PVT", "fields": {
+            "iTOW": 100_000 + i * 1_000, "year": 2024, "month": 1, "day": 15,
+            "hour": 12, "min": 0, "sec": i, "valid": {"validDate": 1, "validTime": 1}
+        }});
+        out.extend(encoder.encode_json(&pvt).unwrap().to_bytes());
+        let sfrbx = json!({"name": "RXM-SFRBX", "fields": {"gnssId": 0, "svId": i + 1, "numWords": 0}});
+        out.extend(encoder.encode_json(&sfrbx).unwrap().to_bytes());
+    }
+    out.extend_from_slice(b"$GPGGA,junk*00\r\n");
+    out
+}
+
+#[test]
+fn filters_by_type_and_itow() {
+    let schema = Schema::load_default().unwrap();
+    let input = log(&schema);
+
+    let pvt = Filter::default().include(["NAV-PVT"]).apply(&schema, &input);
+    assert_eq!(pvt.len(), 4);
+    assert!(pvt.iter().all(|f| (f.class, f.id) == (0x01, 0x07)));
+
+    let window = Filter::default()
+        .exclude(["nav-pvt"])
+        .itow(ItowRange {
+            from: Some(101_000),
+            to: Some(103_000),
+        })
+        .apply(&schema, &input);
+    // SFRBX inherits the epoch of the preceding NAV-PVT.
+    let svs: Vec<u8> = window.iter().map(|f| f.payload[1]).collect();
+    assert_eq!(svs, [2, 3]);
+    // The original bytes are returned untouched.
+    for f in &window {
+        assert_eq!(&input[f.offset..f.offset + f.raw.len()], f.raw);
+    }
+}
+
+#[test]
+fn filters_by_utc_window() {
+    let schema = Schema::load_default().unwrap();
+    let input = log(&schema);
+    let from = parse_iso8601("2024-01-15T12:00:02Z").unwrap().to_unix();
+    let selected = Filter::default().utc(Some(from), None).apply(&schema, &input);
+    assert_eq!(selected.len(), 4);
+    assert_eq!(selected[0].payload[..4], 102_000u32.to_le_bytes());
+
+    assert!(ItowRange {
+        from: Some(604_000_000),
+        to: Some(1_000),
+    }
+    .contains(500));
+}
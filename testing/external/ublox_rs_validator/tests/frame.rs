@@ -0,0 +1,231 @@
+//! Framing tests: checksum, parsing and stream resynchronization.
+
+use ubx_schema::frame::{
+    checksum, checksum_bytewise, frames, parse_frame, write_frame, FillerFilter, Frame, FrameIter, FrameParser,
+    FrameWriter, LengthPolicy, OversizeAction, Transport, MAX_PAYLOAD_LEN, SPI_IDLE_RUN,
+};
+use ubx_schema::ntrip::RtcmFrame;
+use ubx_schema::FrameError;
+
+#[test]
+fn checksum_matches_known_frame() {
+    // UBX-ACK-ACK for CFG-PRT, from the interface description.
+    let frame = [0xB5, 0x62, 0x05, 0x01, 0x02, 0x00, 0x06, 0x00, 0x0E, 0x37];
+    assert_eq!(checksum(&frame[2..8]), [0x0E, 0x37]);
+    let parsed = parse_frame(&frame).unwrap();
+    assert_eq!((parsed.class, parsed.id), (0x05, 0x01));
+    assert_eq!(parsed.payload, &[0x06, 0x00]);
+}
+
+#[test]
+fn blocked_checksum_matches_the_byte_loop() {
+    // Lengths either side of the 32-byte block, and a full RAWX-sized payload.
+    let data: Vec<u8> = (0..2048u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+    for len in (0..70).chain([1_000, 2_048]) {
+        assert_eq!(checksum(&data[..len]), checksum_bytewise(&data[..len]), "{len} bytes");
+    }
+    assert_eq!(checksum(&[0xFF; 4096]), checksum_bytewise(&[0xFF; 4096]));
+}
+
+#[test]
+fn to_bytes_round_trips() {
+    let frame = Frame::new(0x01, 0x07, (0..92).collect());
+    let bytes = frame.to_bytes();
+    assert_eq!(bytes.len(), 100);
+    assert_eq!(parse_frame(&bytes).unwrap().to_owned(), frame);
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(parse_frame(&[0xB5, 0x62]), Err(FrameError::TooShort(2)));
+    assert!(matches!(
+        parse_frame(&[0x24, 0x47, 0, 0, 0, 0, 0, 0]),
+        Err(FrameError::InvalidSync(0x24, 0x47))
+    ));
+    let mut bytes = Frame::new(0x05, 0x01, vec![6, 0]).to_bytes();
+    assert!(matches!(
+        parse_frame(&bytes[..9]),
+        Err(FrameError::Truncated { expected: 10, actual: 9 })
+    ));
+    bytes[7] ^= 0xFF;
+    assert!(matches!(parse_frame(&bytes), Err(FrameError::Checksum { .. })));
+}
+
+#[test]
+fn iterator_skips_noise_and_resyncs() {
+    let good = Frame::new(0x05, 0x01, vec![6, 1]).to_bytes();
+    let mut bad = Frame::new(0x05, 0x00, vec![6, 1]).to_bytes();
+    bad[8] ^= 1;
+
+    let mut stream = b"$GNGGA,noise*00\r\n".to_vec();
+    stream.extend_from_slice(&good);
+    stream.extend_from_slice(&bad);
+    stream.extend_from_slice(&good);
+    stream.extend_from_slice(&[0xB5, 0x62, 0x01]);
+
+    let mut iter = frames(&stream);
+    let results: Vec<_> = iter.by_ref().collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().offset, 17);
+    assert!(matches!(results[1], Err(FrameError::Checksum { class: 5, id: 0, .. })));
+    assert_eq!(results[2].as_ref().unwrap().raw, &good[..]);
+
+    let stats = iter.stats();
+    assert_eq!(stats.frames, 2);
+    assert_eq!(stats.checksum_errors, 1);
+    assert_eq!(stats.skipped_bytes, 17 + bad.len() + 3);
+}
+
+#[test]
+fn parser_reassembles_split_frames() {
+    let good = Frame::new(0x01, 0x07, vec![1, 2, 3, 4]).to_bytes();
+    let mut bad = Frame::new(0x05, 0x00, vec![6, 1]).to_bytes();
+    bad[8] ^= 1;
+    let mut stream = b"noise".to_vec();
+    stream.extend_from_slice(&good);
+    stream.extend_from_slice(&bad);
+    stream.extend_from_slice(&good);
+
+    let mut parser = FrameParser::new();
+    let mut results = Vec::new();
+    // Feed one byte at a time, the worst case for a serial port.
+    for &byte in &stream {
+        parser.push(&[byte]);
+        while let Some(result) = parser.next_frame() {
+            results.push(result);
+        }
+    }
+    assert_eq!(results.len(), 3);
+    let (offset, frame) = results[0].as_ref().unwrap();
+    assert_eq!(*offset, 5);
+    assert_eq!(frame.payload, [1, 2, 3, 4]);
+    assert!(matches!(results[1], Err(FrameError::Checksum { .. })));
+    assert_eq!(results[2].as_ref().unwrap().0, (5 + good.len() + bad.len()) as u64);
+    assert_eq!(parser.pending(), 0);
+    assert_eq!(parser.stats().frames, 2);
+}
+
+#[test]
+fn filler_is_dropped_between_frames_only() {
+    // Payloads full of 0xFF, NMEA and RTCM in between, and idle filler around it all.
+    let a = Frame::new(0x01, 0x07, vec![0xFF; 20]);
+    let b = Frame::new(0x0A, 0x04, [0xFF, 0x00, 0xFF].repeat(10));
+    let nmea = b"$GNGGA,*4E\r\n";
+    // RTCM 3 is binary too: 0xFF in the payload and both ends of the CRC.
+    let rtcm = RtcmFrame { message_type: 1005, payload: hex::decode("3ed0ffff005a5b").unwrap() }.to_bytes();
+    assert_eq!((rtcm[rtcm.len() - 3], rtcm[rtcm.len() - 1]), (0xFF, 0xFF));
+    let mut data: Vec<u8> = vec![0xFF; 60];
+    data.extend(a.to_bytes());
+    data.extend([0xFF; 3]);
+    data.extend(nmea);
+    data.extend(&rtcm);
+    data.extend([0xFF; 2]);
+    data.extend([0xB5, 0xFF, 0xFF]);
+    data.extend(b.to_bytes());
+    data.extend([0xFF; SPI_IDLE_RUN]);
+    let mut expected = a.to_bytes();
+    expected.extend(nmea);
+    expected.extend(&rtcm);
+    expected.push(0xB5);
+    expected.extend(b.to_bytes());
+
+    // DDC: reads of up to 32 bytes; SPI: a byte at a time.
+    for (transport, chunk) in [(Transport::Ddc, 32), (Transport::Spi, 1)] {
+        let mut filter = FillerFilter::new(transport);
+        let mut out = Vec::new();
+        for read in data.chunks(chunk) {
+            out.extend(filter.strip(read));
+        }
+        assert_eq!(out, expected, "{transport}");
+        let stats = filter.stats();
+        assert_eq!((stats.filler_bytes, stats.data_bytes), (60 + 3 + 2 + 2 + SPI_IDLE_RUN, expected.len()));
+        assert_eq!(stats.longest_filler_run, 60);
+        assert!(filter.idle());
+        let mut parser = FrameParser::new();
+        parser.push(&out);
+        let parsed: Vec<Frame> = std::iter::from_fn(|| parser.next_frame()).map(|r| r.unwrap().1).collect();
+        assert_eq!(parsed, [a.clone(), b.clone()]);
+    }
+
+    let stats = {
+        let mut filter = FillerFilter::new(Transport::Spi);
+        filter.strip(&[0xFF; 8]);
+        filter.strip(&a.to_bytes());
+        filter.stats()
+    };
+    assert_eq!((stats.reads, stats.idle_reads), (2, 1));
+    let mut uart = FillerFilter::new(Transport::Uart);
+    assert_eq!(uart.strip(&data), data);
+    assert_eq!("i2c".parse::<Transport>(), Ok(Transport::Ddc));
+}
+
+#[test]
+fn frame_writer_fills_a_caller_buffer() {
+    let mut buf = [0u8; 24];
+    let mut writer = FrameWriter::new(&mut buf);
+    // MON-VER poll, then CFG-MSG setting NAV-PVT to 1 on the current port.
+    assert_eq!(writer.write(0x0A, 0x04, &[]).unwrap(), Frame::new(0x0A, 0x04, Vec::new()).to_bytes());
+    writer.write_frame(&Frame::new(0x06, 0x01, vec![0x01, 0x07, 0x01])).unwrap();
+    assert_eq!((writer.len(), writer.remaining()), (19, 5));
+    assert_eq!(
+        writer.write(0x0A, 0x04, &[]),
+        Err(FrameError::BufferTooSmall { needed: 8, available: 5 })
+    );
+    let parsed: Vec<_> = frames(writer.as_bytes()).flatten().map(|f| (f.class, f.id)).collect();
+    assert_eq!(parsed, [(0x0A, 0x04), (0x06, 0x01)]);
+
+    let payload = vec![0; MAX_PAYLOAD_LEN + 1];
+    let mut big = vec![0; payload.len() + 8];
+    assert_eq!(write_frame(&mut big, 0x02, 0x15, &payload), Err(FrameError::PayloadTooLong(MAX_PAYLOAD_LEN + 1)));
+}
+
+#[test]
+fn length_policy_applies_to_oversized_and_truncated_frames() {
+    let small = Frame::new(0x05, 0x01, vec![0x06, 0x00]).to_bytes();
+    // A corrupted header claiming 0x4000 bytes, a good frame, then all
+    // but the last byte of another.
+    let mut data = vec![0xB5, 0x62, 0x01, 0x07, 0x00, 0x40];
+    data.extend_from_slice(&small);
+    data.extend_from_slice(&small[..9]);
+    let scan = |action| {
+        let mut iter = FrameIter::with_policy(&data, LengthPolicy::new(1_024, action));
+        let items: Vec<_> = iter.by_ref().map(|f| f.map(|f| f.offset)).collect();
+        (items, iter.stats().oversized, iter.position())
+    };
+
+    let (items, oversized, position) = scan(OversizeAction::Resync);
+    assert_eq!((items, oversized, position), (vec![Ok(6)], 2, data.len()));
+
+    let (items, oversized, _) = scan(OversizeAction::Error);
+    let oversize = FrameError::Oversized { class: 0x01, id: 0x07, length: 0x4000, limit: 1_024 };
+    assert_eq!(items, [Err(oversize), Ok(6), Err(FrameError::Truncated { expected: 10, actual: 9 })]);
+    assert_eq!(oversized, 2);
+
+    // Waiting trusts the first length and stops for the rest of that frame.
+    let (items, oversized, position) = scan(OversizeAction::Wait);
+    assert_eq!((items, oversized, position), (vec![], 0, 0));
+    let mut iter = FrameIter::with_policy(&data[6..], LengthPolicy::new(1_024, OversizeAction::Wait));
+    assert_eq!(iter.by_ref().flatten().count(), 1);
+    assert_eq!(iter.position(), small.len());
+}
+
+#[test]
+fn parser_length_policy() {
+    let small = Frame::new(0x05, 0x01, vec![0x06, 0x00]).to_bytes();
+    let mut data = vec![0xB5, 0x62, 0x01, 0x07, 0x00, 0x40];
+    data.extend_from_slice(&small);
+
+    let mut parser = FrameParser::with_policy(LengthPolicy::new(1_024, OversizeAction::Error));
+    parser.push(&data);
+    assert!(matches!(parser.next_frame(), Some(Err(FrameError::Oversized { length: 0x4000, .. }))));
+    assert_eq!(parser.next_frame().unwrap().unwrap().0, 6);
+    assert!(parser.next_frame().is_none());
+
+    // The default cap is the length field's range, so a long frame is
+    // buffered until it completes.
+    let mut parser = FrameParser::new();
+    parser.push(&data);
+    assert!(parser.next_frame().is_none());
+    assert_eq!(parser.pending(), data.len());
+    assert_eq!(LengthPolicy::default().max_payload, MAX_PAYLOAD_LEN);
+}
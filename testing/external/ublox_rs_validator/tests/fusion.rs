@@ -0,0 +1,75 @@
+//! ESF-STATUS and ESF-ALG fusion reports.
+
+use serde_json::json;
+use ubx_schema::fusion::{FusionAnalyzer, StatusChange, FINE_ALIGNMENT};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn analyze(messages: &[serde_json::Value]) -> FusionAnalyzer {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut fusion = FusionAnalyzer::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        fusion.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    fusion
+}
+
+#[test]
+fn fusion_mode_and_sensor_calibration() {
+    // 1 Hz: initialization for 3 s, then fusion; the speed sensor
+    // calibrates after 2 s and reports one bad measurement.
+    let epochs = [(0, 0, 0, 0), (1, 1, 0, 0), (2, 2, 0, 1), (3, 2, 1, 0), (4, 2, 1, 0), (5, 3, 1, 0)];
+    let messages: Vec<_> = epochs
+        .iter()
+        .map(|&(t, calib, mode, bad)| {
+            json!({"name": "ESF-STATUS", "fields": {"iTOW": 1_000 * t, "version": 2, "fusionMode": mode,
+                "numSens": 1, "sensStatus1": {"type": 11, "used": mode, "ready": 1},
+                "sensStatus2": {"calibStatus": calib, "timeStatus": 2}, "freq": 10,
+                "faults": {"badMeas": bad}}})
+        })
+        .collect();
+    let fusion = analyze(&messages);
+
+    let mode = &fusion.fusion;
+    assert_eq!(mode.epochs, 6);
+    assert_eq!(mode.changes, [StatusChange { itow: 3_000, from: 0, to: 1 }]);
+    assert_eq!((mode.time_in[&0], mode.time_in[&1], mode.time_to[&1]), (3_000, 2_000, 3_000));
+
+    let speed = &fusion.sensors[&11];
+    assert_eq!((speed.epochs, speed.used, speed.ready, speed.freq), (6, 3, 6, 10));
+    assert_eq!(speed.time_to_calibrated(), Some(2_000));
+    assert_eq!(speed.calibration.current(), Some(3));
+    assert_eq!(speed.faults, [1, 0, 0, 0]);
+}
+
+#[test]
+fn mount_alignment_converges_across_north() {
+    // Raw yaw in 0.01°: fine alignment settles between 359° and 0.5°.
+    let epochs = [(0, 1, 0), (1, 3, 1_000), (2, 4, 35_950), (3, 4, 50), (4, 4, 0), (5, 4, 35_900)];
+    let messages: Vec<_> = epochs
+        .iter()
+        .map(|&(t, status, yaw)| {
+            json!({"name": "ESF-ALG", "fields": {"iTOW": 1_000 * t, "version": 1,
+                "flags": {"autoMntAlgOn": 1, "status": status}, "error": {"yawAlgError": u8::from(t == 1)},
+                "yaw": yaw, "pitch": 200, "roll": -150}})
+        })
+        .collect();
+    let alignment = analyze(&messages).alignment;
+
+    assert_eq!(alignment.status.current(), Some(FINE_ALIGNMENT));
+    assert_eq!((alignment.status.time_to[&3], alignment.status.time_to[&4]), (1_000, 2_000));
+    assert_eq!(alignment.errors, [0, 1, 0]);
+    assert_eq!(alignment.yaw.count, 4);
+    assert!((alignment.yaw.max - alignment.yaw.min - 1.5).abs() < 1e-9, "{:?}", alignment.yaw);
+    assert!(alignment.yaw.std_dev().unwrap() < 1.0);
+    assert_eq!((alignment.pitch.mean(), alignment.roll.mean()), (Some(2.0), Some(-1.5)));
+    let (yaw, _, _) = alignment.last.unwrap();
+    assert!((yaw - 359.0).abs() < 1e-9);
+}
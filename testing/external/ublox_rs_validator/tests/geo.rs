@@ -0,0 +1,68 @@
+//! WGS-84 conversions and positions read from decoded NAV messages.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::geo::{
+    ecef_position, ecef_to_enu, ecef_velocity, enu_to_ecef, llh_position, llh_to_ecef, Ecef, Enu, Llh,
+};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Encoder, Schema};
+
+fn decode(schema: &Schema, msg: Json) -> DecodedMessage {
+    let frame = Encoder::new(schema).encode_json(&msg).unwrap();
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    Decoder::with_options(schema, options).decode(frame.class, frame.id, &frame.payload)
+}
+
+#[test]
+fn high_precision_ecef_and_llh_agree() {
+    let schema = Schema::load_default().unwrap();
+    let truth = Llh { lat: 47.397_741_812_3, lon: 8.512_345_678_9, height: 500.123_4 };
+    let p = llh_to_ecef(truth);
+    // Whole centimetres plus the remainder in 0.1 mm steps.
+    let split = |m: f64| {
+        let tenth_mm = (m * 10_000.0).round() as i64;
+        (tenth_mm / 100, tenth_mm % 100)
+    };
+    let ((x, x_hp), (y, y_hp), (z, z_hp)) = (split(p.x), split(p.y), split(p.z));
+    let ecef = decode(&schema, json!({"name": "NAV-HPPOSECEF", "fields": {
+        "iTOW": 1000, "ecefX": x, "ecefY": y, "ecefZ": z, "ecefXHp": x_hp, "ecefYHp": y_hp, "ecefZHp": z_hp
+    }}));
+    let ecef = ecef_position(&ecef).unwrap();
+    assert!(ecef.distance(&p) < 1e-4);
+
+    let llh = decode(&schema, json!({"name": "NAV-HPPOSLLH", "fields": {
+        "iTOW": 1000, "lat": 473_977_418, "lon": 85_123_456, "latHp": 12, "lonHp": 79,
+        "height": 500_123, "heightHp": 4
+    }}));
+    let llh = llh_position(&llh).unwrap();
+    assert!((llh.lat - truth.lat).abs() < 1e-9 && (llh.lon - truth.lon).abs() < 1e-9);
+    assert!((llh.height - truth.height).abs() < 1e-6);
+    assert!(llh_to_ecef(llh).distance(&ecef) < 1e-3);
+
+    // The receiver says the ECEF solution is invalid.
+    let invalid = decode(&schema, json!({"name": "NAV-HPPOSECEF", "fields": {
+        "iTOW": 1000, "ecefX": x, "ecefY": y, "ecefZ": z, "flags": {"invalidEcef": 1}
+    }}));
+    assert_eq!(ecef_position(&invalid), None);
+}
+
+#[test]
+fn velocity_in_local_frame() {
+    let schema = Schema::load_default().unwrap();
+    let origin = Llh { lat: 47.4, lon: 8.5, height: 500.0 };
+    // 10 m/s north-east and 1 m/s up, as NAV-VELECEF would report it.
+    let enu = Enu { east: 7.0, north: 7.0, up: 1.0 };
+    let v = enu_to_ecef(enu, origin);
+    let cm = |m: f64| (m * 100.0).round() as i64;
+    let msg = decode(&schema, json!({"name": "NAV-VELECEF", "fields": {
+        "iTOW": 1000, "ecefVX": cm(v.x), "ecefVY": cm(v.y), "ecefVZ": cm(v.z)
+    }}));
+    let local = ecef_to_enu(ecef_velocity(&msg).unwrap(), origin);
+    assert!((local.east - 7.0).abs() < 0.01 && (local.north - 7.0).abs() < 0.01 && (local.up - 1.0).abs() < 0.01);
+    let round = ecef_to_enu(Ecef { x: 1.0, y: -2.0, z: 3.0 }, origin);
+    let back = enu_to_ecef(round, origin);
+    assert!((back.x - 1.0).abs() < 1e-12 && (back.y + 2.0).abs() < 1e-12 && (back.z - 3.0).abs() < 1e-12);
+}
@@ -0,0 +1,88 @@
+//! gpsd TPV and SKY reports from encoded NAV-PVT, NAV-DOP and NAV-SAT frames.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::gpsd::{gpsd_prn, MODE_3D, STATUS_RTK_FIX};
+use ubx_schema::output::MessageWriter;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn reports(messages: &[Json]) -> Vec<Json> {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut out = Vec::new();
+    let mut writer = MessageWriter::gpsd("/dev/ttyACM0", &mut out).unwrap();
+    for (i, m) in messages.iter().enumerate() {
+        let frame = encoder.encode_json(m).unwrap();
+        writer.write(i, &decoder.decode(frame.class, frame.id, &frame.payload)).unwrap();
+    }
+    writer.finish().unwrap();
+    String::from_utf8(out).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+#[test]
+fn translates_an_epoch_into_tpv_and_sky() {
+    let reports = reports(&[
+        json!({"name": "NAV-DOP", "fields": {
+            "iTOW": 1000, "gDOP": 180, "pDOP": 150, "tDOP": 90, "vDOP": 120, "hDOP": 80, "nDOP": 60, "eDOP": 50
+        }}),
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": 1000, "year": 2024, "month": 1, "day": 15, "hour": 12, "min": 0, "sec": 1, "nano": 250_000_000,
+            "valid": {"validDate": 1, "validTime": 1}, "fixType": 3, "flags": {"gnssFixOK": 1, "carrSoln": 2},
+            "numSV": 20, "lon": 85_123_456, "lat": 473_977_418, "height": 460_000, "hMSL": 412_345,
+            "hAcc": 15, "vAcc": 25, "velN": 1_000, "velE": -500, "velD": 200, "gSpeed": 1_118,
+            "headMot": 33_300_000, "tAcc": 20
+        }}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45, "elev": 60, "azim": 120, "flags": 0x18},
+            {"gnssId": 6, "svId": 3, "cno": 30, "elev": -100, "azim": 0, "flags": 0x20},
+        ]}}),
+    ]);
+    let classes: Vec<&str> = reports.iter().map(|r| r["class"].as_str().unwrap()).collect();
+    assert_eq!(classes, ["VERSION", "DEVICES", "WATCH", "TPV", "SKY"]);
+
+    let tpv = &reports[3];
+    assert_eq!(tpv["device"], "/dev/ttyACM0");
+    assert_eq!(tpv["mode"], MODE_3D);
+    assert_eq!(tpv["status"], STATUS_RTK_FIX);
+    assert_eq!(tpv["time"], "2024-01-15T12:00:01.250Z");
+    assert_eq!(tpv["lat"], 47.3977418);
+    assert_eq!(tpv["altHAE"], 460.0);
+    assert_eq!(tpv["altMSL"], 412.345);
+    assert_eq!(tpv["speed"], 1.118);
+    assert_eq!(tpv["climb"], -0.2);
+    assert_eq!(tpv["track"], 333.0);
+
+    let sky = &reports[4];
+    assert_eq!(sky["time"], tpv["time"]);
+    assert_eq!((sky["hdop"].clone(), sky["xdop"].clone(), sky["ydop"].clone()), (json!(0.8), json!(0.5), json!(0.6)));
+    assert_eq!((sky["nSat"].clone(), sky["uSat"].clone()), (json!(2), json!(1)));
+    assert_eq!(
+        sky["satellites"][0],
+        json!({"PRN": 12, "gnssid": 0, "svid": 12, "el": 60.0, "az": 120.0, "ss": 45.0, "used": true, "health": 1})
+    );
+    // Unknown elevation leaves out el and az.
+    assert_eq!(
+        sky["satellites"][1],
+        json!({"PRN": 67, "gnssid": 6, "svid": 3, "ss": 30.0, "used": false, "health": 2})
+    );
+}
+
+#[test]
+fn no_fix_reports_mode_only_and_maps_prns() {
+    let reports = reports(&[json!({"name": "NAV-PVT", "fields": {
+        "iTOW": 2000, "fixType": 0, "lon": 85_123_456, "lat": 473_977_418, "hMSL": 412_345
+    }})]);
+    let tpv = &reports[3];
+    assert_eq!(tpv["mode"], 1);
+    assert!(tpv.get("lat").is_none() && tpv.get("altMSL").is_none() && tpv.get("time").is_none());
+
+    assert_eq!(gpsd_prn(2, 11), 311);
+    assert_eq!(gpsd_prn(3, 20), 420);
+    assert_eq!(gpsd_prn(5, 1), 193);
+    assert_eq!(gpsd_prn(1, 133), 133);
+}
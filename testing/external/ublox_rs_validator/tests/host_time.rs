@@ -0,0 +1,72 @@
+//! Host receive times from a `ubx-capture --timestamps` CSV.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use ubx_schema::arrow::RecordBatchBuilder;
+use ubx_schema::frame::{frames, Frame};
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::output::{Format, MessageWriter};
+use ubx_schema::sqlite::SqlWriter;
+use ubx_schema::{Decoder, Schema};
+
+const CSV: &str = "offset,host_time,message\n0,1700000000.250000,UBX-NAV-PVT\n\
+                   100,1700000001.000000,UBX-NAV-PVT\n";
+
+fn log() -> Vec<u8> {
+    let pvt = Frame::new(0x01, 0x07, vec![0; 92]).to_bytes();
+    [pvt.clone(), pvt, Frame::new(0x01, 0x35, vec![0; 8]).to_bytes()].concat()
+}
+
+#[test]
+fn writers_report_host_times_by_offset() {
+    let times = HostTimes::parse(CSV).unwrap();
+    assert_eq!((times.len(), times.at(100), times.at(50)), (2, Some(1_700_000_001.0), None));
+    assert!(HostTimes::parse("offset,host_time\n0\n").is_err());
+
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let log = log();
+    let mut out = Vec::new();
+    let mut writer = MessageWriter::new(Format::Ndjson, &mut out).unwrap().with_host_times(times.clone());
+    let mut builder = RecordBatchBuilder::new(&decoder, "NAV-PVT", None).unwrap().with_host_times(times.clone());
+    let mut sql = SqlWriter::new(&schema, decoder.options(), Vec::new()).unwrap().with_host_times(times);
+    for frame in frames(&log).flatten() {
+        let msg = decoder.decode_frame(&frame);
+        writer.write(frame.offset, &msg).unwrap();
+        builder.push(frame.offset, &msg);
+        sql.write(&frame, &msg).unwrap();
+    }
+    writer.finish().unwrap();
+    let records: Vec<serde_json::Value> =
+        out.split(|&b| b == b'\n').filter(|l| !l.is_empty()).map(|l| serde_json::from_slice(l).unwrap()).collect();
+    assert_eq!(records[0]["host_time"], 1_700_000_000.25);
+    assert_eq!(records[1]["host_time"], 1_700_000_001.0);
+    // The CSV has no row for the third frame.
+    assert!(records[2].get("host_time").is_none());
+
+    let batch = builder.finish();
+    let column = batch.column("host_time").unwrap();
+    assert_eq!((column.f64_at(0), column.f64_at(1)), (Some(1_700_000_000.25), Some(1_700_000_001.0)));
+    let sql = String::from_utf8(sql.finish().unwrap()).unwrap();
+    assert!(sql.contains("itow, host_time, raw) VALUES (100, 1, 7, 'UBX-NAV-PVT', 0, 1700000001, X'"));
+    assert!(sql.contains("VALUES (200, 1, 53, 'UBX-NAV-SAT', 0, NULL, X'"));
+}
+
+#[test]
+fn decode_takes_the_timestamps_option() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let (input, csv) = (dir.join("host-time-test.ubx"), dir.join("host-time-test.csv"));
+    fs::write(&input, log()).unwrap();
+    fs::write(&csv, CSV).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ubx-decode"))
+        .arg(&input)
+        .args(["--format", "ndjson", "--timestamps"])
+        .arg(&csv)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let first: serde_json::Value = serde_json::from_slice(output.stdout.split(|&b| b == b'\n').next().unwrap()).unwrap();
+    assert_eq!((&first["offset"], &first["host_time"]), (&0.into(), &1_700_000_000.25.into()));
+}
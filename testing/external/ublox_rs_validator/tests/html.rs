@@ -0,0 +1,57 @@
+//! HTML decode reports.
+
+use serde_json::json;
+use ubx_schema::frame::{frames, Frame};
+use ubx_schema::html::{escape, HtmlReport};
+use ubx_schema::semantic::Validator;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+#[test]
+fn report_lists_frames_fields_and_errors() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let mut capture = Vec::new();
+    // Latitude off the globe raises a range warning.
+    let pvt = json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "fixType": 3, "flags": {"gnssFixOK": 1},
+        "lat": 950_000_000}});
+    capture.extend(encoder.encode_json(&pvt).unwrap().to_bytes());
+    let mut corrupt = Frame::new(0x01, 0x07, vec![0; 4]).to_bytes();
+    *corrupt.last_mut().unwrap() ^= 0xFF;
+    capture.extend(corrupt);
+    capture.extend(Frame::new(0x7F, 0x42, vec![1, 2]).to_bytes());
+
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let validator = Validator::new(&schema, None);
+    let mut report = HtmlReport::new("Bench <test> & co", 10);
+    let mut iter = frames(&capture);
+    while let Some(frame) = iter.next() {
+        match frame {
+            Ok(frame) => {
+                let msg = decoder.decode_frame(&frame);
+                report.add_frame(&frame, &msg, &validator.check(&msg));
+            }
+            Err(e) => report.add_error(iter.position() - 1, &e),
+        }
+    }
+    assert_eq!((report.frames, report.errors, report.unknown), (2, 1, 1));
+    assert!(report.warnings >= 1);
+
+    let mut page = Vec::new();
+    report.write(&mut page).unwrap();
+    let page = String::from_utf8(page).unwrap();
+    assert!(page.starts_with("<!DOCTYPE html>") && page.ends_with("</html>\n"));
+    assert!(page.contains("<title>Bench &lt;test&gt; &amp; co</title>"));
+    assert!(page.contains("<details class=\"frame warning\" data-name=\"NAV-PVT\"><summary>00000000 <b>NAV-PVT</b>"));
+    assert!(page.contains("<tr class=\"warning\"><th>lat</th><td>95</td></tr>"));
+    // The expanded flags nest a table of their members.
+    assert!(page.contains("<th>flags</th><td><details><summary>"));
+    assert!(page.contains("<div class=\"error\" data-name=\"ERROR\">00000064 checksum mismatch for 01-07"));
+    assert!(page.contains("<details class=\"frame unknown\" data-name=\"7F-42\">"));
+    assert!(page.contains("<span class=\"hdr\">b5 62 7f 42 02 00</span> 01 02 <span class=\"ck\">"));
+    assert_eq!(escape("\"a\" <'b'>"), "&quot;a&quot; &lt;&#39;b&#39;&gt;");
+}
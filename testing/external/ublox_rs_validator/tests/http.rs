@@ -0,0 +1,73 @@
+//! HTTP decode service: request parsing and the /decode and /encode endpoints.
+
+use std::io::BufReader;
+
+use serde_json::{json, Value};
+use ubx_schema::http::{DecodeService, Request};
+use ubx_schema::{DecodeOptions, Schema};
+
+fn request(raw: &[u8]) -> Request {
+    Request::read(&mut BufReader::new(raw), 1 << 20).unwrap().unwrap()
+}
+
+#[test]
+fn reads_requests_with_query_and_body() {
+    let raw = b"POST /decode?scale=1&expand_flags HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\n\
+                Connection: close\r\n\r\nb562GET / HTTP/1.1\r\n";
+    let mut reader = BufReader::new(&raw[..]);
+    let req = Request::read(&mut reader, 1 << 20).unwrap().unwrap();
+    assert_eq!((req.method.as_str(), req.path.as_str(), req.body.as_slice()), ("POST", "/decode", &b"b562"[..]));
+    assert_eq!((req.query["scale"].as_str(), req.query["expand_flags"].as_str()), ("1", ""));
+    assert_eq!(req.header("Content-Length"), Some("4"));
+    assert!(req.wants_close());
+    // The next request on the connection is cut off in its headers.
+    assert!(Request::read(&mut reader, 1 << 20).is_err());
+    assert!(Request::read(&mut BufReader::new(&b""[..]), 1 << 20).unwrap().is_none());
+
+    let big = b"POST /decode HTTP/1.1\r\nContent-Length: 100\r\n\r\n";
+    assert!(Request::read(&mut BufReader::new(&big[..]), 10).is_err());
+}
+
+#[test]
+fn encodes_and_decodes_over_the_service() {
+    let schema = Schema::load_default().unwrap();
+    let service = DecodeService::new(&schema, DecodeOptions::default());
+    let message = json!({"name": "CFG-RATE", "fields": {"measRate": 200, "navRate": 1, "timeRef": 0}});
+    let body = serde_json::to_vec(&message).unwrap();
+
+    let mut raw = format!("POST /encode HTTP/1.1\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+    raw.extend(&body);
+    let encoded = service.handle(&request(&raw));
+    assert_eq!((encoded.status, encoded.content_type), (200, "application/octet-stream"));
+    assert_eq!(&encoded.body[..4], [0xB5, 0x62, 0x06, 0x08]);
+
+    let mut raw = format!("POST /encode?format=hex HTTP/1.1\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+    raw.extend(&body);
+    let hex_frames: Value = serde_json::from_slice(&service.handle(&request(&raw)).body).unwrap();
+    let frame = hex_frames["frames"][0].as_str().unwrap().to_string();
+    assert_eq!(frame, hex::encode(&encoded.body));
+
+    // Hex with whitespace and some noise in front of the frame.
+    let text = format!("00 ff {frame}\n");
+    let raw = format!("POST /decode HTTP/1.1\r\nContent-Length: {}\r\n\r\n{text}", text.len());
+    let decoded = service.handle(&request(raw.as_bytes()));
+    assert_eq!(decoded.status, 200);
+    let decoded: Value = serde_json::from_slice(&decoded.body).unwrap();
+    assert_eq!((decoded["frames"].as_u64(), decoded["skipped_bytes"].as_u64()), (Some(1), Some(2)));
+    let msg = &decoded["messages"][0];
+    assert_eq!((msg["name"].as_str(), msg["offset"].as_u64()), (Some("UBX-CFG-RATE"), Some(2)));
+    assert_eq!(msg["fields"]["measRate"], 200);
+
+    let bad = b"POST /encode HTTP/1.1\r\nContent-Length: 2\r\n\r\n{]";
+    assert_eq!(service.handle(&request(bad)).status, 400);
+    // More SFRBX words than a UBX length field can describe is a bad request, not a crash.
+    let oversized = json!({"name": "RXM-SFRBX", "fields": {"numWords": 255, "dwrd": vec![0; 20_000]}});
+    let body = serde_json::to_vec(&oversized).unwrap();
+    let mut raw = format!("POST /encode HTTP/1.1\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+    raw.extend(&body);
+    let rejected = service.handle(&request(&raw));
+    assert_eq!(rejected.status, 400);
+    assert!(String::from_utf8_lossy(&rejected.body).contains("65535"));
+    assert_eq!(service.handle(&request(b"GET /decode HTTP/1.1\r\n\r\n")).status, 405);
+    assert_eq!(service.handle(&request(b"GET /nope HTTP/1.1\r\n\r\n")).status, 404);
+}
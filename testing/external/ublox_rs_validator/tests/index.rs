@@ -0,0 +1,85 @@
+//! Selection through a log index matches a full scan.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::json;
+use ubx_schema::filter::{Filter, ItowRange};
+use ubx_schema::index::{sidecar_path, LogIndex};
+use ubx_schema::time::parse_iso8601;
+use ubx_schema::{Encoder, Schema};
+
+/// NAV-PVT and NAV-SAT epochs, each followed by an RXM-SFRBX (no iTOW), with
+/// NMEA between them.
+fn log(schema: &Schema) -> Vec<u8> {
+    let encoder = Encoder::new(schema);
+    let mut out = b"$GPTXT,start*00\r\n".to_vec();
+    for i in 0..50u32 {
+        let itow = 100_000 + i * 1_000;
+        for msg in [
+            json!({"name": "NAV-PVT", "fields": {
+                "iTOW": itow, "year": 2024, "month": 1, "day": 15, "hour": 12, "min": 0, "sec": i % 60,
+                "valid": {"validDate": u32::from(i > 2), "validTime": 1}
+            }}),
+            json!({"name": "NAV-SAT", "fields": {"iTOW": itow, "satellites": [{"svId": 1}]}}),
+            json!({"name": "RXM-SFRBX", "fields": {"gnssId": 0, "svId": i % 32 + 1, "numWords": 0}}),
+        ] {
+            out.extend(encoder.encode_json(&msg).unwrap().to_bytes());
+        }
+        out.extend_from_slice(b"$GPGGA,junk*00\r\n");
+    }
+    out
+}
+
+#[test]
+fn indexed_selection_matches_a_full_scan() {
+    let schema = Schema::load_default().unwrap();
+    let input = log(&schema);
+    let index = LogIndex::build(&schema, &input);
+    assert_eq!(index.entries.len(), 150);
+    assert_eq!(index.entries[0].offset, 17);
+    assert_eq!((index.entries[5].class, index.entries[5].id, index.entries[5].itow), (0x02, 0x13, Some(101_000)));
+
+    let from = parse_iso8601("2024-01-15T12:00:10Z").unwrap().to_unix();
+    let window = ItowRange {
+        from: Some(110_000),
+        to: Some(120_000),
+    };
+    let filters = [
+        Filter::default(),
+        Filter::default().include(["NAV-SAT"]),
+        Filter::default().include(["RXM-SFRBX", "NAV-PVT"]).itow(window),
+        Filter::default().exclude(["NAV-PVT"]).itow(window),
+        Filter::default().utc(Some(from), Some(from + 5.0)),
+        Filter::default().include(["NAV-SAT"]).utc(None, Some(from)),
+    ];
+    for filter in &filters {
+        let scanned = filter.apply(&schema, &input);
+        assert_eq!(filter.apply_indexed(&schema, &input, &index).unwrap(), scanned, "{filter:?}");
+    }
+    assert_eq!(filters[2].apply(&schema, &input).len(), 20);
+}
+
+#[test]
+fn sidecar_round_trips_and_goes_stale() {
+    let schema = Schema::load_default().unwrap();
+    let input = log(&schema);
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("index-test.ubx");
+    fs::write(&path, &input).unwrap();
+    let _ = fs::remove_file(sidecar_path(&path));
+
+    let (index, built) = LogIndex::load_or_build(&schema, &path, &input, true).unwrap();
+    assert!(built);
+    assert_eq!(sidecar_path(&path), dir.join("index-test.ubx.idx"));
+    assert_eq!(LogIndex::read(&sidecar_path(&path)).unwrap(), index);
+    assert!(!LogIndex::load_or_build(&schema, &path, &input, true).unwrap().1);
+
+    let mut changed = input.clone();
+    changed.truncate(input.len() - 16);
+    assert!(!index.matches(&changed));
+    let (rebuilt, built) = LogIndex::load_or_build(&schema, &path, &changed, false).unwrap();
+    assert!(built && rebuilt.entries.len() == index.entries.len());
+    assert!(LogIndex::from_bytes(&index.to_bytes()[..40]).is_err());
+    assert!(LogIndex::from_bytes(b"not an index at all, really not one").is_err());
+}
@@ -0,0 +1,68 @@
+//! Line protocol from encoded NAV-PVT and NAV-SAT frames.
+
+use serde_json::json;
+use ubx_schema::influx::LineEncoder;
+use ubx_schema::output::{Format, MessageWriter};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Encoder, Schema};
+
+fn decode(messages: &[serde_json::Value]) -> Vec<DecodedMessage> {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = Format::Influx.decode_options(DecodeOptions::default());
+    let decoder = Decoder::with_options(&schema, options);
+    messages
+        .iter()
+        .map(|m| {
+            let frame = encoder.encode_json(m).unwrap();
+            decoder.decode(frame.class, frame.id, &frame.payload)
+        })
+        .collect()
+}
+
+#[test]
+fn writes_tagged_lines_with_utc_timestamps() {
+    let messages = decode(&[
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 999, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45, "elev": 60, "azim": 120, "prRes": -12}
+        ]}}),
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": 1000, "year": 2024, "month": 1, "day": 15, "hour": 12, "min": 0, "sec": 1,
+            "valid": {"validDate": 1, "validTime": 1}, "fixType": 3, "flags": {"gnssFixOK": 1, "carrSoln": 1},
+            "numSV": 20, "lat": 473_977_418
+        }}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "version": 1, "satellites": [
+            {"gnssId": 2, "svId": 11, "cno": 38, "elev": 20, "azim": 300, "prRes": 5}
+        ]}}),
+    ]);
+    let mut encoder = LineEncoder::new();
+    // No UTC yet: the database assigns the time.
+    let first = encoder.lines(&messages[0]);
+    assert_eq!(first[0], "NAV-SAT iTOW=999i,version=1i,numSvs=1i");
+    assert_eq!(
+        first[1],
+        "NAV-SAT,group=satellites,gnss=GPS,sv=12 gnssId=0i,svId=12i,cno=45i,elev=60i,azim=120i,prRes=-1.2,flags=0i"
+    );
+
+    let pvt = encoder.lines(&messages[1]);
+    assert_eq!(pvt.len(), 1);
+    assert!(pvt[0].starts_with("NAV-PVT,fix=rtk_float iTOW=1000i,year=2024i,"), "{}", pvt[0]);
+    assert!(pvt[0].contains(",valid.validDate=1i,") && pvt[0].contains(",lat=47.3977418,"));
+    assert!(pvt[0].ends_with(" 1705320001000000000"), "{}", pvt[0]);
+
+    let sat = encoder.lines(&messages[2]);
+    assert!(sat[1].starts_with("NAV-SAT,group=satellites,gnss=Galileo,sv=11 "));
+    assert!(sat.iter().all(|l| l.ends_with(" 1705320001000000000")));
+}
+
+#[test]
+fn format_writes_one_line_per_record() {
+    let messages = decode(&[json!({"name": "NAV-CLOCK", "fields": {"iTOW": 5000, "clkB": -250, "tAcc": 12}})]);
+    let mut out = Vec::new();
+    let mut writer = MessageWriter::new("influx".parse().unwrap(), &mut out).unwrap();
+    writer.write(0, &messages[0]).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "NAV-CLOCK iTOW=5000i,clkB=-250i,clkD=0i,tAcc=12i,fAcc=0i\n"
+    );
+}
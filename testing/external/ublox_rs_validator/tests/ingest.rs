@@ -0,0 +1,99 @@
+//! Captures are split, de-duplicated, tagged and filed as corpus vectors.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde_json::json;
+use ubx_schema::filter::Filter;
+use ubx_schema::ingest::{split, vector_for};
+use ubx_schema::{Decoder, Encoder, Frame, Schema};
+
+fn mon_ver(encoder: &Encoder, model: &str, firmware: &str) -> Vec<u8> {
+    encoder
+        .encode_json(&json!({
+            "name": "UBX-MON-VER",
+            "fields": {
+                "swVersion": "EXT CORE 1.00",
+                "hwVersion": "00190000",
+                "extensionStrings": [
+                    {"extension": format!("FWVER={firmware}")},
+                    {"extension": "PROTVER=27.31"},
+                    {"extension": format!("MOD={model}")},
+                ],
+            },
+        }))
+        .unwrap()
+        .to_bytes()
+}
+
+fn nav_status(i_tow: u32) -> Vec<u8> {
+    let mut payload = vec![0u8; 16];
+    payload[..4].copy_from_slice(&i_tow.to_le_bytes());
+    Frame::new(0x01, 0x03, payload).to_bytes()
+}
+
+#[test]
+fn splits_deduplicates_and_tags_capture() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let encoder = Encoder::new(&schema);
+
+    let mut capture = nav_status(0);
+    capture.extend(mon_ver(&encoder, "ZED-F9P", "HPG 1.32"));
+    for i_tow in 1..=6 {
+        if i_tow == 4 {
+            capture.extend(mon_ver(&encoder, "NEO-M9N", "SPG 4.04"));
+        }
+        capture.extend(nav_status(i_tow * 1000));
+    }
+    capture.extend(nav_status(1000));
+    capture.extend(Frame::new(0x7f, 0x7f, vec![1, 2, 3]).to_bytes());
+    capture.extend(nav_status(7000));
+
+    let known: HashSet<Vec<u8>> = [nav_status(6000)].into();
+    let (samples, stats) = split(&decoder, &capture, 3, &Filter::default().include(["NAV-STATUS"]), &known);
+    assert_eq!((stats.frames, stats.unknown, stats.duplicates, stats.surplus), (12, 1, 2, 4));
+
+    let picked: Vec<(u32, String)> = samples
+        .iter()
+        .map(|s| {
+            assert_eq!(s.name, "NAV-STATUS");
+            let i_tow = u32::from_le_bytes(s.frame.payload[..4].try_into().unwrap());
+            (i_tow, s.receiver.as_ref().unwrap().slug())
+        })
+        .collect();
+    assert_eq!(
+        picked,
+        [(0, "zed-f9p-hpg-1.32"), (2000, "zed-f9p-hpg-1.32"), (4000, "neo-m9n-spg-4.04")].map(|(t, s)| (t, s.to_string()))
+    );
+    assert_eq!(samples[0].receiver.as_ref().unwrap().to_string(), "ZED-F9P HPG 1.32 (protocol 27.31)");
+}
+
+#[test]
+fn files_vectors_under_receiver_slug() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    let encoder = Encoder::new(&schema);
+    let mut capture = mon_ver(&encoder, "ZED-F9P", "HPG 1.32");
+    capture.extend(nav_status(1000));
+    capture.extend(nav_status(2000));
+
+    let (samples, _) = split(&decoder, &capture, 3, &Filter::default(), &HashSet::new());
+    let corpus = Path::new("/nonexistent/corpus");
+    let mut taken = HashSet::new();
+    let filed: Vec<String> = samples
+        .iter()
+        .map(|s| {
+            let (path, vector) = vector_for(&decoder, s, "drive.ubx", corpus, &mut taken).unwrap();
+            assert_eq!(vector.receiver, s.receiver);
+            assert_eq!(vector.bytes().unwrap(), s.frame.raw);
+            path.strip_prefix(corpus).unwrap().display().to_string()
+        })
+        .collect();
+    assert_eq!(
+        filed,
+        ["MON-VER/zed-f9p-hpg-1.32-1.json", "NAV-STATUS/zed-f9p-hpg-1.32-1.json", "NAV-STATUS/zed-f9p-hpg-1.32-2.json"]
+    );
+    let (_, vector) = vector_for(&decoder, &samples[2], "drive.ubx", corpus, &mut taken).unwrap();
+    assert_eq!(vector.description, "Captured from ZED-F9P HPG 1.32 (protocol 27.31) (drive.ubx@162)");
+}
@@ -0,0 +1,55 @@
+//! Fault injection: rate parsing, determinism and the damage done.
+
+use ubx_schema::frame::{frames, Frame, FrameIter};
+use ubx_schema::inject::{FaultInjector, FaultRates};
+
+fn stream() -> Vec<u8> {
+    (0..50u8).flat_map(|i| Frame::new(0x01, 0x07, vec![i; 20]).to_bytes()).collect()
+}
+
+#[test]
+fn parses_rates() {
+    let rates: FaultRates = "flip=0.01, dup=0.5,garbage=1".parse().unwrap();
+    assert_eq!(rates, FaultRates { flip: 0.01, truncate: 0.0, duplicate: 0.5, garbage: 1.0 });
+    assert_eq!(rates.to_string().parse::<FaultRates>().unwrap(), rates);
+    assert!("".parse::<FaultRates>().unwrap().is_none());
+    assert!("flip=2".parse::<FaultRates>().is_err());
+    assert!("reorder=0.1".parse::<FaultRates>().is_err());
+    assert!("flip".parse::<FaultRates>().is_err());
+}
+
+#[test]
+fn injects_at_rates_and_reproducibly() {
+    let data = stream();
+    let inject = |rates: &str, seed| {
+        let mut injector = FaultInjector::new(rates.parse().unwrap(), seed);
+        let out = injector.buffer(&data);
+        (out, injector.counts)
+    };
+
+    let (out, counts) = inject("duplicate=1", 0);
+    assert_eq!((counts.frames, counts.duplicated), (50, 50));
+    assert_eq!(frames(&out).flatten().count(), 100);
+
+    // Garbage between frames costs nothing once the scanner resyncs.
+    let (out, counts) = inject("garbage=1", 0);
+    assert_eq!(counts.garbage, 50);
+    assert_eq!(out.len(), data.len() + counts.garbage_bytes);
+    assert_eq!(frames(&out).flatten().count(), 50);
+
+    // Every frame damaged: none survive, and the scanner sees the errors.
+    let (out, counts) = inject("flip=1", 7);
+    assert_eq!(counts.flipped, 50);
+    let mut iter = FrameIter::new(&out);
+    assert_eq!(iter.by_ref().flatten().count(), 0);
+    assert!(iter.stats().checksum_errors > 0);
+
+    let (out, counts) = inject("truncate=1", 0);
+    assert_eq!(counts.truncated, 50);
+    assert!(out.len() < data.len());
+
+    let mixed = "flip=0.2,truncate=0.1,duplicate=0.1,garbage=0.2";
+    assert_eq!(inject(mixed, 3), inject(mixed, 3));
+    assert_ne!(inject(mixed, 3).0, inject(mixed, 4).0);
+    assert_eq!(inject("", 3).0, data);
+}
@@ -0,0 +1,90 @@
+//! Jamming/spoofing timelines from MON-RF, SEC-SIG and MON-SPAN.
+
+use serde_json::json;
+use ubx_schema::interference::{Indicator, InterferenceMonitor, InterferenceReport};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn monitor(messages: &[serde_json::Value]) -> InterferenceReport {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut monitor = InterferenceMonitor::new();
+    for (i, m) in messages.iter().enumerate() {
+        let frame = encoder.encode_json(m).unwrap();
+        monitor.push(100 * i, &decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    monitor.finish()
+}
+
+#[test]
+fn state_changes_of_mon_rf_and_sec_sig() {
+    let pvt = |itow: u32| json!({"name": "NAV-PVT", "fields": {"iTOW": itow}});
+    let rf = |state: u8| {
+        json!({"name": "MON-RF", "fields": {"version": 0, "nBlocks": 2, "rfBlocks": [
+            {"blockId": 0, "flags": state}, {"blockId": 1, "flags": 1}
+        ]}})
+    };
+    let sec = |jam: u8, spf: u8| {
+        json!({"name": "SEC-SIG", "fields": {"version": 1,
+            "jamFlags": {"jamDetEnabled": 1, "jamState": jam}, "spfFlags": {"spfDetEnabled": 1, "spfState": spf}}})
+    };
+    let messages = [
+        pvt(1_000),
+        rf(1),
+        sec(1, 1),
+        pvt(2_000),
+        rf(2),
+        sec(2, 1),
+        pvt(3_000),
+        rf(2),
+        sec(1, 3),
+    ];
+    let report = monitor(&messages);
+
+    let changes: Vec<_> = report
+        .changes
+        .iter()
+        .map(|c| (c.offset, c.itow, c.source.as_str(), c.indicator, c.from, c.label))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (100, Some(1_000), "MON-RF block 0", Indicator::Jamming, None, "ok"),
+            (100, Some(1_000), "MON-RF block 1", Indicator::Jamming, None, "ok"),
+            (200, Some(1_000), "SEC-SIG", Indicator::Jamming, None, "ok"),
+            (200, Some(1_000), "SEC-SIG", Indicator::Spoofing, None, "none"),
+            (400, Some(2_000), "MON-RF block 0", Indicator::Jamming, Some(1), "warning"),
+            (500, Some(2_000), "SEC-SIG", Indicator::Jamming, Some(1), "warning"),
+            (800, Some(3_000), "SEC-SIG", Indicator::Jamming, Some(2), "ok"),
+            (800, Some(3_000), "SEC-SIG", Indicator::Spoofing, Some(1), "affirmed"),
+        ]
+    );
+    assert_eq!(report.events().count(), 3);
+    assert_eq!(report.counts["MON-RF block 0 jamming"]["warning"], 2);
+    assert_eq!(report.counts["SEC-SIG spoofing"]["none"], 2);
+}
+
+#[test]
+fn mon_span_peak_marks_narrowband_interferer() {
+    let mut spectrum = vec![40; 256];
+    spectrum[160] = 75;
+    let span = json!({"name": "MON-SPAN", "fields": {"version": 0, "numRfBlocks": 1, "rfBlockData": [{
+        "spectrum": spectrum, "span": 128_000_000, "res": 500_000, "center": 1_575_420_000, "pga": 54
+    }]}});
+    let flat = json!({"name": "MON-SPAN", "fields": {"version": 0, "numRfBlocks": 1, "rfBlockData": [{
+        "spectrum": vec![40; 256], "span": 128_000_000, "res": 500_000, "center": 1_575_420_000, "pga": 54
+    }]}});
+    let report = monitor(&[flat, span]);
+
+    assert_eq!(report.spectra.len(), 2);
+    assert!(!report.spectra[0].narrowband);
+    let s = &report.spectra[1];
+    assert!(s.narrowband);
+    assert_eq!((s.offset, s.block, s.pga_db), (100, 0, 54));
+    assert_eq!((s.peak_db, s.median_db), (75.0, 40.0));
+    assert_eq!(s.peak_hz, 1_575_420_000.0 + 32.0 * 500_000.0);
+}
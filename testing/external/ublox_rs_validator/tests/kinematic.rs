@@ -0,0 +1,73 @@
+//! Kinematic plausibility of successive NAV-PVT solutions.
+
+use serde_json::json;
+use ubx_schema::geo::WGS84_A;
+use ubx_schema::kinematic::{Bounds, KinematicChecker};
+use ubx_schema::semantic::Rule;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+/// NAV-PVT `north` metres north of 47.3977418° N, moving north at `vel_n` mm/s.
+fn pvt(itow: u32, north: f64, vel_n: i64) -> serde_json::Value {
+    let lat = 473_977_418.0 + (north / WGS84_A).to_degrees() * 1e7;
+    json!({"name": "NAV-PVT", "fields": {
+        "iTOW": itow, "fixType": 3, "flags": {"gnssFixOK": 1}, "lon": 85_123_456, "lat": lat.round() as i64,
+        "velN": vel_n, "velE": 0, "gSpeed": vel_n.abs(), "hAcc": 1_500, "sAcc": 200
+    }})
+}
+
+fn check(messages: &[serde_json::Value]) -> Vec<(u32, String)> {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut checker = KinematicChecker::new(Bounds::default());
+    let mut warnings = Vec::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        let msg = decoder.decode(frame.class, frame.id, &frame.payload);
+        for w in checker.push(&msg) {
+            assert_eq!(w.rule, Rule::Kinematic);
+            warnings.push((msg.itow().unwrap(), w.field));
+        }
+    }
+    warnings
+}
+
+#[test]
+fn steady_motion_is_plausible() {
+    // 10 m/s north at 1 Hz, then 5 Hz, then across a 10 s outage.
+    let mut messages: Vec<_> = (0..5).map(|i| pvt(1_000 * i, 10.0 * i as f64, 10_000)).collect();
+    messages.extend((1..5).map(|i| pvt(4_000 + 200 * i, 40.0 + 2.0 * i as f64, 10_000)));
+    messages.push(pvt(15_000, 500.0, 10_000));
+    assert_eq!(check(&messages), []);
+}
+
+#[test]
+fn flags_jumps_speed_and_acceleration() {
+    let messages = [
+        pvt(1_000, 0.0, 10_000),
+        pvt(2_000, 10.0, 10_000),
+        // 40 m further than 10 m/s explains.
+        pvt(3_000, 60.0, 10_000),
+        pvt(4_000, 70.0, 10_000),
+        // From 10 to 70 m/s in one second, moving consistently.
+        pvt(5_000, 110.0, 70_000),
+        pvt(6_000, 180.0, 70_000),
+        // Faster than the export limit, and a jump on top.
+        pvt(7_000, 800.0, 600_000),
+    ];
+    let warnings = check(&messages);
+    let expected = [
+        (3_000, "lat/lon"),
+        (5_000, "velN/velE"),
+        (7_000, "velN/velE"),
+        (7_000, "velN/velE"),
+        (7_000, "lat/lon"),
+    ];
+    let warnings: Vec<(u32, &str)> = warnings.iter().map(|(t, f)| (*t, f.as_str())).collect();
+    assert_eq!(warnings, expected);
+}
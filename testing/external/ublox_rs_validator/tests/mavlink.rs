@@ -0,0 +1,112 @@
+//! MAVLink packets from encoded NAV-DOP, NAV-PVT and NAV-SAT frames.
+
+use serde_json::json;
+use ubx_schema::mavlink::{
+    crc_x25, parse_packet, GpsBridge, GpsRawInt, PacketWriter, Version, GPS_FIX_TYPE_RTK_FIXED, GPS_RAW_INT,
+    GPS_STATUS, HEARTBEAT, MAV_TYPE_GPS,
+};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Encoder, Schema};
+
+fn decode(messages: &[serde_json::Value]) -> Vec<DecodedMessage> {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    messages
+        .iter()
+        .map(|m| {
+            let frame = encoder.encode_json(m).unwrap();
+            decoder.decode(frame.class, frame.id, &frame.payload)
+        })
+        .collect()
+}
+
+fn epoch(itow: u32) -> Vec<DecodedMessage> {
+    decode(&[
+        json!({"name": "NAV-DOP", "fields": {"iTOW": itow, "hDOP": 87, "vDOP": 132}}),
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": itow, "year": 2024, "month": 1, "day": 15, "hour": 12, "min": 0, "sec": 1,
+            "valid": {"validDate": 1, "validTime": 1}, "fixType": 3,
+            "flags": {"gnssFixOK": 1, "diffSoln": 1, "carrSoln": 2}, "numSV": 18,
+            "lon": 85_455_445, "lat": 473_977_418, "height": 452_000, "hMSL": 404_000,
+            "hAcc": 14, "vAcc": 21, "gSpeed": 1_234, "headMot": 27_050_000, "sAcc": 50, "headAcc": 1_500_000
+        }}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": itow, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45, "elev": 60, "azim": 180, "flags": 8},
+            {"gnssId": 6, "svId": 3, "cno": 30, "elev": -5, "azim": 90}
+        ]}}),
+    ])
+}
+
+#[test]
+fn translates_an_epoch_into_gps_packets() {
+    // CRC-16/MCRF4XX check value.
+    assert_eq!(crc_x25(b"123456789", 0xFFFF), 0x6F91);
+
+    let mut bridge = GpsBridge::new(PacketWriter::new(Version::V2, 1, 220));
+    let packets: Vec<_> = epoch(1_000).iter().flat_map(|m| bridge.translate(m)).collect();
+    let parsed: Vec<_> = packets
+        .iter()
+        .map(|p| {
+            let (packet, len) = parse_packet(p).unwrap();
+            assert_eq!(len, p.len());
+            packet
+        })
+        .collect();
+    let ids: Vec<u32> = parsed.iter().map(|p| p.msg_id).collect();
+    assert_eq!(ids, [HEARTBEAT, GPS_RAW_INT, GPS_STATUS]);
+    assert_eq!(parsed.iter().map(|p| p.seq).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!((parsed[0].system_id, parsed[0].component_id, parsed[0].payload[4]), (1, 220, MAV_TYPE_GPS));
+
+    let raw = GpsRawInt::from_payload(&parsed[1].payload).unwrap();
+    assert_eq!(raw.time_usec, 1_705_320_001_000_000);
+    assert_eq!((raw.lat, raw.lon, raw.alt, raw.alt_ellipsoid), (473_977_418, 85_455_445, 404_000, 452_000));
+    assert_eq!((raw.eph, raw.epv, raw.vel, raw.cog), (87, 132, 123, 27_050));
+    assert_eq!((raw.fix_type, raw.satellites_visible), (GPS_FIX_TYPE_RTK_FIXED, 18));
+    assert_eq!((raw.h_acc, raw.v_acc, raw.vel_acc, raw.hdg_acc, raw.yaw), (14, 21, 50, 1_500_000, 0));
+
+    let status = &parsed[2].payload;
+    assert_eq!(status[0], 2);
+    // GLONASS R03 is PRN 67.
+    assert_eq!((status[1], status[2]), (12, 67));
+    assert_eq!((status[21], status[22]), (1, 0));
+    assert_eq!((status[41], status[42]), (60, 0));
+    assert_eq!((status[61], status[81]), (128, 45));
+
+    // Heartbeats are once a second.
+    let next: Vec<_> = epoch(1_200).iter().flat_map(|m| bridge.translate(m)).collect();
+    assert_eq!(next.len(), 2);
+    let next: Vec<_> = epoch(2_000).iter().flat_map(|m| bridge.translate(m)).collect();
+    assert_eq!(parse_packet(&next[0]).unwrap().0.msg_id, HEARTBEAT);
+}
+
+#[test]
+fn mavlink1_packets_leave_out_extensions() {
+    let mut writer = PacketWriter::new(Version::V1, 7, 220);
+    let raw = GpsRawInt {
+        lat: -1,
+        alt_ellipsoid: 99,
+        fix_type: 3,
+        ..GpsRawInt::default()
+    };
+    let packet = writer.packet(GPS_RAW_INT, &raw.payload());
+    assert_eq!(&packet[..6], &[0xFE, 30, 0, 7, 220, 24]);
+    assert_eq!(packet.len(), 6 + 30 + 2);
+    let (parsed, _) = parse_packet(&packet).unwrap();
+    let decoded = GpsRawInt::from_payload(&parsed.payload).unwrap();
+    assert_eq!((decoded.lat, decoded.fix_type, decoded.alt_ellipsoid), (-1, 3, 0));
+
+    let mut corrupted = packet.clone();
+    corrupted[10] ^= 1;
+    assert!(parse_packet(&corrupted).is_none());
+
+    // MAVLink 2 drops trailing zeros from the payload.
+    let packet = PacketWriter::new(Version::V2, 7, 220).packet(GPS_RAW_INT, &raw.payload());
+    assert_eq!(packet[1], 31);
+    let (parsed, _) = parse_packet(&packet).unwrap();
+    assert_eq!(GpsRawInt::from_payload(&parsed.payload).unwrap(), raw);
+}
@@ -0,0 +1,81 @@
+//! Merging logs by iTOW and by host receive time.
+
+use serde_json::json;
+use ubx_schema::host_time::HostTimes;
+use ubx_schema::merge::{merge, Order, Source};
+use ubx_schema::{Encoder, Frame, Schema};
+
+fn pvt(encoder: &Encoder, itow: u32) -> Vec<u8> {
+    encoder
+        .encode_json(&json!({"name": "NAV-PVT", "fields": {"iTOW": itow}}))
+        .unwrap()
+        .to_bytes()
+}
+
+fn ack() -> Vec<u8> {
+    Frame::new(0x05, 0x01, vec![0x06, 0x8A]).to_bytes()
+}
+
+fn source(label: &str, data: Vec<u8>) -> Source {
+    Source {
+        label: label.to_string(),
+        data,
+        host_times: None,
+    }
+}
+
+#[test]
+fn orders_by_itow_across_the_week_rollover() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let week_end = 604_800_000;
+    // The rover's ACK belongs to the epoch before it; the leading ACK to the first epoch.
+    let rover = [ack(), pvt(&encoder, week_end - 1_000), ack(), pvt(&encoder, 0)].concat();
+    let base = [pvt(&encoder, week_end - 1_000), pvt(&encoder, week_end - 500), pvt(&encoder, 500)].concat();
+    let sources = [source("rover", rover), source("base", base)];
+
+    let merged = merge(&schema, &sources, Order::Itow).unwrap();
+    let order: Vec<(usize, u8, Option<f64>)> = merged.iter().map(|m| (m.source, m.frame.class, m.time)).collect();
+    let t = |ms: u32| Some(f64::from(ms) / 1_000.0);
+    assert_eq!(
+        order,
+        [
+            (0, 0x05, t(week_end - 1_000)),
+            (0, 0x01, t(week_end - 1_000)),
+            (0, 0x05, t(week_end - 1_000)),
+            (1, 0x01, t(week_end - 1_000)),
+            (1, 0x01, t(week_end - 500)),
+            (0, 0x01, t(week_end)),
+            (1, 0x01, t(week_end + 500)),
+        ]
+    );
+    assert_eq!(merged.iter().map(|m| m.frame.raw.len()).sum::<usize>(), 2 * 10 + 5 * 100);
+}
+
+#[test]
+fn orders_by_host_time_from_capture_timestamps() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let a = [pvt(&encoder, 1_000), pvt(&encoder, 2_000)].concat();
+    let b = [ack(), pvt(&encoder, 1_000)].concat();
+
+    let times_a = HostTimes::parse("offset,host_time,message\n0,100.50,UBX-NAV-PVT\n100,101.50,UBX-NAV-PVT\n").unwrap();
+    assert_eq!(times_a, [(0, 100.5), (100, 101.5)].into_iter().collect::<HostTimes>());
+    let times_b = [(0, 100.75), (10, 101.0)].into_iter().collect::<HostTimes>();
+    let sources = [
+        Source {
+            host_times: Some(times_a),
+            ..source("port1", a)
+        },
+        Source {
+            host_times: Some(times_b),
+            ..source("port2", b)
+        },
+    ];
+    let merged = merge(&schema, &sources, Order::Host).unwrap();
+    let order: Vec<(usize, usize)> = merged.iter().map(|m| (m.source, m.frame.offset)).collect();
+    assert_eq!(order, [(0, 0), (1, 0), (1, 10), (0, 100)]);
+
+    assert!(merge(&schema, &[source("bare", ack())], Order::Host).is_err());
+    assert!(HostTimes::parse("offset,host_time\nx,1\n").is_err());
+}
@@ -0,0 +1,68 @@
+//! AssistNow Offline splitting, selection and MGA-ACK matching.
+
+use ubx_schema::gnss::Gnss;
+use ubx_schema::mga::{parse_mga_ack, split, Selection};
+use ubx_schema::time::parse_iso8601;
+use ubx_schema::{Error, Frame, FrameError, Schema};
+
+fn ano(gnss_id: u8, sv_id: u8, day: u8) -> Frame {
+    let mut payload = vec![0x00, 0x00, sv_id, gnss_id, 24, 1, day, 0];
+    payload.extend([0xAA; 68]);
+    Frame::new(0x13, 0x20, payload)
+}
+
+#[test]
+fn splits_and_selects_by_day_and_constellation() {
+    let schema = Schema::load_default().unwrap();
+    let mut file = Vec::new();
+    for day in [15, 16, 17] {
+        for (gnss, sv) in [(0, 3), (2, 11), (6, 4)] {
+            file.extend(ano(gnss, sv, day).to_bytes());
+        }
+    }
+    file.extend(Frame::new(0x01, 0x07, vec![0; 92]).to_bytes());
+
+    let frames = split(&schema, &file).unwrap();
+    assert_eq!(frames.len(), 9);
+    assert_eq!(frames[1].name, "MGA-ANO");
+    assert_eq!(frames[1].gnss(), Some(Gnss::Galileo));
+    assert_eq!(frames[1].sv_id, Some(11));
+    assert_eq!(frames[1].date.unwrap().to_iso8601(), "2024-01-15T00:00:00.000Z");
+
+    let from_16th = Selection {
+        days: Some((parse_iso8601("2024-01-16").unwrap(), 2)),
+        gnss: vec![Gnss::Gps, Gnss::Glonass],
+    };
+    let kept: Vec<(u8, u8)> = frames
+        .iter()
+        .filter(|f| from_16th.matches(f))
+        .map(|f| (f.date.unwrap().day, f.gnss_id.unwrap()))
+        .collect();
+    assert_eq!(kept, [(16, 0), (16, 6), (17, 0), (17, 6)]);
+    assert!(frames.iter().all(|f| Selection::default().matches(f)));
+
+    let mut corrupt = ano(0, 3, 15).to_bytes();
+    *corrupt.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        split(&schema, &corrupt),
+        Err(Error::Frame(FrameError::Checksum { .. }))
+    ));
+}
+
+#[test]
+fn matches_mga_ack_to_the_sent_frame() {
+    let sent = ano(2, 11, 15);
+    let ack = |kind: u8, info: u8, start: [u8; 4]| {
+        let mut payload = vec![kind, 0, info, 0x20];
+        payload.extend(start);
+        parse_mga_ack(&Frame::new(0x13, 0x60, payload)).unwrap()
+    };
+
+    let accepted = ack(1, 0, [0, 0, 11, 2]);
+    assert!(accepted.accepted && accepted.acknowledges(&sent));
+    assert!(!ack(1, 0, [0, 0, 12, 2]).acknowledges(&sent));
+    let rejected = ack(0, 1, [0, 0, 11, 2]);
+    assert!(!rejected.accepted && rejected.acknowledges(&sent));
+    assert!(rejected.info().contains("time"));
+    assert_eq!(parse_mga_ack(&Frame::new(0x05, 0x01, vec![0x13, 0x20])), None);
+}
@@ -0,0 +1,95 @@
+//! Legacy CFG message migration to configuration keys.
+
+use ubx_schema::config::ConfigKeys;
+use ubx_schema::migrate::{read_config_frames, Migrator};
+use ubx_schema::schema::fields_extent;
+use ubx_schema::{DecodeOptions, Decoder, Frame, Schema};
+
+const SAVED: &str = "\
+MON-VER - 0A 04 00 00
+CFG-RATE - 06 08 06 00 E8 03 01 00 01 00
+CFG-RATE - 06 08 06 00 C8 00 01 00 01 00
+CFG-MSG - 06 01 08 00 01 07 00 01 00 01 00 00
+CFG-MSG - 06 01 08 00 01 FF 00 01 00 00 00 00
+CFG-PRT - 06 00 14 00 01 00 00 00 C0 08 00 00 00 C2 01 00 07 00 01 00 00 00 00 00
+CFG-PRT - 06 00 14 00 03 00 00 00 00 00 00 00 00 00 00 00 01 00 01 00 00 00 00 00
+CFG-PRT - 06 00 14 00 02 00 00 00 80 24 00 00 80 25 00 00 01 00 01 00 00 00 00 00
+CFG-NAV5 - 06 24 24 00 01 00 04 03 00 00 00 00 10 27 00 00 05 00 FA 00 FA 00 64 00 2C 01 00 3C \
+           00 00 00 00 00 00 00 00 00 00 00 00
+";
+
+#[test]
+fn migrates_saved_configuration() {
+    let schema = Schema::load_default().unwrap();
+    let db = ConfigKeys::load_default().unwrap();
+    let frames = read_config_frames(SAVED.as_bytes()).unwrap();
+    assert_eq!(frames.len(), 9);
+    // The same frames as a capture.
+    let capture: Vec<u8> = frames.iter().flat_map(Frame::to_bytes).collect();
+    assert_eq!(read_config_frames(&capture).unwrap(), frames);
+
+    let mut migrator = Migrator::new(&schema, &db);
+    let set: Vec<usize> = frames.iter().map(|f| migrator.push(f)).collect();
+    assert_eq!(set, [0, 3, 3, 5, 0, 10, 6, 9, 1]);
+    let migration = migrator.finish();
+    assert_eq!(migration.frames, 8);
+
+    let value = |name: &str| {
+        let kv = migration.values.iter().find(|kv| kv.name == name)?;
+        db.get(name).unwrap().decode_value(&kv.value, false).ok()
+    };
+    // The later CFG-RATE wins.
+    assert_eq!(value("CFG-RATE-MEAS"), Some(200.into()));
+    assert_eq!(value("CFG-RATE-TIMEREF"), Some("GPS".into()));
+    assert_eq!(value("CFG-MSGOUT-UBX_NAV_PVT_UART1"), Some(1.into()));
+    assert_eq!(value("CFG-MSGOUT-UBX_NAV_PVT_I2C"), Some(0.into()));
+    assert_eq!(value("CFG-UART1-BAUDRATE"), Some(115_200.into()));
+    assert_eq!(value("CFG-UART1-PARITY"), Some("NONE".into()));
+    assert_eq!(value("CFG-UART1OUTPROT-NMEA"), Some(false.into()));
+    assert_eq!(value("CFG-USBINPROT-UBX"), Some(true.into()));
+    // UART2 asks for 7 data bits, 2 stop bits and a reserved parity; USB
+    // has no UART settings.
+    assert_eq!(value("CFG-UART2-DATABITS"), Some("SEVEN".into()));
+    assert_eq!(value("CFG-UART2-STOPBITS"), Some("TWO".into()));
+    assert_eq!(value("CFG-UART2-PARITY"), None);
+    assert_eq!(value("CFG-USB-BAUDRATE"), None);
+    // Only the dynamic model is in CFG-NAV5's mask.
+    assert_eq!(value("CFG-NAVSPG-DYNMODEL"), Some("AUTOMOT".into()));
+    assert_eq!(value("CFG-NAVSPG-FIXMODE"), None);
+    let skipped = [
+        "CFG-MSG: message 01-FF is not in the schema",
+        "CFG-PRT.mode.parity: 2 has no equivalent for CFG-UART2-PARITY",
+    ];
+    assert_eq!(migration.skipped, skipped.map(String::from));
+}
+
+#[test]
+fn mappings_name_real_fields_and_keys() {
+    let schema = Schema::load_default().unwrap();
+    let db = ConfigKeys::load_default().unwrap();
+    let options = DecodeOptions { scale: false, expand_flags: true, include_reserved: false };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut mapped = 0;
+    for msg in &schema.messages {
+        let Some(migration) = &msg.config_migration else {
+            continue;
+        };
+        let payload = vec![0; fields_extent(&msg.payload.as_ref().unwrap().fields)];
+        let fields = decoder.decode(msg.class_id, msg.message_id, &payload).fields;
+        let ports: Vec<String> = migration.port.iter().flat_map(|p| p.names.iter().flatten().cloned()).collect();
+        for key in &migration.keys {
+            let name = msg.short_name();
+            assert!(fields.get_path(&key.field).is_some(), "{name}: no field {}", key.field);
+            if let Some(flag) = &key.when {
+                assert!(fields.get_path(flag).is_some(), "{name}: no flag {flag}");
+            }
+            let ports: &Vec<String> = if key.ports.is_empty() { &ports } else { &key.ports };
+            for port in ports.iter().map(String::as_str).chain(ports.is_empty().then_some("")) {
+                let full = key.key.replace("{port}", port).replace("{message}", "UBX_NAV_PVT");
+                assert!(db.get(&full).is_some(), "{name}.{}: no key {full}", key.field);
+                mapped += 1;
+            }
+        }
+    }
+    assert!(mapped >= 60, "{mapped}");
+}
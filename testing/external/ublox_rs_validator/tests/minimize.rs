@@ -0,0 +1,60 @@
+//! Minimized captures are as short as the failure allows and keep the failure chosen.
+
+use serde_json::json;
+use ubx_schema::frame::frames;
+use ubx_schema::minimize::{failures, minimize, FailureKind};
+use ubx_schema::{Encoder, Frame, Schema};
+
+fn capture(schema: &Schema, middle: &[u8]) -> Vec<u8> {
+    let encoder = Encoder::new(schema);
+    let mut data = b"\x00\xFFnoise".to_vec();
+    for itow in [1000, 2000, 3000] {
+        let pvt = json!({"name": "UBX-NAV-PVT", "fields": {"iTOW": itow, "fixType": 3, "numSV": 12}});
+        data.extend(encoder.encode_json(&pvt).unwrap().to_bytes());
+        if itow == 2000 {
+            data.extend(middle);
+        }
+    }
+    data.extend(b"\xB5\x62\x01");
+    data
+}
+
+#[test]
+fn shrinks_to_the_bytes_the_test_needs() {
+    let schema = Schema::load_default().unwrap();
+    let data = capture(&schema, &[]);
+
+    // Any valid NAV-PVT frame whose payload has byte 20 (fixType) 3: the
+    // minimum is one frame with a 21-byte payload.
+    let fails = |bytes: &[u8]| {
+        frames(bytes)
+            .flatten()
+            .any(|f| (f.class, f.id) == (0x01, 0x07) && f.payload.get(20) == Some(&3))
+    };
+    let minimized = minimize(&data, fails).unwrap();
+    let frame = frames(&minimized).next().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.raw.len()), (0, minimized.len()));
+    assert_eq!(frame.payload.len(), 21);
+    assert_eq!(frame.payload[20], 3);
+
+    assert_eq!(minimize(b"\xB5\x62", fails), None);
+}
+
+#[test]
+fn keeps_the_chosen_failure() {
+    let schema = Schema::load_default().unwrap();
+    let unknown = Frame::new(0x7F, 0x7F, vec![1, 2, 3, 4]).to_bytes();
+    let mut damaged = Frame::new(0x01, 0x07, vec![0; 92]).to_bytes();
+    *damaged.last_mut().unwrap() ^= 0xFF;
+    let data = capture(&schema, &[unknown, damaged].concat());
+
+    let found = failures(&schema, &data, &mut [], 1e-6);
+    let kinds: Vec<_> = found.iter().map(|f| (f.kind.clone(), f.class, f.id)).collect();
+    assert_eq!(kinds, [(FailureKind::Unknown, 0x7F, 0x7F), (FailureKind::Checksum, 0x01, 0x07)]);
+
+    // An unknown message is unknown whatever its payload, so all that is
+    // left is an empty frame of its class and ID.
+    let target = &found[0];
+    let minimized = minimize(&data, |bytes| failures(&schema, bytes, &mut [], 1e-6).contains(target)).unwrap();
+    assert_eq!(minimized, Frame::new(0x7F, 0x7F, Vec::new()).to_bytes());
+}
@@ -0,0 +1,34 @@
+//! Logs read through a mapping frame the same as logs read into memory.
+
+use std::fs;
+use std::path::Path;
+
+use ubx_schema::frame::{frames, Frame};
+use ubx_schema::mmap::LogFile;
+
+#[test]
+fn maps_regular_files_and_reads_the_rest() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("mmap-test.ubx");
+    let mut log = b"$GPGGA\r\n".to_vec();
+    for i in 0..100u8 {
+        log.extend(Frame::new(0x01, 0x07, vec![i; 92]).to_bytes());
+    }
+    fs::write(&path, &log).unwrap();
+
+    let mapped = LogFile::open(&path).unwrap();
+    assert_eq!(mapped.is_mapped(), cfg!(unix));
+    assert_eq!(&mapped[..], &log[..]);
+    let found: Vec<_> = frames(&mapped).flatten().map(|f| f.payload[0]).collect();
+    assert_eq!(found, (0..100).collect::<Vec<u8>>());
+
+    let empty = dir.join("mmap-empty.ubx");
+    fs::write(&empty, b"").unwrap();
+    let file = LogFile::open(&empty).unwrap();
+    assert!(!file.is_mapped() && file.is_empty());
+
+    let read = LogFile::read(&log[..]).unwrap();
+    assert!(!read.is_mapped());
+    assert_eq!(read.as_ref(), mapped.as_ref());
+    assert!(LogFile::open(dir.join("missing.ubx")).is_err());
+}
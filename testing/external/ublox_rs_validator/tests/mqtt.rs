@@ -0,0 +1,115 @@
+//! Topic routing and MQTT 3.1.1 publishing against a stub broker.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use ubx_schema::mqtt::{connect_packet, ConnectOptions, MqttClient, QoS, Topics};
+use ubx_schema::{Decoder, Encoder, Error, Schema};
+
+/// Read one packet: header byte and body.
+fn read_packet(socket: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut byte = [0u8; 1];
+    socket.read_exact(&mut byte).unwrap();
+    let header = byte[0];
+    let (mut len, mut shift) = (0usize, 0);
+    loop {
+        socket.read_exact(&mut byte).unwrap();
+        len |= usize::from(byte[0] & 0x7F) << shift;
+        shift += 7;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut body = vec![0u8; len];
+    socket.read_exact(&mut body).unwrap();
+    (header, body)
+}
+
+#[test]
+fn publishes_routed_messages_with_acknowledgements() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::new(&schema);
+    let decode = |m: serde_json::Value| {
+        let frame = encoder.encode_json(&m).unwrap();
+        decoder.decode(frame.class, frame.id, &frame.payload)
+    };
+    let pvt = decode(json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "numSV": 12}}));
+    let dop = decode(json!({"name": "NAV-DOP", "fields": {"iTOW": 1000, "pDOP": 150}}));
+    let clock = decode(json!({"name": "NAV-CLOCK", "fields": {"iTOW": 1000}}));
+
+    let topics = Topics::new("fleet/truck-12/", QoS::AtLeastOnce)
+        .map("nav_pvt", "fleet/truck-12/fix")
+        .map_qos("NAV-DOP", QoS::ExactlyOnce)
+        .map_qos("NAV-CLOCK", QoS::AtMostOnce);
+    assert_eq!(topics.route(&pvt), ("fleet/truck-12/fix".to_string(), QoS::AtLeastOnce));
+    assert_eq!(topics.route(&dop), ("fleet/truck-12/NAV-DOP".to_string(), QoS::ExactlyOnce));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let broker = thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut received = vec![read_packet(&mut socket)];
+        socket.write_all(&[0x20, 2, 0, 0]).unwrap();
+        loop {
+            let (header, body) = read_packet(&mut socket);
+            let id = |offset: usize| [body[offset], body[offset + 1]];
+            match header >> 4 {
+                3 => {
+                    let topic_len = usize::from(u16::from_be_bytes(id(0)));
+                    match (header >> 1) & 0x03 {
+                        1 => socket.write_all(&[0x40, 2, body[2 + topic_len], body[3 + topic_len]]).unwrap(),
+                        2 => socket.write_all(&[0x50, 2, body[2 + topic_len], body[3 + topic_len]]).unwrap(),
+                        _ => {}
+                    }
+                }
+                6 => socket.write_all(&[0x70, 2, id(0)[0], id(0)[1]]).unwrap(),
+                14 => break,
+                _ => {}
+            }
+            received.push((header, body));
+        }
+        received
+    });
+
+    let options = ConnectOptions {
+        client_id: "truck-12".into(),
+        username: Some("fleet".into()),
+        password: Some("secret".into()),
+        ..ConnectOptions::default()
+    };
+    let mut client = MqttClient::connect(&address, &options, Duration::from_secs(5)).unwrap();
+    for msg in [&pvt, &dop, &clock] {
+        client.publish_message(msg, &topics, false).unwrap();
+    }
+    assert_eq!(client.published, 3);
+    client.disconnect().unwrap();
+
+    let received = broker.join().unwrap();
+    assert_eq!(received[0].1, connect_packet(&options)[2..]);
+    let headers: Vec<u8> = received[1..].iter().map(|(h, _)| *h).collect();
+    // PUBLISH QoS 1, PUBLISH QoS 2 then its PUBREL, PUBLISH QoS 0.
+    assert_eq!(headers, [0x32, 0x34, 0x62, 0x30]);
+    let (_, body) = &received[1];
+    assert_eq!(&body[2..20], b"fleet/truck-12/fix");
+    let payload: serde_json::Value = serde_json::from_slice(&body[22..]).unwrap();
+    assert_eq!(payload["name"], "UBX-NAV-PVT");
+    assert_eq!(payload["fields"]["numSV"], 12);
+}
+
+#[test]
+fn reports_a_refused_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        read_packet(&mut socket);
+        socket.write_all(&[0x20, 2, 0, 5]).unwrap();
+    });
+    let result = MqttClient::connect(&address, &ConnectOptions::default(), Duration::from_secs(5));
+    assert!(matches!(&result, Err(Error::Mqtt(e)) if e.contains("not authorized")), "{:?}", result.err());
+    assert!("3".parse::<QoS>().is_err());
+}
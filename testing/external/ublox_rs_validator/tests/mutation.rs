@@ -0,0 +1,171 @@
+//! Systematically broken corpus frames are refused with the specific error
+//! for the damage, by every framing path, instead of panicking or decoding.
+
+use ubx_schema::corpus::{default_corpus_path, load_corpus, ublox_verdict, Verdict};
+use ubx_schema::error::FrameError;
+use ubx_schema::frame::{checksum, frames, parse_frame, FrameParser, FRAME_OVERHEAD};
+use ubx_schema::schema::PayloadLength;
+use ubx_schema::{Decoder, Frame, Schema, Value};
+
+fn corpus_frames() -> Vec<(String, Vec<u8>)> {
+    let vectors = load_corpus(&default_corpus_path()).unwrap();
+    assert!(!vectors.is_empty());
+    vectors.into_iter().map(|(path, v)| (path.display().to_string(), v.bytes().unwrap())).collect()
+}
+
+/// What a single mutation of a valid frame must be reported as.
+#[derive(Debug)]
+enum Expect {
+    Checksum,
+    Truncated { expected: usize },
+    TooShort,
+    InvalidSync,
+}
+
+/// All single mutations of `frame`: each checksum byte flipped, the length
+/// field grown and shrunk, the sync characters damaged, and every truncation.
+fn mutations(frame: &[u8]) -> Vec<(String, Vec<u8>, Expect)> {
+    let len = u16::from_le_bytes([frame[4], frame[5]]);
+    let mut out = Vec::new();
+    for i in [frame.len() - 2, frame.len() - 1] {
+        let mut m = frame.to_vec();
+        m[i] ^= 0xFF;
+        out.push((format!("checksum byte {i} flipped"), m, Expect::Checksum));
+    }
+    for new_len in [len.wrapping_add(1), len.wrapping_add(256), len | 0x8000] {
+        let mut m = frame.to_vec();
+        m[4..6].copy_from_slice(&new_len.to_le_bytes());
+        let expected = FRAME_OVERHEAD + usize::from(new_len);
+        out.push((format!("length {len} -> {new_len}"), m, Expect::Truncated { expected }));
+    }
+    for new_len in [0, len / 2, len.wrapping_sub(1)] {
+        if new_len < len {
+            let mut m = frame.to_vec();
+            m[4..6].copy_from_slice(&new_len.to_le_bytes());
+            out.push((format!("length {len} -> {new_len}"), m, Expect::Checksum));
+        }
+    }
+    for i in 0..2 {
+        let mut m = frame.to_vec();
+        m[i] ^= 0x01;
+        out.push((format!("sync byte {i} damaged"), m, Expect::InvalidSync));
+    }
+    for cut in 0..frame.len() {
+        let expect = if cut < FRAME_OVERHEAD {
+            Expect::TooShort
+        } else {
+            Expect::Truncated { expected: frame.len() }
+        };
+        out.push((format!("truncated to {cut} bytes"), frame[..cut].to_vec(), expect));
+    }
+    out
+}
+
+#[test]
+fn framing_reports_the_specific_error_for_each_mutation() {
+    let mut checked = 0;
+    for (source, frame) in corpus_frames() {
+        let (class, id) = (frame[2], frame[3]);
+        for (what, mutated, expect) in mutations(&frame) {
+            let label = format!("{source}: {what}");
+            let result = parse_frame(&mutated);
+            match (&expect, &result) {
+                (Expect::Checksum, Err(FrameError::Checksum { class: c, id: i, expected, found })) => {
+                    assert_eq!((*c, *i), (class, id), "{label}");
+                    let claimed = FRAME_OVERHEAD + usize::from(u16::from_le_bytes([mutated[4], mutated[5]]));
+                    let end = mutated.len().min(claimed);
+                    assert_eq!(*expected, checksum(&mutated[2..end - 2]), "{label}");
+                    assert_eq!(*found, [mutated[end - 2], mutated[end - 1]], "{label}");
+                }
+                (Expect::Truncated { expected }, Err(FrameError::Truncated { expected: e, actual })) => {
+                    assert_eq!((*e, *actual), (*expected, mutated.len()), "{label}");
+                }
+                (Expect::TooShort, Err(FrameError::TooShort(n))) => assert_eq!(*n, mutated.len(), "{label}"),
+                (Expect::InvalidSync, Err(FrameError::InvalidSync(a, b))) => {
+                    assert_eq!([*a, *b], [mutated[0], mutated[1]], "{label}");
+                }
+                _ => panic!("{label}: expected {expect:?}, got {result:?}"),
+            }
+
+            // A scan never yields a frame at the damaged position, and only
+            // a checksum failure is reported as an error there.
+            let first = frames(&mutated).next();
+            match (&expect, &first) {
+                (Expect::Checksum, Some(Err(FrameError::Checksum { .. }))) => {}
+                (Expect::Checksum, _) => panic!("{label}: scan gave {first:?}"),
+                (_, Some(Ok(f))) => assert_ne!(f.offset, 0, "{label}: scan decoded the damaged frame"),
+                _ => {}
+            }
+
+            // A stream parser reports the same checksum failure, and waits
+            // for more data on a truncated frame rather than giving up on it.
+            let mut parser = FrameParser::new();
+            parser.push(&mutated);
+            let next = parser.next_frame();
+            match &expect {
+                Expect::Checksum => {
+                    assert!(matches!(next, Some(Err(FrameError::Checksum { .. }))), "{label}: {next:?}");
+                }
+                Expect::Truncated { .. } | Expect::TooShort => {
+                    assert!(next.is_none(), "{label}: {next:?}");
+                    assert_eq!(parser.pending(), mutated.len(), "{label}");
+                }
+                Expect::InvalidSync => {
+                    if let Some(Ok((offset, _))) = next {
+                        assert_ne!(offset, 0, "{label}: parser decoded the damaged frame");
+                    }
+                }
+            }
+            checked += 1;
+        }
+    }
+    assert!(checked > 400, "{checked} mutations");
+}
+
+/// Equal, or an array or text cut short.
+fn is_prefix(got: &Value, want: &Value) -> bool {
+    match (got, want) {
+        (Value::Array(got), Value::Array(want)) => want.starts_with(got),
+        (Value::Text(got), Value::Text(want)) => want.starts_with(got.as_str()),
+        _ => got == want,
+    }
+}
+
+#[test]
+fn decoders_do_not_misdecode_short_payloads() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    for (source, frame) in corpus_frames() {
+        let whole = parse_frame(&frame).unwrap();
+        let original = decoder.decode_frame(&whole);
+        let expected = original.fields.flatten();
+        let fixed = schema
+            .message_by_name(&original.name)
+            .and_then(|m| m.payload.as_ref())
+            .is_some_and(|p| matches!(p.length, PayloadLength::Fixed(_)));
+
+        // Cut payloads with a valid checksum: whatever the schema decoder
+        // still reads must be what the whole payload said, and ublox-rs must
+        // not accept a fixed-length message of the wrong length.
+        for cut in 0..whole.payload.len() {
+            let msg = decoder.decode(whole.class, whole.id, &whole.payload[..cut]);
+            if msg.parsed && msg.name == original.name && msg.variant == original.variant {
+                for (path, value) in msg.fields.flatten() {
+                    if matches!(&value, Value::Array(items) if items.is_empty()) {
+                        continue;
+                    }
+                    let want = expected.iter().find(|(p, _)| *p == path).map(|(_, v)| v);
+                    assert!(
+                        want.is_some_and(|want| is_prefix(&value, want)),
+                        "{source}: {path} = {value:?} with payload cut to {cut}, whole payload gave {want:?}"
+                    );
+                }
+            }
+            if fixed {
+                let short = Frame::new(whole.class, whole.id, whole.payload[..cut].to_vec()).to_bytes();
+                let (verdict, _) = ublox_verdict(&short);
+                assert_ne!(verdict, Verdict::Parsed, "{source}: ublox-rs parsed payload cut to {cut}");
+            }
+        }
+    }
+}
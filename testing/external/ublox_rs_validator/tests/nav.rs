@@ -0,0 +1,151 @@
+//! Navigation data from synthetic Galileo, BeiDou and GLONASS words.
+
+use serde_json::json;
+use ubx_schema::nav::{NavData, NavDecoder};
+use ubx_schema::nav_beidou::BeidouDecoder;
+use ubx_schema::nav_galileo::InavDecoder;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+/// `(position, length, value)` fields packed into `count` words of `width`
+/// bits, most significant first.
+fn pack(width: usize, count: usize, fields: &[(usize, usize, i64)]) -> Vec<u32> {
+    let mut words = vec![0u32; count];
+    for &(pos, len, value) in fields {
+        for i in 0..len {
+            let bit = ((value >> (len - 1 - i)) & 1) as u32;
+            let at = pos + i;
+            words[at / width] |= bit << (width - 1 - at % width);
+        }
+    }
+    words
+}
+
+fn crc24q(bits: impl Iterator<Item = u32>) -> i64 {
+    let crc = bits.fold(0u32, |crc, bit| {
+        let top = (crc >> 23 & 1) ^ bit;
+        let crc = (crc << 1) & 0x00FF_FFFF;
+        if top == 1 {
+            crc ^ 0x0086_4CFB
+        } else {
+            crc
+        }
+    });
+    i64::from(crc)
+}
+
+/// A nominal E1-B page around a 128-bit data word given as fields, with its CRC.
+fn inav_page(fields: &[(usize, usize, i64)]) -> Vec<u32> {
+    let data = pack(32, 4, fields);
+    let bit = |i: usize| data[i / 32] >> (31 - i % 32) & 1;
+    let mut page: Vec<(usize, usize, i64)> = (0..128)
+        .map(|i| (if i < 112 { 2 + i } else { 130 + i - 112 }, 1, i64::from(bit(i))))
+        .collect();
+    page.push((128, 1, 1));
+    let words = pack(32, 8, &page);
+    let at = |i: usize| words[i / 32] >> (31 - i % 32) & 1;
+    let crc = crc24q((0..114).chain(128..210).map(at));
+    page.push((210, 24, crc));
+    pack(32, 8, &page)
+}
+
+#[test]
+fn decodes_galileo_pages_and_beidou_subframes() {
+    let mut inav = InavDecoder::new();
+    let iod = 77;
+    let words = [
+        inav_page(&[(0, 6, 1), (6, 10, iod), (16, 14, 1_000), (94, 32, 2_852_651_008)]),
+        inav_page(&[(0, 6, 2), (6, 10, iod)]),
+        inav_page(&[(0, 6, 3), (6, 10, iod), (120, 8, 107)]),
+        inav_page(&[(0, 6, 4), (6, 10, iod), (68, 31, -4_096)]),
+    ];
+    for w in &words {
+        assert!(inav.push_words(11, w).is_empty());
+    }
+    // A corrupted word 5 fails the CRC; the intact one completes the set.
+    let mut w5 = inav_page(&[(0, 6, 5), (73, 12, 1_250)]);
+    w5[1] ^= 1;
+    assert!(inav.push_words(11, &w5).is_empty());
+    w5[1] ^= 1;
+    let [NavData::GalileoEphemeris(eph)] = &inav.push_words(11, &w5)[..] else {
+        panic!("expected one Galileo ephemeris");
+    };
+    assert_eq!((eph.sv_id, eph.iod_nav, eph.week, eph.sisa), (11, 77, 1_250, 107));
+    assert_eq!(eph.toe, 60_000.0);
+    assert_eq!(eph.sqrt_a, 5_441.0);
+    assert_eq!(eph.af0, -4_096.0 * 2f64.powi(-34));
+
+    // Word 7 and 8 carry the first almanac of the set.
+    let w7 = inav_page(&[(0, 6, 7), (6, 4, 3), (10, 2, 1), (12, 10, 100), (22, 6, 14), (28, 13, -512)]);
+    assert!(inav.push_words(11, &w7).is_empty());
+    let w8 = inav_page(&[(0, 6, 8), (6, 4, 3), (10, 16, 1_024)]);
+    let [NavData::GalileoAlmanac(alm)] = &inav.push_words(11, &w8)[..] else {
+        panic!("expected one Galileo almanac");
+    };
+    assert_eq!((alm.sv_id, alm.iod_a, alm.week, alm.toa), (14, 3, 1, 60_000.0));
+    assert_eq!(alm.delta_sqrt_a, -1.0);
+    assert_eq!(alm.af0, 1_024.0 * 2f64.powi(-19));
+
+    // BeiDou D1 subframes 1-3, 6 s apart; toe is split across 2 and 3.
+    let subframe = |id: i64, sow: i64, fields: &[(usize, usize, i64)]| {
+        let mut all = vec![(0, 11, 0x712), (15, 3, id), (18, 8, sow >> 12), (30, 12, sow & 0xFFF)];
+        all.extend_from_slice(fields);
+        pack(30, 10, &all)
+    };
+    let toe = 45_000 / 8;
+    let mut beidou = BeidouDecoder::new();
+    let sf1 = subframe(1, 345_600, &[(60, 13, 950), (98, 10, -25)]);
+    let sf2 = subframe(2, 345_606, &[(250, 12, 0xA2C), (270, 20, 0x1_2345), (290, 2, toe >> 15)]);
+    let sf3 = subframe(3, 345_612, &[(42, 10, toe >> 5 & 0x3FF), (60, 5, toe & 0x1F)]);
+    assert!(beidou.push_words(20, &sf1).is_none());
+    assert!(beidou.push_words(20, &sf2).is_none());
+    let eph = beidou.push_words(20, &sf3).expect("D1 ephemeris");
+    assert_eq!((eph.week, eph.geo, eph.transmit_time), (950, false, 345_600.0));
+    assert_eq!(eph.toe, 45_000.0);
+    assert!((eph.tgd1 + 2.5e-9).abs() < 1e-18);
+    assert_eq!(eph.sqrt_a, (0xA2C_u64 << 20 | 0x1_2345) as f64 * 2f64.powi(-19));
+
+    // Subframe 3 out of sequence does not pair with the stored 1 and 2.
+    let late = subframe(3, 345_642, &[]);
+    assert!(beidou.push_words(20, &late).is_none());
+}
+
+#[test]
+fn decodes_glonass_strings_from_sfrbx() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions { scale: true, expand_flags: true, include_reserved: false };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut nav = NavDecoder::new();
+    let mut push = |fields: &[(usize, usize, i64)]| {
+        let dwrd = pack(32, 4, fields);
+        let frame = encoder
+            .encode_json(&json!({"name": "RXM-SFRBX", "fields": {
+                "gnssId": 6, "svId": 3, "sigId": 0, "freqId": 12, "numWords": 4, "version": 2, "dwrd": dwrd
+            }}))
+            .unwrap();
+        nav.push(&decoder.decode(frame.class, frame.id, &frame.payload))
+    };
+    // Sign and magnitude: x = -1000 km in 2^-11 km.
+    let x = (1 << 26) | 1_000 << 11;
+    assert!(push(&[(1, 4, 1), (9, 5, 10), (14, 6, 30), (50, 27, x)]).is_empty());
+    assert!(push(&[(1, 4, 2), (9, 7, 40)]).is_empty());
+    assert!(push(&[(1, 4, 3)]).is_empty());
+    let string4 = [(1, 4, 4), (5, 22, (1 << 21) | 1_024), (59, 11, 700), (70, 5, 3), (75, 2, 1)];
+    let [NavData::GlonassEphemeris(eph)] = &push(&string4)[..] else {
+        panic!("expected one GLONASS ephemeris");
+    };
+    assert_eq!((eph.slot, eph.frequency_channel, eph.nt, eph.m), (3, Some(5), 700, 1));
+    assert_eq!((eph.tk, eph.tb), (37_800.0, 36_000.0));
+    assert_eq!(eph.position[0], -1_000_000.0);
+    assert_eq!(eph.tau_n, -1_024.0 * 2f64.powi(-30));
+    // The same ephemeris again is not reported twice.
+    assert!(push(&string4).is_empty());
+
+    // Strings 6 and 7: the almanac of slot 9 on channel -2.
+    assert!(push(&[(1, 4, 6), (5, 1, 1), (6, 2, 1), (8, 5, 9), (62, 15, 512)]).is_empty());
+    let [NavData::GlonassAlmanac(alm)] = &push(&[(1, 4, 7), (21, 21, 320), (71, 5, 30)])[..] else {
+        panic!("expected one GLONASS almanac");
+    };
+    assert_eq!((alm.slot, alm.frequency_channel, alm.healthy), (9, -2, true));
+    assert_eq!((alm.e, alm.t_lambda), (512.0 * 2f64.powi(-20), 10.0));
+}
@@ -0,0 +1,99 @@
+//! Parquet files written from cells and from decoded logs.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::json;
+use ubx_schema::frame::frames;
+use ubx_schema::parquet::{Cell, ColumnType, Layout, ParquetColumn, ParquetExporter, ParquetWriter, MAGIC};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+/// The footer's metadata, checking the magic and length at both ends.
+fn footer(file: &[u8]) -> &[u8] {
+    assert!(file.starts_with(MAGIC) && file.ends_with(MAGIC));
+    let len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+    &file[file.len() - 8 - len..file.len() - 8]
+}
+
+#[test]
+fn writer_encodes_pages_and_footer() {
+    let columns = vec![
+        ParquetColumn::required("offset", ColumnType::Int64),
+        ParquetColumn::new("cno", ColumnType::Int32),
+    ];
+    let mut writer = ParquetWriter::new(Vec::new(), columns).unwrap();
+    let data = [
+        vec![Some(Cell::Int(0)), Some(Cell::Int(100)), Some(Cell::Int(200))],
+        vec![Some(Cell::Int(45)), None, Some(Cell::Int(38))],
+    ];
+    writer.write_row_group(&data).unwrap();
+    assert_eq!(writer.rows, 3);
+    assert!(writer.write_row_group(&data[..1]).is_err());
+    let file = writer.finish().unwrap();
+
+    // Version 1, then a list of three schema elements: the root and two columns.
+    let meta = footer(&file);
+    assert_eq!(&meta[..4], [0x15, 0x02, 0x19, 0x3C]);
+    // The optional column's page: RLE definition levels (one present, one
+    // null, one present), then the two values.
+    let page = [
+        6, 0, 0, 0, 0x02, 1, 0x02, 0, 0x02, 1, // levels
+        45, 0, 0, 0, 38, 0, 0, 0,
+    ];
+    assert!(file.windows(page.len()).any(|w| w == page));
+    assert!(meta.windows(3).any(|w| w == b"cno"));
+
+    let empty = ParquetWriter::new(Vec::new(), vec![]).unwrap().finish().unwrap();
+    assert_eq!(footer(&empty).len() + 12, empty.len());
+}
+
+#[test]
+fn exporter_writes_a_file_per_table_or_one_wide_file() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut capture = Vec::new();
+    for m in [
+        json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "fixType": 3, "lat": 473_977_418}}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45}, {"gnssId": 2, "svId": 11, "cno": 38}
+        ]}}),
+    ] {
+        capture.extend(encoder.encode_json(&m).unwrap().to_bytes());
+    }
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parquet-test");
+    let _ = fs::remove_dir_all(&dir);
+    let mut exporter = ParquetExporter::new(&schema, options, Layout::PerMessage, &dir).unwrap();
+    for frame in frames(&capture).flatten() {
+        exporter.write(frame.offset, &decoder.decode_frame(&frame)).unwrap();
+    }
+    let mut files: Vec<String> = exporter
+        .finish()
+        .unwrap()
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["nav_pvt.parquet", "nav_sat.parquet", "nav_sat_satellites.parquet"]);
+    let satellites = fs::read(dir.join("nav_sat_satellites.parquet")).unwrap();
+    assert!(footer(&satellites).windows(4).any(|w| w == b"svId"));
+
+    let wide_path = dir.join("wide.parquet");
+    let mut exporter = ParquetExporter::new(&schema, options, Layout::Wide, &wide_path).unwrap();
+    for frame in frames(&capture).flatten() {
+        exporter.write(frame.offset, &decoder.decode_frame(&frame)).unwrap();
+    }
+    assert_eq!(exporter.messages, 2);
+    exporter.finish().unwrap();
+    let wide = fs::read(&wide_path).unwrap();
+    let meta = footer(&wide);
+    for column in [&b"nav_pvt_lat"[..], b"nav_sat_numSvs", b"nav_sat_satellites"] {
+        assert!(meta.windows(column.len()).any(|w| w == column));
+    }
+}
@@ -0,0 +1,118 @@
+//! Capture extraction: pcap/pcapng parsing and stream reassembly.
+
+use ubx_schema::frame::Frame;
+use ubx_schema::pcap::{read_packets, Extractor, StreamKey};
+
+fn tcp_packet(seq: u32, payload: &[u8]) -> Vec<u8> {
+    let mut p = vec![0u8; 12];
+    p.extend_from_slice(&[0x08, 0x00]);
+    let total = (20 + 20 + payload.len()) as u16;
+    p.extend_from_slice(&[0x45, 0, total.to_be_bytes()[0], total.to_be_bytes()[1], 0, 0, 0x40, 0, 64, 6, 0, 0]);
+    p.extend_from_slice(&[192, 168, 1, 10, 192, 168, 1, 1]);
+    p.extend_from_slice(&2101u16.to_be_bytes());
+    p.extend_from_slice(&50000u16.to_be_bytes());
+    p.extend_from_slice(&seq.to_be_bytes());
+    p.extend_from_slice(&[0, 0, 0, 0, 0x50, 0x18, 0xFF, 0xFF, 0, 0, 0, 0]);
+    p.extend_from_slice(payload);
+    p
+}
+
+fn pcap(link_type: u32, packets: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&0xA1B2_C3D4u32.to_le_bytes());
+    out.extend_from_slice(&[2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0, 0]);
+    out.extend_from_slice(&link_type.to_le_bytes());
+    for (i, p) in packets.iter().enumerate() {
+        out.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+        out.extend_from_slice(&(i as u32 * 1000).to_le_bytes());
+        out.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        out.extend_from_slice(p);
+    }
+    out
+}
+
+fn frames() -> (Vec<u8>, Vec<u8>) {
+    (
+        Frame::new(0x01, 0x07, (0..92).collect()).to_bytes(),
+        Frame::new(0x05, 0x01, vec![0x06, 0x8A]).to_bytes(),
+    )
+}
+
+#[test]
+fn reassembles_tcp_out_of_order_and_retransmitted() {
+    let (a, b) = frames();
+    let mut stream = a.clone();
+    stream.extend_from_slice(&b);
+    let seq = 1000u32;
+    let packets = [
+        tcp_packet(seq, &stream[..30]),
+        tcp_packet(seq + 60, &stream[60..]),  // arrives early
+        tcp_packet(seq, &stream[..30]),       // retransmission
+        tcp_packet(seq + 30, &stream[30..60]),
+    ];
+    let file = pcap(1, &packets);
+    let packets = read_packets(&file).unwrap();
+    assert_eq!(packets.len(), 4);
+    assert!((packets[1].timestamp - 1_700_000_000.001).abs() < 1e-6);
+
+    let mut extractor = Extractor::new();
+    let out: Vec<_> = packets.iter().flat_map(|p| extractor.push(p)).collect();
+    assert_eq!(out.len(), 2);
+    assert_eq!(out[0].frame.to_bytes(), a);
+    assert_eq!(out[1].frame.to_bytes(), b);
+    assert_eq!(out[1].offset, a.len() as u64);
+    assert!(matches!(extractor.streams().next(), Some(StreamKey::Tcp { .. })));
+}
+
+#[test]
+fn reads_pcapng_usbmon_bulk_transfers() {
+    let (a, _) = frames();
+    let usb = |data: &[u8]| {
+        let mut h = vec![0u8; 48];
+        h[8] = b'C';
+        h[9] = 3; // bulk
+        h[10] = 0x82; // EP 2 IN
+        h[11] = 5; // device
+        h[12] = 1; // bus
+        h[36..40].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        h.extend_from_slice(data);
+        h
+    };
+    let block = |kind: u32, body: Vec<u8>| {
+        let len = (12 + body.len()) as u32;
+        let mut b = kind.to_le_bytes().to_vec();
+        b.extend_from_slice(&len.to_le_bytes());
+        b.extend_from_slice(&body);
+        b.extend_from_slice(&len.to_le_bytes());
+        b
+    };
+    let epb = |data: Vec<u8>| {
+        let mut body = vec![0u8; 12];
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        let pad = (4 - data.len() % 4) % 4;
+        body.extend_from_slice(&data);
+        body.extend(std::iter::repeat_n(0, pad));
+        block(6, body)
+    };
+
+    let mut shb = 0x1A2B_3C4Du32.to_le_bytes().to_vec();
+    shb.extend_from_slice(&[1, 0, 0, 0]);
+    shb.extend_from_slice(&u64::MAX.to_le_bytes());
+    let mut file = block(0x0A0D_0D0A, shb);
+    file.extend(block(1, vec![189, 0, 0, 0, 0, 0, 0, 0]));
+    file.extend(epb(usb(&a[..50])));
+    file.extend(epb(usb(&a[50..])));
+
+    let packets = read_packets(&file).unwrap();
+    assert_eq!(packets.len(), 2);
+    let mut extractor = Extractor::new();
+    let out: Vec<_> = packets.iter().flat_map(|p| extractor.push(p)).collect();
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].frame.to_bytes(), a);
+    assert_eq!(
+        extractor.streams().next(),
+        Some(&StreamKey::Usb { bus: 1, device: 5, endpoint: 0x82 })
+    );
+}
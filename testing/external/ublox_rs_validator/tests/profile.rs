@@ -0,0 +1,43 @@
+//! Product-generation profiles.
+
+use ubx_schema::completeness::MessageIndex;
+use ubx_schema::profile::Profile;
+use ubx_schema::{Decoder, Encoder, Schema};
+
+#[test]
+fn profiles_partition_the_schema_by_generation() {
+    let schema = Schema::load_default().unwrap();
+    let index = MessageIndex::load_default().unwrap();
+    let profiles = Profile::all(&index);
+    let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["M8", "M9", "M10", "F9", "F10", "X20"]);
+    // Every message belongs to some generation.
+    assert!(schema.messages.iter().all(|m| profiles.iter().any(|p| p.supports(m))));
+
+    let f9 = Profile::new(&index, "f9").unwrap().apply(&schema);
+    assert!(f9.message_by_name("UBX-NAV-PVT").is_some());
+    assert!(f9.message_by_name("UBX-NAV-RELPOSNED").is_some());
+    assert!(f9.message_by_name("UBX-AID-ALM").is_none());
+    assert!(f9.messages.len() < schema.messages.len());
+
+    let err = Profile::new(&index, "M7").unwrap_err().to_string();
+    assert!(err.contains("unknown generation 'M7'") && err.contains("F10"), "{err}");
+}
+
+#[test]
+fn a_profile_decides_what_decodes_and_as_what() {
+    let schema = Schema::load_default().unwrap();
+    let index = MessageIndex::load_default().unwrap();
+    let m8 = Profile::new(&index, "M8").unwrap().apply(&schema);
+    let m10 = Profile::new(&index, "M10").unwrap().apply(&schema);
+
+    let frame = Encoder::new(&schema).encode("AID-ALM", None, &Default::default()).unwrap();
+    assert!(Decoder::new(&m8).decode(frame.class, frame.id, &frame.payload).parsed);
+    let msg = Decoder::new(&m10).decode(frame.class, frame.id, &frame.payload);
+    assert!(!msg.parsed);
+
+    // 0x13 0x60 is MGA-ACK-DATA0 or MGA-ACK on M8; only MGA-ACK later.
+    let ids = |s: &Schema| s.messages_by_ids(0x13, 0x60).map(|m| m.name.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&m8).len(), 2);
+    assert_eq!(ids(&m10), ["UBX-MGA-ACK"]);
+}
@@ -0,0 +1,145 @@
+//! Property-based round trip over the whole schema: for every message and
+//! variant, random valid field values must encode and decode back unchanged.
+//! A message added to `ubx_messages.json` is covered with no test changes.
+//!
+//! The generator (`common/`, shared with the snapshot tests) is written out
+//! rather than taken from proptest, which is not among the crate's
+//! dependencies, so failing cases are not shrunk.
+//! Cases for a message depend only on its name and `UBX_PROPERTY_SEED`, so a
+//! reported failure replays with the same seed. `UBX_PROPERTY_CASES`
+//! (default 32) sets the number of cases per message and variant.
+
+mod common;
+
+use std::env;
+
+use common::{payloads, Generator, Source};
+use serde_json::{Map, Value as Json};
+use ubx_schema::schema::BaseType;
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+/// Longest variable-length array or group to generate.
+const MAX_ITEMS: u64 = 4;
+
+/// splitmix64.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn bits(&mut self, width: u32) -> u64 {
+        let mask = if width >= 64 { u64::MAX } else { (1 << width) - 1 };
+        match self.below(8) {
+            0 => 0,
+            1 => mask,
+            2 => 1,
+            _ => self.next() & mask,
+        }
+    }
+}
+
+impl Source for Rng {
+    /// Biased towards the edges of the range, where encoding bugs live.
+    fn scalar(&mut self, base: BaseType) -> Json {
+        let width = 8 * base.size() as u32;
+        let raw = self.bits(width);
+        match base {
+            BaseType::I1 => Json::from(raw as u8 as i8),
+            BaseType::I2 => Json::from(raw as u16 as i16),
+            BaseType::I4 => Json::from(raw as u32 as i32),
+            BaseType::I8 => Json::from(raw as i64),
+            BaseType::R4 => finite(f64::from(f32::from_bits(raw as u32))),
+            BaseType::R8 => finite(f64::from_bits(raw)),
+            BaseType::CH => Json::from(char::from(b' ' + self.below(95) as u8).to_string()),
+            _ => Json::from(raw),
+        }
+    }
+
+    fn count(&mut self) -> u64 {
+        self.below(MAX_ITEMS + 1)
+    }
+
+    fn text(&mut self, n: u64) -> String {
+        (0..n).map(|_| char::from(b' ' + self.below(95) as u8)).collect()
+    }
+}
+
+fn finite(v: f64) -> Json {
+    Json::from(if v.is_finite() { v } else { 0.0 })
+}
+
+/// The first generated value the decode lost or changed. An empty array or
+/// string at the end of the payload decodes as absent.
+fn first_difference(fields: &Map<String, Json>, decoded: &Json) -> Option<String> {
+    fields.iter().find_map(|(name, want)| {
+        let got = decoded.get(name.as_str());
+        let empty = matches!(want, Json::Array(a) if a.is_empty()) || want == "";
+        match got {
+            Some(got) if got == want => None,
+            None if empty => None,
+            _ => Some(format!("{name}: {want} became {}", got.unwrap_or(&Json::Null))),
+        }
+    })
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+#[test]
+fn random_values_round_trip_for_every_message() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        include_reserved: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let cases = env_u64("UBX_PROPERTY_CASES").unwrap_or(32);
+    let seed = env_u64("UBX_PROPERTY_SEED").unwrap_or(0x5eed);
+
+    let mut failures = Vec::new();
+    let mut checked = 0;
+    for msg in &schema.messages {
+        for (variant, def, skip) in payloads(msg) {
+            let label = variant.map_or_else(|| msg.name.clone(), |v| format!("{} ({v})", msg.name));
+            let mut rng = Rng(label.bytes().fold(seed, |h, b| (h ^ u64::from(b)).wrapping_mul(0x100_0000_01B3)));
+            for case in 0..cases {
+                let fields = Generator { source: &mut rng, room: 1 }.payload(def, skip);
+                let frame = match encoder.encode(&msg.name, variant, &fields) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        failures.push(format!("{label} case {case}: {e}"));
+                        break;
+                    }
+                };
+                let decoded = decoder.decode(frame.class, frame.id, &frame.payload);
+                // A payload that also fits a sibling sharing the class and ID
+                // (MGA-ACK and MGA-ACK-DATA0) may decode as it; the values must
+                // still survive.
+                let mismatch = if decoded.variant.as_deref() != variant || !decoded.parsed {
+                    Some(format!("decoded as {} {:?}", decoded.name, decoded.variant))
+                } else {
+                    first_difference(&fields, &serde_json::to_value(&decoded.fields).unwrap())
+                };
+                if let Some(mismatch) = mismatch {
+                    failures.push(format!("{label} case {case}: {mismatch}\n    fields: {}", Json::Object(fields)));
+                    break;
+                }
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0);
+    assert!(failures.is_empty(), "{} failures (seed {seed}):\n{}", failures.len(), failures.join("\n"));
+}
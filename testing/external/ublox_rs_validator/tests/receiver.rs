@@ -0,0 +1,60 @@
+//! Receiver identification and automatic profile selection.
+
+use serde_json::json;
+use ubx_schema::completeness::MessageIndex;
+use ubx_schema::frame::Frame;
+use ubx_schema::profile::AutoProfile;
+use ubx_schema::receiver::ReceiverInfo;
+use ubx_schema::{DecodedMessage, Decoder, Encoder, Schema};
+
+fn mon_ver(schema: &Schema, hw: &str, extensions: &[&str]) -> DecodedMessage {
+    let extensions: Vec<_> = extensions.iter().map(|e| json!({"extension": e})).collect();
+    let frame = Encoder::new(schema)
+        .encode_json(&json!({"name": "UBX-MON-VER", "fields": {
+            "swVersion": "ROM CORE", "hwVersion": hw, "extensionStrings": extensions
+        }}))
+        .unwrap();
+    Decoder::new(schema).decode(frame.class, frame.id, &frame.payload)
+}
+
+#[test]
+fn generation_from_model_firmware_hardware_or_protocol() {
+    let schema = Schema::load_default().unwrap();
+    let generation = |hw: &str, extensions: &[&str]| {
+        ReceiverInfo::from_mon_ver(&mon_ver(&schema, hw, extensions)).unwrap().generation()
+    };
+    assert_eq!(generation("00190000", &["FWVER=HPG 1.32", "PROTVER=27.31", "MOD=ZED-F9P"]), Some("F9"));
+    assert_eq!(generation("00190000", &["MOD=NEO-M9N"]), Some("M9"));
+    assert_eq!(generation("000A0000", &["MOD=MAX-M10S"]), Some("M10"));
+    assert_eq!(generation("00190000", &["FWVER=SPG 6.00"]), Some("F10"));
+    assert_eq!(generation("00190000", &["FWVER=HPG 2.02", "MOD=ZED-X20P"]), Some("X20"));
+    assert_eq!(generation("00080000", &[]), Some("M8"));
+    assert_eq!(generation("00070000", &["PROTVER=18.00"]), Some("M8"));
+    assert_eq!(generation("00190000", &["PROTVER=32.01", "MOD=NEO-D9S"]), None);
+}
+
+#[test]
+fn auto_profile_switches_on_mon_ver_and_keeps_mon_gnss() {
+    let schema = Schema::load_default().unwrap();
+    let mut auto = AutoProfile::new(schema.clone(), MessageIndex::load_default().unwrap());
+    assert!(auto.profile().is_none());
+    assert_eq!(auto.schema().messages.len(), schema.messages.len());
+
+    // MON-GNSS: GPS, GLONASS, BeiDou and Galileo supported; GPS and Galileo enabled.
+    let gnss = Frame::new(0x0A, 0x28, vec![0, 0x0F, 0x0F, 0x09, 4, 0, 0, 0]);
+    assert!(!auto.observe(&Decoder::new(auto.schema()).decode(gnss.class, gnss.id, &gnss.payload)));
+
+    assert!(auto.observe(&mon_ver(&schema, "00190000", &["FWVER=HPG 1.32", "MOD=ZED-F9P"])));
+    assert_eq!(auto.profile().unwrap().name, "F9");
+    assert!(auto.schema().message_by_name("UBX-AID-ALM").is_none());
+    let receiver = auto.receiver().unwrap();
+    assert_eq!(receiver.model.as_deref(), Some("ZED-F9P"));
+    let gnss = receiver.gnss.as_ref().unwrap();
+    assert_eq!((gnss.supported.len(), gnss.simultaneous), (4, 4));
+    assert_eq!(gnss.enabled, ["GPS", "Galileo"]);
+
+    // The same receiver again is no switch; one that names no generation keeps F9.
+    assert!(!auto.observe(&mon_ver(&schema, "00190000", &["MOD=ZED-F9P"])));
+    assert!(!auto.observe(&mon_ver(&schema, "00190000", &[])));
+    assert_eq!(auto.profile().unwrap().name, "F9");
+}
@@ -0,0 +1,71 @@
+//! Releases record what changed, refuse too small a version bump, and answer
+//! whether a definition held between two versions.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::registry::{Manifest, Release, Stability, Version};
+use ubx_schema::schema_diff::diff_messages;
+use ubx_schema::Schema;
+
+fn schema(fields: Json) -> Schema {
+    Schema::from_json(&json!({"schema_version": "1.5", "messages": [
+        {"name": "UBX-ACK-ACK", "class_id": "0x05", "message_id": "0x01", "message_type": "output",
+         "payload": {"length": {"fixed": 2}, "fields": [
+             {"name": "clsID", "byte_offset": 0, "data_type": "U1"},
+             {"name": "msgID", "byte_offset": 1, "data_type": "U1"}]}},
+        {"name": "UBX-TEST-A", "class_id": "0x01", "message_id": "0x99", "message_type": "output",
+         "payload": {"length": {"fixed": 8}, "fields": fields}},
+    ]}))
+    .unwrap()
+}
+
+fn release(manifest: &mut Manifest, version: &str, old: &Schema, new: &Schema) -> ubx_schema::Result<()> {
+    let release = Release::new(version.parse()?, "2026-10-14", new, None, manifest.latest(), &diff_messages(old, new));
+    manifest.push(release)
+}
+
+#[test]
+fn answers_stability_between_releases() {
+    let v1 = schema(json!([{"name": "iTOW", "byte_offset": 0, "data_type": "U4"}]));
+    let v2 = schema(json!([
+        {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+        {"name": "numSV", "byte_offset": 4, "data_type": "U1"}
+    ]));
+    let v3 = schema(json!([
+        {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+        {"name": "numSV", "byte_offset": 5, "data_type": "U1"}
+    ]));
+
+    let mut manifest = Manifest {
+        current: Version::new(1, 0, 0),
+        releases: Vec::new(),
+    };
+    release(&mut manifest, "1.0.0", &v1, &v1).unwrap();
+    // A new field needs a minor version, a moved one a major version.
+    assert!(release(&mut manifest, "1.0.1", &v1, &v2).is_err());
+    release(&mut manifest, "1.1.0", &v1, &v2).unwrap();
+    assert!(release(&mut manifest, "1.2.0", &v2, &v3).is_err());
+    release(&mut manifest, "2.0.0", &v2, &v3).unwrap();
+    assert_eq!(manifest.current, Version::new(2, 0, 0));
+    assert_eq!(manifest.releases[1].changed, ["UBX-TEST-A"]);
+    assert_eq!(manifest.releases[2].breaking, ["UBX-TEST-A"]);
+
+    let v = |s: &str| s.parse::<Version>().unwrap();
+    let stability = |name: &str, from: &str, to: &str| manifest.stability(name, v(from), v(to)).unwrap();
+    assert_eq!(stability("UBX-ACK-ACK", "1.0.0", "2.0.0"), Stability::Unchanged);
+    assert_eq!(
+        stability("UBX-TEST-A", "1.0.0", "1.1.0"),
+        Stability::Compatible {
+            changed_in: vec![v("1.1.0")]
+        }
+    );
+    assert_eq!(stability("UBX-TEST-A", "1.0.0", "2.0.0"), Stability::Breaking { since: v("2.0.0") });
+    assert!(manifest.stability("UBX-TEST-A", v("1.0.0"), v("3.0.0")).is_err());
+}
+
+#[test]
+fn shipped_manifest_loads() {
+    let manifest = Manifest::load_default().unwrap();
+    let current = manifest.release(manifest.current).unwrap();
+    assert!(current.messages.contains_key("UBX-NAV-PVT"));
+    assert!(current.config_keys.contains_key("CFG-RATE-MEAS"));
+}
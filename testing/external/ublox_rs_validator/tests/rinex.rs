@@ -0,0 +1,114 @@
+//! RINEX conversion from encoded RAWX frames and synthetic LNAV subframes.
+
+use serde_json::json;
+use ubx_schema::gnss::Gnss;
+use ubx_schema::nav::LnavDecoder;
+use ubx_schema::rinex::{ObservationBuilder, RinexHeader};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn rawx(tow: f64, locktime: u64) -> serde_json::Value {
+    json!({
+        "name": "RXM-RAWX",
+        "fields": {
+            "rcvTow": tow, "week": 2297, "leaps": 18, "recStat": {"leapSec": 1}, "version": 1,
+            "repeated_group": [
+                {"prMes": 21_000_123.456, "cpMes": 110_356_789.123, "doMes": -1234.5, "gnssId": 0,
+                 "svId": 5, "sigId": 0, "locktime": locktime, "cno": 45,
+                 "trkStat": {"prValid": 1, "cpValid": 1, "halfCyc": 1}},
+                {"prMes": 23_500_000.25, "cpMes": 0.0, "doMes": 55.5, "gnssId": 2,
+                 "svId": 11, "sigId": 1, "locktime": 0, "cno": 30, "trkStat": {"prValid": 1}}
+            ]
+        }
+    })
+}
+
+#[test]
+fn writes_observation_file() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut obs = ObservationBuilder::new();
+    for (tow, lock) in [(129_600.0, 5000), (129_601.0, 100)] {
+        let frame = encoder.encode_json(&rawx(tow, lock)).unwrap();
+        assert!(obs.push(&decoder.decode(frame.class, frame.id, &frame.payload)));
+    }
+
+    let mut out = Vec::new();
+    obs.write(&RinexHeader::default(), &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "     3.04           OBSERVATION DATA    M                   RINEX VERSION / TYPE"
+    );
+    assert!(lines.iter().all(|l| l.len() <= 80));
+    assert!(text.contains("E    4 C1B L1B D1B S1B"));
+    assert!(text.contains("G    4 C1C L1C D1C S1C"));
+    assert!(text.contains("  2024     1    15    12     0    0.0000000     GPS         TIME OF FIRST OBS"));
+    assert!(text.contains("    18                                                      LEAP SECONDS"));
+
+    let epoch = lines.iter().position(|l| l.starts_with("> ")).unwrap();
+    assert_eq!(lines[epoch], "> 2024 01 15 12 00  0.0000000  0  2");
+    // Galileo: no valid phase, so L1B is blank.
+    assert_eq!(
+        lines[epoch + 1],
+        "E11  23500000.250 5                        55.500 5        30.000 5"
+    );
+    assert_eq!(
+        lines[epoch + 2],
+        "G05  21000123.456 7 110356789.123 7     -1234.500 7        45.000 7"
+    );
+    // Locktime went backwards in the second epoch: loss of lock on the phase.
+    assert!(lines[epoch + 5].starts_with("G05  21000123.456 7 110356789.1231"));
+}
+
+/// Pack LNAV data fields (bit position, width, value) into ten 30-bit words.
+fn subframe(id: u32, fields: &[(usize, usize, i64)]) -> Vec<u32> {
+    let mut bits = [0u32; 10];
+    let mut put = |pos: usize, len: usize, value: i64| {
+        for i in 0..len {
+            let bit = ((value >> (len - 1 - i)) & 1) as u32;
+            let at = pos + i;
+            bits[at / 24] |= bit << (23 - at % 24);
+        }
+    };
+    put(0, 8, 0x8B);
+    put(24, 17, 21_601); // HOW TOW count
+    put(43, 3, i64::from(id));
+    for &(pos, len, value) in fields {
+        put(pos, len, value);
+    }
+    bits.iter().map(|b| b << 6).collect()
+}
+
+#[test]
+fn assembles_lnav_ephemeris() {
+    let sf1 = subframe(1, &[(48, 10, 249), (60, 4, 1), (70, 2, 0), (168, 8, 42), (176, 16, 8100), (216, 22, -12_345)]);
+    let sf2 = subframe(2, &[(48, 8, 42), (56, 16, -100), (136, 8, 0), (144, 24, 1_000_000), (184, 8, 0xA1), (192, 24, 0x0ABCDE), (216, 16, 8100)]);
+    let sf3 = subframe(3, &[(192, 24, -5_000), (216, 8, 42), (224, 14, -37)]);
+
+    let mut decoder = LnavDecoder::new();
+    assert!(decoder.push_words(Gnss::Gps, 5, &sf1).is_none());
+    assert!(decoder.push_words(Gnss::Gps, 5, &sf2).is_none());
+    let eph = decoder.push_words(Gnss::Gps, 5, &sf3).expect("ephemeris complete");
+    assert_eq!(eph.week, 249);
+    assert_eq!(eph.iodc, 42);
+    assert_eq!(eph.iode, 42);
+    assert_eq!(eph.toc, 129_600.0);
+    assert_eq!(eph.toe, 129_600.0);
+    assert_eq!(eph.af0, -12_345.0 * 2f64.powi(-31));
+    assert_eq!(eph.crs, -100.0 * 2f64.powi(-5));
+    assert_eq!(eph.e, 1_000_000.0 * 2f64.powi(-33));
+    assert_eq!(eph.sqrt_a, f64::from(0xA10A_BCDEu32) * 2f64.powi(-19));
+    assert_eq!(eph.idot, -37.0 * 2f64.powi(-43) * std::f64::consts::PI);
+    assert_eq!(eph.transmit_time, 129_600.0);
+    assert_eq!(eph.ura_meters(), 3.4);
+
+    // The same issue of data is only reported once.
+    assert!(decoder.push_words(Gnss::Gps, 5, &sf3).is_none());
+}
@@ -0,0 +1,92 @@
+//! Arbitrary bytes never panic the framing or the decoder. A quick,
+//! deterministic stand-in for the cargo-fuzz targets in `fuzz/`.
+
+use ubx_schema::frame::{checksum, frames, FrameParser, FRAME_OVERHEAD};
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+/// Largest frame the length field allows.
+const MAX_FRAME: usize = FRAME_OVERHEAD + u16::MAX as usize;
+
+/// splitmix64, so failures reproduce.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn bytes(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next() as u8).collect()
+    }
+}
+
+#[test]
+fn every_message_decodes_arbitrary_payloads() {
+    let schema = Schema::load_default().unwrap();
+    let mut rng = Rng(1);
+    for options in [
+        DecodeOptions::default(),
+        DecodeOptions {
+            scale: true,
+            expand_flags: true,
+            include_reserved: true,
+        },
+    ] {
+        let decoder = Decoder::with_options(&schema, options);
+        for msg in &schema.messages {
+            for len in (0..64).chain([100, 255, 256, 1000, 4096]) {
+                let payload = rng.bytes(len);
+                decoder.decode(msg.class_id, msg.message_id, &payload);
+                decoder.decode(msg.class_id, msg.message_id, &vec![0xFF; len]);
+            }
+        }
+    }
+}
+
+#[test]
+fn framing_survives_adversarial_streams() {
+    let mut rng = Rng(2);
+    let mut stream = Vec::new();
+    for _ in 0..2000 {
+        match rng.next() % 4 {
+            // Sync characters followed by a random, often huge, length.
+            0 => stream.extend([0xB5, 0x62, rng.next() as u8, rng.next() as u8, rng.next() as u8, rng.next() as u8]),
+            // A valid frame with a random payload.
+            1 => {
+                let len = (rng.next() % 300) as usize;
+                let mut frame = vec![0xB5, 0x62, rng.next() as u8, rng.next() as u8];
+                frame.extend((len as u16).to_le_bytes());
+                frame.extend(rng.bytes(len));
+                frame.extend(checksum(&frame[2..]));
+                stream.extend(frame);
+            }
+            2 => stream.extend([0xB5; 3]),
+            _ => {
+                let len = (rng.next() % 50) as usize;
+                stream.extend(rng.bytes(len));
+            }
+        }
+    }
+
+    let found = frames(&stream).flatten().count();
+    assert!(found > 0);
+
+    // Zero padding lets the streaming parser rule out every false sync near
+    // the end, which the whole-buffer scan does by running out of data.
+    let mut padded = stream.clone();
+    padded.extend(vec![0; MAX_FRAME]);
+    let mut parser = FrameParser::new();
+    let mut streamed = 0;
+    for chunk in padded.chunks(997) {
+        parser.push(chunk);
+        while let Some(result) = parser.next_frame() {
+            streamed += usize::from(result.is_ok());
+        }
+        assert!(parser.pending() <= MAX_FRAME + chunk.len(), "parser buffered {} bytes", parser.pending());
+    }
+    assert_eq!(streamed, found);
+}
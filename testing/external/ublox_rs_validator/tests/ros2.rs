@@ -0,0 +1,79 @@
+//! ROS 2 definitions generated from the schema, and decoded messages converted to them.
+
+use serde_json::json;
+use ubx_schema::ros2::{field_name, type_name, Package};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn options() -> DecodeOptions {
+    DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    }
+}
+
+#[test]
+fn generates_valid_definitions_for_every_message() {
+    assert_eq!(field_name("iTOW"), "i_tow");
+    assert_eq!(field_name("gnssFixOK"), "gnss_fix_ok");
+    assert_eq!(field_name("UTCStandard"), "utc_standard");
+    assert_eq!(field_name("ecefX"), "ecef_x");
+    assert_eq!(type_name("NAV-HPPOSLLH"), "NavHpposllh");
+
+    let schema = Schema::load_default().unwrap();
+    for options in [DecodeOptions::default(), options()] {
+        let package = Package::generate(&schema, "ubx_msgs", &[], options, true);
+        assert!(package.duplicate_types().is_empty(), "{:?}", package.duplicate_types());
+        for def in &package.messages {
+            let valid_type = def.name.starts_with(|c: char| c.is_ascii_uppercase())
+                && def.name.chars().all(|c| c.is_ascii_alphanumeric());
+            assert!(valid_type, "{}", def.name);
+            for field in &def.fields {
+                let valid = field.name.starts_with(|c: char| c.is_ascii_lowercase())
+                    && field.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                    && !field.name.ends_with('_')
+                    && !field.name.contains("__");
+                assert!(valid, "{}.{}", def.name, field.name);
+            }
+        }
+    }
+
+    let package = Package::generate(&schema, "ubx_msgs", &["NAV-PVT".into()], options(), true);
+    let msg = package.find("NavPvt").unwrap().to_msg(true);
+    assert!(msg.starts_with("# UBX-NAV-PVT\nuint8 CLASS_ID=1\nuint8 MESSAGE_ID=7\n\nstd_msgs/Header header\n"));
+    assert!(msg.contains("\nuint32 i_tow  # ") && msg.contains("\nfloat64 lat  # Latitude [deg]\n"));
+    assert!(msg.contains("\nbool flags_gnss_fix_ok") && msg.contains("\nuint8 flags_carr_soln"));
+    assert!(!msg.contains("reserved"));
+
+    let raw = Package::generate(&schema, "ubx_msgs", &["NAV-PVT".into()], DecodeOptions::default(), false);
+    let files = raw.files();
+    let msg = &files.iter().find(|(path, _)| path == "msg/NavPvt.msg").unwrap().1;
+    assert!(msg.contains("\nint32 lat  # Latitude [deg * 1e-7]\n") && msg.contains("\nuint8 flags  # "));
+    assert!(files.iter().any(|(path, text)| path == "CMakeLists.txt" && text.contains("  \"msg/NavPvt.msg\"\n")));
+}
+
+#[test]
+fn converts_decoded_messages_to_ros_fields() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::with_options(&schema, options());
+    let frame = encoder
+        .encode_json(&json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45, "prRes": -12}
+        ]}}))
+        .unwrap();
+    let msg = decoder.decode(frame.class, frame.id, &frame.payload);
+
+    let package = Package::generate(&schema, "ubx_msgs", &["NAV-SAT".into()], options(), false);
+    let ros = package.convert(&msg).unwrap();
+    assert_eq!(ros["i_tow"], 1000);
+    assert_eq!(ros["num_svs"], 1);
+    let sat = &ros["satellites"][0];
+    assert_eq!((sat["gnss_id"].clone(), sat["sv_id"].clone(), sat["cno"].clone()), (json!(0), json!(12), json!(45)));
+    assert!((sat["pr_res"].as_f64().unwrap() + 1.2).abs() < 1e-9);
+
+    let mapping = package.mapping();
+    assert_eq!(mapping["messages"][0]["type"], "ubx_msgs/msg/NavSat");
+    let fields = mapping["types"]["NavSatSatellites"].as_array().unwrap();
+    assert!(fields.contains(&json!({"path": "svId", "field": "sv_id", "type": "uint8"})));
+}
@@ -0,0 +1,68 @@
+//! RTK carrier-solution timelines.
+
+use serde_json::json;
+use ubx_schema::rtk::{CarrierState, Outage, RtkAnalyzer};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn analyze(messages: &[serde_json::Value]) -> RtkAnalyzer {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut rtk = RtkAnalyzer::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        rtk.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    rtk
+}
+
+#[test]
+fn ttff_transitions_and_outages_from_nav_pvt() {
+    // 1 Hz: 3 s none, 3 s float, 4 s fixed, 2 s float, 3 s fixed, then lost.
+    let states = [0, 0, 0, 1, 1, 1, 2, 2, 2, 2, 1, 1, 2, 2, 2, 0, 0];
+    let messages: Vec<_> = states
+        .iter()
+        .enumerate()
+        .map(|(i, &carr)| {
+            json!({"name": "NAV-PVT", "fields": {
+                "iTOW": (604_795_000 + 1_000 * i as u64) % 604_800_000, "fixType": 3,
+                "flags": {"gnssFixOK": 1, "carrSoln": carr}
+            }})
+        })
+        .collect();
+    let rtk = analyze(&messages);
+    let pvt = &rtk.pvt;
+    assert_eq!((pvt.epochs(), pvt.duration_ms(), pvt.segments.len()), (17, 16_000, 6));
+    assert_eq!(pvt.time_to(CarrierState::Float), Some(3_000));
+    assert_eq!(pvt.time_to(CarrierState::Fixed), Some(6_000));
+    assert_eq!(pvt.time_in(CarrierState::Fixed), 7_000);
+    assert_eq!(pvt.transitions(CarrierState::Float, CarrierState::Fixed), 2);
+    assert_eq!(pvt.transitions(CarrierState::Fixed, CarrierState::Float), 1);
+    assert_eq!(pvt.transitions(CarrierState::Fixed, CarrierState::None), 1);
+    // The week rolls over at the sixth epoch.
+    assert_eq!(
+        pvt.outages(),
+        [
+            Outage { start: 5_000, duration_ms: 2_000, recovered: true },
+            Outage { start: 10_000, duration_ms: 1_000, recovered: false },
+        ]
+    );
+    assert_eq!(rtk.relposned.epochs(), 0);
+}
+
+#[test]
+fn relposned_needs_a_valid_relative_position() {
+    let relpos = |itow: u32, valid: u64, carr: u64| {
+        json!({"name": "NAV-RELPOSNED", "fields": {"iTOW": itow, "flags": {"relPosValid": valid, "carrSoln": carr}}})
+    };
+    let rtk = analyze(&[relpos(1_000, 0, 2), relpos(1_200, 1, 2), relpos(1_400, 1, 2), relpos(1_200, 1, 0)]);
+    let timeline = &rtk.relposned;
+    // The repeated iTOW going backwards is ignored.
+    assert_eq!(timeline.epochs(), 3);
+    assert_eq!(timeline.time_to(CarrierState::Fixed), Some(200));
+    assert!(timeline.outages().is_empty());
+}
@@ -0,0 +1,61 @@
+//! Schema diffs classify changes and render them as a changelog fragment.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::schema_diff::{changelog, diff_messages, Impact};
+use ubx_schema::Schema;
+
+fn schema(messages: Json) -> Schema {
+    Schema::from_json(&json!({"schema_version": "1.5", "messages": messages})).unwrap()
+}
+
+fn message(name: &str, fields: Json) -> Json {
+    json!({
+        "name": name,
+        "class_id": "0x01",
+        "message_id": "0x99",
+        "message_type": "output",
+        "payload": {"length": {"fixed": 8}, "fields": fields}
+    })
+}
+
+#[test]
+fn identical_schemas_have_no_changes() {
+    let schema = Schema::load_default().unwrap();
+    assert!(diff_messages(&schema, &schema).is_empty());
+    assert_eq!(changelog("Nothing", &[]), "## Nothing\n\nNo changes.\n");
+}
+
+#[test]
+fn classifies_field_changes() {
+    let old = schema(json!([
+        message("UBX-TEST-A", json!([
+            {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+            {"name": "lat", "byte_offset": 4, "data_type": "I2", "scale": {"multiplier": 1e-2}},
+            {"name": "reserved0", "byte_offset": 6, "data_type": "U1", "reserved": true},
+            {"name": "old", "byte_offset": 7, "data_type": "U1"}
+        ])),
+        message("UBX-TEST-GONE", json!([])),
+    ]));
+    let new = schema(json!([
+        message("UBX-TEST-A", json!([
+            {"name": "iTOW", "byte_offset": 0, "data_type": "U4"},
+            {"name": "lat", "byte_offset": 4, "data_type": "I2", "scale": {"multiplier": 1e-3}},
+            {"name": "numSV", "byte_offset": 6, "data_type": "U1"},
+            {"name": "renamed", "byte_offset": 7, "data_type": "U1"}
+        ])),
+    ]));
+
+    let changes: Vec<_> = diff_messages(&old, &new).into_iter().map(|c| (c.impact, c.to_string())).collect();
+    assert_eq!(
+        changes,
+        [
+            (Impact::Breaking, "UBX-TEST-A: field `lat` scale 0.001 instead of 0.01".into()),
+            (Impact::Added, "UBX-TEST-A: new field `numSV` at offset 6".into()),
+            (Impact::Breaking, "UBX-TEST-A: field `old` renamed to `renamed`".into()),
+            (Impact::Breaking, "UBX-TEST-GONE: message removed".into()),
+        ]
+    );
+    let fragment = changelog("Since 1.4", &diff_messages(&old, &new));
+    assert!(fragment.contains("### Breaking\n\n- **UBX-TEST-A**: field `lat` scale 0.001 instead of 0.01; "));
+    assert!(fragment.contains("### Added\n\n- **UBX-TEST-A**: new field `numSV` at offset 6\n"));
+}
@@ -0,0 +1,88 @@
+//! Schema linter: the shipped schema files must stay free of errors.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::config::default_keys_path;
+use ubx_schema::lint::{lint_config_keys, lint_messages, Issue, Severity};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::Schema;
+
+/// Errors still open in the extracted data. SEC-OSNMA `osnmaMonitoring` is
+/// X1 in the schema but its bitfield has bits 8-11; the F9-TIM 2.25 manual
+/// documents it as X2 with the following fields shifted, so the message needs
+/// re-extraction rather than a hand edit.
+const KNOWN_ERRORS: &[&str] = &[
+    "UBX-SEC-OSNMA.osnmaMonitoring: bit member 'noData' extends past the 8-bit field",
+    "UBX-SEC-OSNMA.osnmaMonitoring: bit member 'wrongData' extends past the 8-bit field",
+    "UBX-SEC-OSNMA.osnmaMonitoring: bit member 'wrongFlxMac' extends past the 8-bit field",
+    "UBX-SEC-OSNMA.osnmaMonitoring: bit member 'wrongMaclt' extends past the 8-bit field",
+];
+
+fn read_json(path: std::path::PathBuf) -> Json {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn errors(issues: &[Issue]) -> Vec<String> {
+    issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .map(|i| format!("{}: {}", i.location, i.message))
+        .collect()
+}
+
+#[test]
+fn shipped_schema_has_no_new_errors() {
+    let schema = Schema::load_default().unwrap();
+    let enums = read_json(default_schema_path().with_file_name("enumerations.json"));
+    let mut issues = lint_messages(&schema, Some(&enums));
+    issues.extend(lint_config_keys(&read_json(default_keys_path())));
+
+    let new: Vec<String> = errors(&issues)
+        .into_iter()
+        .filter(|e| !KNOWN_ERRORS.contains(&e.as_str()))
+        .collect();
+    assert!(new.is_empty(), "schema-lint errors:\n{}", new.join("\n"));
+}
+
+#[test]
+fn detects_layout_and_reference_errors() {
+    let schema = Schema::from_json(&json!({"messages": [
+        {
+            "name": "UBX-TST-A", "class_id": "0xF0", "message_id": "0x01",
+            "payload": {"length": {"fixed": 6}, "fields": [
+                {"name": "a", "byte_offset": 0, "data_type": "U4"},
+                {"name": "b", "byte_offset": 2, "data_type": "U2", "scale": {"multiplier": 0.1}},
+                {"name": "c", "byte_offset": 4, "data_type": "U4", "enumeration": "noSuchEnum"}
+            ]}
+        },
+        {
+            "name": "UBX-TST-B", "class_id": "0xF0", "message_id": "0x02",
+            "payload": {
+                "length": {"fixed": 14},
+                "fields": [{"name": "n", "byte_offset": 0, "data_type": "U1"}],
+                "repeated_groups": [{
+                    "name": "items", "repetition_type": "count_field", "count_field": "n",
+                    "group_size_bytes": 4, "base_offset": 1,
+                    "fields": [{"name": "x", "byte_offset": 0, "data_type": "U4"}]
+                }]
+            }
+        },
+        {
+            "name": "UBX-TST-C", "class_id": "0xF0", "message_id": "0x02",
+            "payload": {"length": {"fixed": 14}, "fields": [{"name": "n", "byte_offset": 0, "data_type": "U1"}]}
+        }
+    ]}))
+    .unwrap();
+    let enums = json!({"fixType": {"type": "U1", "values": [], "messages": ["UBX-TST-MISSING"]}});
+    let issues = lint_messages(&schema, Some(&enums));
+    let errors = errors(&issues);
+
+    assert!(errors.contains(&"UBX-TST-A: fields 'a' and 'b' overlap at offset 2".to_string()));
+    assert!(errors.contains(&"UBX-TST-A: fields span 8 bytes but the payload is 6".to_string()));
+    assert!(errors.contains(&"UBX-TST-A.c: undefined enumeration 'noSuchEnum'".to_string()));
+    assert!(errors.contains(&"UBX-TST-B group items: payload length 14 is not 1 + a multiple of 4".to_string()));
+    assert!(errors.contains(&"enumeration fixType: references unknown message UBX-TST-MISSING".to_string()));
+    assert!(issues
+        .iter()
+        .any(|i| i.severity == Severity::Warning && i.location == "UBX-TST-A.b" && i.message.contains("no unit")));
+    assert!(issues.iter().any(|i| i.location == "UBX-TST-B" && i.message.contains("cannot be told apart")));
+}
@@ -0,0 +1,73 @@
+//! Semantic plausibility warnings on encoded frames.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::schema::default_schema_path;
+use ubx_schema::semantic::{Rule, Validator};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn check(messages: &[Json]) -> Vec<(Rule, String)> {
+    let schema = Schema::load_default().unwrap();
+    let path = default_schema_path().with_file_name("enumerations.json");
+    let enums: Json = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let validator = Validator::new(&schema, Some(&enums));
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    messages
+        .iter()
+        .flat_map(|m| {
+            let frame = encoder.encode_json(m).unwrap();
+            validator.check(&decoder.decode(frame.class, frame.id, &frame.payload))
+        })
+        .map(|w| (w.rule, w.field))
+        .collect()
+}
+
+fn pvt(fix_type: u64, carr_soln: u64, lat: i64, h_acc: u64) -> Json {
+    json!({"name": "NAV-PVT", "fields": {
+        "iTOW": 1000, "fixType": fix_type, "flags": {"gnssFixOK": 1, "carrSoln": carr_soln},
+        "numSV": 20, "lon": 85_123_456, "lat": lat, "hAcc": h_acc
+    }})
+}
+
+#[test]
+fn plausible_messages_have_no_warnings() {
+    let warnings = check(&[
+        pvt(3, 0, 473_977_418, 2_500),
+        pvt(3, 2, -473_977_418, 14),
+        pvt(0, 0, 0, 4_294_967_295),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "satellites": [
+            {"gnssId": 0, "svId": 5, "cno": 45}, {"gnssId": 2, "svId": 11, "cno": 63}
+        ]}}),
+        json!({"name": "CFG-ODO", "fields": {}}),
+    ]);
+    assert_eq!(warnings, []);
+}
+
+#[test]
+fn flags_each_rule() {
+    let warnings = check(&[
+        pvt(7, 0, 473_977_418, 2_500),
+        pvt(3, 0, 950_000_000, 2_500),
+        pvt(3, 2, 473_977_418, 4_000),
+        pvt(3, 1, 473_977_418, 8_000),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "satellites": [
+            {"gnssId": 0, "svId": 5, "cno": 45}, {"gnssId": 9, "svId": 11, "cno": 70}
+        ]}}),
+        json!({"name": "CFG-ODO", "fields": {"version": 3}}),
+    ]);
+    let expected = [
+        (Rule::Enumeration, "fixType"),
+        (Rule::Range, "lat"),
+        (Rule::Accuracy, "hAcc"),
+        (Rule::Enumeration, "satellites[1].gnssId"),
+        (Rule::Range, "satellites[1].cno"),
+        (Rule::FixedValue, "version"),
+    ];
+    let warnings: Vec<(Rule, &str)> = warnings.iter().map(|(r, f)| (*r, f.as_str())).collect();
+    assert_eq!(warnings, expected);
+}
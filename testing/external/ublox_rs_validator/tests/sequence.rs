@@ -0,0 +1,62 @@
+//! Dropped-frame detection from counters and time tags.
+
+use serde_json::{json, Value as Json};
+use ubx_schema::sequence::{SequenceGap, SequenceKind, SequenceTracker};
+use ubx_schema::{Decoder, Encoder, Schema};
+
+fn track(messages: &[Json]) -> SequenceTracker {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let decoder = Decoder::new(&schema);
+    let mut tracker = SequenceTracker::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        tracker.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    tracker
+}
+
+#[test]
+fn log_entries_share_one_index_across_message_types() {
+    let entry = |name: &str, index: u32| json!({"name": name, "fields": {"entryIndex": index}});
+    let tracker = track(&[
+        entry("LOG-RETRIEVEPOS", 0),
+        entry("LOG-RETRIEVESTRING", 1),
+        entry("LOG-RETRIEVEPOS", 2),
+        entry("LOG-RETRIEVEPOSEXTRA", 5),
+        entry("LOG-RETRIEVEPOS", 6),
+        // A second retrieval.
+        entry("LOG-RETRIEVEPOS", 0),
+        entry("LOG-RETRIEVEPOS", 1),
+    ]);
+    let reports = tracker.reports();
+    assert_eq!(reports.len(), 1);
+    let log = &reports[0];
+    assert_eq!((log.name.as_str(), log.kind), ("LOG-RETRIEVE entryIndex", SequenceKind::Index));
+    assert_eq!((log.count, log.resets), (7, 1));
+    assert_eq!(log.gaps, [SequenceGap { after: 2, before: 5, missing: 2 }]);
+}
+
+#[test]
+fn time_tags_wrap_and_counters_report_increases() {
+    let meas = |tag: u32| json!({"name": "ESF-MEAS", "fields": {"timeTag": tag, "id": 3}});
+    let comms = |overruns: u32, skipped: u32| {
+        json!({"name": "MON-COMMS", "fields": {"portID": 0x0101, "overrunErrs": overruns, "skipped": skipped}})
+    };
+    let start = u32::MAX - 25;
+    let mut messages: Vec<Json> = (0..6).map(|i| meas(start.wrapping_add(i * 10))).collect();
+    // Two samples at one tag, then three intervals lost.
+    messages.push(meas(start.wrapping_add(50)));
+    messages.push(meas(start.wrapping_add(90)));
+    messages.extend([comms(0, 0), comms(0, 0), comms(2, 0), comms(2, 120)]);
+    let reports = track(&messages).reports();
+
+    let names: Vec<&str> = reports.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["ESF-MEAS id 3 timeTag", "MON-COMMS port 0x0101 overrunErrs", "MON-COMMS port 0x0101 skipped"]);
+    let esf = &reports[0];
+    assert_eq!((esf.count, esf.resets), (8, 0));
+    let after = u64::from(start.wrapping_add(50));
+    assert_eq!(esf.gaps, [SequenceGap { after, before: after + 40, missing: 3 }]);
+    assert_eq!(reports[1].gaps, [SequenceGap { after: 0, before: 2, missing: 2 }]);
+    assert_eq!((reports[2].missing(), reports[2].kind), (120, SequenceKind::Counter));
+}
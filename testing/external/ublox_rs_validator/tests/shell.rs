@@ -0,0 +1,61 @@
+//! ubx-shell command parsing and completion.
+
+use serde_json::json;
+use ubx_schema::config::{ConfigKeys, Layers};
+use ubx_schema::shell::{complete, parse_command, Command};
+use ubx_schema::{Error, Schema};
+
+#[test]
+fn parses_commands() {
+    assert_eq!(parse_command("  # note").unwrap(), None);
+    assert_eq!(
+        parse_command("poll CFG-PRT 01").unwrap(),
+        Some(Command::Poll {
+            message: "CFG-PRT".into(),
+            payload: vec![0x01]
+        })
+    );
+    let Some(Command::Send { message, fields }) =
+        parse_command("send CFG-RATE measRate=200 navRate=1 flags.a=1 name=GPS").unwrap()
+    else {
+        panic!("expected send");
+    };
+    assert_eq!(message, "CFG-RATE");
+    assert_eq!(
+        serde_json::Value::Object(fields),
+        json!({"measRate": 200, "navRate": 1, "flags": {"a": 1}, "name": "GPS"})
+    );
+    assert_eq!(
+        parse_command("set CFG-NAVSPG-DYNMODEL AUTOMOT ram,flash").unwrap(),
+        Some(Command::Set {
+            key: "CFG-NAVSPG-DYNMODEL".into(),
+            value: json!("AUTOMOT"),
+            layers: Layers {
+                ram: true,
+                bbr: false,
+                flash: true
+            },
+        })
+    );
+    assert!(matches!(parse_command("set CFG-RATE-MEAS"), Err(Error::Command(_))));
+    assert!(matches!(parse_command("frobnicate"), Err(Error::Command(_))));
+    assert!(matches!(parse_command("send CFG-RATE a=1 a.b=2"), Err(Error::Command(_))));
+}
+
+#[test]
+fn completes_commands_messages_keys_and_fields() {
+    let schema = Schema::load_default().unwrap();
+    let keys = ConfigKeys::load_default().unwrap();
+    let complete = |line: &str| complete(&schema, &keys, line, line.len());
+
+    assert_eq!(complete("po"), (0, vec!["poll".to_string()]));
+    let (start, names) = complete("poll nav-pv");
+    assert_eq!(start, 5);
+    assert!(names.contains(&"NAV-PVT".to_string()));
+    assert!(names.iter().all(|n| n.starts_with("NAV-PV")));
+    let (_, keys) = complete("set CFG-RATE-ME");
+    assert_eq!(keys, ["CFG-RATE-MEAS"]);
+    let (start, fields) = complete("send CFG-RATE measRate=100 nav");
+    assert_eq!(start, 27);
+    assert_eq!(fields, ["navRate="]);
+}
@@ -0,0 +1,92 @@
+//! C/N0 statistics from NAV-SAT and NAV-SIG.
+
+use serde_json::json;
+use ubx_schema::gnss::Gnss;
+use ubx_schema::signal::{SignalKey, SignalStats, Source};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn collect(messages: &[serde_json::Value]) -> SignalStats {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut stats = SignalStats::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        stats.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    stats
+}
+
+fn key(source: Source, gnss: Gnss, sv_id: Option<u8>, sig_id: Option<u8>) -> SignalKey {
+    SignalKey {
+        source,
+        gnss_id: gnss.id(),
+        sv_id,
+        sig_id,
+    }
+}
+
+#[test]
+fn nav_sat_statistics_per_satellite_and_constellation() {
+    // Four 1 Hz epochs, the third repeated. G5 is used from the second
+    // epoch on, G7 only seen, E11 untracked (C/N0 0) until the last.
+    let epochs = [(0, [40, 30, 0], false), (1, [44, 32, 0], true), (1, [44, 32, 0], true), (2, [42, 34, 20], true)];
+    let messages: Vec<_> = epochs
+        .iter()
+        .map(|&(t, cno, used)| {
+            let flags = if used { 0x08 } else { 0 };
+            json!({"name": "NAV-SAT", "fields": {"iTOW": 1_000 * t, "numSvs": 3, "satellites": [
+                {"gnssId": 0, "svId": 5, "cno": cno[0], "flags": flags},
+                {"gnssId": 0, "svId": 7, "cno": cno[1], "flags": 0},
+                {"gnssId": 2, "svId": 11, "cno": cno[2], "flags": 0x08},
+            ]}})
+        })
+        .collect();
+    let stats = collect(&messages);
+
+    let g5 = stats.get(&key(Source::NavSat, Gnss::Gps, Some(5), None)).unwrap();
+    assert_eq!((g5.epochs, g5.min, g5.max, g5.mean()), (3, 40.0, 44.0, Some(42.0)));
+    assert_eq!((g5.used_epochs, g5.used_ms), (2, 2_000));
+    let g7 = stats.get(&key(Source::NavSat, Gnss::Gps, Some(7), None)).unwrap();
+    assert_eq!((g7.epochs, g7.used_epochs), (3, 0));
+
+    let gps = stats.get(&key(Source::NavSat, Gnss::Gps, None, None)).unwrap();
+    assert_eq!((gps.epochs, gps.min, gps.max, gps.mean()), (6, 30.0, 44.0, Some(37.0)));
+    assert_eq!((gps.used_epochs, gps.used_ms), (2, 2_000));
+    let galileo = stats.get(&key(Source::NavSat, Gnss::Galileo, None, None)).unwrap();
+    assert_eq!((galileo.epochs, galileo.used_epochs, galileo.used_ms), (1, 1, 1_000));
+
+    let rows = stats.summaries();
+    assert_eq!(rows.len(), 5);
+    assert_eq!((rows[0].gnss.as_str(), rows[0].sv_id), ("GPS", None));
+}
+
+#[test]
+fn nav_sig_statistics_written_as_csv() {
+    let messages: Vec<_> = [(0, 38, 7), (500, 41, 3)]
+        .iter()
+        .map(|&(t, cno, quality)| {
+            json!({"name": "NAV-SIG", "fields": {
+                "iTOW": t, "numSigs": 1, "gnssId": 2, "svId": 11, "sigId": 6, "cno": cno, "qualityInd": quality
+            }})
+        })
+        .collect();
+    let stats = collect(&messages);
+    let e11 = stats.get(&key(Source::NavSig, Gnss::Galileo, Some(11), Some(6))).unwrap();
+    assert_eq!((e11.epochs, e11.min, e11.max, e11.used_epochs, e11.used_ms), (2, 38.0, 41.0, 1, 0));
+
+    let mut out = Vec::new();
+    stats.write_csv(&mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines[0],
+        "source,gnss,sv_id,sig_id,signal,epochs,mean_cno,min_cno,max_cno,used_epochs,used_s"
+    );
+    assert_eq!(lines[1], "NAV-SIG,Galileo,,,,2,39.5,38.0,41.0,1,0.0");
+    assert_eq!(lines[2], "NAV-SIG,Galileo,11,6,7Q,2,39.5,38.0,41.0,1,0.0");
+}
@@ -0,0 +1,76 @@
+//! Scenario simulation: route, timeline and message rates.
+
+use serde_json::json;
+use ubx_schema::sim::{Scenario, Simulator};
+use ubx_schema::track::FixQuality;
+use ubx_schema::{DecodeOptions, Decoder, Schema};
+
+fn scenario(messages: serde_json::Value) -> Scenario {
+    // 0.01° of latitude is about 1112 m: 111.2 s at 10 m/s.
+    serde_json::from_value(json!({
+        "start": "2024-01-07T00:00:10", "duration": 200, "rate": 2,
+        "route": [{"lat": 10.0, "lon": 20.0}, {"lat": 10.01, "lon": 20.0}],
+        "timeline": [{"at": 0, "fix": "no-fix", "satellites": 2}, {"at": 5, "fix": "3d", "satellites": 8},
+                     {"at": 60, "fix": "rtk-fixed"}],
+        "messages": messages,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn follows_route_and_timeline() {
+    let schema = Schema::load_default().unwrap();
+    let sim = Simulator::new(&schema, scenario(json!({"NAV-PVT": 1}))).unwrap();
+    assert_eq!(sim.epochs(), 400);
+
+    let start = sim.state(0.0);
+    assert_eq!((start.week, start.itow, start.fix, start.satellites), (2296, 10_000, FixQuality::NoFix, 2));
+    let moving = sim.state(50.0);
+    assert_eq!((moving.fix, moving.satellites, moving.itow), (FixQuality::Fix3D, 8, 60_000));
+    assert!((moving.position.lat - 10.0045).abs() < 2e-5, "{:?}", moving.position);
+    assert!((moving.velocity[0] - 10.0).abs() < 1e-9 && moving.heading.abs() < 1e-9);
+    let stopped = sim.state(150.0);
+    assert_eq!((stopped.fix, stopped.speed, stopped.position.lat), (FixQuality::RtkFixed, 0.0, 10.01));
+
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let lat_at_100 = sim.state(100.0).position.lat;
+    let mut sim = Simulator::new(&schema, scenario(json!({"NAV-PVT": 1}))).unwrap();
+    let mut fixes = Vec::new();
+    while let Some(epoch) = sim.next_epoch().unwrap() {
+        assert_eq!(epoch.frames.len(), 1);
+        let f = &epoch.frames[0];
+        let msg = decoder.decode(f.class, f.id, &f.payload);
+        assert_eq!(msg.itow(), Some(epoch.state.itow));
+        let fix = (msg.get("fixType").unwrap().as_u64(), msg.get("flags.carrSoln").unwrap().as_u64());
+        if fixes.last() != Some(&fix) {
+            fixes.push(fix);
+        }
+        if epoch.state.time == 100.0 {
+            assert!((msg.get("lat").unwrap().as_f64().unwrap() - lat_at_100).abs() < 1e-7);
+            assert_eq!(msg.get("velN").unwrap().as_i64(), Some(10_000));
+            // GPS 00:01:50 is UTC 00:01:32.
+            assert_eq!((msg.get("min").unwrap().as_u64(), msg.get("sec").unwrap().as_u64()), (Some(1), Some(32)));
+        }
+    }
+    assert_eq!(fixes, [(Some(0), Some(0)), (Some(3), Some(0)), (Some(3), Some(2))]);
+}
+
+#[test]
+fn message_rates_and_supported_set() {
+    let schema = Schema::load_default().unwrap();
+    let mut sim = Simulator::new(&schema, scenario(json!({"NAV-EOE": 1, "NAV-SAT": 4, "NAV-PVT": 1}))).unwrap();
+    let epoch = sim.next_epoch().unwrap().unwrap();
+    assert_eq!(epoch.frames.iter().map(|f| f.id).collect::<Vec<_>>(), [0x07, 0x35, 0x61]);
+    let epoch = sim.next_epoch().unwrap().unwrap();
+    assert_eq!(epoch.frames.iter().map(|f| f.id).collect::<Vec<_>>(), [0x07, 0x61]);
+
+    assert!(Simulator::new(&schema, scenario(json!({"RXM-RAWX": 1}))).is_err());
+    let mut bad = scenario(json!({}));
+    bad.timeline[0].fix = Some("excellent".into());
+    assert!(Simulator::new(&schema, bad).is_err());
+}
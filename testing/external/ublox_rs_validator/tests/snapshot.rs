@@ -0,0 +1,132 @@
+//! Snapshots of the full decoded JSON of one representative frame per
+//! message and variant, in `tests/snapshots/`. Any change to field names,
+//! order, scaling or bitfield expansion fails here and shows up as a diff of
+//! the snapshot files once they are regenerated with
+//! `UBX_UPDATE_SNAPSHOTS=1 cargo test --test snapshot`.
+//!
+//! Frames are built from counting values (1, 2, 3, ... in field order,
+//! negated for signed types, two elements per variable-length array) so a
+//! byte order or offset change moves a recognisable number.
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{payloads, Generator, Source};
+use serde::Serialize;
+use serde_json::Value as Json;
+use ubx_schema::schema::BaseType;
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Encoder, Schema};
+
+struct Counting(u64);
+
+impl Source for Counting {
+    fn scalar(&mut self, base: BaseType) -> Json {
+        self.0 += 1;
+        let k = self.0;
+        match base {
+            BaseType::I1 | BaseType::I2 | BaseType::I4 | BaseType::I8 => Json::from(-((k % 100) as i64) - 1),
+            BaseType::R4 => Json::from(k as f64 + 0.5),
+            BaseType::R8 => Json::from(k as f64 + 0.25),
+            BaseType::CH => Json::from(char::from(b'A' + (k % 26) as u8).to_string()),
+            _ => Json::from(k % 200 + 1),
+        }
+    }
+
+    fn count(&mut self) -> u64 {
+        2
+    }
+
+    fn text(&mut self, n: u64) -> String {
+        (0..n).map(|i| char::from(b'A' + (i % 26) as u8)).collect()
+    }
+}
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    frame: String,
+    decoded: &'a DecodedMessage,
+}
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots")
+}
+
+/// Snapshot file contents by file name, for every message and variant.
+fn render(schema: &Schema) -> BTreeMap<String, String> {
+    let encoder = Encoder::new(schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(schema, options);
+    let mut out = BTreeMap::new();
+    for msg in &schema.messages {
+        for (variant, def, skip) in payloads(msg) {
+            let fields = Generator { source: &mut Counting(0), room: 1 }.payload(def, skip);
+            let frame = encoder.encode(&msg.name, variant, &fields).unwrap();
+            let decoded = decoder.decode(frame.class, frame.id, &frame.payload);
+            assert!(decoded.parsed, "{} {variant:?} did not decode", msg.name);
+            let snapshot = Snapshot {
+                frame: hex::encode(frame.to_bytes()),
+                decoded: &decoded,
+            };
+            let name = match variant {
+                Some(v) => format!("{}.{v}.json", msg.short_name()),
+                None => format!("{}.json", msg.short_name()),
+            };
+            out.insert(name, serde_json::to_string_pretty(&snapshot).unwrap() + "\n");
+        }
+    }
+    out
+}
+
+/// First differing line, for the failure message.
+fn first_change(old: &str, new: &str) -> String {
+    let (old_lines, new_lines): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+    let i = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+    let line = |lines: &[&str]| lines.get(i).map_or("<end>", |l| l.trim()).to_string();
+    format!("line {}: {} -> {}", i + 1, line(&old_lines), line(&new_lines))
+}
+
+#[test]
+fn decoded_output_matches_snapshots() {
+    let schema = Schema::load_default().unwrap();
+    let rendered = render(&schema);
+    let dir = snapshot_dir();
+    let update = env::var_os("UBX_UPDATE_SNAPSHOTS").is_some();
+    if update {
+        fs::create_dir_all(&dir).unwrap();
+    }
+
+    let mut problems = Vec::new();
+    for (name, new) in &rendered {
+        let path = dir.join(name);
+        match fs::read_to_string(&path) {
+            Ok(old) if old == *new => {}
+            _ if update => fs::write(&path, new).unwrap(),
+            Ok(old) => problems.push(format!("{name} changed, {}", first_change(&old, new))),
+            Err(_) => problems.push(format!("{name} is new")),
+        }
+    }
+    for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".json") && !rendered.contains_key(&name) {
+            if update {
+                fs::remove_file(entry.path()).unwrap();
+            } else {
+                problems.push(format!("{name} has no message any more"));
+            }
+        }
+    }
+    assert!(
+        problems.is_empty(),
+        "{} snapshots differ; review and rerun with UBX_UPDATE_SNAPSHOTS=1 to accept:\n{}",
+        problems.len(),
+        problems.join("\n")
+    );
+}
@@ -0,0 +1,101 @@
+//! UPD-SOS backup and restore checks against a scripted receiver.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::sos::{RestoreStatus, Sos, SosReply};
+use ubx_schema::transact::RetryPolicy;
+use ubx_schema::{Frame, Schema};
+
+/// Answers the n-th write with the n-th scripted reply and keeps what was
+/// written; reads time out when there is nothing to deliver.
+struct Receiver {
+    replies: VecDeque<Vec<u8>>,
+    pending: Vec<u8>,
+    written: Vec<u8>,
+}
+
+impl Receiver {
+    fn new(pending: Vec<u8>, replies: Vec<Vec<u8>>) -> Self {
+        Self {
+            replies: replies.into(),
+            pending,
+            written: Vec::new(),
+        }
+    }
+}
+
+impl Read for Receiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Write for Receiver {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        if let Some(reply) = self.replies.pop_front() {
+            self.pending.extend(reply);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn reply(cmd: u8, response: u8) -> Vec<u8> {
+    Frame::new(0x09, 0x14, vec![cmd, 0, 0, 0, response, 0, 0, 0]).to_bytes()
+}
+
+#[test]
+fn backup_stops_gnss_and_resends_until_acknowledged() {
+    // Nothing for the GNSS stop or the first backup command, then the ack;
+    // a restore report on the way is not an acknowledgment.
+    let mut receiver = Receiver::new(Vec::new(), vec![vec![], vec![], [reply(3, 2), reply(2, 1)].concat()]);
+    let policy = RetryPolicy {
+        timeout: Duration::from_millis(50),
+        retries: 2,
+        backoff: Duration::from_millis(1),
+        retry_nak: true,
+    };
+    let schema = Schema::load_default().unwrap();
+    let ack = Sos::new(&schema).create_backup(&mut receiver, &mut FrameParser::new(), &policy).unwrap();
+    assert_eq!(ack, Some(true));
+
+    let sent: Vec<(u8, u8, Vec<u8>)> =
+        frames(&receiver.written).flatten().map(|f| (f.class, f.id, f.payload.to_vec())).collect();
+    assert_eq!(sent[0], (0x06, 0x04, vec![0, 0, 0x08, 0]));
+    assert_eq!(sent[1..], [(0x09, 0x14, vec![0, 0, 0, 0]), (0x09, 0x14, vec![0, 0, 0, 0])]);
+}
+
+#[test]
+fn restore_status_polls_when_the_startup_report_is_missed() {
+    let schema = Schema::load_default().unwrap();
+    let sos = Sos::new(&schema);
+    let timeout = Duration::from_millis(50);
+    let mut quiet = Receiver::new(Vec::new(), vec![reply(3, 2)]);
+    let status = sos.restore_status(&mut quiet, &mut FrameParser::new(), timeout).unwrap();
+    assert_eq!(status, Some(RestoreStatus::Restored));
+    let polls: Vec<usize> = frames(&quiet.written).flatten().map(|f| f.payload.len()).collect();
+    assert_eq!(polls, [0]);
+
+    // A startup report already waiting is taken without polling.
+    let mut booted = Receiver::new(reply(3, 3), Vec::new());
+    let status = sos.restore_status(&mut booted, &mut FrameParser::new(), timeout).unwrap();
+    assert_eq!(status, Some(RestoreStatus::NotRestored));
+    assert!(booted.written.is_empty());
+
+    let nak = Frame::new(0x09, 0x14, vec![2, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(sos.parse(&nak), Some(SosReply::Ack(false)));
+    assert_eq!(sos.parse(&Frame::new(0x09, 0x14, vec![0, 0, 0, 0])), None);
+}
@@ -0,0 +1,111 @@
+//! SQL generated from the schema and from encoded NAV-PVT and NAV-SAT frames.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::json;
+use ubx_schema::frame::frames;
+use ubx_schema::sqlite::{schema_ddl, schema_tables, Dialect, SqlType, SqlWriter, Table};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn options() -> DecodeOptions {
+    DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        ..DecodeOptions::default()
+    }
+}
+
+#[test]
+fn columns_follow_the_decoded_fields() {
+    let schema = Schema::load_default().unwrap();
+    let pvt = schema.message_by_name("UBX-NAV-PVT").unwrap();
+    let table = Table::for_payload(pvt, None, pvt.payload.as_ref().unwrap(), options());
+    assert_eq!(table.name, "nav_pvt");
+    let column = |name: &str| table.columns.iter().find(|c| c.name == name);
+    assert_eq!(column("lat").unwrap().sql_type, SqlType::Real);
+    assert_eq!(column("numSV").unwrap().sql_type, SqlType::Integer);
+    assert_eq!(column("flags_carrSoln").unwrap().path, "flags.carrSoln");
+    assert!(column("flags").is_none() && table.columns.iter().all(|c| !c.name.starts_with("reserved")));
+
+    let raw = Table::for_payload(pvt, None, pvt.payload.as_ref().unwrap(), DecodeOptions::default());
+    assert_eq!(raw.columns.iter().find(|c| c.name == "lat").unwrap().sql_type, SqlType::Integer);
+
+    let sat = schema.message_by_name("UBX-NAV-SAT").unwrap();
+    let table = Table::for_payload(sat, None, sat.payload.as_ref().unwrap(), options());
+    let (group, child) = &table.groups[0];
+    assert_eq!((group.as_str(), child.name.as_str()), ("satellites", "nav_sat_satellites"));
+    assert!(table.ddl().contains("CREATE TABLE IF NOT EXISTS nav_sat_satellites (frame INTEGER NOT NULL"));
+}
+
+#[test]
+fn builds_a_queryable_database() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let mut capture = Vec::new();
+    for m in [
+        json!({"name": "NAV-PVT", "fields": {
+            "iTOW": 1000, "fixType": 3, "flags": {"gnssFixOK": 1}, "lat": 473_977_418
+        }}),
+        json!({"name": "NAV-SAT", "fields": {"iTOW": 1000, "version": 1, "satellites": [
+            {"gnssId": 0, "svId": 12, "cno": 45}, {"gnssId": 2, "svId": 11, "cno": 38}
+        ]}}),
+        json!({"name": "NAV-PVT", "fields": {"iTOW": 2000, "fixType": 3, "lat": 473_977_500}}),
+    ] {
+        capture.extend(encoder.encode_json(&m).unwrap().to_bytes());
+    }
+    let decoder = Decoder::with_options(&schema, options());
+    let mut writer = SqlWriter::new(&schema, options(), Vec::new()).unwrap();
+    for frame in frames(&capture).flatten() {
+        writer.write(&frame, &decoder.decode_frame(&frame)).unwrap();
+    }
+    let sql = String::from_utf8(writer.finish().unwrap()).unwrap();
+    assert_eq!(sql.matches("CREATE TABLE IF NOT EXISTS nav_pvt ").count(), 1);
+    assert!(sql.contains("INSERT INTO nav_sat_satellites (frame, idx, \"gnssId\", \"svId\", \"cno\""));
+
+    // Run the script when the sqlite3 shell is installed.
+    let Ok(mut child) = Command::new("sqlite3")
+        .arg(":memory:")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+    else {
+        return;
+    };
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(sql.as_bytes()).unwrap();
+    stdin
+        .write_all(
+            b"SELECT count(*) FROM frames;\n\
+              SELECT f.itow, p.lat, p.flags_gnssFixOK FROM nav_pvt p JOIN frames f ON f.id = p.frame ORDER BY f.id;\n\
+              SELECT s.svId, s.cno FROM nav_sat_satellites s ORDER BY s.idx;\n",
+        )
+        .unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "3\n1000|47.3977418|1\n2000|47.39775|0\n12|45\n11|38\n"
+    );
+}
+
+#[test]
+fn postgres_ddl_types_columns_by_base_type() {
+    let schema = Schema::load_default().unwrap();
+    let only = ["NAV-PVT".to_string(), "NAV-SAT".to_string()];
+    let tables = schema_tables(&schema, &only, options());
+    assert_eq!(tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["nav_pvt", "nav_sat"]);
+    let pvt = &tables[0];
+    let column = |name: &str| pvt.columns.iter().find(|c| c.name == name).unwrap().type_name(Dialect::Postgres);
+    assert_eq!((column("iTOW"), column("year"), column("numSV")), ("BIGINT", "INTEGER", "SMALLINT"));
+    assert_eq!((column("lat"), column("flags_carrSoln")), ("DOUBLE PRECISION", "SMALLINT"));
+
+    let sql = schema_ddl(&schema, &only, options(), Dialect::Postgres);
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS frames (id BIGSERIAL PRIMARY KEY"));
+    assert!(sql.contains("CREATE TABLE IF NOT EXISTS nav_pvt (frame BIGINT PRIMARY KEY REFERENCES frames(id), "));
+    assert!(sql.contains("\"svId\" SMALLINT"));
+    assert_eq!("postgresql".parse::<Dialect>(), Ok(Dialect::Postgres));
+    // The SQLite dialect is what SqlWriter creates.
+    assert_eq!(pvt.ddl_for(Dialect::Sqlite), pvt.ddl());
+}
@@ -0,0 +1,55 @@
+//! Message rate and gap statistics.
+
+use serde_json::json;
+use ubx_schema::frame::frames;
+use ubx_schema::stats::{Gap, LogStats};
+use ubx_schema::{Decoder, Encoder, Schema};
+
+#[test]
+fn reports_rates_gaps_and_checksum_errors() {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let mut log = Vec::new();
+    for itow in [1_000u32, 2_000, 3_000, 6_000, 7_010, 8_000] {
+        let frame = encoder
+            .encode_json(&json!({"name": "NAV-PVT", "fields": {"iTOW": itow}}))
+            .unwrap();
+        log.extend(frame.to_bytes());
+    }
+    let mut corrupt = encoder
+        .encode_json(&json!({"name": "NAV-PVT", "fields": {"iTOW": 9_000}}))
+        .unwrap()
+        .to_bytes();
+    *corrupt.last_mut().unwrap() ^= 0xFF;
+    log.extend(corrupt);
+
+    let decoder = Decoder::new(&schema);
+    let mut stats = LogStats::new();
+    let mut iter = frames(&log);
+    for frame in iter.by_ref() {
+        match frame {
+            Ok(frame) => stats.push(&decoder.decode_frame(&frame), frame.raw.len()),
+            Err(e) => stats.push_error(&schema, &e),
+        }
+    }
+    stats.scan = iter.stats();
+
+    let pvt = &stats.messages["NAV-PVT"];
+    assert_eq!(pvt.count, 6);
+    assert_eq!(pvt.bytes, 6 * 100);
+    assert_eq!(pvt.checksum_errors, 1);
+    assert_eq!(pvt.nominal_interval(), Some(1_000));
+    assert_eq!(pvt.rate_hz(), Some(1.0));
+    assert_eq!(
+        pvt.gaps(),
+        [Gap {
+            after: 3_000,
+            before: 6_000,
+            missing: 2
+        }]
+    );
+    // 1000, 1000, 1010, 990 outside the gap.
+    assert!((pvt.jitter().unwrap() - 50f64.sqrt()).abs() < 1e-9);
+    assert_eq!(stats.duration(), 7.0);
+    assert_eq!(stats.scan.checksum_errors, 1);
+}
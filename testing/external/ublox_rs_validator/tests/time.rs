@@ -0,0 +1,136 @@
+//! Time-of-week arithmetic.
+
+use serde_json::json;
+use ubx_schema::time::{
+    datetime_to_gps, gps_to_datetime, gps_to_utc, itow_delta, parse_iso8601, resolve_week, unix_to_datetime,
+    DateTime, LeapSeconds, WeekReference, MS_PER_WEEK,
+};
+use ubx_schema::{Decoder, Encoder, Schema};
+
+#[test]
+fn itow_delta_wraps_at_week_boundary() {
+    assert_eq!(itow_delta(1_000, 1_250), 250);
+    assert_eq!(itow_delta(1_250, 1_000), -250);
+    assert_eq!(itow_delta(MS_PER_WEEK - 500, 500), 1_000);
+    assert_eq!(itow_delta(500, MS_PER_WEEK - 500), -1_000);
+}
+
+#[test]
+fn gps_week_to_calendar() {
+    let t = gps_to_datetime(0, 0.0);
+    assert_eq!((t.year, t.month, t.day, t.hour), (1980, 1, 6, 0));
+    // 2024-01-15 12:34:56 GPS is week 2297, Monday.
+    let t = gps_to_datetime(2297, 86_400.0 + 12.0 * 3600.0 + 34.0 * 60.0 + 56.5);
+    assert_eq!((t.year, t.month, t.day, t.hour, t.minute), (2024, 1, 15, 12, 34));
+    assert!((t.second - 56.5).abs() < 1e-9);
+}
+
+#[test]
+fn resolves_truncated_week_numbers() {
+    // Week 2297 is transmitted as 249 in the 10-bit LNAV field.
+    assert_eq!(resolve_week(249, 10, 2290), 2297);
+    assert_eq!(resolve_week(1020, 10, 2050), 2044);
+    assert_eq!(resolve_week(3, 10, 2045), 2051);
+}
+
+#[test]
+fn calendar_round_trips_through_unix_time() {
+    let t = unix_to_datetime(1_705_320_000.25);
+    assert_eq!((t.year, t.month, t.day, t.hour, t.minute), (2024, 1, 15, 12, 0));
+    assert_eq!(t.to_unix(), 1_705_320_000.25);
+    assert_eq!(t.to_iso8601(), "2024-01-15T12:00:00.250Z");
+    let late = DateTime { second: 59.9996, ..unix_to_datetime(1_705_320_000.0) };
+    assert_eq!(late.to_iso8601(), "2024-01-15T12:01:00.000Z");
+}
+
+#[test]
+fn parses_iso8601() {
+    let t = parse_iso8601("2024-01-15T12:34:56.250Z").unwrap();
+    assert_eq!((t.year, t.month, t.day, t.hour, t.minute), (2024, 1, 15, 12, 34));
+    assert!((t.second - 56.25).abs() < 1e-9);
+    assert_eq!(parse_iso8601("2024-01-15").unwrap().hour, 0);
+    assert_eq!(parse_iso8601("2024-01-15 08:30").unwrap().minute, 30);
+    assert!(parse_iso8601("2024-13-01").is_none());
+    assert!(parse_iso8601("yesterday").is_none());
+}
+
+#[test]
+fn week_reference_from_time_messages() {
+    let schema = Schema::load_default().unwrap();
+    let decoder = Decoder::new(&schema);
+    // NAV-TIMEGPS: iTOW, fTOW, week 2297, leapS 18, valid, tAcc.
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&0u32.to_le_bytes());
+    payload.extend_from_slice(&0i32.to_le_bytes());
+    payload.extend_from_slice(&2297i16.to_le_bytes());
+    payload.extend_from_slice(&[18, 0x07]);
+    payload.extend_from_slice(&10u32.to_le_bytes());
+
+    let mut reference = WeekReference::new();
+    assert_eq!(reference.resolve(249, 10), None);
+    payload[11] = 0x01; // week not valid
+    assert!(!reference.observe(&decoder.decode(0x01, 0x20, &payload)));
+    payload[11] = 0x07;
+    assert!(reference.observe(&decoder.decode(0x01, 0x20, &payload)));
+    assert_eq!(reference.week(), Some(2297));
+    assert_eq!(reference.resolve(249, 10), Some(2297));
+    assert_eq!(reference.resolve(249, 8), Some(2297));
+
+    // A receiver with a rollover fault reporting 1024 weeks early.
+    let stale = gps_to_datetime(2297 - 1024, 3600.0);
+    assert_eq!(stale.year, 2004);
+    let t = reference.correct(&stale);
+    assert_eq!((t.year, t.month, t.day, t.hour), (2024, 1, 14, 1));
+    assert_eq!(datetime_to_gps(&t), (2297, 3600.0));
+
+    // A fixed reference isn't moved by messages.
+    let mut fixed = WeekReference::at(&parse_iso8601("2018-06-01").unwrap());
+    assert_eq!(fixed.week(), Some(2003));
+    assert!(!fixed.observe(&decoder.decode(0x01, 0x20, &payload)));
+    assert_eq!(fixed.resolve(990, 10), Some(2014));
+}
+
+#[test]
+fn leap_seconds_from_table_and_receiver() {
+    // The 2017 leap second: GPS − UTC became 18 at 2017-01-01T00:00:00 UTC.
+    let (week, tow) = datetime_to_gps(&parse_iso8601("2017-01-01T00:00:18").unwrap());
+    let utc = gps_to_utc(week, tow - 1.5);
+    assert_eq!((utc.time.to_iso8601().as_str(), utc.leap_seconds), ("2016-12-31T23:59:59.500Z", 17));
+    let utc = gps_to_utc(week, tow - 0.5);
+    assert_eq!((utc.time.to_iso8601().as_str(), utc.leap_seconds), ("2016-12-31T23:59:60.500Z", 17));
+    let utc = gps_to_utc(week, tow);
+    assert_eq!((utc.time.to_iso8601().as_str(), utc.leap_seconds), ("2017-01-01T00:00:00.000Z", 18));
+    assert!(!utc.valid);
+
+    // An announced leap second the table doesn't know, 10 s ahead.
+    let schema = Schema::load_default().unwrap();
+    let (encoder, decoder) = (Encoder::new(&schema), Decoder::new(&schema));
+    let decode = |msg: serde_json::Value| {
+        let frame = encoder.encode_json(&msg).unwrap();
+        decoder.decode(frame.class, frame.id, &frame.payload)
+    };
+    let (week, tow) = datetime_to_gps(&parse_iso8601("2027-01-01T00:00:19").unwrap());
+    let timels = json!({"name": "NAV-TIMELS", "fields": {
+        "iTOW": (tow as u32 - 10) * 1_000, "version": 0, "currLs": 18, "lsChange": 1, "timeToLsEvent": 10,
+        "dateOfLsGpsWn": week, "valid": {"validCurrLs": 1, "validTimeToLsEvent": 1}
+    }});
+    let mut leap = LeapSeconds::new();
+    assert!(leap.observe(&decode(timels)));
+    assert_eq!(gps_to_utc(week, tow).leap_seconds, 18);
+    let utc = leap.to_utc(week, tow - 0.5);
+    assert_eq!((utc.time.to_iso8601().as_str(), utc.leap_seconds), ("2026-12-31T23:59:60.500Z", 18));
+    let utc = leap.to_utc(week, tow);
+    assert_eq!((utc.time.to_iso8601().as_str(), utc.leap_seconds, utc.valid), ("2027-01-01T00:00:00.000Z", 19, true));
+
+    // The new offset from NAV-TIMEGPS after the event keeps it.
+    let timegps = |leaps: i32| {
+        json!({"name": "NAV-TIMEGPS", "fields": {
+            "iTOW": tow as u32 * 1_000, "week": week, "leaps": leaps,
+            "valid": {"towValid": 1, "weekValid": 1, "leapSValid": 1}
+        }})
+    };
+    leap.observe(&decode(timegps(19)));
+    assert_eq!(leap.to_utc(week, tow - 0.5).time.second, 60.5);
+    leap.observe(&decode(timegps(20)));
+    assert_eq!((leap.current(), leap.to_utc(week, tow - 0.5).leap_seconds), (Some(20), 20));
+}
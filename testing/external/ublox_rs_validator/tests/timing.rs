@@ -0,0 +1,69 @@
+//! TIM-TP and NAV-TIMEUTC timing statistics.
+
+use serde_json::json;
+use ubx_schema::timing::{TimeSource, TimingAnalyzer, TimingReport};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn analyze(messages: &[serde_json::Value]) -> TimingReport {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        expand_flags: true,
+        ..DecodeOptions::default()
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut timing = TimingAnalyzer::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        timing.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    timing.report()
+}
+
+#[test]
+fn quantization_error_missed_pulses_and_source_changes() {
+    // 1 PPS across a week rollover; the pulse at towMS 604_799_000 is
+    // missing, the fourth has an invalid qErr, the last two are on UTC.
+    let pulses = [
+        (2_300, 604_797_000, -1_000, 0, 0),
+        (2_300, 604_798_000, 3_000, 0, 0),
+        (2_301, 0, 500, 0, 0),
+        (2_301, 1_000, 99_999, 0x10, 0),
+        (2_301, 2_000, 1_500, 0x03, 0x30),
+        (2_301, 3_000, -2_000, 0x03, 0x30),
+    ];
+    let messages: Vec<_> = pulses
+        .iter()
+        .map(|&(week, tow, q_err, flags, ref_info)| {
+            json!({"name": "TIM-TP", "fields": {"week": week, "towMS": tow, "qErr": q_err,
+                "flags": flags, "refInfo": ref_info}})
+        })
+        .collect();
+    let report = analyze(&messages);
+
+    assert_eq!((report.pulses, report.missed_pulses, report.q_err_invalid), (6, 1, 1));
+    assert_eq!((report.q_err.count, report.q_err.min, report.q_err.max), (5, -2_000.0, 3_000.0));
+    assert_eq!(report.q_err.mean(), Some(400.0));
+    assert_eq!(report.sources[&TimeSource::Gnss(0)], 4);
+    assert_eq!(report.source_changes.len(), 1);
+    let change = report.source_changes[0];
+    assert_eq!((change.week, change.tow_ms), (2_301, 2_000));
+    assert_eq!((change.from.to_string(), change.to.to_string()), ("GPS time".into(), "UTC(USNO)".into()));
+}
+
+#[test]
+fn utc_validity_and_time_accuracy() {
+    let epochs = [(0, false, 0), (1_000, false, 0), (2_000, true, 40), (3_000, true, 20), (4_000, false, 0)];
+    let messages: Vec<_> = epochs
+        .iter()
+        .map(|&(itow, valid, t_acc)| {
+            json!({"name": "NAV-TIMEUTC", "fields": {"iTOW": itow, "tAcc": t_acc,
+                "valid": {"validTOW": 1, "validWKN": 1, "validUTC": u8::from(valid)}}})
+        })
+        .collect();
+    let report = analyze(&messages);
+
+    assert_eq!((report.utc_epochs, report.utc_valid, report.utc_losses), (5, 2, 1));
+    assert_eq!(report.time_to_utc_ms, Some(2_000));
+    assert_eq!((report.t_acc.mean(), report.t_acc.max), (Some(30.0), 40.0));
+}
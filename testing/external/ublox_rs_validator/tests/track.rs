@@ -0,0 +1,79 @@
+//! Track export from encoded NAV-PVT and NAV-HPPOSLLH frames.
+
+use serde_json::json;
+use ubx_schema::track::{write_gpx, write_kml, FixQuality, TrackBuilder};
+use ubx_schema::{DecodeOptions, Decoder, Encoder, Schema};
+
+fn pvt(itow: u32, carr_soln: u64, fix_type: u64) -> serde_json::Value {
+    json!({"name": "NAV-PVT", "fields": {
+        "iTOW": itow, "year": 2024, "month": 1, "day": 15, "hour": 12, "min": 0, "sec": itow / 1000,
+        "valid": {"validDate": 1, "validTime": 1}, "fixType": fix_type,
+        "flags": {"gnssFixOK": 1, "carrSoln": carr_soln}, "numSV": 20,
+        "lon": 85_123_456, "lat": 473_977_418, "hMSL": 412_345, "hAcc": 15, "pDOP": 120
+    }})
+}
+
+fn track(messages: &[serde_json::Value]) -> TrackBuilder {
+    let schema = Schema::load_default().unwrap();
+    let encoder = Encoder::new(&schema);
+    let options = DecodeOptions {
+        scale: true,
+        expand_flags: true,
+        include_reserved: false,
+    };
+    let decoder = Decoder::with_options(&schema, options);
+    let mut track = TrackBuilder::new();
+    for m in messages {
+        let frame = encoder.encode_json(m).unwrap();
+        track.push(&decoder.decode(frame.class, frame.id, &frame.payload));
+    }
+    track
+}
+
+#[test]
+fn builds_points_with_fix_quality_and_hp_position() {
+    let track = track(&[
+        pvt(1000, 0, 3),
+        pvt(2000, 2, 3),
+        json!({"name": "NAV-HPPOSLLH", "fields": {
+            "iTOW": 2000, "lon": 85_123_456, "lat": 473_977_418, "lonHp": 12, "latHp": -34,
+            "hMSL": 412_345, "hMSLHp": 7, "hAcc": 140
+        }}),
+        pvt(3000, 0, 0),
+    ]);
+    let points = track.points();
+    // The no-fix epoch is dropped.
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0].fix, FixQuality::Fix3D);
+    assert_eq!(points[1].fix, FixQuality::RtkFixed);
+    assert!((points[0].lat - 47.3977418).abs() < 1e-12);
+    assert!((points[1].lat - 47.397741766).abs() < 1e-12);
+    assert!((points[1].lon - 8.512345612).abs() < 1e-12);
+    assert!((points[1].alt_msl - 412.3457).abs() < 1e-9);
+    assert!((points[1].h_acc - 0.014).abs() < 1e-12);
+    assert_eq!(points[0].pdop, Some(1.2));
+    assert_eq!(points[0].time.unwrap().to_iso8601(), "2024-01-15T12:00:01.000Z");
+}
+
+#[test]
+fn writes_gpx_and_kml_per_fix_quality() {
+    let track = track(&[pvt(1000, 0, 3), pvt(2000, 0, 3), pvt(3000, 1, 3)]);
+
+    let mut gpx = Vec::new();
+    write_gpx("drive", track.points(), &mut gpx).unwrap();
+    let gpx = String::from_utf8(gpx).unwrap();
+    assert_eq!(gpx.matches("<trk>").count(), 2);
+    assert!(gpx.contains("<name>drive (3D fix)</name>"));
+    assert!(gpx.contains("<gpxx:DisplayColor>Cyan</gpxx:DisplayColor>"));
+    assert!(gpx.contains(r#"<trkpt lat="47.397741800" lon="8.512345600">"#));
+    assert!(gpx.contains("<time>2024-01-15T12:00:02.000Z</time>"));
+    // The boundary point is shared so the line stays continuous.
+    assert_eq!(gpx.matches("<trkpt").count(), 4);
+
+    let mut kml = Vec::new();
+    write_kml("drive", track.points(), &mut kml).unwrap();
+    let kml = String::from_utf8(kml).unwrap();
+    assert!(kml.contains(r#"<Style id="RtkFloat"><LineStyle><color>ffffff00</color>"#));
+    assert!(kml.contains("      8.512345600,47.397741800,412.3450"));
+    assert_eq!(kml.matches("<Placemark>").count(), 2);
+}
@@ -0,0 +1,107 @@
+//! ACK waiting, retry and backoff against a scripted receiver.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use ubx_schema::frame::FrameParser;
+use ubx_schema::transact::{send_with_ack, wait_for, Outcome, RetryPolicy};
+use ubx_schema::Frame;
+
+/// Answers the n-th write with the n-th scripted reply; reads time out when
+/// there is nothing to deliver, like a serial port.
+struct Receiver {
+    replies: VecDeque<Vec<u8>>,
+    pending: Vec<u8>,
+    writes: usize,
+}
+
+impl Receiver {
+    fn new(replies: Vec<Vec<u8>>) -> Self {
+        Self {
+            replies: replies.into(),
+            pending: Vec::new(),
+            writes: 0,
+        }
+    }
+}
+
+impl Read for Receiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Write for Receiver {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        if let Some(reply) = self.replies.pop_front() {
+            self.pending.extend(reply);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn ack(accepted: bool, class: u8, id: u8) -> Vec<u8> {
+    Frame::new(0x05, u8::from(accepted), vec![class, id]).to_bytes()
+}
+
+fn policy(retries: u32, retry_nak: bool) -> RetryPolicy {
+    RetryPolicy {
+        timeout: Duration::from_millis(20),
+        retries,
+        backoff: Duration::from_millis(1),
+        retry_nak,
+    }
+}
+
+#[test]
+fn retries_after_silence_and_nak_until_acknowledged() {
+    let rate = Frame::new(0x06, 0x08, vec![0xC8, 0x00, 0x01, 0x00, 0x01, 0x00]);
+    // An ACK for another message and a PVT arrive first; neither counts.
+    let noise = [ack(true, 0x06, 0x01), Frame::new(0x01, 0x07, vec![0; 92]).to_bytes()].concat();
+    let mut port = Receiver::new(vec![noise, ack(false, 0x06, 0x08), ack(true, 0x06, 0x08)]);
+    let mut parser = FrameParser::new();
+
+    let report = send_with_ack(&mut port, &mut parser, &rate, &policy(2, true)).unwrap();
+    assert_eq!((report.outcome, report.attempts, port.writes), (Outcome::Ack, 3, 3));
+    assert!(report.is_ack());
+    assert!(report.to_string().starts_with("ACK-ACK after 3 attempts"));
+
+    let mut port = Receiver::new(vec![ack(false, 0x06, 0x08), ack(true, 0x06, 0x08)]);
+    let report = send_with_ack(&mut port, &mut parser, &rate, &policy(2, false)).unwrap();
+    assert_eq!((report.outcome, report.attempts), (Outcome::Nak, 1));
+
+    let mut port = Receiver::new(vec![]);
+    let report = send_with_ack(&mut port, &mut parser, &rate, &policy(1, true)).unwrap();
+    assert_eq!((report.outcome, report.attempts, port.writes), (Outcome::NoReply, 2, 2));
+    assert!(report.to_string().starts_with("no acknowledgment after 2 attempts"));
+
+    let backoff = RetryPolicy {
+        backoff: Duration::from_millis(100),
+        ..RetryPolicy::default()
+    };
+    assert_eq!(backoff.delay(1), Duration::from_millis(100));
+    assert_eq!(backoff.delay(3), Duration::from_millis(400));
+}
+
+#[test]
+fn wait_for_returns_the_first_matching_frame() {
+    let mut input: &[u8] = &[ack(false, 0x06, 0x8A), ack(true, 0x06, 0x8A)].concat();
+    let mut parser = FrameParser::new();
+    let accepted = |f: &Frame| (f.class == 0x05 && f.id == 0x01).then(|| f.payload.clone());
+    let found = wait_for(&mut input, &mut parser, Duration::from_secs(1), accepted).unwrap();
+    assert_eq!(found, Some(vec![0x06, 0x8A]));
+    // End of input ends the wait without a match.
+    assert_eq!(wait_for(&mut input, &mut parser, Duration::from_secs(1), accepted).unwrap(), None);
+}
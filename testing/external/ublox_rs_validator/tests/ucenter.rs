@@ -0,0 +1,31 @@
+//! u-center logs with interleaved protocols.
+
+use ubx_schema::frame::Frame;
+use ubx_schema::ntrip::{nmea_checksum, RtcmFrame};
+use ubx_schema::ucenter::{self, Container};
+
+fn nmea(body: &str) -> Vec<u8> {
+    format!("${body}*{:02X}\r\n", nmea_checksum(body)).into_bytes()
+}
+
+#[test]
+fn detects_interleaved_logs() {
+    let mut log = Frame::new(0x01, 0x07, vec![0; 92]).to_bytes();
+    log.extend(nmea("GNGGA,120000.00,,,,,0,00,99.99,,,,,,"));
+    log.extend(RtcmFrame { message_type: 1005, payload: vec![0x3E, 0xD0, 0, 0, 0, 0] }.to_bytes());
+    log.extend(b"junk");
+    log.extend(nmea("GNRMC,120000.00,V,,,,,,,140126,,,N,V"));
+    log.extend(Frame::new(0x01, 0x35, vec![0; 8]).to_bytes());
+
+    let stats = ucenter::scan(&log);
+    assert_eq!((stats.ubx_frames, stats.nmea_sentences, stats.rtcm_messages), (2, 2, 1));
+    assert_eq!(stats.other_bytes, 4);
+    assert_eq!(ucenter::detect(&log), Container::Interleaved);
+
+    // NMEA switched on well into the log still counts.
+    let pvt = Frame::new(0x01, 0x07, vec![0; 92]).to_bytes();
+    let mut late = pvt.repeat(1_000);
+    assert_eq!(ucenter::detect(&late), Container::Ubx);
+    late.extend(nmea("GNGGA,120000.00,,,,,0,00,99.99,,,,,,"));
+    assert_eq!(ucenter::detect(&late), Container::Interleaved);
+}
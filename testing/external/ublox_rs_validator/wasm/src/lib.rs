@@ -0,0 +1,156 @@
+//! WebAssembly bindings for the schema decoder, so browser tools can decode
+//! UBX captures client-side with the canonical schema.
+//!
+//! The schema is embedded at build time. From JavaScript:
+//!
+//! ```text
+//! import init, { decode, UbxDecoder } from "./pkg/ubx_schema_wasm.js";
+//! await init();
+//! const messages = decode(new Uint8Array(await file.arrayBuffer()), { scale: true, expandFlags: true });
+//! const stream = new UbxDecoder({ scale: true });   // e.g. for Web Serial chunks
+//! for (const msg of stream.push(chunk)) console.log(msg.name, msg.fields);
+//! ```
+//!
+//! Each message is the object `ubx-decode --format json` writes for it, plus
+//! the byte `offset` of its frame. The functions without JavaScript types
+//! are the same operations on `serde_json` values, for native callers.
+
+use std::sync::OnceLock;
+
+use js_sys::{Reflect, JSON};
+use serde_json::{Map, Value as Json};
+use ubx_schema::frame::{frames, FrameParser};
+use ubx_schema::{DecodeOptions, DecodedMessage, Decoder, Schema};
+use wasm_bindgen::prelude::*;
+
+/// `data/messages/ubx_messages.json`.
+pub const SCHEMA_JSON: &str = include_str!("../../../../../data/messages/ubx_messages.json");
+
+/// The embedded schema, parsed on first use.
+pub fn default_schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::from_json_str(SCHEMA_JSON).expect("the embedded schema loads"))
+}
+
+/// A decoded message as JSON, with the offset of its frame.
+pub fn message_json(offset: u64, msg: &DecodedMessage) -> Json {
+    let mut object = Map::new();
+    object.insert("offset".into(), offset.into());
+    if let Ok(Json::Object(fields)) = serde_json::to_value(msg) {
+        object.extend(fields);
+    }
+    Json::Object(object)
+}
+
+/// Every frame in `data`, decoded.
+pub fn decode_json(schema: &Schema, data: &[u8], options: DecodeOptions) -> Vec<Json> {
+    let decoder = Decoder::with_options(schema, options);
+    frames(data)
+        .flatten()
+        .map(|frame| message_json(frame.offset as u64, &decoder.decode_frame(&frame)))
+        .collect()
+}
+
+/// Streaming decoder state: frames may be split across pushed chunks.
+#[derive(Debug, Default)]
+pub struct Stream {
+    parser: FrameParser,
+}
+
+impl Stream {
+    /// The messages completed by `data`.
+    pub fn push(&mut self, schema: &Schema, options: DecodeOptions, data: &[u8]) -> Vec<Json> {
+        let decoder = Decoder::with_options(schema, options);
+        self.parser.push(data);
+        let mut out = Vec::new();
+        while let Some(result) = self.parser.next_frame() {
+            if let Ok((offset, frame)) = result {
+                out.push(message_json(offset, &decoder.decode(frame.class, frame.id, &frame.payload)));
+            }
+        }
+        out
+    }
+
+    /// Bytes held back waiting for the rest of a frame.
+    pub fn pending(&self) -> usize {
+        self.parser.pending()
+    }
+}
+
+/// Options from an object such as `{scale: true, expandFlags: true,
+/// includeReserved: false}`; missing members are false.
+fn options_from(options: &JsValue) -> DecodeOptions {
+    let flag = |name: &str| {
+        (options.is_object())
+            .then(|| Reflect::get(options, &JsValue::from_str(name)).ok())
+            .flatten()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+    DecodeOptions {
+        scale: flag("scale"),
+        expand_flags: flag("expandFlags"),
+        include_reserved: flag("includeReserved"),
+    }
+}
+
+fn to_js(messages: Vec<Json>) -> Result<JsValue, JsError> {
+    JSON::parse(&Json::Array(messages).to_string()).map_err(|_| JsError::new("cannot convert decoded messages"))
+}
+
+/// Decode every frame in a capture to an array of message objects.
+#[wasm_bindgen]
+pub fn decode(data: &[u8], options: JsValue) -> Result<JsValue, JsError> {
+    to_js(decode_json(default_schema(), data, options_from(&options)))
+}
+
+/// Version of the embedded schema.
+#[wasm_bindgen(js_name = schemaVersion)]
+pub fn schema_version() -> String {
+    default_schema().schema_version.clone()
+}
+
+/// Names of the messages in the embedded schema, e.g. `UBX-NAV-PVT`.
+#[wasm_bindgen(js_name = messageNames)]
+pub fn message_names() -> Vec<String> {
+    default_schema().messages.iter().map(|m| m.name.clone()).collect()
+}
+
+/// Decodes a byte stream pushed in chunks, such as reads from a Web Serial port.
+#[wasm_bindgen]
+pub struct UbxDecoder {
+    /// A schema passed to the constructor; the embedded one otherwise.
+    schema: Option<Schema>,
+    options: DecodeOptions,
+    stream: Stream,
+}
+
+#[wasm_bindgen]
+impl UbxDecoder {
+    /// `schema` is the text of a `ubx_messages.json` to use instead of the
+    /// embedded schema.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: JsValue, schema: Option<String>) -> Result<UbxDecoder, JsError> {
+        let schema = match schema {
+            Some(text) => Some(Schema::from_json_str(&text).map_err(|e| JsError::new(&e.to_string()))?),
+            None => None,
+        };
+        Ok(Self {
+            schema,
+            options: options_from(&options),
+            stream: Stream::default(),
+        })
+    }
+
+    /// Add received bytes and return the messages they complete.
+    pub fn push(&mut self, data: &[u8]) -> Result<JsValue, JsError> {
+        let schema = self.schema.as_ref().unwrap_or_else(|| default_schema());
+        to_js(self.stream.push(schema, self.options, data))
+    }
+
+    /// Bytes held back waiting for the rest of a frame.
+    #[wasm_bindgen(getter)]
+    pub fn pending(&self) -> usize {
+        self.stream.pending()
+    }
+}
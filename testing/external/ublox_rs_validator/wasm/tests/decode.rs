@@ -0,0 +1,34 @@
+//! The decoding behind the bindings, run natively.
+
+use ubx_schema::{DecodeOptions, Encoder};
+use ubx_schema_wasm::{decode_json, default_schema, Stream};
+
+#[test]
+fn decodes_captures_and_split_streams() {
+    let schema = default_schema();
+    let encoder = Encoder::new(schema);
+    let pvt = encoder
+        .encode_json(&serde_json::json!({"name": "NAV-PVT", "fields": {"iTOW": 1000, "lat": 473_977_418}}))
+        .unwrap()
+        .to_bytes();
+    let mut capture = b"$GPGGA junk\r\n".to_vec();
+    capture.extend_from_slice(&pvt);
+    capture.extend_from_slice(&pvt);
+
+    let options = DecodeOptions {
+        scale: true,
+        ..DecodeOptions::default()
+    };
+    let messages = decode_json(schema, &capture, options);
+    assert_eq!(messages.len(), 2);
+    assert_eq!((messages[0]["offset"].clone(), messages[1]["offset"].clone()), (13.into(), (13 + pvt.len()).into()));
+    assert_eq!(messages[0]["name"], "UBX-NAV-PVT");
+    assert_eq!(messages[0]["fields"]["lat"], 47.3977418);
+
+    let mut stream = Stream::default();
+    let (head, tail) = capture.split_at(13 + 20);
+    assert_eq!(stream.push(schema, options, head).len(), 0);
+    assert_eq!(stream.pending(), 20);
+    let rest = stream.push(schema, options, tail);
+    assert_eq!(rest, messages);
+}
@@ -0,0 +1,64 @@
+"""Golden test-vector corpus: our Python parser and pyubx2 agree with the vectors.
+
+The Rust runner (`ubx-corpus check`) checks the complete decode; this parser
+covers fixed-offset top-level fields only, so it is compared on those.
+"""
+
+import json
+import sys
+from pathlib import Path
+
+import pytest
+
+# Add lib to path
+sys.path.insert(0, str(Path(__file__).parent.parent))
+
+from lib.ubx_parser import parse_ubx_message
+from external.pyubx2_adapter import is_available as pyubx2_available, parse_ubx_bytes
+
+CORPUS_DIR = Path(__file__).parent.parent / "corpus"
+VECTOR_PATHS = sorted(CORPUS_DIR.rglob("*.json"))
+
+
+def load_vector(path: Path) -> dict:
+    with open(path) as f:
+        return json.load(f)
+
+
+def vector_id(path: Path) -> str:
+    return str(path.relative_to(CORPUS_DIR).with_suffix(""))
+
+
+def test_corpus_is_not_empty():
+    assert VECTOR_PATHS, f"no vectors under {CORPUS_DIR}"
+
+
+@pytest.mark.parametrize("path", VECTOR_PATHS, ids=vector_id)
+def test_our_parser_matches_vector(path):
+    """Name, variant and every scalar field our parser decodes match the vector."""
+    vector = load_vector(path)
+    expected = vector["expected"]
+    result = parse_ubx_message(bytes.fromhex(vector["frame"]))
+
+    assert result["parsed"], result
+    assert result["name"] == expected["name"]
+    assert result.get("variant") == expected.get("variant")
+    for name, value in result["fields"].items():
+        if name not in expected["fields"] or isinstance(value, (list, dict, bytes)):
+            continue
+        want = expected["fields"][name]
+        if isinstance(value, str):
+            assert value == want, name
+        else:
+            assert value == pytest.approx(want), f"{name}: {value}"
+
+
+@pytest.mark.skipif(not pyubx2_available(), reason="pyubx2 not installed")
+@pytest.mark.parametrize("path", VECTOR_PATHS, ids=vector_id)
+def test_pyubx2_identifies_vector(path):
+    """pyubx2 decodes the frame as the same message."""
+    vector = load_vector(path)
+    result = parse_ubx_bytes(bytes.fromhex(vector["frame"]))
+    if not result or not result.get("parsed"):
+        pytest.skip(f"pyubx2 cannot parse: {result.get('error') if result else None}")
+    assert result["name"] == vector["expected"]["name"].removeprefix("UBX-")
@@ -0,0 +1,47 @@
+"""Test the canonical schema formatter and that the data files are canonical."""
+
+import json
+import sys
+from pathlib import Path
+
+# Add project root to path
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from scripts.format_schema import FILES, PROJECT_ROOT, SCHEMA_DIR, Node, canonicalize, format_file
+
+
+def message_node() -> Node:
+    root = json.loads((SCHEMA_DIR / "ubx-message-schema-v1.5.json").read_text())
+    return Node(root, root, "")
+
+
+class TestCanonicalize:
+    """Keys, lists and IDs are put in one canonical form."""
+
+    def test_orders_keys_sorts_messages_and_normalizes_ids(self):
+        data = {
+            "messages": [
+                {"payload": {"fields": [{"unit": "ms", "zzz": 1, "name": "iTOW", "byte_offset": 0,
+                                         "data_type": "U4", "aaa": 2}],
+                             "length": {"fixed": 4}},
+                 "message_id": "0x3b", "class_id": "0x01", "name": "UBX-NAV-Z"},
+                {"name": "UBX-ACK-ACK", "class_id": "0x05", "message_id": "0x01"},
+            ],
+            "schema_version": "1.5",
+        }
+        canonical = canonicalize(data, message_node(), top_level=True)
+        assert list(canonical) == ["schema_version", "messages"]
+        assert [m["name"] for m in canonical["messages"]] == ["UBX-ACK-ACK", "UBX-NAV-Z"]
+        nav = canonical["messages"][1]
+        assert list(nav) == ["name", "class_id", "message_id", "payload"]
+        assert nav["message_id"] == "0x3B"
+        assert list(nav["payload"]) == ["length", "fields"]
+        # Undescribed keys follow in alphabetical order.
+        assert list(nav["payload"]["fields"][0]) == ["name", "byte_offset", "data_type", "unit", "aaa", "zzz"]
+
+    def test_data_files_are_canonical(self):
+        for relative, schema_name in FILES.items():
+            path = PROJECT_ROOT / relative
+            assert path.read_text() == format_file(path, schema_name), (
+                f"{relative} is not canonical; run: uv run python scripts/format_schema.py"
+            )
@@ -0,0 +1,56 @@
+"""Tests for the PyO3 bindings (testing/external/ublox_rs_validator/python).
+
+Skipped unless the module is installed, e.g. with
+`maturin develop -m testing/external/ublox_rs_validator/python/Cargo.toml`.
+"""
+
+import sys
+from pathlib import Path
+
+import pytest
+
+sys.path.insert(0, str(Path(__file__).parent.parent))
+
+from lib.schema_loader import get_all_messages
+from lib.ubx_generator import generate_ubx_message
+from lib.ubx_parser import parse_ubx_message, UBXParseError
+
+ubx_schema = pytest.importorskip("ubx_schema")
+
+
+def test_encode_decode_round_trip():
+    frame = ubx_schema.encode({"name": "CFG-RATE", "fields": {"measRate": 200, "navRate": 1, "timeRef": 1}})
+    assert frame[:4] == b"\xb5\x62\x06\x08"
+
+    messages = ubx_schema.decode(b"junk" + frame + frame)
+    assert [m["offset"] for m in messages] == [4, 4 + len(frame)]
+    assert messages[0]["name"] == "UBX-CFG-RATE"
+    assert messages[0]["fields"]["measRate"] == 200
+
+    with pytest.raises(ubx_schema.UbxError):
+        ubx_schema.encode({"name": "NAV-NOSUCH"})
+    # Longer than the 16-bit length field: an error rather than a panic.
+    with pytest.raises(ubx_schema.UbxError, match="65535"):
+        ubx_schema.encode({"name": "RXM-SFRBX", "fields": {"numWords": 255, "dwrd": [0] * 20_000}})
+
+
+def test_matches_the_python_parser_on_fixed_length_messages():
+    schema = ubx_schema.Schema()
+    compared = 0
+    for message in get_all_messages():
+        length = message.get("payload", {}).get("length", {})
+        if not (isinstance(length, dict) and "fixed" in length):
+            continue
+        try:
+            frame = generate_ubx_message(message)
+            expected = parse_ubx_message(frame, message)
+        except (UBXParseError, ValueError, KeyError, TypeError):
+            continue
+        decoded = schema.decode_message(frame[2], frame[3], frame[6:-2], include_reserved=True)
+        assert decoded["name"] == expected["name"]
+        assert decoded["payload_length"] == expected["payload_length"]
+        for name, value in expected["fields"].items():
+            if isinstance(value, (int, float)) and name in decoded["fields"]:
+                assert decoded["fields"][name] == value, f"{message['name']}.{name}"
+        compared += 1
+    assert compared > 50
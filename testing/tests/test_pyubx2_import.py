@@ -0,0 +1,57 @@
+"""Test laying out pyubx2-style definition tables and comparing them with the schema.
+
+The tables here are written in pyubx2's format, so pyubx2 need not be installed.
+"""
+
+import sys
+from pathlib import Path
+
+# Add project root to path
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from lib.schema_loader import get_message_by_name
+from validation.scripts.pyubx2_import import check_message, layout_pyubx2
+
+U1, U2, U4, I1, I2, I4, X1, X4 = "U001", "U002", "U004", "I001", "I002", "I004", "X001", "X004"
+
+NAV_SAT = {
+    "iTOW": U4, "version": U1, "numSvs": U1, "reserved0": U2,
+    "group": ("numSvs", {
+        "gnssId": U1, "svId": U1, "cno": U1, "elev": I1, "azim": I2, "prRes": [I2, 0.1], "flags": X4,
+    }),
+}
+
+
+class TestLayout:
+    """pyubx2 tables are laid out into offsets, sizes and scales."""
+
+    def test_offsets_follow_field_sizes(self):
+        layout = layout_pyubx2(NAV_SAT)
+        assert [(f.name, f.offset, f.size) for f in layout.fields] == [
+            ("iTOW", 0, 4), ("version", 4, 1), ("numSvs", 5, 1), ("reserved0", 6, 2),
+        ]
+        group = layout.groups[0]
+        assert (group.count, group.offset, group.element_size) == ("numSvs", 8, 12)
+        pr_res = next(f for f in group.fields if f.name == "prRes")
+        assert (pr_res.offset, pr_res.scale) == (6, 0.1)
+        # A counted group makes the payload length variable.
+        assert layout.length is None
+
+
+class TestCrossCheck:
+    """Divergences from the schema are reported by field."""
+
+    def test_shifted_field_and_wrong_scale_are_reported(self):
+        msg = get_message_by_name("UBX-NAV-SAT")
+        assert check_message(msg, {"NAV-SAT": NAV_SAT}, {})["divergences"] == []
+
+        # One byte missing before cno shifts it and every later member.
+        members = dict(NAV_SAT["group"][1])
+        del members["svId"]
+        members["prRes"] = [I2, 0.01]
+        broken = dict(NAV_SAT, group=("numSvs", members))
+        issues = {(d["field"], d["issue"]) for d in check_message(msg, {"NAV-SAT": broken}, {})["divergences"]}
+        assert ("group[numSvs].cno", "offset") in issues
+        assert ("group[numSvs].prRes", "scale") in issues
+        assert ("group[numSvs]", "element size") in issues
+        assert ("group[numSvs].gnssId", "offset") not in issues
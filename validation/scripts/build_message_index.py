@@ -0,0 +1,149 @@
+#!/usr/bin/env python3
+"""
+Build the official message index used by the schema completeness check.
+
+Combines the PDF inventory (which messages each manual documents) with the
+class and message IDs extracted from the manuals, and groups the manuals by
+product generation. The result, data/messages/message_index.json, lists
+every documented (class, id, name) with the generations that document it.
+
+Messages only ever extracted as variants (UBX-CFG-DAT's GET/SET forms, for
+example) take their IDs from the variant definitions in the schema.
+
+Usage:
+    uv run python validation/scripts/build_message_index.py
+    uv run python validation/scripts/build_message_index.py --verbose
+"""
+
+from __future__ import annotations
+
+import argparse
+import json
+import re
+import sys
+from collections import defaultdict
+from datetime import datetime, timezone
+from pathlib import Path
+
+PROJECT_ROOT = Path(__file__).parent.parent.parent
+INVENTORY_FILE = PROJECT_ROOT / "validation" / "inventory" / "pdf_inventory.json"
+BY_VERSION_DIR = PROJECT_ROOT / "data" / "preliminary" / "by_version"
+MISSING_DIR = PROJECT_ROOT / "data" / "preliminary" / "extracted_missing"
+SCHEMA_FILE = PROJECT_ROOT / "data" / "messages" / "ubx_messages.json"
+INDEX_FILE = PROJECT_ROOT / "data" / "messages" / "message_index.json"
+
+# Manual name patterns, checked in order; the first match wins.
+GENERATIONS = [
+    ("M8", re.compile(r"u-blox8|M8")),
+    ("M9", re.compile(r"M9")),
+    ("M10", re.compile(r"M10")),
+    ("F10", re.compile(r"F10")),
+    ("X20", re.compile(r"X20|u-blox-20")),
+    ("F9", re.compile(r"F9|LAP120")),
+]
+ORDER = ["M8", "M9", "M10", "F9", "F10", "X20"]
+
+
+def generation_of(manual: str) -> str | None:
+    """Product generation a manual belongs to, from its name."""
+    for generation, pattern in GENERATIONS:
+        if pattern.search(manual):
+            return generation
+    return None
+
+
+def parse_id(text: str) -> str:
+    """Normalize an ID like '0x3b' to '0x3B'."""
+    return f"0x{int(text, 16):02X}"
+
+
+def extracted_ids() -> dict[str, set[tuple[str, str]]]:
+    """(class, id) pairs per message name from the manual extractions."""
+    ids: dict[str, set[tuple[str, str]]] = defaultdict(set)
+    for path in sorted(BY_VERSION_DIR.glob("*.json")):
+        data = json.loads(path.read_text())
+        structure = data.get("winning_structure") or {}
+        if structure.get("class_id") and structure.get("message_id"):
+            ids[data["message_name"]].add((parse_id(structure["class_id"]), parse_id(structure["message_id"])))
+    for path in sorted(MISSING_DIR.glob("*.json")):
+        data = json.loads(path.read_text())
+        if isinstance(data, dict) and data.get("class_id") and data.get("message_id"):
+            ids[data["name"]].add((parse_id(data["class_id"]), parse_id(data["message_id"])))
+    return ids
+
+
+def schema_ids() -> dict[str, tuple[str, str]]:
+    """(class, id) per schema message name and variant alias."""
+    ids = {}
+    for msg in json.loads(SCHEMA_FILE.read_text())["messages"]:
+        pair = (parse_id(msg["class_id"]), parse_id(msg["message_id"]))
+        for name in [msg["name"], *msg.get("variant_aliases", [])]:
+            ids[name] = pair
+    return ids
+
+
+def ids_for(name: str, extracted: dict, schema: dict) -> tuple[str, str] | None:
+    if len(extracted.get(name, ())) == 1:
+        return next(iter(extracted[name]))
+    if name in schema:
+        return schema[name]
+    variants = {pair for variant, pair in schema.items() if variant.startswith(name + "-")}
+    if len(variants) == 1:
+        return variants.pop()
+    return None
+
+
+def build_index(verbose: bool = False) -> dict:
+    inventory = json.loads(INVENTORY_FILE.read_text())
+    extracted = extracted_ids()
+    schema = schema_ids()
+
+    manuals_by_generation: dict[str, set[str]] = defaultdict(set)
+    generations_by_message: dict[str, set[str]] = defaultdict(set)
+    for manual in inventory["by_manual"]:
+        generation = generation_of(manual["manual_name"])
+        if generation is None:
+            print(f"Warning: no generation for {manual['manual_name']}", file=sys.stderr)
+            continue
+        manuals_by_generation[generation].add(manual["manual_name"])
+        for name in manual["messages"]:
+            generations_by_message[name].add(generation)
+
+    messages = []
+    for name in sorted(generations_by_message):
+        pair = ids_for(name, extracted, schema)
+        if pair is None:
+            print(f"Warning: no class/message ID for {name}", file=sys.stderr)
+            continue
+        messages.append({
+            "name": name,
+            "class_id": pair[0],
+            "message_id": pair[1],
+            "generations": sorted(generations_by_message[name], key=ORDER.index),
+        })
+        if verbose:
+            print(f"  {name} ({pair[0]} {pair[1]}): {', '.join(messages[-1]['generations'])}")
+
+    return {
+        "generated": datetime.now(timezone.utc).isoformat(),
+        "description": "Messages documented in the u-blox interface descriptions, by product generation",
+        "generations": [
+            {"name": g, "manuals": sorted(manuals_by_generation[g])} for g in ORDER if g in manuals_by_generation
+        ],
+        "messages": messages,
+    }
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Build the official message index")
+    parser.add_argument("--verbose", "-v", action="store_true", help="List every indexed message")
+    args = parser.parse_args()
+
+    index = build_index(verbose=args.verbose)
+    INDEX_FILE.write_text(json.dumps(index, indent=2) + "\n")
+    print(f"Wrote {len(index['messages'])} messages in {len(index['generations'])} generations to {INDEX_FILE}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
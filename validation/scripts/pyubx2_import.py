@@ -0,0 +1,435 @@
+#!/usr/bin/env python3
+"""
+Lay out pyubx2's message definition tables and cross-check them against our schema.
+
+cross_validate.py compares field names, types and bitfields. This script
+turns pyubx2's tables into byte offsets, sizes and scale factors, so it also
+finds fields at the wrong offset (a field missing before them in either
+project), wrong sizes and different scales, in repeated groups too.
+
+It also lists the messages pyubx2 defines that our schema lacks, and prints
+a draft layout for one of them. A draft is a checklist for extracting the
+message from the interface manual, not a definition: the schema is built from
+u-blox manuals only, so never merge a draft as-is.
+
+Usage:
+    uv run python validation/scripts/pyubx2_import.py UBX-NAV-PVT
+    uv run python validation/scripts/pyubx2_import.py --all
+    uv run python validation/scripts/pyubx2_import.py --all --save
+    uv run python validation/scripts/pyubx2_import.py --missing
+    uv run python validation/scripts/pyubx2_import.py --draft UBX-NAV-EELL
+"""
+
+from __future__ import annotations
+
+import argparse
+import json
+import re
+import sys
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from pathlib import Path
+
+PROJECT_ROOT = Path(__file__).parent.parent.parent
+sys.path.insert(0, str(PROJECT_ROOT))
+
+from validation.scripts.cross_validate import get_field_alias, load_our_messages  # noqa: E402
+
+BASE_SIZES = {
+    "U1": 1, "I1": 1, "X1": 1, "E1": 1, "L1": 1, "CH": 1, "RU1_3": 1,
+    "U2": 2, "I2": 2, "X2": 2, "E2": 2, "RU2_5": 2,
+    "U4": 4, "I4": 4, "X4": 4, "E4": 4, "R4": 4,
+    "U8": 8, "I8": 8, "X8": 8, "R8": 8,
+}
+
+# Message types whose layout pyubx2 keeps in its SET table rather than GET.
+INPUT_TYPES = {"input", "set", "command"}
+
+SCALE_TOLERANCE = 1e-9
+
+
+@dataclass
+class LaidOutField:
+    """One field at its byte offset. Offsets in a group are from the start of an element."""
+    name: str
+    offset: int | None
+    size: int | None
+    scale: float | None = 1.0
+    data_type: str = ""
+
+
+@dataclass
+class Group:
+    """A repeated group: its count (field name, fixed number or None for 'to the end') and elements."""
+    count: str | int | None
+    offset: int | None
+    element_size: int | None
+    fields: list[LaidOutField]
+
+
+@dataclass
+class Layout:
+    fields: list[LaidOutField]
+    groups: list[Group]
+    # Fixed payload length, or None if it varies.
+    length: int | None
+
+
+# --- pyubx2 tables ---------------------------------------------------------
+
+
+def pyubx2_size(type_str: str) -> int | None:
+    """Byte size of a pyubx2 type such as 'U004', 'C030' or 'A250'; None for variable 'CH'."""
+    if len(type_str) >= 2 and type_str[0] in "UIXRCAEL" and type_str[1:].isdigit():
+        return int(type_str[1:])
+    return None
+
+
+def is_group(spec) -> bool:
+    """pyubx2 writes repeated groups as (count, {...}) and bitfields as ('X00n', {...})."""
+    if not (isinstance(spec, tuple) and len(spec) == 2 and isinstance(spec[1], dict)):
+        return False
+    return not (isinstance(spec[0], str) and spec[0][:1] == "X" and spec[0][1:].isdigit())
+
+
+def layout_pyubx2(definition: dict) -> Layout:
+    """Lay out a pyubx2 payload definition (e.g. UBX_PAYLOADS_GET['NAV-PVT'])."""
+    fields: list[LaidOutField] = []
+    groups: list[Group] = []
+    offset: int | None = 0
+    for name, spec in definition.items():
+        if is_group(spec):
+            count, members = spec
+            inner = layout_pyubx2(members)
+            groups.append(Group(count=None if count == "None" else count, offset=offset,
+                                element_size=inner.length, fields=inner.fields))
+            if isinstance(count, int) and offset is not None and inner.length is not None:
+                offset += count * inner.length
+            else:
+                offset = None
+            continue
+        scale: float | None = 1.0
+        if isinstance(spec, list):
+            type_str, scale = spec[0], float(spec[1])
+        elif isinstance(spec, tuple):
+            type_str = spec[0]
+        else:
+            type_str = spec
+        size = pyubx2_size(type_str)
+        fields.append(LaidOutField(name, offset, size, scale, type_str))
+        offset = offset + size if offset is not None and size is not None else None
+    return Layout(fields, groups, offset)
+
+
+def load_pyubx2_tables() -> tuple[dict, dict, dict]:
+    """pyubx2's GET and SET payload tables and its message ID table."""
+    from pyubx2.ubxtypes_core import UBX_MSGIDS
+    from pyubx2.ubxtypes_get import UBX_PAYLOADS_GET
+    from pyubx2.ubxtypes_set import UBX_PAYLOADS_SET
+
+    return UBX_PAYLOADS_GET, UBX_PAYLOADS_SET, UBX_MSGIDS
+
+
+def pyubx2_definition(name: str, message_type: str, get: dict, set_: dict) -> dict | None:
+    """pyubx2's definition of a message, e.g. 'UBX-NAV-PVT' or 'UBX-MGA-GPS-EPH'."""
+    short = name.removeprefix("UBX-")
+    tables = (set_, get) if message_type in INPUT_TYPES else (get, set_)
+    for table in tables:
+        if short in table:
+            return table[short]
+    return None
+
+
+# --- our schema ------------------------------------------------------------
+
+
+def our_size(data_type) -> int | None:
+    """Byte size of one of our data types, or None if it varies."""
+    if isinstance(data_type, str):
+        m = re.fullmatch(r"(\w+?)\[(\d+)\]", data_type)
+        if m:
+            base = BASE_SIZES.get(m.group(1))
+            return base * int(m.group(2)) if base else None
+        return BASE_SIZES.get(data_type)
+    if isinstance(data_type, dict) and isinstance(data_type.get("count"), int):
+        base = BASE_SIZES.get(data_type.get("array_of"))
+        return base * data_type["count"] if base else None
+    return None
+
+
+def our_scale(field: dict) -> float | None:
+    """Scale multiplier of one of our fields; None if it cannot be read."""
+    scale = field.get("scale")
+    if scale is None:
+        return 1.0
+    if isinstance(scale, (int, float)):
+        return float(scale)
+    if isinstance(scale, dict) and isinstance(scale.get("multiplier"), (int, float)):
+        return float(scale["multiplier"])
+    raw = scale.get("raw") if isinstance(scale, dict) else scale
+    m = re.fullmatch(r"\s*2\^(-?\d+)\s*", str(raw))
+    if m:
+        return 2.0 ** int(m.group(1))
+    try:
+        return float(raw)
+    except (TypeError, ValueError):
+        return None
+
+
+def laid_out(f: dict) -> LaidOutField:
+    offset = f.get("byte_offset")
+    dt = f.get("data_type")
+    return LaidOutField(f["name"], offset if isinstance(offset, int) else None, our_size(dt), our_scale(f),
+                        dt if isinstance(dt, str) else json.dumps(dt))
+
+
+def extent(fields: list[LaidOutField]) -> int | None:
+    """End of the last field, if every offset and size is known."""
+    if not fields or any(f.offset is None or f.size is None for f in fields):
+        return None
+    return max(f.offset + f.size for f in fields)
+
+
+def layout_ours(payload: dict) -> Layout:
+    """Lay out one of our payload definitions (a message's or a variant's payload)."""
+    fields, groups = [], []
+    for f in payload.get("fields", []):
+        dt = f.get("data_type")
+        if isinstance(dt, dict) and dt.get("array_of") == "object":
+            members = [laid_out(m) for m in f.get("fields", [])]
+            groups.append(Group(dt.get("count_field") or dt.get("count"), f.get("byte_offset"),
+                                f.get("group_size_bytes") or extent(members), members))
+        else:
+            fields.append(laid_out(f))
+    for g in payload.get("repeated_groups", []):
+        base = g.get("base_offset")
+        members = [laid_out(m) for m in g.get("fields", [])]
+        groups.append(Group(g.get("count_field") or g.get("count"), base if isinstance(base, int) else None,
+                            g.get("group_size_bytes") or extent(members), members))
+    length = payload.get("length")
+    if isinstance(length, dict):
+        length = length.get("fixed")
+    return Layout(fields, groups, length if isinstance(length, int) else None)
+
+
+# --- comparison ------------------------------------------------------------
+
+
+def match_name(ours: str, theirs: dict) -> str | None:
+    if ours in theirs:
+        return ours
+    alias = get_field_alias(ours)
+    if alias in theirs:
+        return alias
+    folded = {n.lower(): n for n in theirs}
+    return folded.get(ours.lower())
+
+
+def scales_differ(a: float | None, b: float | None) -> bool:
+    if a is None or b is None:
+        return False
+    return abs(a - b) > SCALE_TOLERANCE * max(abs(a), abs(b))
+
+
+def compare_fields(ours: list[LaidOutField], theirs: list[LaidOutField], where: str) -> list[dict]:
+    """Offset, size and scale divergences of the fields both sides name."""
+    by_name = {f.name: f for f in theirs}
+    out = []
+    for f in ours:
+        if f.name.startswith("reserved"):
+            continue
+        name = match_name(f.name, by_name)
+        if name is None:
+            continue
+        t = by_name[name]
+        label = f"{where}{f.name}"
+        if f.offset is not None and t.offset is not None and f.offset != t.offset:
+            out.append({"field": label, "issue": "offset", "ours": f.offset, "pyubx2": t.offset})
+        if f.size is not None and t.size is not None and f.size != t.size:
+            out.append({"field": label, "issue": "size", "ours": f.size, "pyubx2": t.size})
+        if scales_differ(f.scale, t.scale):
+            out.append({"field": label, "issue": "scale", "ours": f.scale, "pyubx2": t.scale})
+    return out
+
+
+def compare_layouts(ours: Layout, theirs: Layout) -> list[dict]:
+    """All divergences between our layout of a payload and pyubx2's."""
+    out = compare_fields(ours.fields, theirs.fields, "")
+    if ours.length is not None and theirs.length is not None and ours.length != theirs.length:
+        out.append({"field": "(payload)", "issue": "length", "ours": ours.length, "pyubx2": theirs.length})
+
+    for i, group in enumerate(ours.groups):
+        counts = {str(g.count): g for g in theirs.groups}
+        match = None
+        if isinstance(group.count, str):
+            name = match_name(group.count, counts)
+            match = counts.get(name) if name else None
+        if match is None and len(ours.groups) == len(theirs.groups):
+            match = theirs.groups[i]
+        if match is None:
+            continue
+        where = f"group[{group.count}]."
+        if group.offset is not None and match.offset is not None and group.offset != match.offset:
+            out.append({"field": where.rstrip("."), "issue": "offset", "ours": group.offset, "pyubx2": match.offset})
+        if group.element_size and match.element_size and group.element_size != match.element_size:
+            out.append({"field": where.rstrip("."), "issue": "element size",
+                        "ours": group.element_size, "pyubx2": match.element_size})
+        out.extend(compare_fields(group.fields, match.fields, where))
+    return out
+
+
+def check_message(msg: dict, get: dict, set_: dict) -> dict:
+    """Cross-check one of our messages, variant by variant, against pyubx2's layout."""
+    payloads = []
+    if msg.get("variants"):
+        aliases = {a.rsplit("-", 1)[-1]: a for a in msg.get("variant_aliases", [])}
+        for v in msg["variants"]:
+            payloads.append((v["name"], aliases.get(v["name"], f"{msg['name']}-{v['name']}"), v.get("payload", {})))
+    else:
+        payloads.append((None, msg["name"], msg.get("payload") or {}))
+
+    result = {"message": msg["name"], "checked": [], "not_in_pyubx2": [], "divergences": []}
+    for variant, name, payload in payloads:
+        definition = pyubx2_definition(name, msg.get("message_type", ""), get, set_)
+        if definition is None:
+            result["not_in_pyubx2"].append(variant or msg["name"])
+            continue
+        result["checked"].append(variant or msg["name"])
+        for d in compare_layouts(layout_ours(payload), layout_pyubx2(definition)):
+            if variant:
+                d["variant"] = variant
+            result["divergences"].append(d)
+    return result
+
+
+# --- bootstrapping ---------------------------------------------------------
+
+
+def missing_messages(ours: dict[str, dict], msgids: dict) -> list[dict]:
+    """Messages pyubx2 has an ID for that our schema does not define."""
+    ids = {(int(m["class_id"], 16), int(m["message_id"], 16)) for m in ours.values()}
+    missing = []
+    for key, name in sorted(msgids.items()):
+        if len(key) == 2 and (key[0], key[1]) not in ids:
+            missing.append({"name": f"UBX-{name}", "class_id": f"0x{key[0]:02X}", "message_id": f"0x{key[1]:02X}"})
+    return missing
+
+
+def draft_type(f: LaidOutField) -> str | dict:
+    """Our data_type for a pyubx2 type."""
+    t = f.data_type
+    if t[:1] == "C" and t[1:].isdigit():
+        return f"CH[{int(t[1:])}]"
+    if t[:1] == "A" and t[1:].isdigit():
+        return {"array_of": "U1", "count": int(t[1:])}
+    if t[:1] in "UIXR" and t[1:].isdigit() and f"{t[0]}{int(t[1:])}" in BASE_SIZES:
+        return f"{t[0]}{int(t[1:])}"
+    return t
+
+
+def draft_fields(fields: list[LaidOutField]) -> list[dict]:
+    out = []
+    for f in fields:
+        entry = {"name": f.name, "byte_offset": f.offset, "data_type": draft_type(f)}
+        if f.scale not in (None, 1.0):
+            entry["scale"] = {"multiplier": f.scale}
+        out.append(entry)
+    return out
+
+
+def draft_message(name: str, class_id: int, message_id: int, definition: dict) -> dict:
+    """A draft definition in our format from pyubx2's layout: a checklist, to be verified against the manual."""
+    layout = layout_pyubx2(definition)
+    payload: dict = {
+        "length": {"fixed": layout.length} if layout.length is not None else {"variable": {}},
+        "fields": draft_fields(layout.fields),
+    }
+    groups = []
+    for g in layout.groups:
+        group = {"name": "group", "base_offset": g.offset, "group_size_bytes": g.element_size,
+                 "fields": draft_fields(g.fields)}
+        if isinstance(g.count, str):
+            group.update(repetition_type="count_field", count_field=g.count)
+        elif isinstance(g.count, int):
+            group.update(repetition_type="fixed", count=g.count)
+        else:
+            group.update(repetition_type="remaining")
+        groups.append(group)
+    if groups:
+        payload["repeated_groups"] = groups
+    return {
+        "name": name,
+        "class_id": f"0x{class_id:02X}",
+        "message_id": f"0x{message_id:02X}",
+        "message_type": "",
+        "payload": payload,
+        "comment": "DRAFT laid out from pyubx2: check every field, offset, type and scale in the interface manual",
+    }
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Cross-check schema layouts against pyubx2's definition tables")
+    parser.add_argument("message", nargs="?", help="Message name (e.g., UBX-NAV-PVT)")
+    parser.add_argument("--all", action="store_true", help="Check all messages")
+    parser.add_argument("--missing", action="store_true", help="List messages pyubx2 defines that the schema lacks")
+    parser.add_argument("--draft", metavar="MESSAGE", help="Print a draft definition of a missing message")
+    parser.add_argument("--save", action="store_true", help="Save --all results to validation/reports/")
+    args = parser.parse_args()
+
+    try:
+        get, set_, msgids = load_pyubx2_tables()
+    except ImportError:
+        print("Error: pyubx2 not installed")
+        print("Install with: uv add pyubx2")
+        return 2
+
+    ours = load_our_messages()
+
+    if args.missing:
+        missing = missing_messages(ours, msgids)
+        for m in missing:
+            known = "" if pyubx2_definition(m["name"], "", get, set_) else "  (no payload table)"
+            print(f"{m['name']:<24} {m['class_id']} {m['message_id']}{known}")
+        print(f"\n{len(missing)} messages in pyubx2 are not in the schema")
+        return 0
+
+    if args.draft:
+        short = args.draft.removeprefix("UBX-")
+        key = next((k for k, n in msgids.items() if n == short and len(k) == 2), None)
+        definition = pyubx2_definition(args.draft, "", get, set_)
+        if key is None or definition is None:
+            print(f"Error: pyubx2 has no definition of {args.draft}")
+            return 2
+        print(json.dumps(draft_message(f"UBX-{short}", key[0], key[1], definition), indent=2))
+        return 0
+
+    if args.all:
+        results = [check_message(m, get, set_) for _, m in sorted(ours.items())]
+    elif args.message:
+        if args.message not in ours:
+            print(f"Error: Message '{args.message}' not found in our schema")
+            return 2
+        results = [check_message(ours[args.message], get, set_)]
+    else:
+        parser.print_help()
+        return 2
+
+    for r in results:
+        for d in r["divergences"]:
+            variant = f" {d['variant']}" if "variant" in d else ""
+            print(f"{r['message']}{variant}: {d['field']}: {d['issue']} ours {d['ours']}, pyubx2 {d['pyubx2']}")
+    checked = sum(1 for r in results if r["checked"])
+    diverging = sum(1 for r in results if r["divergences"])
+    print(f"\n{checked} messages checked against pyubx2, {diverging} with layout divergences")
+
+    if args.save:
+        output_file = PROJECT_ROOT / "validation" / "reports" / "pyubx2_layout.json"
+        with open(output_file, "w") as f:
+            json.dump({"timestamp": datetime.now(timezone.utc).isoformat(), "results": results}, f, indent=2)
+        print(f"Saved to {output_file}")
+
+    return 1 if diverging else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())